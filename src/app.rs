@@ -4,8 +4,8 @@ use std::sync::Arc;
 
 use crate::cleaner;
 use crate::fs::RemoteFs;
-use crate::k8s::{K8sClient, StorageManager};
-use crate::ui::Pane;
+use crate::k8s::{K8sClient, K8sOptions, StorageManager};
+use crate::ui::{DirSizes, Pane};
 
 pub enum AppMode {
     Normal,
@@ -13,23 +13,355 @@ pub enum AppMode {
     SelectNamespace,
     SelectPvc,
     SelectPv,
+    SelectContext,       // Choose kubeconfig context (cluster)
     SelectCloudProvider, // Choose S3/GCS/etc.
     ConfigureCloud,      // Enter bucket/credentials for cloud storage
     DiskAnalyzer,        // ncdu-like disk usage view
     ConfirmDelete,       // Confirmation dialog for delete
     Rename,              // Rename file/directory
+    CreateFile,          // Prompt for the name of a new empty file
     ViewFile,            // View file contents
     Search,              // Search for files
     EditFile,            // Edit file contents (nano-like)
     ConfirmLargeLoad,    // Confirm loading large remote file
     EditorSearch,        // Search text inside editor
+    EditorGoToLine,      // Prompt for a line number to jump to in the editor
+    ViewGoToLine,        // Prompt for a line number to jump to in the streaming viewer
+    EditorSaveAs,        // Prompt for a local path to download the file shown read-only in the editor to
+    ViewSaveAs,          // Prompt for a local path to download the file in the streaming viewer to
     HashMenu,            // Hash operations menu (scan, verify, dedup, etc.)
+    RecentFiles,         // Most recently modified files under a tree
+    DiffView,            // Diff of the selected files in both panes
+    ConfirmRetry,        // Offer to retry/resume copies and moves that failed
+    HashCheckInput,      // Prompt for the expected hash of the selected file
+    HashCheckResult,     // Match/mismatch popup for a quick hash check
+    ObjectDetails,       // Metadata of the selected entry (ETag, storage class, ...)
+    MarkPattern,         // Prompt for a glob of entries to mark
+    UnmarkPattern,       // Prompt for a glob of entries to unmark
+    ConfirmCopyPlan,     // Files and total size a copy would transfer, confirm to start it
+    ConfirmMerge,        // A copied directory already exists in the destination: merge or replace
+    ConfirmEditorOverwrite, // The edited file changed on disk since it was loaded: overwrite, view or cancel
+    VerifyResults,       // Changed, missing and new files found by a verify
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum LargeFileAction {
     View,
     Edit,
+    Diff,
+}
+
+/// External program a file can be handed to (the TUI is suspended while it runs)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalProgram {
+    /// `$ABYSS_EDITOR`, `$VISUAL`, `$EDITOR`, else `vi`; remote files are uploaded back if changed
+    Editor,
+    /// `$ABYSS_PAGER`, `$PAGER`, else `less`; read-only
+    Pager,
+}
+
+impl ExternalProgram {
+    /// Command line to run, possibly with arguments (e.g. `code --wait`)
+    pub fn command(self) -> String {
+        let vars: &[&str] = match self {
+            ExternalProgram::Editor => &["ABYSS_EDITOR", "VISUAL", "EDITOR"],
+            ExternalProgram::Pager => &["ABYSS_PAGER", "PAGER"],
+        };
+        vars.iter()
+            .find_map(|var| std::env::var(var).ok().filter(|v| !v.trim().is_empty()))
+            .unwrap_or_else(|| match self {
+                ExternalProgram::Editor => "vi".to_string(),
+                ExternalProgram::Pager => "less".to_string(),
+            })
+    }
+}
+
+/// File waiting to be opened in an external program; picked up by the main loop,
+/// which owns the terminal
+pub struct ExternalOpen {
+    pub program: ExternalProgram,
+    /// Pane the file was selected in (refreshed afterwards)
+    pub pane: ActivePane,
+    pub storage: Arc<dyn crate::fs::StorageBackend>,
+    /// Backend path of the file
+    pub path: String,
+    pub name: String,
+}
+
+/// Kind of a queued file operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    Copy,
+    Move,
+    /// Stream one file to a local path (saved from the viewer)
+    Download,
+}
+
+impl OperationKind {
+    pub fn verb(self) -> &'static str {
+        match self {
+            Self::Copy => "Copying",
+            Self::Move => "Moving",
+            Self::Download => "Downloading",
+        }
+    }
+
+    pub fn past(self) -> &'static str {
+        match self {
+            Self::Copy => "Copied",
+            Self::Move => "Moved",
+            Self::Download => "Downloaded",
+        }
+    }
+}
+
+/// A copy or move waiting in the operation queue.
+#[derive(Clone)]
+pub struct QueuedOperation {
+    pub kind: OperationKind,
+    pub name: String,
+    pub size: u64,
+    pub is_dir: bool,
+    pub src_storage: Arc<dyn crate::fs::StorageBackend>,
+    pub src_path: String,
+    pub dest_storage: Arc<dyn crate::fs::StorageBackend>,
+    pub dest_path: String,
+    /// Retry of a failed transfer: keep what already reached the destination
+    pub resume: bool,
+    /// What a directory copy does with an existing target directory
+    pub merge: crate::fs::copy::MergeMode,
+}
+
+impl QueuedOperation {
+    /// Bytes the transfer has left at its destination so far; a download keeps them in its
+    /// partial file until it completes
+    async fn bytes_at_destination(&self) -> u64 {
+        match self.kind {
+            OperationKind::Download => {
+                let partial = crate::fs::copy::partial_download_path(std::path::Path::new(&self.dest_path));
+                tokio::fs::metadata(partial).await.map_or(0, |meta| meta.len())
+            }
+            _ => crate::fs::copy::transferred_bytes(&*self.dest_storage, &self.dest_path).await,
+        }
+    }
+}
+
+/// A queued copy or move that failed, kept so the user can retry it.
+#[derive(Clone)]
+pub struct FailedTransfer {
+    pub op: QueuedOperation,
+    /// Bytes found at the destination after the failure
+    pub bytes_transferred: u64,
+    pub error: String,
+}
+
+impl FailedTransfer {
+    /// Whether a retry can continue from `bytes_transferred` instead of starting over
+    pub fn resumable(&self) -> bool {
+        self.bytes_transferred > 0 && self.op.dest_storage.capabilities().append
+    }
+}
+
+/// Copies and moves queued by the user, run one at a time through
+/// `App::background_task` so a batch reports "operation 2/5" progress.
+#[derive(Default)]
+pub struct OperationQueue {
+    pending: std::collections::VecDeque<QueuedOperation>,
+    /// Operation whose task is currently in `App::background_task`
+    pub current: Option<QueuedOperation>,
+    /// Operations in this batch that have finished (successfully or not)
+    pub finished: usize,
+    /// Error messages of failed operations in this batch
+    pub failed: Vec<String>,
+    /// Operations in this batch, including finished and running ones
+    pub total: usize,
+    /// Per-file progress of the running copy
+    pub copy_rx: Option<tokio::sync::mpsc::Receiver<crate::fs::copy::CopyProgress>>,
+    /// Set to stop the running copy (moves and retries can't be cancelled)
+    pub copy_cancelled: Option<Arc<std::sync::atomic::AtomicBool>>,
+    /// Failed transfers of this batch, offered for retry once the batch is done
+    pub failed_transfers: Vec<FailedTransfer>,
+    /// Bytes the running operation transferred, stored by its task once it succeeds
+    pub transferred: Option<Arc<std::sync::atomic::AtomicU64>>,
+}
+
+impl OperationQueue {
+    pub fn push(&mut self, op: QueuedOperation) {
+        self.pending.push_back(op);
+        self.total += 1;
+    }
+
+    /// Number of operations waiting to start
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Whether an operation is running or waiting
+    pub fn is_active(&self) -> bool {
+        self.current.is_some() || !self.pending.is_empty()
+    }
+
+    /// Drop all operations that have not started yet, returning how many
+    pub fn cancel_pending(&mut self) -> usize {
+        let cancelled = self.pending.len();
+        self.pending.clear();
+        self.total -= cancelled;
+        cancelled
+    }
+
+    /// Queue every failed transfer again as a resuming retry, returning how many
+    pub fn retry_failed(&mut self) -> usize {
+        let failed = std::mem::take(&mut self.failed_transfers);
+        let count = failed.len();
+        for transfer in failed {
+            self.push(QueuedOperation { resume: true, ..transfer.op });
+        }
+        count
+    }
+
+    /// Take the next operation and mark it as running
+    fn start_next(&mut self) -> Option<QueuedOperation> {
+        let op = self.pending.pop_front()?;
+        self.current = Some(op.clone());
+        Some(op)
+    }
+
+    /// Record the result of the running operation
+    fn finish_current(&mut self, result: std::result::Result<(), String>) {
+        if self.current.take().is_some() {
+            self.finished += 1;
+            self.copy_rx = None;
+            self.copy_cancelled = None;
+            self.transferred = None;
+            if let Err(e) = result {
+                self.failed.push(e);
+            }
+        }
+    }
+
+    /// Summary for a finished batch, then reset the counters
+    fn finish_batch(&mut self) -> String {
+        let failed = std::mem::take(&mut self.failed);
+        let summary = if failed.is_empty() {
+            format!("✓ {} operations finished", self.finished)
+        } else {
+            format!(
+                "⚠ {}/{} operations finished, {} failed: {}",
+                self.finished - failed.len(),
+                self.finished,
+                failed.len(),
+                failed[0]
+            )
+        };
+        self.finished = 0;
+        self.total = 0;
+        summary
+    }
+}
+
+/// Bytes copied and synced this session, with the cloud traffic split out for anyone
+/// on metered bandwidth. Kept in memory only, so every session starts at zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TransferTotals {
+    /// Everything transferred, whatever the backends
+    pub total: u64,
+    /// Uploaded to cloud storage (ingress)
+    pub cloud_upload: u64,
+    /// Downloaded from cloud storage (egress, usually the billed direction)
+    pub cloud_download: u64,
+}
+
+impl TransferTotals {
+    /// Count `bytes` sent from a `from` backend to a `to` backend; cloud to cloud counts both ways
+    pub fn record(&mut self, from: &crate::fs::BackendType, to: &crate::fs::BackendType, bytes: u64) {
+        self.total += bytes;
+        if from.is_cloud() {
+            self.cloud_download += bytes;
+        }
+        if to.is_cloud() {
+            self.cloud_upload += bytes;
+        }
+    }
+
+    /// Status bar text, empty until something has been transferred
+    pub fn indicator(&self) -> String {
+        if self.total == 0 {
+            return String::new();
+        }
+        let mut text = format!("⇅ {}", crate::fs::types::format_bytes(self.total));
+        if self.cloud_upload > 0 || self.cloud_download > 0 {
+            text.push_str(&format!(
+                " (☁ ↑{} ↓{})",
+                crate::fs::types::format_bytes(self.cloud_upload),
+                crate::fs::types::format_bytes(self.cloud_download)
+            ));
+        }
+        text
+    }
+}
+
+/// Locations kept per pane for back/forward navigation
+pub const HISTORY_LIMIT: usize = 50;
+
+/// A place a pane has shown: the backend and the path in it
+#[derive(Clone)]
+pub struct Location {
+    pub storage: Arc<dyn crate::fs::StorageBackend>,
+    pub path: String,
+}
+
+impl Location {
+    /// Identity of the location; backends are recreated on reconnect, so compare by display path
+    fn key(&self) -> String {
+        self.storage.display_path(&self.path)
+    }
+}
+
+/// Browser-style visited history of one pane, across backend switches
+#[derive(Default)]
+pub struct NavHistory {
+    back: std::collections::VecDeque<Location>,
+    forward: Vec<Location>,
+    current: Option<Location>,
+}
+
+impl NavHistory {
+    /// Note that the pane now shows `location`; a new place drops the forward history
+    pub fn visit(&mut self, location: Location) {
+        if self.current.as_ref().map(Location::key) == Some(location.key()) {
+            self.current = Some(location);
+            return;
+        }
+        if let Some(previous) = self.current.replace(location) {
+            self.push_back(previous);
+        }
+        self.forward.clear();
+    }
+
+    /// Step back, returning the location to show
+    pub fn back(&mut self) -> Option<Location> {
+        let target = self.back.pop_back()?;
+        if let Some(current) = self.current.replace(target.clone()) {
+            self.forward.push(current);
+        }
+        Some(target)
+    }
+
+    /// Step forward again after going back, returning the location to show
+    pub fn forward(&mut self) -> Option<Location> {
+        let target = self.forward.pop()?;
+        if let Some(current) = self.current.replace(target.clone()) {
+            self.push_back(current);
+        }
+        Some(target)
+    }
+
+    fn push_back(&mut self, location: Location) {
+        self.back.push_back(location);
+        if self.back.len() > HISTORY_LIMIT {
+            self.back.pop_front();
+        }
+    }
 }
 
 /// Text input state for rename/search operations.
@@ -95,32 +427,104 @@ pub struct App {
     pub mode: AppMode,
     pub namespaces: Vec<String>,
     pub current_namespace: String,
+    /// Contexts found in the kubeconfig at startup
+    pub kube_contexts: Vec<String>,
+    /// Namespace used for the helper pod when browsing PVs directly
+    pub pv_namespace: String,
     pub should_quit: bool,
+    /// Ask before quitting while work is pending (disable with ABYSS_CONFIRM_QUIT=0)
+    pub confirm_quit: bool,
+    /// Quit confirmation popup is shown
+    pub quit_pending: bool,
+    /// Pane key bindings (defaults, or `~/.config/abyss/keymap.toml`)
+    pub keymap: crate::keymap::Keymap,
+    /// Case setting from `--ignore-case`/`--case-sensitive` or `ABYSS_IGNORE_CASE`; without
+    /// one, pane search ignores case and patterns follow the platform default
+    pub ignore_case: Option<bool>,
+    /// File to open in an external program after the current event
+    pub external_open: Option<ExternalOpen>,
     // Progress tracking
     pub progress: Option<Progress>,
     // Background task for live progress
     pub background_task: Option<tokio::task::JoinHandle<anyhow::Result<String>>>,
-    // Delete confirmation target (full_path, is_local, is_dir)
-    pub delete_target: Option<DeleteTarget>,
+    /// Paths the background task reads or writes (as `display_path` shows them)
+    pub background_paths: Vec<String>,
+    /// Background delete; it runs beside `background_task` unless their paths overlap
+    pub delete_task: Option<tokio::task::JoinHandle<anyhow::Result<String>>>,
+    // Copies/moves waiting for the background task slot
+    pub operation_queue: OperationQueue,
+    // Bytes written by a background editor save (mirrored into `progress`)
+    pub save_written: Option<Arc<std::sync::atomic::AtomicU64>>,
+    // Objects removed by a background delete, and its cancel flag (mirrored into `progress`)
+    pub delete_progress: Option<Arc<crate::fs::backend::DeleteProgress>>,
+    // Entries awaiting delete confirmation (the marked entries, or the selected one)
+    pub delete_targets: Vec<DeleteTarget>,
+    /// Recursive file count and size of the directories being deleted (or why it failed)
+    pub delete_impact: Option<Result<DeleteImpact, String>>,
+    pub delete_impact_rx: Option<crossbeam_channel::Receiver<Result<DeleteImpact, String>>>,
+    pub delete_impact_cancelled: Option<Arc<std::sync::atomic::AtomicBool>>,
     // Sync state
     pub sync_enabled: bool,
     pub sync_status: SyncStatus,
+    pub sync_direction: SyncDirection,
+    pub sync_conflict_strategy: crate::sync::ConflictStrategy,
+    /// Credentials profile used when connecting to cloud storage (`None` = default)
+    pub cloud_profile: Option<String>,
     // Sync background task and progress receiver
     pub sync_task: Option<tokio::task::JoinHandle<anyhow::Result<crate::sync::SyncResult>>>,
     pub sync_progress_rx: Option<tokio::sync::mpsc::Receiver<crate::sync::SyncProgress>>,
+    /// Source and destination backend of the running sync, for the transfer totals
+    pub sync_backends: Option<(crate::fs::BackendType, crate::fs::BackendType)>,
+    /// Bytes copied and synced this session (shown in the status bar)
+    pub transfer_totals: TransferTotals,
     // Text input for rename/search
     pub text_input: TextInput,
     // File viewer content
     pub view_content: Vec<String>,
     pub view_scroll: usize,
+    /// Wrap long lines in the viewers (off: horizontal scroll with Left/Right)
+    pub view_wrap: bool,
+    /// Horizontal scroll offset of the streaming viewer (columns)
+    pub view_hscroll: usize,
+    /// Show the streaming viewer as a hex dump (binary files start in it, x toggles)
+    pub view_hex: bool,
+    /// Lines before the loaded streaming chunk, when known (for absolute line numbers)
+    pub view_line_base: Option<usize>,
+    /// Newlines in the loaded streaming chunk (to carry `view_line_base` to the next chunk)
+    pub view_chunk_newlines: usize,
+    /// Rows shown by the streaming viewer (set by renderer, used for page copies)
+    pub view_visible_height: usize,
+    /// Columns of text in the streaming viewer (set by renderer, to find where lines wrap)
+    pub view_text_width: usize,
+    /// Lines selected for copying in the streaming viewer (started with v)
+    pub view_selection: Option<ViewSelection>,
     // File editor
     pub editor: TextEditor,
+    /// The edited file as found on disk by a save that was stopped for confirmation
+    pub editor_disk_version: Option<FileVersion>,
+    /// The streaming viewer shows the on-disk version of the edited file; closing it returns to the editor
+    pub view_returns_to_editor: bool,
+    /// Sample large files when scanning from the hash menu (toggle with f)
+    pub hash_fast_mode: bool,
+    /// Hash files in parallel from the hash menu (toggle with p)
+    pub hash_parallel: bool,
+    /// Threads for hash menu operations, from ABYSS_HASH_THREADS (0: one per CPU core)
+    pub hash_threads: usize,
+    /// Show a line-number gutter in the editor (toggle with Ctrl+N, ABYSS_LINE_NUMBERS=1 to start on)
+    pub editor_line_numbers: bool,
     
     // Large file handling
     pub pending_large_action: Option<LargeFileAction>,
     pub view_file_offset: u64,
+    /// Bytes per streaming viewer chunk for the file being viewed (see `view_chunk_size`)
+    pub view_chunk_size: u64,
     pub view_file_path: String,
     pub view_file_size: u64,
+
+    // Diff viewer
+    pub diff_lines: Vec<crate::sync::DiffLine>,
+    pub diff_scroll: usize,
+    pub diff_title: String,
     
     // Cleaner / Disk Analyzer state
     pub cleaner_tree: Option<cleaner::DirTree>,
@@ -130,6 +534,8 @@ pub struct App {
     pub cleaner_path: PathBuf,
     pub cleaner_path_stack: Vec<PathBuf>,
     pub cleaner_sort_mode: CleanerSortMode,
+    /// Apparent size or disk usage, shown and sorted by in the analyzer
+    pub cleaner_size_metric: cleaner::SizeMetric,
     pub cleaner_confirm_delete: bool,
     pub cleaner_confirm_clean: bool,
     pub cleaner_status: Option<String>,
@@ -140,10 +546,366 @@ pub struct App {
     pub cleaner_progress: Option<Arc<cleaner::ScanProgress>>,
     pub cleaner_scan_cancelled: Option<Arc<std::sync::atomic::AtomicBool>>,
     pub cleaner_scan_rx: Option<crossbeam_channel::Receiver<cleaner::DirTree>>,
+    /// Backend the analyzer walks with `list_dir` (`None` = local filesystem walk)
+    pub cleaner_backend: Option<Arc<dyn crate::fs::StorageBackend>>,
     
     // Async cleaning state
     pub cleaner_delete_stats: Option<Arc<cleaner::Stats>>,
     pub cleaner_clean_rx: Option<crossbeam_channel::Receiver<anyhow::Result<()>>>,
+    pub cleaner_clean_cancelled: Option<Arc<std::sync::atomic::AtomicBool>>,
+    /// Bytes the running clean is expected to free, for the progress gauge
+    pub cleaner_clean_total: u64,
+
+    // Recently modified files finder
+    pub recent_files: Vec<cleaner::RecentFile>,
+    pub recent_selected: usize,
+    pub recent_root: PathBuf,
+    pub recent_sort_mode: cleaner::RecentSortMode,
+    pub recent_progress: Option<Arc<cleaner::ScanProgress>>,
+    pub recent_cancelled: Option<Arc<std::sync::atomic::AtomicBool>>,
+    pub recent_rx: Option<crossbeam_channel::Receiver<Vec<cleaner::RecentFile>>>,
+    /// Viewer was opened from the recent files list (return there on close)
+    pub recent_viewing: bool,
+
+    // On-demand directory sizes (Space in local panes)
+    pub dir_sizes: DirSizes,
+    pub dir_size_cancelled: Option<Arc<std::sync::atomic::AtomicBool>>,
+    pub dir_size_rx: Option<crossbeam_channel::Receiver<(PathBuf, Option<u64>)>>,
+
+    // Quick hash check of the selected file (Ctrl+V)
+    pub hash_check_path: Option<PathBuf>,
+    pub hash_check_rx: Option<crossbeam_channel::Receiver<Result<crate::hash::HashCheck, String>>>,
+    pub hash_check: Option<crate::hash::HashCheck>,
+    /// Metadata of the selected entry (Alt+I): its shown path and fields, once read
+    pub object_details_rx: Option<crossbeam_channel::Receiver<Result<(String, crate::fs::ObjectMeta), String>>>,
+    pub object_details: Option<(String, crate::fs::ObjectMeta)>,
+    /// Info line of the selected entry (`i`), while its hash is computed
+    pub info_line_rx: Option<crossbeam_channel::Receiver<Result<String, String>>>,
+
+    /// Relative or absolute modification times (`ABYSS_TIME_FORMAT`, toggled with `t`)
+    pub time_format: crate::fs::TimeFormat,
+    /// Help bar page shown when the bindings don't fit the terminal width (F1 cycles)
+    pub help_page: usize,
+    /// Structured event stream for copies, moves, deletes and syncs (`--events`)
+    pub event_log: Option<Arc<crate::event_log::EventLog>>,
+
+    /// F8 deletes without asking (Alt+Y); never persisted, so every session starts safe
+    pub auto_confirm_delete: bool,
+    /// Deletes and cleans only log what they would remove (Alt+D), for review before the real run
+    pub dry_run: bool,
+    pub dry_run_log: DryRunLog,
+    // Matches reported by a simulated analyzer clean, moved into `dry_run_log`
+    pub cleaner_dry_run_rx: Option<crossbeam_channel::Receiver<crate::cleaner::DeleteEvent>>,
+
+    // Copy plan (Ctrl+P): the copies to queue once the listed plan is confirmed
+    pub copy_plan_ops: Vec<QueuedOperation>,
+    pub copy_plan_rx: Option<crossbeam_channel::Receiver<Result<crate::fs::copy::CopyPlan, String>>>,
+    pub copy_plan: Option<crate::fs::copy::CopyPlan>,
+    /// The shown plan was confirmed and is being listed again; the copies are queued
+    /// if nothing changed since
+    pub copy_plan_confirmed: bool,
+
+    // Copies waiting for a merge/replace choice because their target directory exists
+    pub merge_ops: Vec<QueuedOperation>,
+
+    // Verify results view (hash menu → Verify)
+    pub verify_root: PathBuf,
+    pub verify_db: PathBuf,
+    pub verify_progress: Option<Arc<VerifyTally>>,
+    pub verify_rx: Option<crossbeam_channel::Receiver<Result<crate::hash::VerifyReport, String>>>,
+    pub verify_matches: usize,
+    pub verify_entries: Vec<VerifyEntry>,
+    pub verify_selected: usize,
+
+    // Duplicate search (hash menu → Dedup); the result arrives through `background_task`
+    pub dedup_progress_rx: Option<crossbeam_channel::Receiver<crate::hash::DedupProgress>>,
+    pub dedup_cancelled: Option<Arc<std::sync::atomic::AtomicBool>>,
+
+    /// Quick-look pane next to the file panes (toggled with `p`)
+    pub preview: Preview,
+
+    // Redraw tracking: the main loop only draws when something visible may have changed
+    pub needs_redraw: bool,
+    pub was_busy: bool,
+    pub drawn_message: String,
+}
+
+/// Environment variable overriding the largest file the editor loads (e.g. `256M`)
+pub const EDITOR_MAX_SIZE_ENV: &str = "ABYSS_EDITOR_MAX_SIZE";
+
+/// Largest file the editor loads into memory when not configured
+pub const DEFAULT_EDITOR_MAX_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Environment variable turning off the changed-on-disk check before editor saves (`0`)
+pub const EDITOR_CHECK_DISK_ENV: &str = "ABYSS_EDITOR_CHECK_DISK";
+
+/// Environment variable overriding the streaming viewer's chunk size (e.g. `256K`)
+pub const VIEW_CHUNK_SIZE_ENV: &str = "ABYSS_VIEW_CHUNK_SIZE";
+
+/// Bytes the streaming viewer reads at a time from a local file when not configured
+pub const DEFAULT_VIEW_CHUNK_SIZE: u64 = 64 * 1024;
+
+/// Chunk size for remote backends, where every chunk is a network round trip
+pub const DEFAULT_REMOTE_VIEW_CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// Smallest chunk the viewer accepts from `ABYSS_VIEW_CHUNK_SIZE`
+const MIN_VIEW_CHUNK_SIZE: u64 = 4 * 1024;
+
+/// Streaming viewer chunk size for a backend, from `ABYSS_VIEW_CHUNK_SIZE` or the default.
+/// Always a multiple of the hex row width so hex offsets line up across chunks.
+pub fn view_chunk_size(is_local: bool) -> u64 {
+    let configured = std::env::var(VIEW_CHUNK_SIZE_ENV)
+        .ok()
+        .and_then(|v| crate::fs::copy::parse_buffer_size(&v))
+        .map(|size| size as u64);
+    let default = if is_local { DEFAULT_VIEW_CHUNK_SIZE } else { DEFAULT_REMOTE_VIEW_CHUNK_SIZE };
+    align_view_chunk(configured.unwrap_or(default))
+}
+
+fn align_view_chunk(size: u64) -> u64 {
+    let row = crate::ui::hexdump::HEX_ROW_BYTES as u64;
+    size.max(MIN_VIEW_CHUNK_SIZE) / row * row
+}
+
+/// Offset of the chunk before the one at `offset`, if there is one
+pub fn prev_chunk_offset(offset: u64, chunk: u64) -> Option<u64> {
+    (offset > 0).then(|| offset.saturating_sub(chunk))
+}
+
+/// Offset of the chunk after the one at `offset` in a file of `total` bytes, if there is one
+pub fn next_chunk_offset(offset: u64, chunk: u64, total: u64) -> Option<u64> {
+    let next = offset + chunk;
+    (next < total).then_some(next)
+}
+
+/// Local file a viewed file called `name` is downloaded to, from what was typed at the prompt
+///
+/// A leading `~` is the home directory and relative paths start at `base`. A directory
+/// (existing, or typed with a trailing `/`) gets the file's own name. `None` when nothing
+/// was typed.
+pub fn download_target(input: &str, name: &str, base: &std::path::Path) -> Option<PathBuf> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+    let expanded = match (input.strip_prefix('~'), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            PathBuf::from(home).join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(input),
+    };
+    let path = base.join(expanded);
+    Some(if input.ends_with('/') || path.is_dir() { path.join(name) } else { path })
+}
+
+/// How long the selection has to rest on a file before the preview pane reads it
+pub const PREVIEW_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(150);
+
+/// Bytes the preview pane reads from the start of a file
+pub const PREVIEW_BYTES: u64 = 16 * 1024;
+
+/// Largest remote file the preview pane reads, since remote backends fetch the whole
+/// object for a range read
+pub const PREVIEW_REMOTE_MAX: u64 = 1024 * 1024;
+
+/// Line range selected in the streaming viewer, as indices into the loaded chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ViewSelection {
+    /// Line the selection was started on
+    pub anchor: usize,
+    /// Line moved with the arrows
+    pub cursor: usize,
+}
+
+impl ViewSelection {
+    pub fn new(line: usize) -> Self {
+        Self { anchor: line, cursor: line }
+    }
+
+    /// First and last selected line, in order
+    pub fn range(&self) -> (usize, usize) {
+        (self.anchor.min(self.cursor), self.anchor.max(self.cursor))
+    }
+
+    pub fn contains(&self, line: usize) -> bool {
+        let (first, last) = self.range();
+        (first..=last).contains(&line)
+    }
+
+    /// Move the cursor by `delta` lines, staying within `len` lines
+    pub fn move_cursor(&mut self, delta: isize, len: usize) {
+        self.cursor = self.cursor.saturating_add_signed(delta).min(len.saturating_sub(1));
+    }
+
+    /// The selected lines joined with newlines
+    pub fn text(&self, lines: &[String]) -> String {
+        let (first, last) = self.range();
+        lines.get(first..=last.min(lines.len().saturating_sub(1))).unwrap_or_default().join("\n")
+    }
+}
+
+/// Environment variable listing the fields of the info line copied with `i`
+pub const INFO_FIELDS_ENV: &str = "ABYSS_INFO_FIELDS";
+
+/// Fields of the info line when `ABYSS_INFO_FIELDS` is not set
+pub const DEFAULT_INFO_FIELDS: &str = "name,size,modified,hash";
+
+/// One part of the info line that `i` copies for the selected entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InfoField {
+    Name,
+    /// Full path as the pane shows it (with the backend's scheme for remote panes)
+    Path,
+    Size,
+    Modified,
+    Permissions,
+    /// Digest with this algorithm (`hash` alone means BLAKE3); local files only
+    Hash(String),
+}
+
+impl InfoField {
+    /// Parse a comma-separated field list like `name,size,sha256`
+    pub fn parse_list(text: &str) -> Result<Vec<InfoField>, String> {
+        let fields = text
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+            .map(|field| match field.to_ascii_lowercase().as_str() {
+                "name" => Ok(InfoField::Name),
+                "path" => Ok(InfoField::Path),
+                "size" => Ok(InfoField::Size),
+                "modified" | "mtime" => Ok(InfoField::Modified),
+                "permissions" | "mode" => Ok(InfoField::Permissions),
+                "hash" => Ok(InfoField::Hash("blake3".to_string())),
+                algorithm if crate::hash::HashRegistry::get_hasher(algorithm).is_ok() => {
+                    Ok(InfoField::Hash(algorithm.to_string()))
+                }
+                _ => Err(format!(
+                    "unknown info field '{}' (use name, path, size, modified, permissions, hash or an algorithm)",
+                    field
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if fields.is_empty() {
+            return Err("no info fields given".to_string());
+        }
+        Ok(fields)
+    }
+
+    /// Fields from `ABYSS_INFO_FIELDS`, or the defaults
+    pub fn from_env() -> Result<Vec<InfoField>, String> {
+        let configured = std::env::var(INFO_FIELDS_ENV).ok().filter(|v| !v.trim().is_empty());
+        Self::parse_list(configured.as_deref().unwrap_or(DEFAULT_INFO_FIELDS))
+            .map_err(|e| format!("{}: {}", INFO_FIELDS_ENV, e))
+    }
+}
+
+/// The info line for an entry, fields separated by two spaces: e.g.
+/// `notes.txt  1.2 KiB (1234 bytes)  2026-10-16 14:03:05 +02:00  blake3:af13...`
+///
+/// `hashes` holds `(algorithm, digest)` for the hash fields that could be computed; the
+/// others (directories, remote files) are left out, as are unknown times and permissions.
+pub fn format_info_line(
+    entry: &crate::fs::FileEntry,
+    path: &str,
+    fields: &[InfoField],
+    hashes: &[(String, String)],
+) -> String {
+    let parts: Vec<String> = fields
+        .iter()
+        .filter_map(|field| match field {
+            InfoField::Name => Some(if entry.is_dir { format!("{}/", entry.name) } else { entry.name.clone() }),
+            InfoField::Path => Some(path.to_string()),
+            InfoField::Size if entry.is_dir => None,
+            InfoField::Size => Some(format!(
+                "{} ({} bytes)",
+                humansize::format_size(entry.size, humansize::BINARY),
+                entry.size
+            )),
+            InfoField::Modified => entry
+                .modified
+                .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S %:z").to_string()),
+            InfoField::Permissions => entry.permissions.clone(),
+            InfoField::Hash(algorithm) => hashes
+                .iter()
+                .find(|(name, _)| name == algorithm)
+                .map(|(name, digest)| format!("{}:{}", name, digest)),
+        })
+        .collect();
+    parts.join("  ")
+}
+
+/// Quick-look pane showing the start of the selected file
+#[derive(Default)]
+pub struct Preview {
+    pub enabled: bool,
+    /// Entry shown (or being read)
+    pub path: Option<String>,
+    /// Text lines, or hex rows when `hex` is set
+    pub lines: Vec<String>,
+    pub hex: bool,
+    /// Shown instead of content for directories, errors and skipped files
+    pub note: Option<String>,
+    /// Selection waiting out the debounce, and since when
+    pending: Option<(String, std::time::Instant)>,
+    pub rx: Option<crossbeam_channel::Receiver<Result<Vec<u8>, String>>>,
+}
+
+impl Preview {
+    /// Track the selected file; returns it once the selection has rested on it for
+    /// `PREVIEW_DEBOUNCE`, so scrolling through a listing doesn't read every file passed
+    pub fn settle(&mut self, path: &str, now: std::time::Instant) -> Option<String> {
+        if self.path.as_deref() == Some(path) {
+            self.pending = None;
+            return None;
+        }
+        match self.pending {
+            Some((ref pending, since)) if pending == path => {
+                if now.duration_since(since) < PREVIEW_DEBOUNCE {
+                    return None;
+                }
+                self.pending = None;
+                self.path = Some(path.to_string());
+                self.lines.clear();
+                self.note = None;
+                Some(path.to_string())
+            }
+            _ => {
+                self.pending = Some((path.to_string(), now));
+                None
+            }
+        }
+    }
+
+    /// Show a note instead of content (no read needed)
+    pub fn show_note(&mut self, path: &str, note: &str) {
+        self.pending = None;
+        self.rx = None;
+        self.path = Some(path.to_string());
+        self.lines.clear();
+        self.hex = false;
+        self.note = Some(note.to_string());
+    }
+
+    /// Fill the pane from the first bytes of the file, as hex rows if it looks binary
+    pub fn show_data(&mut self, data: &[u8]) {
+        self.hex = crate::ui::hexdump::looks_binary(data);
+        self.lines = if self.hex {
+            crate::ui::hexdump::hex_dump_lines(data, 0)
+        } else {
+            String::from_utf8_lossy(data).lines().map(str::to_string).collect()
+        };
+        self.note = data.is_empty().then(|| "(empty file)".to_string());
+    }
+
+    /// Forget the shown entry, e.g. when the pane is hidden
+    pub fn clear(&mut self) {
+        self.pending = None;
+        self.rx = None;
+        self.path = None;
+        self.lines.clear();
+        self.note = None;
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -152,6 +914,8 @@ pub struct TextEditor {
     pub cursor_row: usize,
     pub cursor_col: usize,
     pub scroll_offset: usize,
+    /// First visible column (horizontal scroll, kept in view of the cursor by the renderer)
+    pub col_offset: usize,
     pub filename: String,
     pub modified: bool,
     pub cut_buffer: Option<String>,
@@ -159,14 +923,65 @@ pub struct TextEditor {
     pub visible_height: usize,
     /// If true, the editor is in readonly mode (viewing only, no edits allowed)
     pub readonly: bool,
+    /// A background save is running; edits and re-saving are blocked until it finishes
+    pub saving: bool,
+    /// The file on disk when it was loaded or last saved; a save that finds it changed
+    /// asks before overwriting (None for unknown, e.g. a backend without `stat`)
+    pub on_disk: Option<FileVersion>,
+}
+
+/// Size and modification time of a file, enough to tell that someone else wrote it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileVersion {
+    pub size: u64,
+    pub modified: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+impl From<&crate::fs::backend::FileStat> for FileVersion {
+    fn from(stat: &crate::fs::backend::FileStat) -> Self {
+        Self { size: stat.size, modified: stat.modified }
+    }
+}
 
 impl TextEditor {
+    /// Largest file the editor will load, from `ABYSS_EDITOR_MAX_SIZE` or the default.
+    /// Bigger files open read-only in the streaming viewer instead.
+    pub fn max_size() -> u64 {
+        Self::max_size_from(std::env::var(EDITOR_MAX_SIZE_ENV).ok().as_deref())
+    }
+
+    /// The editor limit for a setting like `256M`; the default when unset or unreadable
+    fn max_size_from(setting: Option<&str>) -> u64 {
+        setting
+            .and_then(crate::fs::copy::parse_buffer_size)
+            .map_or(DEFAULT_EDITOR_MAX_SIZE, |size| size as u64)
+    }
+
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// An editor holding `data` as lines, with `on_disk` the file's version when it was read
+    pub fn open(filename: String, data: &[u8], on_disk: Option<FileVersion>) -> Self {
+        let content: Vec<String> = String::from_utf8_lossy(data).lines().map(str::to_string).collect();
+        Self {
+            content: if content.is_empty() { vec![String::new()] } else { content },
+            filename,
+            on_disk,
+            ..Self::default()
+        }
+    }
+
+    /// A readonly editor for viewing `data`
+    pub fn read_only(filename: String, data: &[u8]) -> Self {
+        Self { readonly: true, ..Self::open(filename, data, None) }
+    }
+
+    /// Whether saves check the file for outside changes first (`ABYSS_EDITOR_CHECK_DISK=0` turns it off)
+    pub fn checks_disk() -> bool {
+        !matches!(std::env::var(EDITOR_CHECK_DISK_ENV).as_deref(), Ok("0") | Ok("false") | Ok("no"))
+    }
+
     pub fn insert_char(&mut self, c: char) {
         if self.content.is_empty() {
             self.content.push(String::new());
@@ -248,6 +1063,17 @@ impl TextEditor {
         }
     }
 
+    /// Move the cursor to the start of 1-based `line` (clamped to the file) and center it.
+    /// Returns the line actually reached.
+    pub fn go_to_line(&mut self, line: usize) -> usize {
+        let last = self.content.len().saturating_sub(1);
+        self.cursor_row = line.saturating_sub(1).min(last);
+        self.cursor_col = 0;
+        let visible = if self.visible_height > 0 { self.visible_height } else { 20 };
+        self.scroll_offset = self.cursor_row.saturating_sub(visible / 2);
+        self.cursor_row + 1
+    }
+
     pub fn uncut_line(&mut self) {
         if let Some(ref line) = self.cut_buffer {
             if self.content.is_empty() {
@@ -288,32 +1114,239 @@ impl SyncStatus {
     }
 }
 
+/// Direction used by manual sync and dry-run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncDirection {
+    #[default]
+    LeftToRight,
+    RightToLeft,
+    Bidirectional,
+}
+
+impl SyncDirection {
+    pub fn next(self) -> Self {
+        match self {
+            Self::LeftToRight => Self::RightToLeft,
+            Self::RightToLeft => Self::Bidirectional,
+            Self::Bidirectional => Self::LeftToRight,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::LeftToRight => "L→R",
+            Self::RightToLeft => "R→L",
+            Self::Bidirectional => "L↔R",
+        }
+    }
+
+    /// Sync engine mode for this direction
+    pub fn mode(self) -> crate::sync::SyncMode {
+        match self {
+            Self::LeftToRight | Self::RightToLeft => crate::sync::SyncMode::OneWay,
+            Self::Bidirectional => crate::sync::SyncMode::Bidirectional,
+        }
+    }
+}
+
+/// What a verify found for one file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyEntryKind {
+    Changed,
+    Missing,
+    New,
+}
+
+impl VerifyEntryKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Changed => "changed",
+            Self::Missing => "missing",
+            Self::New => "new",
+        }
+    }
+}
+
+/// One row of the verify results view
+#[derive(Debug, Clone)]
+pub struct VerifyEntry {
+    pub kind: VerifyEntryKind,
+    pub path: PathBuf,
+}
+
+/// Rows for a verify report: changed, then missing, then new files, each sorted by path
+pub fn verify_entries(report: &crate::hash::VerifyReport) -> Vec<VerifyEntry> {
+    let mut entries = Vec::new();
+    for (kind, mut paths) in [
+        (VerifyEntryKind::Changed, report.mismatches.iter().map(|m| &m.path).collect::<Vec<_>>()),
+        (VerifyEntryKind::Missing, report.missing_files.iter().collect()),
+        (VerifyEntryKind::New, report.new_files.iter().collect()),
+    ] {
+        paths.sort();
+        entries.extend(paths.into_iter().map(|path| VerifyEntry { kind, path: path.clone() }));
+    }
+    entries
+}
+
+/// Files checked so far by a running verify, fed from its progress callback
+#[derive(Debug, Default)]
+pub struct VerifyTally {
+    pub checked: std::sync::atomic::AtomicUsize,
+    pub total: std::sync::atomic::AtomicUsize,
+}
+
 #[derive(Clone)]
 pub struct DeleteTarget {
     pub backend: std::sync::Arc<dyn crate::fs::StorageBackend>,
     pub path: String,
     pub display_path: String,
     pub is_dir: bool,
+    pub size: u64,
 }
 
-#[derive(Debug, Clone)]
-pub struct Progress {
-    pub stage: ProgressStage,
-    pub current: u64,
-    pub total: u64,
-    pub current_file: String,
-    pub files_done: usize,
-    pub total_files: usize,
+/// Everything a delete would remove, counted before it is confirmed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeleteImpact {
+    pub files: usize,
+    pub directories: usize,
+    pub bytes: u64,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum ProgressStage {
-    Counting,
-    Archiving,
-    Transferring,
-    Extracting,
-    Complete,
-}
+impl DeleteImpact {
+    /// Walk the directories among `targets` with `list_dir_all`, so remote PVCs and bucket
+    /// prefixes are counted the same way they will be deleted, hidden entries included. `None` once `cancel` is set.
+    pub async fn measure(targets: &[DeleteTarget], cancel: &std::sync::atomic::AtomicBool) -> Option<Result<Self, String>> {
+        let mut impact = Self::default();
+        for target in targets {
+            let counted = if target.is_dir {
+                impact.add_dir(target.backend.as_ref(), &target.path, cancel).await
+            } else {
+                impact.files += 1;
+                impact.bytes += target.size;
+                Ok(())
+            };
+            if cancel.load(std::sync::atomic::Ordering::Relaxed) {
+                return None;
+            }
+            if let Err(e) = counted {
+                return Some(Err(e.to_string()));
+            }
+        }
+        Some(Ok(impact))
+    }
+
+    async fn add_dir(
+        &mut self,
+        backend: &dyn crate::fs::StorageBackend,
+        path: &str,
+        cancel: &std::sync::atomic::AtomicBool,
+    ) -> crate::fs::FsResult<()> {
+        if cancel.load(std::sync::atomic::Ordering::Relaxed) {
+            return Ok(());
+        }
+        self.directories += 1;
+        for entry in backend.list_dir_all(path).await? {
+            if entry.name == ".." {
+                continue;
+            }
+            if entry.is_dir {
+                let child = format!("{}/{}", path.trim_end_matches('/'), entry.name);
+                Box::pin(self.add_dir(backend, &child, cancel)).await?;
+            } else {
+                self.files += 1;
+                self.bytes += entry.size;
+            }
+        }
+        Ok(())
+    }
+
+    /// e.g. `340 files in 12 directories, 1.2 GB`
+    pub fn summary(&self) -> String {
+        format!(
+            "{} files in {} directories, {}",
+            self.files,
+            self.directories,
+            crate::fs::types::format_bytes(self.bytes)
+        )
+    }
+}
+
+/// Lines the dry-run log keeps; the oldest are dropped first
+pub const DRY_RUN_LOG_LIMIT: usize = 1000;
+
+/// What deletes and cleans would have removed while dry run is on
+#[derive(Debug, Clone, Default)]
+pub struct DryRunLog {
+    lines: std::collections::VecDeque<String>,
+}
+
+impl DryRunLog {
+    pub fn push(&mut self, line: String) {
+        if self.lines.len() == DRY_RUN_LOG_LIMIT {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+
+    /// Log a path a delete would remove, with its size when known
+    pub fn would_delete(&mut self, path: &str, is_dir: bool, bytes: Option<u64>) {
+        let kind = if is_dir { "directory" } else { "file" };
+        self.push(match bytes {
+            Some(bytes) => format!("Would delete {} {} ({})", kind, path, crate::fs::types::format_bytes(bytes)),
+            None => format!("Would delete {} {}", kind, path),
+        });
+    }
+
+    /// Log one match of a simulated clean
+    pub fn record(&mut self, event: &crate::cleaner::DeleteEvent) {
+        use crate::cleaner::DeleteEvent;
+        match event {
+            DeleteEvent::Removed { path, is_dir, bytes } => {
+                self.would_delete(&path.display().to_string(), *is_dir, Some(*bytes))
+            }
+            DeleteEvent::Unlinked { path, target } => {
+                self.push(format!("Would unlink {} (keeping {})", path.display(), target.display()))
+            }
+            DeleteEvent::Skipped { path } => self.push(format!("Would skip {}", path.display())),
+            DeleteEvent::Failed { path, error } => self.push(format!("Failed: {}: {}", path.display(), error)),
+        }
+    }
+
+    pub fn lines(&self) -> impl DoubleEndedIterator<Item = &str> + ExactSizeIterator {
+        self.lines.iter().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Progress {
+    pub stage: ProgressStage,
+    pub current: u64,
+    pub total: u64,
+    pub current_file: String,
+    pub files_done: usize,
+    pub total_files: usize,
+    /// Position of the running operation in a queued batch (1-based, total)
+    pub batch: Option<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressStage {
+    Counting,
+    Archiving,
+    Transferring,
+    Extracting,
+    Saving,
+    Deleting,
+    Complete,
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ActivePane {
@@ -330,13 +1363,17 @@ pub enum CleanerSortMode {
 
 impl App {
     pub async fn new() -> Result<Self> {
+        Self::with_k8s_options(K8sOptions::from_env()).await
+    }
+
+    pub async fn with_k8s_options(k8s_options: K8sOptions) -> Result<Self> {
+        let (kube_contexts, _) = crate::k8s::client::available_contexts();
+
         // Try to initialize K8s, but don't fail if unavailable
         let (k8s_client, storage_manager, remote_fs, current_namespace, k8s_message) = 
-            match K8sClient::new().await {
-                Ok(client) => {
+            match Self::connect_k8s(k8s_options.context.as_deref()).await {
+                Ok((client, storage_mgr, remote)) => {
                     let namespace = client.current_namespace().to_string();
-                    let storage_mgr = StorageManager::new(client.client());
-                    let remote = RemoteFs::new(client.client());
                     (Some(client), Some(storage_mgr), Some(remote), namespace, None)
                 }
                 Err(e) => {
@@ -354,8 +1391,21 @@ impl App {
         let mut right_pane = Pane::new(home_dir.clone());
         right_pane.is_active = false;
 
-        let welcome_msg = if let Some(k8s_err) = k8s_message {
+        // A broken keymap file shouldn't keep the app from starting
+        let (keymap, keymap_error) = match crate::keymap::Keymap::load() {
+            Ok(keymap) => (keymap, None),
+            Err(e) => (crate::keymap::Keymap::default(), Some(e)),
+        };
+
+        let welcome_msg = if let Some(e) = keymap_error {
+            format!("Keymap ignored, using default keys: {:#}", e)
+        } else if let Some(k8s_err) = k8s_message {
             format!("Welcome to Abyss ({})", k8s_err)
+        } else if kube_contexts.len() > 1 {
+            format!(
+                "Welcome to Abyss - {} kube contexts available, switch via Ctrl+N → Kubernetes Context",
+                kube_contexts.len()
+            )
         } else {
             "Welcome to Abyss - Press Ctrl+N to change pane storage type".to_string()
         };
@@ -372,22 +1422,66 @@ impl App {
             mode: AppMode::Normal,
             namespaces: Vec::new(),
             current_namespace,
+            kube_contexts,
+            pv_namespace: k8s_options.pv_namespace.unwrap_or_else(|| "default".to_string()),
             should_quit: false,
+            confirm_quit: !matches!(
+                std::env::var("ABYSS_CONFIRM_QUIT").as_deref(),
+                Ok("0") | Ok("false") | Ok("no")
+            ),
+            quit_pending: false,
+            keymap,
+            ignore_case: crate::hash::wildcard::ignore_case_from_env(),
+            external_open: None,
             progress: None,
             background_task: None,
-            delete_target: None,
+            background_paths: Vec::new(),
+            delete_task: None,
+            operation_queue: OperationQueue::default(),
+            save_written: None,
+            delete_progress: None,
+            delete_targets: Vec::new(),
+            delete_impact: None,
+            delete_impact_rx: None,
+            delete_impact_cancelled: None,
             sync_enabled: false,
             sync_status: SyncStatus::Disabled,
+            sync_direction: SyncDirection::default(),
+            sync_conflict_strategy: crate::sync::ConflictStrategy::default(),
+            cloud_profile: crate::fs::credentials::active_profile(),
             sync_task: None,
             sync_progress_rx: None,
+            sync_backends: None,
+            transfer_totals: TransferTotals::default(),
             text_input: TextInput::default(),
             view_content: Vec::new(),
             view_scroll: 0,
+            view_wrap: true,
+            view_hscroll: 0,
+            view_hex: false,
+            view_line_base: None,
+            view_chunk_newlines: 0,
+            view_visible_height: 0,
+            view_text_width: 0,
+            view_selection: None,
             editor: TextEditor::default(),
+            editor_disk_version: None,
+            view_returns_to_editor: false,
+            hash_fast_mode: false,
+            hash_parallel: crate::hash::threads_from_env() != Some(1),
+            hash_threads: crate::hash::threads_from_env().unwrap_or(0),
+            editor_line_numbers: matches!(
+                std::env::var("ABYSS_LINE_NUMBERS").as_deref(),
+                Ok("1") | Ok("true") | Ok("yes")
+            ),
             pending_large_action: None,
             view_file_offset: 0,
+            view_chunk_size: DEFAULT_VIEW_CHUNK_SIZE,
             view_file_path: String::new(),
             view_file_size: 0,
+            diff_lines: Vec::new(),
+            diff_scroll: 0,
+            diff_title: String::new(),
             // Cleaner state
             cleaner_tree: None,
             cleaner_entries: Vec::new(),
@@ -396,6 +1490,7 @@ impl App {
             cleaner_path: PathBuf::from(&home_dir),
             cleaner_path_stack: Vec::new(),
             cleaner_sort_mode: CleanerSortMode::default(),
+            cleaner_size_metric: cleaner::SizeMetric::from_env(),
             cleaner_confirm_delete: false,
             cleaner_confirm_clean: false,
             cleaner_status: None,
@@ -406,8 +1501,53 @@ impl App {
             cleaner_progress: None,
             cleaner_scan_cancelled: None,
             cleaner_scan_rx: None,
+            cleaner_backend: None,
             cleaner_delete_stats: None,
             cleaner_clean_rx: None,
+            cleaner_clean_cancelled: None,
+            cleaner_clean_total: 0,
+            recent_files: Vec::new(),
+            recent_selected: 0,
+            recent_root: PathBuf::from(&home_dir),
+            recent_sort_mode: cleaner::RecentSortMode::default(),
+            recent_progress: None,
+            recent_cancelled: None,
+            recent_rx: None,
+            recent_viewing: false,
+            dir_sizes: DirSizes::default(),
+            dir_size_cancelled: None,
+            dir_size_rx: None,
+            hash_check_path: None,
+            hash_check_rx: None,
+            info_line_rx: None,
+            hash_check: None,
+            object_details_rx: None,
+            object_details: None,
+            time_format: crate::fs::TimeFormat::from_env(),
+            help_page: 0,
+            event_log: None,
+            auto_confirm_delete: false,
+            dry_run: false,
+            dry_run_log: DryRunLog::default(),
+            cleaner_dry_run_rx: None,
+            copy_plan_ops: Vec::new(),
+            copy_plan_rx: None,
+            copy_plan: None,
+            copy_plan_confirmed: false,
+            merge_ops: Vec::new(),
+            verify_root: PathBuf::new(),
+            verify_db: PathBuf::new(),
+            verify_progress: None,
+            verify_rx: None,
+            dedup_progress_rx: None,
+            dedup_cancelled: None,
+            verify_matches: 0,
+            verify_entries: Vec::new(),
+            verify_selected: 0,
+            preview: Preview::default(),
+            needs_redraw: true,
+            was_busy: false,
+            drawn_message: String::new(),
         };
 
         // Load initial directories for both panes
@@ -423,8 +1563,9 @@ impl App {
             ActivePane::Right => &mut self.right_pane,
         };
         
-        match pane.storage.list_dir(&pane.path).await {
-            Ok(mut entries) => {
+        match pane.storage.list_dir_paged(&pane.path, crate::fs::backend::LIST_PAGE_SIZE).await {
+            Ok(listing) => {
+                let mut entries = listing.entries;
                 // Add ".." entry at top if not at root
                 let is_root = pane.path.is_empty() || pane.path == "/" || pane.path == ".";
                 if !is_root {
@@ -438,6 +1579,16 @@ impl App {
                 }
                 
                 pane.entries = entries;
+                pane.pending = listing.rest;
+                // Marks on entries not read yet survive until the listing is complete
+                if pane.pending.is_none() {
+                    pane.prune_marks();
+                }
+                // Free space is only informational, so a failed lookup just hides it
+                pane.space = pane.storage.space_info(&pane.path).await.ok().flatten();
+                if !matches!(pane.storage.backend_type(), crate::fs::BackendType::Selecting) {
+                    pane.history.visit(Location { storage: pane.storage.clone(), path: pane.path.clone() });
+                }
                 // Always reset cursor to first entry when directory changes
                 if !pane.entries.is_empty() {
                     pane.state.select(Some(0));
@@ -447,16 +1598,85 @@ impl App {
                 self.message = format!("Error: {}", e);
                 // Clear entries on error to indicate issue
                 pane.entries.clear();
+                pane.marked.clear();
+                pane.space = None;
+                pane.pending = None;
             }
         }
         Ok(())
     }
 
+    /// Backend path of the file open in the editor (the editor edits files in the active pane)
+    pub fn editor_path(&self) -> String {
+        let pane = self.active_pane();
+        if pane.path.ends_with('/') || pane.path.is_empty() {
+            format!("{}{}", pane.path, self.editor.filename)
+        } else {
+            format!("{}/{}", pane.path, self.editor.filename)
+        }
+    }
+
+    /// The edited file's current version if it changed on disk since it was loaded or saved.
+    /// Files that can't be checked (unknown version, no longer there) count as unchanged.
+    pub async fn editor_disk_change(&self) -> Option<FileVersion> {
+        let loaded = self.editor.on_disk?;
+        let stat = self.active_pane().storage.stat(&self.editor_path()).await.ok()?;
+        let current = FileVersion::from(&stat);
+        (current != loaded).then_some(current)
+    }
+
+    /// Remember the edited file as it is on disk now, after a save
+    pub async fn record_editor_version(&mut self) {
+        let stat = self.active_pane().storage.stat(&self.editor_path()).await;
+        self.editor.on_disk = stat.ok().map(|stat| FileVersion::from(&stat));
+    }
+
     // Helper to refresh the currently active pane
     pub async fn refresh_active_pane(&mut self) -> Result<()> {
         self.refresh_pane(self.active_pane).await
     }
 
+    pub fn pane_mut(&mut self, pane_type: ActivePane) -> &mut Pane {
+        match pane_type {
+            ActivePane::Left => &mut self.left_pane,
+            ActivePane::Right => &mut self.right_pane,
+        }
+    }
+
+    /// Re-read a pane's directory in place, keeping the cursor on the selected entry
+    /// (or at the same row when the entry is gone)
+    pub async fn reload_pane(&mut self, pane_type: ActivePane) -> Result<()> {
+        let pane = self.pane_mut(pane_type);
+        let name = pane.selected_entry().map(|e| e.name.clone());
+        let index = pane.state.selected();
+        self.refresh_pane(pane_type).await?;
+        self.pane_mut(pane_type).select_name(name.as_deref(), index);
+        Ok(())
+    }
+
+    /// Re-read both panes after anything that changes files, so neither shows a stale
+    /// listing (the other pane may show the same directory, or the copy destination)
+    pub async fn refresh_both_panes(&mut self) -> Result<()> {
+        self.reload_pane(ActivePane::Left).await?;
+        self.reload_pane(ActivePane::Right).await
+    }
+
+    /// Go back (or forward) through the active pane's visited locations,
+    /// reconnecting to the backend the location was shown in
+    pub async fn navigate_history(&mut self, forward: bool) -> Result<()> {
+        let pane = self.active_pane_mut();
+        let target = if forward { pane.history.forward() } else { pane.history.back() };
+        let Some(location) = target else {
+            self.message = if forward { "No forward history" } else { "No back history" }.to_string();
+            return Ok(());
+        };
+        let label = location.storage.display_path(&location.path);
+        pane.storage = location.storage;
+        pane.path = location.path;
+        self.message = format!("{} {}", if forward { "→" } else { "←" }, label);
+        self.refresh_active_pane().await
+    }
+
     pub fn switch_pane(&mut self) {
         self.left_pane.is_active = !self.left_pane.is_active;
         self.right_pane.is_active = !self.right_pane.is_active;
@@ -468,6 +1688,64 @@ impl App {
         };
     }
 
+    /// Swap the contents of the left and right panes (backend, path, entries, selection).
+    /// The focus stays on the same side, so sync (always left -> right) is reversed.
+    pub fn swap_panes(&mut self) {
+        std::mem::swap(&mut self.left_pane, &mut self.right_pane);
+        std::mem::swap(&mut self.left_pane.is_active, &mut self.right_pane.is_active);
+    }
+
+    /// Connect to a cluster, using the kubeconfig's current context if `context` is `None`
+    pub async fn connect_k8s(context: Option<&str>) -> Result<(K8sClient, StorageManager, RemoteFs)> {
+        let client = K8sClient::with_context(context).await?;
+        let storage_mgr = StorageManager::new(client.client());
+        let remote = RemoteFs::new(client.client());
+        Ok((client, storage_mgr, remote))
+    }
+
+    /// Name of the active kubeconfig context, if connected to a cluster
+    pub fn kube_context(&self) -> Option<&str> {
+        self.k8s_client.as_ref().and_then(|c| c.context())
+    }
+
+    /// Work that would be lost by quitting now (empty when it is safe to quit)
+    pub fn pending_work(&self) -> Vec<&'static str> {
+        let mut pending = Vec::new();
+        if self.editor.saving {
+            pending.push("Editor save in progress");
+        } else if self.background_task.is_some() || self.operation_queue.is_active() {
+            pending.push("Copy/move in progress");
+        }
+        if self.delete_task.is_some() {
+            pending.push("Delete in progress");
+        }
+        if self.sync_task.is_some() {
+            pending.push("Sync in progress");
+        }
+        if self.editor.modified {
+            pending.push("Unsaved editor changes");
+        }
+        pending
+    }
+
+    /// Whether pane search ignores case: yes unless the user asked for case-sensitive matching
+    pub fn search_ignores_case(&self) -> bool {
+        self.ignore_case.unwrap_or(true)
+    }
+
+    /// Whether pattern marking, cleaner patterns and sync excludes ignore case
+    pub fn patterns_ignore_case(&self) -> bool {
+        self.ignore_case.unwrap_or_else(crate::hash::wildcard::ignore_case_default)
+    }
+
+    /// Default sync excludes, matched with the session's case setting
+    pub fn sync_excludes(&self) -> crate::sync::ExcludePatterns {
+        let mut exclude = crate::sync::ExcludePatterns::with_defaults();
+        // The defaults always compile, whatever the case setting
+        let _ = exclude.set_ignore_case(self.patterns_ignore_case());
+        exclude
+    }
+
     pub fn active_pane_mut(&mut self) -> &mut Pane {
         match self.active_pane {
             ActivePane::Left => &mut self.left_pane,
@@ -482,12 +1760,70 @@ impl App {
         }
     }
 
+    /// The pane copies and moves go to
+    pub fn inactive_pane(&self) -> &Pane {
+        match self.active_pane {
+            ActivePane::Left => &self.right_pane,
+            ActivePane::Right => &self.left_pane,
+        }
+    }
+
+    /// Why one of `ops` can't be queued: it would copy or move an entry onto itself or
+    /// into its own directory
+    pub fn overlap_error(ops: &[QueuedOperation]) -> Option<String> {
+        ops.iter().find_map(|op| {
+            crate::fs::copy::overlap_error(op.src_storage.as_ref(), &op.src_path, op.dest_storage.as_ref(), &op.dest_path)
+        })
+    }
+
+    /// Whether `op` copies a directory onto one the other pane already shows
+    pub fn merge_conflict(&self, op: &QueuedOperation) -> bool {
+        op.kind == OperationKind::Copy
+            && op.is_dir
+            && self.inactive_pane().entries.iter().any(|e| e.is_dir && e.name == op.name)
+    }
+
     /// Set a message with timestamp for auto-clear
     pub fn set_message(&mut self, msg: impl Into<String>) {
         self.message = msg.into();
         self.message_time = Some(std::time::Instant::now());
     }
 
+    /// Background work whose progress, spinner or result shows on screen
+    pub fn is_busy(&self) -> bool {
+        self.background_task.is_some()
+            || self.delete_task.is_some()
+            || self.operation_queue.is_active()
+            || self.progress.is_some()
+            || self.editor.saving
+            || self.sync_task.is_some()
+            || self.cleaner_scan_rx.is_some()
+            || self.cleaner_clean_rx.is_some()
+            || self.recent_rx.is_some()
+            || self.dir_size_rx.is_some()
+            || self.hash_check_rx.is_some()
+            || self.object_details_rx.is_some()
+            || self.info_line_rx.is_some()
+            || self.copy_plan_rx.is_some()
+            || self.delete_impact_rx.is_some()
+            || self.verify_rx.is_some()
+            || self.left_pane.pending.is_some()
+            || self.right_pane.pending.is_some()
+    }
+
+    /// Whether the screen must be redrawn, resetting the flag: after input or a message
+    /// change, and on every tick while background work runs (plus one more for its result)
+    pub fn take_redraw(&mut self) -> bool {
+        let busy = self.is_busy();
+        let redraw = self.needs_redraw || busy || self.was_busy || self.message != self.drawn_message;
+        if redraw {
+            self.drawn_message.clone_from(&self.message);
+        }
+        self.needs_redraw = false;
+        self.was_busy = busy;
+        redraw
+    }
+
     /// Clear message if it's been shown for more than 7 seconds
     pub fn clear_expired_message(&mut self) {
         if let Some(time) = self.message_time {
@@ -515,33 +1851,356 @@ impl App {
         self.message.clone()
     }
 
+    /// The path a running background task uses that deleting `targets` would pull out
+    /// from under it
+    pub fn delete_conflict(&self, targets: &[DeleteTarget]) -> Option<&str> {
+        targets.iter().find_map(|target| {
+            let target = target.backend.display_path(&target.path);
+            self.background_paths.iter().find(|busy| paths_overlap(busy, &target)).map(String::as_str)
+        })
+    }
+
+    /// Lines of `view_content` on screen in the streaming viewer; with wrapping on, long
+    /// lines take several rows, so fewer than `view_visible_height` may fit
+    pub fn visible_view_lines(&self) -> std::ops::Range<usize> {
+        let start = self.view_scroll.min(self.view_content.len());
+        let height = self.view_visible_height.max(1);
+        if !self.view_wrap || self.view_text_width == 0 {
+            return start..(start + height).min(self.view_content.len());
+        }
+
+        let mut rows = 0;
+        let mut end = start;
+        for (index, line) in self.view_content.iter().enumerate().skip(start) {
+            // Text lines start with a line number column, hex rows carry their own offset
+            let number = if self.view_hex {
+                0
+            } else {
+                (self.view_line_base.unwrap_or(0) + index + 1).to_string().len().max(4) + 1
+            };
+            rows += (number + line.chars().count()).div_ceil(self.view_text_width).max(1);
+            // A line cut off at the bottom isn't on screen, unless it is the only one
+            if rows > height && end > start {
+                break;
+            }
+            end += 1;
+            if rows >= height {
+                break;
+            }
+        }
+        start..end
+    }
+
+    /// Poll the background delete for completion (non-blocking)
+    async fn poll_delete_task(&mut self) {
+        if !self.delete_task.as_ref().is_some_and(|handle| handle.is_finished()) {
+            return;
+        }
+        let Some(handle) = self.delete_task.take() else {
+            return;
+        };
+        self.delete_progress = None;
+        if self.progress.as_ref().is_some_and(|progress| progress.stage == ProgressStage::Deleting) {
+            self.progress = None;
+        }
+        self.message = match handle.await {
+            Ok(Ok(msg)) => msg,
+            Ok(Err(e)) => format!("✗ Error: {}", e),
+            Err(e) => format!("✗ Task failed: {}", e),
+        };
+        // Refreshing drops marks on deleted entries
+        let _ = self.refresh_both_panes().await;
+    }
+
     /// Poll background task for completion (non-blocking)
     pub async fn poll_background_task(&mut self) {
+        self.poll_delete_task().await;
+
         if let Some(ref mut handle) = self.background_task {
             // Check if task is finished without blocking
             if handle.is_finished() {
                 // Take ownership of the handle
                 if let Some(handle) = self.background_task.take() {
-                    match handle.await {
+                    // Finishing an editor save unblocks the editor
+                    let was_saving = std::mem::take(&mut self.editor.saving);
+                    self.save_written = None;
+                    self.background_paths.clear();
+
+                    let result = match handle.await {
                         Ok(Ok(msg)) => {
+                            if was_saving {
+                                self.editor.modified = false;
+                                self.record_editor_version().await;
+                            }
                             self.message = msg;
                             self.progress = None;
-                            // Refresh both panes to show new files
-                            let _ = self.refresh_pane(ActivePane::Left).await;
-                            let _ = self.refresh_pane(ActivePane::Right).await;
+                            Ok(())
+                        }
+                        Ok(Err(e)) if e.downcast_ref::<crate::fs::copy::CopyCancelled>().is_some() => {
+                            let (name, kind) = self.operation_queue.current.as_ref()
+                                .map_or((String::new(), OperationKind::Copy), |op| (op.name.clone(), op.kind));
+                            self.message = if kind == OperationKind::Download {
+                                format!("⏹ Cancelled downloading {}; saving it again continues where it stopped", name)
+                            } else {
+                                format!("⏹ Cancelled copying {}; files already copied were kept", name)
+                            };
+                            self.progress = None;
+                            Err(format!("Copy of {} cancelled", name))
                         }
                         Ok(Err(e)) => {
                             self.message = format!("✗ Error: {}", e);
                             self.progress = None;
+                            Err(e.to_string())
                         }
                         Err(e) => {
                             self.message = format!("✗ Task failed: {}", e);
                             self.progress = None;
+                            Err(e.to_string())
+                        }
+                    };
+
+                    // Whatever the outcome files may have changed (a failed copy can leave part of a tree)
+                    let _ = self.refresh_both_panes().await;
+
+                    let was_queued = self.operation_queue.current.is_some();
+                    // A cancelled copy was stopped on purpose, so it isn't offered for retry
+                    let was_cancelled = self.operation_queue.copy_cancelled.as_ref()
+                        .is_some_and(|cancelled| cancelled.load(std::sync::atomic::Ordering::Relaxed));
+                    if let (Err(error), Some(op), false) = (&result, self.operation_queue.current.clone(), was_cancelled) {
+                        let bytes_transferred = op.bytes_at_destination().await;
+                        self.operation_queue.failed_transfers.push(FailedTransfer {
+                            op,
+                            bytes_transferred,
+                            error: error.clone(),
+                        });
+                    }
+                    if let Some(ref op) = self.operation_queue.current {
+                        let bytes = self.operation_queue.transferred.as_ref()
+                            .map_or(0, |bytes| bytes.load(std::sync::atomic::Ordering::Relaxed));
+                        self.transfer_totals.record(&op.src_storage.backend_type(), &op.dest_storage.backend_type(), bytes);
+                    }
+                    self.operation_queue.finish_current(result);
+                    if !self.operation_queue.is_active() {
+                        let was_batch = self.operation_queue.finished > 1;
+                        let summary = self.operation_queue.finish_batch();
+                        if was_queued && was_batch {
+                            self.message = summary;
+                        }
+                        if !self.operation_queue.failed_transfers.is_empty() {
+                            // Only interrupt the file panes; elsewhere the error message has to do
+                            if matches!(self.mode, AppMode::Normal) {
+                                self.mode = AppMode::ConfirmRetry;
+                            } else {
+                                self.operation_queue.failed_transfers.clear();
+                            }
                         }
                     }
                 }
             }
         }
+
+        // Queued operations wait for the slot to be free
+        if self.background_task.is_none() {
+            self.start_next_operation();
+        }
+    }
+
+    /// Queue a copy or move; it starts right away when nothing else is running.
+    pub fn enqueue_operation(&mut self, op: QueuedOperation) {
+        let name = op.name.clone();
+        let kind = op.kind;
+        self.operation_queue.push(op);
+
+        if self.background_task.is_none() {
+            self.start_next_operation();
+        } else {
+            self.message = format!(
+                "⏳ Queued {} {} ({} waiting, Esc to cancel queue)",
+                kind.verb().to_lowercase(),
+                name,
+                self.operation_queue.pending_len()
+            );
+        }
+    }
+
+    /// Retry the failed transfers offered in `AppMode::ConfirmRetry`
+    pub fn retry_failed_transfers(&mut self) {
+        self.mode = AppMode::Normal;
+        let count = self.operation_queue.retry_failed();
+        if count == 0 {
+            return;
+        }
+
+        if self.background_task.is_none() {
+            self.start_next_operation();
+        } else {
+            self.message = format!("⏳ Queued {} retries", count);
+        }
+    }
+
+    /// Spawn the next queued operation into the background task slot
+    fn start_next_operation(&mut self) {
+        let Some(op) = self.operation_queue.start_next() else {
+            return;
+        };
+
+        let position = self.operation_queue.finished + 1;
+        let total = self.operation_queue.total;
+        let (progress_tx, progress_rx) = tokio::sync::mpsc::channel(100);
+        let task_op = op.clone();
+        let cancelled = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let task_cancelled = Arc::clone(&cancelled);
+        let transferred = Arc::new(std::sync::atomic::AtomicU64::new(0));
+        let task_transferred = Arc::clone(&transferred);
+        let events = crate::event_log::OperationEvents::start(
+            self.event_log.clone(),
+            match op.kind {
+                OperationKind::Copy | OperationKind::Download => crate::event_log::Operation::Copy,
+                OperationKind::Move => crate::event_log::Operation::Move,
+            },
+            Some(op.src_storage.display_path(&op.src_path)),
+            op.dest_storage.display_path(&op.dest_path),
+            false,
+        );
+        self.background_paths = vec![op.src_storage.display_path(&op.src_path), op.dest_storage.display_path(&op.dest_path)];
+
+        self.background_task = Some(tokio::spawn(async move {
+            let mut event_stats = crate::event_log::OperationStats {
+                files: usize::from(!task_op.is_dir),
+                directories: usize::from(task_op.is_dir),
+                ..Default::default()
+            };
+            // A failed transfer only reports what it added to the destination
+            let bytes_before = task_op.bytes_at_destination().await;
+            let result: Result<String> = async {
+                // Downloads continue their partial file, so a retry is just another run
+                if task_op.resume && task_op.kind != OperationKind::Download {
+                    let kept = crate::fs::copy::resume_between_backends(
+                        &*task_op.src_storage,
+                        &task_op.src_path,
+                        &*task_op.dest_storage,
+                        &task_op.dest_path,
+                        Some(progress_tx),
+                    ).await?;
+                    if task_op.kind == OperationKind::Move {
+                        task_op.src_storage.delete(&task_op.src_path).await?;
+                    }
+                    task_transferred.store(task_op.size.saturating_sub(kept), std::sync::atomic::Ordering::Relaxed);
+                    return Ok(if kept > 0 {
+                        format!(
+                            "✓ {} {} successfully (resumed, {} already transferred)",
+                            task_op.kind.past(),
+                            task_op.name,
+                            crate::fs::types::format_bytes(kept)
+                        )
+                    } else {
+                        format!("✓ {} {} successfully", task_op.kind.past(), task_op.name)
+                    });
+                }
+
+                match task_op.kind {
+                    OperationKind::Copy => {
+                        let stats = crate::fs::copy::copy_between_backends_with_mode(
+                            &*task_op.src_storage,
+                            &task_op.src_path,
+                            &*task_op.dest_storage,
+                            &task_op.dest_path,
+                            Some(progress_tx),
+                            task_op.merge,
+                            &task_cancelled,
+                        ).await?;
+                        task_transferred.store(stats.bytes, std::sync::atomic::Ordering::Relaxed);
+                        event_stats.files = stats.files;
+                        event_stats.skipped = stats.skipped;
+                        event_stats.throughput_mbps = stats.throughput_mbps();
+                        return Ok(format!(
+                            "✓ {} {} successfully: {}",
+                            task_op.kind.past(),
+                            task_op.name,
+                            stats.summary()
+                        ));
+                    }
+                    OperationKind::Download => {
+                        let stats = crate::fs::copy::download_file(
+                            &*task_op.src_storage,
+                            &task_op.src_path,
+                            std::path::Path::new(&task_op.dest_path),
+                            Some(&progress_tx),
+                            &task_cancelled,
+                        ).await?;
+                        task_transferred.store(stats.bytes, std::sync::atomic::Ordering::Relaxed);
+                        event_stats.throughput_mbps = stats.throughput_mbps();
+                        let kept = task_op.size.saturating_sub(stats.bytes);
+                        let resumed = if kept > 0 {
+                            format!(" (resumed, {} already downloaded)", crate::fs::types::format_bytes(kept))
+                        } else {
+                            String::new()
+                        };
+                        return Ok(format!(
+                            "✓ Downloaded {} to {}: {}{}",
+                            task_op.name,
+                            task_op.dest_path,
+                            stats.summary(),
+                            resumed
+                        ));
+                    }
+                    OperationKind::Move => {
+                        crate::fs::copy::move_between_backends(
+                            task_op.src_storage.as_ref(),
+                            &task_op.src_path,
+                            task_op.dest_storage.as_ref(),
+                            &task_op.dest_path,
+                        ).await?;
+                        // A local move is a rename; anything else copies the data across
+                        let local = matches!(task_op.src_storage.backend_type(), crate::fs::BackendType::Local)
+                            && matches!(task_op.dest_storage.backend_type(), crate::fs::BackendType::Local);
+                        if !local {
+                            task_transferred.store(task_op.size, std::sync::atomic::Ordering::Relaxed);
+                        }
+                    }
+                }
+
+                Ok(format!("✓ {} {} successfully", task_op.kind.past(), task_op.name))
+            }.await;
+
+            match result {
+                Ok(_) => {
+                    event_stats.bytes = task_transferred.load(std::sync::atomic::Ordering::Relaxed);
+                    events.completed(event_stats);
+                }
+                Err(ref e) => {
+                    let added = task_op.bytes_at_destination().await.saturating_sub(bytes_before);
+                    task_transferred.store(added, std::sync::atomic::Ordering::Relaxed);
+                    events.failed(e);
+                }
+            }
+            result
+        }));
+        self.operation_queue.copy_rx = Some(progress_rx);
+        self.operation_queue.transferred = Some(transferred);
+        let cancellable = match op.kind {
+            OperationKind::Copy => !op.resume,
+            OperationKind::Move => false,
+            OperationKind::Download => true,
+        };
+        self.operation_queue.copy_cancelled = cancellable.then_some(cancelled);
+
+        let verb = if op.resume { "Retrying" } else { op.kind.verb() };
+        let hint = if cancellable { " (Esc to cancel)" } else { "" };
+        self.message = if total > 1 {
+            format!("{} {} ({}/{})...{}", verb, op.name, position, total, hint)
+        } else {
+            format!("{} {}...{}", verb, op.name, hint)
+        };
+        self.progress = Some(Progress {
+            stage: ProgressStage::Transferring,
+            current: 0,
+            total: op.size,
+            current_file: op.name,
+            files_done: 0,
+            total_files: if op.is_dir { 0 } else { 1 },
+            batch: (total > 1).then_some((position, total)),
+        });
     }
 
     pub async fn navigate_into(&mut self) -> Result<()> {
@@ -628,5 +2287,557 @@ impl App {
         
         Ok(())
     }
+
+    /// Point the inactive pane at the directory matching the active pane's position
+    /// in a similarly structured tree (see `corresponding_path`)
+    pub async fn jump_to_corresponding(&mut self) -> Result<()> {
+        let (active, other, other_side) = match self.active_pane {
+            ActivePane::Left => (&self.left_pane, &self.right_pane, ActivePane::Right),
+            ActivePane::Right => (&self.right_pane, &self.left_pane, ActivePane::Left),
+        };
+
+        if matches!(other.storage.backend_type(), crate::fs::BackendType::Selecting) {
+            self.message = "Other pane has no storage selected".to_string();
+            return Ok(());
+        }
+
+        let Some(target) = corresponding_path(&active.path, &other.path) else {
+            self.message = format!(
+                "No common structure between {} and {}",
+                active.storage.display_path(&active.path),
+                other.storage.display_path(&other.path)
+            );
+            return Ok(());
+        };
+
+        if target == other.path {
+            self.message = "Other pane is already at the corresponding directory".to_string();
+            return Ok(());
+        }
+
+        if !other.storage.is_dir(&target).await.unwrap_or(false) {
+            self.message = format!("No corresponding directory: {}", other.storage.display_path(&target));
+            return Ok(());
+        }
+
+        let selected_name = active.selected_entry().map(|e| e.name.clone());
+        let display = other.storage.display_path(&target);
+
+        let other = match other_side {
+            ActivePane::Left => &mut self.left_pane,
+            ActivePane::Right => &mut self.right_pane,
+        };
+        other.path = target;
+        self.refresh_pane(other_side).await?;
+
+        // Keep the cursor on the same name in both panes where possible
+        let other = match other_side {
+            ActivePane::Left => &mut self.left_pane,
+            ActivePane::Right => &mut self.right_pane,
+        };
+        if let Some(idx) = selected_name.and_then(|name| other.entries.iter().position(|e| e.name == name)) {
+            other.state.select(Some(idx));
+        }
+
+        self.message = format!("⇉ Other pane: {}", display);
+        Ok(())
+    }
+}
+
+/// Directory in `other`'s tree that corresponds to `active`, for browsing two similar trees.
+///
+/// The longest tail of `other` that also occurs in `active` anchors the trees, so with
+/// `active` = `/a/b/c` and `other` = `/backup/b` the result is `/backup/b/c`. Without a
+/// shared tail, sibling roots line up: `/data/v1/src` against `/data/v2` gives `/data/v2/src`.
+pub fn corresponding_path(active: &str, other: &str) -> Option<String> {
+    let active_parts: Vec<&str> = active.split('/').filter(|c| !c.is_empty()).collect();
+    let other_parts: Vec<&str> = other.split('/').filter(|c| !c.is_empty()).collect();
+
+    let join = |rest: &[&str]| {
+        let mut path = other.trim_end_matches('/').to_string();
+        for part in rest {
+            if !path.is_empty() || other.starts_with('/') {
+                path.push('/');
+            }
+            path.push_str(part);
+        }
+        if path.is_empty() && other.starts_with('/') {
+            path.push('/');
+        }
+        path
+    };
+
+    // Longest tail of `other` found in `active`, anchored at its first occurrence
+    for k in (1..=other_parts.len()).rev() {
+        let tail = &other_parts[other_parts.len() - k..];
+        if let Some(start) = active_parts.windows(k).position(|w| w == tail) {
+            return Some(join(&active_parts[start + k..]));
+        }
+    }
+
+    // Sibling roots: `other` is a direct child of one of `active`'s ancestors
+    let (_, other_parent) = other_parts.split_last()?;
+    if active_parts.len() >= other_parts.len() && active_parts.starts_with(other_parent) {
+        return Some(join(&active_parts[other_parts.len()..]));
+    }
+
+    None
+}
+
+/// Whether one path is the other or lies below it
+fn paths_overlap(a: &str, b: &str) -> bool {
+    let (a, b) = (a.trim_end_matches('/'), b.trim_end_matches('/'));
+    let below = |path: &str, dir: &str| dir.is_empty() || path.strip_prefix(dir).is_some_and(|rest| rest.starts_with('/'));
+    a == b || below(a, b) || below(b, a)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::{
+        align_view_chunk, corresponding_path, download_target, next_chunk_offset, prev_chunk_offset, verify_entries, DeleteImpact,
+        DeleteTarget, DryRunLog, FailedTransfer, Location, NavHistory, OperationKind, OperationQueue, Preview, QueuedOperation, TransferTotals,
+        format_info_line, FileVersion, InfoField, TextEditor, VerifyEntryKind, ViewSelection, DEFAULT_EDITOR_MAX_SIZE,
+        DRY_RUN_LOG_LIMIT, HISTORY_LIMIT, PREVIEW_DEBOUNCE,
+    };
+    use std::sync::Arc;
+
+    fn queued(name: &str) -> QueuedOperation {
+        let backend: Arc<dyn crate::fs::StorageBackend> =
+            Arc::new(crate::fs::local::LocalBackend::new(std::env::temp_dir()));
+        QueuedOperation {
+            kind: OperationKind::Copy,
+            name: name.to_string(),
+            size: 0,
+            is_dir: false,
+            src_storage: backend.clone(),
+            src_path: format!("/src/{}", name),
+            dest_storage: backend,
+            dest_path: format!("/dst/{}", name),
+            resume: false,
+            merge: Default::default(),
+        }
+    }
+
+    fn location(path: &str) -> Location {
+        Location { storage: Arc::new(crate::fs::local::LocalBackend::new(std::env::temp_dir())), path: path.to_string() }
+    }
+
+    fn path(location: Option<Location>) -> Option<String> {
+        location.map(|l| l.path)
+    }
+
+    #[test]
+    fn test_view_chunk_offsets() {
+        for chunk in [64 * 1024, 1024 * 1024, 256 * 1000 / 16 * 16] {
+            let total = 3 * chunk + 10;
+            // Paging forward visits every chunk and stops at the last one
+            let mut offsets = vec![0];
+            while let Some(next) = next_chunk_offset(*offsets.last().unwrap(), chunk, total) {
+                offsets.push(next);
+            }
+            assert_eq!(offsets, vec![0, chunk, 2 * chunk, 3 * chunk]);
+            // ... and paging back retraces them
+            let mut back = vec![3 * chunk];
+            while let Some(prev) = prev_chunk_offset(*back.last().unwrap(), chunk) {
+                back.push(prev);
+            }
+            assert_eq!(back, vec![3 * chunk, 2 * chunk, chunk, 0]);
+        }
+
+        // A file that fits in one chunk has no neighbours
+        assert_eq!(next_chunk_offset(0, 1024 * 1024, 1024 * 1024), None);
+        // Unaligned offsets (after go-to-line) can still page back to the start
+        assert_eq!(prev_chunk_offset(1000, 64 * 1024), Some(0));
+    }
+
+    #[test]
+    fn test_preview_waits_for_selection_to_settle() {
+        let mut preview = Preview::default();
+        let start = std::time::Instant::now();
+        let later = |ms: u64| start + std::time::Duration::from_millis(ms);
+
+        // Passing over a file starts the timer; moving on restarts it
+        assert_eq!(preview.settle("/a", start), None);
+        assert_eq!(preview.settle("/b", later(100)), None);
+        assert_eq!(preview.settle("/b", later(100) + PREVIEW_DEBOUNCE / 2), None);
+        assert_eq!(preview.settle("/b", later(100) + PREVIEW_DEBOUNCE).as_deref(), Some("/b"));
+        // Already shown: nothing more to read
+        assert_eq!(preview.settle("/b", later(1000)), None);
+
+        preview.show_data(b"fn main() {}\nmore\n");
+        assert!(!preview.hex);
+        assert_eq!(preview.lines, vec!["fn main() {}", "more"]);
+        preview.show_data(&[0, 1, 2, 3]);
+        assert!(preview.hex);
+        assert_eq!(preview.lines.len(), 1);
+
+        preview.show_note("/dir", "Directory");
+        assert_eq!(preview.settle("/dir", later(2000)), None);
+        assert!(preview.lines.is_empty());
+    }
+
+    #[test]
+    fn test_transfer_totals_split_cloud_traffic() {
+        use crate::fs::BackendType;
+        let s3 = BackendType::S3 {
+            bucket: "b".to_string(),
+            region: "r".to_string(),
+            provider: crate::fs::backend::S3Provider::Aws,
+        };
+        let gcs = BackendType::Gcs { bucket: "g".to_string() };
+        let k8s = BackendType::Kubernetes { namespace: "ns".to_string(), pvc: "data".to_string() };
+
+        let mut totals = TransferTotals::default();
+        assert_eq!(totals.indicator(), "");
+        totals.record(&BackendType::Local, &k8s, 100);
+        assert_eq!(totals, TransferTotals { total: 100, cloud_upload: 0, cloud_download: 0 });
+        assert_eq!(totals.indicator(), "⇅ 100 B");
+
+        totals.record(&BackendType::Local, &s3, 10);
+        totals.record(&gcs, &BackendType::Local, 20);
+        totals.record(&s3, &gcs, 5);
+        assert_eq!(totals, TransferTotals { total: 135, cloud_upload: 15, cloud_download: 25 });
+        assert_eq!(totals.indicator(), "⇅ 135 B (☁ ↑15 B ↓25 B)");
+    }
+
+    #[tokio::test]
+    async fn test_bytes_at_destination_of_a_failed_transfer() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = |name: &str| dir.path().join(name).to_string_lossy().into_owned();
+        std::fs::create_dir_all(dir.path().join("tree/sub")).unwrap();
+        std::fs::write(dir.path().join("tree/a.txt"), b"12345").unwrap();
+        std::fs::write(dir.path().join("tree/sub/b.txt"), b"123").unwrap();
+        std::fs::write(dir.path().join("big.iso.part"), b"1234567").unwrap();
+
+        let copy = QueuedOperation { dest_path: dest("tree"), ..queued("tree") };
+        assert_eq!(copy.bytes_at_destination().await, 8);
+        // A download counts its partial file, not the finished one that isn't there yet
+        let download = QueuedOperation { kind: OperationKind::Download, dest_path: dest("big.iso"), ..queued("big.iso") };
+        assert_eq!(download.bytes_at_destination().await, 7);
+        let missing = QueuedOperation { dest_path: dest("missing"), ..queued("missing") };
+        assert_eq!(missing.bytes_at_destination().await, 0);
+    }
+
+    #[test]
+    fn test_download_target() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("saved")).unwrap();
+        let base = dir.path();
+
+        assert_eq!(download_target("  ", "a.log", base), None);
+        assert_eq!(download_target("copy.log", "a.log", base), Some(base.join("copy.log")));
+        // Existing directories and a trailing slash both mean "into this directory"
+        assert_eq!(download_target("saved", "a.log", base), Some(base.join("saved/a.log")));
+        assert_eq!(download_target("new/", "a.log", base), Some(base.join("new/a.log")));
+        assert_eq!(download_target("/tmp/x.log", "a.log", base), Some(std::path::PathBuf::from("/tmp/x.log")));
+        if let Some(home) = std::env::var_os("HOME") {
+            assert_eq!(download_target("~/x.log", "a.log", base), Some(std::path::PathBuf::from(home).join("x.log")));
+        }
+    }
+
+    #[test]
+    fn test_view_chunk_size_alignment() {
+        assert_eq!(align_view_chunk(1024 * 1024), 1024 * 1024);
+        assert_eq!(align_view_chunk(100_010), 100_000);
+        assert_eq!(align_view_chunk(1), super::MIN_VIEW_CHUNK_SIZE);
+    }
+
+    #[test]
+    fn test_nav_history_back_and_forward() {
+        let mut history = NavHistory::default();
+        for p in ["/a", "/a/b", "/a/b", "/c"] {
+            history.visit(location(p));
+        }
+        assert_eq!(path(history.back()).as_deref(), Some("/a/b"));
+        assert_eq!(path(history.back()).as_deref(), Some("/a"));
+        assert!(history.back().is_none());
+        assert_eq!(path(history.forward()).as_deref(), Some("/a/b"));
+
+        // Revisiting the location just restored is not a new step
+        history.visit(location("/a/b"));
+        assert_eq!(path(history.forward()).as_deref(), Some("/c"));
+
+        // Going somewhere new after going back drops the forward entries
+        history.back();
+        history.visit(location("/d"));
+        assert!(history.forward().is_none());
+        assert_eq!(path(history.back()).as_deref(), Some("/a/b"));
+    }
+
+    #[test]
+    fn test_nav_history_is_capped() {
+        let mut history = NavHistory::default();
+        for i in 0..HISTORY_LIMIT + 10 {
+            history.visit(location(&format!("/{}", i)));
+        }
+        let mut steps = 0;
+        while history.back().is_some() {
+            steps += 1;
+        }
+        assert_eq!(steps, HISTORY_LIMIT);
+    }
+
+    #[test]
+    fn test_operation_queue_runs_in_order_and_summarizes() {
+        let mut queue = OperationQueue::default();
+        for name in ["a", "b", "c"] {
+            queue.push(queued(name));
+        }
+
+        assert_eq!(queue.start_next().map(|op| op.name).as_deref(), Some("a"));
+        queue.finish_current(Ok(()));
+        assert_eq!(queue.start_next().map(|op| op.name).as_deref(), Some("b"));
+        queue.finish_current(Err("disk full".to_string()));
+        assert_eq!(queue.start_next().map(|op| op.name).as_deref(), Some("c"));
+        queue.finish_current(Ok(()));
+
+        assert!(!queue.is_active());
+        assert_eq!(queue.finish_batch(), "⚠ 2/3 operations finished, 1 failed: disk full");
+        assert_eq!(queue.total, 0);
+        assert_eq!(queue.finished, 0);
+    }
+
+    #[test]
+    fn test_operation_queue_cancel_keeps_running_operation() {
+        let mut queue = OperationQueue::default();
+        for name in ["a", "b", "c"] {
+            queue.push(queued(name));
+        }
+        queue.start_next();
+
+        assert_eq!(queue.cancel_pending(), 2);
+        assert_eq!(queue.total, 1);
+        assert!(queue.is_active());
+
+        queue.finish_current(Ok(()));
+        assert!(queue.start_next().is_none());
+        assert_eq!(queue.finish_batch(), "✓ 1 operations finished");
+    }
+
+    #[test]
+    fn test_retry_failed_requeues_as_resume() {
+        let mut queue = OperationQueue::default();
+        queue.failed_transfers.push(FailedTransfer {
+            op: queued("big.iso"),
+            bytes_transferred: 4096,
+            error: "connection reset".to_string(),
+        });
+        // LocalBackend appends, so the partial copy can be continued
+        assert!(queue.failed_transfers[0].resumable());
+
+        assert_eq!(queue.retry_failed(), 1);
+        assert!(queue.failed_transfers.is_empty());
+        assert_eq!(queue.total, 1);
+        let op = queue.start_next().unwrap();
+        assert_eq!(op.name, "big.iso");
+        assert!(op.resume);
+    }
+
+    #[test]
+    fn test_corresponding_path_shared_tail() {
+        assert_eq!(corresponding_path("/a/b/c", "/backup/b").as_deref(), Some("/backup/b/c"));
+        assert_eq!(corresponding_path("/home/me/proj/src/ui", "/mnt/old/proj").as_deref(), Some("/mnt/old/proj/src/ui"));
+        // Already at the same relative position
+        assert_eq!(corresponding_path("/x/proj/src", "/y/proj/src").as_deref(), Some("/y/proj/src"));
+        // Remote paths without a leading slash (bucket prefixes)
+        assert_eq!(corresponding_path("/srv/site/assets/img", "site/").as_deref(), Some("site/assets/img"));
+    }
+
+    #[test]
+    fn test_corresponding_path_sibling_roots() {
+        assert_eq!(corresponding_path("/data/v1/src/lib", "/data/v2").as_deref(), Some("/data/v2/src/lib"));
+        assert_eq!(corresponding_path("/data/v1", "/data/v2").as_deref(), Some("/data/v2"));
+    }
+
+    #[test]
+    fn test_corresponding_path_unrelated() {
+        assert_eq!(corresponding_path("/a/b/c", "/x/y"), None);
+        assert_eq!(corresponding_path("/a/b", "/"), None);
+    }
+
+    #[test]
+    fn test_verify_entries_grouped_and_sorted() {
+        use std::path::PathBuf;
+        let report = crate::hash::VerifyReport {
+            matches: 4,
+            mismatches: vec![crate::hash::Mismatch {
+                path: PathBuf::from("/t/b.txt"),
+                expected: "00".to_string(),
+                actual: "11".to_string(),
+            }],
+            missing_files: vec![PathBuf::from("/t/z.txt"), PathBuf::from("/t/a.txt")],
+            new_files: vec![PathBuf::from("/t/new.txt")],
+        };
+        let rows: Vec<(VerifyEntryKind, PathBuf)> =
+            verify_entries(&report).into_iter().map(|e| (e.kind, e.path)).collect();
+        assert_eq!(
+            rows,
+            vec![
+                (VerifyEntryKind::Changed, PathBuf::from("/t/b.txt")),
+                (VerifyEntryKind::Missing, PathBuf::from("/t/a.txt")),
+                (VerifyEntryKind::Missing, PathBuf::from("/t/z.txt")),
+                (VerifyEntryKind::New, PathBuf::from("/t/new.txt")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_impact_counts_directories_recursively() {
+        use std::sync::atomic::AtomicBool;
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("build/cache/deep")).unwrap();
+        std::fs::write(dir.path().join("build/a.o"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("build/cache/deep/b.o"), vec![0u8; 50]).unwrap();
+        std::fs::create_dir(dir.path().join("build/.git")).unwrap();
+        std::fs::write(dir.path().join("build/.git/HEAD"), vec![0u8; 8]).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), b"hi").unwrap();
+
+        let backend: Arc<dyn crate::fs::StorageBackend> =
+            Arc::new(crate::fs::local::LocalBackend::new(dir.path().to_path_buf()));
+        let target = |name: &str, is_dir: bool, size: u64| DeleteTarget {
+            backend: backend.clone(),
+            path: dir.path().join(name).to_string_lossy().into_owned(),
+            display_path: name.to_string(),
+            is_dir,
+            size,
+        };
+        let targets = vec![target("build", true, 0), target("notes.txt", false, 2)];
+
+        let impact = DeleteImpact::measure(&targets, &AtomicBool::new(false)).await.unwrap().unwrap();
+        // The hidden .git directory and its file count too
+        assert_eq!(impact, DeleteImpact { files: 4, directories: 4, bytes: 160 });
+        assert_eq!(impact.summary(), format!("4 files in 4 directories, {}", crate::fs::types::format_bytes(160)));
+
+        // Dismissing the popup stops the count
+        assert!(DeleteImpact::measure(&targets, &AtomicBool::new(true)).await.is_none());
+        // A directory that vanished reports why instead of a count
+        assert!(DeleteImpact::measure(&[target("gone", true, 0)], &AtomicBool::new(false)).await.unwrap().is_err());
+    }
+
+    #[test]
+    fn test_view_selection_extends_both_ways() {
+        let lines: Vec<String> = (1..=5).map(|i| format!("line {}", i)).collect();
+        let mut selection = ViewSelection::new(2);
+        assert_eq!(selection.text(&lines), "line 3");
+
+        selection.move_cursor(1, lines.len());
+        selection.move_cursor(5, lines.len());
+        assert_eq!(selection.range(), (2, 4));
+        assert_eq!(selection.text(&lines), "line 3\nline 4\nline 5");
+
+        // Moving above the anchor selects upwards, clamped at the first line
+        selection.move_cursor(-10, lines.len());
+        assert_eq!(selection.range(), (0, 2));
+        assert!(selection.contains(1) && !selection.contains(3));
+        assert_eq!(selection.text(&lines), "line 1\nline 2\nline 3");
+    }
+
+    #[tokio::test]
+    async fn test_file_version_sees_outside_writes() {
+        use crate::fs::StorageBackend;
+
+        let dir = tempfile::tempdir().unwrap();
+        let backend = crate::fs::local::LocalBackend::new(dir.path().to_path_buf());
+        // A dotfile, which directory listings leave out
+        let path = dir.path().join(".notes");
+        std::fs::write(&path, b"draft").unwrap();
+        let path = path.to_string_lossy().into_owned();
+        let version = || async { FileVersion::from(&backend.stat(&path).await.unwrap()) };
+
+        let loaded = version().await;
+        assert_eq!(loaded.size, 5);
+        assert_eq!(version().await, loaded);
+
+        // Another program rewrites the file
+        std::fs::write(dir.path().join(".notes"), b"draft, revised").unwrap();
+        let current = version().await;
+        assert_ne!(current, loaded);
+        assert_eq!(current.size, 14);
+    }
+
+    #[test]
+    fn test_info_line_fields() {
+        let fields = InfoField::parse_list("name, size,SHA256,mtime").unwrap();
+        assert_eq!(fields[2], InfoField::Hash("sha256".to_string()));
+        assert_eq!(InfoField::parse_list("hash").unwrap(), vec![InfoField::Hash("blake3".to_string())]);
+        assert!(InfoField::parse_list("name,colour").unwrap_err().contains("colour"));
+        assert!(InfoField::parse_list(" , ").is_err());
+
+        let file = crate::fs::FileEntry {
+            name: "notes.txt".to_string(),
+            size: 2048,
+            is_dir: false,
+            modified: None,
+            permissions: Some("rw-r--r--".to_string()),
+        };
+        let hashes = vec![("sha256".to_string(), "ab12".to_string())];
+        assert_eq!(
+            format_info_line(&file, "/t/notes.txt", &fields, &hashes),
+            "notes.txt  2 KiB (2048 bytes)  sha256:ab12"
+        );
+
+        // Directories have no size or hash; fields are printed in the order given
+        let dir = crate::fs::FileEntry { name: "src".to_string(), is_dir: true, ..file };
+        let fields = InfoField::parse_list("path,permissions,size,hash,name").unwrap();
+        assert_eq!(format_info_line(&dir, "/t/src", &fields, &[]), "/t/src  rw-r--r--  src/");
+    }
+
+    #[test]
+    fn test_dry_run_log_formats_entries_and_drops_the_oldest() {
+        use crate::cleaner::DeleteEvent;
+        use std::path::PathBuf;
+
+        let mut log = DryRunLog::default();
+        log.would_delete("/t/build", true, None);
+        log.would_delete("/t/a.txt", false, Some(2048));
+        log.record(&DeleteEvent::Removed { path: PathBuf::from("/t/node_modules"), is_dir: true, bytes: 10 });
+        log.record(&DeleteEvent::Skipped { path: PathBuf::from("/t/fifo") });
+        assert_eq!(
+            log.lines().collect::<Vec<_>>(),
+            vec![
+                "Would delete directory /t/build",
+                "Would delete file /t/a.txt (2.0 KB)",
+                "Would delete directory /t/node_modules (10 B)",
+                "Would skip /t/fifo",
+            ]
+        );
+
+        for i in 0..DRY_RUN_LOG_LIMIT {
+            log.push(format!("line {}", i));
+        }
+        assert_eq!(log.len(), DRY_RUN_LOG_LIMIT);
+        assert_eq!(log.lines().next(), Some("line 0"));
+        assert_eq!(log.lines().last(), Some(format!("line {}", DRY_RUN_LOG_LIMIT - 1).as_str()));
+    }
+
+    #[test]
+    fn test_go_to_line_clamps_and_centers() {
+        let mut editor = TextEditor {
+            content: (1..=100).map(|i| format!("line {}", i)).collect(),
+            cursor_col: 3,
+            visible_height: 10,
+            ..TextEditor::new()
+        };
+        assert_eq!(editor.go_to_line(50), 50);
+        assert_eq!((editor.cursor_row, editor.cursor_col, editor.scroll_offset), (49, 0, 44));
+
+        assert_eq!(editor.go_to_line(0), 1);
+        assert_eq!((editor.cursor_row, editor.scroll_offset), (0, 0));
+        assert_eq!(editor.go_to_line(1000), 100);
+        assert_eq!((editor.cursor_row, editor.scroll_offset), (99, 94));
+
+        // Before the first render the height isn't known yet
+        editor.visible_height = 0;
+        editor.go_to_line(50);
+        assert_eq!(editor.scroll_offset, 39);
+    }
+
+    #[test]
+    fn test_editor_max_size_setting() {
+        assert_eq!(TextEditor::max_size_from(None), DEFAULT_EDITOR_MAX_SIZE);
+        assert_eq!(TextEditor::max_size_from(Some("256M")), 256 * 1024 * 1024);
+        assert_eq!(TextEditor::max_size_from(Some("4096")), 4096);
+        assert_eq!(TextEditor::max_size_from(Some("lots")), DEFAULT_EDITOR_MAX_SIZE);
+    }
+}
@@ -1,6 +1,7 @@
 //! Configuration loading with priority: env vars > config file > defaults
 
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::Path;
 
 /// Default directories to delete
@@ -43,12 +44,174 @@ pub const DEFAULT_FILES: &[&str] = &[
     "~",
 ];
 
+/// Default marker rules: a matched directory only counts as reclaimable when one of
+/// these files sits next to it. Applied only when marker checks are enabled.
+pub const DEFAULT_MARKERS: &[(&str, &[&str])] = &[
+    ("target", &["Cargo.toml"]),
+    ("node_modules", &["package.json"]),
+    (".next", &["package.json"]),
+    (".nuxt", &["package.json"]),
+    ("coverage", &["package.json"]),
+    ("dist", &["package.json", "setup.py", "pyproject.toml"]),
+    ("build", &["build.gradle", "build.gradle.kts", "setup.py", "pyproject.toml", "CMakeLists.txt", "package.json"]),
+    (".gradle", &["build.gradle", "build.gradle.kts", "settings.gradle", "settings.gradle.kts"]),
+    (".terraform", &["*.tf"]),
+];
+
+/// A named group of cleaner patterns for one language or tool
+#[derive(Debug, Clone, Copy)]
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub directories: &'static [&'static str],
+    pub files: &'static [&'static str],
+}
+
+/// Name of the preset that selects every other preset
+pub const PRESET_ALL: &str = "all";
+
+/// Built-in presets, selectable with `--preset`
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "rust",
+        description: "Cargo build output",
+        directories: &["target"],
+        files: &[],
+    },
+    Preset {
+        name: "node",
+        description: "npm/yarn/pnpm packages and JS framework caches",
+        directories: &["node_modules", ".next", ".nuxt", ".turbo", ".parcel-cache", "dist", "coverage"],
+        files: &[],
+    },
+    Preset {
+        name: "python",
+        description: "Bytecode, virtualenvs, tool caches and build output",
+        directories: &[
+            "__pycache__",
+            ".pytest_cache",
+            ".mypy_cache",
+            ".tox",
+            ".ruff_cache",
+            "venv",
+            ".venv",
+            ".eggs",
+            "*.egg-info",
+            "dist",
+            "build",
+            ".coverage",
+            "htmlcov",
+        ],
+        files: &[".pyc", ".pyo", ".pyd"],
+    },
+    Preset {
+        name: "terraform",
+        description: "Terraform provider and module cache",
+        directories: &[".terraform"],
+        files: &[],
+    },
+    Preset {
+        name: "java",
+        description: "Gradle caches and build output",
+        directories: &[".gradle", "build"],
+        files: &[],
+    },
+    Preset {
+        name: "os",
+        description: "OS metadata and generic caches",
+        directories: &[".cache"],
+        files: &[".DS_Store", "Thumbs.db", "desktop.ini"],
+    },
+    Preset {
+        name: "editor",
+        description: "Editor swap and backup files",
+        directories: &[],
+        files: &[".swp", ".swo", "~"],
+    },
+];
+
+/// Look up a preset by name (case-insensitive)
+pub fn find_preset(name: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+}
+
+/// Resolve preset names into merged (directories, files) pattern lists.
+///
+/// Patterns shared by several presets appear once, in first-seen order.
+/// `all` selects every preset. Unknown names are an error.
+pub fn resolve_presets<S: AsRef<str>>(names: &[S]) -> Result<(Vec<String>, Vec<String>), String> {
+    let mut selected: Vec<&Preset> = Vec::new();
+
+    for name in names {
+        let name = name.as_ref().trim();
+        if name.eq_ignore_ascii_case(PRESET_ALL) {
+            selected.extend(PRESETS.iter());
+        } else {
+            let preset = find_preset(name).ok_or_else(|| {
+                let valid: Vec<&str> = PRESETS.iter().map(|p| p.name).collect();
+                format!("Unknown preset '{}' (valid: {}, {})", name, valid.join(", "), PRESET_ALL)
+            })?;
+            selected.push(preset);
+        }
+    }
+
+    let mut directories: Vec<String> = Vec::new();
+    let mut files: Vec<String> = Vec::new();
+    for preset in selected {
+        for dir in preset.directories {
+            if !directories.iter().any(|d| d == dir) {
+                directories.push(dir.to_string());
+            }
+        }
+        for file in preset.files {
+            if !files.iter().any(|f| f == file) {
+                files.push(file.to_string());
+            }
+        }
+    }
+
+    Ok((directories, files))
+}
+
+/// Split a comma-separated pattern list, keeping commas inside `{a,b}` groups
+pub fn split_patterns(list: &str) -> Vec<String> {
+    let mut patterns = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    for c in list.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                patterns.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    patterns.push(current.trim().to_string());
+    patterns
+}
+
 /// Configuration file structure
 #[derive(Debug, Deserialize, Default)]
 pub struct ConfigFile {
     #[serde(default)]
     pub patterns: PatternsConfig,
     pub days: Option<u64>,
+    /// Only clean directories that have a marker sibling (see `[markers]`)
+    pub require_markers: Option<bool>,
+    /// Directory pattern -> sibling files, any one of which must exist
+    #[serde(default)]
+    pub markers: HashMap<String, Vec<String>>,
+    /// Shell command run after a CLI clean, with the stats on stdin as JSON
+    pub post_hook: Option<String>,
+    /// Match patterns regardless of case (`Target` matches `target`)
+    pub ignore_case: Option<bool>,
+    /// Don't descend into hidden directories that no pattern matches
+    pub skip_hidden: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -65,6 +228,19 @@ pub struct Config {
     pub directories: Vec<String>,
     pub files: Vec<String>,
     pub days: Option<u64>,
+    /// Check `markers` before treating a matched directory as reclaimable
+    pub require_markers: bool,
+    /// Directory pattern -> sibling files, any one of which must exist
+    pub markers: HashMap<String, Vec<String>>,
+    /// Shell command run after a CLI clean (see `hook::run_post_hook`)
+    pub post_hook: Option<String>,
+    /// When non-empty, only these of the configured patterns are matched (`--only`)
+    pub only: Vec<String>,
+    /// Match patterns regardless of case (default on macOS/Windows, see `ignore_case_default`)
+    pub ignore_case: bool,
+    /// Leave out dot-directories (and everything below them) unless a directory pattern
+    /// matches them, like `.terraform`. Hidden files are still matched where they are scanned.
+    pub skip_hidden: bool,
 }
 
 impl Config {
@@ -74,6 +250,14 @@ impl Config {
         let mut directories: Vec<String> = DEFAULT_DIRECTORIES.iter().map(|s| s.to_string()).collect();
         let mut files: Vec<String> = DEFAULT_FILES.iter().map(|s| s.to_string()).collect();
         let mut days = None;
+        let mut require_markers = false;
+        let mut post_hook = None;
+        let mut ignore_case = crate::hash::wildcard::ignore_case_default();
+        let mut skip_hidden = false;
+        let mut markers: HashMap<String, Vec<String>> = DEFAULT_MARKERS
+            .iter()
+            .map(|(dir, files)| (dir.to_string(), files.iter().map(|f| f.to_string()).collect()))
+            .collect();
 
         // Override with config file if provided
         if let Some(path) = config_path {
@@ -88,38 +272,104 @@ impl Config {
                     if config.days.is_some() {
                         days = config.days;
                     }
+                    if let Some(required) = config.require_markers {
+                        require_markers = required;
+                    }
+                    // Configured rules replace the default rule for the same pattern
+                    markers.extend(config.markers);
+                    if config.post_hook.is_some() {
+                        post_hook = config.post_hook;
+                    }
+                    if let Some(ignore) = config.ignore_case {
+                        ignore_case = ignore;
+                    }
+                    if let Some(skip) = config.skip_hidden {
+                        skip_hidden = skip;
+                    }
                 }
             }
         }
 
         // Override with environment variables (highest priority)
         if let Ok(env_dirs) = std::env::var("CLEANER_DIRS") {
-            directories = env_dirs.split(',').map(|s| s.trim().to_string()).collect();
+            directories = split_patterns(&env_dirs);
         }
         if let Ok(env_files) = std::env::var("CLEANER_FILES") {
-            files = env_files.split(',').map(|s| s.trim().to_string()).collect();
+            files = split_patterns(&env_files);
         }
         if let Ok(env_days) = std::env::var("CLEANER_DAYS") {
             if let Ok(d) = env_days.parse() {
                 days = Some(d);
             }
         }
+        if let Ok(env_markers) = std::env::var("CLEANER_REQUIRE_MARKERS") {
+            require_markers = matches!(env_markers.as_str(), "1" | "true" | "yes");
+        }
+        if let Ok(env_hook) = std::env::var("CLEANER_POST_HOOK") {
+            post_hook = Some(env_hook).filter(|hook| !hook.trim().is_empty());
+        }
+        if let Ok(env_skip) = std::env::var("CLEANER_SKIP_HIDDEN") {
+            skip_hidden = matches!(env_skip.as_str(), "1" | "true" | "yes");
+        }
 
         Self {
             directories,
             files,
             days,
+            require_markers,
+            markers,
+            post_hook,
+            only: Vec::new(),
+            ignore_case,
+            skip_hidden,
+        }
+    }
+
+    /// Replace the patterns with the union of the given presets (CLI, highest priority)
+    pub fn apply_presets<S: AsRef<str>>(&mut self, names: &[S]) -> Result<(), String> {
+        if names.is_empty() {
+            return Ok(());
         }
+        let (directories, files) = resolve_presets(names)?;
+        self.directories = directories;
+        self.files = files;
+        Ok(())
     }
 
-    /// Get directories as slice of str references
+    /// Restrict this run to some of the configured patterns (CLI `--only`, after presets)
+    ///
+    /// Each name must be one of the directory or file patterns; the rest of the
+    /// configuration stays as it is but no longer matches anything.
+    pub fn apply_only<S: AsRef<str>>(&mut self, patterns: &[S]) -> Result<(), String> {
+        for pattern in patterns {
+            let pattern = pattern.as_ref().trim();
+            if !self.directories.iter().chain(&self.files).any(|p| p == pattern) {
+                return Err(format!(
+                    "'{}' is not a configured pattern (directories: {}; files: {})",
+                    pattern,
+                    self.directories.join(", "),
+                    self.files.join(", ")
+                ));
+            }
+            if !self.only.iter().any(|p| p == pattern) {
+                self.only.push(pattern.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn is_selected(&self, pattern: &str) -> bool {
+        self.only.is_empty() || self.only.iter().any(|p| p == pattern)
+    }
+
+    /// Directory patterns in effect (all of them unless `only` is set)
     pub fn directories(&self) -> Vec<&str> {
-        self.directories.iter().map(|s| s.as_str()).collect()
+        self.directories.iter().map(|s| s.as_str()).filter(|p| self.is_selected(p)).collect()
     }
 
-    /// Get files as slice of str references
+    /// File patterns in effect (all of them unless `only` is set)
     pub fn files(&self) -> Vec<&str> {
-        self.files.iter().map(|s| s.as_str()).collect()
+        self.files.iter().map(|s| s.as_str()).filter(|p| self.is_selected(p)).collect()
     }
 }
 
@@ -128,3 +378,126 @@ impl Default for Config {
         Self::load(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_single_preset() {
+        let (dirs, files) = resolve_presets(&["rust"]).unwrap();
+        assert_eq!(dirs, vec!["target"]);
+        assert!(files.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_combined_presets_dedups_shared_patterns() {
+        // "dist" is in both node and python, "build" in python and java
+        let (dirs, files) = resolve_presets(&["node", "python", "Java"]).unwrap();
+        assert_eq!(dirs.iter().filter(|d| *d == "dist").count(), 1);
+        assert_eq!(dirs.iter().filter(|d| *d == "build").count(), 1);
+        assert_eq!(dirs[0], "node_modules");
+        assert!(dirs.contains(&".gradle".to_string()));
+        assert_eq!(files, vec![".pyc", ".pyo", ".pyd"]);
+    }
+
+    #[test]
+    fn test_resolve_all_covers_defaults() {
+        let (dirs, files) = resolve_presets(&["all", "rust"]).unwrap();
+        for dir in DEFAULT_DIRECTORIES {
+            assert!(dirs.iter().any(|d| d == dir), "missing {}", dir);
+        }
+        for file in DEFAULT_FILES {
+            assert!(files.iter().any(|f| f == file), "missing {}", file);
+        }
+        assert_eq!(dirs.iter().filter(|d| *d == "target").count(), 1);
+    }
+
+    #[test]
+    fn test_resolve_unknown_preset() {
+        let err = resolve_presets(&["rust", "cobol"]).unwrap_err();
+        assert!(err.contains("cobol"));
+    }
+
+    #[test]
+    fn test_apply_presets_overrides_patterns() {
+        let mut config = Config {
+            directories: vec!["custom".to_string()],
+            files: vec![".log".to_string()],
+            days: Some(7),
+            require_markers: false,
+            markers: HashMap::new(),
+            post_hook: None,
+            only: Vec::new(),
+            ignore_case: false,
+            skip_hidden: false,
+        };
+        config.apply_presets(&["terraform"]).unwrap();
+        assert_eq!(config.directories, vec![".terraform"]);
+        assert!(config.files.is_empty());
+        assert_eq!(config.days, Some(7));
+
+        let no_presets: &[&str] = &[];
+        config.apply_presets(no_presets).unwrap();
+        assert_eq!(config.directories, vec![".terraform"]);
+    }
+
+    #[test]
+    fn test_apply_only_selects_configured_patterns() {
+        let mut config = Config::load(None);
+        config.apply_only(&["__pycache__", ".pyc", "__pycache__"]).unwrap();
+        assert_eq!(config.only, vec!["__pycache__", ".pyc"]);
+        assert_eq!(config.directories(), vec!["__pycache__"]);
+        assert_eq!(config.files(), vec![".pyc"]);
+
+        let err = config.apply_only(&["build-output"]).unwrap_err();
+        assert!(err.contains("build-output"));
+
+        // No --only: everything configured is in effect
+        let config = Config::load(None);
+        assert_eq!(config.directories().len(), config.directories.len());
+    }
+
+    #[test]
+    fn test_split_patterns_keeps_brace_groups() {
+        assert_eq!(split_patterns("target, node_modules"), vec!["target", "node_modules"]);
+        assert_eq!(split_patterns("*.{tmp,cache},test[0-9].log"), vec!["*.{tmp,cache}", "test[0-9].log"]);
+        assert_eq!(split_patterns("a{b,{c,d}},e"), vec!["a{b,{c,d}}", "e"]);
+    }
+
+    #[test]
+    fn test_config_file_marker_rules() {
+        let file: ConfigFile = toml::from_str(
+            r#"
+            require_markers = true
+
+            [markers]
+            target = ["Cargo.toml", "pom.xml"]
+            vendor = ["composer.json"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(file.require_markers, Some(true));
+        assert_eq!(file.markers["target"], vec!["Cargo.toml", "pom.xml"]);
+
+        // Marker checks stay off unless asked for
+        let plain: ConfigFile = toml::from_str("days = 3").unwrap();
+        assert_eq!(plain.require_markers, None);
+        assert!(plain.markers.is_empty());
+    }
+
+    #[test]
+    fn test_config_file_ignore_case() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cleaner.toml");
+
+        for (setting, expected) in [("ignore_case = true", true), ("ignore_case = false", false)] {
+            std::fs::write(&path, setting).unwrap();
+            assert_eq!(Config::load(Some(&path)).ignore_case, expected);
+        }
+
+        // Unset in the file: the platform default
+        std::fs::write(&path, "days = 3").unwrap();
+        assert_eq!(Config::load(Some(&path)).ignore_case, crate::hash::wildcard::ignore_case_default());
+    }
+}
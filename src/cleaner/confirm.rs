@@ -0,0 +1,178 @@
+//! Per-item confirmation for the CLI cleaner (`--confirm-each`)
+//! Asks about every match before it is deleted, like `rm -i`
+
+use crate::cleaner::deleter::Deleter;
+use crate::cleaner::scanner::{ScanResult, SeenInodes};
+use std::io::{self, BufRead, Write};
+
+/// Reply to a confirmation prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Answer {
+    /// Delete this item
+    Yes,
+    /// Keep this item
+    No,
+    /// Delete this and every remaining item without asking
+    All,
+    /// Keep this and every remaining item
+    Quit,
+}
+
+impl Answer {
+    /// Anything but an explicit yes, all or quit keeps the item, as with `rm -i`
+    pub fn parse(input: &str) -> Self {
+        match input.trim().to_lowercase().as_str() {
+            "y" | "yes" => Answer::Yes,
+            "a" | "all" => Answer::All,
+            "q" | "quit" => Answer::Quit,
+            _ => Answer::No,
+        }
+    }
+}
+
+/// Prompts on `output` and reads answers from `input` until told to delete all or quit
+pub struct Confirmer<R, W> {
+    input: R,
+    output: W,
+    all: bool,
+    quit: bool,
+    kept: usize,
+}
+
+impl<R: BufRead, W: Write> Confirmer<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self {
+            input,
+            output,
+            all: false,
+            quit: false,
+            kept: 0,
+        }
+    }
+
+    /// Ask about `item`, returning it to be deleted or `None` to keep it
+    ///
+    /// Directories are measured for the prompt and come back with their size filled in,
+    /// for a deleter using [`Deleter::with_known_sizes`]. End of input counts as quit.
+    pub fn confirm(&mut self, mut item: ScanResult) -> io::Result<Option<ScanResult>> {
+        if self.quit {
+            return Ok(None);
+        }
+        if item.is_dir {
+            (item.size, item.disk_usage) = Deleter::dir_usage_fast(&item.path, &SeenInodes::default());
+        }
+        if self.all {
+            return Ok(Some(item));
+        }
+
+        write!(
+            self.output,
+            "Delete {} {} ({})? [y/n/a/q] ",
+            if item.is_dir { "directory" } else { "file" },
+            item.path.display(),
+            humansize::format_size(item.size, humansize::BINARY)
+        )?;
+        self.output.flush()?;
+        let mut line = String::new();
+        let answer = if self.input.read_line(&mut line)? == 0 {
+            writeln!(self.output)?;
+            Answer::Quit
+        } else {
+            Answer::parse(&line)
+        };
+
+        match answer {
+            Answer::Yes => Ok(Some(item)),
+            Answer::All => {
+                self.all = true;
+                Ok(Some(item))
+            }
+            Answer::No => {
+                self.kept += 1;
+                Ok(None)
+            }
+            Answer::Quit => {
+                self.quit = true;
+                self.kept += 1;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Told to stop; later matches are kept without asking
+    pub fn quit(&self) -> bool {
+        self.quit
+    }
+
+    /// Items answered with no (or quit)
+    pub fn kept(&self) -> usize {
+        self.kept
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn file(name: &str) -> ScanResult {
+        ScanResult { path: PathBuf::from(name), is_dir: false, size: 10, disk_usage: 0 }
+    }
+
+    fn run(input: &str, items: &[&str]) -> (Vec<PathBuf>, usize, bool, String) {
+        let mut output = Vec::new();
+        let mut confirmer = Confirmer::new(input.as_bytes(), &mut output);
+        let deleted: Vec<PathBuf> = items
+            .iter()
+            .filter_map(|name| confirmer.confirm(file(name)).unwrap())
+            .map(|item| item.path)
+            .collect();
+        let (kept, quit) = (confirmer.kept(), confirmer.quit());
+        (deleted, kept, quit, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_parse_answers() {
+        assert_eq!(Answer::parse("y\n"), Answer::Yes);
+        assert_eq!(Answer::parse(" YES "), Answer::Yes);
+        assert_eq!(Answer::parse("a"), Answer::All);
+        assert_eq!(Answer::parse("q"), Answer::Quit);
+        assert_eq!(Answer::parse("n"), Answer::No);
+        assert_eq!(Answer::parse(""), Answer::No);
+        assert_eq!(Answer::parse("sure"), Answer::No);
+    }
+
+    #[test]
+    fn test_yes_no_then_all() {
+        let (deleted, kept, quit, output) = run("y\nn\na\n", &["a.tmp", "b.tmp", "c.tmp", "d.tmp", "e.tmp"]);
+        assert_eq!(deleted, vec![PathBuf::from("a.tmp"), PathBuf::from("c.tmp"), PathBuf::from("d.tmp"), PathBuf::from("e.tmp")]);
+        assert_eq!((kept, quit), (1, false));
+        // No more prompts after "all"
+        assert_eq!(output.matches("[y/n/a/q]").count(), 3);
+        assert!(output.starts_with("Delete file a.tmp (10 B)? [y/n/a/q] "));
+    }
+
+    #[test]
+    fn test_quit_and_end_of_input_keep_the_rest() {
+        let (deleted, kept, quit, output) = run("y\nq\n", &["a.tmp", "b.tmp", "c.tmp"]);
+        assert_eq!(deleted, vec![PathBuf::from("a.tmp")]);
+        assert_eq!((kept, quit), (1, true));
+        assert_eq!(output.matches("[y/n/a/q]").count(), 2);
+
+        let (deleted, _, quit, _) = run("", &["a.tmp", "b.tmp"]);
+        assert!(deleted.is_empty());
+        assert!(quit);
+    }
+
+    #[test]
+    fn test_directories_are_measured() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("blob"), vec![0u8; 2048]).unwrap();
+        let mut output = Vec::new();
+        let mut confirmer = Confirmer::new("y\n".as_bytes(), &mut output);
+        let item = ScanResult { path: dir.path().to_path_buf(), is_dir: true, size: 0, disk_usage: 0 };
+        let confirmed = confirmer.confirm(item).unwrap().unwrap();
+        assert_eq!(confirmed.size, 2048);
+        assert!(String::from_utf8(output).unwrap().contains("(2 KiB)"));
+    }
+}
@@ -1,36 +1,148 @@
 //! Parallel deletion engine
 //! Uses rayon for parallel file/directory removal with streaming processing
 
-use crate::cleaner::scanner::ScanResult;
+use crate::cleaner::patterns::PatternMatcher;
+use crate::cleaner::scanner::{allocated_size, is_special, ScanResult, SeenInodes};
 use crate::cleaner::stats::Stats;
-use crossbeam_channel::Receiver;
+use crate::fs::remove_path;
+use crossbeam_channel::{Receiver, Sender};
 use rayon::prelude::*;
 use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+/// What the deleter did with one matched item
+///
+/// Sent to the channel given to [`Deleter::with_events`], so a caller can draw its own
+/// output (with [`LogLevel::Quiet`] the deleter prints nothing itself).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeleteEvent {
+    /// Removed, or in a dry run would have been. `bytes` is 0 unless sizes are known or
+    /// measured (see [`Deleter::with_known_sizes`] and [`Deleter::with_measured_sizes`])
+    Removed { path: PathBuf, is_dir: bool, bytes: u64 },
+    /// A symlink removed as a link; `target` was left alone
+    Unlinked { path: PathBuf, target: PathBuf },
+    /// Socket, FIFO or device node, never removed
+    Skipped { path: PathBuf },
+    /// Removal failed; also counted in `Stats::error_count`
+    Failed { path: PathBuf, error: String },
+}
+
+/// How much the deleter prints while it works
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum LogLevel {
+    /// Nothing per item, errors are only counted in `Stats` (TUI, `--quiet`)
+    Quiet,
+    /// Per-item errors on stderr
+    #[default]
+    Normal,
+    /// Every matched item with its size, plus errors
+    Verbose,
+}
+
+impl LogLevel {
+    /// Level for the `--quiet` / `--verbose` CLI flags
+    pub fn from_flags(quiet: bool, verbose: bool) -> Self {
+        if quiet {
+            LogLevel::Quiet
+        } else if verbose {
+            LogLevel::Verbose
+        } else {
+            LogLevel::Normal
+        }
+    }
+}
+
 /// Parallel deletion worker
 pub struct Deleter {
     stats: Arc<Stats>,
     dry_run: bool,
-    verbose: bool,
+    log_level: LogLevel,
+    known_sizes: bool,
+    measure_sizes: bool,
+    cancelled: Option<Arc<AtomicBool>>,
+    events: Option<Sender<DeleteEvent>>,
+    matcher: Option<Arc<PatternMatcher>>,
+    /// Hard-linked files already counted by a directory walk
+    seen: SeenInodes,
 }
 
 impl Deleter {
-    pub fn new(stats: Arc<Stats>, dry_run: bool, verbose: bool) -> Self {
+    pub fn new(stats: Arc<Stats>, dry_run: bool, log_level: LogLevel) -> Self {
         Self {
             stats,
             dry_run,
-            verbose,
+            log_level,
+            known_sizes: false,
+            measure_sizes: log_level == LogLevel::Verbose,
+            cancelled: None,
+            events: None,
+            matcher: None,
+            seen: SeenInodes::default(),
+        }
+    }
+
+    /// Trust `ScanResult::size` for directories too and count it as freed in every log level
+    ///
+    /// For callers that already know match sizes (the analyzer tree), so progress can be
+    /// reported in bytes without walking each directory before removing it.
+    pub fn with_known_sizes(mut self) -> Self {
+        self.known_sizes = true;
+        self
+    }
+
+    /// Walk each matched directory for its size before removing it, as verbose mode does,
+    /// so freed bytes are counted without printing every item
+    pub fn with_measured_sizes(mut self) -> Self {
+        self.measure_sizes = true;
+        self
+    }
+
+    /// Report every matched item to `events` as it is handled
+    pub fn with_events(mut self, events: Sender<DeleteEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Stop taking new items once `cancelled` is set; items already removed stay counted
+    pub fn with_cancel(mut self, cancelled: Arc<AtomicBool>) -> Self {
+        self.cancelled = Some(cancelled);
+        self
+    }
+
+    /// Name the pattern each item matched in verbose output, to help debug patterns
+    pub fn with_matcher(mut self, matcher: Arc<PatternMatcher>) -> Self {
+        self.matcher = Some(matcher);
+        self
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.as_ref().is_some_and(|c| c.load(Ordering::Relaxed))
+    }
+
+    fn emit(&self, event: DeleteEvent) {
+        if let Some(ref events) = self.events {
+            // A caller that stopped listening doesn't stop the clean
+            let _ = events.send(event);
         }
     }
 
     /// Process items as they arrive - streaming parallel deletion
     /// Uses a batching approach for efficient parallelism
     pub fn process(&self, rx: Receiver<ScanResult>) {
+        self.process_iter(rx);
+    }
+
+    /// Same as [`Deleter::process`] for any stream of matches
+    pub fn process_iter(&self, items: impl IntoIterator<Item = ScanResult>) {
         const BATCH_SIZE: usize = 64;
         let mut batch = Vec::with_capacity(BATCH_SIZE);
 
-        for item in rx {
+        for item in items {
+            if self.is_cancelled() {
+                return;
+            }
             batch.push(item);
             
             // Process batch when full
@@ -56,66 +168,163 @@ impl Deleter {
 
     /// Delete a single item - size is calculated only in verbose mode
     fn delete_item(&self, item: &ScanResult) {
-        // Only calculate size if verbose (skip expensive recursive walk otherwise)
-        let size = if self.verbose {
-            if item.is_dir {
-                Self::dir_size_fast(&item.path)
-            } else {
-                item.size
-            }
-        } else {
-            0
-        };
-
-        if self.verbose {
-            let type_str = if item.is_dir { "DIR " } else { "FILE" };
-            let size_str = humansize::format_size(size, humansize::BINARY);
-            println!("[{}] {} ({})", type_str, item.path.display(), size_str);
+        if self.is_cancelled() {
+            return;
         }
 
-        if self.dry_run {
-            if item.is_dir {
-                self.stats.add_directory();
-            } else {
-                self.stats.add_file();
+        let file_type = fs::symlink_metadata(&item.path).ok().map(|m| m.file_type());
+        if file_type.as_ref().is_some_and(is_special) {
+            self.stats.add_special();
+            if self.log_level == LogLevel::Verbose {
+                println!("[SKIP] {} (special file)", item.path.display());
             }
-            self.stats.add_bytes(size);
+            self.emit(DeleteEvent::Skipped { path: item.path.clone() });
             return;
         }
 
-        // Actually delete
-        let result = if item.is_dir {
-            fs::remove_dir_all(&item.path)
+        // Links are removed as links and count as files; their targets are never touched
+        let is_link = file_type.is_some_and(|t| t.is_symlink());
+        let is_dir = item.is_dir && !is_link;
+        let verbose = self.log_level == LogLevel::Verbose;
+
+        // Only calculate size when asked to (skip expensive recursive walk otherwise)
+        let (size, disk_usage) = if self.known_sizes {
+            if is_link { (0, 0) } else { (item.size, item.disk_usage) }
+        } else if self.measure_sizes {
+            if is_link {
+                (0, 0)
+            } else if is_dir {
+                Self::dir_usage_fast(&item.path, &self.seen)
+            } else {
+                (item.size, item.disk_usage)
+            }
         } else {
-            fs::remove_file(&item.path)
+            (0, 0)
         };
 
-        match result {
+        let target = is_link.then(|| fs::read_link(&item.path).unwrap_or_default());
+        if verbose {
+            if let Some(ref target) = target {
+                println!("[LINK] {} -> {} (removing link only)", item.path.display(), target.display());
+            } else {
+                let type_str = if is_dir { "DIR " } else { "FILE" };
+                let size_str = humansize::format_size(size, humansize::BINARY);
+                match self.matcher.as_ref().and_then(|m| m.matched_by(&item.path, item.is_dir)) {
+                    Some(pattern) => println!("[{}] {} ({}, matched {})", type_str, item.path.display(), size_str, pattern),
+                    None => println!("[{}] {} ({})", type_str, item.path.display(), size_str),
+                }
+            }
+        }
+
+        // Actually delete (unless this is a dry run)
+        let removed = if self.dry_run { Ok(()) } else { remove_path(&item.path) };
+        match removed {
             Ok(_) => {
-                if item.is_dir {
+                if is_dir {
                     self.stats.add_directory();
                 } else {
                     self.stats.add_file();
                 }
                 self.stats.add_bytes(size);
+                self.stats.add_disk_bytes(disk_usage);
+                self.emit(match target {
+                    Some(target) => DeleteEvent::Unlinked { path: item.path.clone(), target },
+                    None => DeleteEvent::Removed { path: item.path.clone(), is_dir, bytes: size },
+                });
             }
             Err(e) => {
                 self.stats.add_error();
-                eprintln!("Error deleting {}: {}", item.path.display(), e);
+                if self.log_level >= LogLevel::Normal {
+                    eprintln!("Error deleting {}: {}", item.path.display(), e);
+                }
+                self.emit(DeleteEvent::Failed { path: item.path.clone(), error: e.to_string() });
             }
         }
     }
 
-    /// Fast directory size estimation using parallel walk
-    fn dir_size_fast(path: &std::path::Path) -> u64 {
-        use jwalk::WalkDir;
+    /// Fast directory size estimation: apparent size and bytes allocated on disk, skipping
+    /// hard-linked files that `seen` already counted
+    ///
+    /// Walks serially: callers already run one walk per item on the rayon pool, and a nested
+    /// parallel walk can't get a worker and gives up, reporting 0 bytes.
+    pub(crate) fn dir_usage_fast(path: &std::path::Path, seen: &SeenInodes) -> (u64, u64) {
+        use jwalk::{Parallelism, WalkDir};
         
         WalkDir::new(path)
+            .parallelism(Parallelism::Serial)
             .skip_hidden(false)
             .into_iter()
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
-            .map(|e| e.metadata().map(|m| m.len()).unwrap_or(0))
-            .sum()
+            .filter_map(|e| e.metadata().ok())
+            .filter(|m| seen.first_sight(m))
+            .map(|m| (m.len(), allocated_size(&m)))
+            .fold((0, 0), |(size, disk), (s, d)| (size + s, disk + d))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_dir_outside_root_survives() {
+        let outside = tempfile::tempdir().unwrap();
+        let shared = outside.path().join("shared_modules");
+        fs::create_dir_all(shared.join("pkg")).unwrap();
+        fs::write(shared.join("pkg/index.js"), b"keep me").unwrap();
+
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("app");
+        fs::create_dir_all(&project).unwrap();
+        let link = project.join("node_modules");
+        std::os::unix::fs::symlink(&shared, &link).unwrap();
+
+        let stats = Arc::new(Stats::new());
+        let deleter = Deleter::new(Arc::clone(&stats), false, LogLevel::Verbose);
+        let (tx, rx) = crossbeam_channel::unbounded();
+        tx.send(ScanResult { path: link.clone(), is_dir: true, size: 0, disk_usage: 0 }).unwrap();
+        drop(tx);
+        deleter.process(rx);
+
+        assert!(fs::symlink_metadata(&link).is_err(), "link should be removed");
+        assert_eq!(fs::read(shared.join("pkg/index.js")).unwrap(), b"keep me");
+        assert_eq!(stats.files(), 1);
+        assert_eq!(stats.directories(), 0);
+        assert_eq!(stats.error_count(), 0);
+    }
+
+    #[test]
+    fn test_log_level_from_flags() {
+        assert_eq!(LogLevel::from_flags(false, false), LogLevel::Normal);
+        assert_eq!(LogLevel::from_flags(false, true), LogLevel::Verbose);
+        assert_eq!(LogLevel::from_flags(true, false), LogLevel::Quiet);
+        assert!(LogLevel::Quiet < LogLevel::Normal && LogLevel::Normal < LogLevel::Verbose);
+    }
+
+    #[test]
+    fn test_quiet_still_counts_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let stats = Arc::new(Stats::new());
+        let deleter = Deleter::new(Arc::clone(&stats), false, LogLevel::Quiet);
+        let (tx, rx) = crossbeam_channel::unbounded();
+        tx.send(ScanResult { path: dir.path().join("gone"), is_dir: false, size: 0, disk_usage: 0 }).unwrap();
+        drop(tx);
+        deleter.process(rx);
+        assert_eq!(stats.error_count(), 1);
+    }
+
+    #[test]
+    fn test_failed_removal_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let (events_tx, events_rx) = crossbeam_channel::unbounded();
+        let deleter = Deleter::new(Arc::new(Stats::new()), false, LogLevel::Quiet).with_events(events_tx);
+        let missing = dir.path().join("gone");
+        deleter.process_iter([ScanResult { path: missing.clone(), is_dir: false, size: 0, disk_usage: 0 }]);
+        drop(deleter);
+        let events: Vec<_> = events_rx.iter().collect();
+        assert!(matches!(&events[..], [DeleteEvent::Failed { path, .. }] if *path == missing));
+    }
+}
+
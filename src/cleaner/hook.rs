@@ -0,0 +1,117 @@
+//! Post-clean hook - runs a user command after a clean with the results
+//! Stats arrive as JSON on stdin and as `ABYSS_CLEAN_*` environment variables
+
+use crate::cleaner::stats::Stats;
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// What a finished clean did, as handed to the hook
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanReport {
+    pub root: PathBuf,
+    pub dry_run: bool,
+    pub directories: usize,
+    pub files: usize,
+    pub bytes: u64,
+    /// Blocks reclaimed on disk, where `bytes` is the logical size
+    pub disk_bytes: u64,
+    pub errors: usize,
+    pub elapsed_secs: f64,
+}
+
+impl CleanReport {
+    pub fn new(root: &Path, stats: &Stats, dry_run: bool, elapsed: Duration) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            dry_run,
+            directories: stats.directories(),
+            files: stats.files(),
+            bytes: stats.bytes(),
+            disk_bytes: stats.disk_bytes(),
+            errors: stats.error_count(),
+            elapsed_secs: elapsed.as_secs_f64(),
+        }
+    }
+}
+
+/// Run `command` through the shell with the report on stdin (JSON) and in the environment
+///
+/// The hook's output goes to the terminal. A hook that can't start or exits non-zero
+/// is reported as an error message; the clean itself has already finished.
+pub fn run_post_hook(command: &str, report: &CleanReport) -> Result<(), String> {
+    let json = serde_json::to_string(report).map_err(|e| e.to_string())?;
+
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("ABYSS_CLEAN_ROOT", &report.root)
+        .env("ABYSS_CLEAN_DRY_RUN", if report.dry_run { "1" } else { "0" })
+        .env("ABYSS_CLEAN_DIRS", report.directories.to_string())
+        .env("ABYSS_CLEAN_FILES", report.files.to_string())
+        .env("ABYSS_CLEAN_BYTES", report.bytes.to_string())
+        .env("ABYSS_CLEAN_DISK_BYTES", report.disk_bytes.to_string())
+        .env("ABYSS_CLEAN_ERRORS", report.errors.to_string())
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start post-hook '{}': {}", command, e))?;
+
+    // A hook that ignores stdin may exit before reading it; that is not a failure
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(json.as_bytes());
+    }
+
+    let status = child
+        .wait()
+        .map_err(|e| format!("Failed to wait for post-hook '{}': {}", command, e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("Post-hook '{}' failed ({})", command, status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> CleanReport {
+        let stats = Stats::new();
+        stats.add_directory();
+        stats.add_file();
+        stats.add_file();
+        stats.add_bytes(4096);
+        stats.add_disk_bytes(8192);
+        CleanReport::new(Path::new("/tmp/project"), &stats, false, Duration::from_millis(1500))
+    }
+
+    #[test]
+    fn test_post_hook_receives_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let json = dir.path().join("stats.json");
+        let env = dir.path().join("env.txt");
+        let command = format!(
+            "cat > '{}'; echo \"$ABYSS_CLEAN_DIRS $ABYSS_CLEAN_FILES $ABYSS_CLEAN_BYTES $ABYSS_CLEAN_DISK_BYTES\" > '{}'",
+            json.display(),
+            env.display()
+        );
+
+        run_post_hook(&command, &report()).unwrap();
+
+        let value: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&json).unwrap()).unwrap();
+        assert_eq!(value["directories"], 1);
+        assert_eq!(value["files"], 2);
+        assert_eq!(value["bytes"], 4096);
+        assert_eq!(value["disk_bytes"], 8192);
+        assert_eq!(value["root"], "/tmp/project");
+        assert_eq!(std::fs::read_to_string(&env).unwrap().trim(), "1 2 4096 8192");
+    }
+
+    #[test]
+    fn test_post_hook_failure_is_reported() {
+        let err = run_post_hook("exit 3", &report()).unwrap_err();
+        assert!(err.contains("exit 3"), "{}", err);
+    }
+}
@@ -4,15 +4,23 @@
 //! temporary files and folders like .terraform, target, node_modules, __pycache__, etc.
 
 pub mod config;
+pub mod confirm;
 pub mod deleter;
+pub mod hook;
 pub mod patterns;
+pub mod recent;
 pub mod scanner;
 pub mod stats;
+pub mod summary;
 pub mod tree;
 
 pub use config::Config;
-pub use deleter::Deleter;
+pub use confirm::{Answer, Confirmer};
+pub use deleter::{DeleteEvent, Deleter, LogLevel};
+pub use hook::{run_post_hook, CleanReport};
 pub use patterns::PatternMatcher;
+pub use recent::{find_recent_files, RecentFile, RecentSortMode};
 pub use scanner::{ScanResult, Scanner};
 pub use stats::Stats;
-pub use tree::{DirEntry, DirTree, ScanProgress};
+pub use summary::{ReclaimEntry, ReclaimSummary};
+pub use tree::{DirEntry, DirTree, ScanProgress, SizeMetric};
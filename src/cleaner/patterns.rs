@@ -1,36 +1,161 @@
 //! Pattern matching for temporary files and folders
 
 use crate::cleaner::config::Config;
+use crate::hash::wildcard::{contains_wildcard, name_pattern};
+use globset::GlobMatcher;
+use std::borrow::Cow;
 use std::path::Path;
 use std::sync::Arc;
 
 /// Pattern matcher with configurable patterns
+#[derive(Clone)]
 pub struct PatternMatcher {
     directories: Vec<String>,
     files: Vec<String>,
+    directory_patterns: Vec<NamePattern>,
+    file_patterns: Vec<NamePattern>,
+    /// Directory pattern -> required sibling files (empty unless marker checks are on)
+    markers: Vec<(NamePattern, Vec<NamePattern>)>,
+    /// Patterns were compiled lowercase; names are lowercased before matching
+    ignore_case: bool,
+    /// Unmatched dot-directories are left out of scans (`Config::skip_hidden`)
+    skip_hidden: bool,
+}
+
+/// A configured name pattern, compiled once
+#[derive(Clone)]
+struct NamePattern {
+    text: String,
+    /// Set for anything beyond a leading `*`: `?`, classes like `[0-9]`, braces like `{tmp,cache}`
+    glob: Option<GlobMatcher>,
+}
+
+impl NamePattern {
+    fn new(pattern: &str) -> Self {
+        let rest = pattern.strip_prefix('*').unwrap_or(pattern);
+        // An invalid glob (e.g. an unclosed class) falls back to a literal match
+        let glob = if contains_wildcard(rest) { name_pattern(pattern).ok() } else { None };
+        Self { text: pattern.to_string(), glob }
+    }
+
+    fn is_wildcard(&self) -> bool {
+        self.glob.is_some() || self.text.starts_with('*')
+    }
+
+    /// Exact name, a `*suffix` wildcard like "*.egg-info" / "*.tf", or a full glob
+    #[inline]
+    fn matches(&self, name: &str) -> bool {
+        if let Some(glob) = &self.glob {
+            return glob.is_match(name);
+        }
+        match self.text.strip_prefix('*') {
+            Some(suffix) => name.ends_with(suffix),
+            None => name == self.text,
+        }
+    }
+}
+
+/// `text`, lowercased when matching ignores case
+fn fold(text: &str, ignore_case: bool) -> Cow<'_, str> {
+    if ignore_case {
+        Cow::Owned(text.to_lowercase())
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+fn compile(patterns: &[String], ignore_case: bool) -> Vec<NamePattern> {
+    patterns.iter().map(|p| NamePattern::new(&fold(p, ignore_case))).collect()
 }
 
 impl PatternMatcher {
     pub fn new(config: Arc<Config>) -> Self {
+        let ignore_case = config.ignore_case;
+        let directories: Vec<String> = config.directories().into_iter().map(String::from).collect();
+        let files: Vec<String> = config.files().into_iter().map(String::from).collect();
+        let markers = if config.require_markers {
+            config
+                .markers
+                .iter()
+                .map(|(dir, files)| (NamePattern::new(&fold(dir, ignore_case)), compile(files, ignore_case)))
+                .collect()
+        } else {
+            Vec::new()
+        };
         Self {
-            directories: config.directories.clone(),
-            files: config.files.clone(),
+            directory_patterns: compile(&directories, ignore_case),
+            file_patterns: compile(&files, ignore_case),
+            directories,
+            files,
+            markers,
+            ignore_case,
+            skip_hidden: config.skip_hidden,
         }
     }
 
     /// Check if a directory name matches any temp directory pattern
     #[inline]
     pub fn is_temp_directory(&self, name: &str) -> bool {
-        for pattern in &self.directories {
-            if name == pattern {
-                return true;
-            }
-            // Handle wildcard patterns like "*.egg-info"
-            if pattern.starts_with('*') {
-                let suffix = &pattern[1..];
-                if name.ends_with(suffix) {
-                    return true;
-                }
+        self.directory_match(name).is_some()
+    }
+
+    /// Index of the first directory pattern matching `name`
+    #[inline]
+    fn directory_match(&self, name: &str) -> Option<usize> {
+        let name = fold(name, self.ignore_case);
+        self.directory_patterns.iter().position(|pattern| pattern.matches(&name))
+    }
+
+    /// Marker files required next to a directory called `name`, if a rule applies
+    fn required_markers(&self, name: &str) -> Option<&[NamePattern]> {
+        if self.markers.is_empty() {
+            return None;
+        }
+        let name = fold(name, self.ignore_case);
+        let name = name.as_ref();
+        self.markers
+            .iter()
+            .find(|(pattern, _)| pattern.text == name)
+            .or_else(|| {
+                self.markers
+                    .iter()
+                    .find(|(pattern, _)| pattern.is_wildcard() && pattern.matches(name))
+            })
+            .map(|(_, files)| files.as_slice())
+    }
+
+    /// Whether a directory called `name` passes its marker rule, given its siblings' names.
+    /// Directories without a rule (or with marker checks off) always pass.
+    pub fn has_marker<'a>(&self, name: &str, siblings: impl IntoIterator<Item = &'a str>) -> bool {
+        let Some(markers) = self.required_markers(name) else { return true };
+        siblings
+            .into_iter()
+            .map(|sibling| fold(sibling, self.ignore_case))
+            .any(|sibling| markers.iter().any(|marker| marker.matches(&sibling)))
+    }
+
+    /// Check a directory on disk: name matches a pattern and, if a marker rule applies,
+    /// one of the marker files exists next to it
+    pub fn is_temp_directory_at(&self, path: &Path) -> bool {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else { return false };
+        if !self.is_temp_directory(name) {
+            return false;
+        }
+        let Some(markers) = self.required_markers(name) else { return true };
+        let Some(parent) = path.parent() else { return false };
+
+        // Literal markers are a single stat; wildcards (and, ignoring case, any marker
+        // spelled differently on disk) need the parent listing
+        if markers.iter().any(|m| !m.is_wildcard() && parent.join(&m.text).exists()) {
+            return true;
+        }
+        if self.ignore_case || markers.iter().any(|m| m.is_wildcard()) {
+            if let Ok(entries) = std::fs::read_dir(parent) {
+                let names: Vec<String> = entries
+                    .flatten()
+                    .map(|e| e.file_name().to_string_lossy().to_string())
+                    .collect();
+                return self.has_marker(name, names.iter().map(|n| n.as_str()));
             }
         }
         false
@@ -39,36 +164,62 @@ impl PatternMatcher {
     /// Check if a file name matches any temp file pattern
     #[inline]
     pub fn is_temp_file(&self, name: &str) -> bool {
-        for pattern in &self.files {
-            if name == pattern {
-                return true;
-            }
-            // Extension/suffix matches
-            if pattern.starts_with('.') && name.ends_with(pattern.as_str()) {
-                return true;
-            }
-            // Ends with pattern (like ~ for backup files)
-            if name.ends_with(pattern.as_str()) {
-                return true;
+        self.file_match(name).is_some()
+    }
+
+    /// Index of the first file pattern matching `name`
+    #[inline]
+    fn file_match(&self, name: &str) -> Option<usize> {
+        let name = fold(name, self.ignore_case);
+        let name = name.as_ref();
+        self.file_patterns.iter().position(|pattern| {
+            if pattern.is_wildcard() {
+                return pattern.matches(name);
             }
-        }
-        false
+            // Exact names, extensions like ".pyc" and suffixes like "~" for backup files
+            name.ends_with(pattern.text.as_str())
+        })
     }
 
     /// Check if path component matches any temp pattern
     #[inline]
     pub fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if is_dir {
+            return self.is_temp_directory_at(path);
+        }
         if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-            if is_dir {
-                self.is_temp_directory(name)
-            } else {
-                self.is_temp_file(name)
-            }
+            self.is_temp_file(name)
         } else {
             false
         }
     }
 
+    /// The configured pattern, as written, that the name at `path` matches
+    ///
+    /// Explains a match rather than deciding one: marker rules are not checked again.
+    pub fn matched_by(&self, path: &Path, is_dir: bool) -> Option<&str> {
+        let name = path.file_name().and_then(|n| n.to_str())?;
+        if is_dir {
+            self.directory_match(name).map(|i| self.directories[i].as_str())
+        } else {
+            self.file_match(name).map(|i| self.files[i].as_str())
+        }
+    }
+
+    /// Whether a scan should leave out the directory at `path` and everything below it:
+    /// with `skip_hidden` set, a dot-directory that isn't itself a match
+    pub fn skips_hidden_dir(&self, path: &Path) -> bool {
+        self.skip_hidden
+            && path.file_name().is_some_and(|name| name.as_encoded_bytes().starts_with(b"."))
+            && !self.matches(path, true)
+    }
+
+    /// `skips_hidden_dir` for a directory called `name` in a backend listing, with its
+    /// marker rule checked against `siblings` (the listing's names) instead of the local disk
+    pub fn skips_hidden_name<'a>(&self, name: &str, siblings: impl IntoIterator<Item = &'a str>) -> bool {
+        self.skip_hidden && name.starts_with('.') && !(self.is_temp_directory(name) && self.has_marker(name, siblings))
+    }
+
     /// Get directory patterns for display
     pub fn directory_patterns(&self) -> &[String] {
         &self.directories
@@ -83,6 +234,7 @@ impl PatternMatcher {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
     fn test_config() -> Arc<Config> {
         Arc::new(Config {
@@ -99,9 +251,26 @@ mod tests {
                 "~".to_string(),
             ],
             days: None,
+            require_markers: false,
+            markers: HashMap::new(),
+            post_hook: None,
+            only: Vec::new(),
+            ignore_case: false,
+            skip_hidden: false,
         })
     }
 
+    fn marker_config() -> Arc<Config> {
+        let mut config = (*test_config()).clone();
+        config.require_markers = true;
+        config.markers = HashMap::from([
+            ("target".to_string(), vec!["Cargo.toml".to_string()]),
+            ("node_modules".to_string(), vec!["package.json".to_string()]),
+            (".terraform".to_string(), vec!["*.tf".to_string()]),
+        ]);
+        Arc::new(config)
+    }
+
     #[test]
     fn test_temp_directories() {
         let matcher = PatternMatcher::new(test_config());
@@ -122,9 +291,184 @@ mod tests {
         assert!(!matcher.is_temp_file("main.rs"));
     }
 
+    #[test]
+    fn test_matched_by_names_the_configured_pattern() {
+        let mut config = (*test_config()).clone();
+        config.files.push("Thumbs.db".to_string());
+        config.files.push("*.{tmp,cache}".to_string());
+        config.ignore_case = true;
+        let matcher = PatternMatcher::new(Arc::new(config));
+
+        assert_eq!(matcher.matched_by(Path::new("py/__pycache__"), true), Some("__pycache__"));
+        assert_eq!(matcher.matched_by(Path::new("pkg/mypackage.egg-info"), true), Some("*.egg-info"));
+        assert_eq!(matcher.matched_by(Path::new("src/module.pyc"), false), Some(".pyc"));
+        assert_eq!(matcher.matched_by(Path::new("notes.txt~"), false), Some("~"));
+        assert_eq!(matcher.matched_by(Path::new("cache/session.TMP"), false), Some("*.{tmp,cache}"));
+        // Reported as written, not as folded for case-insensitive matching
+        assert_eq!(matcher.matched_by(Path::new("photos/THUMBS.DB"), false), Some("Thumbs.db"));
+        // Directory and file patterns are kept apart
+        assert_eq!(matcher.matched_by(Path::new("target"), false), None);
+        assert_eq!(matcher.matched_by(Path::new("src/main.rs"), false), None);
+    }
+
     #[test]
     fn test_egg_info() {
         let matcher = PatternMatcher::new(test_config());
         assert!(matcher.is_temp_directory("mypackage.egg-info"));
     }
+
+    #[test]
+    fn test_brace_and_class_patterns() {
+        let mut config = (*test_config()).clone();
+        config.directories.push("build-{debug,release}".to_string());
+        config.directories.push("tmp[0-9]".to_string());
+        config.files.push("*.{tmp,cache}".to_string());
+        config.files.push("test[0-9].log".to_string());
+        let matcher = PatternMatcher::new(Arc::new(config));
+
+        assert!(matcher.is_temp_directory("build-debug"));
+        assert!(matcher.is_temp_directory("build-release"));
+        assert!(!matcher.is_temp_directory("build-profile"));
+        assert!(matcher.is_temp_directory("tmp7"));
+        assert!(!matcher.is_temp_directory("tmpx"));
+
+        assert!(matcher.is_temp_file("session.tmp"));
+        assert!(matcher.is_temp_file("thumbs.cache"));
+        assert!(!matcher.is_temp_file("session.tmpl"));
+        assert!(matcher.is_temp_file("test3.log"));
+        assert!(!matcher.is_temp_file("test.log"));
+        assert!(!matcher.is_temp_file("test12.log"));
+
+        // Simple patterns keep their suffix semantics alongside globs
+        assert!(matcher.is_temp_file("test.pyc"));
+        assert!(matcher.is_temp_directory("mypackage.egg-info"));
+    }
+
+    #[test]
+    fn test_star_suffix_file_patterns() {
+        let mut config = (*test_config()).clone();
+        config.files.push("*.tmp".to_string());
+        let matcher = PatternMatcher::new(Arc::new(config));
+
+        assert!(matcher.is_temp_file("session.tmp"));
+        assert!(!matcher.is_temp_file("session.tmpl"));
+        assert_eq!(matcher.matched_by(Path::new("cache/a.tmp"), false), Some("*.tmp"));
+        // Plain suffixes still match without a leading `*`
+        assert!(matcher.is_temp_file("test.pyc"));
+    }
+
+    #[test]
+    fn test_only_restricts_matching() {
+        let mut config = (*test_config()).clone();
+        config.apply_only(&["__pycache__"]).unwrap();
+        let matcher = PatternMatcher::new(Arc::new(config));
+
+        assert!(matcher.is_temp_directory("__pycache__"));
+        assert!(!matcher.is_temp_directory("node_modules"));
+        assert!(!matcher.is_temp_directory("target"));
+        assert!(!matcher.is_temp_file("test.pyc"));
+        assert!(!matcher.is_temp_file(".DS_Store"));
+        assert_eq!(matcher.directory_patterns(), ["__pycache__"]);
+        assert!(matcher.file_patterns().is_empty());
+
+        // Directory and file patterns can be mixed
+        let mut config = (*test_config()).clone();
+        config.apply_only(&["target", ".pyc"]).unwrap();
+        let matcher = PatternMatcher::new(Arc::new(config));
+        assert!(matcher.is_temp_directory("target"));
+        assert!(matcher.is_temp_file("test.pyc"));
+        assert!(!matcher.is_temp_directory("__pycache__"));
+        assert!(!matcher.is_temp_file("backup~"));
+    }
+
+    #[test]
+    fn test_glob_marker_rules() {
+        let mut config = (*marker_config()).clone();
+        config.directories.push("{venv,.venv}".to_string());
+        config.markers.insert("{venv,.venv}".to_string(), vec!["requirements*.txt".to_string()]);
+        let matcher = PatternMatcher::new(Arc::new(config));
+
+        assert!(matcher.has_marker(".venv", ["requirements-dev.txt"]));
+        assert!(matcher.has_marker("venv", ["requirements.txt"]));
+        assert!(!matcher.has_marker("venv", ["setup.py"]));
+    }
+
+    #[test]
+    fn test_marker_rules_against_siblings() {
+        let matcher = PatternMatcher::new(marker_config());
+        assert!(matcher.has_marker("target", ["src", "Cargo.toml", "target"]));
+        assert!(!matcher.has_marker("target", ["src", "README.md", "target"]));
+        assert!(matcher.has_marker(".terraform", ["main.tf", ".terraform"]));
+        assert!(!matcher.has_marker(".terraform", ["notes.txt"]));
+        // No rule for this pattern
+        assert!(matcher.has_marker("__pycache__", ["x.py"]));
+
+        // Rules are ignored unless marker checks are enabled
+        let mut config = (*marker_config()).clone();
+        config.require_markers = false;
+        let matcher = PatternMatcher::new(Arc::new(config));
+        assert!(matcher.has_marker("target", ["README.md"]));
+    }
+
+    #[test]
+    fn test_marker_rules_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let rust = dir.path().join("crate");
+        let photos = dir.path().join("photos");
+        let infra = dir.path().join("infra");
+        for project in [&rust, &photos, &infra] {
+            std::fs::create_dir_all(project.join("target")).unwrap();
+        }
+        std::fs::write(rust.join("Cargo.toml"), "[package]").unwrap();
+        std::fs::create_dir_all(infra.join(".terraform")).unwrap();
+        std::fs::write(infra.join("main.tf"), "").unwrap();
+
+        let matcher = PatternMatcher::new(marker_config());
+        assert!(matcher.matches(&rust.join("target"), true));
+        // A user directory that happens to be called "target"
+        assert!(!matcher.matches(&photos.join("target"), true));
+        assert!(matcher.matches(&infra.join(".terraform"), true));
+
+        let plain = PatternMatcher::new(test_config());
+        assert!(plain.matches(&photos.join("target"), true));
+    }
+
+    #[test]
+    fn test_ignore_case_on_mixed_case_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("Project");
+        std::fs::create_dir_all(project.join("Target")).unwrap();
+        std::fs::create_dir_all(project.join("Node_Modules")).unwrap();
+        std::fs::create_dir_all(project.join("Foo.Egg-Info")).unwrap();
+        std::fs::write(project.join("CARGO.TOML"), "[package]").unwrap();
+        for file in ["MODULE.PYC", ".ds_store", "notes.txt"] {
+            std::fs::write(project.join(file), "").unwrap();
+        }
+
+        let sensitive = PatternMatcher::new(test_config());
+        assert!(!sensitive.matches(&project.join("Target"), true));
+        assert!(!sensitive.matches(&project.join("Node_Modules"), true));
+        assert!(!sensitive.matches(&project.join("Foo.Egg-Info"), true));
+        assert!(!sensitive.matches(&project.join("MODULE.PYC"), false));
+        assert!(!sensitive.matches(&project.join(".ds_store"), false));
+
+        let mut config = (*test_config()).clone();
+        config.ignore_case = true;
+        let insensitive = PatternMatcher::new(Arc::new(config));
+        assert!(insensitive.matches(&project.join("Target"), true));
+        assert!(insensitive.matches(&project.join("Node_Modules"), true));
+        assert!(insensitive.matches(&project.join("Foo.Egg-Info"), true));
+        assert!(insensitive.matches(&project.join("MODULE.PYC"), false));
+        assert!(insensitive.matches(&project.join(".ds_store"), false));
+        assert!(!insensitive.matches(&project.join("notes.txt"), false));
+        // Display keeps the patterns as configured
+        assert!(insensitive.directory_patterns().contains(&"node_modules".to_string()));
+
+        // Marker files are found whatever their case on disk
+        let mut config = (*marker_config()).clone();
+        config.ignore_case = true;
+        let markers = PatternMatcher::new(Arc::new(config));
+        assert!(markers.matches(&project.join("Target"), true));
+        assert!(!markers.matches(&project.join("Node_Modules"), true));
+    }
 }
@@ -0,0 +1,180 @@
+//! Recently modified files finder
+//! Single parallel jwalk pass, keeps only the newest N files in a bounded heap
+
+use crate::cleaner::tree::ScanProgress;
+use jwalk::WalkDir;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Default number of files kept by the finder
+pub const DEFAULT_RECENT_LIMIT: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RecentFile {
+    pub modified: SystemTime,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+impl RecentFile {
+    /// Path relative to the scanned root (falls back to the full path)
+    pub fn relative_to(&self, root: &Path) -> String {
+        self.path
+            .strip_prefix(root)
+            .unwrap_or(&self.path)
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecentSortMode {
+    #[default]
+    Modified,
+    Size,
+    Name,
+}
+
+impl RecentSortMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Modified => Self::Size,
+            Self::Size => Self::Name,
+            Self::Name => Self::Modified,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Modified => "modified",
+            Self::Size => "size",
+            Self::Name => "name",
+        }
+    }
+}
+
+/// Walk `root` and return the `limit` most recently modified files, newest first.
+///
+/// Memory stays bounded by `limit` regardless of tree size. Setting `cancelled`
+/// stops the walk early and returns whatever was collected so far.
+pub fn find_recent_files(
+    root: &Path,
+    limit: usize,
+    progress: Arc<ScanProgress>,
+    cancelled: Arc<AtomicBool>,
+) -> Vec<RecentFile> {
+    // Min-heap on mtime: the oldest kept file sits on top and is evicted first
+    let mut heap: BinaryHeap<Reverse<RecentFile>> = BinaryHeap::with_capacity(limit + 1);
+
+    // Same walker setup as the hash scanner
+    for entry in WalkDir::new(root)
+        .parallelism(jwalk::Parallelism::RayonNewPool(0))
+        .skip_hidden(false)
+        .follow_links(false)
+        .min_depth(1)
+    {
+        if cancelled.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let Ok(e) = entry else { continue };
+
+        if e.file_type().is_dir() {
+            progress.dirs.fetch_add(1, Ordering::Relaxed);
+            continue;
+        }
+        if !e.file_type().is_file() {
+            continue;
+        }
+
+        let Ok(metadata) = e.metadata() else { continue };
+        let Ok(modified) = metadata.modified() else { continue };
+        let size = metadata.len();
+
+        progress.files.fetch_add(1, Ordering::Relaxed);
+        progress.bytes.fetch_add(size, Ordering::Relaxed);
+
+        if limit == 0 {
+            continue;
+        }
+
+        if heap.len() == limit {
+            if let Some(Reverse(oldest)) = heap.peek() {
+                if modified <= oldest.modified {
+                    continue;
+                }
+            }
+            heap.pop();
+        }
+
+        heap.push(Reverse(RecentFile {
+            modified,
+            path: e.path(),
+            size,
+        }));
+    }
+
+    progress.done.store(true, Ordering::Relaxed);
+
+    let mut files: Vec<RecentFile> = heap.into_iter().map(|Reverse(f)| f).collect();
+    sort_recent(&mut files, RecentSortMode::Modified);
+    files
+}
+
+pub fn sort_recent(files: &mut [RecentFile], mode: RecentSortMode) {
+    match mode {
+        RecentSortMode::Modified => files.sort_unstable_by_key(|f| Reverse(f.modified)),
+        RecentSortMode::Size => files.sort_unstable_by_key(|f| Reverse(f.size)),
+        RecentSortMode::Name => files.sort_unstable_by(|a, b| {
+            a.path.to_string_lossy().to_lowercase().cmp(&b.path.to_string_lossy().to_lowercase())
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+
+    #[test]
+    fn test_find_recent_files_keeps_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = SystemTime::now() - Duration::from_secs(3600);
+
+        for i in 0..5u64 {
+            let path = dir.path().join(format!("file{}.txt", i));
+            fs::write(&path, b"data").unwrap();
+            let file = fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(base + Duration::from_secs(i * 60)).unwrap();
+        }
+
+        let progress = Arc::new(ScanProgress::new());
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let files = find_recent_files(dir.path(), 2, Arc::clone(&progress), cancelled);
+
+        assert_eq!(files.len(), 2);
+        assert!(files[0].path.ends_with("file4.txt"));
+        assert!(files[1].path.ends_with("file3.txt"));
+        assert_eq!(progress.get_files(), 5);
+        assert!(progress.is_done());
+    }
+
+    #[test]
+    fn test_sort_recent_by_name() {
+        let now = SystemTime::now();
+        let mut files = vec![
+            RecentFile { modified: now, path: PathBuf::from("/b"), size: 1 },
+            RecentFile { modified: now, path: PathBuf::from("/A"), size: 2 },
+        ];
+        sort_recent(&mut files, RecentSortMode::Name);
+        assert_eq!(files[0].path, PathBuf::from("/A"));
+
+        sort_recent(&mut files, RecentSortMode::Size);
+        assert_eq!(files[0].size, 2);
+    }
+}
@@ -3,9 +3,12 @@
 
 use crate::cleaner::config::Config;
 use crate::cleaner::patterns::PatternMatcher;
+use crate::cleaner::stats::Stats;
+use crate::cleaner::tree::ScanProgress;
 use crossbeam_channel::Sender;
 use jwalk::{Parallelism, WalkDir};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 /// Result of scanning - a path to delete and whether it's a directory
@@ -14,6 +17,63 @@ pub struct ScanResult {
     pub path: PathBuf,
     pub is_dir: bool,
     pub size: u64,
+    /// Bytes allocated on disk, like `du` (0 for directories until measured)
+    pub disk_usage: u64,
+}
+
+/// Bytes a file occupies on disk (512-byte blocks on Unix)
+#[cfg(unix)]
+pub(crate) fn allocated_size(metadata: &std::fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.blocks() * 512
+}
+
+/// No block counts here, so the apparent size stands in
+#[cfg(not(unix))]
+pub(crate) fn allocated_size(metadata: &std::fs::Metadata) -> u64 {
+    metadata.len()
+}
+
+/// Files with several hard links that were already sized, so a file reached through more
+/// than one of its links is counted once
+#[derive(Debug, Default)]
+pub(crate) struct SeenInodes(std::sync::Mutex<std::collections::HashSet<(u64, u64)>>);
+
+impl SeenInodes {
+    /// Whether the file behind `metadata` hasn't been counted yet (always true for a
+    /// single link)
+    #[cfg(unix)]
+    pub(crate) fn first_sight(&self, metadata: &std::fs::Metadata) -> bool {
+        use std::os::unix::fs::MetadataExt;
+        metadata.nlink() <= 1
+            || self.0.lock().map_or(true, |mut seen| seen.insert((metadata.dev(), metadata.ino())))
+    }
+
+    /// Link counts aren't available here, so every link counts
+    #[cfg(not(unix))]
+    pub(crate) fn first_sight(&self, _metadata: &std::fs::Metadata) -> bool {
+        true
+    }
+}
+
+/// Socket, FIFO or block/char device
+///
+/// These are never matched or removed: opening a FIFO blocks until a writer shows up,
+/// and a device node under a build directory is almost certainly not a temp file.
+pub fn is_special(file_type: &std::fs::FileType) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        file_type.is_fifo()
+            || file_type.is_socket()
+            || file_type.is_block_device()
+            || file_type.is_char_device()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = file_type;
+        false
+    }
 }
 
 /// Parallel directory scanner
@@ -22,6 +82,9 @@ pub struct Scanner {
     config: Arc<Config>,
     root: PathBuf,
     num_threads: usize,
+    cancelled: Option<Arc<AtomicBool>>,
+    stats: Option<Arc<Stats>>,
+    progress: Option<Arc<ScanProgress>>,
 }
 
 impl Scanner {
@@ -31,12 +94,42 @@ impl Scanner {
             config,
             root,
             num_threads,
+            cancelled: None,
+            stats: None,
+            progress: None,
         }
     }
 
+    /// Stop walking (and sending matches) once `cancelled` is set
+    pub fn with_cancel(mut self, cancelled: Arc<AtomicBool>) -> Self {
+        self.cancelled = Some(cancelled);
+        self
+    }
+
+    /// Count skipped special files (see [`is_special`]) into `stats`
+    pub fn with_stats(mut self, stats: Arc<Stats>) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
+    /// Count walked files and directories into `progress` while scanning, and mark it
+    /// done at the end, for callers showing their own progress
+    pub fn with_progress(mut self, progress: Arc<ScanProgress>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
     /// Scan directory and send matching paths to channel
     /// Returns total number of entries scanned
     pub fn scan(&self, tx: Sender<ScanResult>) -> usize {
+        let scanned = self.walk(tx);
+        if let Some(ref progress) = self.progress {
+            progress.done.store(true, Ordering::Relaxed);
+        }
+        scanned
+    }
+
+    fn walk(&self, tx: Sender<ScanResult>) -> usize {
         let matcher = Arc::clone(&self.matcher);
         let config_clone = Arc::clone(&self.config);
         let mut scanned = 0;
@@ -92,7 +185,7 @@ impl Scanner {
             .parallelism(Parallelism::RayonNewPool(self.num_threads))
             .skip_hidden(false)
             .follow_links(false)
-            .process_read_dir(move |_depth, _path, _state, children| {
+            .process_read_dir(move |depth, _path, _state, children| {
                 // Skip Docker container on macOS
                 if let Some(ref docker) = *docker_skip_clone {
                     children.retain(|entry| {
@@ -113,37 +206,43 @@ impl Scanner {
                     }
                 });
                 
+                let matcher_clone = Arc::clone(&matcher);
+                
+                // Skip unmatched hidden directories when asked to (--skip-hidden),
+                // but never the root itself (listed with no depth)
+                if depth.is_some() {
+                    children.retain(|entry| match entry {
+                        Ok(e) => !(e.file_type().is_dir() && matcher_clone.skips_hidden_dir(&e.path())),
+                        Err(_) => true,
+                    });
+                }
+                
                 // Mark directories for skip if they match our patterns
                 // This prevents descending into directories we're going to delete
-                let matcher_clone = Arc::clone(&matcher);
                 let days_opt = config_clone.days;
                 
                 children.iter_mut().for_each(|entry| {
                     if let Ok(ref e) = entry {
-                        if e.file_type().is_dir() {
-                            if let Some(name) = e.file_name().to_str() {
-                                if matcher_clone.is_temp_directory(name) {
-                                    // CHECK TIME: If too new, don't delete AND don't skip descending 
-                                    // (treat as normal dir to find potential nested heavy items? 
-                                    // Actually, if we say "don't delete target because recent", 
-                                    // we likely don't want to delete ANYTHING inside it either)
-                                    let should_delete = if let Some(days) = days_opt {
-                                        if let Ok(metadata) = e.metadata() {
-                                            if let Ok(modified) = metadata.modified() {
-                                                if let Ok(elapsed) = modified.elapsed() {
-                                                     elapsed.as_secs() > days * 24 * 60 * 60
-                                                } else { false } // systematic clock issues -> safe default
-                                            } else { false } // no mod time -> safe default
-                                        } else { false } // no metadata -> safe default
-                                    } else {
-                                        true
-                                    };
-
-                                    if should_delete {
-                                        // We'll handle this directory, skip its contents
-                                        let _ = entry.as_mut().map(|e| e.read_children_path = None);
-                                    }
-                                }
+                        if e.file_type().is_dir() && matcher_clone.matches(&e.path(), true) {
+                            // CHECK TIME: If too new, don't delete AND don't skip descending 
+                            // (treat as normal dir to find potential nested heavy items? 
+                            // Actually, if we say "don't delete target because recent", 
+                            // we likely don't want to delete ANYTHING inside it either)
+                            let should_delete = if let Some(days) = days_opt {
+                                if let Ok(metadata) = e.metadata() {
+                                    if let Ok(modified) = metadata.modified() {
+                                        if let Ok(elapsed) = modified.elapsed() {
+                                             elapsed.as_secs() > days * 24 * 60 * 60
+                                        } else { false } // systematic clock issues -> safe default
+                                    } else { false } // no mod time -> safe default
+                                } else { false } // no metadata -> safe default
+                            } else {
+                                true
+                            };
+
+                            if should_delete {
+                                // We'll handle this directory, skip its contents
+                                let _ = entry.as_mut().map(|e| e.read_children_path = None);
                             }
                         }
                     }
@@ -153,11 +252,27 @@ impl Scanner {
         let matcher = Arc::clone(&self.matcher);
 
         for entry in walker {
+            if self.cancelled.as_ref().is_some_and(|c| c.load(Ordering::Relaxed)) {
+                break;
+            }
             scanned += 1;
 
             if let Ok(entry) = entry {
+                if is_special(&entry.file_type()) {
+                    if let Some(ref stats) = self.stats {
+                        stats.add_special();
+                    }
+                    if let Some(ref progress) = self.progress {
+                        progress.special.fetch_add(1, Ordering::Relaxed);
+                    }
+                    continue;
+                }
                 let path = entry.path();
                 let is_dir = entry.file_type().is_dir();
+                if let Some(ref progress) = self.progress {
+                    let counter = if is_dir { &progress.dirs } else { &progress.files };
+                    counter.fetch_add(1, Ordering::Relaxed);
+                }
 
                 if matcher.matches(&path, is_dir) {
                     // Check modification time if configured
@@ -175,17 +290,18 @@ impl Scanner {
 
                     if should_delete {
                         // Calculate size for directories (estimate) or files
-                        let size = if is_dir {
+                        let (size, disk_usage) = if is_dir {
                             // For directories marked for deletion, we'll calculate size during deletion
-                            0
+                            (0, 0)
                         } else {
-                            entry.metadata().map(|m| m.len()).unwrap_or(0)
+                            entry.metadata().map(|m| (m.len(), allocated_size(&m))).unwrap_or((0, 0))
                         };
 
                         let result = ScanResult {
                             path: path.to_path_buf(),
                             is_dir,
                             size,
+                            disk_usage,
                         };
 
                         // Send to deletion channel - ignore send errors (receiver dropped)
@@ -9,7 +9,11 @@ pub struct Stats {
     pub directories_deleted: AtomicUsize,
     pub files_deleted: AtomicUsize,
     pub bytes_freed: AtomicU64,
+    /// Bytes allocated on disk for what was freed, like `du` (the apparent size off Unix)
+    pub disk_bytes_freed: AtomicU64,
     pub errors: AtomicUsize,
+    /// Sockets, FIFOs and device nodes that were left alone
+    pub special_skipped: AtomicUsize,
 }
 
 impl Stats {
@@ -32,11 +36,21 @@ impl Stats {
         self.bytes_freed.fetch_add(bytes, Ordering::Relaxed);
     }
 
+    #[inline]
+    pub fn add_disk_bytes(&self, bytes: u64) {
+        self.disk_bytes_freed.fetch_add(bytes, Ordering::Relaxed);
+    }
+
     #[inline]
     pub fn add_error(&self) {
         self.errors.fetch_add(1, Ordering::Relaxed);
     }
 
+    #[inline]
+    pub fn add_special(&self) {
+        self.special_skipped.fetch_add(1, Ordering::Relaxed);
+    }
+
     pub fn directories(&self) -> usize {
         self.directories_deleted.load(Ordering::Relaxed)
     }
@@ -49,7 +63,15 @@ impl Stats {
         self.bytes_freed.load(Ordering::Relaxed)
     }
 
+    pub fn disk_bytes(&self) -> u64 {
+        self.disk_bytes_freed.load(Ordering::Relaxed)
+    }
+
     pub fn error_count(&self) -> usize {
         self.errors.load(Ordering::Relaxed)
     }
+
+    pub fn special_count(&self) -> usize {
+        self.special_skipped.load(Ordering::Relaxed)
+    }
 }
@@ -0,0 +1,170 @@
+//! Reclaimable space per top-level directory
+//! Groups scan results by the first path component below the scan root
+
+use crate::cleaner::deleter::Deleter;
+use crate::cleaner::scanner::{ScanResult, SeenInodes};
+use crossbeam_channel::Receiver;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::path::{Component, Path};
+
+/// Reclaimable space below one immediate child of the scan root
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReclaimEntry {
+    pub name: String,
+    /// Logical size
+    pub bytes: u64,
+    /// Blocks allocated on disk (the logical size where block counts aren't available)
+    pub disk_bytes: u64,
+    pub directories: usize,
+    pub files: usize,
+}
+
+/// Dry-run totals grouped by top-level directory, biggest first
+#[derive(Debug, Clone, Default)]
+pub struct ReclaimSummary {
+    pub entries: Vec<ReclaimEntry>,
+}
+
+impl ReclaimSummary {
+    /// Drain the scanner channel and total up matched items per top-level child of `root`
+    pub fn collect(root: &Path, rx: Receiver<ScanResult>) -> Self {
+        let items: Vec<ScanResult> = rx.into_iter().collect();
+
+        // Directory sizes need a walk each, do those in parallel; a file hard-linked from
+        // several matches is counted once
+        let seen = SeenInodes::default();
+        let sized: Vec<(ScanResult, (u64, u64))> = items
+            .into_par_iter()
+            .map(|item| {
+                let usage = if item.is_dir {
+                    Deleter::dir_usage_fast(&item.path, &seen)
+                } else {
+                    (item.size, item.disk_usage)
+                };
+                (item, usage)
+            })
+            .collect();
+
+        Self::from_sized(root, sized)
+    }
+
+    fn from_sized(root: &Path, items: Vec<(ScanResult, (u64, u64))>) -> Self {
+        let mut groups: HashMap<String, ReclaimEntry> = HashMap::new();
+
+        for (item, (size, disk_usage)) in items {
+            let name = top_level_name(root, &item.path);
+            let entry = groups.entry(name.clone()).or_insert_with(|| ReclaimEntry {
+                name,
+                ..Default::default()
+            });
+            entry.bytes += size;
+            entry.disk_bytes += disk_usage;
+            if item.is_dir {
+                entry.directories += 1;
+            } else {
+                entry.files += 1;
+            }
+        }
+
+        let mut entries: Vec<ReclaimEntry> = groups.into_values().collect();
+        entries.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+        Self { entries }
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.entries.iter().map(|e| e.bytes).sum()
+    }
+
+    pub fn total_disk_bytes(&self) -> u64 {
+        self.entries.iter().map(|e| e.disk_bytes).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// First path component of `path` below `root` (the path itself if it is not under `root`)
+fn top_level_name(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .ok()
+        .and_then(|rel| rel.components().find_map(|c| match c {
+            Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+            _ => None,
+        }))
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn item(path: &str, is_dir: bool) -> ScanResult {
+        ScanResult { path: PathBuf::from(path), is_dir, size: 0, disk_usage: 0 }
+    }
+
+    #[test]
+    fn test_groups_by_top_level_dir() {
+        let root = Path::new("/home/me/code");
+        let summary = ReclaimSummary::from_sized(root, vec![
+            (item("/home/me/code/web/node_modules", true), (300, 4096)),
+            (item("/home/me/code/web/api/node_modules", true), (200, 4096)),
+            (item("/home/me/code/cli/target", true), (1000, 4096)),
+            (item("/home/me/code/notes/.DS_Store", false), (6, 4096)),
+            (item("/home/me/code/.DS_Store", false), (4, 4096)),
+        ]);
+
+        let names: Vec<&str> = summary.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["cli", "web", "notes", ".DS_Store"]);
+        assert_eq!(summary.entries[1].bytes, 500);
+        assert_eq!(summary.entries[1].disk_bytes, 8192);
+        assert_eq!(summary.entries[1].directories, 2);
+        assert_eq!(summary.entries[2].files, 1);
+        assert_eq!(summary.total_bytes(), 1510);
+        assert_eq!(summary.total_disk_bytes(), 5 * 4096);
+    }
+
+    #[test]
+    fn test_collect_sizes_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("proj/target");
+        std::fs::create_dir_all(target.join("debug")).unwrap();
+        std::fs::write(target.join("debug/a"), vec![0u8; 100]).unwrap();
+        std::fs::write(target.join("b"), vec![0u8; 50]).unwrap();
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        tx.send(ScanResult { path: target, is_dir: true, size: 0, disk_usage: 0 }).unwrap();
+        drop(tx);
+
+        let summary = ReclaimSummary::collect(dir.path(), rx);
+        assert_eq!(summary.entries.len(), 1);
+        assert_eq!((summary.entries[0].name.as_str(), summary.entries[0].bytes), ("proj", 150));
+        assert_eq!((summary.entries[0].directories, summary.entries[0].files), (1, 0));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_collect_counts_hard_links_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a/node_modules"), dir.path().join("b/node_modules"));
+        std::fs::create_dir_all(&a).unwrap();
+        std::fs::create_dir_all(&b).unwrap();
+        std::fs::write(a.join("pkg.js"), vec![1u8; 10_000]).unwrap();
+        std::fs::hard_link(a.join("pkg.js"), a.join("copy.js")).unwrap();
+        std::fs::hard_link(a.join("pkg.js"), b.join("pkg.js")).unwrap();
+        std::fs::write(b.join("own.js"), vec![2u8; 100]).unwrap();
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        for path in [a, b] {
+            tx.send(ScanResult { path, is_dir: true, size: 0, disk_usage: 0 }).unwrap();
+        }
+        drop(tx);
+
+        let summary = ReclaimSummary::collect(dir.path(), rx);
+        assert_eq!(summary.total_bytes(), 10_100);
+        let disk = |path: &str| crate::cleaner::scanner::allocated_size(&std::fs::metadata(dir.path().join(path)).unwrap());
+        assert_eq!(summary.total_disk_bytes(), disk("a/node_modules/pkg.js") + disk("b/node_modules/own.js"));
+    }
+}
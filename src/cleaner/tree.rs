@@ -1,10 +1,16 @@
 //! Directory tree with MAXIMUM PERFORMANCE single-pass scan
 //! Single WalkDir, no duplicate syscalls, O(n) everywhere
 
+use crate::cleaner::config::Config;
+use crate::cleaner::deleter::{DeleteEvent, Deleter, LogLevel};
 use crate::cleaner::patterns::PatternMatcher;
+use crate::cleaner::scanner::{allocated_size, is_special, Scanner};
+use crate::cleaner::stats::Stats;
+use crate::fs::{FileEntry, FsResult, StorageBackend};
+use futures::stream::{FuturesUnordered, StreamExt};
 use jwalk::WalkDir;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering};
 use std::sync::Arc;
 
@@ -12,15 +18,69 @@ use std::sync::Arc;
 pub struct DirEntry {
     pub path: PathBuf,
     pub name: String,
+    /// Apparent size: the file length (summed for directories), like `ls`
     pub size: u64,
+    /// Bytes allocated on disk for the files, like `du`; differs for sparse and tiny files (same as
+    /// `size` where block counts are unknown: remote backends, non-Unix systems)
+    pub disk_usage: u64,
     pub is_dir: bool,
     pub is_temp: bool,
 }
 
+impl DirEntry {
+    /// Size under the chosen metric
+    pub fn size_of(&self, metric: SizeMetric) -> u64 {
+        match metric {
+            SizeMetric::Apparent => self.size,
+            SizeMetric::Disk => self.disk_usage,
+        }
+    }
+}
+
+/// Environment variable choosing the analyzer's size metric (`apparent` or `disk`)
+pub const SIZE_METRIC_ENV: &str = "ABYSS_ANALYZER_SIZE";
+
+/// Which size the disk analyzer shows, totals and sorts by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SizeMetric {
+    /// File lengths, like `ls -l`
+    #[default]
+    Apparent,
+    /// Allocated blocks, like `du`
+    Disk,
+}
+
+impl SizeMetric {
+    /// Metric from `ABYSS_ANALYZER_SIZE`, apparent when unset or unrecognized
+    pub fn from_env() -> Self {
+        match std::env::var(SIZE_METRIC_ENV).map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            Ok("disk") | Ok("du") => SizeMetric::Disk,
+            _ => SizeMetric::Apparent,
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            SizeMetric::Apparent => SizeMetric::Disk,
+            SizeMetric::Disk => SizeMetric::Apparent,
+        }
+    }
+
+    /// Header label
+    pub fn label(self) -> &'static str {
+        match self {
+            SizeMetric::Apparent => "apparent",
+            SizeMetric::Disk => "disk usage",
+        }
+    }
+}
+
 pub struct ScanProgress {
     pub files: AtomicUsize,
     pub dirs: AtomicUsize,
     pub bytes: AtomicU64,
+    /// Sockets, FIFOs and device nodes left out of the tree
+    pub special: AtomicUsize,
     pub done: AtomicBool,
     pub phase: AtomicU8,
 }
@@ -31,6 +91,7 @@ impl ScanProgress {
             files: AtomicUsize::new(0),
             dirs: AtomicUsize::new(0),
             bytes: AtomicU64::new(0),
+            special: AtomicUsize::new(0),
             done: AtomicBool::new(false),
             phase: AtomicU8::new(0),
         }
@@ -39,6 +100,7 @@ impl ScanProgress {
     pub fn get_files(&self) -> usize { self.files.load(Ordering::Relaxed) }
     pub fn get_dirs(&self) -> usize { self.dirs.load(Ordering::Relaxed) }
     pub fn get_bytes(&self) -> u64 { self.bytes.load(Ordering::Relaxed) }
+    pub fn get_special(&self) -> usize { self.special.load(Ordering::Relaxed) }
     pub fn is_done(&self) -> bool { self.done.load(Ordering::Relaxed) }
     pub fn get_phase(&self) -> u8 { self.phase.load(Ordering::Relaxed) }
 }
@@ -55,9 +117,43 @@ struct RawEntry {
     parent: PathBuf,
     name: String,
     size: u64,
+    disk_usage: u64,
     is_dir: bool,
 }
 
+/// Summed apparent size and disk usage of a directory
+type DirSizes = HashMap<PathBuf, (u64, u64)>;
+
+/// Directories listed at once when walking a storage backend
+const BACKEND_LIST_CONCURRENCY: usize = 8;
+
+/// Add a file's sizes to every directory from its parent up to `root`
+fn add_to_parents(dir_sizes: &mut DirSizes, path: &Path, root: &Path, size: u64, disk_usage: u64) {
+    let mut current = path.parent();
+    while let Some(dir) = current {
+        let sums = dir_sizes.entry(dir.to_path_buf()).or_insert((0, 0));
+        sums.0 += size;
+        sums.1 += disk_usage;
+        if dir == root { break; }
+        current = dir.parent();
+    }
+}
+
+/// Join a backend path and a child name the way panes build paths
+fn join_backend_path(dir: &str, name: &str) -> String {
+    if dir.ends_with('/') || dir.is_empty() {
+        format!("{}{}", dir, name)
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+/// List `dir` with hidden entries too; whether dot-directories are scanned is up to the matcher
+async fn list_backend_dir(backend: Arc<dyn StorageBackend>, dir: String) -> (String, FsResult<Vec<FileEntry>>) {
+    let result = backend.list_dir_all(&dir).await;
+    (dir, result)
+}
+
 pub struct DirTree {
     pub children: HashMap<PathBuf, Vec<DirEntry>>,
 }
@@ -70,11 +166,9 @@ impl DirTree {
         progress: Arc<ScanProgress>,
         cancelled: Arc<AtomicBool>,
     ) -> Self {
-        let mut children: HashMap<PathBuf, Vec<DirEntry>> = HashMap::new();
-
         // SINGLE PASS: Collect all entries with parallel jwalk
         let mut entries: Vec<RawEntry> = Vec::new();
-        let mut dir_sizes: HashMap<PathBuf, u64> = HashMap::new();
+        let mut dir_sizes = DirSizes::new();
 
         // macOS Docker exclusion: sparse disk image reports wrong sizes
         #[cfg(target_os = "macos")]
@@ -94,14 +188,27 @@ impl DirTree {
         #[cfg(not(target_os = "macos"))]
         let docker_path: Option<PathBuf> = None;
 
+        // Unmatched hidden directories are left out entirely with --skip-hidden
+        let skip_hidden = matcher.clone();
+
         // Use jwalk with parallelism enabled
         for entry in WalkDir::new(root)
             .parallelism(jwalk::Parallelism::RayonNewPool(num_cpus::get()))
             .skip_hidden(false)
+            .process_read_dir(move |depth, _path, _state, children| {
+                // No depth: the listing holding the root itself, which is always walked
+                if depth.is_none() {
+                    return;
+                }
+                children.retain(|entry| match entry {
+                    Ok(e) => !(e.file_type().is_dir() && skip_hidden.skips_hidden_dir(&e.path())),
+                    Err(_) => true,
+                });
+            })
             .min_depth(1) {
             if cancelled.load(Ordering::Relaxed) {
                 progress.done.store(true, Ordering::Relaxed);
-                return Self { children };
+                return Self { children: HashMap::new() };
             }
 
             if let Ok(e) = entry {
@@ -114,28 +221,30 @@ impl DirTree {
                     }
                 }
 
+                // Never stat or list special files; they can't be cleaned anyway
+                if is_special(&e.file_type()) {
+                    progress.special.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+
                 let is_dir = e.file_type().is_dir(); // Already cached by jwalk!
                 let name = path
                     .file_name()
                     .map(|n| n.to_string_lossy().to_string())
                     .unwrap_or_default();
 
-                let size = if is_dir {
+                let (size, disk_usage) = if is_dir {
                     progress.dirs.fetch_add(1, Ordering::Relaxed);
-                    0 // Will calculate later
+                    (0, 0) // Will calculate later
                 } else {
-                    let s = e.metadata().map(|m| m.len()).unwrap_or(0);
+                    // Block counts come with the same stat, so both metrics cost nothing extra
+                    let (s, d) = e.metadata().map(|m| (m.len(), allocated_size(&m))).unwrap_or((0, 0));
                     progress.files.fetch_add(1, Ordering::Relaxed);
                     progress.bytes.fetch_add(s, Ordering::Relaxed);
                     
                     // Aggregate to parent directories immediately
-                    let mut current = path.parent();
-                    while let Some(dir) = current {
-                        *dir_sizes.entry(dir.to_path_buf()).or_insert(0) += s;
-                        if dir == root.as_path() { break; }
-                        current = dir.parent();
-                    }
-                    s
+                    add_to_parents(&mut dir_sizes, &path, root, s, d);
+                    (s, d)
                 };
 
                 if let Some(parent) = path.parent() {
@@ -145,6 +254,7 @@ impl DirTree {
                         parent: parent_buf,
                         name,
                         size,
+                        disk_usage,
                         is_dir,
                     });
                 }
@@ -153,17 +263,104 @@ impl DirTree {
 
         if cancelled.load(Ordering::Relaxed) {
             progress.done.store(true, Ordering::Relaxed);
-            return Self { children };
+            return Self { children: HashMap::new() };
         }
 
+        Self::from_entries(root, entries, dir_sizes, matcher, &progress)
+    }
+
+    /// Build the tree by walking a storage backend with `list_dir` (K8s PVCs, buckets).
+    /// Sizes come from `FileEntry.size`; local directories should use `build_with_progress`.
+    pub async fn build_from_backend(
+        backend: Arc<dyn StorageBackend>,
+        root: &str,
+        matcher: &PatternMatcher,
+        progress: Arc<ScanProgress>,
+        cancelled: Arc<AtomicBool>,
+    ) -> Self {
+        let root_path = PathBuf::from(root);
+        let mut entries: Vec<RawEntry> = Vec::new();
+        let mut dir_sizes = DirSizes::new();
+
+        // Remote listings are slow (an exec per directory on K8s), keep several in flight
+        let mut pending = vec![root.to_string()];
+        let mut listing = FuturesUnordered::new();
+
+        loop {
+            while listing.len() < BACKEND_LIST_CONCURRENCY {
+                match pending.pop() {
+                    Some(dir) => listing.push(list_backend_dir(Arc::clone(&backend), dir)),
+                    None => break,
+                }
+            }
+
+            let Some((dir, result)) = listing.next().await else { break };
+
+            if cancelled.load(Ordering::Relaxed) {
+                progress.done.store(true, Ordering::Relaxed);
+                return Self { children: HashMap::new() };
+            }
+
+            // Unreadable directories are skipped, like walk errors on the local path
+            let Ok(list) = result else { continue };
+
+            let parent = PathBuf::from(&dir);
+            let names: Vec<String> = list.iter().map(|entry| entry.name.clone()).collect();
+            for entry in list {
+                if entry.name == "." || entry.name == ".." {
+                    continue;
+                }
+
+                let child = join_backend_path(&dir, &entry.name);
+                let path = PathBuf::from(&child);
+                if entry.is_dir && matcher.skips_hidden_name(&entry.name, names.iter().map(String::as_str)) {
+                    continue;
+                }
+
+                let size = if entry.is_dir {
+                    progress.dirs.fetch_add(1, Ordering::Relaxed);
+                    pending.push(child);
+                    0 // Will calculate later
+                } else {
+                    progress.files.fetch_add(1, Ordering::Relaxed);
+                    progress.bytes.fetch_add(entry.size, Ordering::Relaxed);
+                    // Backends only report lengths
+                    add_to_parents(&mut dir_sizes, &path, &root_path, entry.size, entry.size);
+                    entry.size
+                };
+
+                entries.push(RawEntry {
+                    path,
+                    parent: parent.clone(),
+                    name: entry.name,
+                    size,
+                    disk_usage: size,
+                    is_dir: entry.is_dir,
+                });
+            }
+        }
+
+        Self::from_entries(&root_path, entries, dir_sizes, matcher, &progress)
+    }
+
+    /// Second phase shared by both walks: group entries by parent, flag temp items, sort
+    fn from_entries(
+        root: &Path,
+        entries: Vec<RawEntry>,
+        dir_sizes: DirSizes,
+        matcher: &PatternMatcher,
+        progress: &ScanProgress,
+    ) -> Self {
+        let mut children: HashMap<PathBuf, Vec<DirEntry>> = HashMap::new();
+
         progress.phase.store(1, Ordering::Relaxed);
 
         // Build children map - single pass through collected entries
         for e in entries {
-            let size = if e.is_dir {
-                *dir_sizes.get(&e.path).unwrap_or(&0)
+            let (size, disk_usage) = if e.is_dir {
+                dir_sizes.get(&e.path).copied().unwrap_or((0, 0))
             } else {
-                e.size
+                (e.size, e.disk_usage)
             };
 
             let is_temp = if e.is_dir {
@@ -176,11 +373,20 @@ impl DirTree {
                 path: e.path,
                 name: e.name,
                 size,
+                disk_usage,
                 is_dir: e.is_dir,
                 is_temp,
             });
         }
 
+        // Marker rules are checked against the listed siblings, so remote trees work too
+        for entries in children.values_mut() {
+            let names: Vec<String> = entries.iter().map(|e| e.name.clone()).collect();
+            for entry in entries.iter_mut().filter(|e| e.is_dir && e.is_temp) {
+                entry.is_temp = matcher.has_marker(&entry.name, names.iter().map(|n| n.as_str()));
+            }
+        }
+
         // Sort and add ".." navigation
         for (dir_path, entries) in children.iter_mut() {
             entries.sort_unstable_by(|a, b| {
@@ -197,6 +403,7 @@ impl DirTree {
                         path: parent.to_path_buf(),
                         name: "..".to_string(),
                         size: 0,
+                        disk_usage: 0,
                         is_dir: true,
                         is_temp: false,
                     });
@@ -212,6 +419,105 @@ impl DirTree {
         self.children.get(path).cloned().unwrap_or_default()
     }
 
+    /// Size recorded for `path`, if the tree has it
+    pub fn entry_size(&self, path: &Path) -> Option<u64> {
+        self.entry(path).map(|e| e.size)
+    }
+
+    fn entry(&self, path: &Path) -> Option<&DirEntry> {
+        let parent = path.parent()?;
+        self.children
+            .get(parent)?
+            .iter()
+            .find(|e| e.path == path && e.name != "..")
+    }
+
+    /// Bytes a clean of `dir` would free: temp entries, plus temp entries nested in regular dirs
+    pub fn reclaimable_size(&self, dir: &Path) -> u64 {
+        self.children
+            .get(dir)
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter(|e| e.name != "..")
+                    .map(|e| {
+                        if e.is_temp {
+                            e.size
+                        } else if e.is_dir {
+                            self.reclaimable_size(&e.path)
+                        } else {
+                            0
+                        }
+                    })
+                    .sum()
+            })
+            .unwrap_or(0)
+    }
+
+    /// Clean everything the scanner matches under `root`, counting into `stats` as items go
+    ///
+    /// Directory sizes are taken from the tree, so `stats.bytes()` can be shown against
+    /// `reclaimable_size(root)` while the clean runs. Returns false if `cancelled` was set
+    /// before it finished.
+    pub fn clean_with_progress(
+        &self,
+        root: &Path,
+        config: Arc<Config>,
+        stats: Arc<Stats>,
+        cancelled: Arc<AtomicBool>,
+    ) -> bool {
+        self.run_clean(root, config, stats, cancelled, false, None)
+    }
+
+    /// Dry run of [`clean_with_progress`](Self::clean_with_progress): nothing is deleted,
+    /// each match is counted in `stats` and sent to `events` as [`DeleteEvent::Removed`]
+    pub fn simulate_clean(
+        &self,
+        root: &Path,
+        config: Arc<Config>,
+        stats: Arc<Stats>,
+        cancelled: Arc<AtomicBool>,
+        events: crossbeam_channel::Sender<DeleteEvent>,
+    ) -> bool {
+        self.run_clean(root, config, stats, cancelled, true, Some(events))
+    }
+
+    fn run_clean(
+        &self,
+        root: &Path,
+        config: Arc<Config>,
+        stats: Arc<Stats>,
+        cancelled: Arc<AtomicBool>,
+        dry_run: bool,
+        events: Option<crossbeam_channel::Sender<DeleteEvent>>,
+    ) -> bool {
+        let (tx, rx) = crossbeam_channel::bounded(256);
+        let scanner = Scanner::new(root.to_path_buf(), num_cpus::get(), config)
+            .with_cancel(Arc::clone(&cancelled))
+            .with_stats(Arc::clone(&stats));
+        // Errors are counted in stats; printing would corrupt the TUI
+        let mut deleter = Deleter::new(stats, dry_run, LogLevel::Quiet)
+            .with_known_sizes()
+            .with_cancel(Arc::clone(&cancelled));
+        if let Some(events) = events {
+            deleter = deleter.with_events(events);
+        }
+
+        std::thread::scope(|s| {
+            s.spawn(move || scanner.scan(tx));
+            deleter.process_iter(rx.into_iter().map(|mut item| {
+                if item.is_dir {
+                    let entry = self.entry(&item.path);
+                    item.size = entry.map_or(0, |e| e.size);
+                    item.disk_usage = entry.map_or(0, |e| e.disk_usage);
+                }
+                item
+            }));
+        });
+
+        !cancelled.load(Ordering::Relaxed)
+    }
+
     /// Remove entry from the tree and update all parent sizes (O(depth))
     pub fn delete_entry(&mut self, path: &PathBuf, is_dir: bool) {
         if let Some(parent) = path.parent() {
@@ -222,6 +528,7 @@ impl DirTree {
                 if let Some(idx) = entries.iter().position(|e| &e.path == path) {
                     let removed = entries.remove(idx);
                     let size_removed = removed.size;
+                    let disk_removed = removed.disk_usage;
 
                     // 2. Propagate size change up the tree
                     let mut current_parent = parent_buf.clone();
@@ -232,6 +539,7 @@ impl DirTree {
                              if let Some(siblings) = self.children.get_mut(&grandparent_buf) {
                                 if let Some(parent_entry) = siblings.iter_mut().find(|e| e.path == current_parent) {
                                     parent_entry.size = parent_entry.size.saturating_sub(size_removed);
+                                    parent_entry.disk_usage = parent_entry.disk_usage.saturating_sub(disk_removed);
                                 }
                              }
                              current_parent = grandparent_buf;
@@ -250,14 +558,14 @@ impl DirTree {
     }
 }
 
-pub fn sort_by_size(entries: &mut [DirEntry]) {
+pub fn sort_by_size(entries: &mut [DirEntry], metric: SizeMetric) {
     entries.sort_unstable_by(|a, b| {
         if a.name == ".." { return std::cmp::Ordering::Less; }
         if b.name == ".." { return std::cmp::Ordering::Greater; }
         match (a.is_dir, b.is_dir) {
             (true, false) => std::cmp::Ordering::Less,
             (false, true) => std::cmp::Ordering::Greater,
-            _ => b.size.cmp(&a.size),
+            _ => b.size_of(metric).cmp(&a.size_of(metric)),
         }
     });
 }
@@ -273,3 +581,255 @@ pub fn sort_by_name(entries: &mut [DirEntry]) {
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cleaner::Config;
+    use crate::fs::LocalBackend;
+
+    #[tokio::test]
+    async fn test_build_from_backend_sizes_and_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("app/node_modules/pkg")).unwrap();
+        std::fs::write(dir.path().join("app/node_modules/pkg/index.js"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("app/main.js"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), vec![0u8; 5]).unwrap();
+
+        let root = dir.path().to_string_lossy().to_string();
+        let backend: Arc<dyn StorageBackend> = Arc::new(LocalBackend::new(dir.path().to_path_buf()));
+        let matcher = PatternMatcher::new(Arc::new(Config::default()));
+        let progress = Arc::new(ScanProgress::new());
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let tree = DirTree::build_from_backend(backend, &root, &matcher, Arc::clone(&progress), cancelled).await;
+
+        assert!(progress.is_done());
+        assert_eq!(progress.get_files(), 3);
+        assert_eq!(progress.get_dirs(), 3);
+        assert_eq!(progress.get_bytes(), 405);
+
+        let top = tree.get_children(&PathBuf::from(&root));
+        let app = top.iter().find(|e| e.name == "app").unwrap();
+        assert!(app.is_dir);
+        assert_eq!(app.size, 400);
+
+        let nested = tree.get_children(&dir.path().join("app"));
+        assert_eq!(nested[0].name, "..");
+        let node_modules = nested.iter().find(|e| e.name == "node_modules").unwrap();
+        assert_eq!(node_modules.size, 300);
+        assert!(node_modules.is_temp);
+    }
+
+    #[tokio::test]
+    async fn test_build_from_backend_checks_hidden_markers_in_the_listing() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("infra/.terraform")).unwrap();
+        std::fs::write(dir.path().join("infra/.terraform/plugin"), vec![0u8; 20]).unwrap();
+        std::fs::write(dir.path().join("infra/main.tf"), b"").unwrap();
+        std::fs::create_dir_all(dir.path().join("infra/.config")).unwrap();
+
+        // A relative root resolves under the backend's root, never against the local disk
+        let backend: Arc<dyn StorageBackend> = Arc::new(LocalBackend::new(dir.path().to_path_buf()));
+        let mut config = Config { skip_hidden: true, require_markers: true, ..Config::default() };
+        config.markers.insert(".terraform".to_string(), vec!["*.tf".to_string()]);
+        let matcher = PatternMatcher::new(Arc::new(config));
+        let progress = Arc::new(ScanProgress::new());
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let tree = DirTree::build_from_backend(backend, "infra", &matcher, progress, cancelled).await;
+
+        let names: Vec<_> = tree.get_children(&PathBuf::from("infra")).iter().map(|e| e.name.clone()).collect();
+        assert!(names.contains(&".terraform".to_string()), "{:?}", names);
+        assert!(!names.contains(&".config".to_string()), "{:?}", names);
+        assert_eq!(tree.reclaimable_size(Path::new("infra")), 20);
+    }
+
+    fn build_local(root: &Path) -> DirTree {
+        let matcher = PatternMatcher::new(Arc::new(Config::default()));
+        DirTree::build_with_progress(
+            &root.to_path_buf(),
+            &matcher,
+            Arc::new(ScanProgress::new()),
+            Arc::new(AtomicBool::new(false)),
+        )
+    }
+
+    #[test]
+    fn test_build_skip_hidden_leaves_out_unmatched_dot_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".config/node_modules")).unwrap();
+        std::fs::write(dir.path().join(".config/node_modules/index.js"), vec![0u8; 40]).unwrap();
+        std::fs::create_dir_all(dir.path().join(".terraform")).unwrap();
+        std::fs::write(dir.path().join(".terraform/plugin"), vec![0u8; 20]).unwrap();
+        std::fs::write(dir.path().join(".env"), vec![0u8; 3]).unwrap();
+
+        let config = Config { skip_hidden: true, ..Config::default() };
+        let tree = DirTree::build_with_progress(
+            &dir.path().to_path_buf(),
+            &PatternMatcher::new(Arc::new(config)),
+            Arc::new(ScanProgress::new()),
+            Arc::new(AtomicBool::new(false)),
+        );
+
+        let names: Vec<_> = tree.get_children(&dir.path().to_path_buf()).iter().map(|e| e.name.clone()).collect();
+        assert!(!names.contains(&".config".to_string()));
+        assert!(names.contains(&".terraform".to_string()));
+        assert!(names.contains(&".env".to_string()));
+        assert_eq!(tree.reclaimable_size(dir.path()), 20);
+    }
+
+    #[test]
+    fn test_clean_with_progress_counts_tree_sizes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("app/node_modules/pkg")).unwrap();
+        std::fs::write(dir.path().join("app/node_modules/pkg/index.js"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("app/main.js"), vec![0u8; 100]).unwrap();
+        std::fs::create_dir_all(dir.path().join("__pycache__")).unwrap();
+        std::fs::write(dir.path().join("__pycache__/mod.pyc"), vec![0u8; 50]).unwrap();
+
+        let tree = build_local(dir.path());
+        assert_eq!(tree.reclaimable_size(dir.path()), 350);
+        assert_eq!(tree.entry_size(&dir.path().join("app/node_modules")), Some(300));
+
+        let stats = Arc::new(Stats::new());
+        let done = tree.clean_with_progress(
+            dir.path(),
+            Arc::new(Config::default()),
+            Arc::clone(&stats),
+            Arc::new(AtomicBool::new(false)),
+        );
+
+        assert!(done);
+        assert_eq!(stats.directories(), 2);
+        assert_eq!(stats.bytes(), 350);
+        assert!(!dir.path().join("app/node_modules").exists());
+        assert!(dir.path().join("app/main.js").exists());
+    }
+
+    #[test]
+    fn test_clean_with_progress_cancelled_deletes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("node_modules/pkg")).unwrap();
+        std::fs::write(dir.path().join("node_modules/pkg/index.js"), vec![0u8; 10]).unwrap();
+
+        let tree = build_local(dir.path());
+        let stats = Arc::new(Stats::new());
+        let done = tree.clean_with_progress(
+            dir.path(),
+            Arc::new(Config::default()),
+            Arc::clone(&stats),
+            Arc::new(AtomicBool::new(true)),
+        );
+
+        assert!(!done);
+        assert_eq!(stats.directories() + stats.files(), 0);
+        assert!(dir.path().join("node_modules/pkg/index.js").exists());
+    }
+
+    #[test]
+    fn test_simulate_clean_reports_matches_and_deletes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("app/node_modules/pkg")).unwrap();
+        std::fs::write(dir.path().join("app/node_modules/pkg/index.js"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("app/main.js"), vec![0u8; 100]).unwrap();
+
+        let tree = build_local(dir.path());
+        let stats = Arc::new(Stats::new());
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let done = tree.simulate_clean(
+            dir.path(),
+            Arc::new(Config::default()),
+            Arc::clone(&stats),
+            Arc::new(AtomicBool::new(false)),
+            tx,
+        );
+
+        assert!(done);
+        assert_eq!(stats.directories(), 1);
+        assert_eq!(stats.bytes(), 300);
+        let events: Vec<DeleteEvent> = rx.try_iter().collect();
+        assert_eq!(
+            events,
+            vec![DeleteEvent::Removed { path: dir.path().join("app/node_modules"), is_dir: true, bytes: 300 }]
+        );
+        assert!(dir.path().join("app/node_modules/pkg/index.js").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sparse_file_apparent_size_and_disk_usage_differ() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("images")).unwrap();
+        // 64 MiB of holes with no data written
+        let sparse = std::fs::File::create(dir.path().join("images/disk.img")).unwrap();
+        sparse.set_len(64 * 1024 * 1024).unwrap();
+        std::fs::write(dir.path().join("images/data.bin"), vec![1u8; 64 * 1024]).unwrap();
+
+        let tree = build_local(dir.path());
+        let files = tree.get_children(&dir.path().join("images"));
+        let image = files.iter().find(|e| e.name == "disk.img").unwrap().clone();
+        assert_eq!(image.size_of(SizeMetric::Apparent), 64 * 1024 * 1024);
+        assert!(image.size_of(SizeMetric::Disk) < 1024 * 1024, "{}", image.disk_usage);
+        let data = files.iter().find(|e| e.name == "data.bin").unwrap().clone();
+        assert!(data.disk_usage >= 64 * 1024);
+
+        // Directories sum both metrics, and sorting follows the chosen one
+        let mut top = tree.get_children(&dir.path().to_path_buf());
+        let images = top.iter().find(|e| e.name == "images").unwrap();
+        assert_eq!(images.size, 64 * 1024 * 1024 + 64 * 1024);
+        assert_eq!(images.disk_usage, image.disk_usage + data.disk_usage);
+        let mut files = files;
+        sort_by_size(&mut files, SizeMetric::Apparent);
+        assert_eq!(files[1].name, "disk.img"); // after ".."
+        sort_by_size(&mut files, SizeMetric::Disk);
+        assert_eq!(files[1].name, "data.bin");
+
+        // Deleting keeps both sums in step
+        let mut tree = tree;
+        tree.delete_entry(&dir.path().join("images/data.bin"), false);
+        top = tree.get_children(&dir.path().to_path_buf());
+        let images = top.iter().find(|e| e.name == "images").unwrap();
+        assert_eq!(images.disk_usage, image.disk_usage);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_fifo_is_skipped_not_opened() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/mod.pyc"), vec![0u8; 20]).unwrap();
+        // Named like a temp file, so only the file type keeps it from being matched
+        let fifo = dir.path().join("src/pipe.pyc");
+        let c_path = std::ffi::CString::new(fifo.to_string_lossy().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o644) }, 0);
+
+        let matcher = PatternMatcher::new(Arc::new(Config::default()));
+        let progress = Arc::new(ScanProgress::new());
+        let tree = DirTree::build_with_progress(
+            &dir.path().to_path_buf(),
+            &matcher,
+            Arc::clone(&progress),
+            Arc::new(AtomicBool::new(false)),
+        );
+        assert_eq!(progress.get_special(), 1);
+        assert_eq!(progress.get_files(), 1);
+        assert_eq!(tree.reclaimable_size(dir.path()), 20);
+
+        let stats = Arc::new(Stats::new());
+        let done = tree.clean_with_progress(
+            dir.path(),
+            Arc::new(Config::default()),
+            Arc::clone(&stats),
+            Arc::new(AtomicBool::new(false)),
+        );
+
+        assert!(done);
+        assert_eq!(stats.files(), 1);
+        assert_eq!(stats.special_count(), 1);
+        assert_eq!(stats.error_count(), 0);
+        assert!(!dir.path().join("src/mod.pyc").exists());
+        use std::os::unix::fs::FileTypeExt;
+        assert!(std::fs::symlink_metadata(&fifo).unwrap().file_type().is_fifo());
+    }
+}
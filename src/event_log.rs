@@ -0,0 +1,281 @@
+//! Structured event stream for automation
+//!
+//! With `--events <PATH>` (or `ABYSS_EVENTS`), copies, moves, deletes, cleans and syncs
+//! report when they start and how they end as newline-delimited JSON, one [`Event`] per
+//! line. `-` writes the stream to stderr. This is separate from the human-readable output
+//! and the TUI's log panel, and its fields only ever grow.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Environment variable naming the event stream target when `--events` is not given
+pub const EVENTS_ENV: &str = "ABYSS_EVENTS";
+
+/// Version written with every event; bumped only if a field changes meaning or goes away
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// What kind of operation an event belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Copy,
+    Move,
+    Delete,
+    Clean,
+    Sync,
+}
+
+/// Counters of a finished operation; those that don't apply to it stay 0
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OperationStats {
+    /// Files copied, moved or deleted
+    pub files: usize,
+    /// Directories created (sync) or deleted (delete, clean)
+    pub directories: usize,
+    /// Bytes transferred or freed
+    pub bytes: u64,
+    /// Items that failed without stopping the operation
+    pub errors: usize,
+    /// Items left alone (sync: up to date or excluded)
+    pub skipped: usize,
+    /// Sync conflicts left unresolved
+    pub conflicts: usize,
+    /// Average transfer rate of a copy in MB/s
+    #[serde(default)]
+    pub throughput_mbps: f64,
+}
+
+/// One line of the event stream
+///
+/// `source` is set for operations that read from one location and write to another
+/// (copy, move, sync); `target` is what is written to, deleted or cleaned.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    Started {
+        op: Operation,
+        source: Option<String>,
+        target: String,
+        dry_run: bool,
+    },
+    Completed {
+        op: Operation,
+        source: Option<String>,
+        target: String,
+        dry_run: bool,
+        stats: OperationStats,
+        elapsed_secs: f64,
+    },
+    Failed {
+        op: Operation,
+        source: Option<String>,
+        target: String,
+        error: String,
+        elapsed_secs: f64,
+    },
+}
+
+/// An event as written: schema version and timestamp next to the event's own fields
+#[derive(Serialize)]
+struct Record<'a> {
+    version: u32,
+    ts: String,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+/// Sink for the event stream, shared by everything that reports events
+pub struct EventLog {
+    out: Mutex<Box<dyn Write + Send>>,
+}
+
+impl EventLog {
+    /// Stream to stderr for `-`, otherwise append to the file at `target`
+    pub fn open(target: &str) -> io::Result<Self> {
+        if target == "-" {
+            return Ok(Self::from_writer(io::stderr()));
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Path::new(target))?;
+        Ok(Self::from_writer(file))
+    }
+
+    /// Target from `--events`, else `ABYSS_EVENTS`; `None` when neither is set
+    pub fn target_from_arg_or_env(arg: Option<&str>) -> Option<String> {
+        match arg {
+            Some(target) => Some(target.to_string()),
+            None => std::env::var(EVENTS_ENV).ok().filter(|target| !target.trim().is_empty()),
+        }
+    }
+
+    /// Stream opened at `target_from_arg_or_env`, if there is one
+    pub fn from_arg_or_env(arg: Option<&str>) -> io::Result<Option<Arc<Self>>> {
+        match Self::target_from_arg_or_env(arg) {
+            Some(target) => Self::open(&target).map(|log| Some(Arc::new(log))),
+            None => Ok(None),
+        }
+    }
+
+    pub fn from_writer(writer: impl Write + Send + 'static) -> Self {
+        Self { out: Mutex::new(Box::new(writer)) }
+    }
+
+    /// Write `event` as one line and flush it
+    ///
+    /// Write failures are ignored: the stream never fails the operation it describes.
+    pub fn emit(&self, event: &Event) {
+        let record = Record {
+            version: SCHEMA_VERSION,
+            ts: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            event,
+        };
+        let Ok(mut line) = serde_json::to_string(&record) else {
+            return;
+        };
+        line.push('\n');
+        if let Ok(mut out) = self.out.lock() {
+            let _ = out.write_all(line.as_bytes()).and_then(|_| out.flush());
+        }
+    }
+}
+
+/// One operation's events: `Started` when created, then `Completed` or `Failed`
+///
+/// Holds an optional log so callers can report unconditionally; without one nothing is written.
+pub struct OperationEvents {
+    log: Option<Arc<EventLog>>,
+    op: Operation,
+    source: Option<String>,
+    target: String,
+    dry_run: bool,
+    started: Instant,
+}
+
+impl OperationEvents {
+    pub fn start(
+        log: Option<Arc<EventLog>>,
+        op: Operation,
+        source: Option<String>,
+        target: String,
+        dry_run: bool,
+    ) -> Self {
+        let events = Self { log, op, source, target, dry_run, started: Instant::now() };
+        events.emit(Event::Started {
+            op,
+            source: events.source.clone(),
+            target: events.target.clone(),
+            dry_run,
+        });
+        events
+    }
+
+    pub fn completed(&self, stats: OperationStats) {
+        self.emit(Event::Completed {
+            op: self.op,
+            source: self.source.clone(),
+            target: self.target.clone(),
+            dry_run: self.dry_run,
+            stats,
+            elapsed_secs: self.started.elapsed().as_secs_f64(),
+        });
+    }
+
+    pub fn failed(&self, error: impl Display) {
+        self.emit(Event::Failed {
+            op: self.op,
+            source: self.source.clone(),
+            target: self.target.clone(),
+            error: error.to_string(),
+            elapsed_secs: self.started.elapsed().as_secs_f64(),
+        });
+    }
+
+    fn emit(&self, event: Event) {
+        if let Some(ref log) = self.log {
+            log.emit(&event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writer whose output stays readable after it is handed to the log
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn lines(out: &Shared) -> Vec<serde_json::Value> {
+        String::from_utf8(out.0.lock().unwrap().clone())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_operation_writes_started_then_completed() {
+        let out = Shared::default();
+        let log = Arc::new(EventLog::from_writer(out.clone()));
+
+        let events = OperationEvents::start(
+            Some(log),
+            Operation::Sync,
+            Some("/src".to_string()),
+            "s3://bucket/dst".to_string(),
+            false,
+        );
+        events.completed(OperationStats { files: 3, bytes: 42, throughput_mbps: 12.5, ..Default::default() });
+
+        let lines = lines(&out);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["event"], "started");
+        assert_eq!(lines[0]["version"], SCHEMA_VERSION);
+        assert_eq!(lines[0]["op"], "sync");
+        assert_eq!(lines[0]["source"], "/src");
+        assert_eq!(lines[0]["target"], "s3://bucket/dst");
+        assert!(lines[0]["ts"].as_str().unwrap().ends_with('Z'));
+        assert_eq!(lines[1]["event"], "completed");
+        assert_eq!(lines[1]["stats"]["files"], 3);
+        assert_eq!(lines[1]["stats"]["bytes"], 42);
+        assert_eq!(lines[1]["stats"]["conflicts"], 0);
+        assert_eq!(lines[1]["stats"]["throughput_mbps"], 12.5);
+        assert!(lines[1]["elapsed_secs"].is_f64());
+    }
+
+    #[test]
+    fn test_failure_and_round_trip() {
+        let out = Shared::default();
+        let log = Arc::new(EventLog::from_writer(out.clone()));
+
+        let events = OperationEvents::start(Some(log), Operation::Delete, None, "/tmp/x".to_string(), false);
+        events.failed("Permission denied");
+
+        let lines = lines(&out);
+        assert_eq!(lines[1]["event"], "failed");
+        assert_eq!(lines[1]["error"], "Permission denied");
+        assert!(lines[1]["source"].is_null());
+
+        // Extra record fields (version, ts) don't get in the way of reading events back
+        let event: Event = serde_json::from_value(lines[0].clone()).unwrap();
+        assert_eq!(
+            event,
+            Event::Started { op: Operation::Delete, source: None, target: "/tmp/x".to_string(), dry_run: false }
+        );
+    }
+}
@@ -2,8 +2,26 @@ use anyhow::Result;
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
 
 use crate::app::{App, AppMode, LargeFileAction};
+use crate::event_log::{Operation, OperationEvents, OperationStats};
+use crate::keymap::{Action, AnalyzerAction};
 
 pub async fn handle_events(app: &mut App) -> Result<()> {
+    // Merge the rest of huge directory listings once they have been read
+    for pane in [&mut app.left_pane, &mut app.right_pane] {
+        let Some(ref rx) = pane.pending else {
+            continue;
+        };
+        match rx.try_recv() {
+            Ok(Ok(rest)) => pane.finish_listing(rest),
+            Ok(Err(e)) => {
+                pane.pending = None;
+                app.message = format!("Error: {}", e);
+            }
+            Err(crossbeam_channel::TryRecvError::Empty) => {}
+            Err(crossbeam_channel::TryRecvError::Disconnected) => pane.pending = None,
+        }
+    }
+
     // Check for cleaner scan completion
     if let Some(ref rx) = app.cleaner_scan_rx {
         if let Ok(tree) = rx.try_recv() {
@@ -12,7 +30,7 @@ pub async fn handle_events(app: &mut App) -> Result<()> {
             if let Some(ref tree) = app.cleaner_tree {
                  app.cleaner_entries = tree.get_children(&app.cleaner_path);
                  crate::events::cleaner_apply_sort(app);
-                 app.cleaner_total_size = app.cleaner_entries.iter().map(|e| e.size).sum();
+                 app.cleaner_total_size = cleaner_total(app);
             }
             
             app.cleaner_scan_rx = None;
@@ -22,15 +40,35 @@ pub async fn handle_events(app: &mut App) -> Result<()> {
         }
     }
 
+    // Log what a simulated clean would remove
+    if let Some(ref rx) = app.cleaner_dry_run_rx {
+        for event in rx.try_iter() {
+            app.dry_run_log.record(&event);
+        }
+    }
+
     // Check for cleaner cleaning completion
     if let Some(ref rx) = app.cleaner_clean_rx {
         if let Ok(result) = rx.try_recv() {
-            // Cleaning finished
+            // Cleaning finished (a simulated clean has sent all its matches by now)
+            let simulated = app.cleaner_dry_run_rx.take().map(|rx| {
+                for event in rx.try_iter() {
+                    app.dry_run_log.record(&event);
+                }
+            }).is_some();
             match result {
                 Ok(_) => {
+                    let cancelled = app.cleaner_clean_cancelled.as_ref()
+                        .is_some_and(|c| c.load(std::sync::atomic::Ordering::Relaxed));
                     if let Some(ref stats) = app.cleaner_delete_stats {
                         app.cleaner_status = Some(format!(
-                            "Cleaned: {} dirs, {} files ({})",
+                            "{}: {} dirs, {} files ({})",
+                            match (simulated, cancelled) {
+                                (true, true) => "Dry run cancelled after",
+                                (true, false) => "Dry run, would clean",
+                                (false, true) => "Clean cancelled after",
+                                (false, false) => "Cleaned",
+                            },
                             stats.directories(),
                             stats.files(),
                             humansize::format_size(stats.bytes(), humansize::BINARY)
@@ -46,18 +84,232 @@ pub async fn handle_events(app: &mut App) -> Result<()> {
             
             app.cleaner_clean_rx = None;
             app.cleaner_delete_stats = None;
+            app.cleaner_clean_cancelled = None;
+            app.cleaner_clean_total = 0;
+        }
+    }
+
+    // Mirror background save progress into the progress bar
+    if let Some(ref written) = app.save_written {
+        if let Some(ref mut progress) = app.progress {
+            progress.current = written.load(std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+
+    // Mirror per-file progress of a queued copy into the progress bar
+    if let Some(ref mut rx) = app.operation_queue.copy_rx {
+        while let Ok(p) = rx.try_recv() {
+            if let Some(ref mut progress) = app.progress {
+                progress.current_file = p.current_file;
+                progress.files_done = p.files_done;
+                progress.total_files = p.total_files;
+                // Only single-file downloads report bytes as they go
+                if p.bytes_copied > 0 {
+                    progress.current = p.bytes_copied;
+                }
+            }
+        }
+    }
+
+    // Mirror background delete progress into the progress bar, or the status line while
+    // a copy or move has the bar
+    if let Some(ref deleting) = app.delete_progress {
+        match app.progress {
+            Some(ref mut progress) if progress.stage == crate::app::ProgressStage::Deleting => {
+                progress.current = deleting.deleted();
+                progress.total = deleting.total();
+            }
+            _ => {
+                app.message = format!("Deleting... {}/{} objects (Esc to cancel)", deleting.deleted(), deleting.total());
+            }
+        }
+    }
+
+    // Check for directory size completion
+    if let Some(ref rx) = app.dir_size_rx {
+        if let Ok((path, size)) = rx.try_recv() {
+            match size {
+                Some(size) => {
+                    app.message = format!(
+                        "{}: {}",
+                        path.display(),
+                        humansize::format_size(size, humansize::BINARY)
+                    );
+                    app.dir_sizes.sizes.insert(path, size);
+                }
+                None => {
+                    app.message = "Directory size calculation cancelled".to_string();
+                }
+            }
+            app.dir_sizes.computing = None;
+            app.dir_size_cancelled = None;
+            app.dir_size_rx = None;
+        }
+    }
+
+    // Check for quick hash check completion
+    if let Some(ref rx) = app.hash_check_rx {
+        if let Ok(result) = rx.try_recv() {
+            match result {
+                Ok(check) => {
+                    app.message = if check.matches() {
+                        format!("✓ {} matches ({})", check.path.display(), check.algorithm)
+                    } else {
+                        format!("✗ {} does NOT match ({})", check.path.display(), check.algorithm)
+                    };
+                    app.hash_check = Some(check);
+                    if matches!(app.mode, AppMode::Normal) {
+                        app.mode = AppMode::HashCheckResult;
+                    }
+                }
+                Err(e) => {
+                    app.message = format!("Hash check failed: {}", e);
+                }
+            }
+            app.hash_check_rx = None;
+        }
+    }
+
+    // Check for metadata of the selected entry
+    if let Some(ref rx) = app.object_details_rx {
+        if let Ok(result) = rx.try_recv() {
+            match result {
+                Ok(details) => {
+                    app.message.clear();
+                    app.object_details = Some(details);
+                    if matches!(app.mode, AppMode::Normal) {
+                        app.mode = AppMode::ObjectDetails;
+                    }
+                }
+                Err(e) => {
+                    app.message = format!("Reading details failed: {}", e);
+                }
+            }
+            app.object_details_rx = None;
+        }
+    }
+
+    // Check for a hashed info line
+    if let Some(ref rx) = app.info_line_rx {
+        if let Ok(result) = rx.try_recv() {
+            match result {
+                Ok(line) => finish_entry_info(app, line),
+                Err(e) => app.message = format!("Info line failed: {}", e),
+            }
+            app.info_line_rx = None;
+        }
+    }
+
+    poll_copy_plan(app);
+
+    // Show the latest duplicate search progress; the task reports its result itself
+    if let Some(ref rx) = app.dedup_progress_rx {
+        let mut latest = None;
+        let finished = loop {
+            match rx.try_recv() {
+                Ok(progress) => latest = Some(progress),
+                Err(crossbeam_channel::TryRecvError::Empty) => break false,
+                Err(crossbeam_channel::TryRecvError::Disconnected) => break true,
+            }
+        };
+        if finished {
+            app.dedup_progress_rx = None;
+            app.dedup_cancelled = None;
+        } else if let Some(p) = latest {
+            app.message = format!(
+                "Finding duplicates: {} {}/{} files, {} read, {} groups so far (Esc to cancel)",
+                p.phase.label(),
+                p.files_scanned,
+                p.total_files,
+                humansize::format_size(p.bytes_hashed, humansize::BINARY),
+                p.duplicate_groups_found
+            );
         }
     }
 
+    // Check for delete impact completion (dropped if the popup was dismissed meanwhile)
+    if let Some(ref rx) = app.delete_impact_rx {
+        if let Ok(impact) = rx.try_recv() {
+            app.delete_impact = Some(impact);
+            app.delete_impact_rx = None;
+            app.delete_impact_cancelled = None;
+        }
+    }
+
+    // Check for verify completion
+    if let Some(ref rx) = app.verify_rx {
+        if let Ok(result) = rx.try_recv() {
+            match result {
+                Ok(report) => {
+                    app.message = if report.mismatches.is_empty() && report.missing_files.is_empty() {
+                        format!("✓ All {} files OK", report.matches)
+                    } else {
+                        format!("⚠ {} OK, {} changed, {} missing, {} new",
+                            report.matches,
+                            report.mismatches.len(),
+                            report.missing_files.len(),
+                            report.new_files.len())
+                    };
+                    app.verify_matches = report.matches;
+                    app.verify_entries = crate::app::verify_entries(&report);
+                    app.verify_selected = 0;
+                }
+                Err(e) => {
+                    app.message = format!("Verify failed: {}", e);
+                }
+            }
+            app.verify_rx = None;
+            app.verify_progress = None;
+        }
+    }
+
+    // Check for recent files scan completion
+    if let Some(ref rx) = app.recent_rx {
+        if let Ok(files) = rx.try_recv() {
+            app.recent_files = files;
+            crate::cleaner::recent::sort_recent(&mut app.recent_files, app.recent_sort_mode);
+            app.recent_selected = 0;
+            app.recent_rx = None;
+            app.recent_progress = None;
+            app.recent_cancelled = None;
+            app.message = format!(
+                "Found {} recently modified files in {}",
+                app.recent_files.len(),
+                app.recent_root.display()
+            );
+        }
+    }
+
+    // Keep the quick-look pane on the selection
+    update_preview(app);
+
     // Poll for sync progress updates (non-blocking)
     let _ = poll_sync_progress(app).await;
     
     if event::poll(std::time::Duration::from_millis(100))? {
-        if let Event::Key(key) = event::read()? {
-            // Global quit handlers - work in ALL modes
-            if key.code == KeyCode::Char('q') || 
-               (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)) {
-                app.should_quit = true;
+        // Any event (key, resize, focus) may change what is on screen
+        let event = event::read()?;
+        app.needs_redraw = true;
+        if let Event::Key(key) = event {
+            let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+
+            // Quit confirmation popup takes all keys
+            if app.quit_pending {
+                if ctrl_c || matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                    app.should_quit = true;
+                } else {
+                    app.quit_pending = false;
+                    app.message = "Quit cancelled".to_string();
+                }
+                return Ok(());
+            }
+
+            if ctrl_c || is_quit_key(app, &key) {
+                if app.confirm_quit && !app.pending_work().is_empty() {
+                    app.quit_pending = true;
+                } else {
+                    app.should_quit = true;
+                }
                 return Ok(());
             }
             
@@ -67,17 +319,39 @@ pub async fn handle_events(app: &mut App) -> Result<()> {
                 AppMode::SelectNamespace => handle_namespace_select(app, key).await?,
                 AppMode::SelectPvc => handle_pvc_select(app, key).await?,
                 AppMode::SelectPv => handle_pv_select(app, key).await?,
+                AppMode::SelectContext => handle_context_select(app, key).await?,
                 AppMode::SelectCloudProvider => handle_cloud_provider_select(app, key).await?,
                 AppMode::ConfigureCloud => handle_configure_cloud(app, key).await?,
                 AppMode::DiskAnalyzer => handle_disk_analyzer(app, key).await?,
                 AppMode::ConfirmDelete => handle_confirm_delete(app, key).await?,
                 AppMode::Rename => handle_rename_mode(app, key).await?,
+                AppMode::CreateFile => handle_create_file_mode(app, key).await?,
                 AppMode::ViewFile => handle_view_file_mode(app, key).await?,
                 AppMode::Search => handle_search_mode(app, key).await?,
                 AppMode::EditFile => handle_edit_file_mode(app, key).await?,
                 AppMode::ConfirmLargeLoad => handle_confirm_large_load_mode(app, key).await?,
                 AppMode::EditorSearch => handle_editor_search_mode(app, key).await?,
+                AppMode::EditorGoToLine | AppMode::ViewGoToLine => handle_goto_line_mode(app, key).await?,
+                AppMode::EditorSaveAs | AppMode::ViewSaveAs => handle_save_as_mode(app, key),
                 AppMode::HashMenu => handle_hash_menu(app, key).await?,
+                AppMode::RecentFiles => handle_recent_files(app, key).await?,
+                AppMode::DiffView => handle_diff_view_mode(app, key).await?,
+                AppMode::ConfirmRetry => handle_confirm_retry(app, key),
+                AppMode::ConfirmCopyPlan => handle_confirm_copy_plan(app, key),
+                AppMode::ConfirmMerge => handle_confirm_merge(app, key),
+                AppMode::ConfirmEditorOverwrite => handle_confirm_editor_overwrite(app, key).await?,
+                AppMode::VerifyResults => handle_verify_results(app, key).await?,
+                AppMode::HashCheckInput => handle_hash_check_input(app, key),
+                AppMode::MarkPattern | AppMode::UnmarkPattern => handle_mark_pattern_mode(app, key),
+                AppMode::HashCheckResult => {
+                    // Any key dismisses the result
+                    app.mode = AppMode::Normal;
+                    app.hash_check = None;
+                }
+                AppMode::ObjectDetails => {
+                    app.mode = AppMode::Normal;
+                    app.object_details = None;
+                }
             }
         }
     }
@@ -85,71 +359,120 @@ pub async fn handle_events(app: &mut App) -> Result<()> {
 }
 
 async fn handle_normal_mode(app: &mut App, key: KeyEvent) -> Result<()> {
-    match key.code {
-        KeyCode::Tab => {
+    // Esc cancels background work and isn't remappable
+    if key.code == KeyCode::Esc {
+        if app.operation_queue.pending_len() > 0 {
+            // Cancel queued copies/moves first (the running one goes on until the next Esc)
+            let cancelled = app.operation_queue.cancel_pending();
+            app.message = format!("Cancelled {} queued operation(s)", cancelled);
+        } else if let Some(ref cancelled) = app.operation_queue.copy_cancelled {
+            // Stop the running copy; the task reports back once it has stopped
+            cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+            app.message = "Cancelling copy...".to_string();
+        } else if let Some(ref deleting) = app.delete_progress {
+            // The delete stops before its next object or batch and reports back
+            deleting.cancel();
+            app.message = "Cancelling delete...".to_string();
+        } else if app.dir_size_rx.is_some() {
+            // Cancel directory size calculation
+            if let Some(ref cancelled) = app.dir_size_cancelled {
+                cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+            }
+        } else if let Some(ref cancelled) = app.dedup_cancelled {
+            // The search stops at the next file and reports back
+            cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+            app.message = "Cancelling duplicate search...".to_string();
+        }
+        return Ok(());
+    }
+
+    let Some(action) = app.keymap.action(&key) else {
+        return Ok(());
+    };
+    match action {
+        Action::SwitchPane => {
             app.switch_pane();
         }
-        KeyCode::Up => {
+        Action::Up => {
             app.active_pane_mut().select_previous();
         }
-        KeyCode::Down => {
+        Action::Down => {
             app.active_pane_mut().select_next();
         }
-        KeyCode::Enter => {
+        Action::Open => {
             app.navigate_into().await?;
         }
-        KeyCode::Backspace => {
+        Action::Parent => {
             app.navigate_up().await?;
         }
-        KeyCode::F(5) => {
+        Action::HistoryBack => {
+            // Previously visited location
+            app.navigate_history(false).await?;
+        }
+        Action::HistoryForward => {
+            app.navigate_history(true).await?;
+        }
+        Action::Copy => {
             // Copy operation
             handle_copy(app).await?;
         }
-        KeyCode::F(6) => {
+        Action::Move => {
             // Move operation
             handle_move(app).await?;
         }
-        KeyCode::F(2) => {
+        Action::Rename => {
             // Rename file/directory
             handle_rename_start(app)?;
         }
-        KeyCode::F(3) => {
+        Action::View => {
             // View file contents
             handle_view_file(app).await?;
         }
-        KeyCode::F(4) => {
+        Action::Edit => {
             // Edit file (nano-like)
             handle_edit_file_start(app).await?;
         }
-        KeyCode::F(9) => {
+        Action::DiskAnalyzer => {
             // ncdu-like disk analyzer (moved from F4)
             handle_disk_analyzer_enter(app).await?;
         }
-        KeyCode::F(7) => {
+        Action::CreateFile => {
+            // Create empty file
+            handle_create_file_start(app);
+        }
+        Action::Mkdir => {
             // Create directory
             handle_mkdir(app).await?;
         }
-        KeyCode::F(8) => {
+        Action::Delete => {
             // Delete
             handle_delete(app).await?;
         }
-        KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+        Action::Search => {
             // Search/find files
             handle_search_start(app)?;
         }
-        KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+        Action::SyncToggle => {
             // Toggle sync mode
             handle_sync_toggle(app)?;
         }
-        KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+        Action::SyncDirection => {
+            // Cycle sync direction (L→R, R→L, L↔R)
+            handle_sync_direction_cycle(app);
+        }
+        Action::SyncConflict => {
+            // Cycle conflict strategy for bidirectional sync
+            handle_sync_conflict_cycle(app);
+        }
+        Action::SyncNow => {
             // Manual one-time sync
             handle_sync_now(app).await?;
         }
-        KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+        Action::SyncDryRun => {
             // Dry-run sync (preview changes)
             handle_sync_dry_run(app).await?;
         }
-        KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+        Action::SelectStorage => {
             // Select storage type for the ACTIVE pane
             app.mode = AppMode::SelectStorage;
 
@@ -176,6 +499,13 @@ async fn handle_normal_mode(app: &mut App, key: KeyEvent) -> Result<()> {
                     modified: None,
                     permissions: None,
                 },
+                crate::fs::types::FileEntry {
+                    name: "☸ Kubernetes Context - Switch cluster".to_string(),
+                    size: 0,
+                    is_dir: true,
+                    modified: None,
+                    permissions: None,
+                },
                 crate::fs::types::FileEntry {
                     name: "☁ Cloud Storage (S3/GCS/Hetzner/DO)".to_string(),
                     size: 0,
@@ -187,7 +517,7 @@ async fn handle_normal_mode(app: &mut App, key: KeyEvent) -> Result<()> {
 
             // Display storage options in the ACTIVE pane
             let pane = app.active_pane_mut();
-            pane.entries = storage_options;
+            pane.set_entries(storage_options);
             pane.state.select(Some(0));
             pane.storage = std::sync::Arc::new(crate::fs::SelectingBackend);
 
@@ -197,14 +527,116 @@ async fn handle_normal_mode(app: &mut App, key: KeyEvent) -> Result<()> {
             };
             app.message = format!("{} pane: Select storage type (↑/↓ to navigate, Enter to select, Esc to cancel)", pane_name);
         }
-        KeyCode::Char('h') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+        Action::Diff => {
+            // Diff the selected files of both panes
+            handle_diff_start(app).await?;
+        }
+        Action::RecentFiles => {
+            // Recently modified files under the current directory
+            handle_recent_files_enter(app)?;
+        }
+        Action::DirSize => {
+            // Compute recursive size of the selected directory
+            handle_dir_size_start(app);
+        }
+        Action::ToggleMark => {
+            // Mark/unmark the selected entry for batch copy/move/delete
+            app.active_pane_mut().toggle_mark();
+        }
+        Action::MarkPattern => handle_mark_pattern_start(app, true),
+        Action::UnmarkPattern => handle_mark_pattern_start(app, false),
+        Action::InvertMarks => {
+            let pane = app.active_pane_mut();
+            pane.invert_marks();
+            app.message = format!("{} marked", app.active_pane().marked_entries().len());
+        }
+        Action::SwapPanes => {
+            // Swap left and right panes
+            handle_swap_panes(app);
+        }
+        Action::CopyPlan => {
+            // List what F5 would copy before transferring anything
+            handle_copy_plan_start(app);
+        }
+        Action::JumpCorresponding => {
+            // Open the corresponding directory in the other pane
+            app.jump_to_corresponding().await?;
+        }
+        Action::HashCheck => {
+            // Check the selected file against an expected hash
+            handle_hash_check_start(app);
+        }
+        Action::CopyInfo => {
+            // Name, size, time and hash of the selected entry as one line
+            copy_entry_info(app);
+        }
+        Action::Details => {
+            // Everything the backend knows about the selected entry, in a popup
+            show_object_details(app);
+        }
+        Action::Refresh => {
+            // Re-read both panes, e.g. after changes made outside abyss
+            app.refresh_both_panes().await?;
+            app.message = "Refreshed both panes".to_string();
+        }
+        Action::ToggleDeleteConfirm => {
+            // Toggle delete confirmation for this session
+            app.auto_confirm_delete = !app.auto_confirm_delete;
+            app.message = if app.auto_confirm_delete {
+                "⚠ Delete confirmation OFF: F8 deletes immediately (Alt+Y to turn it back on)".to_string()
+            } else {
+                "Delete confirmation ON".to_string()
+            };
+        }
+        Action::ToggleDryRun => {
+            // Deletes and cleans only log what they would remove until this is turned off
+            app.dry_run = !app.dry_run;
+            app.message = if app.dry_run {
+                "Dry run ON: deletes are only logged (Alt+D to turn it off)".to_string()
+            } else {
+                format!("Dry run OFF: deletes remove files again ({} entries were logged)", app.dry_run_log.len())
+            };
+        }
+        Action::ToggleTimeFormat => {
+            app.time_format = app.time_format.toggled();
+            app.message = format!("Showing {} modification times", app.time_format.label());
+        }
+        Action::OpenEditor => {
+            // Open in $EDITOR (suspends the TUI)
+            request_external_open(app, crate::app::ExternalProgram::Editor);
+        }
+        Action::OpenPager => {
+            // Open in $PAGER (suspends the TUI)
+            request_external_open(app, crate::app::ExternalProgram::Pager);
+        }
+        Action::HelpPage => {
+            // Wraps around in the help bar, which knows how many pages fit
+            app.help_page = app.help_page.wrapping_add(1);
+        }
+        Action::TogglePreview => {
+            app.preview.enabled = !app.preview.enabled;
+            if !app.preview.enabled {
+                app.preview.clear();
+            }
+            app.message = format!("Preview pane {}", if app.preview.enabled { "on" } else { "off" });
+        }
+        Action::HashMenu => {
             // Hash operations menu
             app.mode = AppMode::HashMenu;
+
+            // Large files in the listed directory make fast mode worth suggesting
+            let large_files = if app.active_pane().storage.is_local() {
+                app.active_pane().entries.iter()
+                    .filter(|e| !e.is_dir && e.size >= crate::hash::FAST_MODE_THRESHOLD)
+                    .count()
+            } else {
+                0
+            };
             
             // Build hash menu options
             let hash_options = vec![
                 crate::fs::types::FileEntry {
-                    name: "🔍 Scan - Generate hash database".to_string(),
+                    name: hash_scan_entry_name(app.hash_fast_mode),
                     size: 0,
                     is_dir: true,
                     modified: None,
@@ -217,6 +649,13 @@ async fn handle_normal_mode(app: &mut App, key: KeyEvent) -> Result<()> {
                     modified: None,
                     permissions: None,
                 },
+                crate::fs::types::FileEntry {
+                    name: "🌳 Tree Hash - One digest for the whole directory".to_string(),
+                    size: 0,
+                    is_dir: true,
+                    modified: None,
+                    permissions: None,
+                },
                 crate::fs::types::FileEntry {
                     name: "🔄 Dedup - Find duplicate files".to_string(),
                     size: 0,
@@ -248,13 +687,22 @@ async fn handle_normal_mode(app: &mut App, key: KeyEvent) -> Result<()> {
             ];
             
             let pane = app.active_pane_mut();
-            pane.entries = hash_options;
+            pane.set_entries(hash_options);
             pane.state.select(Some(0));
             pane.storage = std::sync::Arc::new(crate::fs::SelectingBackend);
             
-            app.message = "Hash Menu: ↑/↓ navigate, Enter select, Esc cancel".to_string();
+            app.message = if large_files > 0 && !app.hash_fast_mode {
+                format!(
+                    "Hash Menu: {} files here are over {} - press f to enable fast mode (samples large files)",
+                    large_files,
+                    humansize::format_size(crate::hash::FAST_MODE_THRESHOLD, humansize::BINARY)
+                )
+            } else {
+                "Hash Menu: ↑/↓ navigate, Enter select, f fast mode, p parallel hashing, Esc cancel".to_string()
+            };
         }
-        _ => {}
+        // Handled before any mode-specific key
+        Action::Quit => {}
     }
     Ok(())
 }
@@ -271,7 +719,7 @@ async fn handle_storage_select(app: &mut App, key: KeyEvent) -> Result<()> {
             // Refresh the pane with local contents
             let path = std::path::PathBuf::from(&home);
             if let Ok(entries) = crate::fs::LocalFs::list_dir(&path) {
-                pane.entries = entries;
+                pane.set_entries(entries);
                 pane.state.select(Some(0));
             }
             
@@ -298,7 +746,7 @@ async fn handle_storage_select(app: &mut App, key: KeyEvent) -> Result<()> {
                     // Load local directory
                     let path = std::path::PathBuf::from(&home);
                     if let Ok(entries) = crate::fs::LocalFs::list_dir(&path) {
-                        pane.entries = entries;
+                        pane.set_entries(entries);
                         if !pane.entries.is_empty() {
                             pane.state.select(Some(0));
                         }
@@ -336,7 +784,7 @@ async fn handle_storage_select(app: &mut App, key: KeyEvent) -> Result<()> {
                         .collect();
 
                     let pane = app.active_pane_mut();
-                    pane.entries = entries;
+                    pane.set_entries(entries);
                     
                     if !pane.entries.is_empty() {
                         pane.state.select(Some(0));
@@ -405,10 +853,46 @@ async fn handle_storage_select(app: &mut App, key: KeyEvent) -> Result<()> {
                     ];
                     
                     let pane = app.active_pane_mut();
-                    pane.entries = cloud_providers;
+                    pane.set_entries(cloud_providers);
                     pane.state.select(Some(0));
-                    app.message = "Select cloud provider (↑/↓ to navigate, Enter to select, Esc to cancel)".to_string();
+                    app.message = format!(
+                        "Select cloud provider (↑/↓ to navigate, Enter to select, p: profile [{}], Esc to cancel)",
+                        app.cloud_profile.as_deref().unwrap_or(crate::fs::credentials::DEFAULT_PROFILE)
+                    );
                     
+                } else if name.contains("Kubernetes Context") {
+                    if app.kube_contexts.is_empty() {
+                        app.message = "No kubeconfig contexts found".to_string();
+                        return Ok(());
+                    }
+
+                    app.mode = AppMode::SelectContext;
+                    let active = app.kube_context().map(str::to_string);
+                    let entries: Vec<_> = app
+                        .kube_contexts
+                        .iter()
+                        .map(|ctx| crate::fs::types::FileEntry {
+                            name: ctx.clone(),
+                            size: 0,
+                            is_dir: true,
+                            modified: None,
+                            permissions: None,
+                        })
+                        .collect();
+                    let selected = active
+                        .as_deref()
+                        .and_then(|a| app.kube_contexts.iter().position(|c| c == a))
+                        .unwrap_or(0);
+
+                    let pane = app.active_pane_mut();
+                    pane.set_entries(entries);
+                    pane.state.select(Some(selected));
+
+                    app.message = format!(
+                        "Select kube context (current: {}) - ↑/↓ to navigate, Enter to connect, Esc to cancel",
+                        active.as_deref().unwrap_or("none")
+                    );
+
                 } else if name.contains("PersistentVolumeClaims") {
                     // PVC access - check if K8s is available
                     let Some(ref storage_manager) = app.storage_manager else {
@@ -434,7 +918,7 @@ async fn handle_storage_select(app: &mut App, key: KeyEvent) -> Result<()> {
                         .collect();
 
                     let pane = app.active_pane_mut();
-                    pane.entries = entries;
+                    pane.set_entries(entries);
                     
                     if !pane.entries.is_empty() {
                         pane.state.select(Some(0));
@@ -454,11 +938,14 @@ async fn handle_storage_select(app: &mut App, key: KeyEvent) -> Result<()> {
 /// Handle cloud provider selection
 async fn handle_cloud_provider_select(app: &mut App, key: KeyEvent) -> Result<()> {
     match key.code {
+        KeyCode::Char('p') => {
+            cycle_cloud_profile(app);
+        }
         KeyCode::Esc => {
             // Return to storage selection menu
             app.mode = AppMode::SelectStorage;
             let pane = app.active_pane_mut();
-            pane.entries = vec![
+            pane.set_entries(vec![
                 crate::fs::types::FileEntry {
                     name: "📂 Local Filesystem".to_string(),
                     size: 0,
@@ -480,6 +967,13 @@ async fn handle_cloud_provider_select(app: &mut App, key: KeyEvent) -> Result<()
                     modified: None,
                     permissions: None,
                 },
+                crate::fs::types::FileEntry {
+                    name: "☸ Kubernetes Context - Switch cluster".to_string(),
+                    size: 0,
+                    is_dir: true,
+                    modified: None,
+                    permissions: None,
+                },
                 crate::fs::types::FileEntry {
                     name: "☁ Cloud Storage (S3/GCS/Hetzner/DO)".to_string(),
                     size: 0,
@@ -487,7 +981,7 @@ async fn handle_cloud_provider_select(app: &mut App, key: KeyEvent) -> Result<()
                     modified: None,
                     permissions: None,
                 },
-            ];
+            ]);
             pane.state.select(Some(0));
             app.message = "Select storage type".to_string();
         }
@@ -501,77 +995,24 @@ async fn handle_cloud_provider_select(app: &mut App, key: KeyEvent) -> Result<()
             if let Some(entry) = app.active_pane().selected_entry().cloned() {
                 let provider_name = entry.name.trim_start_matches("☁ ").to_string();
                 
-                // Helper to get env var or error
-                let get_env = |key: &str| -> Result<String> {
-                    std::env::var(key).map_err(|_| anyhow::anyhow!("Missing env var: {}", key))
-                };
-                
-                let backend_result: Result<std::sync::Arc<dyn crate::fs::StorageBackend>> = match provider_name.as_str() {
-                    "AWS S3" => {
-                        let bucket = get_env("S3_BUCKET")?;
-                        let region = std::env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string());
-                        
-                        if let (Ok(key), Ok(secret)) = (std::env::var("AWS_ACCESS_KEY_ID"), std::env::var("AWS_SECRET_ACCESS_KEY")) {
-                             Ok(std::sync::Arc::new(crate::fs::s3::S3Fs::new_aws(&bucket, &region, &key, &secret)?))
-                        } else {
-                             // Try IAM
-                             Ok(std::sync::Arc::new(crate::fs::s3::S3Fs::new_with_iam(&bucket, &region)?))
-                        }
-                    },
-                    "Google Cloud Storage (GCS)" => {
-                        let bucket = get_env("GCS_BUCKET")?;
-                        if let Ok(cred) = std::env::var("GOOGLE_APPLICATION_CREDENTIALS") {
-                             Ok(std::sync::Arc::new(crate::fs::gcs::GcsFs::from_service_account(&bucket, &cred)?))
-                        } else {
-                             // Try Workload Identity / ADC
-                             Ok(std::sync::Arc::new(crate::fs::gcs::GcsFs::new_with_workload_identity(&bucket)?))
-                        }
-                    },
-                    "DigitalOcean Spaces" => {
-                        let bucket = get_env("DO_BUCKET")?;
-                        let region = get_env("DO_REGION")?;
-                        let key = get_env("DO_ACCESS_KEY_ID")?;
-                        let secret = get_env("DO_SECRET_ACCESS_KEY")?;
-                        Ok(std::sync::Arc::new(crate::fs::s3::S3Fs::new_digitalocean(&bucket, &region, &key, &secret)?))
-                    },
-                    "Hetzner Object Storage" => {
-                        let bucket = get_env("HETZNER_BUCKET")?;
-                        let region = get_env("HETZNER_REGION")?;
-                        let key = get_env("HETZNER_ACCESS_KEY")?;
-                        let secret = get_env("HETZNER_SECRET_ACCESS_KEY")?;
-                        Ok(std::sync::Arc::new(crate::fs::s3::S3Fs::new_hetzner(&bucket, &region, &key, &secret)?))
-                    },
-                    "Cloudflare R2" => {
-                        let bucket = get_env("R2_BUCKET")?;
-                        let account = get_env("R2_ACCOUNT_ID")?;
-                        let key = get_env("R2_ACCESS_KEY_ID")?;
-                        let secret = get_env("R2_SECRET_ACCESS_KEY")?;
-                        Ok(std::sync::Arc::new(crate::fs::s3::S3Fs::new_cloudflare_r2(&bucket, &account, &key, &secret)?))
-                    },
-                    "MinIO (Local/Self-hosted)" => {
-                        let bucket = get_env("MINIO_BUCKET")?;
-                        let key = get_env("MINIO_ACCESS_KEY")?;
-                        let secret = get_env("MINIO_SECRET_KEY")?;
-                        Ok(std::sync::Arc::new(crate::fs::s3::S3Fs::new_minio(&bucket, &key, &secret)?))
-                    },
-                    "Wasabi" => {
-                        let bucket = get_env("WASABI_BUCKET")?;
-                        let region = get_env("WASABI_REGION")?;
-                        let key = get_env("WASABI_ACCESS_KEY")?;
-                        let secret = get_env("WASABI_SECRET_KEY")?;
-                        Ok(std::sync::Arc::new(crate::fs::s3::S3Fs::new_wasabi(&bucket, &region, &key, &secret)?))
-                    },
-                    _ => Err(anyhow::anyhow!("Unknown provider: {}", provider_name)),
-                };
+                let profile = app.cloud_profile.clone();
+                let backend_result: Result<(std::sync::Arc<dyn crate::fs::StorageBackend>, String)> =
+                    match crate::fs::credentials::CloudProvider::from_menu_name(&provider_name) {
+                        Some(provider) => connect_cloud_provider(provider, profile.as_deref()),
+                        None => Err(anyhow::anyhow!("Unknown provider: {}", provider_name)),
+                    };
                 
                 match backend_result {
-                    Ok(backend) => {
+                    Ok((backend, start_path)) => {
                         let pane = app.active_pane_mut();
                         pane.storage = backend;
-                        pane.path = "".to_string(); // Root of bucket
+                        pane.path = start_path; // Configured prefix, else the root of the bucket
                         app.refresh_active_pane().await?;
                         app.mode = AppMode::Normal;
-                        app.message = format!("Connected to {}", provider_name);
+                        app.message = match profile {
+                            Some(profile) => format!("Connected to {} (profile {})", provider_name, profile),
+                            None => format!("Connected to {}", provider_name),
+                        };
                     }
                     Err(e) => {
                          app.message = format!("❌ Connection failed: {}", e);
@@ -584,6 +1025,50 @@ async fn handle_cloud_provider_select(app: &mut App, key: KeyEvent) -> Result<()
     Ok(())
 }
 
+/// Build the backend for a cloud provider from the environment and the credentials profile,
+/// along with the path the pane should open at
+fn connect_cloud_provider(
+    provider: crate::fs::credentials::CloudProvider,
+    profile: Option<&str>,
+) -> Result<(std::sync::Arc<dyn crate::fs::StorageBackend>, String)> {
+    use crate::fs::credentials::CloudProvider;
+
+    let credentials = crate::fs::credentials::resolve_credentials(provider, profile)?;
+    let backend: std::sync::Arc<dyn crate::fs::StorageBackend> = match provider {
+        CloudProvider::Gcs => std::sync::Arc::new(crate::fs::gcs::GcsFs::from_credentials(&credentials)?),
+        _ => std::sync::Arc::new(crate::fs::s3::S3Fs::from_credentials(provider, &credentials)?),
+    };
+    Ok((backend, credentials.start_path()))
+}
+
+/// Switch to the next profile of the credentials file (after the last one, back to the default)
+fn cycle_cloud_profile(app: &mut App) {
+    let profiles = match crate::fs::credentials::CredentialsFile::load() {
+        // The default profile is what `None` stands for
+        Ok(file) => file.profile_names().into_iter()
+            .filter(|p| p != crate::fs::credentials::DEFAULT_PROFILE)
+            .collect::<Vec<_>>(),
+        Err(e) => {
+            app.message = format!("❌ {:#}", e);
+            return;
+        }
+    };
+    if profiles.is_empty() {
+        app.message = "No other profiles in the credentials file (~/.config/abyss/credentials.toml)".to_string();
+        return;
+    }
+
+    let next = match app.cloud_profile.as_ref().and_then(|current| profiles.iter().position(|p| p == current)) {
+        Some(i) => profiles.get(i + 1).cloned(),
+        None => profiles.first().cloned(),
+    };
+    app.message = format!(
+        "Credentials profile: {} (p to switch)",
+        next.as_deref().unwrap_or(crate::fs::credentials::DEFAULT_PROFILE)
+    );
+    app.cloud_profile = next;
+}
+
 /// Handle cloud storage configuration (placeholder for text input)
 async fn handle_configure_cloud(app: &mut App, key: KeyEvent) -> Result<()> {
     // This is a placeholder - full text input would require additional UI work
@@ -593,15 +1078,40 @@ async fn handle_configure_cloud(app: &mut App, key: KeyEvent) -> Result<()> {
             app.message = "Configuration cancelled".to_string();
         }
         _ => {
-            app.message = "Cloud configuration UI not yet implemented. Use environment variables.".to_string();
+            app.message = "Cloud configuration UI not yet implemented. Use environment variables or a credentials profile.".to_string();
         }
     }
     Ok(())
 }
 
+/// Hash menu label of the Scan entry, showing whether fast mode is on
+fn hash_scan_entry_name(fast_mode: bool) -> String {
+    format!("🔍 Scan - Generate hash database (fast mode: {})", if fast_mode { "on" } else { "off" })
+}
+
 /// Handle hash menu selection
 async fn handle_hash_menu(app: &mut App, key: KeyEvent) -> Result<()> {
     match key.code {
+        KeyCode::Char('f') => {
+            app.hash_fast_mode = !app.hash_fast_mode;
+            let name = hash_scan_entry_name(app.hash_fast_mode);
+            if let Some(entry) = app.active_pane_mut().entries.iter_mut().find(|e| e.name.contains("Scan")) {
+                entry.name = name;
+            }
+            app.message = if app.hash_fast_mode {
+                "Fast mode on: Scan samples the start, middle and end of large files".to_string()
+            } else {
+                "Fast mode off: Scan hashes every byte".to_string()
+            };
+        }
+        KeyCode::Char('p') => {
+            app.hash_parallel = !app.hash_parallel;
+            app.message = match (app.hash_parallel, app.hash_threads) {
+                (false, _) => "Parallel hashing off: one file at a time".to_string(),
+                (true, 0) => "Parallel hashing on: one thread per CPU core".to_string(),
+                (true, threads) => format!("Parallel hashing on: {} threads", threads),
+            };
+        }
         KeyCode::Esc => {
             // Return to normal mode and restore directory
             let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
@@ -643,9 +1153,16 @@ async fn handle_hash_menu(app: &mut App, key: KeyEvent) -> Result<()> {
                     // Run scan in background
                     let dir_clone = dir.clone();
                     let output_clone = output_path.clone();
+                    let fast_mode = app.hash_fast_mode;
+                    let (parallel, threads) = (app.hash_parallel, app.hash_threads);
                     let handle = tokio::task::spawn_blocking(move || {
                         use crate::hash::ScanEngine;
-                        let engine = ScanEngine::with_parallel(true);
+                        let engine = ScanEngine::new()
+                            .with_parallel(parallel)
+                            .with_threads(threads)
+                            .with_fast_mode(fast_mode)
+                            .with_progress(false)
+                            .with_summary(false);
                         
                         let result = engine.scan_directory(
                             std::path::Path::new(&dir_clone),
@@ -654,6 +1171,14 @@ async fn handle_hash_menu(app: &mut App, key: KeyEvent) -> Result<()> {
                         );
                         
                         match result {
+                            Ok(stats) if fast_mode => Ok(format!(
+                                "✓ Scanned {} files → {} (fast mode, {} large files sampled)",
+                                stats.files_processed, output_clone, stats.large_files
+                            )),
+                            Ok(stats) if stats.large_files > 0 => Ok(format!(
+                                "✓ Scanned {} files → {} - {} large files, fast mode (f in hash menu) would sample them",
+                                stats.files_processed, output_clone, stats.large_files
+                            )),
                             Ok(stats) => Ok(format!("✓ Scanned {} files → {}", stats.files_processed, output_clone)),
                             Err(e) => Err(anyhow::anyhow!("Scan failed: {}", e)),
                         }
@@ -662,66 +1187,72 @@ async fn handle_hash_menu(app: &mut App, key: KeyEvent) -> Result<()> {
                     app.background_task = Some(tokio::spawn(async move {
                         handle.await.map_err(|e| anyhow::anyhow!("{}", e))?
                     }));
+                    app.background_paths = vec![dir];
                     
                 } else if name.contains("Verify") {
                     // Verify files against database in current directory
+                    let dir = std::path::PathBuf::from(&app.active_pane().path);
+                    handle_verify_start(app, dir.join("hashes.txt"), dir);
+                    
+                } else if name.contains("Tree Hash") {
+                    // Fold every file hash in the current directory into one digest
                     let dir = app.active_pane().path.clone();
-                    let db_path = format!("{}/hashes.txt", dir);
                     
-                    if !std::path::Path::new(&db_path).exists() {
-                        app.message = format!("No database found at {}. Run Scan first.", db_path);
-                    } else {
-                        app.message = format!("Verifying {}...", dir);
-                        
-                        let dir_clone = dir.clone();
-                        let db_clone = db_path.clone();
-                        let handle = tokio::task::spawn_blocking(move || {
-                            use crate::hash::VerifyEngine;
-                            let engine = VerifyEngine::new();
-                            
-                            let result = engine.verify(
-                                std::path::Path::new(&db_clone),
-                                std::path::Path::new(&dir_clone),
-                            );
-                            
-                            match result {
-                                Ok(report) => {
-                                    let status = if report.mismatches.is_empty() && report.missing_files.is_empty() {
-                                        format!("✓ All {} files OK", report.matches)
-                                    } else {
-                                        format!("⚠ {} OK, {} changed, {} missing, {} new",
-                                            report.matches,
-                                            report.mismatches.len(),
-                                            report.missing_files.len(),
-                                            report.new_files.len())
-                                    };
-                                    Ok(status)
-                                }
-                                Err(e) => Err(anyhow::anyhow!("Verify failed: {}", e)),
-                            }
-                        });
+                    app.message = format!("Hashing tree {}...", dir);
+                    
+                    let dir_clone = dir.clone();
+                    let (parallel, threads) = (app.hash_parallel, app.hash_threads);
+                    let handle = tokio::task::spawn_blocking(move || {
+                        use crate::hash::TreeHashEngine;
+                        let engine = TreeHashEngine::new().with_parallel(parallel).with_threads(threads);
                         
-                        app.background_task = Some(tokio::spawn(async move {
-                            handle.await.map_err(|e| anyhow::anyhow!("{}", e))?
-                        }));
-                    }
+                        match engine.hash_tree(std::path::Path::new(&dir_clone), "blake3") {
+                            Ok(tree) => Ok(format!(
+                                "✓ Tree hash (blake3, {} files): {}",
+                                tree.files, tree.hash
+                            )),
+                            Err(e) => Err(anyhow::anyhow!("Tree hash failed: {}", e)),
+                        }
+                    });
+                    
+                    app.background_task = Some(tokio::spawn(async move {
+                        handle.await.map_err(|e| anyhow::anyhow!("{}", e))?
+                    }));
+                    app.background_paths = vec![dir];
                     
                 } else if name.contains("Dedup") {
                     // Find duplicate files in current directory
                     let dir = app.active_pane().path.clone();
                     
-                    app.message = format!("Finding duplicates in {}...", dir);
+                    app.message = format!("Finding duplicates in {}... (Esc to cancel)", dir);
+                    
+                    // Progress events are dropped while the TUI is behind; only the latest is shown
+                    let (progress_tx, progress_rx) = crossbeam_channel::bounded(64);
+                    let cancelled = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+                    app.dedup_progress_rx = Some(progress_rx);
+                    app.dedup_cancelled = Some(std::sync::Arc::clone(&cancelled));
                     
                     let dir_clone = dir.clone();
+                    let (parallel, threads) = (app.hash_parallel, app.hash_threads);
                     let handle = tokio::task::spawn_blocking(move || {
-                        use crate::hash::DedupEngine;
-                        let engine = DedupEngine::new().with_parallel(true);
+                        use crate::hash::{DedupEngine, HashUtilityError};
+                        // Terminal output would draw over the TUI
+                        let engine = DedupEngine::new()
+                            .with_parallel(parallel)
+                            .with_threads(threads)
+                            .with_summary(false)
+                            .with_progress(false)
+                            .with_cancel(cancelled)
+                            .with_progress_callback(move |p| {
+                                let _ = progress_tx.try_send(p);
+                            });
                         
                         let result = engine.find_duplicates(
                             std::path::Path::new(&dir_clone),
                         );
                         
                         match result {
+                            Err(HashUtilityError::Cancelled { .. }) => Ok("Duplicate search cancelled".to_string()),
                             Ok(report) => {
                                 let dup_count = report.duplicate_groups.len();
                                 let wasted = report.stats.wasted_space;
@@ -738,6 +1269,7 @@ async fn handle_hash_menu(app: &mut App, key: KeyEvent) -> Result<()> {
                     app.background_task = Some(tokio::spawn(async move {
                         handle.await.map_err(|e| anyhow::anyhow!("{}", e))?
                     }));
+                    app.background_paths = vec![dir];
                     
                 } else if name.contains("Benchmark") {
                     // Run hash algorithm benchmarks
@@ -823,16 +1355,17 @@ async fn handle_namespace_select(app: &mut App, key: KeyEvent) -> Result<()> {
                 let pvcs = storage_manager.list_pvcs(&selected_namespace).await?;
 
                 // Convert PVCs to file entries for display
-                app.right_pane.entries = pvcs
-                    .iter()
-                    .map(|pvc| crate::fs::types::FileEntry {
-                        name: format!("{} ({})", pvc.name, pvc.capacity),
-                        size: 0,
-                        is_dir: true,
-                        modified: None,
-                        permissions: None,
-                    })
-                    .collect();
+                app.right_pane.set_entries(
+                    pvcs.iter()
+                        .map(|pvc| crate::fs::types::FileEntry {
+                            name: format!("{} ({})", pvc.name, pvc.capacity),
+                            size: 0,
+                            is_dir: true,
+                            modified: None,
+                            permissions: None,
+                        })
+                        .collect(),
+                );
 
                 // Reset selection
                 if !app.right_pane.entries.is_empty() {
@@ -929,14 +1462,13 @@ async fn handle_pv_select(app: &mut App, key: KeyEvent) -> Result<()> {
                     .to_string();
 
                 // For PV access, we need to create a pod that mounts the PV directly
-                // This is more complex as PVs don't have a namespace
-                // For now, we'll use a default namespace
-                let namespace = "default";
+                // PVs don't have a namespace, so the helper pod runs in the
+                // configured PV namespace (--pv-namespace / ABYSS_PV_NAMESPACE)
+                let namespace = app.pv_namespace.clone();
 
                 // Set right pane to browse this PV using K8sBackend
-                // Uses default namespace hack as before
                 let k8s_backend = crate::fs::K8sBackend::new(
-                    namespace.to_string(),
+                    namespace,
                     pv_name.clone(),
                     remote_fs.clone(),
                 );
@@ -955,14 +1487,98 @@ async fn handle_pv_select(app: &mut App, key: KeyEvent) -> Result<()> {
     Ok(())
 }
 
-fn count_files_in_dir(path: &std::path::Path) -> usize {
-    if path.is_file() {
-        return 1;
-    }
+/// Handle kubeconfig context selection: reconnect all K8s clients to the chosen cluster.
+async fn handle_context_select(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        KeyCode::Esc => {
+            restore_active_pane_home(app);
+            app.mode = AppMode::Normal;
+            app.message = "Cancelled".to_string();
+        }
+        KeyCode::Up => {
+            app.active_pane_mut().select_previous();
+        }
+        KeyCode::Down => {
+            app.active_pane_mut().select_next();
+        }
+        KeyCode::Enter => {
+            let Some(context) = app.active_pane().selected_entry().map(|e| e.name.clone()) else {
+                return Ok(());
+            };
 
-    // Use jwalk for fast parallel directory walking
-    jwalk::WalkDir::new(path)
-        .skip_hidden(false)
+            app.message = match App::connect_k8s(Some(&context)).await {
+                Ok((client, storage_manager, remote_fs)) => {
+                    app.current_namespace = client.current_namespace().to_string();
+                    app.k8s_client = Some(client);
+                    app.storage_manager = Some(storage_manager);
+                    app.remote_fs = Some(remote_fs);
+                    format!("Switched to kube context '{}'", context)
+                }
+                Err(e) => format!("❌ Failed to connect to '{}': {}", context, e),
+            };
+            restore_active_pane_home(app);
+            app.mode = AppMode::Normal;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Point the active pane back at the local home directory (after leaving a selection list).
+fn restore_active_pane_home(app: &mut App) {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    let pane = app.active_pane_mut();
+    pane.storage = std::sync::Arc::new(crate::fs::LocalBackend::new(std::path::PathBuf::from(&home)));
+    pane.path = home.clone();
+    pane.set_entries(crate::fs::LocalFs::list_dir(std::path::Path::new(&home)).unwrap_or_default());
+    pane.state.select(Some(0));
+}
+
+/// Start computing the recursive size of the selected local directory in the background.
+fn handle_dir_size_start(app: &mut App) {
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+
+    if app.dir_size_rx.is_some() {
+        app.message = "Directory size calculation in progress (Esc to cancel)".to_string();
+        return;
+    }
+
+    let pane = app.active_pane();
+    if !pane.storage.is_local() {
+        app.message = "Directory sizes only available for local filesystem".to_string();
+        return;
+    }
+
+    let Some(entry) = pane.selected_entry() else { return };
+    if !entry.is_dir || entry.name == ".." {
+        app.message = "Select a directory to compute its size".to_string();
+        return;
+    }
+
+    let path = std::path::Path::new(&pane.path).join(&entry.name);
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let (tx, rx) = crossbeam_channel::bounded(1);
+
+    app.dir_sizes.computing = Some(path.clone());
+    app.dir_size_cancelled = Some(Arc::clone(&cancelled));
+    app.dir_size_rx = Some(rx);
+    app.message = format!("Computing size of {}... (Esc to cancel)", path.display());
+
+    std::thread::spawn(move || {
+        let size = crate::fs::LocalFs::dir_size(&path, &cancelled);
+        let _ = tx.send((path, size));
+    });
+}
+
+fn count_files_in_dir(path: &std::path::Path) -> usize {
+    if path.is_file() {
+        return 1;
+    }
+
+    // Use jwalk for fast parallel directory walking
+    jwalk::WalkDir::new(path)
+        .skip_hidden(false)
         .into_iter()
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_file())
@@ -971,146 +1587,422 @@ fn count_files_in_dir(path: &std::path::Path) -> usize {
 }
 
 async fn handle_copy(app: &mut App) -> Result<()> {
-    // Don't start new copy if one is already running
-    if app.background_task.is_some() {
-        app.message = "Copy already in progress...".to_string();
-        return Ok(());
+    enqueue_operations(app, crate::app::OperationKind::Copy);
+    Ok(())
+}
+
+/// Queue a copy/move of the marked entries (or the selected one) into the other pane,
+/// clearing the marks once queued.
+fn enqueue_operations(app: &mut App, kind: crate::app::OperationKind) {
+    let ops = queued_operations(app, kind);
+    if ops.is_empty() {
+        app.message = "No entry selected".to_string();
+        return;
+    }
+    if let Some(error) = App::overlap_error(&ops) {
+        app.message = error;
+        return;
+    }
+    enqueue_or_ask_merge(app, ops);
+}
+
+/// Queue the operations, first asking how to merge when a copied directory already
+/// exists in the other pane. Marks are cleared once the operations are queued.
+fn enqueue_or_ask_merge(app: &mut App, ops: Vec<crate::app::QueuedOperation>) {
+    if ops.iter().any(|op| app.merge_conflict(op)) {
+        app.merge_ops = ops;
+        app.mode = AppMode::ConfirmMerge;
+        app.message = "Target directory exists: merge or replace?".to_string();
+        return;
+    }
+
+    app.active_pane_mut().marked.clear();
+    for op in ops {
+        app.enqueue_operation(op);
+    }
+}
+
+/// Choose how copies into existing directories merge, or cancel them all
+fn handle_confirm_merge(app: &mut App, key: KeyEvent) {
+    use crate::fs::copy::MergeMode;
+
+    let merge = match key.code {
+        KeyCode::Char('m') | KeyCode::Char('M') | KeyCode::Enter => MergeMode::Overwrite,
+        KeyCode::Char('k') | KeyCode::Char('K') => MergeMode::KeepExisting,
+        KeyCode::Char('n') | KeyCode::Char('N') => MergeMode::Newer,
+        KeyCode::Char('r') | KeyCode::Char('R') => MergeMode::Replace,
+        KeyCode::Esc => {
+            app.mode = AppMode::Normal;
+            app.merge_ops.clear();
+            app.message = "Copy cancelled".to_string();
+            return;
+        }
+        _ => return,
+    };
+
+    app.mode = AppMode::Normal;
+    app.active_pane_mut().marked.clear();
+    for op in std::mem::take(&mut app.merge_ops) {
+        app.enqueue_operation(crate::app::QueuedOperation { merge, ..op });
     }
+}
 
+/// Build copies/moves from the active pane into the other pane: one per marked entry,
+/// or just the selected entry when nothing is marked.
+fn queued_operations(app: &App, kind: crate::app::OperationKind) -> Vec<crate::app::QueuedOperation> {
     // Get source and destination panes
     let (src_pane, dest_pane) = match app.active_pane {
         crate::app::ActivePane::Left => (&app.left_pane, &app.right_pane),
         crate::app::ActivePane::Right => (&app.right_pane, &app.left_pane),
     };
 
-    if let Some(entry) = src_pane.selected_entry() {
-        let entry_name = entry.name.clone();
-        let entry_size = entry.size;
-        let entry_is_dir = entry.is_dir;
+    let marked = src_pane.marked_entries();
+    let entries = if marked.is_empty() {
+        src_pane.selected_entry().into_iter().collect()
+    } else {
+        marked
+    };
+
+    // Construct full generic paths
+    // SelectingBackend rejects writes, so the operation fails gracefully if used.
+    entries
+        .into_iter()
+        .map(|entry| crate::app::QueuedOperation {
+            kind,
+            name: entry.name.clone(),
+            size: entry.size,
+            is_dir: entry.is_dir,
+            src_storage: src_pane.storage.clone(),
+            src_path: src_pane.entry_path(&entry.name),
+            dest_storage: dest_pane.storage.clone(),
+            dest_path: dest_pane.entry_path(&entry.name),
+            resume: false,
+            merge: Default::default(),
+        })
+        .collect()
+}
 
-        // Check destination path validity
-        // Note: With VFS, empty path might be valid (root), but depends on backend.
-        // SelectingBackend gives empty path usually.
-        // We assume valid storage if not Selecting.
-        // SelectingBackend rejects writes, so copy will fail gracefully if used.
+/// Enumerate what copying the marked (or selected) entries would transfer, in the
+/// background; the plan is shown for confirmation once it is complete.
+fn handle_copy_plan_start(app: &mut App) {
+    if app.copy_plan_rx.is_some() {
+        app.message = "A copy plan is already being prepared".to_string();
+        return;
+    }
+    let ops = queued_operations(app, crate::app::OperationKind::Copy);
+    if ops.is_empty() {
+        app.message = "No entry selected".to_string();
+        return;
+    }
+    if let Some(error) = App::overlap_error(&ops) {
+        app.message = error;
+        return;
+    }
 
-        // Construct full generic paths
-        let src_path_str = if src_pane.path.ends_with('/') || src_pane.path.is_empty() {
-            format!("{}{}", src_pane.path, entry_name)
-        } else {
-            format!("{}/{}", src_pane.path, entry_name)
-        };
+    app.copy_plan_ops = ops;
+    app.message = "Listing files to copy...".to_string();
+    spawn_copy_plan(app);
+}
 
-        let dest_path_str = if dest_pane.path.ends_with('/') || dest_pane.path.is_empty() {
-             format!("{}{}", dest_pane.path, entry_name)
-        } else {
-             format!("{}/{}", dest_pane.path, entry_name)
-        };
+/// List the files `copy_plan_ops` would transfer in the background, sorted by path so
+/// two listings of an unchanged tree compare equal
+fn spawn_copy_plan(app: &mut App) {
+    let sources: Vec<_> = app
+        .copy_plan_ops
+        .iter()
+        .map(|op| (op.src_storage.clone(), op.src_path.clone(), op.name.clone(), op.is_dir))
+        .collect();
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    app.copy_plan_rx = Some(rx);
 
-        // Prepare for background task
-        let src_storage = src_pane.storage.clone();
-        let dest_storage = dest_pane.storage.clone();
-        let src_full = src_path_str.clone();
-        let dest_full = dest_path_str.clone();
-        let entry_name_clone = entry_name.clone();
+    tokio::spawn(async move {
+        let mut plan = crate::fs::copy::CopyPlan::default();
+        let mut result = Ok(());
+        for (storage, path, name, is_dir) in sources {
+            result = if is_dir {
+                crate::fs::copy::plan_dir_copy(storage.as_ref(), &path, &name, &mut plan).await
+            } else {
+                // Sized now rather than from the listing, which may be out of date
+                storage.stat(&path).await.map(|stat| plan.add_file(name, stat.size)).map_err(Into::into)
+            };
+            if result.is_err() {
+                break;
+            }
+        }
+        plan.files.sort_by(|a, b| a.path.cmp(&b.path));
+        let _ = tx.send(result.map(|_| plan).map_err(|e| e.to_string()));
+    });
+}
 
-        app.message = format!("Copying {}...", entry_name);
-        
-        // Spawn background task
-        let handle = tokio::spawn(async move {
-            crate::fs::copy_between_backends(
-                &*src_storage,
-                &src_full,
-                &*dest_storage,
-                &dest_full,
-                None
-            ).await?;
-            
-            Ok(format!("✓ Copied {} successfully", entry_name_clone))
-        });
-        
-        app.background_task = Some(handle);
-        
-        // Setup initial progress (generic/indeterminate)
-        app.progress = Some(crate::app::Progress {
-            stage: crate::app::ProgressStage::Transferring,
-            current: 0,
-            total: entry_size,
-            current_file: entry_name,
-            files_done: 0,
-            total_files: if entry_is_dir { 0 } else { 1 },
-        });
+/// Show a finished copy plan, or queue its copies when it was confirmed and is unchanged
+fn poll_copy_plan(app: &mut App) {
+    let Some(ref rx) = app.copy_plan_rx else {
+        return;
+    };
+    if let Ok(result) = rx.try_recv() {
+        let confirmed = std::mem::take(&mut app.copy_plan_confirmed);
+        match result {
+            Ok(plan) if confirmed && app.copy_plan.as_ref() == Some(&plan) => {
+                app.copy_plan = None;
+                let ops = std::mem::take(&mut app.copy_plan_ops);
+                enqueue_or_ask_merge(app, ops);
+            }
+            Ok(plan) => {
+                app.message = match app.inactive_pane().space {
+                    Some(space) if plan.bytes > space.available => format!(
+                        "⚠ Copy plan: {}, but only {} free at the destination",
+                        plan.summary(),
+                        crate::fs::types::format_bytes(space.available)
+                    ),
+                    _ if confirmed => format!("Source changed since the plan was listed: {}", plan.summary()),
+                    _ => format!("Copy plan: {}", plan.summary()),
+                };
+                app.copy_plan = Some(plan);
+                if matches!(app.mode, AppMode::Normal) {
+                    app.mode = AppMode::ConfirmCopyPlan;
+                }
+            }
+            Err(e) => {
+                app.message = format!("Copy plan failed: {}", e);
+                app.copy_plan = None;
+                app.copy_plan_ops.clear();
+            }
+        }
+        app.copy_plan_rx = None;
+    }
+}
 
-    } else {
-        app.message = "No entry selected".to_string();
+/// Confirm or dismiss a copy plan
+///
+/// Confirming lists the sources again: the copies are queued when the tree is unchanged,
+/// and the new plan is shown for another look when it is not.
+fn handle_confirm_copy_plan(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+            app.mode = AppMode::Normal;
+            app.copy_plan_confirmed = true;
+            app.message = "Checking the copy plan is still current...".to_string();
+            spawn_copy_plan(app);
+        }
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+            app.mode = AppMode::Normal;
+            app.copy_plan = None;
+            app.copy_plan_ops.clear();
+            app.message = "Copy cancelled".to_string();
+        }
+        _ => {}
     }
-    Ok(())
 }
 
-/// Show delete confirmation popup - sets up the target and switches mode
-/// Show delete confirmation popup - sets up the target and switches mode
+/// Show delete confirmation popup - sets up the targets and switches mode
+///
+/// Targets the marked entries, or the selected entry when nothing is marked.
 async fn handle_delete(app: &mut App) -> Result<()> {
     
     // Get info from active pane
     let pane = app.active_pane();
-    
-    if let Some(entry) = pane.selected_entry() {
-        let entry_name = entry.name.clone();
-        let is_dir = entry.is_dir;
-        
-        // Construct full path
-        let path = if pane.path.ends_with('/') || pane.path.is_empty() {
-            format!("{}{}", pane.path, entry_name)
+    let marked = pane.marked_entries();
+    let entries = if marked.is_empty() {
+        pane.selected_entry().filter(|e| e.name != "..").into_iter().collect()
+    } else {
+        marked
+    };
+
+    // Populate generic DeleteTargets
+    let targets: Vec<crate::app::DeleteTarget> = entries
+        .into_iter()
+        .map(|entry| {
+            let path = pane.entry_path(&entry.name);
+            crate::app::DeleteTarget {
+                backend: pane.storage.clone(),
+                path: path.clone(),
+                display_path: path,
+                is_dir: entry.is_dir,
+                size: entry.size,
+            }
+        })
+        .collect();
+
+    if !targets.is_empty() {
+        app.delete_targets = targets;
+        if app.auto_confirm_delete {
+            delete_targets(app).await?;
         } else {
-             format!("{}/{}", pane.path, entry_name)
-        };
-        
-        // Populate generic DeleteTarget
-        app.delete_target = Some(crate::app::DeleteTarget {
-            backend: pane.storage.clone(),
-            path: path.clone(),
-            display_path: path.clone(),
-            is_dir,
-        });
-        
-        app.mode = crate::app::AppMode::ConfirmDelete;
-        app.message = "Press Y to confirm delete, N or Esc to cancel".to_string();
+            app.mode = crate::app::AppMode::ConfirmDelete;
+            app.message = confirm_delete_hint(app);
+            start_delete_impact(app);
+        }
     }
 
     Ok(())
 }
 
-/// Handle confirmation dialog for delete
+/// Count the files and bytes below the directories about to be deleted, in the background,
+/// for the confirmation popup. Answering the popup stops the count.
+fn start_delete_impact(app: &mut App) {
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+
+    stop_delete_impact(app);
+    if !app.delete_targets.iter().any(|target| target.is_dir) {
+        return;
+    }
+
+    let targets = app.delete_targets.clone();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    app.delete_impact_cancelled = Some(Arc::clone(&cancelled));
+    app.delete_impact_rx = Some(rx);
+
+    tokio::spawn(async move {
+        if let Some(impact) = crate::app::DeleteImpact::measure(&targets, &cancelled).await {
+            let _ = tx.send(impact);
+        }
+    });
+}
+
+/// Cancel a running delete impact count and forget its result
+fn stop_delete_impact(app: &mut App) {
+    if let Some(cancelled) = app.delete_impact_cancelled.take() {
+        cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+    app.delete_impact_rx = None;
+    app.delete_impact = None;
+}
+
+/// Status line while the delete confirmation is open
+fn confirm_delete_hint(app: &App) -> String {
+    if app.dry_run {
+        "Dry run: press Y to log what would be deleted, N or Esc to cancel".to_string()
+    } else {
+        "Press Y to confirm delete, N or Esc to cancel".to_string()
+    }
+}
+
+/// Event stream counters for one deleted entry (directory sizes are 0 unless measured)
+fn delete_event_stats(target: &crate::app::DeleteTarget) -> OperationStats {
+    OperationStats {
+        files: usize::from(!target.is_dir),
+        directories: usize::from(target.is_dir),
+        bytes: target.size,
+        ..Default::default()
+    }
+}
+
+/// Delete everything in `app.delete_targets` in the background, with a progress bar
+/// counting removed objects; Esc stops it between objects (or batches, on S3/GCS).
+/// In a dry run the targets are only logged.
+async fn delete_targets(app: &mut App) -> Result<()> {
+    use std::sync::Arc;
+
+    if app.dry_run {
+        let targets = std::mem::take(&mut app.delete_targets);
+        for target in &targets {
+            let bytes = (!target.is_dir).then_some(target.size);
+            app.dry_run_log.would_delete(&target.display_path, target.is_dir, bytes);
+            OperationEvents::start(app.event_log.clone(), Operation::Delete, None, target.display_path.clone(), true)
+                .completed(delete_event_stats(target));
+        }
+        app.message = format!("Dry run: logged {} entries, nothing was deleted", targets.len());
+        return Ok(());
+    }
+
+    // Deletes run beside copies and hashing, but not under the paths those use
+    let targets = std::mem::take(&mut app.delete_targets);
+    if app.delete_task.is_some() {
+        app.message = "Another delete is in progress, try again when it finishes".to_string();
+        return Ok(());
+    }
+    if let Some(busy) = app.delete_conflict(&targets) {
+        app.message = format!("{} is in use by a running operation, try again when it finishes", busy);
+        return Ok(());
+    }
+
+    let progress = Arc::new(crate::fs::backend::DeleteProgress::default());
+    let task_progress = Arc::clone(&progress);
+    let current_file = match targets.as_slice() {
+        [target] => target.display_path.clone(),
+        _ => format!("{} entries", targets.len()),
+    };
+    let event_log = app.event_log.clone();
+
+    // Finishing the task refreshes both panes, which drops marks on deleted entries
+    app.delete_task = Some(tokio::spawn(async move {
+        let progress = task_progress;
+        let mut deleted = 0;
+        let mut last_error = None;
+        for target in &targets {
+            let events = OperationEvents::start(event_log.clone(), Operation::Delete, None, target.display_path.clone(), false);
+            match target.backend.delete_with_progress(&target.path, &progress).await {
+                Ok(_) => {
+                    deleted += 1;
+                    events.completed(delete_event_stats(target));
+                }
+                Err(e) => {
+                    events.failed(&e);
+                    last_error = Some((target.display_path.clone(), e));
+                }
+            }
+            if progress.is_cancelled() {
+                break;
+            }
+        }
+
+        if progress.is_cancelled() {
+            return Ok(format!(
+                "⏹ Delete cancelled; {} of {} objects were removed",
+                progress.deleted(),
+                progress.total()
+            ));
+        }
+        Ok(match (last_error, targets.as_slice()) {
+            (None, [target]) => format!("✓ Deleted {}", target.display_path),
+            (None, _) => format!("✓ Deleted {} entries", deleted),
+            (Some((_, e)), [_]) => format!("✗ Error deleting: {}", e),
+            (Some((path, e)), _) => format!(
+                "✗ Deleted {} of {} entries; {} failed: {}",
+                deleted,
+                targets.len(),
+                path,
+                e
+            ),
+        })
+    }));
+    app.delete_progress = Some(progress);
+    if app.progress.is_none() {
+        app.progress = Some(crate::app::Progress {
+            stage: crate::app::ProgressStage::Deleting,
+            current: 0,
+            total: 0,
+            current_file,
+            files_done: 0,
+            total_files: 0,
+            batch: None,
+        });
+    }
+    app.message = "Deleting... (Esc to cancel)".to_string();
+    Ok(())
+}
+
 /// Handle confirmation dialog for delete
 async fn handle_confirm_delete(app: &mut App, key: KeyEvent) -> Result<()> {
     match key.code {
         KeyCode::Char('y') | KeyCode::Char('Y') => {
-            // User confirmed delete
-            if let Some(target) = app.delete_target.clone() {
-                // Generic delete operation
-                match target.backend.delete(&target.path).await {
-                    Ok(_) => {
-                        app.message = format!("✓ Deleted {}", target.display_path);
-                        // Refresh active pane so changes are reflected
-                        app.refresh_active_pane().await?;
-                    }
-                    Err(e) => {
-                         app.message = format!("✗ Error deleting: {}", e);
-                    }
-                }
-            }
-            app.delete_target = None;
+            // User confirmed delete, whether or not the count has finished
+            stop_delete_impact(app);
+            delete_targets(app).await?;
             app.mode = AppMode::Normal;
         }
         KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
             // User cancelled
+            stop_delete_impact(app);
             app.message = "Delete cancelled".to_string();
-            app.delete_target = None;
+            app.delete_targets.clear();
             app.mode = AppMode::Normal;
         }
         _ => {
             // Ignore other keys, remind user
-            app.message = "Press Y to confirm delete, N or Esc to cancel".to_string();
+            app.message = confirm_delete_hint(app);
         }
     }
 
@@ -1124,21 +2016,26 @@ async fn handle_disk_analyzer_enter(app: &mut App) -> Result<()> {
     use std::sync::Arc;
     use std::sync::atomic::AtomicBool;
 
-    // Check if active pane is local filesystem
+    // Local panes use the fast jwalk scan, other backends are walked with list_dir
     let pane = app.active_pane();
-    if !pane.storage.is_local() {
-        app.message = "Disk analyzer only available for local filesystem".to_string();
+    let backend = if pane.storage.is_local() {
+        None
+    } else if matches!(pane.storage.backend_type(), crate::fs::BackendType::Selecting) {
+        app.message = "Select a storage location to analyze first".to_string();
         return Ok(());
-    }
+    } else {
+        Some(Arc::clone(&pane.storage))
+    };
 
     let current_path = std::path::PathBuf::from(&pane.path);
-    if !current_path.exists() || !current_path.is_dir() {
+    if backend.is_none() && (!current_path.exists() || !current_path.is_dir()) {
         app.message = "Invalid directory for analysis".to_string();
         return Ok(());
     }
+    let display_path = pane.storage.display_path(&pane.path);
 
     // Set up cleaner config and matcher
-    let config = Arc::new(cleaner::Config::default());
+    let config = Arc::new(cleaner::Config { ignore_case: app.patterns_ignore_case(), ..cleaner::Config::default() });
     let matcher = Arc::new(cleaner::PatternMatcher::new(Arc::clone(&config)));
 
     // Set up async scan content
@@ -1160,38 +2057,67 @@ async fn handle_disk_analyzer_enter(app: &mut App) -> Result<()> {
     app.cleaner_confirm_clean = false;
     app.cleaner_status = None;
     app.cleaner_status_time = None;
+    app.cleaner_backend = backend.clone();
 
     // Create channel for result
     let (tx, rx) = crossbeam_channel::bounded(1);
     app.cleaner_scan_rx = Some(rx);
 
-    // Spawn scanning thread
-    let path_clone = current_path.clone();
-    std::thread::spawn(move || {
-        let tree = cleaner::DirTree::build_with_progress(&path_clone, &matcher, progress, cancelled);
-        let _ = tx.send(tree);
-    });
+    spawn_tree_scan(backend, current_path, matcher, progress, cancelled, tx);
 
     app.mode = AppMode::DiskAnalyzer;
-    app.message = format!("Scanning {}...", current_path.display());
+    app.message = format!("Scanning {}...", display_path);
 
     Ok(())
 }
 
+/// Build the analyzer tree in the background: a jwalk thread for local paths,
+/// a tokio task listing directories for other backends
+fn spawn_tree_scan(
+    backend: Option<std::sync::Arc<dyn crate::fs::StorageBackend>>,
+    root: std::path::PathBuf,
+    matcher: std::sync::Arc<crate::cleaner::PatternMatcher>,
+    progress: std::sync::Arc<crate::cleaner::ScanProgress>,
+    cancelled: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    tx: crossbeam_channel::Sender<crate::cleaner::DirTree>,
+) {
+    use crate::cleaner::DirTree;
+
+    match backend {
+        Some(backend) => {
+            tokio::spawn(async move {
+                let root = root.to_string_lossy().to_string();
+                let tree = DirTree::build_from_backend(backend, &root, &matcher, progress, cancelled).await;
+                let _ = tx.send(tree);
+            });
+        }
+        None => {
+            std::thread::spawn(move || {
+                let tree = DirTree::build_with_progress(&root, &matcher, progress, cancelled);
+                let _ = tx.send(tree);
+            });
+        }
+    }
+}
+
 /// Apply sort to cleaner entries
 fn cleaner_apply_sort(app: &mut App) {
     use crate::cleaner::tree::{sort_by_name, sort_by_size};
     match app.cleaner_sort_mode {
-        crate::app::CleanerSortMode::Size => sort_by_size(&mut app.cleaner_entries),
+        crate::app::CleanerSortMode::Size => sort_by_size(&mut app.cleaner_entries, app.cleaner_size_metric),
         crate::app::CleanerSortMode::Name => sort_by_name(&mut app.cleaner_entries),
     }
 }
 
+/// Size of the listed analyzer entries under the chosen metric
+fn cleaner_total(app: &App) -> u64 {
+    app.cleaner_entries.iter().map(|e| e.size_of(app.cleaner_size_metric)).sum()
+}
+
 async fn handle_disk_analyzer(app: &mut App, key: KeyEvent) -> Result<()> {
     use crate::cleaner;
     use std::sync::Arc;
 
-
     // Clear expired status
     if let Some(time) = app.cleaner_status_time {
         if time.elapsed().as_secs() >= 10 {
@@ -1200,80 +2126,53 @@ async fn handle_disk_analyzer(app: &mut App, key: KeyEvent) -> Result<()> {
         }
     }
 
-    match key.code {
-        KeyCode::Esc | KeyCode::Char('q') => {
-            // Cancel scan if running
-            if let Some(ref cancelled) = app.cleaner_scan_cancelled {
-                cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
-            }
-            // Clear scan state
-            app.cleaner_progress = None;
-            app.cleaner_scan_cancelled = None;
-            app.cleaner_scan_rx = None;
+    let action = app.keymap.analyzer_action(&key);
+    let quit = key.code == KeyCode::Esc || action == Some(AnalyzerAction::Quit);
 
-            // Exit analyzer, return to normal mode
-            app.mode = AppMode::Normal;
-            app.cleaner_tree = None;
-            app.cleaner_entries.clear();
-            app.message = "Returned to file browser".to_string();
-        }
-        KeyCode::Up | KeyCode::Char('k') => {
-            if app.cleaner_selected > 0 {
-                app.cleaner_selected -= 1;
+    // While a clean runs only cancelling is possible; the tree is rebuilt when it stops
+    if app.cleaner_clean_rx.is_some() {
+        if quit {
+            if let Some(ref cancelled) = app.cleaner_clean_cancelled {
+                cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
             }
-            app.cleaner_confirm_delete = false;
-            app.cleaner_confirm_clean = false;
+            app.message = "Cancelling clean...".to_string();
         }
-        KeyCode::Down | KeyCode::Char('j') => {
-            if app.cleaner_selected < app.cleaner_entries.len().saturating_sub(1) {
-                app.cleaner_selected += 1;
-            }
-            app.cleaner_confirm_delete = false;
-            app.cleaner_confirm_clean = false;
+        return Ok(());
+    }
+
+    // Esc, the confirmation answers and the breadcrumb digits are fixed; the rest comes
+    // from the keymap's [analyzer] section
+    match key.code {
+        KeyCode::Char(c @ '1'..='9') => {
+            // Jump to the numbered ancestor in the header breadcrumb
+            cleaner_jump_to_ancestor(app, c as usize - '1' as usize);
         }
-        KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
-            // Drill down into selected directory
+        KeyCode::Char('y') if app.cleaner_confirm_delete && app.dry_run => {
             if let Some(entry) = app.cleaner_entries.get(app.cleaner_selected).cloned() {
-                if entry.is_dir {
-                    if entry.name == ".." {
-                        // Go back
-                        cleaner_go_back(app);
-                    } else {
-                        // Enter directory
-                        app.cleaner_path_stack.push(app.cleaner_path.clone());
-                        app.cleaner_path = entry.path.clone();
-                        cleaner_load_current_dir(app);
-                    }
+                if entry.name != ".." {
+                    let bytes = entry.size_of(app.cleaner_size_metric);
+                    app.dry_run_log.would_delete(&entry.path.display().to_string(), entry.is_dir, Some(bytes));
+                    app.cleaner_status = Some(format!(
+                        "Dry run: would delete {} ({})",
+                        entry.name,
+                        humansize::format_size(bytes, humansize::BINARY)
+                    ));
+                    app.cleaner_status_time = Some(std::time::Instant::now());
                 }
             }
-        }
-        KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => {
-            cleaner_go_back(app);
-        }
-        KeyCode::Char('c') => {
-            // Toggle clean confirmation
-            app.cleaner_confirm_clean = !app.cleaner_confirm_clean;
             app.cleaner_confirm_delete = false;
         }
-        KeyCode::Char('d') => {
-            // Toggle delete confirmation
-            if !app.cleaner_entries.is_empty() {
-                if let Some(entry) = app.cleaner_entries.get(app.cleaner_selected) {
-                    if entry.name != ".." {
-                        app.cleaner_confirm_delete = !app.cleaner_confirm_delete;
-                        app.cleaner_confirm_clean = false;
-                    }
-                }
-            }
-        }
         KeyCode::Char('y') if app.cleaner_confirm_delete => {
             // Delete selected item
             if let Some(entry) = app.cleaner_entries.get(app.cleaner_selected).cloned() {
                 if entry.name != ".." {
-                    let result = if entry.is_dir {
-                        std::fs::remove_dir_all(&entry.path)
-                    } else {
-                        std::fs::remove_file(&entry.path)
+                    let result = match app.cleaner_backend {
+                        Some(ref backend) => backend
+                            .delete(&entry.path.to_string_lossy())
+                            .await
+                            .map_err(std::io::Error::other),
+                        // Symlinks are removed as links, never followed
+                        None => crate::fs::remove_path(&entry.path),
                     };
 
                     match result {
@@ -1281,7 +2180,7 @@ async fn handle_disk_analyzer(app: &mut App, key: KeyEvent) -> Result<()> {
                             app.cleaner_status = Some(format!(
                                 "Deleted: {} ({})",
                                 entry.name,
-                                humansize::format_size(entry.size, humansize::BINARY)
+                                humansize::format_size(entry.size_of(app.cleaner_size_metric), humansize::BINARY)
                             ));
                             app.cleaner_status_time = Some(std::time::Instant::now());
 
@@ -1307,69 +2206,174 @@ async fn handle_disk_analyzer(app: &mut App, key: KeyEvent) -> Result<()> {
             if let Some(config) = &app.cleaner_config {
                 let root = app.cleaner_path.clone();
                 let config_clone = Arc::clone(config);
-                
+                // The tree supplies match sizes for progress; it is rebuilt once the clean stops
+                let tree = app.cleaner_tree.take()
+                    .unwrap_or_else(|| cleaner::DirTree { children: Default::default() });
+                app.cleaner_clean_total = tree.reclaimable_size(&root);
+
                 // Create shared stats
                 let stats = Arc::new(cleaner::Stats::new());
                 app.cleaner_delete_stats = Some(Arc::clone(&stats));
+                let cancelled = Arc::new(std::sync::atomic::AtomicBool::new(false));
+                app.cleaner_clean_cancelled = Some(Arc::clone(&cancelled));
 
                 let (tx_res, rx_res) = crossbeam_channel::bounded(1);
                 app.cleaner_clean_rx = Some(rx_res);
 
-                let stats_clone = Arc::clone(&stats);
-                
-                std::thread::spawn(move || {
-                    let (tx_files, rx_files) = crossbeam_channel::unbounded();
-                    let scanner = cleaner::Scanner::new(root, num_cpus::get(), config_clone);
-
-                    // Run scanner
-                    let _scanned = scanner.scan(tx_files);
-
-                    // Process deletions (using delete stats)
-                    let deleter = cleaner::Deleter::new(stats_clone, false, false);
-                    deleter.process(rx_files);
-                    
-                    let _ = tx_res.send(Ok(()));
-                });
-                
-                app.message = "Cleaning in progress...".to_string();
+                if app.dry_run {
+                    // Matches go to the dry-run log instead of being deleted
+                    let (tx_events, rx_events) = crossbeam_channel::unbounded();
+                    app.cleaner_dry_run_rx = Some(rx_events);
+                    std::thread::spawn(move || {
+                        tree.simulate_clean(&root, config_clone, stats, cancelled, tx_events);
+                        let _ = tx_res.send(Ok(()));
+                    });
+                    app.message = "Dry run in progress...".to_string();
+                } else {
+                    std::thread::spawn(move || {
+                        tree.clean_with_progress(&root, config_clone, stats, cancelled);
+                        let _ = tx_res.send(Ok(()));
+                    });
+                    app.message = "Cleaning in progress...".to_string();
+                }
             }
             app.cleaner_confirm_clean = false;
         }
-        KeyCode::Char('n') => {
+        KeyCode::Char('n') if app.cleaner_confirm_delete || app.cleaner_confirm_clean => {
             app.cleaner_confirm_delete = false;
             app.cleaner_confirm_clean = false;
         }
-        KeyCode::Char('s') => {
-            // Toggle sort
-            app.cleaner_sort_mode = match app.cleaner_sort_mode {
-                crate::app::CleanerSortMode::Size => crate::app::CleanerSortMode::Name,
-                crate::app::CleanerSortMode::Name => crate::app::CleanerSortMode::Size,
-            };
-            cleaner_apply_sort(app);
-        }
-        KeyCode::Char('r') => {
-            // Refresh
-            cleaner_rebuild_tree(app);
-            app.cleaner_status = Some("Refreshed".to_string());
-            app.cleaner_status_time = Some(std::time::Instant::now());
-        }
-        KeyCode::Home | KeyCode::Char('g') => {
-            app.cleaner_selected = 0;
-            app.cleaner_scroll = 0;
-            app.cleaner_confirm_delete = false;
-            app.cleaner_confirm_clean = false;
-        }
-        KeyCode::End | KeyCode::Char('G') => {
-            app.cleaner_selected = app.cleaner_entries.len().saturating_sub(1);
-            app.cleaner_confirm_delete = false;
-            app.cleaner_confirm_clean = false;
+        _ if quit => {
+            // Cancel scan if running
+            if let Some(ref cancelled) = app.cleaner_scan_cancelled {
+                cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+            }
+            // Clear scan state
+            app.cleaner_progress = None;
+            app.cleaner_scan_cancelled = None;
+            app.cleaner_scan_rx = None;
+            app.cleaner_backend = None;
+
+            // Exit analyzer, return to normal mode
+            app.mode = AppMode::Normal;
+            app.cleaner_tree = None;
+            app.cleaner_entries.clear();
+            // The analyzer may have deleted files the panes still list
+            app.refresh_both_panes().await?;
+            app.message = "Returned to file browser".to_string();
         }
-        _ => {}
+        _ => match action {
+            Some(AnalyzerAction::Up) => {
+                if app.cleaner_selected > 0 {
+                    app.cleaner_selected -= 1;
+                }
+                app.cleaner_confirm_delete = false;
+                app.cleaner_confirm_clean = false;
+            }
+            Some(AnalyzerAction::Down) => {
+                if app.cleaner_selected < app.cleaner_entries.len().saturating_sub(1) {
+                    app.cleaner_selected += 1;
+                }
+                app.cleaner_confirm_delete = false;
+                app.cleaner_confirm_clean = false;
+            }
+            Some(AnalyzerAction::Open) => {
+                // Drill down into selected directory
+                if let Some(entry) = app.cleaner_entries.get(app.cleaner_selected).cloned() {
+                    if entry.is_dir {
+                        if entry.name == ".." {
+                            // Go back
+                            cleaner_go_back(app);
+                        } else {
+                            // Enter directory
+                            app.cleaner_path_stack.push(app.cleaner_path.clone());
+                            app.cleaner_path = entry.path.clone();
+                            cleaner_load_current_dir(app);
+                        }
+                    }
+                }
+            }
+            Some(AnalyzerAction::Parent) => {
+                cleaner_go_back(app);
+            }
+            Some(AnalyzerAction::Clean) => {
+                // The cleaner scanner walks the local filesystem only
+                if app.cleaner_backend.is_some() {
+                    app.cleaner_status = Some("Clean is only available for local directories".to_string());
+                    app.cleaner_status_time = Some(std::time::Instant::now());
+                    return Ok(());
+                }
+                // Toggle clean confirmation
+                app.cleaner_confirm_clean = !app.cleaner_confirm_clean;
+                app.cleaner_confirm_delete = false;
+            }
+            Some(AnalyzerAction::Delete) => {
+                // Toggle delete confirmation
+                if !app.cleaner_entries.is_empty() {
+                    if let Some(entry) = app.cleaner_entries.get(app.cleaner_selected) {
+                        if entry.name != ".." {
+                            app.cleaner_confirm_delete = !app.cleaner_confirm_delete;
+                            app.cleaner_confirm_clean = false;
+                        }
+                    }
+                }
+            }
+            Some(AnalyzerAction::DryRun) => {
+                // Same session switch as Alt+D in the file panes
+                app.dry_run = !app.dry_run;
+                app.cleaner_status = Some(if app.dry_run {
+                    "Dry run ON: deletes and cleans are only logged".to_string()
+                } else {
+                    "Dry run OFF".to_string()
+                });
+                app.cleaner_status_time = Some(std::time::Instant::now());
+            }
+            Some(AnalyzerAction::Sort) => {
+                // Toggle sort
+                app.cleaner_sort_mode = match app.cleaner_sort_mode {
+                    crate::app::CleanerSortMode::Size => crate::app::CleanerSortMode::Name,
+                    crate::app::CleanerSortMode::Name => crate::app::CleanerSortMode::Size,
+                };
+                cleaner_apply_sort(app);
+            }
+            Some(AnalyzerAction::SizeMetric) => {
+                // Switch between apparent size and allocated disk usage
+                app.cleaner_size_metric = app.cleaner_size_metric.toggled();
+                cleaner_apply_sort(app);
+                app.cleaner_total_size = cleaner_total(app);
+                app.cleaner_status = Some(format!("Showing {}", app.cleaner_size_metric.label()));
+                app.cleaner_status_time = Some(std::time::Instant::now());
+            }
+            Some(AnalyzerAction::Refresh) => {
+                // Refresh
+                cleaner_rebuild_tree(app);
+                app.cleaner_status = Some("Refreshed".to_string());
+                app.cleaner_status_time = Some(std::time::Instant::now());
+            }
+            Some(AnalyzerAction::Top) => {
+                app.cleaner_selected = 0;
+                app.cleaner_scroll = 0;
+                app.cleaner_confirm_delete = false;
+                app.cleaner_confirm_clean = false;
+            }
+            Some(AnalyzerAction::Bottom) => {
+                app.cleaner_selected = app.cleaner_entries.len().saturating_sub(1);
+                app.cleaner_confirm_delete = false;
+                app.cleaner_confirm_clean = false;
+            }
+            Some(AnalyzerAction::Quit) | None => {}
+        },
     }
 
     Ok(())
 }
 
+/// Whether `key` is the pane quit chord; other modes type or handle the same keys
+/// themselves, so only Ctrl+C quits from there
+fn is_quit_key(app: &App, key: &KeyEvent) -> bool {
+    matches!(app.mode, AppMode::Normal) && app.keymap.action(key) == Some(Action::Quit)
+}
+
 /// Go back in cleaner navigation
 fn cleaner_go_back(app: &mut App) {
     if let Some(prev) = app.cleaner_path_stack.pop() {
@@ -1382,6 +2386,25 @@ fn cleaner_go_back(app: &mut App) {
     app.cleaner_confirm_clean = false;
 }
 
+/// Jump several levels up at once: `level` indexes the breadcrumb (0 = scan root).
+/// The directory leading back down toward where we were is reselected.
+fn cleaner_jump_to_ancestor(app: &mut App, level: usize) {
+    app.cleaner_confirm_delete = false;
+    app.cleaner_confirm_clean = false;
+    if level >= app.cleaner_path_stack.len() {
+        return;
+    }
+    let child_name = app.cleaner_path_stack.get(level + 1)
+        .unwrap_or(&app.cleaner_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string());
+    app.cleaner_path_stack.truncate(level + 1);
+    if let Some(target) = app.cleaner_path_stack.pop() {
+        app.cleaner_path = target;
+    }
+    cleaner_load_current_dir_with_selection(app, child_name.as_deref());
+}
+
 /// Load current directory entries
 fn cleaner_load_current_dir(app: &mut App) {
     cleaner_load_current_dir_with_selection(app, None);
@@ -1392,7 +2415,7 @@ fn cleaner_load_current_dir_with_selection(app: &mut App, select_name: Option<&s
     if let Some(ref tree) = app.cleaner_tree {
         app.cleaner_entries = tree.get_children(&app.cleaner_path);
         cleaner_apply_sort(app);
-        app.cleaner_total_size = app.cleaner_entries.iter().map(|e| e.size).sum();
+        app.cleaner_total_size = cleaner_total(app);
     }
 
     // Try to select the specified item
@@ -1433,13 +2456,7 @@ fn cleaner_rebuild_tree(app: &mut App) {
         let (tx, rx) = crossbeam_channel::bounded(1);
         app.cleaner_scan_rx = Some(rx);
         
-        let matcher_clone = Arc::clone(matcher);
-        let root_clone = root.clone();
-
-        std::thread::spawn(move || {
-            let tree = cleaner::DirTree::build_with_progress(&root_clone, &matcher_clone, progress, cancelled);
-            let _ = tx.send(tree);
-        });
+        spawn_tree_scan(app.cleaner_backend.clone(), root.clone(), Arc::clone(matcher), progress, cancelled, tx);
         
         app.message = format!("Rescanning {}...", root.display());
     }
@@ -1451,74 +2468,14 @@ fn cleaner_rebuild_tree(app: &mut App) {
 
 /// Move file/directory from active pane to other pane (copy + delete).
 async fn handle_move(app: &mut App) -> Result<()> {
-    // Don't start new move if one is already running
-    if app.background_task.is_some() {
-        app.message = "Operation already in progress...".to_string();
+    // Skip ".." (marked entries never include it)
+    let pane = app.active_pane();
+    if pane.marked_entries().is_empty() && pane.selected_entry().is_some_and(|e| e.name == "..") {
+        app.message = "Cannot move '..'".to_string();
         return Ok(());
     }
 
-    // Get source and destination panes
-    let (src_pane, dest_pane) = match app.active_pane {
-        crate::app::ActivePane::Left => (&app.left_pane, &app.right_pane),
-        crate::app::ActivePane::Right => (&app.right_pane, &app.left_pane),
-    };
-
-    if let Some(entry) = src_pane.selected_entry() {
-        // Skip ".."
-        if entry.name == ".." {
-            app.message = "Cannot move '..'".to_string();
-            return Ok(());
-        }
-        
-        let entry_name = entry.name.clone();
-        let entry_size = entry.size;
-        let entry_is_dir = entry.is_dir;
-        
-        // Compute paths
-        let src_path = if src_pane.path.ends_with('/') || src_pane.path.is_empty() {
-            format!("{}{}", src_pane.path, entry_name)
-        } else {
-            format!("{}/{}", src_pane.path, entry_name)
-        };
-        
-        let dest_path = if dest_pane.path.ends_with('/') || dest_pane.path.is_empty() {
-            format!("{}{}", dest_pane.path, entry_name)
-        } else {
-            format!("{}/{}", dest_pane.path, entry_name)
-        };
-        
-        let src_backend = src_pane.storage.clone();
-        let dest_backend = dest_pane.storage.clone();
-        
-        app.message = format!("🔄 Moving {}...", entry_name);
-        
-        let entry_name_clone = entry_name.clone();
-        
-        // Spawn move task
-        let handle = tokio::spawn(async move {
-            crate::fs::copy::move_between_backends(
-                src_backend.as_ref(),
-                &src_path,
-                dest_backend.as_ref(),
-                &dest_path,
-            ).await?;
-            
-            Ok(format!("✓ Moved {} successfully", entry_name_clone))
-        });
-        
-        app.background_task = Some(handle);
-        
-        app.progress = Some(crate::app::Progress {
-            stage: crate::app::ProgressStage::Transferring,
-            current: 0,
-            total: entry_size,
-            current_file: entry_name,
-            files_done: 0,
-            total_files: if entry_is_dir { 0 } else { 1 },
-        });
-    } else {
-        app.message = "No entry selected".to_string();
-    }
+    enqueue_operations(app, crate::app::OperationKind::Move);
     Ok(())
 }
 
@@ -1538,7 +2495,8 @@ async fn handle_mkdir(app: &mut App) -> Result<()> {
     match pane.storage.create_dir(&new_path).await {
         Ok(_) => {
             app.message = format!("✓ Created directory: {}", new_dir_name);
-            app.refresh_active_pane().await?;
+            app.refresh_both_panes().await?;
+            app.active_pane_mut().select_name(Some(new_dir_name), None);
         }
         Err(e) => {
             app.message = format!("❌ Failed to create directory: {}", e);
@@ -1548,6 +2506,225 @@ async fn handle_mkdir(app: &mut App) -> Result<()> {
     Ok(())
 }
 
+/// Prompt for the name of a new empty file in the active pane.
+fn handle_create_file_start(app: &mut App) {
+    if matches!(app.active_pane().storage.backend_type(), crate::fs::BackendType::Selecting) {
+        app.message = "Cannot create files here, open a storage location first".to_string();
+        return;
+    }
+    app.text_input = crate::app::TextInput::new("");
+    app.mode = AppMode::CreateFile;
+    app.message = "Enter file name (Enter to create, Esc to cancel)".to_string();
+}
+
+/// Handle new file name input.
+async fn handle_create_file_mode(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        KeyCode::Esc => {
+            app.mode = AppMode::Normal;
+            app.message = "Create file cancelled".to_string();
+        }
+        KeyCode::Enter => {
+            let name = app.text_input.value.trim().to_string();
+            if name.is_empty() {
+                app.message = "Name cannot be empty".to_string();
+                return Ok(());
+            }
+            if name.contains('/') || name == "." || name == ".." {
+                app.message = format!("Invalid file name: {}", name);
+                return Ok(());
+            }
+
+            let pane = app.active_pane();
+            let path = if pane.path.ends_with('/') || pane.path.is_empty() {
+                format!("{}{}", pane.path, name)
+            } else {
+                format!("{}/{}", pane.path, name)
+            };
+            let backend = pane.storage.clone();
+
+            match backend.create_file(&path).await {
+                Ok(_) => {
+                    app.mode = AppMode::Normal;
+                    app.refresh_both_panes().await?;
+                    app.active_pane_mut().select_name(Some(name.as_str()), None);
+                    app.message = format!("✓ Created file: {}", name);
+                }
+                Err(crate::fs::FsError::AlreadyExists(_)) => {
+                    // Keep the prompt open so the name can be changed
+                    app.message = format!("'{}' already exists", name);
+                }
+                Err(e) => {
+                    app.mode = AppMode::Normal;
+                    app.message = format!("❌ Failed to create file: {}", e);
+                }
+            }
+        }
+        KeyCode::Backspace => {
+            app.text_input.delete_back();
+        }
+        KeyCode::Left => {
+            app.text_input.move_left();
+        }
+        KeyCode::Right => {
+            app.text_input.move_right();
+        }
+        KeyCode::Char(c) => {
+            app.text_input.insert(c);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Queue the selected file for an external program; the main loop runs it
+fn request_external_open(app: &mut App, program: crate::app::ExternalProgram) {
+    let pane = app.active_pane();
+    if matches!(pane.storage.backend_type(), crate::fs::BackendType::Selecting) {
+        return;
+    }
+
+    let Some(entry) = pane.selected_entry() else {
+        app.message = "No file selected".to_string();
+        return;
+    };
+    if entry.is_dir {
+        app.message = format!("'{}' is a directory", entry.name);
+        return;
+    }
+
+    let path = if pane.path.ends_with('/') || pane.path.is_empty() {
+        format!("{}{}", pane.path, entry.name)
+    } else {
+        format!("{}/{}", pane.path, entry.name)
+    };
+
+    app.external_open = Some(crate::app::ExternalOpen {
+        program,
+        pane: app.active_pane,
+        storage: std::sync::Arc::clone(&pane.storage),
+        path,
+        name: entry.name.clone(),
+    });
+}
+
+/// Size and mtime, to tell whether an external editor saved the file
+fn file_stamp(path: &std::path::Path) -> Option<(u64, std::time::SystemTime)> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.len(), meta.modified().ok()?))
+}
+
+/// Run a queued external program with the terminal handed over to it.
+///
+/// Remote files are downloaded to a temp directory first and, for the editor,
+/// uploaded back when the program changed them.
+pub async fn run_external_open<B: ratatui::backend::Backend>(
+    terminal: &mut ratatui::Terminal<B>,
+    app: &mut App,
+    request: crate::app::ExternalOpen,
+) -> Result<()> {
+    use crate::app::ExternalProgram;
+    use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+
+    let is_local = request.storage.is_local();
+    let temp_dir = std::env::temp_dir().join(format!(
+        "abyss-open-{}-{}",
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0)
+    ));
+
+    let local_path = if is_local {
+        std::path::PathBuf::from(&request.path)
+    } else {
+        // Keep the file name so the program can pick a syntax/mode from the extension
+        let local = temp_dir.join(&request.name);
+        app.message = format!("Downloading {}...", request.name);
+        let downloaded = async {
+            tokio::fs::create_dir_all(&temp_dir).await?;
+            request.storage.download(&request.path, &local).await?;
+            anyhow::Ok(())
+        }
+        .await;
+        if let Err(e) = downloaded {
+            let _ = std::fs::remove_dir_all(&temp_dir);
+            app.message = format!("Failed to download {}: {}", request.name, e);
+            return Ok(());
+        }
+        local
+    };
+
+    let command = request.program.command();
+    let mut words = command.split_whitespace();
+    let program = words.next().unwrap_or_default().to_string();
+    let args: Vec<String> = words.map(|w| w.to_string()).collect();
+    let before = file_stamp(&local_path);
+
+    // Hand the terminal over
+    disable_raw_mode()?;
+    crossterm::execute!(std::io::stdout(), LeaveAlternateScreen)?;
+
+    let status = tokio::process::Command::new(&program)
+        .args(&args)
+        .arg(&local_path)
+        .status()
+        .await;
+
+    // Take it back, whatever the program did
+    enable_raw_mode()?;
+    crossterm::execute!(std::io::stdout(), EnterAlternateScreen)?;
+    terminal.clear()?;
+
+    let changed = file_stamp(&local_path) != before;
+    let mut keep_copy = false;
+    app.message = match status {
+        Err(e) => format!("Failed to run '{}': {}", command, e),
+        Ok(status) if !status.success() => format!("'{}' exited with {}", command, status),
+        Ok(_) if !is_local && changed && request.program == ExternalProgram::Editor => {
+            match request.storage.upload(&local_path, &request.path).await {
+                Ok(()) => format!("✓ Saved {} back to {}", request.name, request.storage.display_path(&request.path)),
+                Err(e) => {
+                    // Don't throw away the user's edits
+                    keep_copy = true;
+                    format!("Failed to upload {}: {} (edited copy left at {})", request.name, e, local_path.display())
+                }
+            }
+        }
+        Ok(_) => format!("Closed {}", request.name),
+    };
+
+    if !is_local && !keep_copy {
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    // Size/mtime may have changed; keep the cursor on the file
+    if request.program == ExternalProgram::Editor && changed {
+        app.refresh_both_panes().await?;
+        app.pane_mut(request.pane).select_name(Some(request.name.as_str()), None);
+    }
+
+    Ok(())
+}
+
+/// Swap the panes so copy/sync direction can be reversed without re-navigating.
+fn handle_swap_panes(app: &mut App) {
+    app.swap_panes();
+
+    if app.sync_enabled {
+        let (src_backend, src_path, dst_backend, dst_path) = sync_endpoints(app);
+        app.message = format!(
+            "⇄ Panes swapped - sync now runs {} {} {}",
+            src_backend.display_path(&src_path),
+            if app.sync_direction == crate::app::SyncDirection::Bidirectional { "↔" } else { "→" },
+            dst_backend.display_path(&dst_path),
+        );
+    } else {
+        app.message = "⇄ Panes swapped".to_string();
+    }
+}
+
 // ============================================================================
 // Sync Handlers (Phase 3)
 // ============================================================================
@@ -1560,7 +2737,11 @@ fn handle_sync_toggle(app: &mut App) -> Result<()> {
     
     if app.sync_enabled {
         app.sync_status = SyncStatus::Idle;
-        app.message = "🔄 Sync enabled - Left pane ↔ Right pane | Ctrl+Y to sync now, Ctrl+D for dry-run".to_string();
+        app.message = format!(
+            "🔄 Sync enabled ({}, conflicts: {}) | Ctrl+Y to sync now, Ctrl+D for dry-run, Ctrl+T to change direction, Ctrl+K conflict strategy",
+            app.sync_direction.label(),
+            app.sync_conflict_strategy.label()
+        );
     } else {
         app.sync_status = SyncStatus::Disabled;
         app.message = "Sync disabled".to_string();
@@ -1569,11 +2750,41 @@ fn handle_sync_toggle(app: &mut App) -> Result<()> {
     Ok(())
 }
 
+/// Cycle the sync direction used by Ctrl+Y and Ctrl+D.
+fn handle_sync_direction_cycle(app: &mut App) {
+    app.sync_direction = app.sync_direction.next();
+    app.message = format!("🔄 Sync direction: {}", app.sync_direction.label());
+}
+
+/// Cycle the conflict strategy used when both sides changed a file.
+fn handle_sync_conflict_cycle(app: &mut App) {
+    app.sync_conflict_strategy = app.sync_conflict_strategy.next();
+    app.message = format!(
+        "🔄 Conflict strategy: {} - {}{}",
+        app.sync_conflict_strategy.label(),
+        app.sync_conflict_strategy.description(),
+        if app.sync_direction == crate::app::SyncDirection::Bidirectional { "" } else { " (applies to L↔R sync)" }
+    );
+}
+
+/// Source and destination (backend, path) for the current sync direction.
+/// Bidirectional sync treats the left pane as the source side.
+fn sync_endpoints(app: &App) -> (std::sync::Arc<dyn crate::fs::StorageBackend>, String, std::sync::Arc<dyn crate::fs::StorageBackend>, String) {
+    let left = (app.left_pane.storage.clone(), app.left_pane.path.clone());
+    let right = (app.right_pane.storage.clone(), app.right_pane.path.clone());
+
+    let ((src_backend, src_path), (dst_backend, dst_path)) = match app.sync_direction {
+        crate::app::SyncDirection::RightToLeft => (right, left),
+        _ => (left, right),
+    };
+    (src_backend, src_path, dst_backend, dst_path)
+}
+
 /// Perform a one-time sync between left and right panes.
 /// This spawns the sync as a background task and returns immediately.
 async fn handle_sync_now(app: &mut App) -> Result<()> {
     use crate::app::SyncStatus;
-    use crate::sync::{SyncEngine, SyncConfig, SyncMode};
+    use crate::sync::{SyncEngine, SyncConfig};
     
     if !app.sync_enabled {
         app.message = "Sync not enabled - Press Ctrl+S to enable".to_string();
@@ -1586,38 +2797,74 @@ async fn handle_sync_now(app: &mut App) -> Result<()> {
         return Ok(());
     }
     
+    // Get backends and paths in the configured direction
+    let (src_backend, src_path, dst_backend, dst_path) = sync_endpoints(app);
+
+    // Both panes on the same location (or one inside the other) would sync onto itself
+    if let Some(message) = crate::sync::overlap::check_overlap(&*src_backend, &src_path, &*dst_backend, &dst_path) {
+        app.message = format!("✗ {}", message);
+        app.sync_status = SyncStatus::Error { message };
+        return Ok(());
+    }
+
     app.sync_status = SyncStatus::Scanning;
     app.message = "🔄 Starting sync (background)...".to_string();
     
-    // Get backends and paths from both panes
-    let left_backend = app.left_pane.storage.clone();
-    let right_backend = app.right_pane.storage.clone();
-    let left_path = app.left_pane.path.clone();
-    let right_path = app.right_pane.path.clone();
-    
     // Create progress channel
     let (progress_tx, progress_rx) = tokio::sync::mpsc::channel(100);
     
     // Create sync engine with progress
     let config = SyncConfig {
-        mode: SyncMode::OneWay, // Left -> Right by default
+        mode: app.sync_direction.mode(),
+        conflict_strategy: app.sync_conflict_strategy,
+        exclude: app.sync_excludes(),
         ..Default::default()
     };
     
-    let mut engine = SyncEngine::with_progress(left_backend, right_backend, config, progress_tx);
+    let (src_type, dst_type) = (src_backend.backend_type(), dst_backend.backend_type());
+    let events = OperationEvents::start(
+        app.event_log.clone(),
+        Operation::Sync,
+        Some(src_backend.display_path(&src_path)),
+        dst_backend.display_path(&dst_path),
+        false,
+    );
+    let mut engine = SyncEngine::with_progress(src_backend, dst_backend, config, progress_tx);
     
     // Spawn sync task - runs in background, doesn't block TUI
     let sync_handle = tokio::spawn(async move {
-        engine.sync(&left_path, &right_path).await
+        let result = engine.sync(&src_path, &dst_path).await;
+        match result {
+            Ok(ref result) => events.completed(OperationStats {
+                files: result.stats.files_copied + result.stats.files_deleted,
+                directories: result.stats.dirs_created,
+                bytes: result.stats.bytes_transferred,
+                skipped: result.stats.files_skipped,
+                conflicts: result.conflicts.len(),
+                ..Default::default()
+            }),
+            Err(ref e) => events.failed(e),
+        }
+        result
     });
     
     // Store handles for polling in main event loop
+    app.sync_backends = Some((src_type, dst_type));
     app.sync_task = Some(sync_handle);
     app.sync_progress_rx = Some(progress_rx);
     
     Ok(())
 }
 
+/// Add a finished sync's bytes to the session transfer totals
+fn record_sync_transfer(app: &mut App, stats: &crate::sync::engine::SyncStats) {
+    if let Some((source, dest)) = app.sync_backends.take() {
+        let to_dest = stats.bytes_transferred.saturating_sub(stats.bytes_to_source);
+        app.transfer_totals.record(&source, &dest, to_dest);
+        app.transfer_totals.record(&dest, &source, stats.bytes_to_source);
+    }
+}
+
 /// Poll for sync progress updates. Called from main event loop.
 /// Returns true if sync is still in progress.
 pub async fn poll_sync_progress(app: &mut App) -> Result<bool> {
@@ -1646,6 +2893,7 @@ pub async fn poll_sync_progress(app: &mut App) -> Result<bool> {
                 current_file: p.current_file.clone(),
                 files_done: p.files_done,
                 total_files: p.total_files,
+                batch: None,
             });
             
             app.sync_status = SyncStatus::Syncing {
@@ -1667,6 +2915,7 @@ pub async fn poll_sync_progress(app: &mut App) -> Result<bool> {
                     
                     match task.await {
                         Ok(Ok(result)) => {
+                            record_sync_transfer(app, &result.stats);
                             let files_synced = result.stats.files_copied + result.stats.dirs_created;
                             app.sync_status = SyncStatus::Complete { files_synced };
                             app.progress = None;
@@ -1679,10 +2928,12 @@ pub async fn poll_sync_progress(app: &mut App) -> Result<bool> {
                             );
                             
                             // Refresh both panes
-                            app.refresh_pane(crate::app::ActivePane::Left).await?;
-                            app.refresh_pane(crate::app::ActivePane::Right).await?;
+                            app.refresh_both_panes().await?;
                         }
                         Ok(Err(e)) => {
+                            if let Some(failed) = e.downcast_ref::<crate::sync::SyncFailed>() {
+                                record_sync_transfer(app, &failed.stats);
+                            }
                             app.sync_status = SyncStatus::Error { message: e.to_string() };
                             app.progress = None;
                             app.message = format!("❌ Sync failed: {}", e);
@@ -1708,6 +2959,7 @@ pub async fn poll_sync_progress(app: &mut App) -> Result<bool> {
             if let Some(task) = app.sync_task.take() {
                 match task.await {
                     Ok(Ok(result)) => {
+                        record_sync_transfer(app, &result.stats);
                         let files_synced = result.stats.files_copied + result.stats.dirs_created;
                         app.sync_status = SyncStatus::Complete { files_synced };
                         app.progress = None;
@@ -1718,10 +2970,12 @@ pub async fn poll_sync_progress(app: &mut App) -> Result<bool> {
                             result.stats.files_skipped
                         );
                         
-                        app.refresh_pane(crate::app::ActivePane::Left).await?;
-                        app.refresh_pane(crate::app::ActivePane::Right).await?;
+                        app.refresh_both_panes().await?;
                     }
                     Ok(Err(e)) => {
+                        if let Some(failed) = e.downcast_ref::<crate::sync::SyncFailed>() {
+                            record_sync_transfer(app, &failed.stats);
+                        }
                         app.sync_status = SyncStatus::Error { message: e.to_string() };
                         app.progress = None;
                         app.message = format!("❌ Sync failed: {}", e);
@@ -1741,28 +2995,27 @@ pub async fn poll_sync_progress(app: &mut App) -> Result<bool> {
 /// Perform a dry-run sync to preview changes.
 async fn handle_sync_dry_run(app: &mut App) -> Result<()> {
     use crate::app::SyncStatus;
-    use crate::sync::{SyncEngine, SyncConfig, SyncMode, SyncAction};
+    use crate::sync::{SyncEngine, SyncConfig, SyncAction};
     
     app.sync_status = SyncStatus::Scanning;
     app.message = "🔍 Analyzing changes (dry-run)...".to_string();
     
-    // Get backends and paths from both panes
-    let left_backend = app.left_pane.storage.clone();
-    let right_backend = app.right_pane.storage.clone();
-    let left_path = app.left_pane.path.clone();
-    let right_path = app.right_pane.path.clone();
+    // Preview with the same direction the real sync will use
+    let (src_backend, src_path, dst_backend, dst_path) = sync_endpoints(app);
     
     // Create sync engine with dry-run enabled
     let config = SyncConfig {
-        mode: SyncMode::OneWay,
+        mode: app.sync_direction.mode(),
+        conflict_strategy: app.sync_conflict_strategy,
+        exclude: app.sync_excludes(),
         dry_run: true,
         ..Default::default()
     };
     
-    let mut engine = SyncEngine::new(left_backend, right_backend, config);
+    let mut engine = SyncEngine::new(src_backend, dst_backend, config);
     
     // Perform dry-run
-    match engine.dry_run(&left_path, &right_path).await {
+    match engine.dry_run(&src_path, &dst_path).await {
         Ok(result) => {
             // Count actions by type
             let mut copies = 0;
@@ -1773,7 +3026,8 @@ async fn handle_sync_dry_run(app: &mut App) -> Result<()> {
             
             for action in &result.actions {
                 match action {
-                    SyncAction::CopyToDestination { .. } | SyncAction::CopyToSource { .. } => copies += 1,
+                    SyncAction::CopyToDestination { .. } | SyncAction::CopyToSource { .. }
+                    | SyncAction::CopyToDestinationAs { .. } | SyncAction::CopyToSourceAs { .. } => copies += 1,
                     SyncAction::CreateDirInDestination { .. } | SyncAction::CreateDirInSource { .. } => creates += 1,
                     SyncAction::DeleteFromDestination { .. } | SyncAction::DeleteFromSource { .. } => deletes += 1,
                     SyncAction::Skip { .. } => skips += 1,
@@ -1783,8 +3037,8 @@ async fn handle_sync_dry_run(app: &mut App) -> Result<()> {
             
             app.sync_status = SyncStatus::Idle;
             app.message = format!(
-                "Dry-run: {} to copy, {} to create, {} to delete, {} skip, {} conflicts | Ctrl+Y to apply",
-                copies, creates, deletes, skips, conflicts
+                "Dry-run ({}): {} to copy, {} to create, {} to delete, {} skip, {} conflicts | Ctrl+Y to apply",
+                app.sync_direction.label(), copies, creates, deletes, skips, conflicts
             );
         }
         Err(e) => {
@@ -1859,7 +3113,8 @@ async fn handle_rename_mode(app: &mut App, key: KeyEvent) -> Result<()> {
                 Ok(_) => {
                     app.message = format!("Renamed '{}' to '{}'", old_name, new_name);
                     app.mode = AppMode::Normal;
-                    app.refresh_active_pane().await?;
+                    app.refresh_both_panes().await?;
+                    app.active_pane_mut().select_name(Some(new_name.as_str()), None);
                 }
                 Err(e) => {
                     app.message = format!("Rename failed: {}", e);
@@ -1923,24 +3178,22 @@ async fn handle_view_file(app: &mut App) -> Result<()> {
         
         // Read file content and load into editor as readonly
         match backend.read_bytes(&path).await {
+            Ok(data) if crate::ui::hexdump::looks_binary(&data) => {
+                // Binary content would be a mess of replacement characters as text
+                app.view_hex = true;
+                app.view_hscroll = 0;
+                app.view_chunk_size = crate::app::view_chunk_size(backend.is_local());
+                let chunk = &data[..data.len().min(app.view_chunk_size as usize)];
+                show_hex_chunk(app, chunk, &path, 0, data.len() as u64);
+            }
             Ok(data) => {
-                let content = String::from_utf8_lossy(&data).to_string();
-                let lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
-                
-                app.editor = crate::app::TextEditor {
-                    content: if lines.is_empty() { vec![String::new()] } else { lines },
-                    cursor_row: 0,
-                    cursor_col: 0,
-                    scroll_offset: 0,
-                    filename: entry.name.clone(),
-                    modified: false,
-                    cut_buffer: None,
-                    visible_height: 0,
-                    readonly: true,  // View mode is readonly
-                };
+                app.view_file_path = path.clone();
+                app.view_file_size = data.len() as u64;
+                app.view_file_offset = 0;
+                app.editor = crate::app::TextEditor::read_only(entry.name.clone(), &data);
                 
                 app.mode = AppMode::EditFile;  // Use same mode, but readonly flag prevents edits
-                app.message = format!("Viewing: {} (readonly) - q/Esc to close", entry.name);
+                app.message = format!("Viewing: {} (readonly) - x for hex, q/Esc to close", entry.name);
             }
             Err(e) => {
                 app.message = format!("Failed to read file: {}", e);
@@ -1953,10 +3206,33 @@ async fn handle_view_file(app: &mut App) -> Result<()> {
 }
 
 async fn load_view_chunk(app: &mut App, backend: std::sync::Arc<dyn crate::fs::StorageBackend>, path: &str, offset: u64, total_size: u64) -> Result<()> {
-    match backend.read_range(path, offset, 64 * 1024).await { // 64KB chunk
-        Ok(data) => {
-             let content = String::from_utf8_lossy(&data);
+    let chunk = crate::app::view_chunk_size(backend.is_local());
+    app.view_chunk_size = chunk;
+    if app.view_hex {
+        // Hex rows are 16 bytes and chunk sizes are multiples of 16, so offsets line up across chunks
+        match backend.read_range(path, offset, chunk).await {
+            Ok(data) => show_hex_chunk(app, &data, path, offset, total_size),
+            Err(e) => app.message = format!("Failed to stream: {}", e),
+        }
+        return Ok(());
+    }
+
+    match backend.read_text_range(path, offset, chunk).await {
+        Ok(content) => {
+             // Carry absolute line numbers across adjacent chunks once the start line is known
+             let newlines = content.matches('\n').count();
+             app.view_line_base = if offset == 0 {
+                 Some(0)
+             } else if offset == app.view_file_offset + chunk {
+                 app.view_line_base.map(|base| base + app.view_chunk_newlines)
+             } else if offset + chunk == app.view_file_offset {
+                 app.view_line_base.and_then(|base| base.checked_sub(newlines))
+             } else {
+                 None
+             };
+             app.view_chunk_newlines = newlines;
              app.view_content = content.lines().map(|s| s.to_string()).collect();
+             app.view_selection = None;
              // Add continuation marker if we truncated a line or middle of file?
              // Simple approach: just show lines.
              app.view_scroll = 0;
@@ -1973,20 +3249,159 @@ async fn load_view_chunk(app: &mut App, backend: std::sync::Arc<dyn crate::fs::S
     Ok(())
 }
 
+/// Start streaming a file from its first chunk, as a hex dump when its start looks binary.
+async fn open_view_stream(app: &mut App, backend: std::sync::Arc<dyn crate::fs::StorageBackend>, path: &str, total_size: u64) -> Result<()> {
+    let sniff_len = (crate::ui::hexdump::BINARY_SNIFF_LEN as u64).min(total_size);
+    app.view_hex = match backend.read_range_sized(path, 0, sniff_len, total_size).await {
+        Ok(start) => crate::ui::hexdump::looks_binary(&start),
+        Err(_) => false,
+    };
+    app.view_hscroll = 0;
+    load_view_chunk(app, backend, path, 0, total_size).await
+}
+
+/// Show a chunk of raw bytes in the streaming viewer as a hex dump.
+fn show_hex_chunk(app: &mut App, data: &[u8], path: &str, offset: u64, total_size: u64) {
+    app.view_line_base = None;
+    app.view_chunk_newlines = 0;
+    app.view_content = crate::ui::hexdump::hex_dump_lines(data, offset);
+    app.view_selection = None;
+    app.view_scroll = 0;
+    app.view_file_size = total_size;
+    app.view_file_offset = offset;
+    app.view_file_path = path.to_string();
+    app.mode = AppMode::ViewFile;
+    app.message = format!(
+        "Viewing hex ({}%): {} - x for text, q/Esc to close",
+        (offset * 100) / total_size.max(1),
+        path
+    );
+}
+
+/// Switch the streaming viewer between text and hex, reloading the current chunk.
+async fn toggle_view_hex(app: &mut App, path: String, total_size: u64) -> Result<()> {
+    app.view_hex = !app.view_hex;
+    app.view_hscroll = 0;
+    let offset = if total_size > 0 { app.view_file_offset } else { 0 };
+    let backend = app.active_pane().storage.clone();
+    load_view_chunk(app, backend, &path, offset, total_size).await
+}
+
+/// Offset of the previous streaming viewer chunk, if the viewed file has one
+fn prev_chunk_offset_in_view(app: &App) -> Option<u64> {
+    if app.view_file_size == 0 {
+        return None;
+    }
+    crate::app::prev_chunk_offset(app.view_file_offset, app.view_chunk_size)
+}
+
+/// Offset of the next streaming viewer chunk, if the viewed file has one
+fn next_chunk_offset_in_view(app: &App) -> Option<u64> {
+    crate::app::next_chunk_offset(app.view_file_offset, app.view_chunk_size, app.view_file_size)
+}
+
+/// Columns moved per Left/Right press in the streaming viewer.
+const VIEW_HSCROLL_STEP: usize = 8;
+
+/// Toggle line wrapping in the viewers (shared by the streaming and readonly views).
+fn toggle_view_wrap(app: &mut App) {
+    app.view_wrap = !app.view_wrap;
+    app.view_hscroll = 0;
+    app.message = if app.view_wrap {
+        "Line wrap on".to_string()
+    } else {
+        "Line wrap off - ←/→ to scroll horizontally".to_string()
+    };
+}
+
+/// Copy lines from the streaming viewer to the clipboard and say how it went
+fn copy_view_lines(app: &mut App, text: String, what: &str) {
+    app.message = if write_clipboard(&text) {
+        format!("Copied {} to the clipboard", what)
+    } else {
+        "No clipboard available".to_string()
+    };
+}
+
+/// Keys of the streaming viewer while lines are selected: arrows and paging extend the
+/// selection within the loaded chunk. Returns false for keys the viewer handles as usual.
+fn handle_view_selection(app: &mut App, key: KeyEvent) -> bool {
+    let Some(mut selection) = app.view_selection else {
+        return false;
+    };
+    let page = app.view_visible_height.max(1);
+    let delta = match key.code {
+        KeyCode::Up | KeyCode::Char('k') => -1,
+        KeyCode::Down | KeyCode::Char('j') => 1,
+        KeyCode::PageUp => -(page as isize),
+        KeyCode::PageDown => page as isize,
+        KeyCode::Esc | KeyCode::Char('v') => {
+            app.view_selection = None;
+            app.message = "Selection cleared".to_string();
+            return true;
+        }
+        KeyCode::Char('y') => {
+            let (first, last) = selection.range();
+            let what = format!("{} line{}", last - first + 1, if first == last { "" } else { "s" });
+            copy_view_lines(app, selection.text(&app.view_content), &what);
+            app.view_selection = None;
+            return true;
+        }
+        _ => return false,
+    };
+
+    selection.move_cursor(delta, app.view_content.len());
+    // Keep the moving end on screen
+    if selection.cursor < app.view_scroll {
+        app.view_scroll = selection.cursor;
+    } else if selection.cursor >= app.view_scroll + page {
+        app.view_scroll = selection.cursor + 1 - page;
+    }
+    app.view_selection = Some(selection);
+    true
+}
+
 /// Handle view file mode input.
 async fn handle_view_file_mode(app: &mut App, key: KeyEvent) -> Result<()> {
+    if handle_view_selection(app, key) {
+        return Ok(());
+    }
     match key.code {
         KeyCode::Esc | KeyCode::Char('q') => {
-            app.mode = AppMode::Normal;
             app.view_content.clear();
-            app.message = String::new();
+            app.view_selection = None;
+            app.view_hex = false;
+            if std::mem::take(&mut app.view_returns_to_editor) {
+                // Back to the unsaved edits after looking at the on-disk version
+                app.mode = AppMode::EditFile;
+                app.message = format!("Editing: {} - ^O: WriteOut (asks again), ^X: Exit", app.editor.filename);
+            } else {
+                close_editor(app);
+                app.message = String::new();
+            }
         }
-        KeyCode::Up | KeyCode::Char('k') => {
-            if app.view_scroll > 0 {
+        KeyCode::Char('v') if !app.view_content.is_empty() => {
+            let line = app.view_scroll.min(app.view_content.len() - 1);
+            app.view_selection = Some(crate::app::ViewSelection::new(line));
+            app.message = "Select lines with ↑/↓/PgUp/PgDn, y to copy, v/Esc to cancel".to_string();
+        }
+        KeyCode::Char('y') => {
+            // Without a selection the current line is the top one on screen
+            if let Some(line) = app.view_content.get(app.view_scroll).cloned() {
+                copy_view_lines(app, line, "the current line");
+            }
+        }
+        KeyCode::Char('Y') => {
+            if let Some(lines) = app.view_content.get(app.visible_view_lines()) {
+                let what = format!("{} visible lines", lines.len());
+                copy_view_lines(app, lines.join("\n"), &what);
+            }
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            if app.view_scroll > 0 {
                 app.view_scroll -= 1;
-            } else if app.view_file_size > 0 && app.view_file_offset >= 64 * 1024 {
+            } else if let Some(prev_offset) = prev_chunk_offset_in_view(app) {
                 // Prev Chunk
-                let prev_offset = app.view_file_offset - 64 * 1024;
                 let pane = app.active_pane();
                 let backend = pane.storage.clone();
                 return load_view_chunk(app, backend, &app.view_file_path.clone(), prev_offset, app.view_file_size).await;
@@ -1995,9 +3410,8 @@ async fn handle_view_file_mode(app: &mut App, key: KeyEvent) -> Result<()> {
         KeyCode::Down | KeyCode::Char('j') => {
             if app.view_scroll < app.view_content.len().saturating_sub(20) {
                 app.view_scroll += 1;
-            } else if app.view_file_size > 0 && app.view_file_offset + 64 * 1024 < app.view_file_size {
+            } else if let Some(next_offset) = next_chunk_offset_in_view(app) {
                 // Next Chunk
-                let next_offset = app.view_file_offset + 64 * 1024;
                 let pane = app.active_pane();
                 let backend = pane.storage.clone();
                 return load_view_chunk(app, backend, &app.view_file_path.clone(), next_offset, app.view_file_size).await;
@@ -2006,9 +3420,8 @@ async fn handle_view_file_mode(app: &mut App, key: KeyEvent) -> Result<()> {
         KeyCode::PageUp => {
             if app.view_scroll > 0 {
                 app.view_scroll = app.view_scroll.saturating_sub(20);
-            } else if app.view_file_size > 0 && app.view_file_offset >= 64 * 1024 {
+            } else if let Some(prev_offset) = prev_chunk_offset_in_view(app) {
                 // Prev Chunk
-                let prev_offset = app.view_file_offset - 64 * 1024;
                 let pane = app.active_pane();
                 let backend = pane.storage.clone();
                 return load_view_chunk(app, backend, &app.view_file_path.clone(), prev_offset, app.view_file_size).await;
@@ -2017,9 +3430,8 @@ async fn handle_view_file_mode(app: &mut App, key: KeyEvent) -> Result<()> {
         KeyCode::PageDown => {
             if app.view_scroll < app.view_content.len().saturating_sub(20) {
                  app.view_scroll = (app.view_scroll + 20).min(app.view_content.len().saturating_sub(20));
-            } else if app.view_file_size > 0 && app.view_file_offset + 64 * 1024 < app.view_file_size {
+            } else if let Some(next_offset) = next_chunk_offset_in_view(app) {
                  // Next Chunk
-                 let next_offset = app.view_file_offset + 64 * 1024;
                  let pane = app.active_pane();
                  let backend = pane.storage.clone();
                  return load_view_chunk(app, backend, &app.view_file_path.clone(), next_offset, app.view_file_size).await;
@@ -2038,11 +3450,117 @@ async fn handle_view_file_mode(app: &mut App, key: KeyEvent) -> Result<()> {
             app.view_scroll = app.view_content.len().saturating_sub(20);
              // Optionally: Jump to last chunk? (Tricky to calculate offset without ceil)
         }
+        KeyCode::Char('w') => {
+            toggle_view_wrap(app);
+        }
+        KeyCode::Char('x') => {
+            return toggle_view_hex(app, app.view_file_path.clone(), app.view_file_size).await;
+        }
+        KeyCode::Char('g') if app.view_hex => {
+            app.message = "Go to line is not available in hex mode (x for text)".to_string();
+        }
+        KeyCode::Char('g') => {
+            app.mode = AppMode::ViewGoToLine;
+            app.text_input.clear();
+            app.message = "Go to line: ".to_string();
+        }
+        KeyCode::Char('s') => {
+            start_save_as(app, AppMode::ViewSaveAs);
+        }
+        KeyCode::Left | KeyCode::Char('h') if !app.view_wrap => {
+            app.view_hscroll = app.view_hscroll.saturating_sub(VIEW_HSCROLL_STEP);
+        }
+        KeyCode::Right | KeyCode::Char('l') if !app.view_wrap => {
+            let longest = app.view_content.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+            app.view_hscroll = (app.view_hscroll + VIEW_HSCROLL_STEP).min(longest.saturating_sub(1));
+        }
         _ => {}
     }
     Ok(())
 }
 
+/// Ask where to download the viewed file, suggesting the other pane's directory when it
+/// is local and the working directory otherwise.
+fn start_save_as(app: &mut App, mode: AppMode) {
+    let name = viewed_file_name(app);
+    let other = app.inactive_pane();
+    let dir = if other.storage.is_local() {
+        std::path::PathBuf::from(&other.path)
+    } else {
+        std::env::current_dir().unwrap_or_default()
+    };
+    app.text_input = crate::app::TextInput::new(&dir.join(&name).to_string_lossy());
+    app.mode = mode;
+    app.message = format!("Save a local copy of {} to: ", name);
+}
+
+/// File name of the file open in the viewer
+fn viewed_file_name(app: &App) -> String {
+    app.view_file_path.trim_end_matches('/').rsplit('/').next().unwrap_or_default().to_string()
+}
+
+/// Prompt for the local path of a viewed file's download; Enter queues it like a copy, so it
+/// runs in the background with a progress bar while the viewer stays open.
+fn handle_save_as_mode(app: &mut App, key: KeyEvent) {
+    let back = if matches!(app.mode, AppMode::EditorSaveAs) { AppMode::EditFile } else { AppMode::ViewFile };
+    match key.code {
+        KeyCode::Esc => {
+            app.mode = back;
+            app.message = String::new();
+        }
+        KeyCode::Enter => {
+            let name = viewed_file_name(app);
+            let base = std::env::current_dir().unwrap_or_default();
+            let Some(target) = crate::app::download_target(&app.text_input.value, &name, &base) else {
+                app.message = "Path cannot be empty".to_string();
+                return;
+            };
+            if target.exists() {
+                // Keep the prompt open so another path can be typed
+                app.message = format!("'{}' already exists", target.display());
+                return;
+            }
+
+            let dest_path = target.to_string_lossy().to_string();
+            let op = crate::app::QueuedOperation {
+                kind: crate::app::OperationKind::Download,
+                name,
+                size: app.view_file_size,
+                is_dir: false,
+                src_storage: app.active_pane().storage.clone(),
+                src_path: app.view_file_path.clone(),
+                dest_storage: std::sync::Arc::new(crate::fs::LocalBackend::new(
+                    target.parent().map(|p| p.to_path_buf()).unwrap_or_default(),
+                )),
+                dest_path,
+                resume: false,
+                merge: Default::default(),
+            };
+            app.mode = back;
+            app.enqueue_operation(op);
+        }
+        KeyCode::Backspace => app.text_input.delete_back(),
+        KeyCode::Left => app.text_input.move_left(),
+        KeyCode::Right => app.text_input.move_right(),
+        KeyCode::Char(c) => app.text_input.insert(c),
+        _ => {}
+    }
+}
+
+/// Retry prompt shown after queued copies/moves failed
+fn handle_confirm_retry(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+            app.retry_failed_transfers();
+        }
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+            app.operation_queue.failed_transfers.clear();
+            app.mode = AppMode::Normal;
+        }
+        _ => {}
+    }
+}
+
 /// Handle confirmation for large file load.
 async fn handle_confirm_large_load_mode(app: &mut App, key: KeyEvent) -> Result<()> {
     match key.code {
@@ -2056,7 +3574,7 @@ async fn handle_confirm_large_load_mode(app: &mut App, key: KeyEvent) -> Result<
                          let pane = app.active_pane();
                          let backend = pane.storage.clone();
                          // Load first chunk
-                         load_view_chunk(app, backend, &app.view_file_path.clone(), 0, app.view_file_size).await?;
+                         open_view_stream(app, backend, &app.view_file_path.clone(), app.view_file_size).await?;
                     }
                     LargeFileAction::Edit => {
                          // Proceed to edit
@@ -2064,21 +3582,12 @@ async fn handle_confirm_large_load_mode(app: &mut App, key: KeyEvent) -> Result<
                          // Since we don't have separate function for "load editor", we duplicate logic for now
                          let pane = app.active_pane();
                          let backend = pane.storage.clone();
+                         // Taken before reading, so a write in between shows up as a change on save
+                         let on_disk = backend.stat(&app.view_file_path).await.ok().map(|stat| crate::app::FileVersion::from(&stat));
                          match backend.read_bytes(&app.view_file_path).await {
                              Ok(data) => {
-                                 let content = String::from_utf8_lossy(&data).to_string();
-                                 let lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
-                                 app.editor = crate::app::TextEditor {
-                                     content: if lines.is_empty() { vec![String::new()] } else { lines },
-                                     cursor_row: 0,
-                                     cursor_col: 0,
-                                     scroll_offset: 0,
-                                     filename: app.view_file_path.rsplit('/').next().unwrap_or("").to_string(), // Approximate default
-                                     modified: false,
-                                     cut_buffer: None,
-                                     visible_height: 0,
-                                     readonly: false,
-                                 };
+                                 let filename = app.view_file_path.rsplit('/').next().unwrap_or("").to_string();
+                                 app.editor = crate::app::TextEditor::open(filename, &data, on_disk);
                                  app.mode = AppMode::EditFile;
                                  app.message = format!("Editing: {} - ^O: WriteOut, ^X: Exit, ^K: Cut, ^U: Uncut", app.editor.filename);
                              }
@@ -2088,6 +3597,9 @@ async fn handle_confirm_large_load_mode(app: &mut App, key: KeyEvent) -> Result<
                              }
                          }
                     }
+                    LargeFileAction::Diff => {
+                         load_diff(app).await?;
+                    }
                 }
             } else {
                  app.mode = AppMode::Normal;
@@ -2123,7 +3635,12 @@ async fn handle_search_mode(app: &mut App, key: KeyEvent) -> Result<()> {
             app.message = "Search cancelled".to_string();
         }
         KeyCode::Enter => {
-            let pattern = app.text_input.value.to_lowercase();
+            let ignore_case = app.search_ignores_case();
+            let pattern = if ignore_case {
+                app.text_input.value.to_lowercase()
+            } else {
+                app.text_input.value.clone()
+            };
             if pattern.is_empty() {
                 app.mode = AppMode::Normal;
                 app.message = "Empty search pattern".to_string();
@@ -2135,8 +3652,27 @@ async fn handle_search_mode(app: &mut App, key: KeyEvent) -> Result<()> {
             let mut found_idx = None;
             let mut found_name = String::new();
             
+            // Wildcards, classes and braces match whole names; plain text matches anywhere
+            let glob = if crate::hash::wildcard::contains_wildcard(&pattern) {
+                match crate::hash::wildcard::build_name_pattern(&pattern, ignore_case) {
+                    Ok(glob) => Some(glob),
+                    Err(e) => {
+                        app.mode = AppMode::Normal;
+                        app.message = format!("✗ {}", e);
+                        return Ok(());
+                    }
+                }
+            } else {
+                None
+            };
+            
             for (i, entry) in pane.entries.iter().enumerate() {
-                if entry.name.to_lowercase().contains(&pattern) {
+                let matched = match &glob {
+                    Some(glob) => glob.is_match(&entry.name),
+                    None if ignore_case => entry.name.to_lowercase().contains(&pattern),
+                    None => entry.name.contains(&pattern),
+                };
+                if matched {
                     found_idx = Some(i);
                     found_name = entry.name.clone();
                     break;
@@ -2163,6 +3699,49 @@ async fn handle_search_mode(app: &mut App, key: KeyEvent) -> Result<()> {
     Ok(())
 }
 
+/// Prompt for a glob of entry names to mark (`+`) or unmark (`-`).
+fn handle_mark_pattern_start(app: &mut App, mark: bool) {
+    app.text_input = crate::app::TextInput::new("*");
+    app.mode = if mark { AppMode::MarkPattern } else { AppMode::UnmarkPattern };
+    app.message = "Pattern, e.g. *.log (Enter to apply, Esc to cancel)".to_string();
+}
+
+/// Handle the mark/unmark pattern prompt.
+fn handle_mark_pattern_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => {
+            app.mode = AppMode::Normal;
+            app.message = "Cancelled".to_string();
+        }
+        KeyCode::Enter => {
+            let mark = matches!(app.mode, AppMode::MarkPattern);
+            app.mode = AppMode::Normal;
+            let pattern = match crate::hash::wildcard::build_name_pattern(&app.text_input.value, app.patterns_ignore_case()) {
+                Ok(pattern) => pattern,
+                Err(e) => {
+                    app.message = format!("✗ {}", e);
+                    return;
+                }
+            };
+            let pane = app.active_pane_mut();
+            let changed = pane.mark_matching(&pattern, mark);
+            let total = pane.marked_entries().len();
+            app.message = format!(
+                "{} {} matching '{}' ({} marked)",
+                if mark { "Marked" } else { "Unmarked" },
+                changed,
+                pattern.glob().glob(),
+                total
+            );
+        }
+        KeyCode::Backspace => app.text_input.delete_back(),
+        KeyCode::Left => app.text_input.move_left(),
+        KeyCode::Right => app.text_input.move_right(),
+        KeyCode::Char(c) => app.text_input.insert(c),
+        _ => {}
+    }
+}
+
 // ============================================================================
 // File Editor Handlers
 // ============================================================================
@@ -2175,16 +3754,22 @@ async fn handle_edit_file_start(app: &mut App) -> Result<()> {
             return Ok(());
         }
         
-        
         if entry.is_dir {
             app.message = "Cannot edit directory".to_string();
             return Ok(());
         }
 
         let pane = app.active_pane();
+        let backend = pane.storage.clone();
+
+        // The editor holds the whole file as lines; anything bigger streams read-only instead
+        if entry.size > crate::app::TextEditor::max_size() {
+            let path = pane.entry_path(&entry.name);
+            open_over_editor_limit(app, backend, &path, &entry.name, entry.size).await?;
+            return Ok(());
+        }
         
         // Size check for remote
-        let backend = pane.storage.clone();
         let is_remote = matches!(backend.backend_type(), crate::fs::BackendType::S3 { .. } | crate::fs::BackendType::Gcs { .. });
         
         if is_remote && entry.size > 40 * 1024 * 1024 {
@@ -2209,24 +3794,13 @@ async fn handle_edit_file_start(app: &mut App) -> Result<()> {
         };
         
         let backend = pane.storage.clone();
+        // Taken before reading, so a write in between shows up as a change on save
+        let on_disk = backend.stat(&path).await.ok().map(|stat| crate::app::FileVersion::from(&stat));
         
         // Read file content
         match backend.read_bytes(&path).await {
             Ok(data) => {
-                let content = String::from_utf8_lossy(&data).to_string();
-                let lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
-                
-                app.editor = crate::app::TextEditor {
-                    content: if lines.is_empty() { vec![String::new()] } else { lines },
-                    cursor_row: 0,
-                    cursor_col: 0,
-                    scroll_offset: 0,
-                    filename: entry.name.clone(),
-                    modified: false,
-                    cut_buffer: None,
-                    visible_height: 0,
-                    readonly: false,
-                };
+                app.editor = crate::app::TextEditor::open(entry.name.clone(), &data, on_disk);
                 
                 app.mode = AppMode::EditFile;
                 app.message = format!("Editing: {} - ^O: WriteOut, ^X: Exit, ^K: Cut, ^U: Uncut", entry.name);
@@ -2241,9 +3815,142 @@ async fn handle_edit_file_start(app: &mut App) -> Result<()> {
     Ok(())
 }
 
+/// Stream a file too big for the editor read-only from its first chunk.
+async fn open_over_editor_limit(
+    app: &mut App,
+    backend: std::sync::Arc<dyn crate::fs::StorageBackend>,
+    path: &str,
+    name: &str,
+    size: u64,
+) -> Result<()> {
+    open_view_stream(app, backend, path, size).await?;
+    if matches!(app.mode, AppMode::ViewFile) {
+        app.message = format!(
+            "{} is {}, over the {} editor limit ({}): opened read-only",
+            name,
+            crate::fs::format_bytes(size),
+            crate::fs::format_bytes(crate::app::TextEditor::max_size()),
+            crate::app::EDITOR_MAX_SIZE_ENV
+        );
+    }
+    Ok(())
+}
+
+/// The edited file's current version if it changed on disk since it was opened, when
+/// saves are checked (see `TextEditor::checks_disk`)
+async fn editor_disk_change(app: &App) -> Option<crate::app::FileVersion> {
+    if !crate::app::TextEditor::checks_disk() {
+        return None;
+    }
+    app.editor_disk_change().await
+}
+
+/// Handle the overwrite prompt for a save onto a file that changed on disk:
+/// Y overwrites, V shows the on-disk version, N/Esc goes back to editing.
+async fn handle_confirm_editor_overwrite(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') => {
+            app.mode = AppMode::EditFile;
+            app.editor_disk_version = None;
+            handle_editor_save(app).await?;
+        }
+        KeyCode::Char('v') | KeyCode::Char('V') => {
+            let Some(current) = app.editor_disk_version.take() else {
+                app.mode = AppMode::EditFile;
+                return Ok(());
+            };
+            let backend = app.active_pane().storage.clone();
+            app.view_hex = false;
+            app.view_returns_to_editor = true;
+            load_view_chunk(app, backend, &app.editor_path(), 0, current.size).await?;
+            if !matches!(app.mode, AppMode::ViewFile) {
+                // Reading failed (the message says why); stay in the editor
+                app.view_returns_to_editor = false;
+                app.mode = AppMode::EditFile;
+            }
+        }
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+            app.mode = AppMode::EditFile;
+            app.editor_disk_version = None;
+            app.message = "Save cancelled; your changes are still in the editor".to_string();
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Leave the editor or viewer, returning to the recent files list if it was opened from there.
+fn close_editor(app: &mut App) {
+    if app.recent_viewing {
+        app.recent_viewing = false;
+        app.mode = AppMode::RecentFiles;
+    } else {
+        app.mode = AppMode::Normal;
+    }
+}
+
+/// Content size above which the editor saves in the background with progress.
+const BACKGROUND_SAVE_THRESHOLD: usize = 1024 * 1024;
+
+/// Save the editor content. Large content and remote backends are written by a
+/// background task with progress; the editor stays readonly until it finishes.
+async fn handle_editor_save(app: &mut App) -> Result<()> {
+    let content = app.editor.content.join("\n").into_bytes();
+    let path = app.editor_path();
+    let backend = app.active_pane().storage.clone();
+
+    if content.len() < BACKGROUND_SAVE_THRESHOLD && backend.is_local() {
+        match backend.write_bytes(&path, content).await {
+            Ok(_) => {
+                app.editor.modified = false;
+                app.record_editor_version().await;
+                app.message = format!("Saved '{}'", app.editor.filename);
+                app.refresh_both_panes().await?;
+            }
+            Err(e) => {
+                app.message = format!("Save failed: {}", e);
+            }
+        }
+        return Ok(());
+    }
+
+    // The progress bar and completion message share the background task slot
+    if app.background_task.is_some() {
+        app.message = "Another operation is in progress, try again when it finishes".to_string();
+        return Ok(());
+    }
+
+    let total = content.len() as u64;
+    let written = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
+    let written_task = std::sync::Arc::clone(&written);
+    let filename = app.editor.filename.clone();
+
+    app.background_paths = vec![backend.display_path(&path)];
+    app.background_task = Some(tokio::spawn(async move {
+        backend.write_bytes_with_progress(&path, content, &written_task).await
+            .map_err(|e| anyhow::anyhow!("Save failed: {}", e))?;
+        Ok(format!("Saved '{}'", filename))
+    }));
+    app.save_written = Some(written);
+    app.editor.saving = true;
+    app.progress = Some(crate::app::Progress {
+        stage: crate::app::ProgressStage::Saving,
+        current: 0,
+        total,
+        current_file: app.editor.filename.clone(),
+        files_done: 0,
+        total_files: 1,
+        batch: None,
+    });
+    app.message = format!("Saving '{}' ({})...", app.editor.filename, humansize::format_size(total, humansize::BINARY));
+
+    Ok(())
+}
+
 /// Handle edit file mode input.
 async fn handle_edit_file_mode(app: &mut App, key: KeyEvent) -> Result<()> {
-    let readonly = app.editor.readonly;
+    // Edits are blocked while a background save is writing the content
+    let readonly = app.editor.readonly || app.editor.saving;
     
     match key.code {
         // Exit: Ctrl+X or Ctrl+Q or Esc (or just q/Esc in readonly mode)
@@ -2251,44 +3958,33 @@ async fn handle_edit_file_mode(app: &mut App, key: KeyEvent) -> Result<()> {
             if app.editor.modified {
                 app.message = "Changes discarded".to_string();
             }
-            app.mode = AppMode::Normal;
+            close_editor(app);
         }
-        KeyCode::Char('q') if readonly || key.modifiers.contains(KeyModifiers::CONTROL) => {
+        KeyCode::Char('q') if app.editor.readonly || key.modifiers.contains(KeyModifiers::CONTROL) => {
             if app.editor.modified {
                 app.message = "Changes discarded".to_string();
             }
-            app.mode = AppMode::Normal;
+            close_editor(app);
         }
         KeyCode::Char('x') if key.modifiers.contains(KeyModifiers::CONTROL) => {
             if app.editor.modified {
                 app.message = "Changes discarded".to_string();
             }
-            app.mode = AppMode::Normal;
+            close_editor(app);
         }
         // Save: Ctrl+O (Write Out) or Ctrl+S - blocked in readonly mode
         KeyCode::Char('o') | KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-            if readonly {
+            if app.editor.saving {
+                app.message = "Save already in progress...".to_string();
+            } else if readonly {
                 app.message = "Cannot save: file is readonly".to_string();
+            } else if let Some(current) = editor_disk_change(app).await {
+                // Someone else wrote the file since it was opened: don't clobber it silently
+                app.editor_disk_version = Some(current);
+                app.mode = AppMode::ConfirmEditorOverwrite;
+                app.message = format!("'{}' changed on disk since it was opened", app.editor.filename);
             } else {
-                let content = app.editor.content.join("\n");
-                let pane = app.active_pane();
-                let path = if pane.path.ends_with('/') || pane.path.is_empty() {
-                    format!("{}{}", pane.path, app.editor.filename)
-                } else {
-                    format!("{}/{}", pane.path, app.editor.filename)
-                };
-                
-                let backend = pane.storage.clone();
-                match backend.write_bytes(&path, content.as_bytes().to_vec()).await {
-                    Ok(_) => {
-                        app.editor.modified = false;
-                        app.message = format!("Saved '{}'", app.editor.filename);
-                        app.refresh_active_pane().await?;
-                    }
-                    Err(e) => {
-                        app.message = format!("Save failed: {}", e);
-                    }
-                }
+                handle_editor_save(app).await?;
             }
         }
         // Cut Line: Ctrl+K - blocked in readonly mode
@@ -2309,6 +4005,30 @@ async fn handle_edit_file_mode(app: &mut App, key: KeyEvent) -> Result<()> {
             app.text_input.clear();
             app.message = "Search (Where Is): ".to_string();
         }
+        // Wrap toggle: w - readonly mode only
+        KeyCode::Char('w') if app.editor.readonly => {
+            toggle_view_wrap(app);
+        }
+        // Hex dump: x - readonly mode only (opens the streaming viewer)
+        KeyCode::Char('x') if app.editor.readonly && !app.recent_viewing && !app.view_file_path.is_empty() => {
+            app.view_hex = false;
+            return toggle_view_hex(app, app.view_file_path.clone(), app.view_file_size).await;
+        }
+        // Save a local copy: s - readonly mode only
+        KeyCode::Char('s') if app.editor.readonly && !app.recent_viewing && !app.view_file_path.is_empty() => {
+            start_save_as(app, AppMode::EditorSaveAs);
+        }
+        // Go to line: Ctrl+G (or g in readonly mode)
+        KeyCode::Char('g') if app.editor.readonly || key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.mode = AppMode::EditorGoToLine;
+            app.text_input.clear();
+            app.message = "Go to line: ".to_string();
+        }
+        // Line numbers toggle: Ctrl+N
+        KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.editor_line_numbers = !app.editor_line_numbers;
+            app.message = if app.editor_line_numbers { "Line numbers on" } else { "Line numbers off" }.to_string();
+        }
         // Navigation - always allowed
         KeyCode::Up => {
             if app.editor.cursor_row > 0 {
@@ -2460,3 +4180,1099 @@ async fn handle_editor_search_mode(app: &mut App, key: KeyEvent) -> Result<()> {
     }
     Ok(())
 }
+
+/// Handle the go-to-line prompt of the editor and the streaming viewer.
+async fn handle_goto_line_mode(app: &mut App, key: KeyEvent) -> Result<()> {
+    let in_viewer = matches!(app.mode, AppMode::ViewGoToLine);
+    match key.code {
+        KeyCode::Esc => {
+            app.mode = if in_viewer { AppMode::ViewFile } else { AppMode::EditFile };
+            app.message = String::new();
+        }
+        KeyCode::Enter => {
+            app.mode = if in_viewer { AppMode::ViewFile } else { AppMode::EditFile };
+            let line = match app.text_input.value.trim().parse::<usize>() {
+                Ok(line) if line > 0 => line,
+                _ => {
+                    app.message = format!("Invalid line number: '{}'", app.text_input.value);
+                    return Ok(());
+                }
+            };
+
+            if !in_viewer {
+                let reached = app.editor.go_to_line(line);
+                app.message = format!("Line {} of {}", reached, app.editor.content.len());
+                return Ok(());
+            }
+
+            // Streaming viewer: find where the line starts and load the chunk from there
+            let backend = app.active_pane().storage.clone();
+            let path = app.view_file_path.clone();
+            match find_line_offset(backend.as_ref(), &path, line, app.view_file_size).await {
+                Ok((offset, reached)) => {
+                    load_view_chunk(app, backend, &path, offset, app.view_file_size).await?;
+                    app.view_line_base = Some(reached - 1);
+                    app.message = if reached < line {
+                        format!("Line {} is past the end, showing last line {}", line, reached)
+                    } else {
+                        format!("Line {}", reached)
+                    };
+                }
+                Err(e) => {
+                    app.message = format!("Failed to find line {}: {}", line, e);
+                }
+            }
+        }
+        KeyCode::Backspace => app.text_input.delete_back(),
+        KeyCode::Left => app.text_input.move_left(),
+        KeyCode::Right => app.text_input.move_right(),
+        KeyCode::Char(c) if c.is_ascii_digit() => app.text_input.insert(c),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Bytes read per request while counting lines for go-to-line.
+const GOTO_SCAN_CHUNK: u64 = 1024 * 1024;
+
+/// Byte offset where 1-based `line` starts, counting newlines from the start of the file.
+///
+/// Returns the offset and the line reached, which is the last line if the file is shorter.
+async fn find_line_offset(
+    backend: &dyn crate::fs::StorageBackend,
+    path: &str,
+    line: usize,
+    total_size: u64,
+) -> Result<(u64, usize)> {
+    let mut current = 1;
+    let mut line_start = 0;
+    let mut offset = 0;
+    while current < line && offset < total_size {
+        let data = backend.read_range(path, offset, GOTO_SCAN_CHUNK).await?;
+        if data.is_empty() {
+            break;
+        }
+        for (i, _) in data.iter().enumerate().filter(|(_, &b)| b == b'\n') {
+            let next = offset + i as u64 + 1;
+            // A trailing newline does not start another line
+            if next >= total_size {
+                break;
+            }
+            current += 1;
+            line_start = next;
+            if current == line {
+                break;
+            }
+        }
+        offset += data.len() as u64;
+    }
+    Ok((line_start, current))
+}
+
+// ============================================================================
+// Quick Hash Check Handlers
+// ============================================================================
+
+/// Clipboard handle, kept open once it worked: on X11 the copied text is only served
+/// while its owner is alive
+static CLIPBOARD: std::sync::Mutex<Option<arboard::Clipboard>> = std::sync::Mutex::new(None);
+
+/// Run `f` on the system clipboard; `None` when there is none (no display to talk to,
+/// or no clipboard support)
+fn with_clipboard<T>(f: impl FnOnce(&mut arboard::Clipboard) -> Option<T>) -> Option<T> {
+    let mut clipboard = CLIPBOARD.lock().ok()?;
+    if clipboard.is_none() {
+        *clipboard = arboard::Clipboard::new().ok();
+    }
+    clipboard.as_mut().and_then(f)
+}
+
+/// Clipboard text, if there is a clipboard holding some
+fn read_clipboard() -> Option<String> {
+    with_clipboard(|clipboard| clipboard.get_text().ok())
+}
+
+/// Put `text` on the system clipboard. Returns false when there is no clipboard.
+fn write_clipboard(text: &str) -> bool {
+    with_clipboard(|clipboard| clipboard.set_text(text).ok()).is_some()
+}
+
+/// Follow the active pane's selection in the preview pane, reading the start of the
+/// selected file in the background once the selection stops moving
+fn update_preview(app: &mut App) {
+    if !app.preview.enabled {
+        return;
+    }
+
+    if let Some(ref rx) = app.preview.rx {
+        if let Ok(result) = rx.try_recv() {
+            match result {
+                Ok(data) => app.preview.show_data(&data),
+                Err(e) => app.preview.note = Some(format!("Cannot preview: {}", e.lines().next().unwrap_or_default())),
+            }
+            app.preview.rx = None;
+            app.needs_redraw = true;
+        }
+    }
+
+    // Menus and dialogs keep whatever was shown last
+    if !matches!(app.mode, AppMode::Normal) {
+        return;
+    }
+    let pane = app.active_pane();
+    if matches!(pane.storage.backend_type(), crate::fs::BackendType::Selecting) {
+        return;
+    }
+    let Some(entry) = pane.selected_entry().cloned() else { return };
+    let path = pane.entry_path(&entry.name);
+    let backend = pane.storage.clone();
+
+    if entry.is_dir {
+        if app.preview.path.as_deref() != Some(path.as_str()) {
+            app.preview.show_note(&path, "Directory");
+            app.needs_redraw = true;
+        }
+        return;
+    }
+    let Some(path) = app.preview.settle(&path, std::time::Instant::now()) else { return };
+    app.needs_redraw = true;
+    if !backend.is_local() && entry.size > crate::app::PREVIEW_REMOTE_MAX {
+        app.preview.show_note(&path, "Remote file too large to preview");
+        return;
+    }
+
+    // A newer read replaces the receiver, so a slow earlier one is simply dropped
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    app.preview.rx = Some(rx);
+    tokio::spawn(async move {
+        let result = backend
+            .read_range(&path, 0, crate::app::PREVIEW_BYTES)
+            .await
+            .map_err(|e| e.to_string());
+        let _ = tx.send(result);
+    });
+}
+
+/// Prompt for the expected hash of the selected local file, prefilled from the clipboard
+/// when it holds something that parses as a hash.
+fn handle_hash_check_start(app: &mut App) {
+    if app.hash_check_rx.is_some() {
+        app.message = "Hash check already running".to_string();
+        return;
+    }
+
+    let pane = app.active_pane();
+    if !pane.storage.is_local() {
+        app.message = "Hash check only available for local files".to_string();
+        return;
+    }
+    let Some(entry) = pane.selected_entry() else { return };
+    if entry.is_dir {
+        app.message = "Select a file to check its hash".to_string();
+        return;
+    }
+
+    let path = std::path::Path::new(&pane.path).join(&entry.name);
+    let clipboard = read_clipboard()
+        .map(|text| text.trim().to_string())
+        .filter(|text| crate::hash::parse_expected_hash(text).is_ok());
+
+    app.message = match clipboard {
+        Some(_) => "Expected hash from clipboard - Enter to check, Esc to cancel".to_string(),
+        None => "Paste the expected hash ([algorithm:]digest) - Enter to check, Esc to cancel".to_string(),
+    };
+    app.text_input = crate::app::TextInput::new(clipboard.as_deref().unwrap_or(""));
+    app.hash_check_path = Some(path);
+    app.mode = AppMode::HashCheckInput;
+}
+
+/// Copy the selected entry's info line (fields from `ABYSS_INFO_FIELDS`) to the clipboard.
+/// Hashes of local files are computed in the background first.
+fn copy_entry_info(app: &mut App) {
+    if app.info_line_rx.is_some() {
+        app.message = "Info line already being prepared".to_string();
+        return;
+    }
+    let fields = match crate::app::InfoField::from_env() {
+        Ok(fields) => fields,
+        Err(e) => {
+            app.message = e;
+            return;
+        }
+    };
+    let pane = app.active_pane();
+    let Some(entry) = pane.selected_entry().filter(|e| e.name != "..").cloned() else {
+        app.message = "No entry selected".to_string();
+        return;
+    };
+    let path = if pane.path.ends_with('/') || pane.path.is_empty() {
+        format!("{}{}", pane.path, entry.name)
+    } else {
+        format!("{}/{}", pane.path, entry.name)
+    };
+    let shown_path = pane.storage.display_path(&path);
+
+    let algorithms: Vec<String> = fields
+        .iter()
+        .filter_map(|field| match field {
+            crate::app::InfoField::Hash(algorithm) => Some(algorithm.clone()),
+            _ => None,
+        })
+        .collect();
+    if algorithms.is_empty() || entry.is_dir || !pane.storage.is_local() {
+        // Nothing to hash: remote files would have to be downloaded first
+        let line = crate::app::format_info_line(&entry, &shown_path, &fields, &[]);
+        finish_entry_info(app, line);
+        return;
+    }
+
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    app.info_line_rx = Some(rx);
+    app.message = format!("Hashing {}...", entry.name);
+    std::thread::spawn(move || {
+        let computer = crate::hash::HashComputer::new();
+        let result = computer
+            .compute_multiple_hashes(std::path::Path::new(&path), &algorithms)
+            .map(|results| {
+                let hashes: Vec<(String, String)> =
+                    results.into_iter().map(|r| (r.algorithm, r.hash.to_lowercase())).collect();
+                crate::app::format_info_line(&entry, &shown_path, &fields, &hashes)
+            })
+            .map_err(|e| e.to_string().lines().next().unwrap_or_default().to_string());
+        let _ = tx.send(result);
+    });
+}
+
+/// Read the metadata of the selected entry in the background for the details popup
+fn show_object_details(app: &mut App) {
+    if app.object_details_rx.is_some() {
+        app.message = "Details already being read".to_string();
+        return;
+    }
+    let pane = app.active_pane();
+    let Some(name) = pane.selected_entry().filter(|e| e.name != "..").map(|e| e.name.clone()) else {
+        app.message = "No entry selected".to_string();
+        return;
+    };
+    let path = if pane.path.ends_with('/') || pane.path.is_empty() {
+        format!("{}{}", pane.path, name)
+    } else {
+        format!("{}/{}", pane.path, name)
+    };
+    let shown_path = pane.storage.display_path(&path);
+    let backend = std::sync::Arc::clone(&pane.storage);
+
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    app.object_details_rx = Some(rx);
+    app.message = format!("Reading details of {}...", name);
+    tokio::spawn(async move {
+        let result = backend.object_meta(&path).await
+            .map(|meta| (shown_path, meta))
+            .map_err(|e| e.to_string());
+        let _ = tx.send(result);
+    });
+}
+
+/// Put a finished info line on the clipboard, or show it when there is no clipboard
+fn finish_entry_info(app: &mut App, line: String) {
+    app.message = if write_clipboard(&line) {
+        format!("Copied: {}", line)
+    } else {
+        format!("No clipboard available: {}", line)
+    };
+}
+
+fn handle_hash_check_input(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => {
+            app.mode = AppMode::Normal;
+            app.hash_check_path = None;
+            app.message = "Hash check cancelled".to_string();
+        }
+        KeyCode::Enter => {
+            let expected = match crate::hash::parse_expected_hash(&app.text_input.value) {
+                Ok(expected) => expected,
+                Err(e) => {
+                    // Stay in the prompt so the input can be fixed
+                    app.message = e.to_string().lines().next().unwrap_or_default().to_string();
+                    return;
+                }
+            };
+            let Some(path) = app.hash_check_path.take() else {
+                app.mode = AppMode::Normal;
+                return;
+            };
+
+            let (tx, rx) = crossbeam_channel::bounded(1);
+            app.hash_check_rx = Some(rx);
+            app.message = format!("Hashing {} with {}...", path.display(), expected.algorithm);
+            app.mode = AppMode::Normal;
+
+            std::thread::spawn(move || {
+                let result = crate::hash::check_file(&path, &expected)
+                    .map_err(|e| e.to_string().lines().next().unwrap_or_default().to_string());
+                let _ = tx.send(result);
+            });
+        }
+        KeyCode::Backspace => {
+            app.text_input.delete_back();
+        }
+        KeyCode::Left => {
+            app.text_input.move_left();
+        }
+        KeyCode::Right => {
+            app.text_input.move_right();
+        }
+        KeyCode::Char(c) => {
+            app.text_input.insert(c);
+        }
+        _ => {}
+    }
+}
+
+// ============================================================================
+// Recent Files Handlers
+// ============================================================================
+
+/// Start a background walk collecting the most recently modified files.
+fn handle_recent_files_enter(app: &mut App) -> Result<()> {
+    use crate::cleaner;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+
+    let pane = app.active_pane();
+    if !pane.storage.is_local() {
+        app.message = "Recent files finder only available for local filesystem".to_string();
+        return Ok(());
+    }
+
+    let root = std::path::PathBuf::from(&pane.path);
+    if !root.is_dir() {
+        app.message = "Invalid directory for recent files".to_string();
+        return Ok(());
+    }
+
+    let progress = Arc::new(cleaner::ScanProgress::new());
+    let cancelled = Arc::new(AtomicBool::new(false));
+
+    app.recent_progress = Some(Arc::clone(&progress));
+    app.recent_cancelled = Some(Arc::clone(&cancelled));
+    app.recent_root = root.clone();
+    app.recent_files.clear();
+    app.recent_selected = 0;
+    app.recent_viewing = false;
+
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    app.recent_rx = Some(rx);
+
+    let root_clone = root.clone();
+    std::thread::spawn(move || {
+        let files = cleaner::find_recent_files(
+            &root_clone,
+            cleaner::recent::DEFAULT_RECENT_LIMIT,
+            progress,
+            cancelled,
+        );
+        let _ = tx.send(files);
+    });
+
+    app.mode = AppMode::RecentFiles;
+    app.message = format!("Finding recently modified files in {}...", root.display());
+
+    Ok(())
+}
+
+/// Handle recent files list input.
+async fn handle_recent_files(app: &mut App, key: KeyEvent) -> Result<()> {
+    match key.code {
+        KeyCode::Esc => {
+            // Stop the walk if it is still running
+            if let Some(ref cancelled) = app.recent_cancelled {
+                cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+            }
+            app.recent_progress = None;
+            app.recent_cancelled = None;
+            app.recent_rx = None;
+            app.recent_files.clear();
+            app.mode = AppMode::Normal;
+            app.message = "Returned to file browser".to_string();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.recent_selected = app.recent_selected.saturating_sub(1);
+        }
+        KeyCode::Down | KeyCode::Char('j') if app.recent_selected < app.recent_files.len().saturating_sub(1) => {
+            app.recent_selected += 1;
+        }
+        KeyCode::PageUp => {
+            app.recent_selected = app.recent_selected.saturating_sub(20);
+        }
+        KeyCode::PageDown => {
+            app.recent_selected = (app.recent_selected + 20).min(app.recent_files.len().saturating_sub(1));
+        }
+        KeyCode::Home | KeyCode::Char('g') => {
+            app.recent_selected = 0;
+        }
+        KeyCode::End | KeyCode::Char('G') => {
+            app.recent_selected = app.recent_files.len().saturating_sub(1);
+        }
+        KeyCode::Char('s') => {
+            app.recent_sort_mode = app.recent_sort_mode.next();
+            crate::cleaner::recent::sort_recent(&mut app.recent_files, app.recent_sort_mode);
+            app.recent_selected = 0;
+        }
+        KeyCode::Char('r') => {
+            handle_recent_files_enter(app)?;
+        }
+        KeyCode::Enter => {
+            // Open the selected file in the readonly viewer
+            if let Some(file) = app.recent_files.get(app.recent_selected).cloned() {
+                let backend = app.active_pane().storage.clone();
+                let path = file.path.to_string_lossy().into_owned();
+                let filename = file.relative_to(&app.recent_root);
+                if file.size > crate::app::TextEditor::max_size() {
+                    open_over_editor_limit(app, backend, &path, &filename, file.size).await?;
+                    app.recent_viewing = matches!(app.mode, AppMode::ViewFile);
+                    return Ok(());
+                }
+                match backend.read_bytes(&path).await {
+                    Ok(data) => {
+                        app.editor = crate::app::TextEditor::read_only(filename.clone(), &data);
+                        app.recent_viewing = true;
+                        app.mode = AppMode::EditFile;
+                        app.message = format!("Viewing: {} (readonly) - Esc to return", filename);
+                    }
+                    Err(e) => {
+                        app.message = format!("Failed to read file: {}", e);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Verify Results Handlers
+// ============================================================================
+
+/// Verify `root` against `db_path` in the background and show the results view
+fn handle_verify_start(app: &mut App, db_path: std::path::PathBuf, root: std::path::PathBuf) {
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    if !db_path.exists() {
+        app.message = format!("No database found at {}. Run Scan first.", db_path.display());
+        return;
+    }
+
+    let tally = Arc::new(crate::app::VerifyTally::default());
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    app.verify_progress = Some(Arc::clone(&tally));
+    app.verify_rx = Some(rx);
+    app.message = format!("Verifying {}...", root.display());
+    app.verify_root = root.clone();
+    app.verify_db = db_path.clone();
+    app.verify_entries.clear();
+    app.verify_matches = 0;
+    app.verify_selected = 0;
+
+    let (parallel, threads) = (app.hash_parallel, app.hash_threads);
+    std::thread::spawn(move || {
+        // The terminal progress bar would draw over the TUI; the tally feeds the view instead
+        // Keyed databases verify with ABYSS_HASH_KEY; without it their entries report the missing key
+        let engine = crate::hash::VerifyEngine::new()
+            .with_parallel(parallel)
+            .with_threads(threads)
+            .with_key(crate::hash::HashKey::from_env().unwrap_or_default())
+            .with_progress(false)
+            .with_progress_callback(move |p| {
+                tally.total.store(p.total_files, Ordering::Relaxed);
+                tally.checked.fetch_max(p.files_checked, Ordering::Relaxed);
+            });
+        let _ = tx.send(engine.verify(&db_path, &root).map_err(|e| e.to_string()));
+    });
+
+    app.mode = AppMode::VerifyResults;
+}
+
+/// Open the selected verify entry in the active pane (its directory, for missing files)
+async fn verify_jump_to_entry(app: &mut App) -> Result<()> {
+    let Some(entry) = app.verify_entries.get(app.verify_selected).cloned() else {
+        return Ok(());
+    };
+    let (Some(dir), Some(name)) = (entry.path.parent(), entry.path.file_name()) else {
+        return Ok(());
+    };
+    if !dir.is_dir() {
+        app.message = format!("{} no longer exists", dir.display());
+        return Ok(());
+    }
+    let name = name.to_string_lossy().to_string();
+
+    let pane = app.active_pane_mut();
+    if !pane.storage.is_local() {
+        pane.storage = std::sync::Arc::new(crate::fs::LocalBackend::new(dir.to_path_buf()));
+    }
+    pane.path = dir.to_string_lossy().to_string();
+    app.refresh_active_pane().await?;
+
+    let pane = app.active_pane_mut();
+    if let Some(idx) = pane.entries.iter().position(|e| e.name == name) {
+        pane.state.select(Some(idx));
+    }
+    app.mode = AppMode::Normal;
+    app.message = match entry.kind {
+        crate::app::VerifyEntryKind::Missing => format!("{} is missing (listed in the database)", name),
+        kind => format!("{}: {}", kind.label(), name),
+    };
+    Ok(())
+}
+
+/// Handle verify results input.
+async fn handle_verify_results(app: &mut App, key: KeyEvent) -> Result<()> {
+    let last = app.verify_entries.len().saturating_sub(1);
+    match key.code {
+        KeyCode::Esc => {
+            // A running verify finishes in the background; its result is dropped
+            app.verify_rx = None;
+            app.verify_progress = None;
+            app.verify_entries.clear();
+            app.mode = AppMode::Normal;
+            app.message = "Returned to file browser".to_string();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.verify_selected = app.verify_selected.saturating_sub(1);
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.verify_selected = (app.verify_selected + 1).min(last);
+        }
+        KeyCode::PageUp => {
+            app.verify_selected = app.verify_selected.saturating_sub(20);
+        }
+        KeyCode::PageDown => {
+            app.verify_selected = (app.verify_selected + 20).min(last);
+        }
+        KeyCode::Home | KeyCode::Char('g') => {
+            app.verify_selected = 0;
+        }
+        KeyCode::End | KeyCode::Char('G') => {
+            app.verify_selected = last;
+        }
+        KeyCode::Char('r') if app.verify_rx.is_none() => {
+            let (db_path, root) = (app.verify_db.clone(), app.verify_root.clone());
+            handle_verify_start(app, db_path, root);
+        }
+        KeyCode::Enter if app.verify_rx.is_none() => {
+            verify_jump_to_entry(app).await?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Diff Handlers
+// ============================================================================
+
+/// Build the full path of a pane's selected file, or explain why it can't be diffed.
+fn diff_target(pane: &crate::ui::Pane) -> std::result::Result<(String, u64), String> {
+    let entry = match pane.selected_entry() {
+        Some(entry) => entry,
+        None => return Err("No file selected".to_string()),
+    };
+    if entry.name == ".." || entry.is_dir {
+        return Err(format!("Cannot diff directory '{}'", entry.name));
+    }
+
+    let path = if pane.path.ends_with('/') || pane.path.is_empty() {
+        format!("{}{}", pane.path, entry.name)
+    } else {
+        format!("{}/{}", pane.path, entry.name)
+    };
+    Ok((path, entry.size))
+}
+
+/// Diff the selected file in the left pane against the one in the right pane.
+async fn handle_diff_start(app: &mut App) -> Result<()> {
+    let (left, right) = match (diff_target(&app.left_pane), diff_target(&app.right_pane)) {
+        (Ok(left), Ok(right)) => (left, right),
+        (Err(e), _) | (_, Err(e)) => {
+            app.message = format!("Diff needs a file selected in each pane: {}", e);
+            return Ok(());
+        }
+    };
+
+    // Same large-file guard as the viewer for remote files
+    let is_large = |pane: &crate::ui::Pane, size: u64| {
+        matches!(pane.storage.backend_type(), crate::fs::BackendType::S3 { .. } | crate::fs::BackendType::Gcs { .. })
+            && size > 40 * 1024 * 1024
+    };
+    if is_large(&app.left_pane, left.1) || is_large(&app.right_pane, right.1) {
+        app.pending_large_action = Some(LargeFileAction::Diff);
+        app.view_file_size = left.1.max(right.1);
+        app.mode = AppMode::ConfirmLargeLoad;
+        app.message = format!("Remote file is large ({} MB). Diff? (y/n)", app.view_file_size / 1024 / 1024);
+        return Ok(());
+    }
+
+    load_diff(app).await
+}
+
+/// Read both selected files and compute the diff.
+async fn load_diff(app: &mut App) -> Result<()> {
+    let (left, right) = match (diff_target(&app.left_pane), diff_target(&app.right_pane)) {
+        (Ok((left, _)), Ok((right, _))) => (left, right),
+        _ => {
+            app.mode = AppMode::Normal;
+            return Ok(());
+        }
+    };
+
+    let left_backend = app.left_pane.storage.clone();
+    let right_backend = app.right_pane.storage.clone();
+
+    let (left_data, right_data) = match tokio::try_join!(
+        left_backend.read_bytes(&left),
+        right_backend.read_bytes(&right)
+    ) {
+        Ok(data) => data,
+        Err(e) => {
+            app.message = format!("Failed to read file: {}", e);
+            app.mode = AppMode::Normal;
+            return Ok(());
+        }
+    };
+
+    for (data, path) in [(&left_data, &left), (&right_data, &right)] {
+        if crate::sync::diff::is_binary(data) {
+            app.message = format!("Cannot diff binary file: {}", path);
+            app.mode = AppMode::Normal;
+            return Ok(());
+        }
+    }
+
+    let lines = crate::sync::diff::diff_lines(
+        &String::from_utf8_lossy(&left_data),
+        &String::from_utf8_lossy(&right_data),
+    );
+    let (added, removed) = crate::sync::diff::diff_stats(&lines);
+
+    app.diff_title = format!(
+        " {} ↔ {} ",
+        left_backend.display_path(&left),
+        right_backend.display_path(&right)
+    );
+    app.diff_lines = lines;
+    app.diff_scroll = 0;
+    app.mode = AppMode::DiffView;
+    app.message = if added == 0 && removed == 0 {
+        "Files are identical".to_string()
+    } else {
+        format!("Diff: +{} -{} lines", added, removed)
+    };
+    Ok(())
+}
+
+/// Handle diff view input.
+async fn handle_diff_view_mode(app: &mut App, key: KeyEvent) -> Result<()> {
+    let max_scroll = app.diff_lines.len().saturating_sub(1);
+    match key.code {
+        KeyCode::Esc => {
+            app.mode = AppMode::Normal;
+            app.diff_lines.clear();
+            app.message = String::new();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.diff_scroll = app.diff_scroll.saturating_sub(1);
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.diff_scroll = (app.diff_scroll + 1).min(max_scroll);
+        }
+        KeyCode::PageUp => {
+            app.diff_scroll = app.diff_scroll.saturating_sub(20);
+        }
+        KeyCode::PageDown => {
+            app.diff_scroll = (app.diff_scroll + 20).min(max_scroll);
+        }
+        KeyCode::Home | KeyCode::Char('g') => {
+            app.diff_scroll = 0;
+        }
+        KeyCode::End | KeyCode::Char('G') => {
+            app.diff_scroll = max_scroll;
+        }
+        KeyCode::Char('n') => {
+            // Jump to the next changed hunk
+            use crate::sync::DiffLineKind;
+            let start = app.diff_scroll + 1;
+            let mut in_context = !matches!(
+                app.diff_lines.get(app.diff_scroll),
+                Some(l) if l.kind != DiffLineKind::Context
+            );
+            for (i, line) in app.diff_lines.iter().enumerate().skip(start) {
+                if line.kind == DiffLineKind::Context {
+                    in_context = true;
+                } else if in_context {
+                    app.diff_scroll = i;
+                    break;
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    async fn analyzer_at(dir: &std::path::Path) -> App {
+        let mut app = App::with_k8s_options(Default::default()).await.unwrap();
+        app.mode = AppMode::DiskAnalyzer;
+        app.cleaner_path = dir.to_path_buf();
+        app.cleaner_entries = vec![crate::cleaner::DirEntry {
+            path: dir.join("victim.txt"),
+            name: "victim.txt".to_string(),
+            size: 4,
+            disk_usage: 4,
+            is_dir: false,
+            is_temp: false,
+        }];
+        app
+    }
+
+    #[tokio::test]
+    async fn test_breadcrumb_jump_drops_pending_confirmation() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("victim.txt"), b"keep").unwrap();
+
+        // Jumping to an ancestor, or pressing a number with no ancestor to jump to,
+        // must not leave the delete armed for whatever is selected afterwards
+        for stack in [vec![], vec![dir.path().to_path_buf()]] {
+            let mut app = analyzer_at(dir.path()).await;
+            app.cleaner_path_stack = stack;
+            for c in ['d', '1', 'y'] {
+                handle_disk_analyzer(&mut app, key(c)).await.unwrap();
+            }
+            assert!(!app.cleaner_confirm_delete);
+            assert!(dir.path().join("victim.txt").exists());
+        }
+
+        let mut app = analyzer_at(dir.path()).await;
+        handle_disk_analyzer(&mut app, key('c')).await.unwrap();
+        assert!(app.cleaner_confirm_clean);
+        handle_disk_analyzer(&mut app, key('1')).await.unwrap();
+        assert!(!app.cleaner_confirm_clean);
+    }
+
+    #[tokio::test]
+    async fn test_quit_key_only_quits_from_the_panes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = analyzer_at(dir.path()).await;
+        assert!(!is_quit_key(&app, &key('q')));
+        app.mode = AppMode::EditFile;
+        assert!(!is_quit_key(&app, &key('q')));
+        app.mode = AppMode::Normal;
+        assert!(is_quit_key(&app, &key('q')));
+        assert!(!is_quit_key(&app, &KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL)));
+    }
+
+    #[tokio::test]
+    async fn test_pane_search_ignores_case_unless_asked_not_to() {
+        let mut app = App::with_k8s_options(Default::default()).await.unwrap();
+        app.ignore_case = None;
+        app.active_pane_mut().entries = ["a.txt", "Target"]
+            .into_iter()
+            .map(|name| crate::fs::types::FileEntry {
+                name: name.to_string(),
+                size: 0,
+                is_dir: false,
+                modified: None,
+                permissions: None,
+            })
+            .collect();
+
+        for (setting, found) in [(None, true), (Some(true), true), (Some(false), false)] {
+            app.ignore_case = setting;
+            app.active_pane_mut().state.select(Some(0));
+            app.mode = AppMode::Search;
+            app.text_input.value = "target".to_string();
+            handle_search_mode(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+            assert_eq!(app.active_pane().state.selected() == Some(1), found, "{:?}", setting);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_excludes_follow_the_case_setting() {
+        let mut app = App::with_k8s_options(Default::default()).await.unwrap();
+        app.ignore_case = Some(true);
+        assert!(app.sync_excludes().ignores_case());
+        assert!(app.sync_excludes().is_excluded("THUMBS.DB"));
+        app.ignore_case = Some(false);
+        assert!(!app.sync_excludes().is_excluded("THUMBS.DB"));
+    }
+
+    #[tokio::test]
+    async fn test_analyzer_follows_the_keymap() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = analyzer_at(dir.path()).await;
+        app.keymap = crate::keymap::Keymap::parse("[analyzer]\ndelete = \"x\"").unwrap();
+        handle_disk_analyzer(&mut app, key('d')).await.unwrap();
+        assert!(!app.cleaner_confirm_delete);
+        handle_disk_analyzer(&mut app, key('x')).await.unwrap();
+        assert!(app.cleaner_confirm_delete);
+    }
+
+    #[tokio::test]
+    async fn test_hash_menu_drops_the_rest_of_a_paged_listing() {
+        let mut app = App::with_k8s_options(Default::default()).await.unwrap();
+        let (tx, rx) = crossbeam_channel::bounded(1);
+        app.active_pane_mut().pending = Some(rx);
+
+        handle_normal_mode(&mut app, KeyEvent::new(KeyCode::Char('h'), KeyModifiers::CONTROL)).await.unwrap();
+        assert!(matches!(app.mode, AppMode::HashMenu));
+        assert!(app.active_pane().pending.is_none());
+
+        // The late rest of the old directory has nowhere to go
+        assert!(tx.send(Ok(Vec::new())).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_page_copy_counts_wrapped_rows() {
+        let mut app = App::with_k8s_options(Default::default()).await.unwrap();
+        app.view_content = vec!["x".repeat(30), "short".to_string(), "also short".to_string(), "last".to_string()];
+        app.view_visible_height = 4;
+        app.view_text_width = 20;
+
+        // The line number column and 30 characters take two of the four rows
+        app.view_wrap = true;
+        assert_eq!(app.visible_view_lines(), 0..3);
+        app.view_wrap = false;
+        assert_eq!(app.visible_view_lines(), 0..4);
+
+        // A line taller than the screen is still the one on it
+        app.view_wrap = true;
+        app.view_content[0] = "x".repeat(200);
+        assert_eq!(app.visible_view_lines(), 0..1);
+        app.view_scroll = 1;
+        assert_eq!(app.visible_view_lines(), 1..4);
+    }
+
+    #[tokio::test]
+    async fn test_large_binary_file_streams_as_hex() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut data = b"\x7fELF".to_vec();
+        data.resize(100, 0);
+        std::fs::write(dir.path().join("app.bin"), &data).unwrap();
+
+        let mut app = App::with_k8s_options(Default::default()).await.unwrap();
+        app.active_pane_mut().storage = std::sync::Arc::new(crate::fs::LocalBackend::new(dir.path().to_path_buf()));
+        app.view_file_path = dir.path().join("app.bin").to_string_lossy().into_owned();
+        app.view_file_size = data.len() as u64;
+        app.pending_large_action = Some(LargeFileAction::View);
+        app.mode = AppMode::ConfirmLargeLoad;
+
+        handle_confirm_large_load_mode(&mut app, key('y')).await.unwrap();
+        assert!(matches!(app.mode, AppMode::ViewFile));
+        assert!(app.view_hex);
+        assert!(app.view_content[0].starts_with("00000000  7f 45 4c 46"), "{}", app.view_content[0]);
+    }
+
+    #[tokio::test]
+    async fn test_alt_y_skips_the_delete_confirmation() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), b"a").unwrap();
+        std::fs::write(dir.path().join("b.txt"), b"b").unwrap();
+
+        let mut app = App::with_k8s_options(Default::default()).await.unwrap();
+        let pane = app.active_pane_mut();
+        pane.path = dir.path().to_string_lossy().into_owned();
+        pane.storage = std::sync::Arc::new(crate::fs::LocalBackend::new(dir.path().to_path_buf()));
+        pane.set_entries(crate::fs::LocalFs::list_dir(dir.path()).unwrap());
+        pane.state.select(Some(0));
+        let alt_y = KeyEvent::new(KeyCode::Char('y'), KeyModifiers::ALT);
+        let f8 = KeyEvent::new(KeyCode::F(8), KeyModifiers::NONE);
+
+        // Asks first by default
+        handle_normal_mode(&mut app, f8).await.unwrap();
+        assert!(matches!(app.mode, AppMode::ConfirmDelete));
+        app.mode = AppMode::Normal;
+
+        handle_normal_mode(&mut app, alt_y).await.unwrap();
+        assert!(app.auto_confirm_delete);
+        assert!(app.message.starts_with("⚠ Delete confirmation OFF"), "{}", app.message);
+        handle_normal_mode(&mut app, f8).await.unwrap();
+        assert!(matches!(app.mode, AppMode::Normal));
+        while app.delete_task.is_some() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            app.poll_background_task().await;
+        }
+        assert!(!dir.path().join("a.txt").exists());
+        assert!(dir.path().join("b.txt").exists());
+
+        // Pressed again, it asks again
+        handle_normal_mode(&mut app, alt_y).await.unwrap();
+        assert!(!app.auto_confirm_delete);
+        assert_eq!(app.message, "Delete confirmation ON");
+    }
+
+    #[tokio::test]
+    async fn test_find_line_offset_across_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        // 16-byte lines, so the scan crosses several chunk boundaries
+        let text: String = (0..200_000).map(|i| format!("line {:>10}\n", i)).collect();
+        std::fs::write(dir.path().join("big.txt"), &text).unwrap();
+        let backend = crate::fs::LocalBackend::new(dir.path().to_path_buf());
+        let path = dir.path().join("big.txt").to_string_lossy().into_owned();
+        let size = text.len() as u64;
+        assert!(size > 3 * GOTO_SCAN_CHUNK);
+
+        assert_eq!(find_line_offset(&backend, &path, 1, size).await.unwrap(), (0, 1));
+        assert_eq!(find_line_offset(&backend, &path, 2, size).await.unwrap(), (16, 2));
+        assert_eq!(find_line_offset(&backend, &path, 150_001, size).await.unwrap(), (150_000 * 16, 150_001));
+        // The trailing newline doesn't start a line past the last one
+        assert_eq!(find_line_offset(&backend, &path, 999_999, size).await.unwrap(), (199_999 * 16, 200_000));
+    }
+
+    #[tokio::test]
+    async fn test_file_over_the_editor_limit_opens_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let size = crate::app::TextEditor::max_size() + 1;
+        let file = std::fs::File::create(dir.path().join("huge.log")).unwrap();
+        file.set_len(size).unwrap();
+
+        let mut app = App::with_k8s_options(Default::default()).await.unwrap();
+        let pane = app.active_pane_mut();
+        pane.path = dir.path().to_string_lossy().into_owned();
+        pane.storage = std::sync::Arc::new(crate::fs::LocalBackend::new(dir.path().to_path_buf()));
+        pane.set_entries(vec![crate::fs::types::FileEntry {
+            name: "huge.log".to_string(),
+            size,
+            is_dir: false,
+            modified: None,
+            permissions: None,
+        }]);
+        pane.state.select(Some(0));
+
+        handle_edit_file_start(&mut app).await.unwrap();
+        assert!(matches!(app.mode, AppMode::ViewFile));
+        assert!(app.message.ends_with("opened read-only"), "{}", app.message);
+        // Only the first chunk was read
+        assert_eq!(app.view_file_size, size);
+        assert!(app.view_content.len() < 100_000, "{} lines loaded", app.view_content.len());
+    }
+
+    #[tokio::test]
+    async fn test_large_recent_file_streams_and_returns_to_the_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("huge.log");
+        let size = crate::app::TextEditor::max_size() + 1;
+        std::fs::File::create(&path).unwrap().set_len(size).unwrap();
+
+        let mut app = App::with_k8s_options(Default::default()).await.unwrap();
+        app.active_pane_mut().storage = std::sync::Arc::new(crate::fs::LocalBackend::new(dir.path().to_path_buf()));
+        app.recent_root = dir.path().to_path_buf();
+        app.recent_files = vec![crate::cleaner::RecentFile { modified: std::time::SystemTime::now(), path, size }];
+        app.mode = AppMode::RecentFiles;
+
+        handle_recent_files(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await.unwrap();
+        assert!(matches!(app.mode, AppMode::ViewFile));
+        assert!(app.message.ends_with("opened read-only"), "{}", app.message);
+        assert!(app.view_content.len() < 100_000, "{} lines loaded", app.view_content.len());
+
+        handle_view_file_mode(&mut app, KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)).await.unwrap();
+        assert!(matches!(app.mode, AppMode::RecentFiles));
+    }
+
+    #[tokio::test]
+    async fn test_delete_runs_beside_a_task_on_other_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("busy")).unwrap();
+        std::fs::write(dir.path().join("busy/a.txt"), b"a").unwrap();
+        std::fs::write(dir.path().join("free.txt"), b"b").unwrap();
+
+        let mut app = App::with_k8s_options(Default::default()).await.unwrap();
+        let backend: std::sync::Arc<dyn crate::fs::StorageBackend> =
+            std::sync::Arc::new(crate::fs::LocalBackend::new(dir.path().to_path_buf()));
+        let target = |name: &str| {
+            let path = dir.path().join(name).to_string_lossy().into_owned();
+            crate::app::DeleteTarget { backend: backend.clone(), display_path: path.clone(), path, is_dir: false, size: 1 }
+        };
+        app.background_task = Some(tokio::spawn(std::future::pending()));
+        app.background_paths = vec![dir.path().join("busy").to_string_lossy().into_owned()];
+
+        // Deleting from under the running task is refused
+        app.delete_targets = vec![target("busy/a.txt")];
+        delete_targets(&mut app).await.unwrap();
+        assert!(app.delete_task.is_none());
+        assert!(app.message.contains("in use"), "{}", app.message);
+        assert!(dir.path().join("busy/a.txt").exists());
+
+        // Anything else goes ahead while the task still runs
+        app.delete_targets = vec![target("free.txt")];
+        delete_targets(&mut app).await.unwrap();
+        while app.delete_task.is_some() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            app.poll_background_task().await;
+        }
+        assert!(app.message.starts_with("✓ Deleted"), "{}", app.message);
+        assert!(!dir.path().join("free.txt").exists());
+        assert!(app.background_task.is_some());
+        app.background_task.take().unwrap().abort();
+    }
+
+    #[tokio::test]
+    async fn test_copy_plan_is_listed_again_when_confirmed() {
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        std::fs::create_dir(src.path().join("tree")).unwrap();
+        std::fs::write(src.path().join("tree/a.txt"), b"a").unwrap();
+
+        let mut app = App::with_k8s_options(Default::default()).await.unwrap();
+        app.copy_plan_ops = vec![crate::app::QueuedOperation {
+            kind: crate::app::OperationKind::Copy,
+            name: "tree".to_string(),
+            size: 0,
+            is_dir: true,
+            src_storage: std::sync::Arc::new(crate::fs::LocalBackend::new(src.path().to_path_buf())),
+            src_path: src.path().join("tree").to_string_lossy().into_owned(),
+            dest_storage: std::sync::Arc::new(crate::fs::LocalBackend::new(dst.path().to_path_buf())),
+            dest_path: dst.path().join("tree").to_string_lossy().into_owned(),
+            resume: false,
+            merge: Default::default(),
+        }];
+        async fn settle(app: &mut App) {
+            while app.copy_plan_rx.is_some() {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                poll_copy_plan(app);
+            }
+        }
+        spawn_copy_plan(&mut app);
+        settle(&mut app).await;
+        assert!(matches!(app.mode, AppMode::ConfirmCopyPlan));
+
+        // A file added after the plan was shown is listed before anything is copied
+        std::fs::write(src.path().join("tree/b.txt"), b"b").unwrap();
+        handle_confirm_copy_plan(&mut app, key('y'));
+        settle(&mut app).await;
+        assert!(matches!(app.mode, AppMode::ConfirmCopyPlan));
+        assert!(app.message.starts_with("Source changed"), "{}", app.message);
+        assert_eq!(app.copy_plan.as_ref().map(|plan| plan.files.len()), Some(2));
+        assert!(app.background_task.is_none());
+
+        // Confirming the unchanged plan starts the copy
+        handle_confirm_copy_plan(&mut app, key('y'));
+        settle(&mut app).await;
+        assert!(app.copy_plan.is_none());
+        while app.background_task.is_some() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            app.poll_background_task().await;
+        }
+        assert!(dst.path().join("tree/b.txt").exists());
+    }
+}
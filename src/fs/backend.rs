@@ -1,8 +1,84 @@
-use anyhow::Result;
 use async_trait::async_trait;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
-use crate::fs::types::FileEntry;
+use crate::fs::error::{FsError, FsResult};
+use crate::fs::types::{DirListing, FileEntry, ObjectMeta, SpaceInfo};
+
+/// Chunk size for writes that report progress (above the S3 multipart minimum of 5 MiB)
+pub const WRITE_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Entries a pane shows before the rest of a huge directory has been read
+pub const LIST_PAGE_SIZE: usize = 2000;
+
+/// Objects a delete has removed out of those it found, and a flag that stops it early.
+/// Shared with the UI through an `Arc`; counts add up across several deleted paths.
+#[derive(Debug, Default)]
+pub struct DeleteProgress {
+    deleted: AtomicU64,
+    total: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+impl DeleteProgress {
+    pub fn deleted(&self) -> u64 {
+        self.deleted.load(Ordering::Relaxed)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    pub fn add_deleted(&self, count: u64) {
+        self.deleted.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn add_total(&self, count: u64) {
+        self.total.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Ask the delete to stop before its next object or batch
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Fail once the delete has been cancelled
+    pub fn check_cancelled(&self) -> FsResult<()> {
+        if self.is_cancelled() {
+            return Err(FsError::Other("Delete cancelled".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Number of UTF-8 continuation bytes (at most 3) at the start of `data`
+pub fn utf8_leading_continuation_bytes(data: &[u8]) -> usize {
+    data.iter().take(3).take_while(|&&b| b & 0xC0 == 0x80).count()
+}
+
+/// Number of bytes missing to complete a multibyte UTF-8 character cut off at the end of `data`
+pub fn utf8_missing_tail_bytes(data: &[u8]) -> usize {
+    // The lead byte of the last character is within the last 4 bytes
+    for back in 1..=data.len().min(4) {
+        let byte = data[data.len() - back];
+        if byte & 0xC0 == 0x80 {
+            continue;
+        }
+        let width: usize = match byte {
+            b if b & 0x80 == 0 => 1,
+            b if b & 0xE0 == 0xC0 => 2,
+            b if b & 0xF0 == 0xE0 => 3,
+            b if b & 0xF8 == 0xF0 => 4,
+            _ => return 0, // Invalid lead byte, nothing sensible to complete
+        };
+        return width.saturating_sub(back);
+    }
+    0
+}
 
 /// Backend type information for display and identification
 #[derive(Debug, Clone, PartialEq)]
@@ -25,6 +101,11 @@ impl BackendType {
             BackendType::Selecting => "Select",
         }
     }
+
+    /// Object storage, where transfers usually count against metered bandwidth
+    pub fn is_cloud(&self) -> bool {
+        matches!(self, BackendType::S3 { .. } | BackendType::Gcs { .. })
+    }
 }
 
 /// S3-compatible storage providers
@@ -157,38 +238,85 @@ pub struct FileStat {
 }
 
 /// Unified storage backend trait for all storage providers
+///
+/// Methods return [`FsError`] so callers can distinguish missing paths, permission
+/// problems and transient network failures; it converts into `anyhow::Error` with `?`.
 #[async_trait]
 pub trait StorageBackend: Send + Sync {
     // ========== Core Operations ==========
     
     /// List directory contents
-    async fn list_dir(&self, path: &str) -> Result<Vec<FileEntry>>;
+    async fn list_dir(&self, path: &str) -> FsResult<Vec<FileEntry>>;
+
+    /// List directory contents including hidden entries, for walks that must see every
+    /// file; only local listings hide dotfiles, so other backends list as usual
+    async fn list_dir_all(&self, path: &str) -> FsResult<Vec<FileEntry>> {
+        self.list_dir(path).await
+    }
+
+    /// List at most `page` entries now and read the rest in the background; backends
+    /// without incremental listing return everything at once
+    async fn list_dir_paged(&self, path: &str, _page: usize) -> FsResult<DirListing> {
+        Ok(DirListing::complete(self.list_dir(path).await?))
+    }
     
     /// Delete a file or directory
-    async fn delete(&self, path: &str) -> Result<()>;
+    async fn delete(&self, path: &str) -> FsResult<()>;
+
+    /// Delete like [`delete`](Self::delete), counting removed objects in `progress` and
+    /// stopping early once it is cancelled
+    async fn delete_with_progress(&self, path: &str, progress: &DeleteProgress) -> FsResult<()> {
+        // Default impl deletes in one go (counted as one object, override to report more)
+        progress.check_cancelled()?;
+        progress.add_total(1);
+        self.delete(path).await?;
+        progress.add_deleted(1);
+        Ok(())
+    }
     
     /// Create a directory
-    async fn create_dir(&self, path: &str) -> Result<()>;
+    async fn create_dir(&self, path: &str) -> FsResult<()>;
     
     /// Check if a path is a directory
-    async fn is_dir(&self, path: &str) -> Result<bool>;
+    async fn is_dir(&self, path: &str) -> FsResult<bool>;
     
     // ========== File Transfer ==========
     
     /// Upload a file from local filesystem
-    async fn upload(&self, local_path: &Path, remote_path: &str) -> Result<()>;
+    async fn upload(&self, local_path: &Path, remote_path: &str) -> FsResult<()>;
     
     /// Download a file to local filesystem
-    async fn download(&self, remote_path: &str, local_path: &Path) -> Result<()>;
+    async fn download(&self, remote_path: &str, local_path: &Path) -> FsResult<()>;
     
     /// Read file contents as bytes (for cross-backend copy)
-    async fn read_bytes(&self, path: &str) -> Result<Vec<u8>>;
+    async fn read_bytes(&self, path: &str) -> FsResult<Vec<u8>>;
     
     /// Write bytes to a file (for cross-backend copy)
-    async fn write_bytes(&self, path: &str, data: Vec<u8>) -> Result<()>;
+    async fn write_bytes(&self, path: &str, data: Vec<u8>) -> FsResult<()>;
+
+    /// Write bytes to a file, storing the number of bytes written so far in `written`
+    async fn write_bytes_with_progress(&self, path: &str, data: Vec<u8>, written: &AtomicU64) -> FsResult<()> {
+        // Default impl writes in one go (progress jumps to 100%, override for chunked writes)
+        let len = data.len() as u64;
+        self.write_bytes(path, data).await?;
+        written.store(len, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Append bytes to a file, creating it if missing (used to resume interrupted copies).
+    /// Backends that can't append return `FsError::Unsupported` and the copy restarts instead.
+    async fn append_bytes(&self, path: &str, _data: Vec<u8>) -> FsResult<()> {
+        Err(FsError::Unsupported(format!("Appending is not supported: {}", path)))
+    }
+
+    /// Set a file's modification time (used to preserve timestamps on copy).
+    /// Backends that can't set it return `FsError::Unsupported` and keep their own.
+    async fn set_modified_time(&self, path: &str, _modified: chrono::DateTime<chrono::Utc>) -> FsResult<()> {
+        Err(FsError::Unsupported(format!("Setting modification times is not supported: {}", path)))
+    }
 
     /// Read a range of bytes from a file (for streaming/large files)
-    async fn read_range(&self, path: &str, offset: u64, length: u64) -> Result<Vec<u8>> {
+    async fn read_range(&self, path: &str, offset: u64, length: u64) -> FsResult<Vec<u8>> {
         // Default impl reads everything and slices (inefficient, override for performance)
         let all = self.read_bytes(path).await?;
         let start = offset as usize;
@@ -198,11 +326,57 @@ pub trait StorageBackend: Send + Sync {
         }
         Ok(all[start..end.min(all.len())].to_vec())
     }
-    
+
+    /// Like [`read_range`](Self::read_range), for callers that already know the file's `size`.
+    /// Object stores otherwise stat the object on every call to clip the range.
+    async fn read_range_sized(&self, path: &str, offset: u64, length: u64, _size: u64) -> FsResult<Vec<u8>> {
+        self.read_range(path, offset, length).await
+    }
+
+    /// Read a range of a text file as a string without splitting multibyte UTF-8 characters.
+    ///
+    /// Continuation bytes at the start of the range belong to the previous chunk and are
+    /// dropped; a character cut off at the end is completed by reading a few more bytes.
+    async fn read_text_range(&self, path: &str, offset: u64, length: u64) -> FsResult<String> {
+        let mut data = self.read_range(path, offset, length).await?;
+        let read = data.len() as u64;
+
+        if offset > 0 {
+            let skip = utf8_leading_continuation_bytes(&data);
+            data.drain(..skip);
+        }
+        if read == length {
+            let missing = utf8_missing_tail_bytes(&data);
+            if missing > 0 {
+                let rest = self.read_range(path, offset + length, missing as u64).await?;
+                data.extend_from_slice(&rest);
+            }
+        }
+        Ok(String::from_utf8_lossy(&data).into_owned())
+    }
+
+    /// Create an empty file, failing with `FsError::AlreadyExists` if the path is taken
+    async fn create_file(&self, path: &str) -> FsResult<()> {
+        // Default impl checks first, then writes (override where creation can be exclusive)
+        match self.stat(path).await {
+            Ok(_) => return Err(FsError::AlreadyExists(format!("Already exists: {}", path))),
+            Err(FsError::NotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+        self.write_bytes(path, Vec::new()).await
+    }
+
+    /// Copy a file to another path on the same backend
+    async fn copy_within(&self, from: &str, to: &str) -> FsResult<()> {
+        // Default implementation: download + upload (override for server-side copy)
+        let data = self.read_bytes(from).await?;
+        self.write_bytes(to, data).await
+    }
+
     // ========== Metadata ==========
     
     /// Get file/directory metadata
-    async fn stat(&self, path: &str) -> Result<FileStat> {
+    async fn stat(&self, path: &str) -> FsResult<FileStat> {
         // Default implementation using list_dir
         let parent = if path.contains('/') {
             path.rsplit_once('/').map(|(p, _)| p).unwrap_or("")
@@ -221,11 +395,17 @@ pub trait StorageBackend: Send + Sync {
                 created: None,
                 permissions: e.permissions,
             })
-            .ok_or_else(|| anyhow::anyhow!("Path not found: {}", path))
+            .ok_or_else(|| FsError::NotFound(format!("Path not found: {}", path)))
     }
     
+    /// Metadata for the details popup; object stores add their ETag, storage class and
+    /// content type to what [`stat`](Self::stat) knows
+    async fn object_meta(&self, path: &str) -> FsResult<ObjectMeta> {
+        Ok(self.stat(path).await?.into())
+    }
+
     /// Rename/move a file or directory
-    async fn rename(&self, from: &str, to: &str) -> Result<()> {
+    async fn rename(&self, from: &str, to: &str) -> FsResult<()> {
         // Default implementation: copy + delete
         let data = self.read_bytes(from).await?;
         self.write_bytes(to, data).await?;
@@ -233,8 +413,14 @@ pub trait StorageBackend: Send + Sync {
         Ok(())
     }
     
+    /// Free and total space where `path` lives, `None` when the backend can't tell
+    /// (object stores have no fixed capacity)
+    async fn space_info(&self, _path: &str) -> FsResult<Option<SpaceInfo>> {
+        Ok(None)
+    }
+
     /// Get disk usage info (if supported)
-    async fn get_disk_usage(&self) -> Result<Option<String>> {
+    async fn get_disk_usage(&self) -> FsResult<Option<String>> {
         Ok(None)
     }
     
@@ -263,3 +449,49 @@ pub trait StorageBackend: Send + Sync {
         matches!(self.backend_type(), BackendType::Local)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::local::LocalBackend;
+
+    #[test]
+    fn test_utf8_boundary_helpers() {
+        let euro = "€".as_bytes(); // 3 bytes
+        assert_eq!(utf8_missing_tail_bytes(b"abc"), 0);
+        assert_eq!(utf8_missing_tail_bytes(&[b'a', euro[0]]), 2);
+        assert_eq!(utf8_missing_tail_bytes(&[b'a', euro[0], euro[1]]), 1);
+        assert_eq!(utf8_missing_tail_bytes(euro), 0);
+        assert_eq!(utf8_leading_continuation_bytes(&euro[1..]), 2);
+        assert_eq!(utf8_leading_continuation_bytes(b"abc"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_read_text_range_multibyte_boundary() {
+        let dir = tempfile::tempdir().unwrap();
+        // "ab€cd": the euro sign spans bytes 2..5, so a 4-byte chunk ends inside it
+        std::fs::write(dir.path().join("t.txt"), "ab€cd€".as_bytes()).unwrap();
+        let backend = LocalBackend::new(dir.path().to_path_buf());
+
+        let first = backend.read_text_range("t.txt", 0, 4).await.unwrap();
+        assert_eq!(first, "ab€");
+        let second = backend.read_text_range("t.txt", 4, 4).await.unwrap();
+        assert_eq!(second, "cd€");
+        assert!(!first.contains('\u{FFFD}') && !second.contains('\u{FFFD}'));
+    }
+
+    #[tokio::test]
+    async fn test_write_bytes_with_progress_local() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        let dir = tempfile::tempdir().unwrap();
+        let backend = LocalBackend::new(dir.path().to_path_buf());
+        let data = vec![7u8; WRITE_CHUNK_SIZE + 10];
+        let written = AtomicU64::new(0);
+
+        backend.write_bytes_with_progress("big.bin", data.clone(), &written).await.unwrap();
+
+        assert_eq!(written.load(Ordering::Relaxed), data.len() as u64);
+        assert_eq!(std::fs::read(dir.path().join("big.bin")).unwrap(), data);
+    }
+}
@@ -0,0 +1,531 @@
+//! Cloud credentials resolved from environment variables and profile files.
+//!
+//! Every setting is looked up in this order:
+//! 1. The provider's environment variables (`S3_BUCKET`, `DO_REGION`, `R2_ACCESS_KEY_ID`, ...)
+//! 2. The active profile in `~/.config/abyss/credentials.toml` (`ABYSS_CREDENTIALS_FILE`)
+//! 3. For AWS only: the same profile in `~/.aws/credentials` (`AWS_SHARED_CREDENTIALS_FILE`),
+//!    or `AWS_PROFILE`/`default` when no profile was selected
+//!
+//! The access key and secret key are a pair and always come from the same source: the
+//! first one that sets either of them.
+//!
+//! The abyss file has one table per profile and provider:
+//!
+//! ```toml
+//! [default.s3]
+//! bucket = "backups"
+//! region = "eu-central-1"
+//! prefix = "nightly/db"
+//!
+//! [work.hetzner]
+//! bucket = "media"
+//! region = "fsn1"
+//! access_key = "..."
+//! secret_key = "..."
+//! ```
+//!
+//! The active profile is `ABYSS_PROFILE`, else `default`; the TUI can switch it
+//! in the cloud provider menu.
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Profile used when none is selected
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Cloud providers offered in the storage menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloudProvider {
+    Aws,
+    Gcs,
+    DigitalOcean,
+    Hetzner,
+    CloudflareR2,
+    MinIO,
+    Wasabi,
+}
+
+/// A single credential setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialField {
+    Bucket,
+    Region,
+    AccessKey,
+    SecretKey,
+    AccountId,
+    ServiceAccount,
+    Prefix,
+}
+
+impl CredentialField {
+    pub const ALL: [CredentialField; 7] = [
+        CredentialField::Bucket,
+        CredentialField::Region,
+        CredentialField::AccessKey,
+        CredentialField::SecretKey,
+        CredentialField::AccountId,
+        CredentialField::ServiceAccount,
+        CredentialField::Prefix,
+    ];
+
+    /// Key of this setting in `credentials.toml`
+    pub fn key(self) -> &'static str {
+        match self {
+            CredentialField::Bucket => "bucket",
+            CredentialField::Region => "region",
+            CredentialField::AccessKey => "access_key",
+            CredentialField::SecretKey => "secret_key",
+            CredentialField::AccountId => "account_id",
+            CredentialField::ServiceAccount => "service_account",
+            CredentialField::Prefix => "prefix",
+        }
+    }
+}
+
+impl CloudProvider {
+    pub const ALL: [CloudProvider; 7] = [
+        CloudProvider::Aws,
+        CloudProvider::Gcs,
+        CloudProvider::DigitalOcean,
+        CloudProvider::Hetzner,
+        CloudProvider::CloudflareR2,
+        CloudProvider::MinIO,
+        CloudProvider::Wasabi,
+    ];
+
+    /// Name shown in the storage menu
+    pub fn menu_name(self) -> &'static str {
+        match self {
+            CloudProvider::Aws => "AWS S3",
+            CloudProvider::Gcs => "Google Cloud Storage (GCS)",
+            CloudProvider::DigitalOcean => "DigitalOcean Spaces",
+            CloudProvider::Hetzner => "Hetzner Object Storage",
+            CloudProvider::CloudflareR2 => "Cloudflare R2",
+            CloudProvider::MinIO => "MinIO (Local/Self-hosted)",
+            CloudProvider::Wasabi => "Wasabi",
+        }
+    }
+
+    pub fn from_menu_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.menu_name() == name)
+    }
+
+    /// Table name of this provider inside a profile
+    pub fn profile_key(self) -> &'static str {
+        match self {
+            CloudProvider::Aws => "s3",
+            CloudProvider::Gcs => "gcs",
+            CloudProvider::DigitalOcean => "digitalocean",
+            CloudProvider::Hetzner => "hetzner",
+            CloudProvider::CloudflareR2 => "r2",
+            CloudProvider::MinIO => "minio",
+            CloudProvider::Wasabi => "wasabi",
+        }
+    }
+
+    /// Environment variable overriding `field`, if the provider uses that setting
+    pub fn env_var(self, field: CredentialField) -> Option<&'static str> {
+        use CredentialField::*;
+        let var = match (self, field) {
+            (CloudProvider::Aws, Bucket) => "S3_BUCKET",
+            (CloudProvider::Aws, Region) => "AWS_REGION",
+            (CloudProvider::Aws, AccessKey) => "AWS_ACCESS_KEY_ID",
+            (CloudProvider::Aws, SecretKey) => "AWS_SECRET_ACCESS_KEY",
+            (CloudProvider::Aws, Prefix) => "S3_PREFIX",
+            (CloudProvider::Gcs, Bucket) => "GCS_BUCKET",
+            (CloudProvider::Gcs, ServiceAccount) => "GOOGLE_APPLICATION_CREDENTIALS",
+            (CloudProvider::Gcs, Prefix) => "GCS_PREFIX",
+            (CloudProvider::DigitalOcean, Bucket) => "DO_BUCKET",
+            (CloudProvider::DigitalOcean, Region) => "DO_REGION",
+            (CloudProvider::DigitalOcean, AccessKey) => "DO_ACCESS_KEY_ID",
+            (CloudProvider::DigitalOcean, SecretKey) => "DO_SECRET_ACCESS_KEY",
+            (CloudProvider::DigitalOcean, Prefix) => "DO_PREFIX",
+            (CloudProvider::Hetzner, Bucket) => "HETZNER_BUCKET",
+            (CloudProvider::Hetzner, Region) => "HETZNER_REGION",
+            (CloudProvider::Hetzner, AccessKey) => "HETZNER_ACCESS_KEY",
+            (CloudProvider::Hetzner, SecretKey) => "HETZNER_SECRET_ACCESS_KEY",
+            (CloudProvider::Hetzner, Prefix) => "HETZNER_PREFIX",
+            (CloudProvider::CloudflareR2, Bucket) => "R2_BUCKET",
+            (CloudProvider::CloudflareR2, AccountId) => "R2_ACCOUNT_ID",
+            (CloudProvider::CloudflareR2, AccessKey) => "R2_ACCESS_KEY_ID",
+            (CloudProvider::CloudflareR2, SecretKey) => "R2_SECRET_ACCESS_KEY",
+            (CloudProvider::CloudflareR2, Prefix) => "R2_PREFIX",
+            (CloudProvider::MinIO, Bucket) => "MINIO_BUCKET",
+            (CloudProvider::MinIO, AccessKey) => "MINIO_ACCESS_KEY",
+            (CloudProvider::MinIO, SecretKey) => "MINIO_SECRET_KEY",
+            (CloudProvider::MinIO, Prefix) => "MINIO_PREFIX",
+            (CloudProvider::Wasabi, Bucket) => "WASABI_BUCKET",
+            (CloudProvider::Wasabi, Region) => "WASABI_REGION",
+            (CloudProvider::Wasabi, AccessKey) => "WASABI_ACCESS_KEY",
+            (CloudProvider::Wasabi, SecretKey) => "WASABI_SECRET_KEY",
+            (CloudProvider::Wasabi, Prefix) => "WASABI_PREFIX",
+            _ => return None,
+        };
+        Some(var)
+    }
+}
+
+/// Credentials for one provider; unset fields fall through to the next source
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct CloudCredentials {
+    pub bucket: Option<String>,
+    pub region: Option<String>,
+    pub access_key: Option<String>,
+    pub secret_key: Option<String>,
+    /// Cloudflare account ID (R2)
+    pub account_id: Option<String>,
+    /// Path to a service account JSON file (GCS)
+    pub service_account: Option<String>,
+    /// Folder the pane opens at after connecting, instead of the bucket root
+    pub prefix: Option<String>,
+}
+
+impl CloudCredentials {
+    pub fn get(&self, field: CredentialField) -> Option<&str> {
+        let value = match field {
+            CredentialField::Bucket => &self.bucket,
+            CredentialField::Region => &self.region,
+            CredentialField::AccessKey => &self.access_key,
+            CredentialField::SecretKey => &self.secret_key,
+            CredentialField::AccountId => &self.account_id,
+            CredentialField::ServiceAccount => &self.service_account,
+            CredentialField::Prefix => &self.prefix,
+        };
+        value.as_deref()
+    }
+
+    fn slot(&mut self, field: CredentialField) -> &mut Option<String> {
+        match field {
+            CredentialField::Bucket => &mut self.bucket,
+            CredentialField::Region => &mut self.region,
+            CredentialField::AccessKey => &mut self.access_key,
+            CredentialField::SecretKey => &mut self.secret_key,
+            CredentialField::AccountId => &mut self.account_id,
+            CredentialField::ServiceAccount => &mut self.service_account,
+            CredentialField::Prefix => &mut self.prefix,
+        }
+    }
+
+    /// Pane path to open after connecting: the prefix without surrounding slashes,
+    /// or `""` (the bucket root) when none is set
+    pub fn start_path(&self) -> String {
+        self.prefix.as_deref().unwrap_or("").trim_matches('/').to_string()
+    }
+
+    /// Fill the fields that are still unset from `fallback`. The key pair is only taken
+    /// when neither half is set yet, so a key never ends up with another source's secret.
+    fn fill_from(&mut self, fallback: &CloudCredentials) {
+        let has_key_pair = self.access_key.is_some() || self.secret_key.is_some();
+        for field in CredentialField::ALL {
+            let key_pair = matches!(field, CredentialField::AccessKey | CredentialField::SecretKey);
+            if self.get(field).is_none() && !(key_pair && has_key_pair) {
+                *self.slot(field) = fallback.get(field).map(str::to_string);
+            }
+        }
+    }
+
+    /// A setting the provider can't do without, with a hint on where to set it
+    pub fn require(&self, provider: CloudProvider, field: CredentialField) -> Result<&str> {
+        match self.get(field) {
+            Some(value) => Ok(value),
+            None => match provider.env_var(field) {
+                Some(var) => bail!(
+                    "Missing {}: set {} or `{}` under [<profile>.{}] in the credentials file",
+                    field.key(),
+                    var,
+                    field.key(),
+                    provider.profile_key()
+                ),
+                None => bail!("Missing {} for {}", field.key(), provider.menu_name()),
+            },
+        }
+    }
+}
+
+/// Parsed `credentials.toml`: profile -> provider -> credentials
+#[derive(Debug, Clone, Default)]
+pub struct CredentialsFile {
+    profiles: HashMap<String, HashMap<String, CloudCredentials>>,
+}
+
+impl CredentialsFile {
+    /// `ABYSS_CREDENTIALS_FILE`, else `~/.config/abyss/credentials.toml`
+    pub fn path() -> Option<PathBuf> {
+        if let Ok(path) = std::env::var("ABYSS_CREDENTIALS_FILE") {
+            return Some(PathBuf::from(path));
+        }
+        dirs::home_dir().map(|home| home.join(".config/abyss/credentials.toml"))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let profiles = toml::from_str(content).context("Invalid credentials file")?;
+        Ok(Self { profiles })
+    }
+
+    /// Load the credentials file; a missing file is the same as an empty one
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content).with_context(|| path.display().to_string()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// Profile names, sorted
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.profiles.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn get(&self, profile: &str, provider: CloudProvider) -> Option<&CloudCredentials> {
+        self.profiles.get(profile)?.get(provider.profile_key())
+    }
+}
+
+/// Read a profile from an AWS shared credentials file (INI: `[profile]` then `key = value`)
+pub fn parse_aws_credentials(content: &str, profile: &str) -> CloudCredentials {
+    let mut credentials = CloudCredentials::default();
+    let mut in_profile = false;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_profile = section.trim() == profile;
+            continue;
+        }
+        if !in_profile {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            let value = Some(value.trim().to_string());
+            match key.trim() {
+                "aws_access_key_id" => credentials.access_key = value,
+                "aws_secret_access_key" => credentials.secret_key = value,
+                "region" => credentials.region = value,
+                _ => {}
+            }
+        }
+    }
+    credentials
+}
+
+/// Profile selected through `ABYSS_PROFILE`
+pub fn active_profile() -> Option<String> {
+    std::env::var("ABYSS_PROFILE").ok().filter(|p| !p.is_empty())
+}
+
+/// Merge the credential sources for `provider`: `env` first, then `profile` in `file`,
+/// then (AWS only) the contents of the shared AWS credentials file.
+pub fn resolve_credentials_with(
+    provider: CloudProvider,
+    profile: Option<&str>,
+    env: &dyn Fn(&str) -> Option<String>,
+    file: &CredentialsFile,
+    aws_file: Option<&str>,
+) -> CloudCredentials {
+    let mut credentials = CloudCredentials::default();
+    for field in CredentialField::ALL {
+        *credentials.slot(field) = provider.env_var(field).and_then(env).filter(|v| !v.is_empty());
+    }
+
+    if let Some(from_file) = file.get(profile.unwrap_or(DEFAULT_PROFILE), provider) {
+        credentials.fill_from(from_file);
+    }
+
+    if provider == CloudProvider::Aws {
+        if let Some(content) = aws_file {
+            let aws_profile = profile
+                .map(str::to_string)
+                .or_else(|| env("AWS_PROFILE"))
+                .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+            credentials.fill_from(&parse_aws_credentials(content, &aws_profile));
+        }
+    }
+
+    credentials
+}
+
+/// Resolve credentials from the real environment and credential files
+pub fn resolve_credentials(provider: CloudProvider, profile: Option<&str>) -> Result<CloudCredentials> {
+    let file = CredentialsFile::load()?;
+    let aws_file = (provider == CloudProvider::Aws)
+        .then(|| {
+            std::env::var("AWS_SHARED_CREDENTIALS_FILE")
+                .map(PathBuf::from)
+                .ok()
+                .or_else(|| dirs::home_dir().map(|home| home.join(".aws/credentials")))
+        })
+        .flatten()
+        .and_then(|path| std::fs::read_to_string(path).ok());
+
+    Ok(resolve_credentials_with(
+        provider,
+        profile,
+        &|var| std::env::var(var).ok(),
+        &file,
+        aws_file.as_deref(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"
+[default.s3]
+bucket = "backups"
+region = "eu-central-1"
+access_key = "file-key"
+secret_key = "file-secret"
+
+[work.hetzner]
+bucket = "media"
+region = "fsn1"
+access_key = "hk"
+secret_key = "hs"
+
+[work.gcs]
+bucket = "work-bucket"
+service_account = "/etc/sa.json"
+prefix = "/exports/2024/"
+"#;
+
+    const AWS_FILE: &str = "
+# comment
+[default]
+aws_access_key_id = aws-default-key
+aws_secret_access_key = aws-default-secret
+
+[work]
+aws_access_key_id = aws-work-key
+aws_secret_access_key = aws-work-secret
+region = us-west-2
+";
+
+    fn env_from(vars: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+        move |name| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+    }
+
+    #[test]
+    fn test_parse_profiles() {
+        let file = CredentialsFile::parse(FILE).unwrap();
+        assert_eq!(file.profile_names(), vec!["default", "work"]);
+
+        let hetzner = file.get("work", CloudProvider::Hetzner).unwrap();
+        assert_eq!(hetzner.bucket.as_deref(), Some("media"));
+        assert_eq!(hetzner.region.as_deref(), Some("fsn1"));
+        assert_eq!(file.get("work", CloudProvider::Gcs).unwrap().service_account.as_deref(), Some("/etc/sa.json"));
+        assert!(file.get("work", CloudProvider::Aws).is_none());
+        assert!(file.get("missing", CloudProvider::Aws).is_none());
+
+        assert!(CredentialsFile::parse("[default.s3]\nbucket = 3").is_err());
+    }
+
+    #[test]
+    fn test_parse_aws_credentials() {
+        let work = parse_aws_credentials(AWS_FILE, "work");
+        assert_eq!(work.access_key.as_deref(), Some("aws-work-key"));
+        assert_eq!(work.secret_key.as_deref(), Some("aws-work-secret"));
+        assert_eq!(work.region.as_deref(), Some("us-west-2"));
+        assert_eq!(parse_aws_credentials(AWS_FILE, "nope"), CloudCredentials::default());
+    }
+
+    #[test]
+    fn test_env_overrides_profile() {
+        let file = CredentialsFile::parse(FILE).unwrap();
+        let env = env_from(&[
+            ("S3_BUCKET", "env-bucket"),
+            ("AWS_ACCESS_KEY_ID", "env-key"),
+            ("AWS_SECRET_ACCESS_KEY", "env-secret"),
+        ]);
+
+        let resolved = resolve_credentials_with(CloudProvider::Aws, None, &env, &file, Some(AWS_FILE));
+        assert_eq!(resolved.bucket.as_deref(), Some("env-bucket"));
+        assert_eq!(resolved.access_key.as_deref(), Some("env-key"));
+        assert_eq!(resolved.secret_key.as_deref(), Some("env-secret"));
+        // Not in the environment: the profile fills in
+        assert_eq!(resolved.region.as_deref(), Some("eu-central-1"));
+    }
+
+    #[test]
+    fn test_key_pair_comes_from_one_source() {
+        let file = CredentialsFile::parse(FILE).unwrap();
+
+        // Half a pair in the environment isn't completed with the profile's other half
+        let env = env_from(&[("AWS_ACCESS_KEY_ID", "env-key")]);
+        let resolved = resolve_credentials_with(CloudProvider::Aws, None, &env, &file, Some(AWS_FILE));
+        assert_eq!(resolved.access_key.as_deref(), Some("env-key"));
+        assert_eq!(resolved.secret_key, None);
+        assert!(resolved.require(CloudProvider::Aws, CredentialField::SecretKey).is_err());
+
+        // Nor is a profile's pair mixed with the AWS file's
+        let half = CredentialsFile::parse("[work.s3]\nsecret_key = \"file-secret\"").unwrap();
+        let no_env = env_from(&[]);
+        let resolved = resolve_credentials_with(CloudProvider::Aws, Some("work"), &no_env, &half, Some(AWS_FILE));
+        assert_eq!(resolved.access_key, None);
+        assert_eq!(resolved.secret_key.as_deref(), Some("file-secret"));
+        // Other settings still fall through
+        assert_eq!(resolved.region.as_deref(), Some("us-west-2"));
+    }
+
+    #[test]
+    fn test_profile_overrides_aws_file() {
+        let file = CredentialsFile::parse(FILE).unwrap();
+        let no_env = env_from(&[]);
+
+        // The abyss "work" profile has no s3 table, so ~/.aws/credentials [work] is used
+        let work = resolve_credentials_with(CloudProvider::Aws, Some("work"), &no_env, &file, Some(AWS_FILE));
+        assert_eq!(work.access_key.as_deref(), Some("aws-work-key"));
+        assert_eq!(work.region.as_deref(), Some("us-west-2"));
+        assert_eq!(work.bucket, None);
+
+        // AWS_PROFILE picks the AWS profile when none was selected
+        let env = env_from(&[("AWS_PROFILE", "work")]);
+        let empty = CredentialsFile::default();
+        let resolved = resolve_credentials_with(CloudProvider::Aws, None, &env, &empty, Some(AWS_FILE));
+        assert_eq!(resolved.access_key.as_deref(), Some("aws-work-key"));
+
+        // Other providers never read the AWS file
+        let hetzner = resolve_credentials_with(CloudProvider::Hetzner, Some("work"), &no_env, &file, Some(AWS_FILE));
+        assert_eq!(hetzner.access_key.as_deref(), Some("hk"));
+        let wasabi = resolve_credentials_with(CloudProvider::Wasabi, None, &no_env, &file, Some(AWS_FILE));
+        assert_eq!(wasabi, CloudCredentials::default());
+    }
+
+    #[test]
+    fn test_start_path_from_prefix() {
+        let file = CredentialsFile::parse(FILE).unwrap();
+        let no_env = env_from(&[]);
+
+        let gcs = resolve_credentials_with(CloudProvider::Gcs, Some("work"), &no_env, &file, None);
+        assert_eq!(gcs.start_path(), "exports/2024");
+
+        // The environment wins over the profile, like every other setting
+        let env = env_from(&[("GCS_PREFIX", "imports")]);
+        let gcs = resolve_credentials_with(CloudProvider::Gcs, Some("work"), &env, &file, None);
+        assert_eq!(gcs.start_path(), "imports");
+
+        // No prefix anywhere: the bucket root
+        let s3 = resolve_credentials_with(CloudProvider::Aws, None, &no_env, &file, Some(AWS_FILE));
+        assert_eq!(s3.start_path(), "");
+    }
+
+    #[test]
+    fn test_require_names_sources() {
+        let credentials = CloudCredentials::default();
+        let err = credentials.require(CloudProvider::CloudflareR2, CredentialField::AccountId).unwrap_err();
+        assert!(err.to_string().contains("R2_ACCOUNT_ID"));
+        assert!(err.to_string().contains("[<profile>.r2]"));
+        assert!(credentials.require(CloudProvider::MinIO, CredentialField::Region).is_err());
+        assert_eq!(CloudProvider::from_menu_name("Wasabi"), Some(CloudProvider::Wasabi));
+    }
+}
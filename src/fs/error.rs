@@ -0,0 +1,249 @@
+//! Error type for storage backends.
+//!
+//! Backends classify failures into a small set of kinds so callers (copy,
+//! sync, retry logic, UI messages) can tell a missing file from a denied
+//! permission or a flaky network. The message keeps the full context chain.
+
+use std::fmt;
+use std::io;
+
+/// Result type returned by `StorageBackend` methods
+pub type FsResult<T> = std::result::Result<T, FsError>;
+
+/// Classified storage backend error
+#[derive(Debug)]
+pub enum FsError {
+    /// Path, object or volume does not exist
+    NotFound(String),
+    /// Missing permissions or credentials rejected
+    PermissionDenied(String),
+    /// Connection, timeout, throttling or other transient transport failure
+    Network(String),
+    /// Operation not supported by this backend
+    Unsupported(String),
+    /// Target already exists
+    AlreadyExists(String),
+    /// Volume, disk or quota has no room left
+    StorageFull(String),
+    /// Anything else
+    Other(String),
+}
+
+impl FsError {
+    /// The error message (without the kind prefix)
+    pub fn message(&self) -> &str {
+        match self {
+            FsError::NotFound(m)
+            | FsError::PermissionDenied(m)
+            | FsError::Network(m)
+            | FsError::Unsupported(m)
+            | FsError::AlreadyExists(m)
+            | FsError::StorageFull(m)
+            | FsError::Other(m) => m,
+        }
+    }
+
+    /// Same kind of error with a different message
+    pub fn with_message(&self, message: String) -> Self {
+        match self {
+            FsError::NotFound(_) => FsError::NotFound(message),
+            FsError::PermissionDenied(_) => FsError::PermissionDenied(message),
+            FsError::Network(_) => FsError::Network(message),
+            FsError::Unsupported(_) => FsError::Unsupported(message),
+            FsError::AlreadyExists(_) => FsError::AlreadyExists(message),
+            FsError::StorageFull(_) => FsError::StorageFull(message),
+            FsError::Other(_) => FsError::Other(message),
+        }
+    }
+
+    /// Whether retrying the same operation may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, FsError::Network(_))
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, FsError::NotFound(_))
+    }
+
+    fn from_io_kind(kind: io::ErrorKind, message: String) -> Self {
+        match kind {
+            io::ErrorKind::NotFound => FsError::NotFound(message),
+            io::ErrorKind::PermissionDenied => FsError::PermissionDenied(message),
+            io::ErrorKind::AlreadyExists => FsError::AlreadyExists(message),
+            io::ErrorKind::Unsupported => FsError::Unsupported(message),
+            io::ErrorKind::StorageFull | io::ErrorKind::QuotaExceeded => FsError::StorageFull(message),
+            io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::UnexpectedEof => FsError::Network(message),
+            _ => FsError::Other(message),
+        }
+    }
+
+    fn from_opendal_kind(err: &opendal::Error, message: String) -> Self {
+        use opendal::ErrorKind;
+        match err.kind() {
+            ErrorKind::NotFound => FsError::NotFound(message),
+            ErrorKind::PermissionDenied => FsError::PermissionDenied(message),
+            ErrorKind::AlreadyExists => FsError::AlreadyExists(message),
+            ErrorKind::Unsupported => FsError::Unsupported(message),
+            ErrorKind::RateLimited => FsError::Network(message),
+            _ if err.is_temporary() => FsError::Network(message),
+            _ => FsError::Other(message),
+        }
+    }
+
+    fn from_kube(err: &kube::Error, message: String) -> Self {
+        match err {
+            kube::Error::Api(response) => match response.code {
+                404 => FsError::NotFound(message),
+                401 | 403 => FsError::PermissionDenied(message),
+                409 => FsError::AlreadyExists(message),
+                429 | 500..=599 => FsError::Network(message),
+                _ => FsError::Other(message),
+            },
+            kube::Error::HyperError(_) | kube::Error::Service(_) => FsError::Network(message),
+            _ => FsError::Other(message),
+        }
+    }
+
+    /// Classify from command output, e.g. stderr of `ls`/`cat` run inside a K8s pod
+    fn from_message(message: String) -> Self {
+        let lower = message.to_lowercase();
+        if lower.contains("no such file or directory") || lower.contains("not found") {
+            FsError::NotFound(message)
+        } else if lower.contains("permission denied") || lower.contains("forbidden") {
+            FsError::PermissionDenied(message)
+        } else if lower.contains("file exists") || lower.contains("already exists") {
+            FsError::AlreadyExists(message)
+        } else if lower.contains("no space left") || lower.contains("quota exceeded") {
+            FsError::StorageFull(message)
+        } else {
+            FsError::Other(message)
+        }
+    }
+}
+
+impl fmt::Display for FsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Only the message, so UI/status text reads the same as before classification
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for FsError {}
+
+impl From<io::Error> for FsError {
+    fn from(err: io::Error) -> Self {
+        FsError::from_io_kind(err.kind(), err.to_string())
+    }
+}
+
+impl From<opendal::Error> for FsError {
+    fn from(err: opendal::Error) -> Self {
+        let message = err.to_string();
+        FsError::from_opendal_kind(&err, message)
+    }
+}
+
+impl From<kube::Error> for FsError {
+    fn from(err: kube::Error) -> Self {
+        let message = err.to_string();
+        FsError::from_kube(&err, message)
+    }
+}
+
+impl From<anyhow::Error> for FsError {
+    /// Classify by the first recognizable error in the context chain
+    fn from(err: anyhow::Error) -> Self {
+        let message = format!("{:#}", err);
+        for cause in err.chain() {
+            if let Some(e) = cause.downcast_ref::<FsError>() {
+                return e.with_message(message);
+            }
+            if let Some(e) = cause.downcast_ref::<io::Error>() {
+                return FsError::from_io_kind(e.kind(), message);
+            }
+            if let Some(e) = cause.downcast_ref::<opendal::Error>() {
+                return FsError::from_opendal_kind(e, message);
+            }
+            if let Some(e) = cause.downcast_ref::<kube::Error>() {
+                return FsError::from_kube(e, message);
+            }
+        }
+        FsError::from_message(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_io_error_mapping() {
+        let not_found: FsError = io::Error::new(io::ErrorKind::NotFound, "gone").into();
+        assert!(not_found.is_not_found());
+        let denied: FsError = io::Error::new(io::ErrorKind::PermissionDenied, "nope").into();
+        assert!(matches!(denied, FsError::PermissionDenied(_)));
+        let exists: FsError = io::Error::new(io::ErrorKind::AlreadyExists, "dup").into();
+        assert!(matches!(exists, FsError::AlreadyExists(_)));
+        let timeout: FsError = io::Error::new(io::ErrorKind::TimedOut, "slow").into();
+        assert!(timeout.is_retryable());
+        let other: FsError = io::Error::new(io::ErrorKind::InvalidData, "bad").into();
+        assert!(matches!(other, FsError::Other(_)));
+    }
+
+    #[test]
+    fn test_opendal_error_mapping() {
+        let err = |kind| FsError::from(opendal::Error::new(kind, "test"));
+        assert!(err(opendal::ErrorKind::NotFound).is_not_found());
+        assert!(matches!(err(opendal::ErrorKind::PermissionDenied), FsError::PermissionDenied(_)));
+        assert!(matches!(err(opendal::ErrorKind::AlreadyExists), FsError::AlreadyExists(_)));
+        assert!(matches!(err(opendal::ErrorKind::Unsupported), FsError::Unsupported(_)));
+        assert!(err(opendal::ErrorKind::RateLimited).is_retryable());
+        assert!(FsError::from(opendal::Error::new(opendal::ErrorKind::Unexpected, "503").set_temporary()).is_retryable());
+        assert!(matches!(err(opendal::ErrorKind::ConfigInvalid), FsError::Other(_)));
+    }
+
+    #[test]
+    fn test_anyhow_chain_mapping_keeps_context() {
+        let err = Err::<(), _>(io::Error::new(io::ErrorKind::NotFound, "No such file"))
+            .context("Failed to read /x")
+            .unwrap_err();
+        let fs_err = FsError::from(err);
+        assert!(fs_err.is_not_found());
+        assert_eq!(fs_err.to_string(), "Failed to read /x: No such file");
+
+        // Plain messages (e.g. stderr from a pod) fall back to text matching
+        let pod_err = FsError::from(anyhow::anyhow!("cat: /data/x: No such file or directory"));
+        assert!(pod_err.is_not_found());
+        let full = FsError::from(anyhow::anyhow!("cat: write error: No space left on device"));
+        assert!(matches!(full, FsError::StorageFull(_)));
+        assert!(matches!(FsError::from(anyhow::anyhow!("boom")), FsError::Other(_)));
+    }
+
+    #[test]
+    fn test_round_trip_through_anyhow() {
+        let original = FsError::PermissionDenied("denied".to_string());
+        let wrapped = anyhow::Error::from(original).context("Failed to delete");
+        assert!(matches!(FsError::from(wrapped), FsError::PermissionDenied(_)));
+    }
+
+    #[tokio::test]
+    async fn test_backends_classify_errors() {
+        use crate::fs::{LocalBackend, SelectingBackend, StorageBackend};
+
+        let dir = tempfile::tempdir().unwrap();
+        let backend = LocalBackend::new(dir.path().to_path_buf());
+        assert!(backend.list_dir("missing").await.unwrap_err().is_not_found());
+        assert!(backend.read_bytes("missing.txt").await.unwrap_err().is_not_found());
+        assert!(backend.stat("missing.txt").await.unwrap_err().is_not_found());
+
+        let err = SelectingBackend.delete("x").await.unwrap_err();
+        assert!(matches!(err, FsError::Unsupported(_)));
+    }
+}
@@ -2,14 +2,19 @@ use anyhow::{Context, Result};
 use async_trait::async_trait;
 use opendal::{services::Gcs, Operator};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use crate::fs::backend::{BackendType, StorageBackend};
-use crate::fs::types::FileEntry;
+use crate::fs::backend::{BackendType, DeleteProgress, StorageBackend, WRITE_CHUNK_SIZE};
+use crate::fs::credentials::{CloudCredentials, CloudProvider, CredentialField};
+use crate::fs::error::FsResult;
+use crate::fs::timeout::{self, NetworkTimeout};
+use crate::fs::types::{FileEntry, ObjectMeta};
 
 /// Google Cloud Storage backend using OpenDAL
 pub struct GcsFs {
     operator: Operator,
     bucket: String,
+    timeout: NetworkTimeout,
 }
 
 impl GcsFs {
@@ -31,9 +36,16 @@ impl GcsFs {
         Ok(Self {
             operator,
             bucket: bucket.to_string(),
+            timeout: NetworkTimeout::from_env(),
         })
     }
 
+    /// Limit each network request to `timeout` instead of the `ABYSS_NETWORK_TIMEOUT` setting
+    pub fn with_timeout(mut self, timeout: NetworkTimeout) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
     /// Create GCS backend using GCE/GKE Workload Identity (automatic credentials)
     /// 
     /// Uses the standard Google credential chain:
@@ -47,6 +59,16 @@ impl GcsFs {
         Self::new(bucket, None)
     }
 
+    /// Create GCS backend from resolved credentials (environment and/or profile);
+    /// without a service account file, the Workload Identity / ADC chain is used
+    pub fn from_credentials(credentials: &CloudCredentials) -> Result<Self> {
+        let bucket = credentials.require(CloudProvider::Gcs, CredentialField::Bucket)?;
+        match credentials.service_account.as_deref() {
+            Some(path) => Self::from_service_account(bucket, path),
+            None => Self::new_with_workload_identity(bucket),
+        }
+    }
+
     /// Create GCS backend using service account JSON file
     pub fn from_service_account(bucket: &str, service_account_path: &str) -> Result<Self> {
         let credential = std::fs::read_to_string(service_account_path)
@@ -58,7 +80,7 @@ impl GcsFs {
 
 #[async_trait]
 impl StorageBackend for GcsFs {
-    async fn list_dir(&self, path: &str) -> Result<Vec<FileEntry>> {
+    async fn list_dir(&self, path: &str) -> FsResult<Vec<FileEntry>> {
         let path = if path.is_empty() || path == "/" { "" } else { path.trim_start_matches('/') };
         let path = if !path.is_empty() && !path.ends_with('/') {
             format!("{}/", path)
@@ -66,7 +88,7 @@ impl StorageBackend for GcsFs {
             path.to_string()
         };
 
-        let entries = self.operator.list(&path).await
+        let entries = self.timeout.run("Listing GCS directory", self.operator.list(&path)).await?
             .context("Failed to list GCS directory")?;
 
         let mut result = Vec::new();
@@ -101,39 +123,41 @@ impl StorageBackend for GcsFs {
         Ok(result)
     }
 
-    async fn delete(&self, path: &str) -> Result<()> {
+    async fn delete(&self, path: &str) -> FsResult<()> {
+        self.delete_with_progress(path, &DeleteProgress::default()).await
+    }
+
+    async fn delete_with_progress(&self, path: &str, progress: &DeleteProgress) -> FsResult<()> {
         let path = path.trim_start_matches('/');
-        
-        // Check if it's a directory
-        let meta = self.operator.stat(path).await;
-        
-        if let Ok(meta) = meta {
-            if meta.mode().is_dir() {
-                self.operator.remove_all(path).await
-                    .context("Failed to delete GCS directory")?;
-            } else {
-                self.operator.delete(path).await
-                    .context("Failed to delete GCS object")?;
-            }
-        } else {
-            self.operator.delete(path).await
-                .context("Failed to delete GCS object")?;
+
+        if timeout::is_prefix(&self.operator, path, self.timeout).await? {
+            let path = &format!("{}/", path.trim_end_matches('/'));
+            timeout::remove_all(&self.operator, path, self.timeout, progress).await
+                .context("Failed to delete GCS directory")?;
+            return Ok(());
         }
 
+        // A single object, or nothing at all: try deleting it as-is
+        progress.check_cancelled()?;
+        progress.add_total(1);
+        self.timeout.run("GCS delete", self.operator.delete(path)).await?
+            .context("Failed to delete GCS object")?;
+        progress.add_deleted(1);
+
         Ok(())
     }
 
-    async fn create_dir(&self, path: &str) -> Result<()> {
+    async fn create_dir(&self, path: &str) -> FsResult<()> {
         let path = path.trim_start_matches('/');
         let path = if path.ends_with('/') { path.to_string() } else { format!("{}/", path) };
         
-        self.operator.write(&path, Vec::<u8>::new()).await
+        self.timeout.run("Creating GCS directory", self.operator.write(&path, Vec::<u8>::new())).await?
             .context("Failed to create GCS directory marker")?;
 
         Ok(())
     }
 
-    async fn upload(&self, local_path: &Path, remote_path: &str) -> Result<()> {
+    async fn upload(&self, local_path: &Path, remote_path: &str) -> FsResult<()> {
         let remote_path = remote_path.trim_start_matches('/');
         
         if local_path.is_dir() {
@@ -142,55 +166,110 @@ impl StorageBackend for GcsFs {
             let content = tokio::fs::read(local_path).await
                 .context("Failed to read local file")?;
             
-            self.operator.write(remote_path, content).await
+            self.timeout.for_transfer(content.len() as u64)
+                .run("Upload to GCS", self.operator.write(remote_path, content)).await?
                 .context("Failed to upload to GCS")?;
         }
 
         Ok(())
     }
 
-    async fn download(&self, remote_path: &str, local_path: &Path) -> Result<()> {
+    async fn download(&self, remote_path: &str, local_path: &Path) -> FsResult<()> {
         let remote_path = remote_path.trim_start_matches('/');
         
         if let Some(parent) = local_path.parent() {
             tokio::fs::create_dir_all(parent).await?;
         }
 
-        let content = self.operator.read(remote_path).await
+        let content = timeout::read_object(&self.operator, remote_path, self.timeout).await
             .context("Failed to download from GCS")?;
         
-        tokio::fs::write(local_path, content.to_vec()).await
+        tokio::fs::write(local_path, content).await
             .context("Failed to write local file")?;
 
         Ok(())
     }
 
-    async fn read_bytes(&self, path: &str) -> Result<Vec<u8>> {
+    async fn read_bytes(&self, path: &str) -> FsResult<Vec<u8>> {
+        let path = path.trim_start_matches('/');
+        let content = timeout::read_object(&self.operator, path, self.timeout).await
+            .context("Failed to read from GCS")?;
+        Ok(content)
+    }
+
+    async fn read_range(&self, path: &str, offset: u64, length: u64) -> FsResult<Vec<u8>> {
         let path = path.trim_start_matches('/');
-        let content = self.operator.read(path).await
+        let content = timeout::read_object_range(&self.operator, path, offset, length, None, self.timeout).await
             .context("Failed to read from GCS")?;
-        Ok(content.to_vec())
+        Ok(content)
     }
 
-    async fn write_bytes(&self, path: &str, data: Vec<u8>) -> Result<()> {
+    async fn read_range_sized(&self, path: &str, offset: u64, length: u64, size: u64) -> FsResult<Vec<u8>> {
         let path = path.trim_start_matches('/');
-        self.operator.write(path, data).await
+        let content = timeout::read_object_range(&self.operator, path, offset, length, Some(size), self.timeout).await
+            .context("Failed to read from GCS")?;
+        Ok(content)
+    }
+
+    async fn write_bytes(&self, path: &str, data: Vec<u8>) -> FsResult<()> {
+        let path = path.trim_start_matches('/');
+        self.timeout.for_transfer(data.len() as u64)
+            .run("Write to GCS", self.operator.write(path, data)).await?
             .context("Failed to write to GCS")?;
         Ok(())
     }
 
-    async fn is_dir(&self, path: &str) -> Result<bool> {
+    async fn write_bytes_with_progress(&self, path: &str, data: Vec<u8>, written: &AtomicU64) -> FsResult<()> {
+        let path = path.trim_start_matches('/');
+        let mut writer = self.timeout.run("Opening GCS writer", self.operator.writer(path)).await?
+            .context("Failed to open GCS writer")?;
+        for chunk in data.chunks(WRITE_CHUNK_SIZE) {
+            self.timeout.for_transfer(chunk.len() as u64)
+                .run("Write to GCS", writer.write(chunk.to_vec())).await?
+                .context("Failed to write to GCS")?;
+            written.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+        }
+        self.timeout.run("Finishing GCS upload", writer.close()).await?
+            .context("Failed to finish GCS upload")?;
+        Ok(())
+    }
+
+    async fn copy_within(&self, from: &str, to: &str) -> FsResult<()> {
+        // Server-side rewrite, the object data never leaves the bucket
+        let from = from.trim_start_matches('/');
+        let to = to.trim_start_matches('/');
+        // The copy runs server-side, but still takes longer for larger objects
+        let size = self.timeout.run("GCS stat", self.operator.stat(from)).await?
+            .map(|meta| meta.content_length())
+            .unwrap_or(0);
+        self.timeout.for_transfer(size)
+            .run("Copy within GCS", self.operator.copy(from, to)).await?
+            .context("Failed to copy within GCS")?;
+        Ok(())
+    }
+
+    async fn object_meta(&self, path: &str) -> FsResult<ObjectMeta> {
+        let path = path.trim_start_matches('/');
+        // Prefixes have no metadata of their own
+        if self.is_dir(path).await? {
+            return Ok(ObjectMeta { is_dir: true, ..ObjectMeta::default() });
+        }
+        // GCS names the class on every object, but only presigns with a service account key
+        timeout::object_meta(&self.operator, path, self.timeout, "x-goog-storage-class", None).await
+    }
+
+    async fn is_dir(&self, path: &str) -> FsResult<bool> {
         let path = path.trim_start_matches('/');
         if path.is_empty() {
             return Ok(true);
         }
         
-        match self.operator.stat(path).await {
+        match self.timeout.run("GCS stat", self.operator.stat(path)).await? {
             Ok(meta) => Ok(meta.mode().is_dir()),
             Err(_) => {
                 // Try with trailing slash
                 let dir_path = if path.ends_with('/') { path.to_string() } else { format!("{}/", path) };
-                match self.operator.stat(&dir_path).await {
+                match self.timeout.run("GCS stat", self.operator.stat(&dir_path)).await? {
                     Ok(meta) => Ok(meta.mode().is_dir()),
                     Err(_) => Ok(false),
                 }
@@ -223,7 +302,8 @@ impl GcsFs {
                     self.upload_dir(&path, &remote).await?;
                 } else {
                     let content = tokio::fs::read(&path).await?;
-                    self.operator.write(&remote, content).await?;
+                    self.timeout.for_transfer(content.len() as u64)
+                        .run("Upload to GCS", self.operator.write(&remote, content)).await??;
                 }
             }
             
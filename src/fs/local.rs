@@ -2,17 +2,80 @@ use anyhow::{Context, Result};
 use chrono::DateTime;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
-use crate::fs::types::FileEntry;
+use crate::fs::backend::WRITE_CHUNK_SIZE;
+use crate::fs::error::FsResult;
+use crate::fs::types::{sort_entries, DirListing, FileEntry, SpaceInfo};
+
+/// Remove a file or directory without following symlinks.
+///
+/// A symlink (e.g. a `node_modules` linked to a shared cache outside the scanned
+/// tree) is removed as a link; whatever it points to is left alone.
+pub fn remove_path(path: &Path) -> std::io::Result<()> {
+    let meta = fs::symlink_metadata(path)?;
+    if meta.file_type().is_symlink() {
+        // Windows directory links are removed like empty directories
+        #[cfg(windows)]
+        if fs::metadata(path).map(|m| m.is_dir()).unwrap_or(false) {
+            return fs::remove_dir(path);
+        }
+        fs::remove_file(path)
+    } else if meta.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
 
 pub struct LocalFs;
 
 impl LocalFs {
     pub fn list_dir(path: &Path) -> Result<Vec<FileEntry>> {
-        let mut entries = Vec::new();
+        let read_dir = fs::read_dir(path)
+            .with_context(|| format!("Failed to read directory: {}", path.display()))?;
+        let mut entries = Self::read_entries(read_dir, usize::MAX, false)?;
+        sort_entries(&mut entries);
+        Ok(entries)
+    }
 
+    /// Like [`list_dir`](Self::list_dir), with hidden entries too
+    pub fn list_dir_all(path: &Path) -> Result<Vec<FileEntry>> {
         let read_dir = fs::read_dir(path)
             .with_context(|| format!("Failed to read directory: {}", path.display()))?;
+        let mut entries = Self::read_entries(read_dir, usize::MAX, true)?;
+        sort_entries(&mut entries);
+        Ok(entries)
+    }
+
+    /// List the first `page` entries (in directory order, then sorted) and hand the rest to
+    /// a background thread, so a directory with hundreds of thousands of files shows at once
+    pub fn list_dir_paged(path: &Path, page: usize) -> Result<DirListing> {
+        let mut read_dir = fs::read_dir(path)
+            .with_context(|| format!("Failed to read directory: {}", path.display()))?
+            .peekable();
+        let mut entries = Self::read_entries(read_dir.by_ref(), page, false)?;
+        sort_entries(&mut entries);
+        if read_dir.peek().is_none() {
+            return Ok(DirListing::complete(entries));
+        }
+
+        let (tx, rx) = crossbeam_channel::bounded(1);
+        std::thread::spawn(move || {
+            let rest = Self::read_entries(read_dir, usize::MAX, false).map_err(|e| e.to_string());
+            // The pane may have moved on, dropping the receiver
+            let _ = tx.send(rest);
+        });
+        Ok(DirListing { entries, rest: Some(rx) })
+    }
+
+    /// Read up to `limit` entries, unsorted; hidden ones only with `hidden`
+    fn read_entries(
+        read_dir: impl Iterator<Item = std::io::Result<fs::DirEntry>>,
+        limit: usize,
+        hidden: bool,
+    ) -> Result<Vec<FileEntry>> {
+        let mut entries = Vec::new();
 
         for entry in read_dir {
             let entry = entry?;
@@ -20,7 +83,7 @@ impl LocalFs {
             let name = entry.file_name().to_string_lossy().to_string();
 
             // Skip hidden files starting with .
-            if name.starts_with('.') {
+            if !hidden && name.starts_with('.') {
                 continue;
             }
 
@@ -38,20 +101,21 @@ impl LocalFs {
                 modified,
                 permissions: None,
             });
+            if entries.len() >= limit {
+                break;
+            }
         }
 
-        // Sort: directories first, then by name
-        entries.sort_by(|a, b| match (a.is_dir, b.is_dir) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-        });
-
         Ok(entries)
     }
 
     pub fn delete(path: &Path) -> Result<()> {
-        if path.is_dir() {
+        // Don't follow symlinks: deleting a link to a directory removes only the link
+        let is_link = fs::symlink_metadata(path).map(|m| m.file_type().is_symlink()).unwrap_or(false);
+        if is_link {
+            remove_path(path)
+                .with_context(|| format!("Failed to delete link: {}", path.display()))?;
+        } else if path.is_dir() {
             fs::remove_dir_all(path)
                 .with_context(|| format!("Failed to delete directory: {}", path.display()))?;
         } else {
@@ -100,6 +164,24 @@ impl LocalFs {
         Ok(())
     }
 
+    /// Recursive size of all files under `path`. Returns `None` if cancelled.
+    pub fn dir_size(path: &Path, cancelled: &AtomicBool) -> Option<u64> {
+        let mut total = 0;
+
+        // Use jwalk for fast parallel directory walking
+        for entry in jwalk::WalkDir::new(path).skip_hidden(false) {
+            if cancelled.load(Ordering::Relaxed) {
+                return None;
+            }
+            let Ok(entry) = entry else { continue };
+            if entry.file_type().is_file() {
+                total += entry.metadata().map(|m| m.len()).unwrap_or(0);
+            }
+        }
+
+        Some(total)
+    }
+
     pub fn normalize_path(path: &Path) -> PathBuf {
         let mut normalized = PathBuf::new();
 
@@ -126,6 +208,33 @@ pub struct LocalBackend {
     pub root: PathBuf,
 }
 
+/// Free and total space of the filesystem holding `path`
+#[cfg(unix)]
+fn filesystem_space(path: &Path) -> std::io::Result<Option<SpaceInfo>> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    // SAFETY: statvfs is plain data, fully written by a successful call
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path is NUL-terminated and outlives the call
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let block = stat.f_frsize as u64;
+    Ok(Some(SpaceInfo {
+        available: stat.f_bavail as u64 * block,
+        total: stat.f_blocks as u64 * block,
+    }))
+}
+
+/// No statvfs binding on this platform
+#[cfg(not(unix))]
+fn filesystem_space(_path: &Path) -> std::io::Result<Option<SpaceInfo>> {
+    Ok(None)
+}
+
 impl LocalBackend {
     pub fn new(root: PathBuf) -> Self {
         Self { root }
@@ -142,25 +251,33 @@ impl LocalBackend {
 
 #[async_trait::async_trait]
 impl crate::fs::backend::StorageBackend for LocalBackend {
-    async fn list_dir(&self, path: &str) -> Result<Vec<FileEntry>> {
-        LocalFs::list_dir(&self.full_path(path))
+    async fn list_dir(&self, path: &str) -> FsResult<Vec<FileEntry>> {
+        Ok(LocalFs::list_dir(&self.full_path(path))?)
+    }
+
+    async fn list_dir_all(&self, path: &str) -> FsResult<Vec<FileEntry>> {
+        Ok(LocalFs::list_dir_all(&self.full_path(path))?)
+    }
+
+    async fn list_dir_paged(&self, path: &str, page: usize) -> FsResult<DirListing> {
+        Ok(LocalFs::list_dir_paged(&self.full_path(path), page)?)
     }
     
-    async fn delete(&self, path: &str) -> Result<()> {
-        LocalFs::delete(&self.full_path(path))
+    async fn delete(&self, path: &str) -> FsResult<()> {
+        Ok(LocalFs::delete(&self.full_path(path))?)
     }
     
-    async fn create_dir(&self, path: &str) -> Result<()> {
+    async fn create_dir(&self, path: &str) -> FsResult<()> {
         tokio::fs::create_dir_all(self.full_path(path)).await?;
         Ok(())
     }
     
-    async fn is_dir(&self, path: &str) -> Result<bool> {
+    async fn is_dir(&self, path: &str) -> FsResult<bool> {
         let full = self.full_path(path);
         Ok(full.is_dir())
     }
     
-    async fn upload(&self, local_path: &Path, remote_path: &str) -> Result<()> {
+    async fn upload(&self, local_path: &Path, remote_path: &str) -> FsResult<()> {
         let dest = self.full_path(remote_path);
         if let Some(parent) = dest.parent() {
             tokio::fs::create_dir_all(parent).await?;
@@ -171,20 +288,20 @@ impl crate::fs::backend::StorageBackend for LocalBackend {
         Ok(())
     }
     
-    async fn download(&self, remote_path: &str, local_path: &Path) -> Result<()> {
+    async fn download(&self, remote_path: &str, local_path: &Path) -> FsResult<()> {
         let src = self.full_path(remote_path);
         // For local-to-local downloads, same recursive copy
         LocalFs::copy_file(&src, local_path)?;
         Ok(())
     }
     
-    async fn read_bytes(&self, path: &str) -> Result<Vec<u8>> {
+    async fn read_bytes(&self, path: &str) -> FsResult<Vec<u8>> {
         let data = tokio::fs::read(self.full_path(path)).await
             .context("Failed to read local file")?;
         Ok(data)
     }
     
-    async fn read_range(&self, path: &str, offset: u64, length: u64) -> Result<Vec<u8>> {
+    async fn read_range(&self, path: &str, offset: u64, length: u64) -> FsResult<Vec<u8>> {
         use tokio::io::{AsyncReadExt, AsyncSeekExt};
         let mut file = tokio::fs::File::open(self.full_path(path)).await
             .context("Failed to open local file")?;
@@ -204,7 +321,7 @@ impl crate::fs::backend::StorageBackend for LocalBackend {
         Ok(buffer)
     }
     
-    async fn write_bytes(&self, path: &str, data: Vec<u8>) -> Result<()> {
+    async fn write_bytes(&self, path: &str, data: Vec<u8>) -> FsResult<()> {
         let dest = self.full_path(path);
         if let Some(parent) = dest.parent() {
             tokio::fs::create_dir_all(parent).await?;
@@ -213,8 +330,70 @@ impl crate::fs::backend::StorageBackend for LocalBackend {
             .context("Failed to write local file")?;
         Ok(())
     }
+
+    async fn write_bytes_with_progress(&self, path: &str, data: Vec<u8>, written: &AtomicU64) -> FsResult<()> {
+        use tokio::io::AsyncWriteExt;
+        let dest = self.full_path(path);
+        if let Some(parent) = dest.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::File::create(&dest).await
+            .context("Failed to create local file")?;
+        for chunk in data.chunks(WRITE_CHUNK_SIZE) {
+            file.write_all(chunk).await
+                .context("Failed to write local file")?;
+            written.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+        }
+        file.flush().await
+            .context("Failed to write local file")?;
+        Ok(())
+    }
+
+    async fn append_bytes(&self, path: &str, data: Vec<u8>) -> FsResult<()> {
+        use tokio::io::AsyncWriteExt;
+        let dest = self.full_path(path);
+        if let Some(parent) = dest.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new().create(true).append(true).open(&dest).await
+            .context("Failed to open local file for appending")?;
+        file.write_all(&data).await
+            .context("Failed to append to local file")?;
+        file.flush().await
+            .context("Failed to append to local file")?;
+        Ok(())
+    }
     
-    async fn rename(&self, from: &str, to: &str) -> Result<()> {
+    async fn space_info(&self, path: &str) -> FsResult<Option<SpaceInfo>> {
+        Ok(filesystem_space(&self.full_path(path))?)
+    }
+
+    async fn create_file(&self, path: &str) -> FsResult<()> {
+        // create_new fails with AlreadyExists instead of truncating an existing file
+        tokio::fs::OpenOptions::new().write(true).create_new(true).open(self.full_path(path)).await?;
+        Ok(())
+    }
+
+    async fn stat(&self, path: &str) -> FsResult<crate::fs::backend::FileStat> {
+        // Straight from the metadata: hidden files included, full mtime precision
+        let metadata = tokio::fs::metadata(self.full_path(path)).await?;
+        Ok(crate::fs::backend::FileStat {
+            size: metadata.len(),
+            is_dir: metadata.is_dir(),
+            modified: metadata.modified().ok().map(chrono::DateTime::from),
+            created: metadata.created().ok().map(chrono::DateTime::from),
+            permissions: None,
+        })
+    }
+
+    async fn set_modified_time(&self, path: &str, modified: chrono::DateTime<chrono::Utc>) -> FsResult<()> {
+        let file = crate::fs::copy::open_for_times(&self.full_path(path))?;
+        file.set_times(fs::FileTimes::new().set_modified(modified.into()))
+            .context("Failed to set modification time")?;
+        Ok(())
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> FsResult<()> {
         let from_path = self.full_path(from);
         let to_path = self.full_path(to);
         tokio::fs::rename(from_path, to_path).await
@@ -238,3 +417,121 @@ impl crate::fs::backend::StorageBackend for LocalBackend {
         self.full_path(path).to_string_lossy().to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::backend::StorageBackend;
+    use crate::fs::error::FsError;
+
+    #[test]
+    fn test_remove_path_regular_entries() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("target");
+        fs::create_dir_all(dir.join("debug")).unwrap();
+        fs::write(dir.join("debug/out"), b"x").unwrap();
+        let file = root.path().join(".DS_Store");
+        fs::write(&file, b"x").unwrap();
+
+        remove_path(&dir).unwrap();
+        remove_path(&file).unwrap();
+        assert!(!dir.exists());
+        assert!(!file.exists());
+    }
+
+    #[tokio::test]
+    async fn test_create_file_never_overwrites() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = LocalBackend::new(dir.path().to_path_buf());
+
+        backend.create_file("empty.txt").await.unwrap();
+        assert_eq!(fs::metadata(dir.path().join("empty.txt")).unwrap().len(), 0);
+
+        fs::write(dir.path().join("kept.txt"), "data").unwrap();
+        let err = backend.create_file("kept.txt").await.unwrap_err();
+        assert!(matches!(err, FsError::AlreadyExists(_)), "{:?}", err);
+        assert_eq!(fs::read_to_string(dir.path().join("kept.txt")).unwrap(), "data");
+
+        // Hidden files are not listed but must still be detected
+        fs::write(dir.path().join(".hidden"), "x").unwrap();
+        assert!(backend.create_file(".hidden").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stat_sees_hidden_files_with_full_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = LocalBackend::new(dir.path().to_path_buf());
+        fs::write(dir.path().join(".env"), "KEY=1").unwrap();
+
+        let stat = backend.stat(".env").await.unwrap();
+        assert_eq!(stat.size, 5);
+        assert!(!stat.is_dir);
+        let mtime = fs::metadata(dir.path().join(".env")).unwrap().modified().unwrap();
+        assert_eq!(stat.modified, Some(chrono::DateTime::from(mtime)));
+
+        assert!(backend.stat("").await.unwrap().is_dir);
+        let err = backend.stat(".missing").await.unwrap_err();
+        assert!(matches!(err, FsError::NotFound(_)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_set_modified_time_on_files_and_directories() {
+        use chrono::TimeZone;
+
+        let dir = tempfile::tempdir().unwrap();
+        let backend = LocalBackend::new(dir.path().to_path_buf());
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/a.txt"), "a").unwrap();
+
+        let old = chrono::Utc.with_ymd_and_hms(2020, 1, 2, 3, 4, 5).unwrap();
+        for path in ["sub/a.txt", "sub"] {
+            backend.set_modified_time(path, old).await.unwrap();
+            assert_eq!(backend.stat(path).await.unwrap().modified, Some(old), "{}", path);
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_space_info_reports_filesystem_capacity() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = LocalBackend::new(dir.path().to_path_buf());
+
+        let space = backend.space_info("").await.unwrap().unwrap();
+        assert!(space.total > 0);
+        assert!(space.available <= space.total);
+        assert!(backend.space_info("missing/dir").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_huge_directory_lists_first_page_at_once() {
+        use crate::fs::backend::LIST_PAGE_SIZE;
+
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..100_000 {
+            fs::File::create(dir.path().join(format!("f{:06}", i))).unwrap();
+        }
+        fs::create_dir(dir.path().join("zdir")).unwrap();
+        let backend = LocalBackend::new(dir.path().to_path_buf());
+
+        let started = std::time::Instant::now();
+        let listing = backend.list_dir_paged("", LIST_PAGE_SIZE).await.unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_secs(1), "{:?}", started.elapsed());
+        assert_eq!(listing.entries.len(), LIST_PAGE_SIZE);
+
+        // The rest arrives in the background and completes the full set
+        let rest = listing.rest.unwrap().recv().unwrap().unwrap();
+        let mut all = listing.entries;
+        all.extend(rest);
+        assert_eq!(all.len(), 100_001);
+        sort_entries(&mut all);
+        assert_eq!(all[0].name, "zdir");
+        assert_eq!(all[1].name, "f000000");
+
+        // Small directories come back in one piece
+        let small = tempfile::tempdir().unwrap();
+        fs::write(small.path().join("a.txt"), "a").unwrap();
+        let listing = LocalFs::list_dir_paged(small.path(), LIST_PAGE_SIZE).unwrap();
+        assert_eq!(listing.entries.len(), 1);
+        assert!(listing.rest.is_none());
+    }
+}
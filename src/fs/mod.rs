@@ -1,15 +1,22 @@
 pub mod backend;
 pub mod copy;
+pub mod credentials;
+pub mod error;
 pub mod gcs;
 pub mod local;
 pub mod remote;
 pub mod s3;
 pub mod selecting;
+pub mod timeout;
 pub mod types;
+pub mod uri;
 
 pub use backend::{BackendType, StorageBackend};
 pub use copy::copy_between_backends;
-pub use local::{LocalBackend, LocalFs};
+pub use error::{FsError, FsResult};
+pub use local::{remove_path, LocalBackend, LocalFs};
 pub use remote::{K8sBackend, RemoteFs};
 pub use selecting::SelectingBackend;
+pub use timeout::NetworkTimeout;
 pub use types::*;
+pub use uri::{parse_uri, Location};
@@ -2,13 +2,17 @@ use anyhow::{Context, Result};
 use kube::Client;
 use std::sync::Arc;
 
-use crate::fs::types::FileEntry;
-use crate::k8s::pod::PodManager;
+use crate::fs::backend::DeleteProgress;
+use crate::fs::error::FsResult;
+use crate::fs::timeout::NetworkTimeout;
+use crate::fs::types::{FileEntry, SpaceInfo};
+use crate::k8s::pod::{ExecOutput, PodExec, PodManager};
 
 #[derive(Clone)]
 pub struct RemoteFs {
     client: Client,
     pod_manager: Arc<PodManager>,
+    timeout: NetworkTimeout,
 }
 
 impl RemoteFs {
@@ -16,9 +20,24 @@ impl RemoteFs {
         Self {
             pod_manager: Arc::new(PodManager::new(client.clone())),
             client,
+            timeout: NetworkTimeout::from_env(),
         }
     }
 
+    /// Limit each exec/transfer to `timeout` instead of the `ABYSS_NETWORK_TIMEOUT` setting.
+    /// Starting the helper pod has its own wait and is not covered.
+    pub fn with_timeout(mut self, timeout: NetworkTimeout) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run a command in the helper pod under the base limit
+    async fn exec(&self, namespace: &str, pod_name: &str, command: Vec<String>) -> Result<String> {
+        self.timeout
+            .run("Kubernetes exec", self.pod_manager.exec_command(namespace, pod_name, command))
+            .await?
+    }
+
     pub async fn list_dir(&self, namespace: &str, pvc: &str, path: &str) -> Result<Vec<FileEntry>> {
         let pod_name = self
             .pod_manager
@@ -37,8 +56,7 @@ impl RemoteFs {
         ];
 
         let output = self
-            .pod_manager
-            .exec_command(namespace, &pod_name, command)
+            .exec(namespace, &pod_name, command)
             .await
             .context("Failed to execute ls command")?;
 
@@ -107,8 +125,7 @@ impl RemoteFs {
         ];
 
         let output = self
-            .pod_manager
-            .exec_command(namespace, &pod_name, command)
+            .exec(namespace, &pod_name, command)
             .await
             .context("Failed to get disk usage")?;
 
@@ -127,6 +144,27 @@ impl RemoteFs {
         }
     }
 
+    /// Free and total space of the PVC mount
+    pub async fn space_info(&self, namespace: &str, pvc: &str) -> Result<Option<SpaceInfo>> {
+        let pod_name = self
+            .pod_manager
+            .ensure_pod(namespace, pvc)
+            .await
+            .context("Failed to create pod for PVC access")?;
+
+        let command = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "df -Pk /data 2>/dev/null | tail -1".to_string(),
+        ];
+
+        let output = self
+            .exec(namespace, &pod_name, command)
+            .await
+            .context("Failed to get free space")?;
+        Ok(SpaceInfo::from_df_line(output.trim()))
+    }
+
     /// Get directory sizes for ncdu-like analysis
     pub async fn get_directory_sizes(
         &self,
@@ -140,6 +178,7 @@ impl RemoteFs {
             .await
             .context("Failed to create pod for PVC access")?;
 
+        // Not under the network limit: du runs as long as the tree needs
         // Use du to get sizes, with -s for summary and -b for bytes
         let command = vec![
             "sh".to_string(),
@@ -179,6 +218,7 @@ impl RemoteFs {
     pub async fn delete(&self, namespace: &str, pvc: &str, path: &str) -> Result<()> {
         let pod_name = self.pod_manager.ensure_pod(namespace, pvc).await?;
 
+        // Not under the network limit: rm -rf runs as long as the tree needs
         let command = vec!["rm".to_string(), "-rf".to_string(), path.to_string()];
 
         self.pod_manager
@@ -192,9 +232,7 @@ impl RemoteFs {
 
         let command = vec!["mkdir".to_string(), "-p".to_string(), path.to_string()];
 
-        self.pod_manager
-            .exec_command(namespace, &pod_name, command)
-            .await?;
+        self.exec(namespace, &pod_name, command).await?;
         Ok(())
     }
 
@@ -215,9 +253,15 @@ impl RemoteFs {
             }
         }
 
-        self.pod_manager
-            .copy_to_pod(namespace, &pod_name, local_path, remote_path)
-            .await?;
+        let size = if local_path.is_dir() {
+            crate::fs::LocalFs::dir_size(local_path, &std::sync::atomic::AtomicBool::new(false)).unwrap_or(0)
+        } else {
+            std::fs::metadata(local_path).map(|m| m.len()).unwrap_or(0)
+        };
+        self.timeout
+            .for_transfer(size)
+            .run("Upload to Kubernetes", self.pod_manager.copy_to_pod(namespace, &pod_name, local_path, remote_path))
+            .await??;
         Ok(())
     }
 
@@ -235,17 +279,32 @@ impl RemoteFs {
             std::fs::create_dir_all(parent)?;
         }
 
-        self.pod_manager
-            .copy_from_pod(namespace, &pod_name, remote_path, local_path)
-            .await?;
+        // Size the limit by what is on the volume (du -k reports KiB)
+        let size = self
+            .exec(namespace, &pod_name, vec!["du".to_string(), "-sk".to_string(), remote_path.to_string()])
+            .await?
+            .split_whitespace()
+            .next()
+            .and_then(|kib| kib.parse::<u64>().ok())
+            .unwrap_or(0)
+            * 1024;
+        self.timeout
+            .for_transfer(size)
+            .run("Download from Kubernetes", self.pod_manager.copy_from_pod(namespace, &pod_name, remote_path, local_path))
+            .await??;
         Ok(())
     }
 
+    /// Write `data` to `path`, verified in the pod before it replaces the old file
+    /// (see `k8s::write`)
+    pub async fn write_file(&self, namespace: &str, pvc: &str, path: &str, data: &[u8]) -> FsResult<()> {
+        crate::k8s::write::write_file(self, namespace, pvc, path, data).await
+    }
+
     pub async fn is_dir(&self, namespace: &str, pvc: &str, path: &str) -> Result<bool> {
         let pod_name = self.pod_manager.ensure_pod(namespace, pvc).await?;
         let output = self
-            .pod_manager
-            .exec_command(
+            .exec(
                 namespace,
                 &pod_name,
                 vec![
@@ -259,6 +318,32 @@ impl RemoteFs {
     }
 }
 
+#[async_trait::async_trait]
+impl PodExec for RemoteFs {
+    async fn ensure_pod(&self, namespace: &str, pvc: &str) -> Result<String> {
+        self.pod_manager.ensure_pod(namespace, pvc).await
+    }
+
+    /// Runs under the network limit, extended for the size of `stdin`
+    async fn exec_with_input(
+        &self,
+        namespace: &str,
+        pod_name: &str,
+        command: Vec<String>,
+        stdin: &[u8],
+    ) -> Result<ExecOutput> {
+        self.timeout
+            .for_transfer(stdin.len() as u64)
+            .run("Kubernetes exec", self.pod_manager.exec_with_input(namespace, pod_name, command, stdin))
+            .await?
+    }
+
+    /// Not under the network limit: these commands run as long as the tree needs
+    async fn exec_long(&self, namespace: &str, pod_name: &str, command: Vec<String>) -> Result<ExecOutput> {
+        self.pod_manager.exec_with_input(namespace, pod_name, command, b"").await
+    }
+}
+
 /// Kubernetes PVC storage backend
 pub struct K8sBackend {
     pub namespace: String,
@@ -278,31 +363,40 @@ impl K8sBackend {
 
 #[async_trait::async_trait]
 impl crate::fs::backend::StorageBackend for K8sBackend {
-    async fn list_dir(&self, path: &str) -> Result<Vec<FileEntry>> {
-        self.fs.list_dir(&self.namespace, &self.pvc, path).await
+    async fn list_dir(&self, path: &str) -> FsResult<Vec<FileEntry>> {
+        Ok(self.fs.list_dir(&self.namespace, &self.pvc, path).await?)
+    }
+
+    async fn delete(&self, path: &str) -> FsResult<()> {
+        Ok(self.fs.delete(&self.namespace, &self.pvc, path).await?)
     }
 
-    async fn delete(&self, path: &str) -> Result<()> {
-        self.fs.delete(&self.namespace, &self.pvc, path).await
+    async fn delete_with_progress(&self, path: &str, progress: &DeleteProgress) -> FsResult<()> {
+        crate::k8s::delete::delete_with_progress(&self.fs, &self.namespace, &self.pvc, path, progress).await
     }
 
-    async fn create_dir(&self, path: &str) -> Result<()> {
-        self.fs.create_dir(&self.namespace, &self.pvc, path).await
+    async fn create_dir(&self, path: &str) -> FsResult<()> {
+        Ok(self.fs.create_dir(&self.namespace, &self.pvc, path).await?)
     }
 
-    async fn is_dir(&self, path: &str) -> Result<bool> {
-        self.fs.is_dir(&self.namespace, &self.pvc, path).await
+    async fn is_dir(&self, path: &str) -> FsResult<bool> {
+        Ok(self.fs.is_dir(&self.namespace, &self.pvc, path).await?)
     }
 
-    async fn upload(&self, local_path: &std::path::Path, remote_path: &str) -> Result<()> {
-        self.fs.copy_to_remote(&self.namespace, &self.pvc, local_path, remote_path).await
+    async fn space_info(&self, _path: &str) -> FsResult<Option<SpaceInfo>> {
+        // Everything under the PVC lives on one volume
+        Ok(self.fs.space_info(&self.namespace, &self.pvc).await?)
     }
 
-    async fn download(&self, remote_path: &str, local_path: &std::path::Path) -> Result<()> {
-        self.fs.copy_from_remote(&self.namespace, &self.pvc, remote_path, local_path).await
+    async fn upload(&self, local_path: &std::path::Path, remote_path: &str) -> FsResult<()> {
+        Ok(self.fs.copy_to_remote(&self.namespace, &self.pvc, local_path, remote_path).await?)
     }
 
-    async fn read_bytes(&self, path: &str) -> Result<Vec<u8>> {
+    async fn download(&self, remote_path: &str, local_path: &std::path::Path) -> FsResult<()> {
+        Ok(self.fs.copy_from_remote(&self.namespace, &self.pvc, remote_path, local_path).await?)
+    }
+
+    async fn read_bytes(&self, path: &str) -> FsResult<Vec<u8>> {
         // Implement read via download to temp file
         let ts = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -316,18 +410,8 @@ impl crate::fs::backend::StorageBackend for K8sBackend {
         Ok(data)
     }
 
-    async fn write_bytes(&self, path: &str, data: Vec<u8>) -> Result<()> {
-        // Implement write via upload from temp file
-        let ts = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_nanos();
-        let temp_path = std::env::temp_dir().join(format!("abyss_write_{}_{}", std::process::id(), ts));
-        
-        tokio::fs::write(&temp_path, &data).await?;
-        self.upload(&temp_path, path).await?;
-        let _ = tokio::fs::remove_file(temp_path).await;
-        Ok(())
+    async fn write_bytes(&self, path: &str, data: Vec<u8>) -> FsResult<()> {
+        self.fs.write_file(&self.namespace, &self.pvc, path, &data).await
     }
 
     fn backend_type(&self) -> crate::fs::backend::BackendType {
@@ -2,14 +2,19 @@ use anyhow::{Context, Result};
 use async_trait::async_trait;
 use opendal::{services::S3, Operator};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use crate::fs::backend::{BackendType, S3Provider, StorageBackend};
-use crate::fs::types::FileEntry;
+use crate::fs::backend::{BackendType, DeleteProgress, S3Provider, StorageBackend, WRITE_CHUNK_SIZE};
+use crate::fs::credentials::{CloudCredentials, CloudProvider, CredentialField};
+use crate::fs::error::FsResult;
+use crate::fs::timeout::{self, NetworkTimeout};
+use crate::fs::types::{FileEntry, ObjectMeta};
 
 /// S3 and S3-compatible storage backend using OpenDAL
 pub struct S3Fs {
     operator: Operator,
     bucket: String,
+    timeout: NetworkTimeout,
     region: String,
     provider: S3Provider,
 }
@@ -42,6 +47,7 @@ impl S3Fs {
             bucket: bucket.to_string(),
             region: region.to_string(),
             provider: S3Provider::Aws,
+            timeout: NetworkTimeout::from_env(),
         })
     }
 
@@ -72,9 +78,63 @@ impl S3Fs {
             bucket: bucket.to_string(),
             region: region.to_string(),
             provider,
+            timeout: NetworkTimeout::from_env(),
         })
     }
 
+    /// Limit each network request to `timeout` instead of the `ABYSS_NETWORK_TIMEOUT` setting
+    pub fn with_timeout(mut self, timeout: NetworkTimeout) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Create a backend for `provider` from resolved credentials (environment and/or profile).
+    /// AWS without keys falls back to the IAM credential chain.
+    pub fn from_credentials(provider: CloudProvider, credentials: &CloudCredentials) -> Result<Self> {
+        let bucket = credentials.require(provider, CredentialField::Bucket)?;
+        let keys = || -> Result<(&str, &str)> {
+            Ok((
+                credentials.require(provider, CredentialField::AccessKey)?,
+                credentials.require(provider, CredentialField::SecretKey)?,
+            ))
+        };
+
+        match provider {
+            CloudProvider::Aws => {
+                let region = credentials.region.as_deref().unwrap_or("us-east-1");
+                match (&credentials.access_key, &credentials.secret_key) {
+                    (Some(key), Some(secret)) => Self::new_aws(bucket, region, key, secret),
+                    _ => Self::new_with_iam(bucket, region),
+                }
+            }
+            CloudProvider::DigitalOcean => {
+                let region = credentials.require(provider, CredentialField::Region)?;
+                let (key, secret) = keys()?;
+                Self::new_digitalocean(bucket, region, key, secret)
+            }
+            CloudProvider::Hetzner => {
+                let region = credentials.require(provider, CredentialField::Region)?;
+                let (key, secret) = keys()?;
+                Self::new_hetzner(bucket, region, key, secret)
+            }
+            CloudProvider::CloudflareR2 => {
+                let account = credentials.require(provider, CredentialField::AccountId)?;
+                let (key, secret) = keys()?;
+                Self::new_cloudflare_r2(bucket, account, key, secret)
+            }
+            CloudProvider::MinIO => {
+                let (key, secret) = keys()?;
+                Self::new_minio(bucket, key, secret)
+            }
+            CloudProvider::Wasabi => {
+                let region = credentials.require(provider, CredentialField::Region)?;
+                let (key, secret) = keys()?;
+                Self::new_wasabi(bucket, region, key, secret)
+            }
+            CloudProvider::Gcs => anyhow::bail!("GCS is not an S3-compatible provider"),
+        }
+    }
+
     /// Create backend for DigitalOcean Spaces
     pub fn new_digitalocean(bucket: &str, region: &str, access_key: &str, secret_key: &str) -> Result<Self> {
         Self::new(bucket, region, access_key, secret_key, S3Provider::DigitalOcean)
@@ -124,7 +184,7 @@ impl S3Fs {
 
 #[async_trait]
 impl StorageBackend for S3Fs {
-    async fn list_dir(&self, path: &str) -> Result<Vec<FileEntry>> {
+    async fn list_dir(&self, path: &str) -> FsResult<Vec<FileEntry>> {
         let path = if path.is_empty() || path == "/" { "" } else { path.trim_start_matches('/') };
         let path = if !path.is_empty() && !path.ends_with('/') {
             format!("{}/", path)
@@ -132,7 +192,7 @@ impl StorageBackend for S3Fs {
             path.to_string()
         };
 
-        let entries = self.operator.list(&path).await
+        let entries = self.timeout.run("Listing S3 directory", self.operator.list(&path)).await?
             .context("Failed to list S3 directory")?;
 
         let mut result = Vec::new();
@@ -167,42 +227,43 @@ impl StorageBackend for S3Fs {
         Ok(result)
     }
 
-    async fn delete(&self, path: &str) -> Result<()> {
+    async fn delete(&self, path: &str) -> FsResult<()> {
+        self.delete_with_progress(path, &DeleteProgress::default()).await
+    }
+
+    async fn delete_with_progress(&self, path: &str, progress: &DeleteProgress) -> FsResult<()> {
         let path = path.trim_start_matches('/');
-        
-        // Check if it's a directory
-        let meta = self.operator.stat(path).await;
-        
-        if let Ok(meta) = meta {
-            if meta.mode().is_dir() {
-                // Recursively delete directory contents
-                self.operator.remove_all(path).await
-                    .context("Failed to delete S3 directory")?;
-            } else {
-                self.operator.delete(path).await
-                    .context("Failed to delete S3 object")?;
-            }
-        } else {
-            // Try deleting as-is
-            self.operator.delete(path).await
-                .context("Failed to delete S3 object")?;
+
+        if timeout::is_prefix(&self.operator, path, self.timeout).await? {
+            let path = &format!("{}/", path.trim_end_matches('/'));
+            // Recursively delete directory contents
+            timeout::remove_all(&self.operator, path, self.timeout, progress).await
+                .context("Failed to delete S3 directory")?;
+            return Ok(());
         }
 
+        // A single object, or nothing at all: try deleting it as-is
+        progress.check_cancelled()?;
+        progress.add_total(1);
+        self.timeout.run("S3 delete", self.operator.delete(path)).await?
+            .context("Failed to delete S3 object")?;
+        progress.add_deleted(1);
+
         Ok(())
     }
 
-    async fn create_dir(&self, path: &str) -> Result<()> {
+    async fn create_dir(&self, path: &str) -> FsResult<()> {
         let path = path.trim_start_matches('/');
         let path = if path.ends_with('/') { path.to_string() } else { format!("{}/", path) };
         
         // S3 doesn't have real directories, create a zero-byte object with trailing slash
-        self.operator.write(&path, Vec::<u8>::new()).await
+        self.timeout.run("Creating S3 directory", self.operator.write(&path, Vec::<u8>::new())).await?
             .context("Failed to create S3 directory marker")?;
 
         Ok(())
     }
 
-    async fn upload(&self, local_path: &Path, remote_path: &str) -> Result<()> {
+    async fn upload(&self, local_path: &Path, remote_path: &str) -> FsResult<()> {
         let remote_path = remote_path.trim_start_matches('/');
         
         if local_path.is_dir() {
@@ -212,14 +273,15 @@ impl StorageBackend for S3Fs {
             let content = tokio::fs::read(local_path).await
                 .context("Failed to read local file")?;
             
-            self.operator.write(remote_path, content).await
+            self.timeout.for_transfer(content.len() as u64)
+                .run("Upload to S3", self.operator.write(remote_path, content)).await?
                 .context("Failed to upload to S3")?;
         }
 
         Ok(())
     }
 
-    async fn download(&self, remote_path: &str, local_path: &Path) -> Result<()> {
+    async fn download(&self, remote_path: &str, local_path: &Path) -> FsResult<()> {
         let remote_path = remote_path.trim_start_matches('/');
         
         // Create parent directory if needed
@@ -227,41 +289,95 @@ impl StorageBackend for S3Fs {
             tokio::fs::create_dir_all(parent).await?;
         }
 
-        let content = self.operator.read(remote_path).await
+        let content = timeout::read_object(&self.operator, remote_path, self.timeout).await
             .context("Failed to download from S3")?;
         
-        tokio::fs::write(local_path, content.to_vec()).await
+        tokio::fs::write(local_path, content).await
             .context("Failed to write local file")?;
 
         Ok(())
     }
 
-    async fn read_bytes(&self, path: &str) -> Result<Vec<u8>> {
+    async fn read_bytes(&self, path: &str) -> FsResult<Vec<u8>> {
+        let path = path.trim_start_matches('/');
+        let content = timeout::read_object(&self.operator, path, self.timeout).await
+            .context("Failed to read from S3")?;
+        Ok(content)
+    }
+
+    async fn read_range(&self, path: &str, offset: u64, length: u64) -> FsResult<Vec<u8>> {
+        let path = path.trim_start_matches('/');
+        let content = timeout::read_object_range(&self.operator, path, offset, length, None, self.timeout).await
+            .context("Failed to read from S3")?;
+        Ok(content)
+    }
+
+    async fn read_range_sized(&self, path: &str, offset: u64, length: u64, size: u64) -> FsResult<Vec<u8>> {
         let path = path.trim_start_matches('/');
-        let content = self.operator.read(path).await
+        let content = timeout::read_object_range(&self.operator, path, offset, length, Some(size), self.timeout).await
             .context("Failed to read from S3")?;
-        Ok(content.to_vec())
+        Ok(content)
     }
 
-    async fn write_bytes(&self, path: &str, data: Vec<u8>) -> Result<()> {
+    async fn write_bytes(&self, path: &str, data: Vec<u8>) -> FsResult<()> {
         let path = path.trim_start_matches('/');
-        self.operator.write(path, data).await
+        self.timeout.for_transfer(data.len() as u64)
+            .run("Write to S3", self.operator.write(path, data)).await?
             .context("Failed to write to S3")?;
         Ok(())
     }
 
-    async fn is_dir(&self, path: &str) -> Result<bool> {
+    async fn write_bytes_with_progress(&self, path: &str, data: Vec<u8>, written: &AtomicU64) -> FsResult<()> {
+        let path = path.trim_start_matches('/');
+        let mut writer = self.timeout.run("Opening S3 writer", self.operator.writer(path)).await?
+            .context("Failed to open S3 writer")?;
+        for chunk in data.chunks(WRITE_CHUNK_SIZE) {
+            self.timeout.for_transfer(chunk.len() as u64)
+                .run("Write to S3", writer.write(chunk.to_vec())).await?
+                .context("Failed to write to S3")?;
+            written.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+        }
+        self.timeout.run("Finishing S3 upload", writer.close()).await?
+            .context("Failed to finish S3 upload")?;
+        Ok(())
+    }
+
+    async fn copy_within(&self, from: &str, to: &str) -> FsResult<()> {
+        // Server-side copy, the object data never leaves the bucket
+        let from = from.trim_start_matches('/');
+        let to = to.trim_start_matches('/');
+        // The copy runs server-side, but still takes longer for larger objects
+        let size = self.timeout.run("S3 stat", self.operator.stat(from)).await?
+            .map(|meta| meta.content_length())
+            .unwrap_or(0);
+        self.timeout.for_transfer(size)
+            .run("Copy within S3", self.operator.copy(from, to)).await?
+            .context("Failed to copy within S3")?;
+        Ok(())
+    }
+
+    async fn object_meta(&self, path: &str) -> FsResult<ObjectMeta> {
+        let path = path.trim_start_matches('/');
+        // Prefixes have no metadata of their own
+        if self.is_dir(path).await? {
+            return Ok(ObjectMeta { is_dir: true, ..ObjectMeta::default() });
+        }
+        // S3 leaves the class header out for STANDARD objects
+        timeout::object_meta(&self.operator, path, self.timeout, "x-amz-storage-class", Some("STANDARD")).await
+    }
+
+    async fn is_dir(&self, path: &str) -> FsResult<bool> {
         let path = path.trim_start_matches('/');
         if path.is_empty() {
             return Ok(true);
         }
         
-        match self.operator.stat(path).await {
+        match self.timeout.run("S3 stat", self.operator.stat(path)).await? {
             Ok(meta) => Ok(meta.mode().is_dir()),
             Err(_) => {
                 // Try with trailing slash
                 let dir_path = if path.ends_with('/') { path.to_string() } else { format!("{}/", path) };
-                match self.operator.stat(&dir_path).await {
+                match self.timeout.run("S3 stat", self.operator.stat(&dir_path)).await? {
                     Ok(meta) => Ok(meta.mode().is_dir()),
                     Err(_) => Ok(false),
                 }
@@ -296,7 +412,8 @@ impl S3Fs {
                     self.upload_dir(&path, &remote).await?;
                 } else {
                     let content = tokio::fs::read(&path).await?;
-                    self.operator.write(&remote, content).await?;
+                    self.timeout.for_transfer(content.len() as u64)
+                        .run("Upload to S3", self.operator.write(&remote, content)).await??;
                 }
             }
             
@@ -304,3 +421,212 @@ impl S3Fs {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::timeout::NetworkTimeout;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// Bucket endpoint holding `count` objects under `dir/`: recursive listings return
+    /// them all, HEADs find nothing (there is no `dir` object) and every DeleteObjects
+    /// request is answered and recorded by its number of keys, after `on_batch` ran
+    async fn mock_bucket(count: usize, on_batch: impl Fn() + Send + Sync + 'static) -> (String, Arc<Mutex<Vec<usize>>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&batches);
+        let on_batch = Arc::new(on_batch);
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let batches = Arc::clone(&recorded);
+                let on_batch = Arc::clone(&on_batch);
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 65536];
+                    let header_end = loop {
+                        if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                            break end + 4;
+                        }
+                        match socket.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => request.extend_from_slice(&buf[..n]),
+                        }
+                    };
+                    let head = String::from_utf8_lossy(&request[..header_end]).to_string();
+                    let length: usize = head
+                        .lines()
+                        .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().to_string()))
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(0);
+                    while request.len() < header_end + length {
+                        match socket.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => request.extend_from_slice(&buf[..n]),
+                        }
+                    }
+                    let body = String::from_utf8_lossy(&request[header_end..]).to_string();
+                    let request_line = head.lines().next().unwrap_or_default().to_string();
+
+                    let (status, xml) = if request_line.starts_with("HEAD") {
+                        ("404 Not Found", String::new())
+                    } else if request_line.starts_with("GET") && request_line.contains("list-type=2") {
+                        let contents: String = if request_line.contains("prefix=dir") {
+                            (0..count)
+                                .map(|i| {
+                                    format!(
+                                        "<Contents><Key>dir/file{}</Key><Size>1</Size>\
+                                         <LastModified>2024-10-15T10:00:00.000Z</LastModified></Contents>",
+                                        i
+                                    )
+                                })
+                                .collect()
+                        } else {
+                            String::new()
+                        };
+                        ("200 OK", format!("<ListBucketResult><IsTruncated>false</IsTruncated>{}</ListBucketResult>", contents))
+                    } else if request_line.starts_with("POST") && request_line.contains("delete") {
+                        let keys: Vec<&str> = body
+                            .split("<Key>")
+                            .skip(1)
+                            .filter_map(|rest| rest.split("</Key>").next())
+                            .collect();
+                        on_batch();
+                        batches.lock().unwrap().push(keys.len());
+                        let deleted: String = keys.iter().map(|key| format!("<Deleted><Key>{}</Key></Deleted>", key)).collect();
+                        ("200 OK", format!("<DeleteResult>{}</DeleteResult>", deleted))
+                    } else if request_line.starts_with("DELETE") {
+                        ("204 No Content", String::new())
+                    } else {
+                        ("400 Bad Request", String::new())
+                    };
+                    let response = format!(
+                        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        xml.len(),
+                        xml
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        (endpoint, batches)
+    }
+
+    fn mock_s3(endpoint: String) -> S3Fs {
+        let provider = S3Provider::Custom { name: "mock".to_string(), endpoint };
+        S3Fs::new("bucket", "us-east-1", "key", "secret", provider).unwrap()
+    }
+
+    /// Endpoint answering every request with `headers` and no body, like a HEAD of one object
+    async fn mock_object_endpoint(headers: &'static str) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                        match socket.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => request.extend_from_slice(&buf[..n]),
+                        }
+                    }
+                    let response = format!("HTTP/1.1 200 OK\r\n{}Connection: close\r\n\r\n", headers);
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        endpoint
+    }
+
+    #[tokio::test]
+    async fn test_object_meta_reads_etag_content_type_and_storage_class() {
+        let endpoint = mock_object_endpoint(
+            "Content-Length: 11\r\n\
+             Content-Type: text/plain\r\n\
+             ETag: \"5eb63bbbe01eeed093cb22bb8f5acdc3\"\r\n\
+             Last-Modified: Tue, 15 Oct 2024 10:00:00 GMT\r\n\
+             x-amz-storage-class: GLACIER\r\n",
+        )
+        .await;
+
+        let meta = mock_s3(endpoint).object_meta("/reports/hello.txt").await.unwrap();
+        assert!(!meta.is_dir);
+        assert_eq!(meta.size, 11);
+        assert_eq!(meta.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(meta.etag.as_deref(), Some("\"5eb63bbbe01eeed093cb22bb8f5acdc3\""));
+        assert_eq!(meta.etag_md5().as_deref(), Some("5eb63bbbe01eeed093cb22bb8f5acdc3"));
+        assert_eq!(meta.storage_class.as_deref(), Some("GLACIER"));
+        assert_eq!(meta.modified.map(|time| time.to_rfc3339()).as_deref(), Some("2024-10-15T10:00:00+00:00"));
+    }
+
+    #[tokio::test]
+    async fn test_object_meta_without_class_header_is_standard() {
+        let endpoint = mock_object_endpoint("Content-Length: 3\r\nETag: \"abc-2\"\r\n").await;
+
+        let meta = mock_s3(endpoint).object_meta("data.bin").await.unwrap();
+        assert_eq!(meta.storage_class.as_deref(), Some("STANDARD"));
+        assert_eq!(meta.etag_md5(), None);
+        assert_eq!(meta.content_type, None);
+    }
+
+    #[tokio::test]
+    async fn test_object_meta_of_a_dotfile() {
+        let endpoint = mock_object_endpoint("Content-Length: 42\r\nContent-Type: text/plain\r\n").await;
+
+        let meta = mock_s3(endpoint).object_meta("/config/.env").await.unwrap();
+        assert!(!meta.is_dir);
+        assert_eq!(meta.size, 42);
+        assert_eq!(meta.content_type.as_deref(), Some("text/plain"));
+    }
+
+    #[tokio::test]
+    async fn test_unresponsive_endpoint_times_out() {
+        // Accepts connections and never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+            }
+        });
+
+        let backend = mock_s3(endpoint).with_timeout(NetworkTimeout::new(Some(Duration::from_millis(200))));
+
+        let started = std::time::Instant::now();
+        let err = backend.list_dir("/").await.unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(err.is_retryable(), "{:?}", err);
+        assert!(err.message().contains("timed out"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_directory_delete_sends_batches_of_at_most_1000() {
+        let (endpoint, batches) = mock_bucket(2500, || {}).await;
+        let progress = DeleteProgress::default();
+        mock_s3(endpoint).delete_with_progress("/dir", &progress).await.unwrap();
+
+        assert_eq!(*batches.lock().unwrap(), vec![1000, 1000, 500]);
+        assert_eq!((progress.total(), progress.deleted()), (2500, 2500));
+    }
+
+    #[tokio::test]
+    async fn test_directory_delete_stops_after_the_batch_in_flight_when_cancelled() {
+        let progress = Arc::new(DeleteProgress::default());
+        let cancel = Arc::clone(&progress);
+        let (endpoint, batches) = mock_bucket(2500, move || cancel.cancel()).await;
+        let err = mock_s3(endpoint).delete_with_progress("dir", &progress).await.unwrap_err();
+
+        assert!(err.message().contains("cancelled"), "{}", err);
+        assert_eq!(*batches.lock().unwrap(), vec![1000]);
+        assert_eq!(progress.deleted(), 1000);
+    }
+}
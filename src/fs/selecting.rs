@@ -1,9 +1,9 @@
-use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use std::path::Path;
 
 use crate::fs::types::FileEntry;
 use crate::fs::backend::{StorageBackend, BackendType, BackendCapabilities};
+use crate::fs::error::{FsError, FsResult};
 
 /// Backend for storage selection menu
 /// Presents storage options as "files" in a directory
@@ -11,7 +11,7 @@ pub struct SelectingBackend;
 
 #[async_trait]
 impl StorageBackend for SelectingBackend {
-    async fn list_dir(&self, _path: &str) -> Result<Vec<FileEntry>> {
+    async fn list_dir(&self, _path: &str) -> FsResult<Vec<FileEntry>> {
         Ok(vec![
             FileEntry {
                 name: "📁 Local Filesystem".to_string(),
@@ -44,32 +44,36 @@ impl StorageBackend for SelectingBackend {
         ])
     }
     
-    async fn delete(&self, _path: &str) -> Result<()> {
-        Err(anyhow!("Cannot modify selection menu"))
+    async fn delete(&self, _path: &str) -> FsResult<()> {
+        Err(FsError::Unsupported("Cannot modify selection menu".to_string()))
     }
     
-    async fn create_dir(&self, _path: &str) -> Result<()> {
-        Err(anyhow!("Cannot modify selection menu"))
+    async fn create_dir(&self, _path: &str) -> FsResult<()> {
+        Err(FsError::Unsupported("Cannot modify selection menu".to_string()))
     }
     
-    async fn is_dir(&self, _path: &str) -> Result<bool> {
+    async fn is_dir(&self, _path: &str) -> FsResult<bool> {
         Ok(true) // Treat root as directory
     }
     
-    async fn upload(&self, _local_path: &Path, _remote_path: &str) -> Result<()> {
-        Err(anyhow!("Cannot upload to selection menu"))
+    async fn upload(&self, _local_path: &Path, _remote_path: &str) -> FsResult<()> {
+        Err(FsError::Unsupported("Cannot upload to selection menu".to_string()))
     }
     
-    async fn download(&self, _remote_path: &str, _local_path: &Path) -> Result<()> {
-        Err(anyhow!("Cannot download from selection menu"))
+    async fn download(&self, _remote_path: &str, _local_path: &Path) -> FsResult<()> {
+        Err(FsError::Unsupported("Cannot download from selection menu".to_string()))
     }
     
-    async fn read_bytes(&self, _path: &str) -> Result<Vec<u8>> {
-        Err(anyhow!("Cannot read from selection menu"))
+    async fn read_bytes(&self, _path: &str) -> FsResult<Vec<u8>> {
+        Err(FsError::Unsupported("Cannot read from selection menu".to_string()))
     }
     
-    async fn write_bytes(&self, _path: &str, _data: Vec<u8>) -> Result<()> {
-        Err(anyhow!("Cannot write to selection menu"))
+    async fn write_bytes(&self, _path: &str, _data: Vec<u8>) -> FsResult<()> {
+        Err(FsError::Unsupported("Cannot write to selection menu".to_string()))
+    }
+
+    async fn create_file(&self, _path: &str) -> FsResult<()> {
+        Err(FsError::Unsupported("Cannot create files in selection menu".to_string()))
     }
     
     fn backend_type(&self) -> BackendType {
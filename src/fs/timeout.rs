@@ -0,0 +1,324 @@
+//! Time limits for cloud and Kubernetes network calls
+//!
+//! The event loop awaits backend calls, so without a limit a dead connection would
+//! freeze the TUI. Each request gets `ABYSS_NETWORK_TIMEOUT` seconds (default 30, `0`
+//! disables); transfers get extra time for their payload.
+
+use futures::{Stream, TryStreamExt};
+use opendal::Operator;
+use std::future::{Future, IntoFuture};
+use std::time::Duration;
+
+use crate::fs::backend::DeleteProgress;
+use crate::fs::error::{FsError, FsResult};
+use crate::fs::types::ObjectMeta;
+
+/// Environment variable overriding the per-request limit, in seconds (`0` = no limit)
+pub const NETWORK_TIMEOUT_ENV: &str = "ABYSS_NETWORK_TIMEOUT";
+
+/// Limit for one network request when not configured
+pub const DEFAULT_NETWORK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Objects removed per delete request (the most one S3 `DeleteObjects` call accepts)
+pub const DELETE_BATCH_SIZE: usize = 1000;
+
+/// Pessimistic throughput transfers are allowed to take on top of the base limit
+pub const MIN_TRANSFER_RATE: u64 = 256 * 1024;
+
+/// Per-request limit for a backend's network calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetworkTimeout {
+    limit: Option<Duration>,
+}
+
+impl Default for NetworkTimeout {
+    fn default() -> Self {
+        Self::new(Some(DEFAULT_NETWORK_TIMEOUT))
+    }
+}
+
+impl NetworkTimeout {
+    /// `None` waits forever
+    pub fn new(limit: Option<Duration>) -> Self {
+        Self { limit }
+    }
+
+    /// Limit from `ABYSS_NETWORK_TIMEOUT`, falling back to the default when unset or invalid
+    pub fn from_env() -> Self {
+        Self::parse(std::env::var(NETWORK_TIMEOUT_ENV).ok().as_deref())
+    }
+
+    fn parse(value: Option<&str>) -> Self {
+        match value.map(|v| v.trim().parse::<u64>()) {
+            Some(Ok(0)) => Self::new(None),
+            Some(Ok(secs)) => Self::new(Some(Duration::from_secs(secs))),
+            _ => Self::default(),
+        }
+    }
+
+    pub fn limit(&self) -> Option<Duration> {
+        self.limit
+    }
+
+    /// Limit for moving `bytes` in one request: the base limit plus `bytes` at `MIN_TRANSFER_RATE`
+    pub fn for_transfer(self, bytes: u64) -> Self {
+        Self::new(self.limit.map(|base| base + Duration::from_secs(bytes / MIN_TRANSFER_RATE)))
+    }
+
+    /// Await `fut`, failing with a retryable [`FsError::Network`] naming `operation` if it
+    /// takes longer than the limit
+    pub async fn run<F: Future>(self, operation: &str, fut: F) -> FsResult<F::Output> {
+        let Some(limit) = self.limit else {
+            return Ok(fut.await);
+        };
+        tokio::time::timeout(limit, fut).await.map_err(|_| {
+            FsError::Network(format!(
+                "{} timed out after {}s (set {} to change the limit)",
+                operation,
+                limit.as_secs(),
+                NETWORK_TIMEOUT_ENV
+            ))
+        })
+    }
+}
+
+/// Read a whole object, with a limit scaled to its size
+pub(crate) async fn read_object(operator: &Operator, path: &str, timeout: NetworkTimeout) -> FsResult<Vec<u8>> {
+    let size = timeout.run("Stat", operator.stat(path)).await??.content_length();
+    let content = timeout
+        .for_transfer(size)
+        .run("Download", operator.read(path))
+        .await??;
+    Ok(content.to_vec())
+}
+
+/// Read up to `length` bytes of an object from `offset`, with a limit scaled to the range
+///
+/// The range is clipped to the object's size first, since a ranged GET starting past the
+/// end is an error rather than an empty body. Without a known `size` the object is stat'ed.
+pub(crate) async fn read_object_range(
+    operator: &Operator,
+    path: &str,
+    offset: u64,
+    length: u64,
+    size: Option<u64>,
+    timeout: NetworkTimeout,
+) -> FsResult<Vec<u8>> {
+    let size = match size {
+        Some(size) => size,
+        None => timeout.run("Stat", operator.stat(path)).await??.content_length(),
+    };
+    let end = offset.saturating_add(length).min(size);
+    if offset >= end {
+        return Ok(Vec::new());
+    }
+    let content = timeout
+        .for_transfer(end - offset)
+        .run("Download", operator.read_with(path).range(offset..end).into_future())
+        .await??;
+    Ok(content.to_vec())
+}
+
+/// Metadata of one object for the details popup
+///
+/// `stat` doesn't expose the storage class, so it is read from the `class_header` of a
+/// presigned HEAD. `default_class` is what a response without that header means (S3 leaves
+/// it out for `STANDARD`); when the service can't presign, the class stays unknown.
+pub(crate) async fn object_meta(
+    operator: &Operator,
+    path: &str,
+    timeout: NetworkTimeout,
+    class_header: &str,
+    default_class: Option<&str>,
+) -> FsResult<ObjectMeta> {
+    let meta = timeout.run("Stat", operator.stat(path)).await??;
+    let mut object = ObjectMeta {
+        size: meta.content_length(),
+        is_dir: meta.is_dir(),
+        modified: meta
+            .last_modified()
+            .map(|time| chrono::DateTime::<chrono::Utc>::from(std::time::SystemTime::from(time))),
+        permissions: None,
+        content_type: meta.content_type().map(str::to_string),
+        etag: meta.etag().map(str::to_string),
+        storage_class: None,
+        content_md5: meta.content_md5().map(str::to_string),
+    };
+    if !object.is_dir {
+        object.storage_class = storage_class(operator, path, timeout, class_header)
+            .await
+            .map(|class| class.or(default_class.map(str::to_string)))
+            .unwrap_or_default();
+    }
+    Ok(object)
+}
+
+/// Value of `class_header` on a presigned HEAD of `path`; `None` if the request couldn't be made
+async fn storage_class(operator: &Operator, path: &str, timeout: NetworkTimeout, class_header: &str) -> Option<Option<String>> {
+    let limit = timeout.limit().unwrap_or(DEFAULT_NETWORK_TIMEOUT);
+    let presigned = operator.presign_stat(path, limit).await.ok()?;
+    let client = opendal::raw::HttpClient::new().ok()?;
+    let response = timeout.run("Stat", client.send(presigned.into())).await.ok()?.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    Some(
+        response
+            .headers()
+            .get(class_header)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+    )
+}
+
+/// Whether `path` is a directory, i.e. a prefix with objects under it
+///
+/// Pane entries name directories without the trailing slash, and a HEAD of that name
+/// finds nothing, so the prefix is checked instead.
+pub(crate) async fn is_prefix(operator: &Operator, path: &str, timeout: NetworkTimeout) -> FsResult<bool> {
+    let prefix = format!("{}/", path.trim_end_matches('/'));
+    match timeout.run("Stat", operator.stat(&prefix)).await? {
+        Ok(meta) => Ok(meta.mode().is_dir()),
+        Err(e) if e.kind() == opendal::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Remove a prefix and everything under it, each list page and delete batch under the limit
+///
+/// Mirrors `Operator::remove_all`, which can't be bounded as a whole: its duration grows
+/// with the number of objects. Objects are removed `DELETE_BATCH_SIZE` at a time while the
+/// listing goes on, so only one batch of keys is held and the total grows page by page.
+pub(crate) async fn remove_all(
+    operator: &Operator,
+    path: &str,
+    timeout: NetworkTimeout,
+    progress: &DeleteProgress,
+) -> FsResult<()> {
+    let lister = timeout
+        .run("List", operator.lister_with(path).recursive(true).into_future())
+        .await??;
+    let paths = futures::stream::try_unfold(lister, move |mut lister| async move {
+        let entry = timeout.run("List", lister.try_next()).await??;
+        FsResult::Ok(entry.map(|entry| (entry.path().to_string(), lister)))
+    });
+
+    delete_in_batches(paths, progress, |batch| async move {
+        // The deleter sends the whole batch in as few requests as the service allows
+        let mut deleter = timeout.run("Delete", operator.deleter()).await??;
+        for path in batch {
+            timeout.run("Delete", deleter.delete(path)).await??;
+        }
+        timeout.run("Delete", deleter.close()).await??;
+        Ok(())
+    })
+    .await
+}
+
+/// Hand `paths` to `delete_batch` in chunks of `DELETE_BATCH_SIZE` as they arrive,
+/// counting each listed path and finished chunk and stopping once `progress` is cancelled
+async fn delete_in_batches<S, F, Fut>(paths: S, progress: &DeleteProgress, mut delete_batch: F) -> FsResult<()>
+where
+    S: Stream<Item = FsResult<String>>,
+    F: FnMut(Vec<String>) -> Fut,
+    Fut: Future<Output = FsResult<()>>,
+{
+    futures::pin_mut!(paths);
+    let mut batch = Vec::with_capacity(DELETE_BATCH_SIZE);
+    loop {
+        let next = paths.try_next().await?;
+        let listed = next.is_none();
+        if let Some(path) = next {
+            progress.check_cancelled()?;
+            progress.add_total(1);
+            batch.push(path);
+        }
+        if batch.len() == DELETE_BATCH_SIZE || (listed && !batch.is_empty()) {
+            progress.check_cancelled()?;
+            let count = batch.len() as u64;
+            delete_batch(std::mem::take(&mut batch)).await?;
+            progress.add_deleted(count);
+        }
+        if listed {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_limit() {
+        assert_eq!(NetworkTimeout::parse(None), NetworkTimeout::default());
+        assert_eq!(NetworkTimeout::parse(Some("5")).limit(), Some(Duration::from_secs(5)));
+        assert_eq!(NetworkTimeout::parse(Some("0")).limit(), None);
+        assert_eq!(NetworkTimeout::parse(Some("soon")), NetworkTimeout::default());
+
+        let transfer = NetworkTimeout::parse(Some("5")).for_transfer(10 * MIN_TRANSFER_RATE);
+        assert_eq!(transfer.limit(), Some(Duration::from_secs(15)));
+    }
+
+    fn keys(count: usize) -> impl Stream<Item = FsResult<String>> {
+        futures::stream::iter((0..count).map(|i| Ok(format!("dir/file{}", i))))
+    }
+
+    #[tokio::test]
+    async fn test_delete_in_batches_splits_into_requests_of_at_most_1000() {
+        let progress = DeleteProgress::default();
+        let mut batches = Vec::new();
+        delete_in_batches(keys(2500), &progress, |batch| {
+            batches.push(batch);
+            async { Ok(()) }
+        })
+        .await
+        .unwrap();
+
+        let sizes: Vec<usize> = batches.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![1000, 1000, 500]);
+        assert_eq!(batches[2].last().map(String::as_str), Some("dir/file2499"));
+        assert_eq!(progress.deleted(), 2500);
+        assert_eq!(progress.total(), 2500);
+    }
+
+    #[tokio::test]
+    async fn test_delete_in_batches_stops_when_cancelled() {
+        let progress = DeleteProgress::default();
+        let mut requests = 0;
+        let err = delete_in_batches(keys(2500), &progress, |_| {
+            requests += 1;
+            // Cancelled while the first request is in flight
+            progress.cancel();
+            async { Ok(()) }
+        })
+        .await
+        .unwrap_err();
+
+        assert_eq!(requests, 1);
+        assert_eq!(progress.deleted(), 1000);
+        assert!(err.message().contains("cancelled"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_delete_in_batches_stops_at_failed_request() {
+        let progress = DeleteProgress::default();
+        let mut requests = 0;
+        let err = delete_in_batches(keys(2500), &progress, |_| {
+            requests += 1;
+            let failed = requests == 2;
+            async move {
+                if failed {
+                    return Err(FsError::Network("connection reset".to_string()));
+                }
+                Ok(())
+            }
+        })
+        .await
+        .unwrap_err();
+
+        assert_eq!(requests, 2);
+        assert_eq!(progress.deleted(), 1000);
+        assert!(err.is_retryable());
+    }
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Local, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -24,16 +24,203 @@ impl FileEntry {
             return "<DIR>".to_string();
         }
 
-        let size = self.size;
-        if size < 1024 {
-            format!("{} B", size)
-        } else if size < 1024 * 1024 {
-            format!("{:.1} KB", size as f64 / 1024.0)
-        } else if size < 1024 * 1024 * 1024 {
-            format!("{:.1} MB", size as f64 / (1024.0 * 1024.0))
-        } else {
-            format!("{:.1} GB", size as f64 / (1024.0 * 1024.0 * 1024.0))
+        format_bytes(self.size)
+    }
+}
+
+/// Format a byte count for the pane size column (B/KB/MB/GB)
+pub fn format_bytes(size: u64) -> String {
+    if size < 1024 {
+        format!("{} B", size)
+    } else if size < 1024 * 1024 {
+        format!("{:.1} KB", size as f64 / 1024.0)
+    } else if size < 1024 * 1024 * 1024 {
+        format!("{:.1} MB", size as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{:.1} GB", size as f64 / (1024.0 * 1024.0 * 1024.0))
+    }
+}
+
+/// Environment variable choosing how modification times are shown (`relative` or `absolute`)
+pub const TIME_FORMAT_ENV: &str = "ABYSS_TIME_FORMAT";
+
+/// How modification times are shown in panes and lists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// Age, e.g. `3d ago`
+    #[default]
+    Relative,
+    /// Date and time in the local timezone, e.g. `2024-05-01 14:03`
+    Absolute,
+}
+
+impl TimeFormat {
+    /// Format from `ABYSS_TIME_FORMAT`, relative when unset or unrecognized
+    pub fn from_env() -> Self {
+        match std::env::var(TIME_FORMAT_ENV).map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            Ok("absolute") | Ok("abs") | Ok("date") => TimeFormat::Absolute,
+            _ => TimeFormat::Relative,
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            TimeFormat::Relative => TimeFormat::Absolute,
+            TimeFormat::Absolute => TimeFormat::Relative,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TimeFormat::Relative => "relative",
+            TimeFormat::Absolute => "absolute",
+        }
+    }
+}
+
+/// Show a modification time as of `now`; unknown times (e.g. some remote listings) show `-`
+pub fn format_timestamp(modified: Option<DateTime<Utc>>, format: TimeFormat, now: DateTime<Utc>) -> String {
+    let Some(modified) = modified else {
+        return "-".to_string();
+    };
+    match format {
+        TimeFormat::Absolute => modified.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string(),
+        TimeFormat::Relative => {
+            let Ok(elapsed) = (now - modified).to_std() else {
+                return "future".to_string();
+            };
+            let secs = elapsed.as_secs();
+            if secs < 60 {
+                format!("{}s ago", secs)
+            } else if secs < 3600 {
+                format!("{}m ago", secs / 60)
+            } else if secs < 86400 {
+                format!("{}h ago", secs / 3600)
+            } else {
+                format!("{}d ago", secs / 86400)
+            }
+        }
+    }
+}
+
+/// Free and total space of the filesystem or volume holding a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpaceInfo {
+    /// Bytes available to unprivileged writers
+    pub available: u64,
+    pub total: u64,
+}
+
+impl SpaceInfo {
+    /// Parse the data line of `df -Pk` (`Filesystem 1024-blocks Used Available Capacity Mounted`)
+    pub fn from_df_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 6 {
+            return None;
+        }
+        // The filesystem name may contain spaces, so count from the end
+        let n = fields.len();
+        let total: u64 = fields[n - 5].parse().ok()?;
+        let available: u64 = fields[n - 3].parse().ok()?;
+        Some(Self { available: available * 1024, total: total * 1024 })
+    }
+
+    /// Whether less than a tenth of the space is left
+    pub fn is_low(&self) -> bool {
+        self.total > 0 && self.available < self.total / 10
+    }
+
+    /// Pane header label, e.g. `12.3 GB free of 500.0 GB`
+    pub fn label(&self) -> String {
+        format!("{} free of {}", format_bytes(self.available), format_bytes(self.total))
+    }
+}
+
+/// Sort a listing the way panes show it: directories first, then by name ignoring case
+pub fn sort_entries(entries: &mut [FileEntry]) {
+    entries.sort_by_cached_key(|e| (!e.is_dir, e.name.to_lowercase()));
+}
+
+/// Entries still being read after the first page of a listing
+pub type PendingEntries = crossbeam_channel::Receiver<Result<Vec<FileEntry>, String>>;
+
+/// A directory listing that may arrive in two parts: a first page to show right away and
+/// the remaining entries, read in the background
+pub struct DirListing {
+    pub entries: Vec<FileEntry>,
+    pub rest: Option<PendingEntries>,
+}
+
+impl DirListing {
+    /// A listing read in full
+    pub fn complete(entries: Vec<FileEntry>) -> Self {
+        Self { entries, rest: None }
+    }
+}
+
+/// What a backend knows about one file or object, for the details popup. Object stores
+/// fill in the service's own fields; elsewhere they stay `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObjectMeta {
+    pub size: u64,
+    pub is_dir: bool,
+    pub modified: Option<DateTime<Utc>>,
+    pub permissions: Option<String>,
+    pub content_type: Option<String>,
+    /// As the service returns it, quotes included
+    pub etag: Option<String>,
+    /// e.g. `STANDARD`, `GLACIER` (S3) or `NEARLINE` (GCS)
+    pub storage_class: Option<String>,
+    /// Base64 MD5 the service stored with the object (GCS)
+    pub content_md5: Option<String>,
+}
+
+impl From<crate::fs::backend::FileStat> for ObjectMeta {
+    fn from(stat: crate::fs::backend::FileStat) -> Self {
+        Self {
+            size: stat.size,
+            is_dir: stat.is_dir,
+            modified: stat.modified,
+            permissions: stat.permissions,
+            ..Self::default()
+        }
+    }
+}
+
+impl ObjectMeta {
+    /// The MD5 of the content in hex when the ETag is one, as it is for objects uploaded
+    /// in a single part (a multipart ETag ends in `-<parts>` and isn't)
+    pub fn etag_md5(&self) -> Option<String> {
+        let etag = self.etag.as_deref()?.trim_matches('"');
+        (etag.len() == 32 && etag.bytes().all(|b| b.is_ascii_hexdigit())).then(|| etag.to_ascii_lowercase())
+    }
+
+    /// Label and value of every known field, in display order
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        let mut rows = vec![
+            ("Type", if self.is_dir { "Directory" } else { "File" }.to_string()),
+            ("Size", format!("{} ({} bytes)", format_bytes(self.size), self.size)),
+        ];
+        if let Some(modified) = self.modified {
+            rows.push(("Modified", modified.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S %:z").to_string()));
+        }
+        if let Some(ref permissions) = self.permissions {
+            rows.push(("Permissions", permissions.clone()));
         }
+        if let Some(ref content_type) = self.content_type {
+            rows.push(("Content type", content_type.clone()));
+        }
+        if let Some(ref etag) = self.etag {
+            let kind = if self.etag_md5().is_some() { "MD5 of the content" } else { "multipart, not an MD5" };
+            rows.push(("ETag", format!("{} ({})", etag, kind)));
+        }
+        if let Some(ref md5) = self.content_md5 {
+            rows.push(("MD5", md5.clone()));
+        }
+        if let Some(ref class) = self.storage_class {
+            rows.push(("Storage class", class.clone()));
+        }
+        rows
     }
 }
 
@@ -72,3 +259,74 @@ pub struct StorageInfo {
     pub status: String,
     pub is_pv: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn test_object_meta_rows_and_etag_md5() {
+        let plain = ObjectMeta { size: 2048, permissions: Some("rw-r--r--".to_string()), ..ObjectMeta::default() };
+        assert_eq!(
+            plain.rows(),
+            vec![
+                ("Type", "File".to_string()),
+                ("Size", "2.0 KB (2048 bytes)".to_string()),
+                ("Permissions", "rw-r--r--".to_string()),
+            ]
+        );
+
+        let object = ObjectMeta {
+            size: 11,
+            content_type: Some("text/plain".to_string()),
+            etag: Some("\"5EB63BBBE01EEED093CB22BB8F5ACDC3\"".to_string()),
+            storage_class: Some("GLACIER".to_string()),
+            ..ObjectMeta::default()
+        };
+        assert_eq!(object.etag_md5().as_deref(), Some("5eb63bbbe01eeed093cb22bb8f5acdc3"));
+        let rows = object.rows();
+        assert_eq!(rows[2], ("Content type", "text/plain".to_string()));
+        assert_eq!(rows[3].1, "\"5EB63BBBE01EEED093CB22BB8F5ACDC3\" (MD5 of the content)");
+        assert_eq!(rows[4], ("Storage class", "GLACIER".to_string()));
+
+        let multipart = ObjectMeta { etag: Some("\"d41d8cd98f00b204e9800998ecf8427e-3\"".to_string()), ..ObjectMeta::default() };
+        assert_eq!(multipart.etag_md5(), None);
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+        let relative = |age: Duration| format_timestamp(Some(now - age), TimeFormat::Relative, now);
+        assert_eq!(relative(Duration::seconds(5)), "5s ago");
+        assert_eq!(relative(Duration::minutes(90)), "1h ago");
+        assert_eq!(relative(Duration::days(3)), "3d ago");
+        assert_eq!(relative(Duration::seconds(-30)), "future");
+
+        // Absolute times are shown in the local timezone
+        let modified = Utc.with_ymd_and_hms(2024, 5, 1, 14, 3, 0).unwrap();
+        let expected = modified.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string();
+        assert_eq!(format_timestamp(Some(modified), TimeFormat::Absolute, now), expected);
+
+        for format in [TimeFormat::Relative, TimeFormat::Absolute] {
+            assert_eq!(format_timestamp(None, format, now), "-");
+        }
+        assert_eq!(TimeFormat::Relative.toggled(), TimeFormat::Absolute);
+    }
+
+    #[test]
+    fn test_space_info_from_df_line() {
+        let space = SpaceInfo::from_df_line("/dev/sdb1  10475520  9961472  514048  96% /data").unwrap();
+        assert_eq!(space.total, 10475520 * 1024);
+        assert_eq!(space.available, 514048 * 1024);
+        assert!(space.is_low());
+        assert_eq!(space.label(), "502.0 MB free of 10.0 GB");
+
+        // Filesystem names with spaces, header lines and garbage
+        let space = SpaceInfo::from_df_line("map auto_home 2048 1024 1024 50% /home").unwrap();
+        assert_eq!(space.available, 1024 * 1024);
+        assert!(!space.is_low());
+        assert!(SpaceInfo::from_df_line("Filesystem 1024-blocks Used Available Capacity Mounted on").is_none());
+        assert!(SpaceInfo::from_df_line("").is_none());
+    }
+}
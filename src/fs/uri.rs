@@ -0,0 +1,214 @@
+//! Location URIs for constructing storage backends from strings.
+//!
+//! Recognized forms:
+//! - `/path`, `./path`, `~/path` or `file:///path` - Local filesystem
+//! - `s3://bucket/prefix` - AWS S3
+//! - `gs://bucket/prefix` or `gcs://bucket/prefix` - Google Cloud Storage
+//! - `pvc://namespace/claim/path` - Kubernetes PVC
+//! - `pv://volume/path` - Kubernetes PV (helper pod in `ABYSS_PV_NAMESPACE`, default `default`)
+//!
+//! Cloud credentials come from the same environment variables and credentials
+//! profile (`ABYSS_PROFILE`) as the storage menu in the TUI; the bucket always
+//! comes from the URI. Kubernetes uses `ABYSS_KUBE_CONTEXT` if set.
+
+use anyhow::{bail, Result};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::fs::backend::StorageBackend;
+use crate::fs::credentials::{active_profile, resolve_credentials, CloudProvider};
+use crate::fs::gcs::GcsFs;
+use crate::fs::local::LocalBackend;
+use crate::fs::remote::{K8sBackend, RemoteFs};
+use crate::fs::s3::S3Fs;
+
+/// Mount point of PVCs/PVs inside the helper pod
+const K8S_MOUNT_PATH: &str = "/data";
+
+/// A parsed location, before any backend is constructed
+#[derive(Debug, Clone, PartialEq)]
+pub enum Location {
+    Local { path: PathBuf },
+    S3 { bucket: String, path: String },
+    Gcs { bucket: String, path: String },
+    Pvc { namespace: String, claim: String, path: String },
+    Pv { volume: String, path: String },
+}
+
+impl Location {
+    /// Parse a location string without touching the network or filesystem
+    pub fn parse(uri: &str) -> Result<Self> {
+        if let Some(rest) = uri.strip_prefix("s3://") {
+            let (bucket, path) = split_first(rest);
+            require("bucket", &bucket, uri)?;
+            return Ok(Location::S3 { bucket, path });
+        }
+
+        if let Some(rest) = uri.strip_prefix("gs://").or_else(|| uri.strip_prefix("gcs://")) {
+            let (bucket, path) = split_first(rest);
+            require("bucket", &bucket, uri)?;
+            return Ok(Location::Gcs { bucket, path });
+        }
+
+        if let Some(rest) = uri.strip_prefix("pvc://") {
+            let (namespace, rest) = split_first(rest);
+            let (claim, path) = split_first(&rest);
+            require("namespace", &namespace, uri)?;
+            require("claim", &claim, uri)?;
+            return Ok(Location::Pvc { namespace, claim, path });
+        }
+
+        if let Some(rest) = uri.strip_prefix("pv://") {
+            let (volume, path) = split_first(rest);
+            require("volume", &volume, uri)?;
+            return Ok(Location::Pv { volume, path });
+        }
+
+        if let Some(rest) = uri.strip_prefix("file://") {
+            return Ok(Location::Local { path: PathBuf::from(rest) });
+        }
+
+        if let Some((scheme, _)) = uri.split_once("://") {
+            bail!(
+                "Unsupported location scheme '{}://' (use a local path, s3://, gs://, pvc:// or pv://)",
+                scheme
+            );
+        }
+
+        let path = match uri.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir().unwrap_or_else(|| PathBuf::from(".")).join(rest),
+            None if uri == "~" => dirs::home_dir().unwrap_or_else(|| PathBuf::from(".")),
+            None => PathBuf::from(uri),
+        };
+        Ok(Location::Local { path })
+    }
+
+    /// Construct the backend for this location and the path to use with it
+    pub async fn connect(self) -> Result<(Arc<dyn StorageBackend>, String)> {
+        match self {
+            Location::Local { path } => {
+                let path = path.canonicalize().unwrap_or_else(|_| {
+                    std::env::current_dir().map(|cwd| cwd.join(&path)).unwrap_or(path)
+                });
+                Ok((
+                    Arc::new(LocalBackend::new(PathBuf::from("/"))),
+                    path.to_string_lossy().to_string(),
+                ))
+            }
+            Location::S3 { bucket, path } => {
+                let mut credentials = resolve_credentials(CloudProvider::Aws, active_profile().as_deref())?;
+                credentials.bucket = Some(bucket);
+                Ok((Arc::new(S3Fs::from_credentials(CloudProvider::Aws, &credentials)?), path))
+            }
+            Location::Gcs { bucket, path } => {
+                let mut credentials = resolve_credentials(CloudProvider::Gcs, active_profile().as_deref())?;
+                credentials.bucket = Some(bucket);
+                Ok((Arc::new(GcsFs::from_credentials(&credentials)?), path))
+            }
+            Location::Pvc { namespace, claim, path } => {
+                let fs = k8s_remote_fs().await?;
+                Ok((Arc::new(K8sBackend::new(namespace, claim, fs)), k8s_path(&path)))
+            }
+            Location::Pv { volume, path } => {
+                // PVs have no namespace; use the same one as the PV browser
+                let options = crate::k8s::K8sOptions::from_env();
+                let namespace = options.pv_namespace.unwrap_or_else(|| "default".to_string());
+                let fs = k8s_remote_fs().await?;
+                Ok((Arc::new(K8sBackend::new(namespace, volume, fs)), k8s_path(&path)))
+            }
+        }
+    }
+}
+
+/// Parse a location string and construct its backend.
+///
+/// Returns the backend together with the path to pass to it (bucket prefix,
+/// path inside the PVC mount, or absolute local path).
+pub async fn parse_uri(uri: &str) -> Result<(Arc<dyn StorageBackend>, String)> {
+    Location::parse(uri)?.connect().await
+}
+
+/// Split `head/rest` into the first segment and the remaining path (without surrounding slashes)
+fn split_first(s: &str) -> (String, String) {
+    let (head, rest) = s.split_once('/').unwrap_or((s, ""));
+    (head.to_string(), rest.trim_matches('/').to_string())
+}
+
+fn require(what: &str, value: &str, uri: &str) -> Result<()> {
+    if value.is_empty() {
+        bail!("Missing {} in location '{}'", what, uri);
+    }
+    Ok(())
+}
+
+fn k8s_path(path: &str) -> String {
+    if path.is_empty() {
+        K8S_MOUNT_PATH.to_string()
+    } else {
+        format!("{}/{}", K8S_MOUNT_PATH, path)
+    }
+}
+
+async fn k8s_remote_fs() -> Result<RemoteFs> {
+    let context = crate::k8s::K8sOptions::from_env().context;
+    let client = crate::k8s::K8sClient::with_context(context.as_deref()).await?;
+    Ok(RemoteFs::new(client.client()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cloud_uris() {
+        assert_eq!(
+            Location::parse("s3://bucket/a/b/").unwrap(),
+            Location::S3 { bucket: "bucket".into(), path: "a/b".into() }
+        );
+        assert_eq!(
+            Location::parse("gs://bucket").unwrap(),
+            Location::Gcs { bucket: "bucket".into(), path: "".into() }
+        );
+        assert_eq!(
+            Location::parse("gcs://bucket/x").unwrap(),
+            Location::Gcs { bucket: "bucket".into(), path: "x".into() }
+        );
+    }
+
+    #[test]
+    fn test_parse_k8s_uris() {
+        assert_eq!(
+            Location::parse("pvc://prod/data-pvc/backups/2024").unwrap(),
+            Location::Pvc { namespace: "prod".into(), claim: "data-pvc".into(), path: "backups/2024".into() }
+        );
+        assert_eq!(
+            Location::parse("pv://pv-0001").unwrap(),
+            Location::Pv { volume: "pv-0001".into(), path: "".into() }
+        );
+        assert!(Location::parse("pvc://prod").is_err());
+        assert_eq!(k8s_path(""), "/data");
+        assert_eq!(k8s_path("x/y"), "/data/x/y");
+    }
+
+    #[test]
+    fn test_parse_local_and_invalid() {
+        assert_eq!(
+            Location::parse("/tmp/x").unwrap(),
+            Location::Local { path: PathBuf::from("/tmp/x") }
+        );
+        assert_eq!(
+            Location::parse("file:///tmp/x").unwrap(),
+            Location::Local { path: PathBuf::from("/tmp/x") }
+        );
+        assert!(Location::parse("ftp://host/x").is_err());
+        assert!(Location::parse("s3://").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parse_uri_local() {
+        let dir = tempfile::tempdir().unwrap();
+        let (backend, path) = parse_uri(dir.path().to_str().unwrap()).await.unwrap();
+        assert!(backend.is_local());
+        assert!(backend.is_dir(&path).await.unwrap());
+    }
+}
@@ -1,7 +1,7 @@
 // Benchmark module
 // Measures hash algorithm performance
 
-use super::hash::HashRegistry;
+use super::hash::{HashKey, HashRegistry};
 use super::error::HashUtilityError;
 use std::time::{Duration, Instant};
 
@@ -59,8 +59,9 @@ impl BenchmarkEngine {
         test_data: &[u8],
         data_size_mb: usize,
     ) -> Result<BenchmarkResult, HashUtilityError> {
-        // Get hasher for this algorithm
-        let mut hasher = HashRegistry::get_hasher(algorithm)
+        // Get hasher for this algorithm; the keyed modes run with a throwaway key
+        let key = HashKey::new().with_key([0; 32]).with_context("abyss benchmark");
+        let mut hasher = HashRegistry::get_keyed_hasher(algorithm, &key)
             .map_err(|e| HashUtilityError::BenchmarkFailed {
                 algorithm: algorithm.to_string(),
                 reason: e.to_string(),
@@ -83,25 +84,32 @@ impl BenchmarkEngine {
     
     /// Display benchmark results in a formatted table
     pub fn display_results(&self, results: &[BenchmarkResult]) {
+        print!("{}", self.format_results(results));
+    }
+    
+    /// Format benchmark results as a plain text table
+    pub fn format_results(&self, results: &[BenchmarkResult]) -> String {
         if results.is_empty() {
-            println!("No benchmark results to display.");
-            return;
+            return "No benchmark results to display.\n".to_string();
         }
         
+        let mut output = String::new();
+        
         // Sort results by throughput (descending)
         let mut sorted_results = results.to_vec();
         sorted_results.sort_by(|a, b| b.throughput_mbps.partial_cmp(&a.throughput_mbps).unwrap());
         
         // Print header
-        println!("\n{:<20} {:>15}", "Algorithm", "Throughput (MB/s)");
-        println!("{}", "-".repeat(37));
+        output.push_str(&format!("\n{:<20} {:>15}\n", "Algorithm", "Throughput (MB/s)"));
+        output.push_str(&format!("{}\n", "-".repeat(37)));
         
         // Print results
         for result in sorted_results {
-            println!("{:<20} {:>15.2}", result.algorithm, result.throughput_mbps);
+            output.push_str(&format!("{:<20} {:>15.2}\n", result.algorithm, result.throughput_mbps));
         }
         
-        println!();
+        output.push('\n');
+        output
     }
 }
 
@@ -0,0 +1,92 @@
+// Quick-verify module
+// Checks a single file against a published hash (e.g. a SHA256 from a download page)
+
+use std::path::{Path, PathBuf};
+use super::hash::{HashComputer, HashRegistry};
+use super::database::{DatabaseHandler, UNKNOWN_ALGORITHM};
+use super::error::HashUtilityError;
+
+/// A hash to check against, with the algorithm it was made with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedHash {
+    pub algorithm: String,
+    /// Lowercase hex
+    pub hash: String,
+}
+
+/// Outcome of checking one file against an expected hash
+#[derive(Debug, Clone)]
+pub struct HashCheck {
+    pub path: PathBuf,
+    pub algorithm: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl HashCheck {
+    pub fn matches(&self) -> bool {
+        self.expected == self.actual
+    }
+}
+
+/// Parse an expected hash as it is usually published
+///
+/// Accepts a bare hex digest, `algorithm:digest`, `sha256sum` output (`digest  file`)
+/// and BSD-style `SHA256 (file) = digest`. Without an algorithm name it is detected
+/// from the digest length.
+pub fn parse_expected_hash(input: &str) -> Result<ExpectedHash, HashUtilityError> {
+    let input = input.trim();
+
+    let (algorithm, digest) = if let Some((head, digest)) = input.rsplit_once(" = ") {
+        // BSD style: ALGO (file) = digest
+        let algorithm = head.split(" (").next().unwrap_or(head).trim();
+        (Some(algorithm), digest.trim())
+    } else if let Some((prefix, digest)) = input
+        .split_once(':')
+        .filter(|(prefix, _)| HashRegistry::get_hasher(prefix.trim()).is_ok())
+    {
+        (Some(prefix.trim()), digest.trim())
+    } else {
+        (None, input.split_whitespace().next().unwrap_or(""))
+    };
+
+    if digest.is_empty() || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(HashUtilityError::InvalidArguments {
+            message: format!("'{}' is not a hex digest", digest),
+        });
+    }
+
+    let algorithm = match algorithm {
+        Some(name) => {
+            HashRegistry::get_hasher(name)?;
+            name.to_lowercase()
+        }
+        None => match DatabaseHandler::infer_algorithm_from_hash(digest) {
+            detected if detected == UNKNOWN_ALGORITHM => {
+                return Err(HashUtilityError::InvalidArguments {
+                    message: format!(
+                        "Cannot tell the algorithm of a {}-character digest; prefix it, e.g. blake3:<hash>",
+                        digest.len()
+                    ),
+                });
+            }
+            detected => detected,
+        },
+    };
+
+    Ok(ExpectedHash {
+        algorithm,
+        hash: digest.to_lowercase(),
+    })
+}
+
+/// Hash `path` with the expected hash's algorithm and compare
+pub fn check_file(path: &Path, expected: &ExpectedHash) -> Result<HashCheck, HashUtilityError> {
+    let result = HashComputer::new().compute_hash(path, &expected.algorithm)?;
+    Ok(HashCheck {
+        path: path.to_path_buf(),
+        algorithm: expected.algorithm.clone(),
+        expected: expected.hash.clone(),
+        actual: result.hash.to_lowercase(),
+    })
+}
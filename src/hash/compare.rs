@@ -5,6 +5,7 @@ use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use super::database::{DatabaseHandler, DatabaseEntry};
 use super::error::HashUtilityError;
+use super::path_utils;
 
 /// Result of comparing a single file between two databases
 #[derive(Debug, Clone, serde::Serialize)]
@@ -255,16 +256,34 @@ impl CompareReport {
 }
 
 /// Engine for comparing two hash databases
-pub struct CompareEngine;
+pub struct CompareEngine {
+    ignored_paths: Vec<PathBuf>,
+}
 
 impl CompareEngine {
     /// Create a new CompareEngine
     pub fn new() -> Self {
-        CompareEngine
+        CompareEngine { ignored_paths: Vec::new() }
+    }
+    
+    /// Leave entries for these files (or anything under these directories) out of the report
+    pub fn with_ignored_paths<I, P>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        self.ignored_paths
+            .extend(paths.into_iter().map(|p| path_utils::canonicalize_lenient(p.as_ref())));
+        self
     }
     
     /// Compare two hash databases and generate a detailed report
     /// 
+    /// Relative entries are resolved against the directory of the database holding them
+    /// (where `scan` writes it by default). Entries that resolve to either database file
+    /// or to an ignored path are skipped, so a database scanned into the tree of another
+    /// doesn't show up as an added or changed file.
+    /// 
     /// # Arguments
     /// * `database1` - Path to the first database file
     /// * `database2` - Path to the second database file
@@ -280,8 +299,15 @@ impl CompareEngine {
         database2: &Path,
     ) -> Result<CompareReport, HashUtilityError> {
         // Load both databases
-        let db1 = DatabaseHandler::read_database(database1)?;
-        let db2 = DatabaseHandler::read_database(database2)?;
+        let mut db1 = DatabaseHandler::read_database(database1)?;
+        let mut db2 = DatabaseHandler::read_database(database2)?;
+        
+        // Neither database is part of the data being compared
+        let mut ignored = self.ignored_paths.clone();
+        ignored.push(path_utils::canonicalize_lenient(database1));
+        ignored.push(path_utils::canonicalize_lenient(database2));
+        Self::remove_ignored(&mut db1, database1, &ignored);
+        Self::remove_ignored(&mut db2, database2, &ignored);
         
         // Detect duplicates in each database
         let duplicates_db1 = Self::find_duplicates(&db1);
@@ -347,6 +373,19 @@ impl CompareEngine {
         })
     }
     
+    /// Drop entries of the database at `database_path` that resolve to an ignored path
+    fn remove_ignored(database: &mut HashMap<PathBuf, DatabaseEntry>, database_path: &Path, ignored: &[PathBuf]) {
+        let base = path_utils::canonicalize_lenient(database_path)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        database.retain(|path, _| {
+            // Lexical only: canonicalizing every entry would cost a syscall per file
+            let resolved = path_utils::clean_path(&path_utils::resolve_path(path, &base));
+            !path_utils::is_ignored_path(&resolved, ignored)
+        });
+    }
+    
     /// Find duplicate hashes within a database
     /// 
     /// # Arguments
@@ -358,7 +397,8 @@ impl CompareEngine {
         // Build a map from hash to list of paths
         let mut hash_to_paths: HashMap<String, Vec<PathBuf>> = HashMap::new();
         
-        for (path, entry) in database {
+        // Directory entries all share the same sentinel hash, so they are never duplicates
+        for (path, entry) in database.iter().filter(|(_, entry)| !entry.is_directory()) {
             hash_to_paths
                 .entry(entry.hash.clone())
                 .or_insert_with(Vec::new)
@@ -5,18 +5,39 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::io::{self, BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
 use xz2::read::XzDecoder;
 use xz2::write::XzEncoder;
 
 use super::path_utils;
 use super::error::HashUtilityError;
 
+/// Hash written for directory entries (`hash scan --include-dirs`) in every format
+///
+/// Directories have no content to hash; the sentinel only records that the path
+/// existed as a directory, so verify can report an empty directory that went missing.
+pub const DIRECTORY_HASH: &str = "<dir>";
+
+/// Algorithm of entries that don't record one (a hashdeep file without header whose
+/// hash length matches no known algorithm)
+pub const UNKNOWN_ALGORITHM: &str = "unknown";
+
 /// Database entry with metadata
 #[derive(Debug, Clone)]
 pub struct DatabaseEntry {
     pub hash: String,
     pub algorithm: String,
     pub fast_mode: bool,
+    /// The algorithm was guessed from the hash length (hashdeep files without a header)
+    pub inferred: bool,
+}
+
+impl DatabaseEntry {
+    /// Whether this entry records a directory rather than a hashed file
+    pub fn is_directory(&self) -> bool {
+        self.hash == DIRECTORY_HASH
+    }
 }
 
 /// Database format type
@@ -26,6 +47,26 @@ pub enum DatabaseFormat {
     Standard,
     /// Hashdeep format: size,hash1,hash2,...,filename
     Hashdeep,
+    /// NDJSON format: one `{"path":...,"algorithm":...,"hash":...,"size":...}` object per line
+    Json,
+}
+
+/// One line of a JSON (NDJSON) database.
+///
+/// Paths that are not valid UTF-8 are stored as base64 of their raw bytes
+/// in `path_base64` instead of `path`.
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path_base64: Option<String>,
+    algorithm: String,
+    hash: String,
+    #[serde(default)]
+    size: u64,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    fast_mode: bool,
 }
 
 /// Handler for reading and writing hash database files
@@ -109,6 +150,11 @@ impl DatabaseHandler {
                 continue;
             }
             
+            // Check for JSON lines (checked first, objects contain commas)
+            if trimmed.starts_with('{') {
+                return Ok(DatabaseFormat::Json);
+            }
+            
             // Check for hashdeep header (starts with %)
             if trimmed.starts_with('%') {
                 return Ok(DatabaseFormat::Hashdeep);
@@ -170,6 +216,31 @@ impl DatabaseHandler {
         writeln!(writer, ",{}", path.display())
     }
     
+    /// Write a single entry in JSON (NDJSON) format, one object per line
+    pub fn write_json_entry(
+        writer: &mut impl Write,
+        hash: &str,
+        algorithm: &str,
+        fast_mode: bool,
+        size: u64,
+        path: &Path,
+    ) -> io::Result<()> {
+        let (path, path_base64) = match path.to_str() {
+            Some(p) => (Some(p.to_string()), None),
+            None => (None, Some(base64::engine::general_purpose::STANDARD.encode(path_bytes(path)))),
+        };
+        let entry = JsonEntry {
+            path,
+            path_base64,
+            algorithm: algorithm.to_string(),
+            hash: hash.to_string(),
+            size,
+            fast_mode,
+        };
+        serde_json::to_writer(&mut *writer, &entry)?;
+        writeln!(writer)
+    }
+    
     /// Read a hash database file and parse it into a HashMap
     /// Maps file paths to their database entries (hash, algorithm, fast_mode)
     /// Malformed lines are skipped with a warning to stderr
@@ -180,7 +251,67 @@ impl DatabaseHandler {
         match format {
             DatabaseFormat::Standard => Self::read_standard_database(path),
             DatabaseFormat::Hashdeep => Self::read_hashdeep_database(path),
+            DatabaseFormat::Json => Self::read_json_database(path),
+        }
+    }
+    
+    /// Read a JSON (NDJSON) format database file
+    fn read_json_database(path: &Path) -> Result<HashMap<PathBuf, DatabaseEntry>, HashUtilityError> {
+        let reader = Self::open_database_reader(path)?;
+        let mut database = HashMap::new();
+        
+        for (line_num, line_result) in reader.lines().enumerate() {
+            let line = line_result.map_err(|e| {
+                HashUtilityError::from_io_error(e, "reading database", Some(path.to_path_buf()))
+            })?;
+            
+            // Skip empty lines
+            if line.trim().is_empty() {
+                continue;
+            }
+            
+            match Self::parse_json_line(&line) {
+                Some((file_path, entry)) => {
+                    database.insert(file_path, entry);
+                }
+                None => {
+                    eprintln!(
+                        "Warning: Skipping malformed line {} in JSON database {}: {}",
+                        line_num + 1,
+                        path.display(),
+                        line
+                    );
+                }
+            }
+        }
+        
+        Ok(database)
+    }
+    
+    /// Parse a single JSON database line
+    /// Returns None if the line is not a valid entry
+    pub fn parse_json_line(line: &str) -> Option<(PathBuf, DatabaseEntry)> {
+        let entry: JsonEntry = serde_json::from_str(line.trim()).ok()?;
+        
+        let path = match (entry.path, entry.path_base64) {
+            (Some(p), _) if !p.is_empty() => path_utils::parse_database_path(&p),
+            (_, Some(encoded)) => {
+                let bytes = base64::engine::general_purpose::STANDARD.decode(encoded).ok()?;
+                path_from_bytes(bytes)?
+            }
+            _ => return None,
+        };
+        
+        if entry.hash.is_empty() || entry.algorithm.is_empty() {
+            return None;
         }
+        
+        Some((path, DatabaseEntry {
+            hash: entry.hash,
+            algorithm: entry.algorithm,
+            fast_mode: entry.fast_mode,
+            inferred: false,
+        }))
     }
     
     /// Read a standard format database file
@@ -205,6 +336,7 @@ impl DatabaseHandler {
                         hash,
                         algorithm,
                         fast_mode,
+                        inferred: false,
                     });
                 }
                 None => {
@@ -367,6 +499,7 @@ impl DatabaseHandler {
                             hash: hash.to_string(),
                             algorithm: algorithms[i].clone(),
                             fast_mode: false,
+                            inferred: false,
                         }
                     ));
                 }
@@ -382,6 +515,7 @@ impl DatabaseHandler {
                             hash: hash.to_string(),
                             algorithm,
                             fast_mode: false,
+                            inferred: true,
                         }
                     ));
                 }
@@ -396,6 +530,9 @@ impl DatabaseHandler {
     }
     
     /// Infer hash algorithm from hash string length
+    ///
+    /// Lengths shared by several algorithms resolve to the one most often published
+    /// (64 hex chars is SHA-256, not SHA3-256 or BLAKE3); name the algorithm to override.
     pub fn infer_algorithm_from_hash(hash: &str) -> String {
         match hash.len() {
             32 => "md5".to_string(),
@@ -404,10 +541,65 @@ impl DatabaseHandler {
             64 => "sha256".to_string(),
             96 => "sha384".to_string(),
             128 => "sha512".to_string(),
-            _ => "unknown".to_string(),
+            _ => UNKNOWN_ALGORITHM.to_string(),
+        }
+    }
+
+    /// The single algorithm the hashed files of a database were written with
+    ///
+    /// Directory entries are left out; `None` means the database has no hashed files.
+    /// Fails when entries use different algorithms or some don't record one.
+    pub fn database_algorithm(
+        database: &HashMap<PathBuf, DatabaseEntry>,
+        path: &Path,
+    ) -> Result<Option<String>, HashUtilityError> {
+        let mut algorithms: Vec<String> = database
+            .values()
+            .filter(|entry| !entry.is_directory())
+            .map(|entry| entry.algorithm.to_lowercase())
+            .collect();
+        algorithms.sort();
+        algorithms.dedup();
+
+        if algorithms.iter().any(|a| a == UNKNOWN_ALGORITHM) {
+            return Err(HashUtilityError::DatabaseAlgorithm {
+                path: path.to_path_buf(),
+                reason: "some entries don't record their algorithm".to_string(),
+            });
+        }
+        if algorithms.len() > 1 {
+            return Err(HashUtilityError::DatabaseAlgorithm {
+                path: path.to_path_buf(),
+                reason: format!("entries mix {}", algorithms.join(", ")),
+            });
         }
+        Ok(algorithms.pop())
     }
 }
 
+/// Raw bytes of a path (lossy on platforms without byte paths)
+#[cfg(unix)]
+fn path_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+fn path_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().into_owned().into_bytes()
+}
+
+/// Rebuild a path from raw bytes written by `path_bytes`
+#[cfg(unix)]
+fn path_from_bytes(bytes: Vec<u8>) -> Option<PathBuf> {
+    use std::os::unix::ffi::OsStringExt;
+    Some(PathBuf::from(std::ffi::OsString::from_vec(bytes)))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: Vec<u8>) -> Option<PathBuf> {
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
 // Tests moved to tests/hash/database_tests.rs
 
@@ -10,10 +10,10 @@ use super::error::HashUtilityError;
 use super::ignore_handler::IgnoreHandler;
 use rayon::prelude::*;
 use indicatif::{ProgressBar, ProgressStyle};
-use crossbeam_channel::bounded;
+use crossbeam_channel::unbounded;
 use jwalk::WalkDir;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
-use std::thread;
 
 /// Statistics collected during a dedup scan
 #[derive(Debug, Clone, serde::Serialize)]
@@ -21,6 +21,10 @@ pub struct DedupStats {
     pub files_scanned: usize,
     pub files_failed: usize,
     pub total_bytes: u64,
+    /// Same-size files whose first and last block were hashed
+    pub partial_hashed: usize,
+    /// Files hashed in full (those still matching after the partial pass)
+    pub full_hashed: usize,
     pub duplicate_groups: usize,
     pub duplicate_files: usize,
     pub wasted_space: u64,
@@ -28,10 +32,37 @@ pub struct DedupStats {
     pub duration: Duration,
 }
 
+/// Which pass of a dedup run a progress event comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DedupPhase {
+    /// Reading file sizes to group same-size files
+    Sizes,
+    /// Hashing the first and last block of same-size files
+    Partial,
+    /// Hashing the remaining candidates in full
+    Full,
+}
+
+impl DedupPhase {
+    pub fn label(self) -> &'static str {
+        match self {
+            DedupPhase::Sizes => "sizing",
+            DedupPhase::Partial => "sampling",
+            DedupPhase::Full => "hashing",
+        }
+    }
+}
+
 /// Progress information for dedup operations
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct DedupProgress {
+    pub phase: DedupPhase,
+    /// Files done in this phase, out of `total_files`
     pub files_scanned: usize,
+    pub total_files: usize,
+    /// Bytes read by the partial and full hashing passes so far
+    pub bytes_hashed: u64,
     pub current_file: String,
     pub duplicate_groups_found: usize,
 }
@@ -64,49 +95,59 @@ pub struct DuplicateGroupWithSize {
 impl DedupReport {
     /// Display the dedup report in plain text format
     pub fn display(&self) {
-        println!("\n=== Duplicate Files Report ===\n");
+        print!("{}", self.to_plain_text());
+    }
+    
+    /// Format the dedup report as plain text string
+    pub fn to_plain_text(&self) -> String {
+        let mut output = String::new();
+        
+        output.push_str("\n=== Duplicate Files Report ===\n\n");
         
         // Summary section
-        println!("Summary:");
-        println!("  Files scanned:     {}", self.stats.files_scanned);
-        println!("  Files failed:      {}", self.stats.files_failed);
-        println!("  Total bytes:       {} ({:.2} MB)", 
+        output.push_str("Summary:\n");
+        output.push_str(&format!("  Files scanned:     {}\n", self.stats.files_scanned));
+        output.push_str(&format!("  Files failed:      {}\n", self.stats.files_failed));
+        output.push_str(&format!("  Total bytes:       {} ({:.2} MB)\n", 
             self.stats.total_bytes, 
             self.stats.total_bytes as f64 / 1_048_576.0
-        );
-        println!("  Duplicate groups:  {}", self.stats.duplicate_groups);
-        println!("  Duplicate files:   {}", self.stats.duplicate_files);
-        println!("  Wasted space:      {} ({:.2} MB)", 
+        ));
+        output.push_str(&format!("  Partial hashes:    {}\n", self.stats.partial_hashed));
+        output.push_str(&format!("  Full hashes:       {}\n", self.stats.full_hashed));
+        output.push_str(&format!("  Duplicate groups:  {}\n", self.stats.duplicate_groups));
+        output.push_str(&format!("  Duplicate files:   {}\n", self.stats.duplicate_files));
+        output.push_str(&format!("  Wasted space:      {} ({:.2} MB)\n", 
             self.stats.wasted_space, 
             self.stats.wasted_space as f64 / 1_048_576.0
-        );
-        println!("  Duration:          {:.2}s", self.stats.duration.as_secs_f64());
+        ));
+        output.push_str(&format!("  Duration:          {:.2}s\n", self.stats.duration.as_secs_f64()));
         
         // Calculate and display throughput
         if self.stats.duration.as_secs_f64() > 0.0 {
             let throughput_mbps = (self.stats.total_bytes as f64 / 1_048_576.0) / self.stats.duration.as_secs_f64();
-            println!("  Throughput:        {:.2} MB/s", throughput_mbps);
+            output.push_str(&format!("  Throughput:        {:.2} MB/s\n", throughput_mbps));
         }
         
         // Duplicate groups section (sorted by wasted space, largest first)
         if !self.duplicate_groups.is_empty() {
-            println!("\nDuplicate Groups (sorted by wasted space):");
+            output.push_str("\nDuplicate Groups (sorted by wasted space):\n");
             for group in &self.duplicate_groups {
-                println!("\n  Hash: {} ({} files, {} bytes each, {} bytes wasted)", 
+                output.push_str(&format!("\n  Hash: {} ({} files, {} bytes each, {} bytes wasted)\n", 
                     group.hash, 
                     group.count, 
                     group.file_size,
                     group.wasted_space
-                );
+                ));
                 for path in &group.paths {
-                    println!("    {}", path.display());
+                    output.push_str(&format!("    {}\n", path.display()));
                 }
             }
         } else {
-            println!("\nNo duplicate files found.");
+            output.push_str("\nNo duplicate files found.\n");
         }
         
-        println!();
+        output.push('\n');
+        output
     }
     
     /// Format the dedup report as JSON string
@@ -153,12 +194,26 @@ impl DedupReport {
 /// Type alias for progress callback function
 pub type ProgressCallback = Box<dyn Fn(DedupProgress) + Send + Sync>;
 
+/// Files (with their sizes) grouped by full content hash
+type HashGroups = HashMap<String, Vec<(PathBuf, u64)>>;
+
+/// Bytes hashed from each end of a file in the partial-hash pre-pass
+pub const PARTIAL_HASH_BLOCK: u64 = 4096;
+
 /// Engine for finding duplicate files in a directory
+///
+/// Files are compared in three passes: only files sharing a size are considered, a cheap
+/// hash of their first and last block weeds out most non-duplicates, and only the files
+/// still matching after that are hashed in full.
 pub struct DedupEngine {
     computer: HashComputer,
     fast_mode: bool,
     parallel: bool,
+    pool: Option<Arc<rayon::ThreadPool>>,
     progress_callback: Option<Arc<ProgressCallback>>,
+    show_progress: bool,
+    show_summary: bool,
+    cancel: Option<Arc<AtomicBool>>,
 }
 
 impl DedupEngine {
@@ -169,7 +224,11 @@ impl DedupEngine {
             computer: HashComputer::new(),
             fast_mode: false,
             parallel: true, // Default to parallel for better performance
+            pool: None,
             progress_callback: None,
+            show_progress: true,
+            show_summary: true,
+            cancel: None,
         }
     }
     
@@ -179,11 +238,23 @@ impl DedupEngine {
         self
     }
     
+    /// Enable or disable memory-mapped reads (see `HashComputer::with_mmap`)
+    pub fn with_mmap(mut self, use_mmap: bool) -> Self {
+        self.computer.use_mmap = use_mmap;
+        self
+    }
+    
     /// Enable or disable parallel processing
     pub fn with_parallel(mut self, parallel: bool) -> Self {
         self.parallel = parallel;
         self
     }
+
+    /// Hash on a dedicated pool of `threads` threads (0 uses the global rayon pool)
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.pool = super::pool::build_pool(threads);
+        self
+    }
     
     /// Set a progress callback function
     pub fn with_progress_callback<F>(mut self, callback: F) -> Self
@@ -194,6 +265,55 @@ impl DedupEngine {
         self
     }
     
+    /// Enable or disable the terminal progress bar of the full hashing pass
+    pub fn with_progress(mut self, show_progress: bool) -> Self {
+        self.show_progress = show_progress;
+        self
+    }
+    
+    /// Enable or disable the messages printed to stdout while scanning
+    ///
+    /// The TUI turns them off (and the progress bar) and follows the progress callback.
+    pub fn with_summary(mut self, show_summary: bool) -> Self {
+        self.show_summary = show_summary;
+        self
+    }
+    
+    /// Stop between files once `cancel` is set; `find_duplicates` then fails with
+    /// `HashUtilityError::Cancelled`
+    pub fn with_cancel(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+    
+    fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+    
+    fn check_cancelled(&self) -> Result<(), HashUtilityError> {
+        if self.is_cancelled() {
+            return Err(HashUtilityError::Cancelled { operation: "Duplicate search".to_string() });
+        }
+        Ok(())
+    }
+    
+    /// Report one more file done in `phase`, if anyone is listening
+    fn report(&self, phase: DedupPhase, counter: &AtomicUsize, total_files: usize, bytes_hashed: u64, path: &Path, groups: usize) {
+        if let Some(ref callback) = self.progress_callback {
+            callback(DedupProgress {
+                phase,
+                files_scanned: counter.fetch_add(1, Ordering::Relaxed) + 1,
+                total_files,
+                bytes_hashed,
+                current_file: path.file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("unknown")
+                    .to_string(),
+                duplicate_groups_found: groups,
+            });
+        }
+    }
+    
     /// Scan a directory recursively and find duplicate files
     /// 
     /// # Arguments
@@ -212,19 +332,93 @@ impl DedupEngine {
             HashUtilityError::from_io_error(e, "scanning directory", Some(root.to_path_buf()))
         })?;
         
-        println!("Scanning directory for duplicates: {}", root.display());
-        println!("Using BLAKE3 algorithm (fast and secure)");
-        
-        if self.fast_mode {
-            println!("Fast mode enabled: sampling first, middle, and last 100MB of large files");
+        if self.show_summary {
+            println!("Scanning directory for duplicates: {}", root.display());
+            println!("Using BLAKE3 algorithm (fast and secure)");
+            
+            if self.fast_mode {
+                println!("Fast mode enabled: sampling first, middle, and last 100MB of large files");
+            }
         }
         
-        // Scan directory and compute hashes
-        let (hash_map, files_scanned, files_failed, total_bytes) = if self.parallel {
-            self.scan_parallel(&canonical_root, start_time)?
+        let files = if self.parallel {
+            Self::walk_directory(&canonical_root)
         } else {
-            self.scan_sequential(&canonical_root, start_time)?
+            self.collect_files(&canonical_root)?
         };
+        if self.show_summary {
+            println!("Found {} files to process", files.len());
+        }
+        self.check_cancelled()?;
+
+        // Pass 1: sizes. A file whose size is unique can't have a duplicate.
+        let sized = AtomicUsize::new(0);
+        let sizes = self.map_files(&files, |path| {
+            if self.is_cancelled() {
+                return None;
+            }
+            self.report(DedupPhase::Sizes, &sized, files.len(), 0, path, 0);
+            fs::metadata(path).map(|m| m.len()).ok()
+        });
+        self.check_cancelled()?;
+        let mut files_failed = 0;
+        let mut files_scanned = 0;
+        let mut total_bytes = 0u64;
+        let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+        for (path, size) in files.into_iter().zip(sizes) {
+            match size {
+                Some(size) => {
+                    files_scanned += 1;
+                    total_bytes += size;
+                    by_size.entry(size).or_default().push(path);
+                }
+                None => files_failed += 1,
+            }
+        }
+
+        // Pass 2: first/last block. Small files would be read whole anyway, so they skip it.
+        let (small, large): (Vec<_>, Vec<_>) = by_size
+            .into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .flat_map(|(size, paths)| paths.into_iter().map(move |p| (p, size)))
+            .partition(|(_, size)| *size <= 2 * PARTIAL_HASH_BLOCK);
+
+        let partial_hashed = large.len();
+        let sampled = AtomicUsize::new(0);
+        let bytes_hashed = AtomicU64::new(0);
+        let partials = self.map_files(&large, |(path, size)| {
+            if self.is_cancelled() {
+                return Err(std::io::ErrorKind::Interrupted.into());
+            }
+            let partial = partial_hash(path, *size);
+            let bytes = bytes_hashed.fetch_add(2 * PARTIAL_HASH_BLOCK, Ordering::Relaxed) + 2 * PARTIAL_HASH_BLOCK;
+            self.report(DedupPhase::Partial, &sampled, large.len(), bytes, path, 0);
+            partial
+        });
+        self.check_cancelled()?;
+        let mut by_partial: HashMap<(u64, String), Vec<(PathBuf, u64)>> = HashMap::new();
+        for ((path, size), partial) in large.into_iter().zip(partials) {
+            match partial {
+                Ok(partial) => by_partial.entry((size, partial)).or_default().push((path, size)),
+                Err(e) => {
+                    eprintln!("Warning: Failed to hash {}: {}", path.display(), e);
+                    files_failed += 1;
+                    files_scanned -= 1;
+                    total_bytes -= size;
+                }
+            }
+        }
+
+        let mut candidates = small;
+        candidates.extend(by_partial.into_values().filter(|group| group.len() > 1).flatten());
+
+        // Pass 3: full hashes of whatever still might be a duplicate
+        let full_hashed = candidates.len();
+        let (hash_map, hash_failed, failed_bytes) = self.hash_candidates(&candidates, &bytes_hashed);
+        self.check_cancelled()?;
+        files_failed += hash_failed;
+        files_scanned -= hash_failed;
+        total_bytes -= failed_bytes;
         
         let duration = start_time.elapsed();
         
@@ -239,6 +433,8 @@ impl DedupEngine {
             files_scanned,
             files_failed,
             total_bytes,
+            partial_hashed,
+            full_hashed,
             duplicate_groups: duplicate_groups.len(),
             duplicate_files,
             wasted_space,
@@ -250,271 +446,102 @@ impl DedupEngine {
             duplicate_groups,
         })
     }
-    
-    /// Sequential scan implementation
-    fn scan_sequential(
+
+    /// Apply `f` to every item, on the configured pool when parallel and on one thread otherwise
+    fn map_files<I, T, F>(&self, items: &[I], f: F) -> Vec<T>
+    where
+        I: Sync,
+        T: Send,
+        F: Fn(&I) -> T + Sync + Send,
+    {
+        let pool = super::pool::engine_pool(self.parallel, self.pool.as_ref());
+        super::pool::install(pool.as_ref(), || {
+            if self.parallel {
+                items.par_iter().map(&f).collect()
+            } else {
+                items.iter().map(&f).collect()
+            }
+        })
+    }
+
+    /// Fully hash the remaining candidates, returning the hash map plus the number
+    /// and total size of files that could not be read
+    fn hash_candidates(
         &self,
-        canonical_root: &Path,
-        _start_time: Instant,
-    ) -> Result<(HashMap<String, Vec<(PathBuf, u64)>>, usize, usize, u64), HashUtilityError> {
-        // Collect all files
-        let files = self.collect_files(canonical_root)?;
-        
-        println!("Found {} files to process", files.len());
-        
-        // Track statistics
-        let mut files_scanned = 0;
-        let mut files_failed = 0;
-        let mut total_bytes = 0u64;
-        
-        // Map from hash to list of (path, size) tuples
-        let mut hash_map: HashMap<String, Vec<(PathBuf, u64)>> = HashMap::new();
-        
-        // Create progress bar
-        let pb = ProgressBar::new(files.len() as u64);
+        candidates: &[(PathBuf, u64)],
+        bytes_hashed: &AtomicU64,
+    ) -> (HashGroups, usize, u64) {
+        let pb = if self.show_progress {
+            ProgressBar::new(candidates.len() as u64)
+        } else {
+            ProgressBar::hidden()
+        };
         pb.set_style(
             ProgressStyle::default_bar()
-                .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files ({percent}%) | Processed: {msg}")
+                .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files ({percent}%) | Hashing candidates")
                 .unwrap()
                 .progress_chars("=>-")
         );
-        
-        // Process each file
-        for file_path in files.iter() {
-            // Update progress bar
-            pb.set_message(format!("{} OK, {} failed", files_scanned, files_failed));
-            
-            // Check if file still exists and is accessible
-            let metadata = match fs::metadata(file_path) {
-                Ok(m) => m,
-                Err(_) => {
-                    files_failed += 1;
-                    pb.inc(1);
-                    continue;
-                }
-            };
-            
-            let file_size = metadata.len();
-            
-            // Compute hash for the file (always use BLAKE3)
+
+        // Hash counts so far, for the duplicate-group count in progress events
+        let hash_counts = Mutex::new(HashMap::<String, usize>::new());
+        let hashed = AtomicUsize::new(0);
+
+        let results = self.map_files(candidates, |(path, size)| {
+            if self.is_cancelled() {
+                return None;
+            }
             let hash_result = if self.fast_mode {
-                self.computer.compute_hash_fast(file_path, "blake3")
+                self.computer.compute_hash_fast(path, "blake3")
             } else {
-                self.computer.compute_hash(file_path, "blake3")
+                self.computer.compute_hash(path, "blake3")
             };
-            
-            match hash_result {
-                Ok(result) => {
-                    // Add to hash map
-                    hash_map
-                        .entry(result.hash)
-                        .or_insert_with(Vec::new)
-                        .push((file_path.clone(), file_size));
-                    
-                    files_scanned += 1;
-                    total_bytes += file_size;
-                    
-                    // Emit progress event if callback is set
-                    if let Some(ref callback) = self.progress_callback {
-                        // Count duplicate groups so far
-                        let duplicate_groups = hash_map.values().filter(|v| v.len() > 1).count();
-                        
-                        callback(DedupProgress {
-                            files_scanned,
-                            current_file: file_path.file_name()
-                                .and_then(|n| n.to_str())
-                                .unwrap_or("unknown")
-                                .to_string(),
-                            duplicate_groups_found: duplicate_groups,
-                        });
-                    }
-                }
+            pb.inc(1);
+
+            let hash = match hash_result {
+                Ok(result) => result.hash,
                 Err(e) => {
-                    eprintln!("Warning: Failed to hash {}: {}", file_path.display(), e);
-                    files_failed += 1;
+                    eprintln!("Warning: Failed to hash {}: {}", path.display(), e);
+                    return None;
                 }
+            };
+
+            let bytes = bytes_hashed.fetch_add(*size, Ordering::Relaxed) + size;
+            if self.progress_callback.is_some() {
+                let duplicate_groups = {
+                    let mut counts = hash_counts.lock().unwrap();
+                    *counts.entry(hash.clone()).or_insert(0) += 1;
+                    counts.values().filter(|&&count| count > 1).count()
+                };
+                self.report(DedupPhase::Full, &hashed, candidates.len(), bytes, path, duplicate_groups);
             }
-            
-            pb.inc(1);
-        }
-        
-        pb.finish_and_clear();
-        
-        Ok((hash_map, files_scanned, files_failed, total_bytes))
-    }
-    
-    /// Parallel scan implementation using producer-consumer pattern
-    fn scan_parallel(
-        &self,
-        canonical_root: &Path,
-        _start_time: Instant,
-    ) -> Result<(HashMap<String, Vec<(PathBuf, u64)>>, usize, usize, u64), HashUtilityError> {
-        // Thread-safe counters
-        let files_scanned = Arc::new(Mutex::new(0usize));
-        let files_failed = Arc::new(Mutex::new(0usize));
-        let total_bytes = Arc::new(Mutex::new(0u64));
-        
-        // Create progress bar
-        let pb = ProgressBar::new(0);
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template("[{elapsed_precise}] Counting... {pos} files found | Processing: {msg}")
-                .unwrap()
-                .progress_chars("=>-")
-        );
-        
-        // Create bounded channel
-        let (sender, receiver) = bounded::<PathBuf>(10000);
-        
-        // Track total files discovered
-        let total_files_discovered = Arc::new(Mutex::new(0usize));
-        let discovery_complete = Arc::new(Mutex::new(false));
-        
-        // Capture fast_mode for use in closure
-        let fast_mode = self.fast_mode;
-        
-        // Clone for walker thread
-        let walker_root = canonical_root.to_path_buf();
-        let total_files_discovered_walker = Arc::clone(&total_files_discovered);
-        let discovery_complete_walker = Arc::clone(&discovery_complete);
-        let pb_walker = pb.clone();
-        
-        // Spawn walker thread
-        let walker_handle = thread::spawn(move || {
-            let result = Self::walk_directory_streaming(&walker_root, sender, Arc::clone(&total_files_discovered_walker));
-            
-            // Mark discovery as complete
-            let total = *total_files_discovered_walker.lock().unwrap();
-            pb_walker.set_length(total as u64);
-            pb_walker.set_style(
-                ProgressStyle::default_bar()
-                    .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files ({percent}%) | Processed: {msg}")
-                    .unwrap()
-                    .progress_chars("=>-")
-            );
-            *discovery_complete_walker.lock().unwrap() = true;
-            
-            result
+            Some(hash)
         });
-        
-        // Clone Arc references for parallel closure
-        let files_scanned_clone = Arc::clone(&files_scanned);
-        let files_failed_clone = Arc::clone(&files_failed);
-        let total_bytes_clone = Arc::clone(&total_bytes);
-        let pb_clone = pb.clone();
-        let progress_callback_clone = self.progress_callback.clone();
-        
-        // Shared hash map for tracking duplicates (wrapped in Arc<Mutex>)
-        let hash_map_for_progress = Arc::new(Mutex::new(HashMap::<String, usize>::new()));
-        let hash_map_for_progress_clone = Arc::clone(&hash_map_for_progress);
-        
-        // Use rayon's par_bridge to consume from channel in parallel
-        let results: Vec<_> = receiver
-            .into_iter()
-            .par_bridge()
-            .filter_map(|file_path| {
-                // Check if file still exists and is accessible
-                let metadata = match fs::metadata(&file_path) {
-                    Ok(m) => m,
-                    Err(_) => {
-                        let mut failed = files_failed_clone.lock().unwrap();
-                        *failed += 1;
-                        pb_clone.inc(1);
-                        return None;
-                    }
-                };
-                
-                let file_size = metadata.len();
-                
-                // Update progress bar
-                let scanned = files_scanned_clone.lock().unwrap();
-                let failed = files_failed_clone.lock().unwrap();
-                pb_clone.set_message(format!("{} OK, {} failed", *scanned, *failed));
-                drop(scanned);
-                drop(failed);
-                
-                // Compute hash (always use BLAKE3)
-                let computer = HashComputer::new();
-                let hash_result = if fast_mode {
-                    computer.compute_hash_fast(&file_path, "blake3")
-                } else {
-                    computer.compute_hash(&file_path, "blake3")
-                };
-                
-                let result = match hash_result {
-                    Ok(result) => {
-                        // Update counters
-                        let mut scanned = files_scanned_clone.lock().unwrap();
-                        *scanned += 1;
-                        let current_scanned = *scanned;
-                        drop(scanned);
-                        
-                        let mut bytes = total_bytes_clone.lock().unwrap();
-                        *bytes += file_size;
-                        drop(bytes);
-                        
-                        // Track hash for duplicate counting
-                        let mut hash_counts = hash_map_for_progress_clone.lock().unwrap();
-                        *hash_counts.entry(result.hash.clone()).or_insert(0) += 1;
-                        let duplicate_groups = hash_counts.values().filter(|&&count| count > 1).count();
-                        drop(hash_counts);
-                        
-                        // Emit progress event if callback is set
-                        if let Some(ref callback) = progress_callback_clone {
-                            callback(DedupProgress {
-                                files_scanned: current_scanned,
-                                current_file: file_path.file_name()
-                                    .and_then(|n| n.to_str())
-                                    .unwrap_or("unknown")
-                                    .to_string(),
-                                duplicate_groups_found: duplicate_groups,
-                            });
-                        }
-                        
-                        Some((result.hash, file_path.clone(), file_size))
-                    }
-                    Err(e) => {
-                        eprintln!("Warning: Failed to hash {}: {}", file_path.display(), e);
-                        let mut failed = files_failed_clone.lock().unwrap();
-                        *failed += 1;
-                        None
-                    }
-                };
-                
-                pb_clone.inc(1);
-                result
-            })
-            .collect();
-        
-        // Wait for walker thread
-        match walker_handle.join() {
-            Ok(walk_result) => {
-                if let Err(e) = walk_result {
-                    eprintln!("Warning: Walker thread encountered error: {}", e);
+        pb.finish_and_clear();
+
+        let mut hash_map = HashGroups::new();
+        let mut failed = 0;
+        let mut failed_bytes = 0;
+        for ((path, size), hash) in candidates.iter().zip(results) {
+            match hash {
+                Some(hash) => hash_map.entry(hash).or_default().push((path.clone(), *size)),
+                None => {
+                    failed += 1;
+                    failed_bytes += size;
                 }
             }
-            Err(e) => {
-                eprintln!("Warning: Walker thread panicked: {:?}", e);
-            }
         }
-        
-        pb.finish_and_clear();
-        
-        // Build hash map from results
-        let mut hash_map: HashMap<String, Vec<(PathBuf, u64)>> = HashMap::new();
-        for (hash, path, size) in results {
-            hash_map
-                .entry(hash)
-                .or_insert_with(Vec::new)
-                .push((path, size));
+        (hash_map, failed, failed_bytes)
+    }
+
+    /// Collect files with jwalk's parallel traversal
+    fn walk_directory(root: &Path) -> Vec<PathBuf> {
+        let (sender, receiver) = unbounded();
+        let discovered = Arc::new(Mutex::new(0usize));
+        if let Err(e) = Self::walk_directory_streaming(root, sender, discovered) {
+            eprintln!("Warning: Error walking directory: {}", e);
         }
-        
-        // Extract final statistics
-        let final_scanned = *files_scanned.lock().unwrap();
-        let final_failed = *files_failed.lock().unwrap();
-        let final_bytes = *total_bytes.lock().unwrap();
-        
-        Ok((hash_map, final_scanned, final_failed, final_bytes))
+        receiver.into_iter().collect()
     }
     
     /// Walk directory and send file paths to channel
@@ -662,7 +689,7 @@ impl DedupEngine {
     /// Find duplicate groups from hash map
     fn find_duplicate_groups(
         &self,
-        hash_map: &HashMap<String, Vec<(PathBuf, u64)>>,
+        hash_map: &HashGroups,
     ) -> Vec<DuplicateGroupWithSize> {
         // Filter to only groups with more than one file (duplicates)
         let mut duplicates: Vec<DuplicateGroupWithSize> = hash_map
@@ -693,6 +720,23 @@ impl DedupEngine {
     }
 }
 
+/// BLAKE3 of a file's first and last `PARTIAL_HASH_BLOCK` bytes
+fn partial_hash(path: &Path, size: u64) -> std::io::Result<String> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = fs::File::open(path)?;
+    let mut block = vec![0u8; PARTIAL_HASH_BLOCK as usize];
+    let mut hasher = blake3::Hasher::new();
+
+    file.read_exact(&mut block)?;
+    hasher.update(&block);
+    file.seek(SeekFrom::Start(size.saturating_sub(PARTIAL_HASH_BLOCK)))?;
+    file.read_exact(&mut block)?;
+    hasher.update(&block);
+
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
 impl Default for DedupEngine {
     fn default() -> Self {
         Self::new()
@@ -24,6 +24,8 @@ pub enum HashUtilityError {
     DatabaseParseError { path: PathBuf, line: usize, reason: String },
     DatabaseWriteError { path: PathBuf, reason: String },
     EmptyDatabase { path: PathBuf },
+    /// A database whose entries name no algorithm, or more than one
+    DatabaseAlgorithm { path: PathBuf, reason: String },
     
     /// Verification errors
     VerificationFailed { reason: String },
@@ -34,6 +36,9 @@ pub enum HashUtilityError {
     
     /// Benchmark errors
     BenchmarkFailed { algorithm: String, reason: String },
+
+    /// Stopped through a cancel flag before finishing
+    Cancelled { operation: String },
 }
 
 impl fmt::Display for HashUtilityError {
@@ -58,7 +63,11 @@ impl fmt::Display for HashUtilityError {
                 } else {
                     write!(f, "I/O error while {}: {}\n", operation, source)?;
                 }
-                write!(f, "Suggestion: Check file permissions and disk space")
+                if is_too_many_open_files(source) {
+                    write!(f, "Suggestion: Raise the open file limit (ulimit -n) or lower --max-open-files")
+                } else {
+                    write!(f, "Suggestion: Check file permissions and disk space")
+                }
             }
             
             // Hash computation errors
@@ -88,6 +97,10 @@ impl fmt::Display for HashUtilityError {
                 write!(f, "Database file is empty: {}\n", path.display())?;
                 write!(f, "Suggestion: Ensure the database contains at least one hash entry")
             }
+            HashUtilityError::DatabaseAlgorithm { path, reason } => {
+                writeln!(f, "Cannot tell which algorithm database {} uses: {}", path.display(), reason)?;
+                write!(f, "Suggestion: Pass --algorithm to name it, or rescan the directory with a single algorithm")
+            }
             
             // Verification errors
             HashUtilityError::VerificationFailed { reason } => {
@@ -110,6 +123,8 @@ impl fmt::Display for HashUtilityError {
                 write!(f, "Benchmark failed for {}: {}\n", algorithm, reason)?;
                 write!(f, "Suggestion: Try running the benchmark again or with a smaller data size")
             }
+
+            HashUtilityError::Cancelled { operation } => write!(f, "{} cancelled", operation),
         }
     }
 }
@@ -123,8 +138,20 @@ impl std::error::Error for HashUtilityError {
     }
 }
 
+/// Whether `err` means the process ran out of file descriptors (EMFILE)
+fn is_too_many_open_files(err: &io::Error) -> bool {
+    // ERROR_TOO_MANY_OPEN_FILES on Windows, EMFILE everywhere else
+    let code = if cfg!(windows) { 4 } else { 24 };
+    err.raw_os_error() == Some(code)
+}
+
 // Conversion from io::Error with context
 impl HashUtilityError {
+    /// Whether this error comes from running out of file descriptors
+    pub fn is_too_many_open_files(&self) -> bool {
+        matches!(self, HashUtilityError::IoError { source, .. } if is_too_many_open_files(source))
+    }
+    
     /// Create an IoError with context about the operation and optional path
     pub fn from_io_error(err: io::Error, operation: &str, path: Option<PathBuf>) -> Self {
         // Check for specific error kinds and provide more specific errors
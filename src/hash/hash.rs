@@ -312,12 +312,115 @@ impl Hasher for Xxh128Wrapper {
     }
 }
 
+/// Key material for the keyed BLAKE3 modes
+///
+/// `blake3-keyed` hashes with a secret 32-byte key, so a database can't be rewritten to
+/// match altered files without it. `blake3-derive` hashes in BLAKE3's key derivation mode
+/// under a context string; the context is not secret and only keeps digests made for
+/// different purposes apart. The key is never printed, including by `Debug`.
+#[derive(Clone, Default)]
+pub struct HashKey {
+    key: Option<[u8; 32]>,
+    context: Option<String>,
+}
+
+impl HashKey {
+    /// Environment variable holding the `blake3-keyed` key as 64 hex characters
+    pub const KEY_ENV: &'static str = "ABYSS_HASH_KEY";
+    /// Environment variable holding the `blake3-derive` context string
+    pub const CONTEXT_ENV: &'static str = "ABYSS_HASH_CONTEXT";
+
+    /// No key and no context: only the unkeyed algorithms work
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the 32-byte key for `blake3-keyed`
+    pub fn with_key(mut self, key: [u8; 32]) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Set the `blake3-keyed` key from 64 hex characters (surrounding whitespace is ignored)
+    pub fn with_key_hex(self, hex: &str) -> Result<Self, HashError> {
+        let hex = hex.trim();
+        let invalid = || HashUtilityError::InvalidArguments {
+            // Only the length is reported, never the key itself
+            message: format!("a hash key must be 64 hex characters (32 bytes), got {} characters", hex.chars().count()),
+        };
+        if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        let mut key = [0u8; 32];
+        for (byte, pair) in key.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+            *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+        }
+        Ok(self.with_key(key))
+    }
+
+    /// Read the `blake3-keyed` key from a file holding either 64 hex characters or 32 raw bytes
+    pub fn with_key_file(self, path: &Path) -> Result<Self, HashError> {
+        let bytes = std::fs::read(path).map_err(|e| HashUtilityError::from_io_error(e, "reading", Some(path.to_path_buf())))?;
+        match std::str::from_utf8(&bytes) {
+            Ok(text) if text.trim().len() == 64 => self.with_key_hex(text),
+            _ => match <[u8; 32]>::try_from(bytes.as_slice()) {
+                Ok(key) => Ok(self.with_key(key)),
+                Err(_) => self.with_key_hex(&String::from_utf8_lossy(&bytes)),
+            },
+        }
+    }
+
+    /// Set the context string for `blake3-derive`, e.g. "backups 2026 manifest v1"
+    pub fn with_context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+
+    /// Key and context from `ABYSS_HASH_KEY` and `ABYSS_HASH_CONTEXT`, where set
+    pub fn from_env() -> Result<Self, HashError> {
+        let mut key = Self::new();
+        if let Ok(hex) = std::env::var(Self::KEY_ENV) {
+            key = key.with_key_hex(&hex)?;
+        }
+        if let Ok(context) = std::env::var(Self::CONTEXT_ENV) {
+            key = key.with_context(context);
+        }
+        Ok(key)
+    }
+
+    pub fn has_key(&self) -> bool {
+        self.key.is_some()
+    }
+
+    pub fn context(&self) -> Option<&str> {
+        self.context.as_deref()
+    }
+}
+
+impl std::fmt::Debug for HashKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HashKey")
+            .field("key", &self.key.map(|_| "<redacted>"))
+            .field("context", &self.context)
+            .finish()
+    }
+}
+
 /// Registry for hash algorithms
 pub struct HashRegistry;
 
 impl HashRegistry {
     /// Get a hasher instance for the specified algorithm
+    ///
+    /// The keyed modes fail here for lack of key material; use `get_keyed_hasher`.
     pub fn get_hasher(algorithm: &str) -> Result<Box<dyn Hasher>, HashError> {
+        Self::get_keyed_hasher(algorithm, &HashKey::new())
+    }
+
+    /// Get a hasher instance, taking the key or context of `blake3-keyed` and
+    /// `blake3-derive` from `key`
+    pub fn get_keyed_hasher(algorithm: &str, key: &HashKey) -> Result<Box<dyn Hasher>, HashError> {
         let alg_lower = algorithm.to_lowercase();
         
         match alg_lower.as_str() {
@@ -334,6 +437,18 @@ impl HashRegistry {
             "blake2b" | "blake2b-512" => Ok(Box::new(Blake2b512Wrapper(Blake2Digest::new()))),
             "blake2s" | "blake2s-256" => Ok(Box::new(Blake2s256Wrapper(Blake2Digest::new()))),
             "blake3" => Ok(Box::new(Blake3Wrapper(Blake3Hasher::new()))),
+            "blake3-keyed" => match &key.key {
+                Some(secret) => Ok(Box::new(Blake3Wrapper(Blake3Hasher::new_keyed(secret)))),
+                None => Err(HashUtilityError::MissingRequiredArgument {
+                    argument: format!("a key for blake3-keyed (--key-file or {})", HashKey::KEY_ENV),
+                }),
+            },
+            "blake3-derive" => match &key.context {
+                Some(context) => Ok(Box::new(Blake3Wrapper(Blake3Hasher::new_derive_key(context)))),
+                None => Err(HashUtilityError::MissingRequiredArgument {
+                    argument: format!("a context for blake3-derive (--key-context or {})", HashKey::CONTEXT_ENV),
+                }),
+            },
             "xxh3" => Ok(Box::new(Xxh3Wrapper(Xxh3Hasher::new()))),
             "xxh128" => Ok(Box::new(Xxh128Wrapper(Xxh3HasherBase::new()))),
             _ => Err(HashUtilityError::UnsupportedAlgorithm {
@@ -423,6 +538,18 @@ impl HashRegistry {
                 post_quantum: false,
                 cryptographic: true,
             },
+            AlgorithmInfo {
+                name: "BLAKE3-Keyed".to_string(),
+                output_bits: 256,
+                post_quantum: false,
+                cryptographic: true,
+            },
+            AlgorithmInfo {
+                name: "BLAKE3-Derive".to_string(),
+                output_bits: 256,
+                post_quantum: false,
+                cryptographic: true,
+            },
             AlgorithmInfo {
                 name: "XXH3".to_string(),
                 output_bits: 64,
@@ -460,11 +587,16 @@ pub struct HashResult {
 /// Hash computer with streaming I/O
 pub struct HashComputer {
     pub buffer_size: usize,
+    /// Memory-map files under 2GB instead of reading them (see `with_mmap`)
+    pub use_mmap: bool,
+    /// Key material for `blake3-keyed` and `blake3-derive` (see `with_key`)
+    pub key: HashKey,
 }
 
 // Constants for fast mode sampling
 const FAST_MODE_SAMPLE_SIZE: u64 = 100 * 1024 * 1024; // 100MB
-const FAST_MODE_THRESHOLD: u64 = 3 * FAST_MODE_SAMPLE_SIZE; // 300MB
+/// Files at least this large are sampled instead of fully hashed in fast mode
+pub const FAST_MODE_THRESHOLD: u64 = 3 * FAST_MODE_SAMPLE_SIZE; // 300MB
 
 // Constants for memory mapping
 const MMAP_THRESHOLD: u64 = 2 * 1024 * 1024 * 1024; // 2GB
@@ -476,14 +608,46 @@ const PROGRESS_UPDATE_INTERVAL_MS: u64 = 100; // 10 times per second
 impl HashComputer {
     /// Create a new HashComputer with default buffer size (1MB)
     pub fn new() -> Self {
-        Self {
-            buffer_size: 1024 * 1024,
-        }
+        Self::with_buffer_size(1024 * 1024)
     }
     
     /// Create a new HashComputer with custom buffer size
     pub fn with_buffer_size(buffer_size: usize) -> Self {
-        Self { buffer_size }
+        Self { buffer_size, use_mmap: true, key: HashKey::new() }
+    }
+    
+    /// Use `key` for the keyed BLAKE3 modes
+    pub fn with_key(mut self, key: HashKey) -> Self {
+        self.key = key;
+        self
+    }
+    
+    /// Get a hasher for `algorithm`, keyed with this computer's key material
+    pub fn hasher(&self, algorithm: &str) -> Result<Box<dyn Hasher>, HashError> {
+        HashRegistry::get_keyed_hasher(algorithm, &self.key)
+    }
+    
+    /// Enable or disable memory mapping (on by default)
+    ///
+    /// Turn it off on NFS, SMB and other network or FUSE mounts: if a mapped file is
+    /// truncated by another client while it is being hashed, reading past the new end
+    /// raises SIGBUS and kills the process instead of returning an error. With mapping
+    /// off every file is hashed with buffered reads, which only ever see a short read.
+    pub fn with_mmap(mut self, use_mmap: bool) -> Self {
+        self.use_mmap = use_mmap;
+        self
+    }
+    
+    /// Map `file` if memory mapping is enabled and the file is between 0 and 2GB
+    ///
+    /// Returns None (so the caller reads the file instead) when mapping is off, the
+    /// size is out of range or the mapping fails.
+    fn map_file(&self, file: &File, file_size: u64) -> Option<Mmap> {
+        if !self.use_mmap || file_size == 0 || file_size >= MMAP_THRESHOLD {
+            return None;
+        }
+        // Safety: see `with_mmap`; a concurrent size change is caught by `unchanged_size`
+        unsafe { Mmap::map(file) }.ok()
     }
     
     /// Compute hash from text string
@@ -493,7 +657,7 @@ impl HashComputer {
         algorithm: &str,
     ) -> Result<HashResult, HashError> {
         // Get hasher for the specified algorithm
-        let mut hasher = HashRegistry::get_hasher(algorithm)?;
+        let mut hasher = self.hasher(algorithm)?;
         
         // Hash the UTF-8 bytes of the text
         hasher.update(text.as_bytes());
@@ -518,7 +682,7 @@ impl HashComputer {
         // Get hashers for all specified algorithms
         let mut hashers: Vec<(String, Box<dyn Hasher>)> = Vec::new();
         for algorithm in algorithms {
-            let hasher = HashRegistry::get_hasher(algorithm)?;
+            let hasher = self.hasher(algorithm)?;
             hashers.push((algorithm.clone(), hasher));
         }
         
@@ -552,7 +716,7 @@ impl HashComputer {
         use std::io::{stdin, Read};
         
         // Get hasher for the specified algorithm
-        let mut hasher = HashRegistry::get_hasher(algorithm)?;
+        let mut hasher = self.hasher(algorithm)?;
         
         // Get stdin handle
         let mut stdin = stdin();
@@ -585,13 +749,15 @@ impl HashComputer {
     /// Compute hash for a single file using streaming I/O or memory mapping
     /// 
     /// For files smaller than 2GB, uses memory mapping to avoid kernel-to-userspace copy overhead.
-    /// For files larger than 2GB, falls back to buffered reading with 1MB buffer.
+    /// For files larger than 2GB, when mapping fails or is turned off with `with_mmap(false)`,
+    /// falls back to buffered reading with 1MB buffer.
     /// 
     /// # Safety
     /// 
     /// Memory mapping assumes the file will not be modified by other processes during hashing.
-    /// If the file is modified concurrently, the hash result may be inconsistent.
-    /// This is acceptable for typical use cases where files are not being actively modified.
+    /// A file whose size changed while mapped is hashed again with buffered reads; other
+    /// concurrent modifications may still give an inconsistent hash. This is acceptable for
+    /// typical use cases where files are not being actively modified.
     pub fn compute_hash(
         &self,
         path: &Path,
@@ -611,7 +777,7 @@ impl HashComputer {
         show_progress: bool,
     ) -> Result<HashResult, HashError> {
         // Get hasher for the specified algorithm
-        let mut hasher = HashRegistry::get_hasher(algorithm)?;
+        let mut hasher = self.hasher(algorithm)?;
         
         // Open file for reading with better error context
         let file = File::open(path).map_err(|e| {
@@ -629,25 +795,20 @@ impl HashComputer {
             && std::io::stdout().is_terminal();
         
         // Use memory mapping for files smaller than 2GB
-        if file_size > 0 && file_size < MMAP_THRESHOLD {
-            // Try to memory map the file
-            match unsafe { Mmap::map(&file) } {
-                Ok(mmap) => {
-                    // Hash the entire mapped file in one go
-                    // Note: Progress bar not shown for mmap as it's very fast
-                    hasher.update(&mmap[..]);
-                }
-                Err(_) => {
-                    // Fall back to buffered reading if mmap fails
-                    if should_show_progress {
-                        self.hash_with_buffered_io_progress(&mut hasher, file, path, file_size)?;
-                    } else {
-                        self.hash_with_buffered_io(&mut hasher, file, path)?;
-                    }
-                }
+        let mut hashed = false;
+        if let Some(mmap) = self.map_file(&file, file_size) {
+            // Hash the entire mapped file in one go
+            // Note: Progress bar not shown for mmap as it's very fast
+            hasher.update(&mmap[..]);
+            hashed = unchanged_size(&file, file_size);
+            if !hashed {
+                // The file grew or shrank under the mapping: start over with buffered reads
+                hasher = self.hasher(algorithm)?;
             }
-        } else {
-            // Use buffered reading for large files (>2GB) or empty files
+        }
+        
+        if !hashed {
+            // Use buffered reading for large files (>2GB), empty files or when mmap is unavailable
             if should_show_progress {
                 self.hash_with_buffered_io_progress(&mut hasher, file, path, file_size)?;
             } else {
@@ -749,7 +910,7 @@ impl HashComputer {
         // Get hashers for all specified algorithms
         let mut hashers: Vec<(String, Box<dyn Hasher>)> = Vec::new();
         for algorithm in algorithms {
-            let hasher = HashRegistry::get_hasher(algorithm)?;
+            let hasher = self.hasher(algorithm)?;
             hashers.push((algorithm.clone(), hasher));
         }
         
@@ -820,7 +981,7 @@ impl HashComputer {
         // Get hashers for all specified algorithms
         let mut hashers: Vec<(String, Box<dyn Hasher>)> = Vec::new();
         for algorithm in algorithms {
-            let hasher = HashRegistry::get_hasher(algorithm)?;
+            let hasher = self.hasher(algorithm)?;
             hashers.push((algorithm.clone(), hasher));
         }
         
@@ -840,27 +1001,24 @@ impl HashComputer {
             && std::io::stdout().is_terminal();
         
         // Use memory mapping for files smaller than 2GB
-        if file_size > 0 && file_size < MMAP_THRESHOLD {
-            // Try to memory map the file
-            match unsafe { Mmap::map(&file) } {
-                Ok(mmap) => {
-                    // Hash the entire mapped file with all hashers
-                    // Note: Progress bar not shown for mmap as it's very fast
-                    for (_, hasher) in &mut hashers {
-                        hasher.update(&mmap[..]);
-                    }
-                }
-                Err(_) => {
-                    // Fall back to buffered reading if mmap fails
-                    if should_show_progress {
-                        self.hash_multiple_with_buffered_io_progress(&mut hashers, file, path, file_size)?;
-                    } else {
-                        self.hash_multiple_with_buffered_io(&mut hashers, file, path)?;
-                    }
+        let mut hashed = false;
+        if let Some(mmap) = self.map_file(&file, file_size) {
+            // Hash the entire mapped file with all hashers
+            // Note: Progress bar not shown for mmap as it's very fast
+            for (_, hasher) in &mut hashers {
+                hasher.update(&mmap[..]);
+            }
+            hashed = unchanged_size(&file, file_size);
+            if !hashed {
+                // The file grew or shrank under the mapping: start over with buffered reads
+                for (algorithm, hasher) in &mut hashers {
+                    *hasher = self.hasher(algorithm)?;
                 }
             }
-        } else {
-            // Use buffered reading for large files (>2GB) or empty files
+        }
+        
+        if !hashed {
+            // Use buffered reading for large files (>2GB), empty files or when mmap is unavailable
             if should_show_progress {
                 self.hash_multiple_with_buffered_io_progress(&mut hashers, file, path, file_size)?;
             } else {
@@ -981,7 +1139,7 @@ impl HashComputer {
     ) -> Result<HashResult, HashError> {
         
         // Get hasher for the specified algorithm
-        let mut hasher = HashRegistry::get_hasher(algorithm)?;
+        let mut hasher = self.hasher(algorithm)?;
         
         // Open file for reading with better error context
         let mut file = File::open(path).map_err(|e| {
@@ -1072,6 +1230,11 @@ impl Default for HashComputer {
     }
 }
 
+/// Whether `file` still has the size it had when it was mapped
+fn unchanged_size(file: &File, mapped_size: u64) -> bool {
+    file.metadata().is_ok_and(|m| m.len() == mapped_size)
+}
+
 /// Convert bytes to hexadecimal string
 fn bytes_to_hex(bytes: &[u8]) -> String {
     bytes.iter()
@@ -12,14 +12,23 @@ pub mod ignore_handler;
 pub mod wildcard;
 pub mod compare;
 pub mod dedup;
+pub mod check;
+pub mod tree;
+pub mod pool;
 
 // Re-export commonly used types for convenience
 pub use error::HashUtilityError;
-pub use hash::{HashComputer, HashRegistry, HashResult, AlgorithmInfo, Hasher};
-pub use scan::{ScanEngine, ScanStats};
-pub use verify::{VerifyEngine, VerifyReport, Mismatch};
+pub use hash::{HashComputer, HashKey, HashRegistry, HashResult, AlgorithmInfo, Hasher, FAST_MODE_THRESHOLD};
+pub use scan::{
+    ExtensionFilter, OpenFileLimit, OpenFilePermit, ScanEngine, ScanStats, DEFAULT_MAX_OPEN_FILES,
+    WRITE_QUEUE_CAPACITY,
+};
+pub use verify::{VerifyEngine, VerifyProgress, VerifyReport, Mismatch};
 pub use benchmark::{BenchmarkEngine, BenchmarkResult, generate_test_data, calculate_throughput};
 pub use database::{DatabaseHandler, DatabaseFormat, DatabaseEntry};
 pub use compare::{CompareEngine, CompareReport, ChangedFile, DuplicateGroup};
-pub use dedup::{DedupEngine, DedupReport, DedupStats};
+pub use dedup::{DedupEngine, DedupPhase, DedupProgress, DedupReport, DedupStats};
+pub use check::{check_file, parse_expected_hash, ExpectedHash, HashCheck};
+pub use tree::{TreeHash, TreeHashEngine};
+pub use pool::{threads_from_env, HASH_THREADS_ENV};
 
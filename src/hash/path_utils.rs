@@ -126,5 +126,30 @@ pub fn clean_path(path: &Path) -> PathBuf {
     }
 }
 
+/// Canonicalize a path that may not exist yet (e.g. a report about to be written)
+/// Falls back to the canonical parent joined with the file name, then to the cleaned absolute path
+pub fn canonicalize_lenient(path: &Path) -> PathBuf {
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+    let absolute = match std::env::current_dir() {
+        Ok(cwd) => resolve_path(path, &cwd),
+        Err(_) => path.to_path_buf(),
+    };
+    let absolute = clean_path(&absolute);
+    match (absolute.parent(), absolute.file_name()) {
+        (Some(parent), Some(name)) => match parent.canonicalize() {
+            Ok(parent) => parent.join(name),
+            Err(_) => absolute,
+        },
+        _ => absolute,
+    }
+}
+
+/// Whether `path` is one of `ignored` or lies under one of them (all canonical)
+pub fn is_ignored_path(path: &Path, ignored: &[PathBuf]) -> bool {
+    ignored.iter().any(|ignored| path.starts_with(ignored))
+}
+
 // Tests moved to tests/hash/utility_tests.rs
 
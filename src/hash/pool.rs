@@ -0,0 +1,48 @@
+//! Thread control shared by the hash engines
+//!
+//! Every engine that hashes files takes `with_parallel(bool)` and `with_threads(usize)`.
+//! By default files are hashed in parallel on the global rayon pool (one thread per CPU
+//! core); a thread count gives the engine a pool of its own, and turning parallelism off
+//! hashes one file at a time on a single thread (blake3 included, which otherwise splits
+//! large files over the pool it runs on).
+
+use rayon::ThreadPool;
+use std::sync::Arc;
+
+/// Environment variable with the hashing thread count when `--threads` is not given
+/// (also used by the TUI hash menu)
+pub const HASH_THREADS_ENV: &str = "ABYSS_HASH_THREADS";
+
+/// Thread count from `ABYSS_HASH_THREADS`, if it is set to a number
+pub fn threads_from_env() -> Option<usize> {
+    std::env::var(HASH_THREADS_ENV).ok().and_then(|threads| threads.trim().parse().ok())
+}
+
+/// A dedicated pool of `threads` threads; `None` for 0, which means the global pool
+pub(crate) fn build_pool(threads: usize) -> Option<Arc<ThreadPool>> {
+    (threads > 0)
+        .then(|| rayon::ThreadPoolBuilder::new().num_threads(threads).build().ok())
+        .flatten()
+        .map(Arc::new)
+}
+
+/// The pool an engine hashes on: `pool` (or the global one) when `parallel`, else one thread
+pub(crate) fn engine_pool(parallel: bool, pool: Option<&Arc<ThreadPool>>) -> Option<Arc<ThreadPool>> {
+    if parallel {
+        pool.cloned()
+    } else {
+        build_pool(1)
+    }
+}
+
+/// Run `f` on `pool`, or on the global pool without one
+pub(crate) fn install<R, F>(pool: Option<&Arc<ThreadPool>>, f: F) -> R
+where
+    R: Send,
+    F: FnOnce() -> R + Send,
+{
+    match pool {
+        Some(pool) => pool.install(f),
+        None => f(),
+    }
+}
@@ -1,8 +1,8 @@
 // Directory scanning module
 // Handles recursive directory traversal and hash computation
 
-use super::hash::HashComputer;
-use super::database::DatabaseHandler;
+use super::hash::{HashComputer, HashKey, FAST_MODE_THRESHOLD};
+use super::database::{DatabaseHandler, DIRECTORY_HASH};
 use super::path_utils;
 use super::error::HashUtilityError;
 use super::ignore_handler::IgnoreHandler;
@@ -10,10 +10,11 @@ use std::fs::{self, File};
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
-use rayon::prelude::*;
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io::IsTerminal;
 use crossbeam_channel::{bounded, Sender};
 use jwalk::WalkDir;
 
@@ -26,6 +27,16 @@ pub struct ScanStats {
     pub files_processed: usize,
     pub files_failed: usize,
     pub total_bytes: u64,
+    /// Files at or above the fast-mode threshold (sampled when fast mode is on)
+    pub large_files: usize,
+    /// Most hashed entries held in memory at once before being written
+    pub peak_buffered_entries: usize,
+    /// Directory entries written (only with `with_include_dirs`)
+    pub directories: usize,
+    /// Most files held open for hashing at once (bounded by `with_max_open_files`)
+    pub peak_open_files: usize,
+    /// Files that failed because the process ran out of file descriptors
+    pub too_many_open_files: usize,
     #[serde(serialize_with = "serialize_duration")]
     pub duration: Duration,
 }
@@ -49,42 +60,168 @@ where
 
 use super::database::DatabaseFormat;
 
+/// Default progress bar refresh interval
+const DEFAULT_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Most hashed entries a parallel scan keeps in memory waiting to be written (unsorted output)
+pub const WRITE_QUEUE_CAPACITY: usize = 1024;
+
+/// Most files a parallel scan hashes at once unless set with `with_max_open_files`
+pub const DEFAULT_MAX_OPEN_FILES: usize = 256;
+
+/// A hashed file on its way to the output: hash, path as written, size
+type ScanResult = (String, PathBuf, u64);
+
 /// Type alias for progress callback function
 pub type ProgressCallback = Box<dyn Fn(ScanProgress) + Send + Sync>;
 
+/// Which files a scan hashes, by extension
+///
+/// Extensions are matched case-insensitively against the end of the file name, so
+/// multi-part ones like `tar.gz` work; a leading dot is optional. With include
+/// extensions set, files matching none of them (or without an extension) are skipped.
+/// Exclusions win over inclusions.
+#[derive(Debug, Clone, Default)]
+pub struct ExtensionFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl ExtensionFilter {
+    fn normalize<I, S>(extensions: I) -> Vec<String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        extensions
+            .into_iter()
+            .map(|ext| format!(".{}", ext.as_ref().trim().trim_start_matches('.').to_lowercase()))
+            .filter(|ext| ext.len() > 1)
+            .collect()
+    }
+
+    /// Whether nothing is filtered
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Whether the file at `path` should be hashed
+    pub fn matches(&self, path: &Path) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        let Some(name) = path.file_name() else {
+            return false;
+        };
+        let name = name.to_string_lossy().to_lowercase();
+        // A bare `.iso` is a hidden file named iso, not an iso image
+        let has = |ext: &String| name.len() > ext.len() && name.ends_with(ext.as_str());
+        if self.exclude.iter().any(has) {
+            return false;
+        }
+        self.include.is_empty() || self.include.iter().any(has)
+    }
+}
+
+/// Counting semaphore bounding how many files are open at once
+///
+/// Thread counts follow the CPU count, not the descriptor limit, so on wide machines
+/// (or with a low `ulimit -n`) a parallel scan could otherwise run out of descriptors.
+/// Only wait on it from plain threads: a rayon thread blocked here can hold up the
+/// work-stealing that would release a slot.
+pub struct OpenFileLimit {
+    max: usize,
+    open: Mutex<usize>,
+    released: Condvar,
+}
+
+/// A slot in an [`OpenFileLimit`], given back when dropped
+pub struct OpenFilePermit<'a> {
+    limit: &'a OpenFileLimit,
+}
+
+impl OpenFileLimit {
+    /// Allow up to `max` files open at once (at least one)
+    pub fn new(max: usize) -> Self {
+        Self { max: max.max(1), open: Mutex::new(0), released: Condvar::new() }
+    }
+
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    /// Files currently holding a permit
+    pub fn in_use(&self) -> usize {
+        *self.open.lock().unwrap()
+    }
+
+    /// Wait for a free slot and take it
+    pub fn acquire(&self) -> OpenFilePermit<'_> {
+        let mut open = self.open.lock().unwrap();
+        while *open >= self.max {
+            open = self.released.wait(open).unwrap();
+        }
+        *open += 1;
+        OpenFilePermit { limit: self }
+    }
+}
+
+impl Drop for OpenFilePermit<'_> {
+    fn drop(&mut self) {
+        *self.limit.open.lock().unwrap() -= 1;
+        self.limit.released.notify_one();
+    }
+}
+
 /// Engine for scanning directories and generating hash databases
 pub struct ScanEngine {
     computer: HashComputer,
     parallel: bool,
+    pool: Option<Arc<rayon::ThreadPool>>,
     fast_mode: bool,
     use_ignore: bool,
     format: DatabaseFormat,
     progress_callback: Option<Arc<ProgressCallback>>,
+    show_progress: bool,
+    show_summary: bool,
+    progress_interval: Duration,
+    sort_output: bool,
+    include_dirs: bool,
+    extensions: ExtensionFilter,
+    max_open_files: usize,
 }
 
 impl ScanEngine {
-    /// Create a new ScanEngine with default settings
+    /// Create a new ScanEngine with default settings, hashing in parallel on the global pool
     pub fn new() -> Self {
         Self {
             computer: HashComputer::new(),
-            parallel: false,
+            parallel: true,
+            pool: None,
             fast_mode: false,
             use_ignore: true,
             format: DatabaseFormat::Standard,
             progress_callback: None,
+            show_progress: true,
+            show_summary: true,
+            progress_interval: DEFAULT_PROGRESS_INTERVAL,
+            sort_output: false,
+            include_dirs: false,
+            extensions: ExtensionFilter::default(),
+            max_open_files: DEFAULT_MAX_OPEN_FILES,
         }
     }
     
-    /// Create a new ScanEngine with parallel processing enabled
-    pub fn with_parallel(parallel: bool) -> Self {
-        Self {
-            computer: HashComputer::new(),
-            parallel,
-            fast_mode: false,
-            use_ignore: true,
-            format: DatabaseFormat::Standard,
-            progress_callback: None,
-        }
+    /// Enable or disable parallel processing
+    pub fn with_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Hash on a dedicated pool of `threads` threads (0 uses the global rayon pool)
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.pool = super::pool::build_pool(threads);
+        self
     }
     
     /// Enable or disable fast mode for large file hashing
@@ -93,6 +230,18 @@ impl ScanEngine {
         self
     }
     
+    /// Enable or disable memory-mapped reads (see `HashComputer::with_mmap`)
+    pub fn with_mmap(mut self, use_mmap: bool) -> Self {
+        self.computer.use_mmap = use_mmap;
+        self
+    }
+    
+    /// Key material for `blake3-keyed` and `blake3-derive` (see `HashKey`)
+    pub fn with_key(mut self, key: HashKey) -> Self {
+        self.computer.key = key;
+        self
+    }
+    
     /// Enable or disable .hashignore file support
     pub fn with_ignore(mut self, use_ignore: bool) -> Self {
         self.use_ignore = use_ignore;
@@ -105,6 +254,90 @@ impl ScanEngine {
         self
     }
     
+    /// Enable or disable the progress bar (it is always hidden when stdout is not a TTY)
+    pub fn with_progress(mut self, show_progress: bool) -> Self {
+        self.show_progress = show_progress;
+        self
+    }
+    
+    /// Enable or disable the messages printed to stdout before and after a scan
+    ///
+    /// On by default for the CLI. Library callers and the TUI turn them off and read
+    /// the returned `ScanStats` (and the progress callback) instead.
+    pub fn with_summary(mut self, show_summary: bool) -> Self {
+        self.show_summary = show_summary;
+        self
+    }
+    
+    /// Record every directory (empty ones included) in the database as well as files
+    ///
+    /// Directory entries carry `DIRECTORY_HASH` instead of a digest and are written
+    /// before the files. Verify then reports a recorded directory that no longer exists.
+    pub fn with_include_dirs(mut self, include_dirs: bool) -> Self {
+        self.include_dirs = include_dirs;
+        self
+    }
+    
+    /// Only hash files with one of these extensions (e.g. `iso`, `img`, `tar.gz`)
+    ///
+    /// Applied while walking, so skipped files are never read and never reach the
+    /// database. See [`ExtensionFilter`] for the matching rules.
+    pub fn with_include_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.extensions.include = ExtensionFilter::normalize(extensions);
+        self
+    }
+    
+    /// Skip files with any of these extensions, even if they are included
+    pub fn with_exclude_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.extensions.exclude = ExtensionFilter::normalize(extensions);
+        self
+    }
+    
+    /// Sort output entries by path
+    ///
+    /// Off by default: parallel scans then write entries in completion order as they
+    /// finish, holding at most `WRITE_QUEUE_CAPACITY` in memory. Sorting a parallel scan
+    /// buffers every entry until the end, so memory grows with the file count.
+    pub fn with_sorted_output(mut self, sort_output: bool) -> Self {
+        self.sort_output = sort_output;
+        self
+    }
+    
+    /// Cap how many files a parallel scan hashes (and so holds open) at once
+    ///
+    /// Defaults to `DEFAULT_MAX_OPEN_FILES`; 0 is treated as 1. A lower cap than the
+    /// thread count also means fewer file workers. Sequential scans only ever open one file.
+    pub fn with_max_open_files(mut self, max_open_files: usize) -> Self {
+        self.max_open_files = max_open_files;
+        self
+    }
+    
+    /// Set how often the progress bar is redrawn
+    pub fn with_progress_interval(mut self, interval: Duration) -> Self {
+        self.progress_interval = interval;
+        self
+    }
+    
+    /// Create a progress bar honoring the progress settings
+    fn create_progress_bar(&self, len: u64) -> ProgressBar {
+        if !self.show_progress || !std::io::stdout().is_terminal() {
+            return ProgressBar::hidden();
+        }
+        
+        // indicatif takes a refresh rate in Hz
+        let interval_ms = self.progress_interval.as_millis().max(1);
+        let hz = (1000 / interval_ms).clamp(1, u8::MAX as u128) as u8;
+        ProgressBar::with_draw_target(Some(len), ProgressDrawTarget::stderr_with_hz(hz))
+    }
+    
     /// Set a progress callback function
     pub fn with_progress_callback<F>(mut self, callback: F) -> Self
     where
@@ -147,26 +380,39 @@ impl ScanEngine {
         };
         
         // Collect all files in the directory tree (only for sequential mode)
-        println!("Scanning directory: {}", root.display());
+        if self.show_summary {
+            println!("Scanning directory: {}", root.display());
+        }
         let files = if !self.parallel {
-            self.collect_files_with_exclusion(root, Some(&output_absolute))?
+            let mut files = self.collect_files_with_exclusion(root, Some(&output_absolute))?;
+            // Sequential scans write in file order, so sorting the list sorts the output
+            if self.sort_output {
+                files.sort();
+            }
+            files
         } else {
             // For parallel mode, we don't pre-collect files
             Vec::new()
         };
         
-        if !self.parallel {
+        if self.show_summary && !self.parallel {
             println!("Found {} files to process", files.len());
         }
         
-        if self.fast_mode {
+        if self.show_summary && self.fast_mode {
             println!("Fast mode enabled: sampling first, middle, and last 100MB of large files");
         }
         
+        let dirs = if self.include_dirs { self.collect_dirs(&canonical_root) } else { Vec::new() };
+        
         if self.parallel {
-            self.scan_parallel(&files, algorithm, output, &canonical_root, &output_absolute, start_time)
+            // Workers enter the pool per file; see `scan_parallel`
+            self.scan_parallel(&files, &dirs, algorithm, output, &canonical_root, &output_absolute, start_time)
         } else {
-            self.scan_sequential(&files, algorithm, output, &canonical_root, start_time)
+            let pool = super::pool::engine_pool(false, None);
+            super::pool::install(pool.as_ref(), || {
+                self.scan_sequential(&files, &dirs, algorithm, output, &canonical_root, start_time)
+            })
         }
     }
     
@@ -174,6 +420,7 @@ impl ScanEngine {
     fn scan_sequential(
         &self,
         files: &[PathBuf],
+        dirs: &[PathBuf],
         algorithm: &str,
         output: &Path,
         canonical_root: &Path,
@@ -192,15 +439,20 @@ impl ScanEngine {
                     HashUtilityError::from_io_error(e, "writing hashdeep header", Some(output.to_path_buf()))
                 })?;
         }
+        self.write_directories(&mut writer, algorithm, dirs).map_err(|e| {
+            HashUtilityError::from_io_error(e, "writing directory entries", Some(output.to_path_buf()))
+        })?;
         
         // Track statistics
         let mut files_processed = 0;
         let mut files_failed = 0;
         let mut files_skipped = 0;
+        let mut too_many_open_files = 0;
         let mut total_bytes = 0u64;
+        let mut large_files = 0;
         
         // Create progress bar
-        let pb = ProgressBar::new(files.len() as u64);
+        let pb = self.create_progress_bar(files.len() as u64);
         pb.set_style(
             ProgressStyle::default_bar()
                 .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files ({percent}%) | Processed: {msg}")
@@ -261,6 +513,16 @@ impl ScanEngine {
                                 &path_to_write,
                             )
                         }
+                        DatabaseFormat::Json => {
+                            DatabaseHandler::write_json_entry(
+                                &mut writer,
+                                &result.hash,
+                                algorithm,
+                                self.fast_mode,
+                                file_size,
+                                &path_to_write,
+                            )
+                        }
                     };
                     
                     if let Err(e) = write_result {
@@ -270,6 +532,9 @@ impl ScanEngine {
                     } else {
                         files_processed += 1;
                         total_bytes += file_size;
+                        if file_size >= FAST_MODE_THRESHOLD {
+                            large_files += 1;
+                        }
                         
                         // Emit progress event if callback is set
                         if let Some(ref callback) = self.progress_callback {
@@ -295,6 +560,9 @@ impl ScanEngine {
                 Err(e) => {
                     // Log error but continue processing
                     eprintln!("Warning: Failed to hash {}: {}", file_path.display(), e);
+                    if e.is_too_many_open_files() {
+                        too_many_open_files += 1;
+                    }
                     files_failed += 1;
                 }
             }
@@ -307,33 +575,28 @@ impl ScanEngine {
         // Clear progress bar and display summary
         pb.finish_and_clear();
         
-        println!("\nScan complete!");
-        println!("Files processed: {}", files_processed);
-        println!("Files failed: {}", files_failed);
-        println!("Files skipped: {}", files_skipped);
-        println!("Total bytes: {} ({:.2} MB)", total_bytes, total_bytes as f64 / 1_048_576.0);
-        println!("Duration: {:.2}s", duration.as_secs_f64());
-        
-        // Calculate and display throughput
-        if duration.as_secs_f64() > 0.0 {
-            let throughput_mbps = (total_bytes as f64 / 1_048_576.0) / duration.as_secs_f64();
-            println!("Throughput: {:.2} MB/s", throughput_mbps);
-        }
-        
-        println!("Output written to: {}", output.display());
+        self.print_summary(files_processed, files_failed, files_skipped, total_bytes, duration, output);
         
         Ok(ScanStats {
             files_processed,
             files_failed: files_failed + files_skipped,
             total_bytes,
+            large_files,
+            // Sequential scans write each entry as soon as it is hashed
+            peak_buffered_entries: 0,
+            directories: dirs.len(),
+            peak_open_files: usize::from(!files.is_empty()),
+            too_many_open_files,
             duration,
         })
     }
     
     /// Parallel scan implementation using producer-consumer pattern with jwalk and crossbeam-channel
+    #[allow(clippy::too_many_arguments)]
     fn scan_parallel(
         &self,
         _files: &[PathBuf],
+        dirs: &[PathBuf],
         algorithm: &str,
         output: &Path,
         canonical_root: &Path,
@@ -345,9 +608,16 @@ impl ScanEngine {
         let files_failed = Arc::new(Mutex::new(0usize));
         let files_skipped = Arc::new(Mutex::new(0usize));
         let total_bytes = Arc::new(Mutex::new(0u64));
+        let large_files = Arc::new(Mutex::new(0usize));
+        let too_many_open_files = AtomicUsize::new(0);
+        let open_files = OpenFileLimit::new(self.max_open_files);
+        let peak_open = AtomicUsize::new(0);
+        // More workers than open-file slots would only wait for one
+        let threads = self.pool.as_ref().map_or_else(rayon::current_num_threads, |pool| pool.current_num_threads());
+        let worker_count = threads.min(open_files.max());
         
         // Create progress bar (we'll update the style once discovery is complete)
-        let pb = ProgressBar::new(0);
+        let pb = self.create_progress_bar(0);
         // Start with "Counting..." style
         pb.set_style(
             ProgressStyle::default_bar()
@@ -370,6 +640,7 @@ impl ScanEngine {
         // Clone canonical_root and output_absolute for the walker thread
         let walker_root = canonical_root.to_path_buf();
         let use_ignore = self.use_ignore;
+        let extensions = self.extensions.clone();
         let output_to_exclude = output_absolute.to_path_buf();
         
         // Clone for walker thread
@@ -377,9 +648,15 @@ impl ScanEngine {
         let discovery_complete_walker = Arc::clone(&discovery_complete);
         let pb_walker = pb.clone();
         
+        // Open the output up front so a bad path fails before any hashing, and so it
+        // exists (and can be recognized and left out) by the time the walker gets to it
+        let output_file = File::create(output).map_err(|e| {
+            HashUtilityError::from_io_error(e, "creating output file", Some(output.to_path_buf()))
+        })?;
+        
         // Spawn walker thread using jwalk to traverse directories
         let walker_handle = thread::spawn(move || {
-            let result = Self::walk_directory_streaming(&walker_root, sender, use_ignore, Some(&output_to_exclude), Arc::clone(&total_files_discovered_walker));
+            let result = Self::walk_directory_streaming(&walker_root, sender, use_ignore, &extensions, Some(&output_to_exclude), Arc::clone(&total_files_discovered_walker));
             
             // Mark discovery as complete and update progress bar with total and new style
             let total = *total_files_discovered_walker.lock().unwrap();
@@ -400,104 +677,168 @@ impl ScanEngine {
         let files_failed_clone = Arc::clone(&files_failed);
         let files_skipped_clone = Arc::clone(&files_skipped);
         let total_bytes_clone = Arc::clone(&total_bytes);
+        let large_files_clone = Arc::clone(&large_files);
         let pb_clone = pb.clone();
         let canonical_root_clone = canonical_root.to_path_buf();
         let progress_callback_clone = self.progress_callback.clone();
         let start_time_clone = start_time;
         
-        // Use rayon's par_bridge to consume from channel in parallel
-        // This starts hashing immediately as files are discovered
-        let results: Vec<_> = receiver
-            .into_iter()
-            .par_bridge()
-            .filter_map(|file_path| {
-                // Check if file still exists and is accessible before processing
-                let metadata_check = fs::metadata(&file_path);
-                if metadata_check.is_err() {
-                    let mut skipped = files_skipped_clone.lock().unwrap();
-                    *skipped += 1;
-                    pb_clone.inc(1);
-                    return None;
+        let mut writer = BufWriter::new(output_file);
+        
+        // Write hashdeep header if using hashdeep format
+        if self.format == DatabaseFormat::Hashdeep {
+            if let Err(e) = DatabaseHandler::write_hashdeep_header(&mut writer, &[algorithm.to_string()]) {
+                eprintln!("Warning: Failed to write hashdeep header: {}", e);
+            }
+        }
+        self.write_directories(&mut writer, algorithm, dirs).map_err(|e| {
+            HashUtilityError::from_io_error(e, "writing directory entries", Some(output.to_path_buf()))
+        })?;
+        
+        // Hashed entries travel to a dedicated writer thread through a bounded channel,
+        // so at most WRITE_QUEUE_CAPACITY of them wait in memory (hashing blocks when it's full)
+        let (result_sender, result_receiver) = bounded::<ScanResult>(WRITE_QUEUE_CAPACITY);
+        let peak_buffered = AtomicUsize::new(0);
+        let format = self.format;
+        let sort_output = self.sort_output;
+        
+        let (mut writer, sorted_entries) = thread::scope(|scope| {
+            let writer_handle = scope.spawn(move || {
+                // Sorting needs every entry, so only then are they all buffered
+                let mut pending = Vec::new();
+                for result in result_receiver {
+                    if sort_output {
+                        pending.push(result);
+                    } else if let Err(e) = Self::write_result(&mut writer, format, algorithm, fast_mode, &result) {
+                        eprintln!("Warning: Failed to write entry: {}", e);
+                    }
+                }
+                pending.sort_by(|a, b| a.1.cmp(&b.1));
+                for result in &pending {
+                    if let Err(e) = Self::write_result(&mut writer, format, algorithm, fast_mode, result) {
+                        eprintln!("Warning: Failed to write entry: {}", e);
+                    }
                 }
-                
-                // Update progress bar with counts instead of filename to avoid encoding issues
-                let processed = files_processed_clone.lock().unwrap();
-                let failed = files_failed_clone.lock().unwrap();
-                let skipped = files_skipped_clone.lock().unwrap();
-                pb_clone.set_message(format!("{} OK, {} failed, {} skipped", *processed, *failed, *skipped));
-                drop(processed);
-                drop(failed);
-                drop(skipped);
-                
-                // Compute hash for the file (using fast mode if enabled)
-                let computer = HashComputer::new();
-                let hash_result = if fast_mode {
+                (writer, pending.len())
+            });
+            
+            // Plain worker threads take files off the channel as they are discovered, one
+            // file each at a time. Hashing itself may still fan out over the engine's rayon
+            // pool (blake3 does for large files); keeping the file-level work off rayon means
+            // a worker waiting for an open-file slot never blocks a rayon thread.
+            let result_sender = result_sender;
+            let hash_file = |file_path: PathBuf| {
+            // Held until the file is hashed and closed
+            let _permit = open_files.acquire();
+            peak_open.fetch_max(open_files.in_use(), Ordering::Relaxed);
+            
+            // Check if file still exists and is accessible before processing
+            let metadata_check = fs::metadata(&file_path);
+            if metadata_check.is_err() {
+                let mut skipped = files_skipped_clone.lock().unwrap();
+                *skipped += 1;
+                pb_clone.inc(1);
+                return;
+            }
+            
+            // Update progress bar with counts instead of filename to avoid encoding issues
+            let processed = files_processed_clone.lock().unwrap();
+            let failed = files_failed_clone.lock().unwrap();
+            let skipped = files_skipped_clone.lock().unwrap();
+            pb_clone.set_message(format!("{} OK, {} failed, {} skipped", *processed, *failed, *skipped));
+            drop(processed);
+            drop(failed);
+            drop(skipped);
+            
+            // Compute hash for the file (using fast mode if enabled)
+            let computer = &self.computer;
+            let hash_result = super::pool::install(self.pool.as_ref(), || {
+                if fast_mode {
                     computer.compute_hash_fast(&file_path, algorithm)
                 } else {
                     computer.compute_hash(&file_path, algorithm)
-                };
-                
-                let result = match hash_result {
-                    Ok(result) => {
-                        // Try to get relative path for cleaner database entries
-                        // Use cached version since canonical_root_clone is already canonicalized
-                        let path_to_write = match path_utils::get_relative_path_cached(&file_path, &canonical_root_clone) {
-                            Ok(rel_path) => rel_path,
-                            Err(_) => file_path.clone(),
+                }
+            });
+            
+            match hash_result {
+                Ok(result) => {
+                    // Try to get relative path for cleaner database entries
+                    // Use cached version since canonical_root_clone is already canonicalized
+                    let path_to_write = match path_utils::get_relative_path_cached(&file_path, &canonical_root_clone) {
+                        Ok(rel_path) => rel_path,
+                        Err(_) => file_path.clone(),
+                    };
+                    
+                    // Track file size
+                    let file_size = fs::metadata(&file_path).map(|m| m.len()).unwrap_or(0);
+                    *total_bytes_clone.lock().unwrap() += file_size;
+                    if file_size >= FAST_MODE_THRESHOLD {
+                        *large_files_clone.lock().unwrap() += 1;
+                    }
+                    
+                    // Update success counter
+                    let mut processed = files_processed_clone.lock().unwrap();
+                    *processed += 1;
+                    let current_processed = *processed;
+                    drop(processed);
+                    
+                    // Emit progress event if callback is set
+                    if let Some(ref callback) = progress_callback_clone {
+                        let bytes = *total_bytes_clone.lock().unwrap();
+                        let elapsed = start_time_clone.elapsed().as_secs_f64();
+                        let throughput_mbps = if elapsed > 0.0 {
+                            (bytes as f64 / 1_048_576.0) / elapsed
+                        } else {
+                            0.0
                         };
                         
-                        // Track file size
-                        if let Ok(metadata) = fs::metadata(&file_path) {
-                            let size = metadata.len();
-                            let mut bytes = total_bytes_clone.lock().unwrap();
-                            *bytes += size;
-                        }
-                        
-                        // Update success counter
-                        let mut processed = files_processed_clone.lock().unwrap();
-                        *processed += 1;
-                        let current_processed = *processed;
-                        drop(processed);
-                        
-                        // Emit progress event if callback is set
-                        if let Some(ref callback) = progress_callback_clone {
-                            let bytes = *total_bytes_clone.lock().unwrap();
-                            let elapsed = start_time_clone.elapsed().as_secs_f64();
-                            let throughput_mbps = if elapsed > 0.0 {
-                                (bytes as f64 / 1_048_576.0) / elapsed
-                            } else {
-                                0.0
-                            };
-                            
-                            callback(ScanProgress {
-                                files_processed: current_processed,
-                                current_file: file_path.file_name()
-                                    .and_then(|n| n.to_str())
-                                    .unwrap_or("unknown")
-                                    .to_string(),
-                                bytes_processed: bytes,
-                                throughput_mbps,
-                            });
-                        }
-                        
-                        Some((result.hash, path_to_write))
+                        callback(ScanProgress {
+                            files_processed: current_processed,
+                            current_file: file_path.file_name()
+                                .and_then(|n| n.to_str())
+                                .unwrap_or("unknown")
+                                .to_string(),
+                            bytes_processed: bytes,
+                            throughput_mbps,
+                        });
                     }
-                    Err(e) => {
-                        // Log error but continue processing
-                        eprintln!("Warning: Failed to hash {}: {}", file_path.display(), e);
-                        
-                        // Update failure counter
-                        let mut failed = files_failed_clone.lock().unwrap();
-                        *failed += 1;
-                        
-                        None
+                    
+                    // Only fails if the writer thread is gone, which the join below reports
+                    if result_sender.send((result.hash, path_to_write, file_size)).is_ok() {
+                        peak_buffered.fetch_max(result_sender.len(), Ordering::Relaxed);
                     }
-                };
-                
-                pb_clone.inc(1);
-                result
-            })
-            .collect();
+                }
+                Err(e) => {
+                    // Log error but continue processing
+                    eprintln!("Warning: Failed to hash {}: {}", file_path.display(), e);
+                    if e.is_too_many_open_files() {
+                        too_many_open_files.fetch_add(1, Ordering::Relaxed);
+                    }
+                    
+                    // Update failure counter
+                    let mut failed = files_failed_clone.lock().unwrap();
+                    *failed += 1;
+                }
+            }
+            
+            pb_clone.inc(1);
+            };
+            thread::scope(|workers| {
+                for _ in 0..worker_count {
+                    workers.spawn(|| receiver.iter().for_each(&hash_file));
+                }
+            });
+            
+            // Closing the channel lets the writer finish
+            drop(result_sender);
+            writer_handle.join()
+        }).map_err(|_| {
+            HashUtilityError::from_io_error(
+                std::io::Error::other("output writer thread panicked"),
+                "writing output file",
+                Some(output.to_path_buf()),
+            )
+        })?;
         
         // Wait for walker thread to complete
         // Note: The walker thread should already be done since we consumed all items from the channel
@@ -517,49 +858,6 @@ impl ScanEngine {
         // Clear progress bar
         pb.finish_and_clear();
         
-        // Write all results to output file
-        let output_file = File::create(output).map_err(|e| {
-            HashUtilityError::from_io_error(e, "creating output file", Some(output.to_path_buf()))
-        })?;
-        let mut writer = BufWriter::new(output_file);
-        
-        // Write hashdeep header if using hashdeep format
-        if self.format == DatabaseFormat::Hashdeep {
-            if let Err(e) = DatabaseHandler::write_hashdeep_header(&mut writer, &[algorithm.to_string()]) {
-                eprintln!("Warning: Failed to write hashdeep header: {}", e);
-            }
-        }
-        
-        for result in results.iter() {
-            let write_result = match self.format {
-                DatabaseFormat::Standard => {
-                    DatabaseHandler::write_entry(
-                        &mut writer,
-                        &result.0,
-                        algorithm,
-                        fast_mode,
-                        &result.1,
-                    )
-                }
-                DatabaseFormat::Hashdeep => {
-                    // Get file size
-                    let file_size = fs::metadata(&result.1)
-                        .map(|m| m.len())
-                        .unwrap_or(0);
-                    DatabaseHandler::write_hashdeep_entry(
-                        &mut writer,
-                        file_size,
-                        &[result.0.clone()],
-                        &result.1,
-                    )
-                }
-            };
-            
-            if let Err(e) = write_result {
-                eprintln!("Warning: Failed to write entry: {}", e);
-            }
-        }
-        
         // Flush the writer to ensure all data is written
         writer.flush().map_err(|e| {
             HashUtilityError::from_io_error(e, "flushing output file", Some(output.to_path_buf()))
@@ -570,29 +868,118 @@ impl ScanEngine {
         let final_failed = *files_failed.lock().unwrap();
         let final_skipped = *files_skipped.lock().unwrap();
         let final_bytes = *total_bytes.lock().unwrap();
+        let final_large = *large_files.lock().unwrap();
         
         // Display summary
+        self.print_summary(final_processed, final_failed, final_skipped, final_bytes, duration, output);
+        
+        Ok(ScanStats {
+            files_processed: final_processed,
+            files_failed: final_failed + final_skipped,
+            total_bytes: final_bytes,
+            large_files: final_large,
+            peak_buffered_entries: sorted_entries.max(peak_buffered.into_inner()),
+            directories: dirs.len(),
+            peak_open_files: peak_open.into_inner(),
+            too_many_open_files: too_many_open_files.into_inner(),
+            duration,
+        })
+    }
+    
+    /// Print the end-of-scan summary, unless turned off with `with_summary(false)`
+    fn print_summary(
+        &self,
+        processed: usize,
+        failed: usize,
+        skipped: usize,
+        total_bytes: u64,
+        duration: Duration,
+        output: &Path,
+    ) {
+        if !self.show_summary {
+            return;
+        }
         println!("\nScan complete!");
-        println!("Files processed: {}", final_processed);
-        println!("Files failed: {}", final_failed);
-        println!("Files skipped: {}", final_skipped);
-        println!("Total bytes: {} ({:.2} MB)", final_bytes, final_bytes as f64 / 1_048_576.0);
+        println!("Files processed: {}", processed);
+        println!("Files failed: {}", failed);
+        println!("Files skipped: {}", skipped);
+        println!("Total bytes: {} ({:.2} MB)", total_bytes, total_bytes as f64 / 1_048_576.0);
         println!("Duration: {:.2}s", duration.as_secs_f64());
         
         // Calculate and display throughput
         if duration.as_secs_f64() > 0.0 {
-            let throughput_mbps = (final_bytes as f64 / 1_048_576.0) / duration.as_secs_f64();
+            let throughput_mbps = (total_bytes as f64 / 1_048_576.0) / duration.as_secs_f64();
             println!("Throughput: {:.2} MB/s", throughput_mbps);
         }
         
         println!("Output written to: {}", output.display());
+    }
+    
+    /// Write one hashed file in the given database format
+    fn write_result(
+        writer: &mut impl Write,
+        format: DatabaseFormat,
+        algorithm: &str,
+        fast_mode: bool,
+        result: &ScanResult,
+    ) -> std::io::Result<()> {
+        let (hash, path, size) = result;
+        match format {
+            DatabaseFormat::Standard => {
+                DatabaseHandler::write_entry(writer, hash, algorithm, fast_mode, path)
+            }
+            DatabaseFormat::Hashdeep => {
+                DatabaseHandler::write_hashdeep_entry(writer, *size, std::slice::from_ref(hash), path)
+            }
+            DatabaseFormat::Json => {
+                DatabaseHandler::write_json_entry(writer, hash, algorithm, fast_mode, *size, path)
+            }
+        }
+    }
+    
+    /// Write a `DIRECTORY_HASH` entry for each of `dirs` (paths relative to the scan root)
+    fn write_directories(&self, writer: &mut impl Write, algorithm: &str, dirs: &[PathBuf]) -> std::io::Result<()> {
+        for dir in dirs {
+            let entry = (DIRECTORY_HASH.to_string(), dir.clone(), 0);
+            Self::write_result(writer, self.format, algorithm, self.fast_mode, &entry)?;
+        }
+        Ok(())
+    }
+    
+    /// Every directory below `root` (relative to it, sorted), skipping ignored directories
+    /// and everything under them, and not following symlinks
+    fn collect_dirs(&self, root: &Path) -> Vec<PathBuf> {
+        let ignore_handler = if self.use_ignore {
+            IgnoreHandler::new(root).ok()
+        } else {
+            None
+        };
         
-        Ok(ScanStats {
-            files_processed: final_processed,
-            files_failed: final_failed + final_skipped,
-            total_bytes: final_bytes,
-            duration,
-        })
+        let mut dirs = Vec::new();
+        let mut pending = vec![root.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    eprintln!("Warning: Cannot read directory {}: {}", dir.display(), e);
+                    continue;
+                }
+            };
+            for entry in entries.flatten() {
+                if !entry.file_type().is_ok_and(|t| t.is_dir()) {
+                    continue;
+                }
+                let path = entry.path();
+                let Ok(rel_path) = path.strip_prefix(root) else { continue };
+                if ignore_handler.as_ref().is_some_and(|h| h.should_ignore(rel_path, true)) {
+                    continue;
+                }
+                dirs.push(rel_path.to_path_buf());
+                pending.push(path);
+            }
+        }
+        dirs.sort();
+        dirs
     }
     
     /// Walk directory using jwalk and send file paths to channel as they're discovered
@@ -601,6 +988,7 @@ impl ScanEngine {
         root: &Path,
         sender: Sender<PathBuf>,
         use_ignore: bool,
+        extensions: &ExtensionFilter,
         exclude_file: Option<&Path>,
         total_files_discovered: Arc<Mutex<usize>>,
     ) -> Result<(), ScanError> {
@@ -633,8 +1021,8 @@ impl ScanEngine {
                 Ok(entry) => {
                     let path = entry.path();
                     
-                    // Only process regular files
-                    if !entry.file_type().is_file() {
+                    // Only process regular files with a wanted extension
+                    if !entry.file_type().is_file() || !extensions.matches(&path) {
                         continue;
                     }
                     
@@ -809,8 +1197,10 @@ impl ScanEngine {
             }
             
             if metadata.is_file() {
-                // Add regular files to the list
-                files.push(path);
+                // Add regular files with a wanted extension to the list
+                if self.extensions.matches(&path) {
+                    files.push(path);
+                }
             } else if is_dir {
                 // Recursively process subdirectories with cached exclude path
                 if let Err(e) = self.collect_files_recursive_with_cache(root, &path, files, ignore_handler, exclude_file, canonical_exclude_cache) {
@@ -0,0 +1,148 @@
+// Directory tree hash module
+// Folds the hash of every file under a directory into a single digest
+
+use super::error::HashUtilityError;
+use super::hash::{HashComputer, HashKey};
+use super::scan::ScanEngine;
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// One digest standing for a whole directory
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TreeHash {
+    pub root: PathBuf,
+    pub algorithm: String,
+    /// Lowercase hex
+    pub hash: String,
+    pub files: usize,
+    pub total_bytes: u64,
+}
+
+impl TreeHash {
+    /// `hash  root` (like `sha256sum`) followed by a summary line
+    pub fn to_plain_text(&self) -> String {
+        format!(
+            "{}  {}\n{} files, {} bytes ({})\n",
+            self.hash,
+            self.root.display(),
+            self.files,
+            self.total_bytes,
+            self.algorithm
+        )
+    }
+}
+
+/// Engine computing directory tree hashes
+///
+/// Every regular file is hashed, the `relative/path` + digest pairs are sorted by path,
+/// and the sorted list is hashed again with the same algorithm. Identical trees give the
+/// same digest however they were walked; changing, adding, removing or renaming any file
+/// changes it. Empty directories do not contribute.
+pub struct TreeHashEngine {
+    computer: HashComputer,
+    parallel: bool,
+    pool: Option<Arc<rayon::ThreadPool>>,
+    use_ignore: bool,
+}
+
+impl TreeHashEngine {
+    /// Create a new TreeHashEngine hashing files in parallel on the global pool
+    pub fn new() -> Self {
+        Self {
+            computer: HashComputer::new(),
+            parallel: true,
+            pool: None,
+            use_ignore: true,
+        }
+    }
+
+    /// Enable or disable parallel processing
+    pub fn with_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Hash on a dedicated pool of `threads` threads (0 uses the global rayon pool)
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.pool = super::pool::build_pool(threads);
+        self
+    }
+
+    /// Enable or disable .hashignore file support
+    pub fn with_ignore(mut self, use_ignore: bool) -> Self {
+        self.use_ignore = use_ignore;
+        self
+    }
+
+    /// Enable or disable memory-mapped reads (see `HashComputer::with_mmap`)
+    pub fn with_mmap(mut self, use_mmap: bool) -> Self {
+        self.computer.use_mmap = use_mmap;
+        self
+    }
+
+    /// Key material for `blake3-keyed` and `blake3-derive` (see `HashKey`)
+    pub fn with_key(mut self, key: HashKey) -> Self {
+        self.computer.key = key;
+        self
+    }
+
+    /// Hash every file under `root` and fold the results into one digest
+    ///
+    /// Any file that can't be read fails the whole hash, since a digest over part of
+    /// the tree would silently verify the wrong thing.
+    pub fn hash_tree(&self, root: &Path, algorithm: &str) -> Result<TreeHash, HashUtilityError> {
+        self.computer.hasher(algorithm)?;
+        if !root.is_dir() {
+            return Err(HashUtilityError::DirectoryNotFound { path: root.to_path_buf() });
+        }
+
+        let files = ScanEngine::new().with_ignore(self.use_ignore).collect_files(root)?;
+        let hash_one = |path: &PathBuf| -> Result<(String, String, u64), HashUtilityError> {
+            let result = self.computer.compute_hash(path, algorithm)?;
+            let size = std::fs::metadata(path)?.len();
+            Ok((relative_key(root, path), result.hash.to_lowercase(), size))
+        };
+        let pool = super::pool::engine_pool(self.parallel, self.pool.as_ref());
+        let mut entries: Vec<(String, String, u64)> = super::pool::install(pool.as_ref(), || {
+            if self.parallel {
+                files.par_iter().map(hash_one).collect::<Result<_, _>>()
+            } else {
+                files.iter().map(hash_one).collect::<Result<_, _>>()
+            }
+        })?;
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(TreeHash {
+            root: root.to_path_buf(),
+            algorithm: algorithm.to_lowercase(),
+            hash: self.computer.compute_hash_text(&manifest(&entries), algorithm)?.hash.to_lowercase(),
+            files: entries.len(),
+            total_bytes: entries.iter().map(|(_, _, size)| size).sum(),
+        })
+    }
+}
+
+impl Default for TreeHashEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Path relative to `root` with `/` separators, so the digest is the same on every platform
+fn relative_key(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The sorted `path\0digest\n` lines that the tree digest is taken over
+fn manifest(entries: &[(String, String, u64)]) -> String {
+    entries
+        .iter()
+        .map(|(path, digest, _)| format!("{}\0{}\n", path, digest))
+        .collect()
+}
@@ -6,8 +6,8 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
-use super::database::{DatabaseHandler, DatabaseEntry};
-use super::hash::HashComputer;
+use super::database::{DatabaseHandler, DatabaseEntry, UNKNOWN_ALGORITHM};
+use super::hash::{HashComputer, HashKey};
 use super::path_utils;
 use super::error::HashUtilityError;
 use indicatif::{ProgressBar, ProgressStyle};
@@ -28,6 +28,8 @@ pub struct Mismatch {
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct VerifyProgress {
     pub files_checked: usize,
+    /// Database entries being checked
+    pub total_files: usize,
     pub current_file: String,
     pub matches: usize,
     pub mismatches: usize,
@@ -45,74 +47,82 @@ pub struct VerifyReport {
 impl VerifyReport {
     /// Display a detailed report of verification results
     pub fn display(&self) {
+        print!("{}", self.to_plain_text());
+    }
+    
+    /// Format the verification report as plain text string
+    pub fn to_plain_text(&self) -> String {
+        let mut output = String::new();
+        
         // Determine overall status
         let has_issues = !self.mismatches.is_empty() 
             || !self.missing_files.is_empty() 
             || !self.new_files.is_empty();
         
         // Display clear status banner
-        println!("\n================================================================");
+        output.push_str("\n================================================================\n");
         if has_issues {
-            println!("                  FILE CHANGES DETECTED                         ");
+            output.push_str("                  FILE CHANGES DETECTED                         \n");
         } else {
-            println!("                       ALL GOOD                                 ");
+            output.push_str("                       ALL GOOD                                 \n");
         }
-        println!("================================================================\n");
+        output.push_str("================================================================\n\n");
         
         // Display summary counts
-        println!("Verification Summary:");
-        println!("  Matches:        {}", self.matches);
-        println!("  Mismatches:     {}", self.mismatches.len());
-        println!("  Missing files:  {}", self.missing_files.len());
-        println!("  New files:      {}", self.new_files.len());
+        output.push_str("Verification Summary:\n");
+        output.push_str(&format!("  Matches:        {}\n", self.matches));
+        output.push_str(&format!("  Mismatches:     {}\n", self.mismatches.len()));
+        output.push_str(&format!("  Missing files:  {}\n", self.missing_files.len()));
+        output.push_str(&format!("  New files:      {}\n", self.new_files.len()));
         
         // If everything is good, show success message and return
         if !has_issues {
-            println!("\nAll files match the database. No changes detected.");
+            output.push_str("\nAll files match the database. No changes detected.\n");
             let total_checked = self.matches + self.mismatches.len();
-            println!("Total files verified: {}", total_checked);
-            return;
+            output.push_str(&format!("Total files verified: {}\n", total_checked));
+            return output;
         }
         
         // Show detailed information about issues
         if !self.mismatches.is_empty() {
-            println!("\n--- Files with Changed Hashes ({}) ---", self.mismatches.len());
+            output.push_str(&format!("\n--- Files with Changed Hashes ({}) ---\n", self.mismatches.len()));
             for mismatch in &self.mismatches {
-                println!();
-                println!("  File: {}", mismatch.path.display());
-                println!("    Expected: {}", mismatch.expected);
-                println!("    Actual:   {}", mismatch.actual);
+                output.push('\n');
+                output.push_str(&format!("  File: {}\n", mismatch.path.display()));
+                output.push_str(&format!("    Expected: {}\n", mismatch.expected));
+                output.push_str(&format!("    Actual:   {}\n", mismatch.actual));
             }
-            println!("----------------------------------------------------------------");
+            output.push_str("----------------------------------------------------------------\n");
         }
         
         if !self.missing_files.is_empty() {
-            println!("\n--- Deleted Files ({}) ---", self.missing_files.len());
-            println!("(in database but not in filesystem)");
+            output.push_str(&format!("\n--- Deleted Files ({}) ---\n", self.missing_files.len()));
+            output.push_str("(in database but not in filesystem)\n");
             for path in &self.missing_files {
-                println!("  - {}", path.display());
+                output.push_str(&format!("  - {}\n", path.display()));
             }
-            println!("----------------------------------------------------------------");
+            output.push_str("----------------------------------------------------------------\n");
         }
         
         if !self.new_files.is_empty() {
-            println!("\n--- New Files ({}) ---", self.new_files.len());
-            println!("(in filesystem but not in database)");
+            output.push_str(&format!("\n--- New Files ({}) ---\n", self.new_files.len()));
+            output.push_str("(in filesystem but not in database)\n");
             for path in &self.new_files {
-                println!("  + {}", path.display());
+                output.push_str(&format!("  + {}\n", path.display()));
             }
-            println!("----------------------------------------------------------------");
+            output.push_str("----------------------------------------------------------------\n");
         }
         
         // Final summary
-        println!("\n================================================================");
+        output.push_str("\n================================================================\n");
         let total_checked = self.matches + self.mismatches.len();
         let total_in_db = total_checked + self.missing_files.len();
         let total_in_fs = total_checked + self.new_files.len();
-        println!("Total files checked:      {}", total_checked);
-        println!("Total files in database:  {}", total_in_db);
-        println!("Total files in filesystem: {}", total_in_fs);
-        println!("================================================================");
+        output.push_str(&format!("Total files checked:      {}\n", total_checked));
+        output.push_str(&format!("Total files in database:  {}\n", total_in_db));
+        output.push_str(&format!("Total files in filesystem: {}\n", total_in_fs));
+        output.push_str("================================================================\n");
+        output
     }
 }
 
@@ -123,7 +133,11 @@ pub type ProgressCallback = Box<dyn Fn(VerifyProgress) + Send + Sync>;
 pub struct VerifyEngine {
     computer: HashComputer,
     parallel: bool,
+    pool: Option<Arc<rayon::ThreadPool>>,
     progress_callback: Option<Arc<ProgressCallback>>,
+    show_progress: bool,
+    ignored_paths: Vec<PathBuf>,
+    algorithm: Option<String>,
 }
 
 impl VerifyEngine {
@@ -132,17 +146,47 @@ impl VerifyEngine {
         Self {
             computer: HashComputer::new(),
             parallel: true,
+            pool: None,
             progress_callback: None,
+            show_progress: true,
+            ignored_paths: Vec::new(),
+            algorithm: None,
         }
     }
     
-    /// Create a new VerifyEngine with parallel processing control
-    pub fn with_parallel(parallel: bool) -> Self {
-        Self {
-            computer: HashComputer::new(),
-            parallel,
-            progress_callback: None,
-        }
+    /// Enable or disable parallel processing
+    pub fn with_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Hash on a dedicated pool of `threads` threads (0 uses the global rayon pool)
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.pool = super::pool::build_pool(threads);
+        self
+    }
+    
+    /// Enable or disable memory-mapped reads (see `HashComputer::with_mmap`)
+    pub fn with_mmap(mut self, use_mmap: bool) -> Self {
+        self.computer.use_mmap = use_mmap;
+        self
+    }
+    
+    /// Key material for `blake3-keyed` and `blake3-derive` (see `HashKey`)
+    pub fn with_key(mut self, key: HashKey) -> Self {
+        self.computer.key = key;
+        self
+    }
+    
+    /// Algorithm for entries that don't record one
+    ///
+    /// It also replaces an algorithm only guessed from the hash length (a 64-digit hash
+    /// in a headerless hashdeep file may be blake3 rather than sha256). Otherwise the
+    /// algorithm is read from the database, and a database written with a different one
+    /// is rejected rather than reported as all mismatches.
+    pub fn with_algorithm(mut self, algorithm: impl Into<String>) -> Self {
+        self.algorithm = Some(algorithm.into());
+        self
     }
     
     /// Set a progress callback function
@@ -154,8 +198,57 @@ impl VerifyEngine {
         self
     }
     
+    /// Enable or disable the progress bar (always hidden when stdout is not a TTY)
+    ///
+    /// The callback set with `with_progress_callback` is called either way.
+    pub fn with_progress(mut self, show_progress: bool) -> Self {
+        self.show_progress = show_progress;
+        self
+    }
+    
+    /// Progress bar over `len` database entries, honoring `show_progress`
+    fn create_progress_bar(&self, len: u64) -> ProgressBar {
+        use std::io::IsTerminal;
+        if !self.show_progress || !std::io::stdout().is_terminal() {
+            return ProgressBar::hidden();
+        }
+        let pb = ProgressBar::new(len);
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files ({percent}%) | {msg}")
+                .unwrap()
+                .progress_chars("=>-")
+        );
+        pb
+    }
+    
+    /// Leave these files (or everything under these directories) out of the report
+    ///
+    /// Useful for other databases or reports kept inside the verified tree. Paths
+    /// that don't exist yet are accepted, so a report can be ignored before it is written.
+    pub fn with_ignored_paths<I, P>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        self.ignored_paths
+            .extend(paths.into_iter().map(|p| path_utils::canonicalize_lenient(p.as_ref())));
+        self
+    }
+
     /// Verify directory contents against a hash database
     /// 
+    /// The database file itself and any ignored paths are left out on both sides:
+    /// they are never reported as new, and entries recorded for them are skipped.
+    /// 
+    /// Directory entries (from `ScanEngine::with_include_dirs`) count as matches when
+    /// the directory still exists and as missing when it doesn't. Only a database that
+    /// has directory entries gets directories reported as new.
+    /// 
+    /// The algorithm comes from the database itself (per entry in the standard and JSON
+    /// formats, from the header in hashdeep files); a database that mixes algorithms or
+    /// leaves them out is an error unless `with_algorithm` names one for the latter.
+    /// 
     /// This function:
     /// 1. Loads the hash database from the specified file
     /// 2. Recursively scans the directory to find all files
@@ -182,25 +275,71 @@ impl VerifyEngine {
         }
         
         // Load the hash database
-        let database = DatabaseHandler::read_database(database_path)?;
+        let mut database = DatabaseHandler::read_database(database_path)?;
+        
+        // Recompute with the algorithm the database was written with
+        if let Some(ref named) = self.algorithm {
+            for entry in database.values_mut().filter(|e| e.inferred || e.algorithm == UNKNOWN_ALGORITHM) {
+                entry.algorithm = named.clone();
+                entry.inferred = false;
+            }
+        }
+        if let Some(recorded) = DatabaseHandler::database_algorithm(&database, database_path)? {
+            if let Some(ref named) = self.algorithm {
+                if !named.eq_ignore_ascii_case(&recorded) {
+                    return Err(HashUtilityError::DatabaseAlgorithm {
+                        path: database_path.to_path_buf(),
+                        reason: format!("it was written with {}, not {}", recorded, named),
+                    });
+                }
+            }
+            // An unsupported algorithm or missing key fails once, not for every file
+            self.computer.hasher(&recorded)?;
+        }
         
-        // Get canonical path of database file to exclude it from scan
-        let database_canonical = database_path.canonicalize().ok();
+        // The database can't describe itself, so it is ignored along with the user's paths
+        let mut ignored = self.ignored_paths.clone();
+        ignored.push(path_utils::canonicalize_lenient(database_path));
         
-        // Collect all files in the directory (as canonical paths), excluding the database file
+        // Collect all files in the directory (as canonical paths), excluding ignored ones
         let mut current_files = self.collect_files_optimized(directory)?;
-        if let Some(db_path) = &database_canonical {
-            current_files.remove(db_path);
-        }
+        current_files.retain(|path| !path_utils::is_ignored_path(path, &ignored));
         
         // Convert database paths to canonical for comparison (optimized with caching)
-        let database_canonical = self.resolve_database_paths_optimized(&database, directory)?;
+        let mut database_canonical = self.resolve_database_paths_optimized(&database, directory)?;
+        database_canonical.retain(|path, _| !path_utils::is_ignored_path(path, &ignored));
         
-        if self.parallel {
-            self.verify_parallel(database_canonical, current_files)
-        } else {
-            self.verify_sequential(database_canonical, current_files)
+        // Directories have nothing to hash, so they are checked apart from the files
+        let recorded_dirs: HashSet<PathBuf> = database_canonical
+            .iter()
+            .filter(|(_, entry)| entry.is_directory())
+            .map(|(path, _)| path.clone())
+            .collect();
+        database_canonical.retain(|_, entry| !entry.is_directory());
+        
+        let pool = super::pool::engine_pool(self.parallel, self.pool.as_ref());
+        let mut report = super::pool::install(pool.as_ref(), || {
+            if self.parallel {
+                self.verify_parallel(database_canonical, current_files)
+            } else {
+                self.verify_sequential(database_canonical, current_files)
+            }
+        })?;
+        
+        if !recorded_dirs.is_empty() {
+            let mut current_dirs = self.collect_dirs(directory);
+            current_dirs.retain(|path| !path_utils::is_ignored_path(path, &ignored));
+            for dir in &recorded_dirs {
+                if current_dirs.contains(dir) {
+                    report.matches += 1;
+                } else {
+                    report.missing_files.push(dir.clone());
+                }
+            }
+            report.new_files.extend(current_dirs.difference(&recorded_dirs).cloned());
         }
+        
+        Ok(report)
     }
     
     /// Sequential verification implementation
@@ -215,15 +354,10 @@ impl VerifyEngine {
         let mut missing_files = Vec::new();
         let mut checked_files = HashSet::new();
         let mut files_checked = 0;
+        let total_files = database_canonical.len();
         
         // Create progress bar
-        let pb = ProgressBar::new(database_canonical.len() as u64);
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files ({percent}%) | {msg}")
-                .unwrap()
-                .progress_chars("=>-")
-        );
+        let pb = self.create_progress_bar(total_files as u64);
         
         // Check each file in the database
         for (db_path, entry) in &database_canonical {
@@ -270,6 +404,7 @@ impl VerifyEngine {
             if let Some(ref callback) = self.progress_callback {
                 callback(VerifyProgress {
                     files_checked,
+                    total_files,
                     current_file: file_name.to_string(),
                     matches,
                     mismatches: mismatches.len(),
@@ -308,15 +443,10 @@ impl VerifyEngine {
         let mismatches = Arc::new(Mutex::new(Vec::new()));
         let missing_files = Arc::new(Mutex::new(Vec::new()));
         let files_checked = Arc::new(Mutex::new(0usize));
+        let total_files = database_canonical.len();
         
         // Create progress bar
-        let pb = ProgressBar::new(database_canonical.len() as u64);
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files ({percent}%) | {msg}")
-                .unwrap()
-                .progress_chars("=>-")
-        );
+        let pb = self.create_progress_bar(total_files as u64);
         
         // Clone Arc references for use in parallel closure
         let matches_clone = Arc::clone(&matches);
@@ -343,7 +473,7 @@ impl VerifyEngine {
             
             if current_files.contains(*db_path) {
                 // File exists, compute current hash using the mode specified in the database
-                let computer = HashComputer::new();
+                let computer = &self.computer;
                 let hash_result = if entry.fast_mode {
                     computer.compute_hash_fast(db_path, &entry.algorithm)
                 } else {
@@ -387,6 +517,7 @@ impl VerifyEngine {
                 
                 callback(VerifyProgress {
                     files_checked: current_checked,
+                    total_files,
                     current_file: file_name.to_string(),
                     matches,
                     mismatches,
@@ -459,6 +590,22 @@ impl VerifyEngine {
         Ok(files)
     }
     
+    /// Every directory below `directory` (canonical paths), not following symlinks
+    fn collect_dirs(&self, directory: &Path) -> HashSet<PathBuf> {
+        use jwalk::WalkDir;
+        
+        WalkDir::new(directory)
+            .parallelism(jwalk::Parallelism::RayonNewPool(0))
+            .skip_hidden(false)
+            .follow_links(false)
+            .min_depth(1)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_dir())
+            .filter_map(|entry| entry.path().canonicalize().ok())
+            .collect()
+    }
+    
     /// Legacy method for backward compatibility
     fn collect_files(&self, directory: &Path) -> Result<HashSet<PathBuf>, VerifyError> {
         self.collect_files_optimized(directory)
@@ -1,6 +1,7 @@
 // Wildcard pattern expansion module
 // Handles cross-platform wildcard pattern matching using glob
 
+use globset::{GlobBuilder, GlobMatcher};
 use std::path::PathBuf;
 use super::error::HashUtilityError;
 
@@ -11,6 +12,7 @@ use super::error::HashUtilityError;
 /// - `file?.bin` - matches file1.bin, fileA.bin, etc.
 /// - `[abc]*.jpg` - matches files starting with a, b, or c
 /// - `data/*/hashes` - matches hashes file in any subdirectory of data
+/// - `*.{md,txt}` - matches all .md and .txt files
 /// 
 /// # Arguments
 /// * `pattern` - The wildcard pattern to expand
@@ -27,26 +29,28 @@ pub fn expand_pattern(pattern: &str) -> Result<Vec<PathBuf>, HashUtilityError> {
         return Ok(vec![PathBuf::from(pattern)]);
     }
     
-    // Use glob to expand the pattern
+    // glob has no brace support, so expand each alternative separately
     let mut matches = Vec::new();
     
-    match glob::glob(pattern) {
-        Ok(paths) => {
-            for entry in paths {
-                match entry {
-                    Ok(path) => matches.push(path),
-                    Err(e) => {
-                        return Err(HashUtilityError::InvalidArguments {
-                            message: format!("Error reading glob pattern '{}': {}", pattern, e),
-                        });
+    for alternative in expand_braces(pattern) {
+        match glob::glob(&alternative) {
+            Ok(paths) => {
+                for entry in paths {
+                    match entry {
+                        Ok(path) => matches.push(path),
+                        Err(e) => {
+                            return Err(HashUtilityError::InvalidArguments {
+                                message: format!("Error reading glob pattern '{}': {}", pattern, e),
+                            });
+                        }
                     }
                 }
             }
-        }
-        Err(e) => {
-            return Err(HashUtilityError::InvalidArguments {
-                message: format!("Invalid glob pattern '{}': {}", pattern, e),
-            });
+            Err(e) => {
+                return Err(HashUtilityError::InvalidArguments {
+                    message: format!("Invalid glob pattern '{}': {}", pattern, e),
+                });
+            }
         }
     }
     
@@ -57,15 +61,93 @@ pub fn expand_pattern(pattern: &str) -> Result<Vec<PathBuf>, HashUtilityError> {
         });
     }
     
-    // Sort matches for consistent ordering
+    // Sort matches for consistent ordering; overlapping alternatives can repeat a path
     matches.sort();
+    matches.dedup();
     
     Ok(matches)
 }
 
 /// Check if a string contains wildcard characters
 pub fn contains_wildcard(s: &str) -> bool {
-    s.contains('*') || s.contains('?') || s.contains('[')
+    s.contains('*') || s.contains('?') || s.contains('[') || s.contains('{')
+}
+
+/// Expand `{a,b}` alternatives into one pattern per combination
+///
+/// `src/{lib,main}.rs` becomes `src/lib.rs` and `src/main.rs`; groups may nest.
+/// Braces without a comma or without a closing brace are kept literally.
+pub fn expand_braces(pattern: &str) -> Vec<String> {
+    let mut depth = 0;
+    let mut open = 0;
+    let mut commas = Vec::new();
+    for (i, c) in pattern.char_indices() {
+        match c {
+            '{' => {
+                if depth == 0 {
+                    open = i;
+                    commas.clear();
+                }
+                depth += 1;
+            }
+            ',' if depth == 1 => commas.push(i),
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 && !commas.is_empty() {
+                    let (prefix, suffix) = (&pattern[..open], &pattern[i + 1..]);
+                    let mut bounds = vec![open];
+                    bounds.extend(&commas);
+                    bounds.push(i);
+                    return bounds
+                        .windows(2)
+                        .flat_map(|w| expand_braces(&format!("{}{}{}", prefix, &pattern[w[0] + 1..w[1]], suffix)))
+                        .collect();
+                }
+            }
+            _ => {}
+        }
+    }
+    vec![pattern.to_string()]
+}
+
+/// Compile a pattern matched against bare entry names (e.g. when marking files in a pane)
+///
+/// Supports `*`, `?`, character classes like `[0-9]` and alternatives like `*.{tmp,cache}`.
+/// A pattern without wildcards matches the name exactly.
+pub fn name_pattern(pattern: &str) -> Result<GlobMatcher, HashUtilityError> {
+    build_name_pattern(pattern, false)
+}
+
+/// Like [`name_pattern`], ignoring case
+pub fn name_pattern_ignore_case(pattern: &str) -> Result<GlobMatcher, HashUtilityError> {
+    build_name_pattern(pattern, true)
+}
+
+/// [`name_pattern`] or [`name_pattern_ignore_case`], picked by a setting
+pub fn build_name_pattern(pattern: &str, case_insensitive: bool) -> Result<GlobMatcher, HashUtilityError> {
+    GlobBuilder::new(pattern.trim())
+        .literal_separator(true)
+        .case_insensitive(case_insensitive)
+        .build()
+        .map(|glob| glob.compile_matcher())
+        .map_err(|e| HashUtilityError::InvalidArguments {
+            message: format!("Invalid glob pattern '{}': {}", pattern, e),
+        })
+}
+
+/// The case setting in `ABYSS_IGNORE_CASE`, if it holds one
+pub fn ignore_case_from_env() -> Option<bool> {
+    match std::env::var("ABYSS_IGNORE_CASE").as_deref() {
+        Ok("1") | Ok("true") | Ok("yes") => Some(true),
+        Ok("0") | Ok("false") | Ok("no") => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether name patterns ignore case when nothing else says: `ABYSS_IGNORE_CASE` if set,
+/// otherwise on macOS and Windows, whose filesystems usually do too
+pub fn ignore_case_default() -> bool {
+    ignore_case_from_env().unwrap_or(cfg!(any(target_os = "macos", target_os = "windows")))
 }
 
 // Tests moved to tests/hash/utility_tests.rs
@@ -1,32 +1,92 @@
 use anyhow::Result;
+use kube::config::{KubeConfigOptions, Kubeconfig};
 use kube::{Client, Config};
 
+/// Kubernetes connection settings chosen at startup
+#[derive(Debug, Clone, Default)]
+pub struct K8sOptions {
+    /// kubeconfig context to connect to (`None` = current context)
+    pub context: Option<String>,
+    /// Namespace for direct PV access (`None` = "default")
+    pub pv_namespace: Option<String>,
+}
+
+impl K8sOptions {
+    /// Read `ABYSS_KUBE_CONTEXT` and `ABYSS_PV_NAMESPACE`
+    pub fn from_env() -> Self {
+        Self {
+            context: std::env::var("ABYSS_KUBE_CONTEXT").ok().filter(|s| !s.is_empty()),
+            pv_namespace: std::env::var("ABYSS_PV_NAMESPACE").ok().filter(|s| !s.is_empty()),
+        }
+    }
+}
+
 pub struct K8sClient {
     client: Client,
     current_namespace: String,
+    context: Option<String>,
 }
 
 impl K8sClient {
+    /// Connect using the kubeconfig's current context (or in-cluster config)
     pub async fn new() -> Result<Self> {
-        let config = Config::infer().await?;
+        Self::with_context(None).await
+    }
+
+    /// Connect using a named kubeconfig context, or the default one if `None`
+    pub async fn with_context(context: Option<&str>) -> Result<Self> {
+        let config = match context {
+            Some(name) => {
+                let options = KubeConfigOptions {
+                    context: Some(name.to_string()),
+                    ..Default::default()
+                };
+                Config::from_kubeconfig(&options).await?
+            }
+            None => Config::infer().await?,
+        };
         let namespace = config.default_namespace.clone();
         let client = Client::try_from(config)?;
-        
+
+        // Remember which context we ended up on for display
+        let context = context
+            .map(str::to_string)
+            .or_else(|| Kubeconfig::read().ok().and_then(|k| k.current_context));
+
         Ok(Self {
             client,
             current_namespace: namespace,
+            context,
         })
     }
-    
+
     pub fn client(&self) -> Client {
         self.client.clone()
     }
-    
+
     pub fn current_namespace(&self) -> &str {
         &self.current_namespace
     }
-    
+
     pub fn set_namespace(&mut self, namespace: String) {
         self.current_namespace = namespace;
     }
+
+    /// Name of the kubeconfig context in use (`None` for in-cluster config)
+    pub fn context(&self) -> Option<&str> {
+        self.context.as_deref()
+    }
+}
+
+/// List the contexts in the kubeconfig along with the current one.
+///
+/// Returns an empty list when there is no kubeconfig (e.g. running in-cluster).
+pub fn available_contexts() -> (Vec<String>, Option<String>) {
+    match Kubeconfig::read() {
+        Ok(config) => (
+            config.contexts.into_iter().map(|c| c.name).collect(),
+            config.current_context,
+        ),
+        Err(_) => (Vec::new(), None),
+    }
 }
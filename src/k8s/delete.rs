@@ -0,0 +1,218 @@
+//! Recursive deletes in a PVC through the helper pod, with progress and cancellation
+//!
+//! The tree is counted first, one number per top-level entry, so the progress total
+//! covers every file and directory below the deleted path. Entries are then removed one
+//! `rm -rf` at a time, which is where a cancel takes effect.
+
+use super::pod::PodExec;
+use crate::fs::backend::DeleteProgress;
+use crate::fs::error::{FsError, FsResult};
+
+/// Print `dir` for a directory, then the object count and path of each entry in it
+/// (hidden ones included); print nothing for anything else
+const COUNT_SCRIPT: &str = r#"[ -d "$1" ] && [ ! -L "$1" ] || exit 0
+echo dir
+for entry in "$1"/* "$1"/.[!.]* "$1"/..?*; do
+    if [ -e "$entry" ] || [ -L "$entry" ]; then
+        printf '%s\t%s\n' "$(find "$entry" | wc -l)" "$entry"
+    fi
+done"#;
+
+/// Delete `path` in the PVC, counting every removed file and directory in `progress`
+/// and stopping between top-level entries once it is cancelled
+pub async fn delete_with_progress<E: PodExec + ?Sized>(
+    exec: &E,
+    namespace: &str,
+    pvc: &str,
+    path: &str,
+    progress: &DeleteProgress,
+) -> FsResult<()> {
+    progress.check_cancelled()?;
+    let pod = exec
+        .ensure_pod(namespace, pvc)
+        .await
+        .map_err(|e| FsError::from(e.context(format!("Failed to start helper pod for PVC {}", pvc))))?;
+
+    let path = match path.trim_end_matches('/') {
+        "" => "/",
+        trimmed => trimmed,
+    };
+    let count = vec!["sh".to_string(), "-c".to_string(), COUNT_SCRIPT.to_string(), "sh".to_string(), path.to_string()];
+    let output = exec.exec_long(namespace, &pod, count).await?;
+    if !output.success {
+        return Err(FsError::from(anyhow::anyhow!("Failed to count {}: {}", path, output.stderr.trim())));
+    }
+
+    let children = parse_counts(&output.stdout);
+    progress.add_total(children.as_ref().map_or(0, |children| children.iter().map(|(count, _)| count).sum()) + 1);
+    for (count, child) in children.unwrap_or_default() {
+        progress.check_cancelled()?;
+        remove(exec, namespace, &pod, &child).await?;
+        progress.add_deleted(count);
+    }
+    progress.check_cancelled()?;
+    remove(exec, namespace, &pod, path).await?;
+    progress.add_deleted(1);
+    Ok(())
+}
+
+async fn remove<E: PodExec + ?Sized>(exec: &E, namespace: &str, pod: &str, path: &str) -> FsResult<()> {
+    let output = exec
+        .exec_long(namespace, pod, vec!["rm".to_string(), "-rf".to_string(), path.to_string()])
+        .await?;
+    if !output.success {
+        return Err(FsError::from(anyhow::anyhow!("Failed to delete {}: {}", path, output.stderr.trim())));
+    }
+    Ok(())
+}
+
+/// Entries of a directory with their object counts; `None` when the path isn't a directory
+fn parse_counts(stdout: &str) -> Option<Vec<(u64, String)>> {
+    let mut lines = stdout.lines();
+    if lines.next()?.trim() != "dir" {
+        return None;
+    }
+    Some(
+        lines
+            .filter_map(|line| {
+                let (count, path) = line.split_once('\t')?;
+                Some((count.trim().parse().ok()?, path.to_string()))
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::k8s::pod::ExecOutput;
+    use anyhow::{bail, Result};
+    use std::collections::BTreeSet;
+    use std::sync::{Arc, Mutex};
+
+    /// Helper pod holding a tree of paths (directories end in `/`) that answers the
+    /// count script and `rm -rf`
+    struct MockPod {
+        paths: Mutex<BTreeSet<String>>,
+        removed: Mutex<Vec<String>>,
+        /// Cancelled after this many `rm` commands
+        cancel_after: Option<(usize, Arc<DeleteProgress>)>,
+    }
+
+    impl MockPod {
+        fn new(paths: &[&str]) -> Self {
+            Self {
+                paths: Mutex::new(paths.iter().map(|p| p.to_string()).collect()),
+                removed: Mutex::new(Vec::new()),
+                cancel_after: None,
+            }
+        }
+
+        fn below(&self, path: &str) -> Vec<String> {
+            let prefix = format!("{}/", path);
+            self.paths.lock().unwrap().iter().filter(|p| p.starts_with(&prefix) || p.as_str() == path).cloned().collect()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl PodExec for MockPod {
+        async fn ensure_pod(&self, _namespace: &str, pvc: &str) -> Result<String> {
+            Ok(format!("abyss-{}", pvc))
+        }
+
+        async fn exec_with_input(
+            &self,
+            _namespace: &str,
+            _pod_name: &str,
+            command: Vec<String>,
+            _stdin: &[u8],
+        ) -> Result<ExecOutput> {
+            let ok = |stdout: String| ExecOutput { stdout, stderr: String::new(), success: true };
+            match command[0].as_str() {
+                "sh" => {
+                    let dir = &command[4];
+                    if !self.paths.lock().unwrap().contains(&format!("{}/", dir)) {
+                        return Ok(ok(String::new()));
+                    }
+                    let prefix = format!("{}/", dir);
+                    let children: BTreeSet<String> = self
+                        .below(dir)
+                        .iter()
+                        .filter_map(|p| p.strip_prefix(&prefix))
+                        .filter_map(|rest| rest.split('/').next())
+                        .filter(|name| !name.is_empty())
+                        .map(|name| format!("{}{}", prefix, name))
+                        .collect();
+                    let mut stdout = "dir\n".to_string();
+                    for child in children {
+                        // find prints the entry itself and everything in it
+                        let count = self.below(&child).len();
+                        stdout.push_str(&format!("{}\t{}\n", count, child));
+                    }
+                    Ok(ok(stdout))
+                }
+                "rm" => {
+                    let path = command[2].clone();
+                    for gone in self.below(&path) {
+                        self.paths.lock().unwrap().remove(&gone);
+                    }
+                    let mut removed = self.removed.lock().unwrap();
+                    removed.push(path);
+                    if let Some((limit, ref progress)) = self.cancel_after {
+                        if removed.len() == limit {
+                            progress.cancel();
+                        }
+                    }
+                    Ok(ok(String::new()))
+                }
+                other => bail!("unexpected command {}", other),
+            }
+        }
+    }
+
+    const TREE: &[&str] = &[
+        "/data/logs/",
+        "/data/logs/.hidden",
+        "/data/logs/a.log",
+        "/data/logs/old/",
+        "/data/logs/old/b.log",
+        "/data/logs/old/c.log",
+    ];
+
+    #[tokio::test]
+    async fn test_counts_every_object_below_the_path() {
+        let pod = MockPod::new(TREE);
+        let progress = DeleteProgress::default();
+        delete_with_progress(&pod, "ns", "data", "/data/logs/", &progress).await.unwrap();
+
+        // The directory, its three entries and the two files in old/
+        assert_eq!(progress.total(), 6);
+        assert_eq!(progress.deleted(), 6);
+        assert!(pod.paths.lock().unwrap().is_empty());
+        assert_eq!(pod.removed.lock().unwrap().last().map(String::as_str), Some("/data/logs"));
+    }
+
+    #[tokio::test]
+    async fn test_single_file_counts_as_one() {
+        let pod = MockPod::new(TREE);
+        let progress = DeleteProgress::default();
+        delete_with_progress(&pod, "ns", "data", "/data/logs/a.log", &progress).await.unwrap();
+        assert_eq!((progress.total(), progress.deleted()), (1, 1));
+        assert_eq!(pod.below("/data/logs").len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_stops_between_entries() {
+        let progress = Arc::new(DeleteProgress::default());
+        let pod = MockPod { cancel_after: Some((1, Arc::clone(&progress))), ..MockPod::new(TREE) };
+        let err = delete_with_progress(&pod, "ns", "data", "/data/logs", &progress).await.unwrap_err();
+
+        assert!(err.message().contains("cancelled"), "{}", err);
+        assert_eq!(pod.removed.lock().unwrap().len(), 1);
+        assert_eq!(progress.total(), 6);
+        assert_eq!(progress.deleted(), 1);
+        // The directory itself and the entries after the first are still there
+        assert!(pod.paths.lock().unwrap().contains("/data/logs/"));
+        assert_eq!(pod.below("/data/logs").len(), 5);
+    }
+}
@@ -1,6 +1,8 @@
 pub mod client;
+pub mod delete;
 pub mod pod;
 pub mod pvc;
+pub mod write;
 
-pub use client::K8sClient;
+pub use client::{K8sClient, K8sOptions};
 pub use pvc::StorageManager;
@@ -9,6 +9,38 @@ use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+/// Output of a command run in a helper pod
+#[derive(Debug, Clone, Default)]
+pub struct ExecOutput {
+    pub stdout: String,
+    pub stderr: String,
+    /// The command exited with status 0
+    pub success: bool,
+}
+
+/// Running commands in a PVC's helper pod; writes go through this so they can be
+/// tested without a cluster
+#[async_trait::async_trait]
+pub trait PodExec: Send + Sync {
+    /// Name of a running helper pod for the PVC, started (again) if needed
+    async fn ensure_pod(&self, namespace: &str, pvc: &str) -> Result<String>;
+
+    /// Run `command` in the pod with `stdin` as its input
+    async fn exec_with_input(
+        &self,
+        namespace: &str,
+        pod_name: &str,
+        command: Vec<String>,
+        stdin: &[u8],
+    ) -> Result<ExecOutput>;
+
+    /// Run `command` without input, for commands like `rm -rf` that take as long as the
+    /// tree they work on; implementations may leave out their time limit
+    async fn exec_long(&self, namespace: &str, pod_name: &str, command: Vec<String>) -> Result<ExecOutput> {
+        self.exec_with_input(namespace, pod_name, command, b"").await
+    }
+}
+
 pub struct PodManager {
     client: Client,
     pods: Arc<Mutex<HashMap<String, String>>>, // key: "namespace/pvc", value: pod_name
@@ -148,6 +180,57 @@ impl PodManager {
         Ok(String::from_utf8_lossy(&output).to_string())
     }
 
+    /// Run `command` with `stdin` as its input, collecting stdout, stderr and the exit status
+    pub async fn exec_with_input(
+        &self,
+        namespace: &str,
+        pod_name: &str,
+        command: Vec<String>,
+        stdin: &[u8],
+    ) -> Result<ExecOutput> {
+        use tokio::io::AsyncWriteExt;
+
+        let api: Api<Pod> = Api::namespaced(self.client.clone(), namespace);
+
+        let attach_params = AttachParams {
+            container: Some("helper".to_string()),
+            stdout: true,
+            stderr: true,
+            stdin: true,
+            tty: false,
+            ..Default::default()
+        };
+
+        let mut attached = api.exec(pod_name, command, &attach_params).await?;
+        let status = attached.take_status();
+        let stdout_reader = attached.stdout();
+        let stderr_reader = attached.stderr();
+        let stdin_writer = attached.stdin();
+
+        // Feed stdin while draining both outputs, so neither side can stall the other
+        let write = async {
+            if let Some(mut writer) = stdin_writer {
+                writer.write_all(stdin).await?;
+                writer.shutdown().await?;
+            }
+            Ok::<_, std::io::Error>(())
+        };
+        let (_, stdout, stderr) = tokio::try_join!(write, read_all(stdout_reader), read_all(stderr_reader))?;
+
+        // No status object means the command finished normally
+        let status = match status {
+            Some(status) => status.await,
+            None => None,
+        };
+        attached.join().await?;
+
+        Ok(ExecOutput {
+            stdout: String::from_utf8_lossy(&stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&stderr).into_owned(),
+            success: status.is_none_or(|s| s.status.as_deref() == Some("Success")),
+        })
+    }
+
     pub async fn copy_to_pod(
         &self,
         namespace: &str,
@@ -284,3 +367,13 @@ impl PodManager {
         Ok(())
     }
 }
+
+/// Everything a pod output stream produces, or nothing if it wasn't attached
+async fn read_all(reader: Option<impl tokio::io::AsyncRead + Unpin>) -> std::io::Result<Vec<u8>> {
+    use tokio::io::AsyncReadExt;
+    let mut output = Vec::new();
+    if let Some(mut reader) = reader {
+        reader.read_to_end(&mut output).await?;
+    }
+    Ok(output)
+}
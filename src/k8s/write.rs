@@ -0,0 +1,344 @@
+//! Verified writes into a PVC through the helper pod
+//!
+//! The data is streamed into a hidden file next to the target, its size and SHA-256
+//! are checked inside the pod, and only then is it renamed over the target. A dropped
+//! connection or a restarted pod therefore never leaves a truncated file behind, and
+//! such failures are retried on a fresh pod. A full volume or a permission problem is
+//! reported as such and not retried. The new file takes the mode and (where the pod may
+//! change it) the owner of the file it replaces.
+
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+use super::pod::{ExecOutput, PodExec};
+use crate::fs::error::{FsError, FsResult};
+
+/// Attempts for a write that failed in a way a retry can fix
+pub const WRITE_ATTEMPTS: u32 = 3;
+
+/// Pause before the next attempt, multiplied by the attempts made so far
+const RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// Create the directory, store stdin in the temporary file, give it the mode and owner of
+/// the target if there is one, and print its size and digest
+const UPLOAD_SCRIPT: &str = r#"mkdir -p "$1" && cat > "$2" &&
+if [ -e "$3" ]; then
+    chmod "$(stat -c %a "$3")" "$2" && { chown "$(stat -c %u:%g "$3")" "$2" 2>/dev/null || true; }
+fi &&
+wc -c < "$2" && sha256sum "$2""#;
+
+/// Write `data` to `path` in the PVC, verifying it arrived intact before it replaces
+/// the old file
+pub async fn write_file<E: PodExec + ?Sized>(
+    exec: &E,
+    namespace: &str,
+    pvc: &str,
+    path: &str,
+    data: &[u8],
+) -> FsResult<()> {
+    let digest = format!("{:x}", Sha256::digest(data));
+    let mut attempts = 1;
+    loop {
+        match write_once(exec, namespace, pvc, path, data, &digest).await {
+            Err(e) if e.is_retryable() && attempts < WRITE_ATTEMPTS => {
+                tokio::time::sleep(RETRY_DELAY * attempts).await;
+                attempts += 1;
+            }
+            Err(e) if attempts > 1 => {
+                return Err(e.with_message(format!("{} (gave up after {} attempts)", e, attempts)));
+            }
+            result => return result,
+        }
+    }
+}
+
+async fn write_once<E: PodExec + ?Sized>(
+    exec: &E,
+    namespace: &str,
+    pvc: &str,
+    path: &str,
+    data: &[u8],
+    digest: &str,
+) -> FsResult<()> {
+    let pod = exec
+        .ensure_pod(namespace, pvc)
+        .await
+        .map_err(|e| FsError::from(e.context(format!("Failed to start helper pod for PVC {}", pvc))))?;
+    let (dir, temp) = temp_path(path);
+    let run = |command: Vec<String>, stdin: &'static [u8]| exec.exec_with_input(namespace, &pod, command, stdin);
+
+    let upload = vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        UPLOAD_SCRIPT.to_string(),
+        "sh".to_string(),
+        dir,
+        temp.clone(),
+        path.to_string(),
+    ];
+    let output = exec
+        .exec_with_input(namespace, &pod, upload, data)
+        .await
+        .map_err(|e| exec_failure(&pod, e))?;
+    let failure = if !output.success {
+        Some(command_failure(pvc, path, &output))
+    } else {
+        match parse_check(&output.stdout) {
+            Some((size, found)) if size == data.len() as u64 && found == digest => None,
+            Some((size, _)) if size != data.len() as u64 => Some(FsError::Network(format!(
+                "Partial write of {}: {} of {} bytes arrived",
+                path,
+                size,
+                data.len()
+            ))),
+            Some(_) => Some(FsError::Network(format!("Checksum mismatch after writing {}", path))),
+            None => Some(FsError::Other(format!(
+                "Could not verify {}: unexpected output {:?}",
+                path,
+                output.stdout.trim()
+            ))),
+        }
+    };
+    if let Some(e) = failure {
+        // Best effort: the pod may be the thing that failed
+        let _ = run(vec!["rm".to_string(), "-f".to_string(), temp], b"").await;
+        return Err(e);
+    }
+
+    let output = run(vec!["mv".to_string(), "-f".to_string(), temp.clone(), path.to_string()], b"")
+        .await
+        .map_err(|e| exec_failure(&pod, e))?;
+    if !output.success {
+        let _ = run(vec!["rm".to_string(), "-f".to_string(), temp], b"").await;
+        return Err(command_failure(pvc, path, &output));
+    }
+    Ok(())
+}
+
+/// Directory of `path` and the hidden file the data goes to before it is verified
+fn temp_path(path: &str) -> (String, String) {
+    let path = path.trim_end_matches('/');
+    let (dir, name) = match path.rsplit_once('/') {
+        Some(("", name)) => ("/", name),
+        Some((dir, name)) => (dir, name),
+        None => (".", path),
+    };
+    (dir.to_string(), format!("{}/.{}.abyss-part", dir.trim_end_matches('/'), name))
+}
+
+/// Size and lowercase digest from the upload script's `wc -c` and `sha256sum` lines
+fn parse_check(stdout: &str) -> Option<(u64, String)> {
+    let mut lines = stdout.lines().map(str::trim).filter(|line| !line.is_empty());
+    let size = lines.next()?.parse().ok()?;
+    let digest = lines.next()?.split_whitespace().next()?.to_lowercase();
+    Some((size, digest))
+}
+
+/// The exec itself failed: the pod was deleted or restarted, or the connection dropped
+fn exec_failure(pod: &str, err: anyhow::Error) -> FsError {
+    match FsError::from(err) {
+        FsError::PermissionDenied(m) => {
+            FsError::PermissionDenied(format!("Not allowed to run commands in helper pod {}: {}", pod, m))
+        }
+        e => FsError::Network(format!("Helper pod {} is gone or unreachable: {}", pod, e)),
+    }
+}
+
+/// The command ran but failed, e.g. the volume is full or the path isn't writable
+fn command_failure(pvc: &str, path: &str, output: &ExecOutput) -> FsError {
+    let detail = output.stderr.trim();
+    match FsError::from(anyhow::anyhow!("{}", detail)) {
+        FsError::StorageFull(_) => {
+            FsError::StorageFull(format!("PVC {} is out of space, {} was not written: {}", pvc, path, detail))
+        }
+        FsError::PermissionDenied(_) => {
+            FsError::PermissionDenied(format!("No permission to write {} in PVC {}: {}", path, pvc, detail))
+        }
+        e => e.with_message(format!("Failed to write {} in PVC {}: {}", path, pvc, detail)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{bail, Result};
+    use std::collections::{HashMap, VecDeque};
+    use std::sync::Mutex;
+
+    enum Fault {
+        /// Only half of stdin reaches the file
+        Truncate,
+        /// The exec fails as if the pod had been deleted
+        PodGone,
+        /// The upload command fails with this stderr
+        Stderr(&'static str),
+    }
+
+    /// Helper pod that keeps files in memory and runs the commands `write_file` sends
+    #[derive(Default)]
+    struct MockPod {
+        files: Mutex<HashMap<String, Vec<u8>>>,
+        /// Faults for the next uploads, in order
+        faults: Mutex<VecDeque<Fault>>,
+        pods_started: Mutex<usize>,
+        uploads: Mutex<usize>,
+    }
+
+    impl MockPod {
+        fn with_faults(faults: Vec<Fault>) -> Self {
+            Self { faults: Mutex::new(faults.into()), ..Default::default() }
+        }
+
+        fn file(&self, path: &str) -> Option<Vec<u8>> {
+            self.files.lock().unwrap().get(path).cloned()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl PodExec for MockPod {
+        async fn ensure_pod(&self, _namespace: &str, pvc: &str) -> Result<String> {
+            let mut started = self.pods_started.lock().unwrap();
+            *started += 1;
+            Ok(format!("abyss-{}-{}", pvc, started))
+        }
+
+        async fn exec_with_input(
+            &self,
+            _namespace: &str,
+            _pod_name: &str,
+            command: Vec<String>,
+            stdin: &[u8],
+        ) -> Result<ExecOutput> {
+            let ok = |stdout: String| ExecOutput { stdout, stderr: String::new(), success: true };
+            let mut files = self.files.lock().unwrap();
+            match command[0].as_str() {
+                "sh" => {
+                    *self.uploads.lock().unwrap() += 1;
+                    let temp = command[5].clone();
+                    let stored = match self.faults.lock().unwrap().pop_front() {
+                        Some(Fault::PodGone) => bail!("pods \"abyss-data\" not found"),
+                        Some(Fault::Stderr(stderr)) => {
+                            return Ok(ExecOutput { stdout: String::new(), stderr: stderr.to_string(), success: false })
+                        }
+                        Some(Fault::Truncate) => stdin[..stdin.len() / 2].to_vec(),
+                        None => stdin.to_vec(),
+                    };
+                    let stdout = format!("{}\n{:x}  {}\n", stored.len(), Sha256::digest(&stored), temp);
+                    files.insert(temp, stored);
+                    Ok(ok(stdout))
+                }
+                "mv" => match files.remove(&command[2]) {
+                    Some(data) => {
+                        files.insert(command[3].clone(), data);
+                        Ok(ok(String::new()))
+                    }
+                    None => bail!("mv: can't rename '{}': No such file or directory", command[2]),
+                },
+                "rm" => {
+                    files.remove(&command[2]);
+                    Ok(ok(String::new()))
+                }
+                other => bail!("unexpected command {}", other),
+            }
+        }
+    }
+
+    const PATH: &str = "/data/app/config.yaml";
+    const TEMP: &str = "/data/app/.config.yaml.abyss-part";
+
+    #[test]
+    fn test_temp_path_is_next_to_target() {
+        assert_eq!(temp_path(PATH), ("/data/app".to_string(), TEMP.to_string()));
+        assert_eq!(temp_path("/x"), ("/".to_string(), "/.x.abyss-part".to_string()));
+        assert_eq!(parse_check("      12\nABC  /tmp/x\n"), Some((12, "abc".to_string())));
+        assert_eq!(parse_check("12\n"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_upload_script_keeps_the_target_mode() {
+        use std::io::Write;
+        use std::os::unix::fs::PermissionsExt;
+        use std::process::{Command, Stdio};
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("run.sh");
+        std::fs::write(&target, b"old").unwrap();
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o751)).unwrap();
+        let (parent, temp) = temp_path(target.to_str().unwrap());
+
+        let mut child = Command::new("sh")
+            .args(["-c", UPLOAD_SCRIPT, "sh", &parent, &temp, target.to_str().unwrap()])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(b"new").unwrap();
+        let output = child.wait_with_output().unwrap();
+
+        assert!(output.status.success());
+        let (size, digest) = parse_check(&String::from_utf8_lossy(&output.stdout)).unwrap();
+        assert_eq!((size, digest), (3, format!("{:x}", Sha256::digest(b"new"))));
+        assert_eq!(std::fs::metadata(&temp).unwrap().permissions().mode() & 0o777, 0o751);
+    }
+
+    #[tokio::test]
+    async fn test_write_replaces_file_after_verifying() {
+        let pod = MockPod::default();
+        pod.files.lock().unwrap().insert(PATH.to_string(), b"old".to_vec());
+
+        write_file(&pod, "ns", "data", PATH, b"key: value\n").await.unwrap();
+        assert_eq!(pod.file(PATH).unwrap(), b"key: value\n");
+        assert_eq!(pod.file(TEMP), None);
+        assert_eq!(*pod.uploads.lock().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_partial_write_and_lost_pod_are_retried() {
+        let pod = MockPod::with_faults(vec![Fault::Truncate, Fault::PodGone]);
+        pod.files.lock().unwrap().insert(PATH.to_string(), b"old".to_vec());
+
+        write_file(&pod, "ns", "data", PATH, b"0123456789").await.unwrap();
+        assert_eq!(pod.file(PATH).unwrap(), b"0123456789");
+        assert_eq!(pod.file(TEMP), None);
+        // Every attempt asks for a running pod, so a deleted one gets replaced
+        assert_eq!(*pod.uploads.lock().unwrap(), 3);
+        assert_eq!(*pod.pods_started.lock().unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_partial_writes_give_up_and_keep_old_file() {
+        let pod = MockPod::with_faults((0..WRITE_ATTEMPTS).map(|_| Fault::Truncate).collect());
+        pod.files.lock().unwrap().insert(PATH.to_string(), b"old".to_vec());
+
+        let err = write_file(&pod, "ns", "data", PATH, b"0123456789").await.unwrap_err();
+        assert!(err.is_retryable());
+        assert!(err.to_string().contains("5 of 10 bytes"), "{}", err);
+        assert!(err.to_string().contains("after 3 attempts"), "{}", err);
+        assert_eq!(pod.file(PATH).unwrap(), b"old");
+        assert_eq!(pod.file(TEMP), None);
+    }
+
+    #[tokio::test]
+    async fn test_full_volume_and_permission_are_reported_without_retry() {
+        let pod = MockPod::with_faults(vec![Fault::Stderr("cat: write error: No space left on device")]);
+        let err = write_file(&pod, "ns", "data", PATH, b"data").await.unwrap_err();
+        assert!(matches!(err, FsError::StorageFull(_)), "{:?}", err);
+        assert!(err.to_string().contains("PVC data is out of space"), "{}", err);
+        assert_eq!(*pod.uploads.lock().unwrap(), 1);
+
+        let pod = MockPod::with_faults(vec![Fault::Stderr("sh: can't create /data/app/x: Permission denied")]);
+        let err = write_file(&pod, "ns", "data", PATH, b"data").await.unwrap_err();
+        assert!(matches!(err, FsError::PermissionDenied(_)), "{:?}", err);
+        assert_eq!(*pod.uploads.lock().unwrap(), 1);
+        assert_eq!(pod.file(PATH), None);
+    }
+
+    #[tokio::test]
+    async fn test_lost_pod_is_reported_as_network_error() {
+        let pod = MockPod::with_faults((0..WRITE_ATTEMPTS).map(|_| Fault::PodGone).collect());
+        let err = write_file(&pod, "ns", "data", PATH, b"data").await.unwrap_err();
+        assert!(matches!(err, FsError::Network(_)), "{:?}", err);
+        assert!(err.to_string().contains("is gone or unreachable"), "{}", err);
+    }
+}
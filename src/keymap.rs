@@ -0,0 +1,706 @@
+//! Remappable key bindings for the file manager panes and the disk analyzer
+//!
+//! Actions are bound to key chords like `F5`, `ctrl+s` or `alt+left`. The built-in
+//! bindings are the defaults; `~/.config/abyss/keymap.toml` (or `ABYSS_KEYMAP_FILE`)
+//! rebinds actions by name, pane actions at the top and analyzer actions (the TUI's
+//! analyzer and `abyss clean`) in an `[analyzer]` table:
+//!
+//! ```toml
+//! copy = "c"
+//! delete = ["F8", "Delete"]
+//!
+//! [analyzer]
+//! delete = "x"
+//! ```
+//!
+//! An action listed in the file loses its default chords. Quit is only bound in the
+//! panes; dialogs, the viewer and the editor keep their fixed keys, and so do the
+//! analyzer's `Esc`, confirmation (`y`/`n`) and breadcrumb (`1`-`9`) keys.
+
+use anyhow::{bail, Context, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// The actions of one keymap section, with their names and built-in chords
+pub trait KeyAction: Copy + Eq + std::hash::Hash + 'static {
+    fn all() -> &'static [Self];
+
+    /// Name used in the keymap file
+    fn name(self) -> &'static str;
+
+    /// Built-in chords, as they would be written in the keymap file
+    fn default_chords(self) -> &'static [&'static str];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|a| a.name() == name)
+    }
+}
+
+/// Something a key can do in the panes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    SwitchPane,
+    Up,
+    Down,
+    Open,
+    Parent,
+    HistoryBack,
+    HistoryForward,
+    Copy,
+    Move,
+    Rename,
+    View,
+    Edit,
+    DiskAnalyzer,
+    CreateFile,
+    Mkdir,
+    Delete,
+    Search,
+    SyncToggle,
+    SyncDirection,
+    SyncConflict,
+    SyncNow,
+    SyncDryRun,
+    SelectStorage,
+    Diff,
+    RecentFiles,
+    DirSize,
+    ToggleMark,
+    MarkPattern,
+    UnmarkPattern,
+    InvertMarks,
+    SwapPanes,
+    CopyPlan,
+    JumpCorresponding,
+    HashCheck,
+    CopyInfo,
+    Details,
+    Refresh,
+    HashMenu,
+    ToggleDeleteConfirm,
+    ToggleDryRun,
+    ToggleTimeFormat,
+    OpenEditor,
+    OpenPager,
+    TogglePreview,
+    HelpPage,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: &'static [Action] = &[
+        Action::SwitchPane,
+        Action::Up,
+        Action::Down,
+        Action::Open,
+        Action::Parent,
+        Action::HistoryBack,
+        Action::HistoryForward,
+        Action::Copy,
+        Action::Move,
+        Action::Rename,
+        Action::View,
+        Action::Edit,
+        Action::DiskAnalyzer,
+        Action::CreateFile,
+        Action::Mkdir,
+        Action::Delete,
+        Action::Search,
+        Action::SyncToggle,
+        Action::SyncDirection,
+        Action::SyncConflict,
+        Action::SyncNow,
+        Action::SyncDryRun,
+        Action::SelectStorage,
+        Action::Diff,
+        Action::RecentFiles,
+        Action::DirSize,
+        Action::ToggleMark,
+        Action::MarkPattern,
+        Action::UnmarkPattern,
+        Action::InvertMarks,
+        Action::SwapPanes,
+        Action::CopyPlan,
+        Action::JumpCorresponding,
+        Action::HashCheck,
+        Action::CopyInfo,
+        Action::Details,
+        Action::Refresh,
+        Action::HashMenu,
+        Action::ToggleDeleteConfirm,
+        Action::ToggleDryRun,
+        Action::ToggleTimeFormat,
+        Action::OpenEditor,
+        Action::OpenPager,
+        Action::TogglePreview,
+        Action::HelpPage,
+        Action::Quit,
+    ];
+}
+
+impl KeyAction for Action {
+    fn all() -> &'static [Self] {
+        Action::ALL
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Action::SwitchPane => "switch_pane",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Open => "open",
+            Action::Parent => "parent",
+            Action::HistoryBack => "history_back",
+            Action::HistoryForward => "history_forward",
+            Action::Copy => "copy",
+            Action::Move => "move",
+            Action::Rename => "rename",
+            Action::View => "view",
+            Action::Edit => "edit",
+            Action::DiskAnalyzer => "disk_analyzer",
+            Action::CreateFile => "create_file",
+            Action::Mkdir => "mkdir",
+            Action::Delete => "delete",
+            Action::Search => "search",
+            Action::SyncToggle => "sync_toggle",
+            Action::SyncDirection => "sync_direction",
+            Action::SyncConflict => "sync_conflict",
+            Action::SyncNow => "sync_now",
+            Action::SyncDryRun => "sync_dry_run",
+            Action::SelectStorage => "select_storage",
+            Action::Diff => "diff",
+            Action::RecentFiles => "recent_files",
+            Action::DirSize => "dir_size",
+            Action::ToggleMark => "toggle_mark",
+            Action::MarkPattern => "mark_pattern",
+            Action::UnmarkPattern => "unmark_pattern",
+            Action::InvertMarks => "invert_marks",
+            Action::SwapPanes => "swap_panes",
+            Action::CopyPlan => "copy_plan",
+            Action::JumpCorresponding => "jump_corresponding",
+            Action::HashCheck => "hash_check",
+            Action::CopyInfo => "copy_info",
+            Action::Details => "details",
+            Action::Refresh => "refresh",
+            Action::HashMenu => "hash_menu",
+            Action::ToggleDeleteConfirm => "toggle_delete_confirm",
+            Action::ToggleDryRun => "toggle_dry_run",
+            Action::ToggleTimeFormat => "toggle_time_format",
+            Action::OpenEditor => "open_editor",
+            Action::OpenPager => "open_pager",
+            Action::TogglePreview => "toggle_preview",
+            Action::HelpPage => "help_page",
+            Action::Quit => "quit",
+        }
+    }
+
+    fn default_chords(self) -> &'static [&'static str] {
+        match self {
+            Action::SwitchPane => &["Tab"],
+            Action::Up => &["Up"],
+            Action::Down => &["Down"],
+            Action::Open => &["Enter"],
+            Action::Parent => &["Backspace"],
+            Action::HistoryBack => &["alt+Left"],
+            Action::HistoryForward => &["alt+Right"],
+            Action::Copy => &["F5"],
+            Action::Move => &["F6"],
+            Action::Rename => &["F2"],
+            Action::View => &["F3"],
+            Action::Edit => &["F4"],
+            Action::DiskAnalyzer => &["F9"],
+            Action::CreateFile => &["shift+F7"],
+            Action::Mkdir => &["F7"],
+            Action::Delete => &["F8"],
+            Action::Search => &["ctrl+f"],
+            Action::SyncToggle => &["ctrl+s"],
+            Action::SyncDirection => &["ctrl+t"],
+            Action::SyncConflict => &["ctrl+k"],
+            Action::SyncNow => &["ctrl+y"],
+            Action::SyncDryRun => &["ctrl+d"],
+            Action::SelectStorage => &["ctrl+n"],
+            Action::Diff => &["="],
+            Action::RecentFiles => &["ctrl+r"],
+            Action::DirSize => &["Space"],
+            Action::ToggleMark => &["Insert"],
+            Action::MarkPattern => &["+"],
+            Action::UnmarkPattern => &["-"],
+            Action::InvertMarks => &["*"],
+            Action::SwapPanes => &["ctrl+u"],
+            Action::CopyPlan => &["ctrl+p"],
+            Action::JumpCorresponding => &["ctrl+o"],
+            Action::HashCheck => &["ctrl+v"],
+            Action::CopyInfo => &["i"],
+            Action::Details => &["alt+i"],
+            Action::Refresh => &["ctrl+l"],
+            Action::HashMenu => &["ctrl+h"],
+            Action::ToggleDeleteConfirm => &["alt+y"],
+            Action::ToggleDryRun => &["alt+d"],
+            Action::ToggleTimeFormat => &["t"],
+            Action::OpenEditor => &["e"],
+            Action::OpenPager => &["v"],
+            Action::TogglePreview => &["p"],
+            Action::HelpPage => &["F1"],
+            Action::Quit => &["q"],
+        }
+    }
+}
+
+/// Something a key can do in the disk analyzer, in the TUI and in `abyss clean`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnalyzerAction {
+    Up,
+    Down,
+    Open,
+    Parent,
+    Top,
+    Bottom,
+    Clean,
+    Delete,
+    Sort,
+    SizeMetric,
+    DryRun,
+    Refresh,
+    Quit,
+}
+
+impl KeyAction for AnalyzerAction {
+    fn all() -> &'static [Self] {
+        &[
+            AnalyzerAction::Up,
+            AnalyzerAction::Down,
+            AnalyzerAction::Open,
+            AnalyzerAction::Parent,
+            AnalyzerAction::Top,
+            AnalyzerAction::Bottom,
+            AnalyzerAction::Clean,
+            AnalyzerAction::Delete,
+            AnalyzerAction::Sort,
+            AnalyzerAction::SizeMetric,
+            AnalyzerAction::DryRun,
+            AnalyzerAction::Refresh,
+            AnalyzerAction::Quit,
+        ]
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            AnalyzerAction::Up => "up",
+            AnalyzerAction::Down => "down",
+            AnalyzerAction::Open => "open",
+            AnalyzerAction::Parent => "parent",
+            AnalyzerAction::Top => "top",
+            AnalyzerAction::Bottom => "bottom",
+            AnalyzerAction::Clean => "clean",
+            AnalyzerAction::Delete => "delete",
+            AnalyzerAction::Sort => "sort",
+            AnalyzerAction::SizeMetric => "size_metric",
+            AnalyzerAction::DryRun => "dry_run",
+            AnalyzerAction::Refresh => "refresh",
+            AnalyzerAction::Quit => "quit",
+        }
+    }
+
+    fn default_chords(self) -> &'static [&'static str] {
+        match self {
+            AnalyzerAction::Up => &["Up", "k"],
+            AnalyzerAction::Down => &["Down", "j"],
+            AnalyzerAction::Open => &["Enter", "Right", "l"],
+            AnalyzerAction::Parent => &["Left", "Backspace", "h"],
+            AnalyzerAction::Top => &["Home", "g"],
+            AnalyzerAction::Bottom => &["End", "G"],
+            AnalyzerAction::Clean => &["c"],
+            AnalyzerAction::Delete => &["d"],
+            AnalyzerAction::Sort => &["s"],
+            AnalyzerAction::SizeMetric => &["a"],
+            AnalyzerAction::DryRun => &["D"],
+            AnalyzerAction::Refresh => &["r"],
+            AnalyzerAction::Quit => &["q"],
+        }
+    }
+}
+
+/// A key plus modifiers, e.g. `ctrl+s`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    /// Parse `[ctrl+][alt+][shift+]key`; modifiers are case-insensitive, so are named keys
+    /// (`Enter`, `F5`, `PageDown`, ...). A single character is taken literally, so `Q` is
+    /// shift+q and `+` is the plus key.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut rest = text.trim();
+        let mut modifiers = KeyModifiers::NONE;
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            let (modifier, len) = if lower.starts_with("ctrl+") {
+                (KeyModifiers::CONTROL, 5)
+            } else if lower.starts_with("control+") {
+                (KeyModifiers::CONTROL, 8)
+            } else if lower.starts_with("alt+") {
+                (KeyModifiers::ALT, 4)
+            } else if lower.starts_with("shift+") {
+                (KeyModifiers::SHIFT, 6)
+            } else {
+                break;
+            };
+            // "ctrl++" still leaves the key itself
+            if rest.len() == len {
+                break;
+            }
+            modifiers |= modifier;
+            rest = &rest[len..];
+        }
+
+        let code = match rest.to_ascii_lowercase().as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "esc" | "escape" => KeyCode::Esc,
+            "space" => KeyCode::Char(' '),
+            "insert" | "ins" => KeyCode::Insert,
+            "delete" | "del" => KeyCode::Delete,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" | "pgup" => KeyCode::PageUp,
+            "pagedown" | "pgdn" => KeyCode::PageDown,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            name => {
+                let function_key = name
+                    .strip_prefix('f')
+                    .and_then(|n| n.parse::<u8>().ok())
+                    .filter(|n| (1..=12).contains(n));
+                let mut chars = rest.chars();
+                match (function_key, chars.next(), chars.next()) {
+                    (Some(n), _, _) => KeyCode::F(n),
+                    (None, Some(c), None) => KeyCode::Char(c),
+                    _ => bail!("Unknown key '{}' in '{}'", rest, text),
+                }
+            }
+        };
+
+        Ok(Self::normalized(code, modifiers))
+    }
+
+    /// Terminals report letters with Ctrl in lower case and shifted characters as the
+    /// character itself, so chords are stored the same way
+    fn normalized(code: KeyCode, mut modifiers: KeyModifiers) -> Self {
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => KeyCode::Char(c.to_ascii_lowercase()),
+            code => code,
+        };
+        Self { code, modifiers }
+    }
+
+    /// Short label for the help bar, e.g. `^S`, `F5`, `Alt+←`
+    pub fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) if self.modifiers.contains(KeyModifiers::CONTROL) => c.to_ascii_uppercase().to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::F(n) => format!("F{}", n),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::Backspace => "Bksp".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Insert => "Ins".to_string(),
+            KeyCode::Delete => "Del".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            other => format!("{:?}", other),
+        };
+        let mut label = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            label.push('^');
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            label.push_str("Alt+");
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            label.push_str("Shift+");
+        }
+        label.push_str(&key);
+        label
+    }
+}
+
+/// One chord or several for an action in the keymap file
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ChordList {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Chord -> action lookup for one section of the keymap
+#[derive(Debug, Clone)]
+pub struct Bindings<A> {
+    bindings: HashMap<KeyChord, A>,
+    /// Chords per action in the order they were given, for labels
+    chords: HashMap<A, Vec<KeyChord>>,
+}
+
+impl<A: KeyAction> Bindings<A> {
+    /// Defaults with the actions in `table` rebound
+    ///
+    /// A chord an action is rebound to is taken away from whichever action had it by
+    /// default; two rebound actions sharing a chord is an error.
+    fn rebind(table: BTreeMap<String, ChordList>) -> Result<Self> {
+        let mut overrides: Vec<(A, Vec<KeyChord>)> = Vec::new();
+        for (name, list) in table {
+            let action = A::from_name(&name).with_context(|| format!("Unknown action '{}'", name))?;
+            let texts = match list {
+                ChordList::One(text) => vec![text],
+                ChordList::Many(texts) => texts,
+            };
+            let chords = texts.iter().map(|t| KeyChord::parse(t)).collect::<Result<Vec<_>>>()?;
+            overrides.push((action, chords));
+        }
+
+        let mut section = Self::default();
+        for (action, _) in &overrides {
+            for chord in section.chords.remove(action).unwrap_or_default() {
+                section.bindings.remove(&chord);
+            }
+        }
+        let mut rebound: HashMap<KeyChord, A> = HashMap::new();
+        for (action, chords) in overrides {
+            for chord in &chords {
+                if let Some(other) = rebound.insert(*chord, action).filter(|other| *other != action) {
+                    bail!("'{}' is bound to both {} and {}", chord.label(), other.name(), action.name());
+                }
+                if let Some(previous) = section.bindings.insert(*chord, action) {
+                    if let Some(list) = section.chords.get_mut(&previous) {
+                        list.retain(|c| c != chord);
+                    }
+                }
+            }
+            section.chords.insert(action, chords);
+        }
+        Ok(section)
+    }
+
+    /// Action for a key press; the chord has to match exactly, modifiers included
+    pub fn action(&self, key: &KeyEvent) -> Option<A> {
+        self.bindings.get(&KeyChord::normalized(key.code, key.modifiers)).copied()
+    }
+
+    /// Help bar label for the first chord of `action`, or `-` when it is unbound
+    pub fn label(&self, action: A) -> String {
+        self.chords
+            .get(&action)
+            .and_then(|chords| chords.first())
+            .map_or_else(|| "-".to_string(), KeyChord::label)
+    }
+}
+
+impl<A: KeyAction> Default for Bindings<A> {
+    fn default() -> Self {
+        let mut bindings = HashMap::new();
+        let mut chords = HashMap::new();
+        for &action in A::all() {
+            let parsed: Vec<KeyChord> = action
+                .default_chords()
+                .iter()
+                .map(|text| KeyChord::parse(text).expect("valid default chord"))
+                .collect();
+            for chord in &parsed {
+                bindings.insert(*chord, action);
+            }
+            chords.insert(action, parsed);
+        }
+        Self { bindings, chords }
+    }
+}
+
+/// Layout of the keymap file: pane actions at the top, analyzer actions in `[analyzer]`
+#[derive(Deserialize)]
+struct KeymapFile {
+    #[serde(default)]
+    analyzer: BTreeMap<String, ChordList>,
+    #[serde(flatten)]
+    panes: BTreeMap<String, ChordList>,
+}
+
+/// Key bindings of the panes and of the disk analyzer
+#[derive(Debug, Clone, Default)]
+pub struct Keymap {
+    panes: Bindings<Action>,
+    analyzer: Bindings<AnalyzerAction>,
+}
+
+impl Keymap {
+    /// `ABYSS_KEYMAP_FILE`, else `~/.config/abyss/keymap.toml`
+    pub fn path() -> Option<PathBuf> {
+        if let Ok(path) = std::env::var("ABYSS_KEYMAP_FILE") {
+            return Some(PathBuf::from(path));
+        }
+        dirs::home_dir().map(|home| home.join(".config/abyss/keymap.toml"))
+    }
+
+    /// Defaults with the actions in `content` rebound
+    pub fn parse(content: &str) -> Result<Self> {
+        let file: KeymapFile = toml::from_str(content).context("Invalid keymap file")?;
+        Ok(Self {
+            panes: Bindings::rebind(file.panes)?,
+            analyzer: Bindings::rebind(file.analyzer).context("In [analyzer]")?,
+        })
+    }
+
+    /// Load the keymap file; a missing file means the default bindings
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content).with_context(|| path.display().to_string()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// Pane action for a key press
+    pub fn action(&self, key: &KeyEvent) -> Option<Action> {
+        self.panes.action(key)
+    }
+
+    /// Help bar label for a pane action
+    pub fn label(&self, action: Action) -> String {
+        self.panes.label(action)
+    }
+
+    /// Disk analyzer action for a key press
+    pub fn analyzer_action(&self, key: &KeyEvent) -> Option<AnalyzerAction> {
+        self.analyzer.action(key)
+    }
+
+    /// Help bar label for a disk analyzer action
+    pub fn analyzer_label(&self, action: AnalyzerAction) -> String {
+        self.analyzer.label(action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_chords() {
+        assert_eq!(KeyChord::parse("F5").unwrap(), KeyChord::new(KeyCode::F(5), KeyModifiers::NONE));
+        assert_eq!(KeyChord::parse("Ctrl+S").unwrap(), KeyChord::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+        assert_eq!(KeyChord::parse("alt+left").unwrap(), KeyChord::new(KeyCode::Left, KeyModifiers::ALT));
+        assert_eq!(KeyChord::parse("shift+q").unwrap(), KeyChord::parse("Q").unwrap());
+        assert_eq!(KeyChord::parse("ctrl++").unwrap(), KeyChord::new(KeyCode::Char('+'), KeyModifiers::CONTROL));
+        assert_eq!(KeyChord::parse("space").unwrap().code, KeyCode::Char(' '));
+        assert!(KeyChord::parse("ctrl+nope").is_err());
+        assert!(KeyChord::parse("F13").is_err());
+        assert_eq!(KeyChord::parse("ctrl+n").unwrap().label(), "^N");
+    }
+
+    #[test]
+    fn test_defaults_match_builtin_keys() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.action(&press(KeyCode::F(5), KeyModifiers::NONE)), Some(Action::Copy));
+        assert_eq!(keymap.action(&press(KeyCode::F(7), KeyModifiers::SHIFT)), Some(Action::CreateFile));
+        assert_eq!(keymap.action(&press(KeyCode::F(7), KeyModifiers::NONE)), Some(Action::Mkdir));
+        assert_eq!(keymap.action(&press(KeyCode::Char('y'), KeyModifiers::CONTROL)), Some(Action::SyncNow));
+        assert_eq!(keymap.action(&press(KeyCode::Char('y'), KeyModifiers::ALT)), Some(Action::ToggleDeleteConfirm));
+        assert_eq!(keymap.action(&press(KeyCode::Char('+'), KeyModifiers::SHIFT)), Some(Action::MarkPattern));
+        assert_eq!(keymap.action(&press(KeyCode::Char('q'), KeyModifiers::SHIFT)), None);
+        assert_eq!(keymap.action(&press(KeyCode::Left, KeyModifiers::NONE)), None);
+        assert_eq!(keymap.label(Action::Delete), "F8");
+        assert_eq!(keymap.analyzer_action(&press(KeyCode::Char('G'), KeyModifiers::SHIFT)), Some(AnalyzerAction::Bottom));
+        assert_eq!(keymap.analyzer_action(&press(KeyCode::Backspace, KeyModifiers::NONE)), Some(AnalyzerAction::Parent));
+
+        // Every action has a default and no two defaults collide
+        let total: usize = Action::ALL.iter().map(|a| a.default_chords().len()).sum();
+        assert_eq!(keymap.panes.bindings.len(), total);
+        let total: usize = AnalyzerAction::all().iter().map(|a| a.default_chords().len()).sum();
+        assert_eq!(keymap.analyzer.bindings.len(), total);
+    }
+
+    #[test]
+    fn test_modified_chords_do_not_fall_back_to_the_bare_key() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.action(&press(KeyCode::Char('q'), KeyModifiers::CONTROL)), None);
+        assert_eq!(keymap.action(&press(KeyCode::F(5), KeyModifiers::ALT)), None);
+        assert_eq!(keymap.analyzer_action(&press(KeyCode::Char('d'), KeyModifiers::CONTROL)), None);
+    }
+
+    #[test]
+    fn test_file_rebinds_analyzer_actions() {
+        let keymap = Keymap::parse(
+            r#"
+            copy = "c"
+
+            [analyzer]
+            delete = "x"
+            quit = ["q", "Q"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(keymap.analyzer_action(&press(KeyCode::Char('x'), KeyModifiers::NONE)), Some(AnalyzerAction::Delete));
+        assert_eq!(keymap.analyzer_action(&press(KeyCode::Char('d'), KeyModifiers::NONE)), None);
+        assert_eq!(keymap.analyzer_action(&press(KeyCode::Char('q'), KeyModifiers::SHIFT)), Some(AnalyzerAction::Quit));
+        assert_eq!(keymap.analyzer_label(AnalyzerAction::Delete), "x");
+        // The sections are independent
+        assert_eq!(keymap.action(&press(KeyCode::Char('c'), KeyModifiers::NONE)), Some(Action::Copy));
+        assert_eq!(keymap.analyzer_action(&press(KeyCode::Char('c'), KeyModifiers::NONE)), Some(AnalyzerAction::Clean));
+        assert!(Keymap::parse("[analyzer]
+copy = \"c\"").unwrap_err().to_string().contains("analyzer"));
+    }
+
+    #[test]
+    fn test_file_rebinds_actions() {
+        let keymap = Keymap::parse(
+            r#"
+            copy = "c"
+            delete = ["F8", "Delete"]
+            quit = "ctrl+s"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(keymap.action(&press(KeyCode::Char('c'), KeyModifiers::NONE)), Some(Action::Copy));
+        // The old chord is free again
+        assert_eq!(keymap.action(&press(KeyCode::F(5), KeyModifiers::NONE)), None);
+        assert_eq!(keymap.action(&press(KeyCode::Delete, KeyModifiers::NONE)), Some(Action::Delete));
+        // A rebound chord is taken from the default action that had it
+        assert_eq!(keymap.action(&press(KeyCode::Char('s'), KeyModifiers::CONTROL)), Some(Action::Quit));
+        assert_eq!(keymap.label(Action::SyncToggle), "-");
+        assert_eq!(keymap.action(&press(KeyCode::Char('q'), KeyModifiers::NONE)), None);
+    }
+
+    #[test]
+    fn test_file_errors() {
+        assert!(Keymap::parse("teleport = \"x\"").unwrap_err().to_string().contains("teleport"));
+        assert!(Keymap::parse("copy = \"ctrl+\"").is_err());
+        let clash = Keymap::parse("copy = \"c\"\nmove = \"c\"").unwrap_err();
+        assert!(clash.to_string().contains("copy") && clash.to_string().contains("move"));
+    }
+}
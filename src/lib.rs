@@ -3,9 +3,11 @@
 
 pub mod app;
 pub mod cleaner;
+pub mod event_log;
 pub mod events;
 pub mod fs;
 pub mod hash;
 pub mod k8s;
+pub mod keymap;
 pub mod sync;
 pub mod ui;
@@ -1,10 +1,14 @@
 use abyss::app;
 use abyss::cleaner;
+use abyss::event_log::{EventLog, Operation, OperationEvents, OperationStats};
 use abyss::events;
+use abyss::fs::StorageBackend;
+use abyss::hash;
+use abyss::sync;
 use abyss::ui;
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use colored::Colorize;
 use crossterm::{
     execute,
@@ -16,7 +20,7 @@ use ratatui::{
     Terminal,
 };
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::thread;
 use std::time::Instant;
@@ -33,13 +37,64 @@ use ui::{render_delete_confirm, render_help_bar, render_progress_bar, render_sta
   abyss clean [PATH]         Scan and clean temp files (node_modules, target, .terraform, etc.)
   abyss clean -d             Dry run - preview without deleting
   abyss clean -i             Interactive ncdu-like TUI mode
+  abyss clean -I             Ask before deleting each match (y/n/a/q)
   abyss clean --days 7       Only delete items older than 7 days
 
 For more cleaner options: abyss clean --help
+
+HASH COMMANDS:
+  abyss hash scan [PATH]     Write a hash database (hashes.txt) for a directory
+  abyss hash verify [PATH]   Check files against an existing hash database
+  abyss hash dedup [PATH]    Find duplicate files by content
+  abyss hash compare A B     Compare two hash databases
+  abyss hash benchmark       Measure throughput of every hash algorithm
+
+For more hash options: abyss hash --help
+
+SYNC COMMANDS:
+  abyss sync SRC DST         Sync between local paths, s3://, gs://, pvc:// or pv:// locations
+  abyss sync SRC DST -d      Dry run - show planned changes
+
+For more sync options: abyss sync --help
 "#)]
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+
+    /// Kubeconfig context to connect to (default: current context, or ABYSS_KUBE_CONTEXT)
+    #[arg(long = "context")]
+    context: Option<String>,
+
+    /// Namespace for the helper pod when browsing PVs directly (default: "default", or ABYSS_PV_NAMESPACE)
+    #[arg(long = "pv-namespace")]
+    pv_namespace: Option<String>,
+
+    /// Match names regardless of case: cleaner patterns, pane search and sync excludes
+    /// (default on macOS and Windows, or ABYSS_IGNORE_CASE=1)
+    #[arg(long = "ignore-case", global = true, conflicts_with = "case_sensitive")]
+    ignore_case: bool,
+
+    /// Match names case-sensitively (default on Linux, or ABYSS_IGNORE_CASE=0)
+    #[arg(long = "case-sensitive", global = true)]
+    case_sensitive: bool,
+
+    /// Write operation start/completion events as JSON lines to PATH (`-` for stderr outside the TUI,
+    /// or ABYSS_EVENTS): copies, moves, deletes, cleans and syncs
+    #[arg(long = "events", global = true, value_name = "PATH")]
+    events: Option<String>,
+}
+
+impl Cli {
+    /// Case setting from the command line, if one was given
+    fn ignore_case(&self) -> Option<bool> {
+        if self.ignore_case {
+            Some(true)
+        } else if self.case_sensitive {
+            Some(false)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -49,84 +104,615 @@ enum Commands {
   abyss clean ~/Projects           # Scan and clean ~/Projects
   abyss clean -d                   # Dry run on home directory
   abyss clean ~/Code -d -v         # Verbose dry run
+  abyss clean ~/Code -q            # Only print the final summary
   abyss clean --days 30            # Only delete items older than 30 days
   abyss clean -i                   # Interactive TUI mode
+  abyss clean -i -d                # Interactive, deletes only logged (D switches)
+  abyss clean ~/Code -I            # Ask y/n/a(ll)/q(uit) before each deletion
+  abyss clean --preset rust,node   # Only Rust and Node artifacts
+  abyss clean --only __pycache__   # Just one of the configured patterns, this run only
+  abyss clean ~/Code --summary     # Reclaimable space per top-level dir
+  abyss clean ~/Code -m -d         # Only dirs next to Cargo.toml, package.json, ...
+  abyss clean ~/Code --post-hook 'jq .bytes >> ~/reclaimed.log'
+  abyss clean ~ --skip-hidden      # Stay out of ~/.cache, ~/.local, ~/.git, ...
 
 ENVIRONMENT VARIABLES:
   CLEANER_DIRS    Comma-separated list of directory patterns
   CLEANER_FILES   Comma-separated list of file patterns
+                  (globs like `*.{tmp,cache}` or `test[0-9].log` are allowed)
   CLEANER_DAYS    Default age filter in days
+  CLEANER_REQUIRE_MARKERS  Set to 1 to require marker files (like --require-markers)
+  CLEANER_POST_HOOK  Command to run after a clean (like --post-hook)
+  CLEANER_SKIP_HIDDEN  Set to 1 to skip unmatched hidden directories (like --skip-hidden)
+  ABYSS_IGNORE_CASE  1 to match patterns regardless of case, 0 to match exactly
+                  (default: ignore case on macOS and Windows)
+  ABYSS_ANALYZER_SIZE  Size the interactive analyzer starts with: apparent (file
+                  lengths, default) or disk (allocated blocks, like du); `a` switches
 
 CONFIG FILE:
   Create a cleaner.toml file with [patterns] section to customize targets.
+  Set require_markers = true and add a [markers] table (pattern = ["sibling", "*.ext"])
+  to only clean matched directories that sit next to one of the listed files.
+  Set post_hook = "command" to run a command after every CLI clean.
+  Set ignore_case = true (or false) to override the platform's case default;
+  --ignore-case / --case-sensitive override the file.
+  Set skip_hidden = true to make --skip-hidden the default.
+
+HIDDEN DIRECTORIES:
+  With --skip-hidden, directories whose name starts with a dot are not descended
+  into unless a directory pattern matches them: .terraform is still cleaned, while
+  .git, .cache or .venv and everything inside them are left alone, so a
+  __pycache__ under .venv is not found. Hidden files such as .DS_Store are still
+  matched by file patterns wherever the scan goes.
+
+POST HOOK:
+  The command runs through sh after the clean (including dry runs) and gets the
+  results as JSON on stdin ({"root", "dry_run", "directories", "files", "bytes",
+  "disk_bytes", "errors", "elapsed_secs"}) and as ABYSS_CLEAN_ROOT,
+  ABYSS_CLEAN_DRY_RUN, ABYSS_CLEAN_DIRS, ABYSS_CLEAN_FILES, ABYSS_CLEAN_BYTES,
+  ABYSS_CLEAN_DISK_BYTES and ABYSS_CLEAN_ERRORS. bytes is the logical size freed,
+  disk_bytes the blocks reclaimed on disk.
+  A failing hook is reported but does not change the exit code.
+
+EXIT CODES:
+  0    Matching items were deleted (or would be, with --dry-run/--summary)
+  1    Invalid arguments (missing or non-directory path, unknown preset)
+  2    Some items could not be deleted (permission denied or in use)
+  3    Nothing matched - no items to clean
+"#)]
+    Clean(CleanArgs),
+
+    /// Hash directories, verify integrity and find duplicates (scriptable hash menu)
+    #[command(after_help = r#"EXAMPLES:
+  abyss hash scan ~/data                      # Write ~/data/hashes.txt (BLAKE3)
+  abyss hash scan . -a sha256 -o sums.txt     # SHA-256 database at sums.txt
+  abyss hash scan . -f json -o sums.jsonl     # One JSON object per file (NDJSON)
+  abyss hash verify ~/data                    # Check ~/data against ~/data/hashes.txt
+  abyss hash verify . --ignore-path old.txt   # Also leave old.txt out of the report
+  abyss hash verify . -d old.hashdeep -a md5  # Name the algorithm of a header-less hashdeep file
+  abyss hash tree ~/data -a sha256            # One digest for the whole directory
+  abyss hash dedup ~/Photos --fast -f json    # Duplicate report as JSON
+  abyss hash compare old.txt new.txt          # Diff two databases
+  abyss hash benchmark --size 100             # Benchmark with 100 MB of data
+  abyss hash scan . -a blake3-keyed --key-file ~/.abyss.key
+                                              # Keyed database: can't be forged without the key
+
+KEYED HASHING:
+  blake3-keyed needs a 32-byte key: --key-file (64 hex characters or 32 raw bytes)
+  or ABYSS_HASH_KEY (64 hex characters). Verify with the same key. The key is never
+  printed or written to the database.
+  blake3-derive hashes in BLAKE3's key derivation mode under a context string from
+  --key-context or ABYSS_HASH_CONTEXT; the context is not secret.
+
+EXIT CODES:
+  0    Success (verify/compare: no differences)
+  1    Error (invalid arguments, unreadable database or directory)
+  2    verify/compare found changed, missing or new files
+"#)]
+    Hash(HashArgs),
+
+    /// Synchronize two locations without the TUI (for scripts and cron jobs)
+    #[command(after_help = r#"LOCATIONS:
+  /path/to/dir                     Local directory
+  s3://bucket/prefix               AWS S3 (AWS_REGION, AWS_ACCESS_KEY_ID/SECRET, profile or IAM role)
+  gs://bucket/prefix               Google Cloud Storage (GOOGLE_APPLICATION_CREDENTIALS, profile or ADC)
+  pvc://namespace/claim/path       Kubernetes PVC (current kubeconfig context)
+  pv://volume/path                 Kubernetes PV
+
+EXAMPLES:
+  abyss sync ~/site s3://my-bucket/site            # One-way upload
+  abyss sync ~/site s3://my-bucket/site -d         # Preview changes
+  abyss sync ./data pvc://prod/data-pvc --mode mirror -d    # List what mirroring deletes
+  abyss sync ./data pvc://prod/data-pvc --mode mirror --confirm-deletes --bandwidth 10M
+  abyss sync ~/a ~/b --mode bidirectional --exclude '*.log' --verify
+  abyss sync ~/a ~/b --mode bidirectional --conflict keep-both
+  abyss sync ~/repo /mnt/backup/repo --no-default-excludes   # Include .git etc.
+  abyss sync ~/site s3://my-bucket/site -d -v      # Show why files are skipped
+
+EXIT CODES:
+  0    Sync completed (or dry run planned) without conflicts
+  1    Error (invalid location, backend or transfer failure)
+  2    Sync completed but some conflicts were left unresolved
+  3    Sync would delete files but --confirm-deletes was not given (nothing changed)
 "#)]
-    Clean {
-        /// Target folder to scan (defaults to home directory)
+    Sync(SyncArgs),
+}
+
+#[derive(Args)]
+struct SyncArgs {
+    /// Source location (local path or s3://, gs://, pvc://, pv:// URI)
+    #[arg(index = 1)]
+    source: String,
+
+    /// Destination location (local path or s3://, gs://, pvc://, pv:// URI)
+    #[arg(index = 2)]
+    dest: String,
+
+    /// Sync mode
+    #[arg(short = 'm', long = "mode", value_enum, default_value = "one-way")]
+    mode: SyncModeArg,
+
+    /// Dry run - show planned changes without transferring anything
+    #[arg(short = 'd', long = "dry-run")]
+    dry_run: bool,
+
+    /// How to resolve files changed on both sides (bidirectional mode)
+    #[arg(short = 'c', long = "conflict", value_enum, default_value = "last-write-wins")]
+    conflict: ConflictStrategyArg,
+
+    /// Delete files in the destination that are not in the source
+    #[arg(long = "delete-extra")]
+    delete_extra: bool,
+
+    /// Carry out the deletions a mirror or --delete-extra sync plans (required when there are any)
+    #[arg(long = "confirm-deletes")]
+    confirm_deletes: bool,
+
+    /// Bandwidth limit, e.g. 500K, 10M, 1G (bytes per second, default: unlimited)
+    #[arg(short = 'b', long = "bandwidth")]
+    bandwidth: Option<sync::throttle::BandwidthLimit>,
+
+    /// Additional exclude pattern (repeatable, .gitignore syntax)
+    #[arg(short = 'e', long = "exclude")]
+    exclude: Vec<String>,
+
+    /// Sync everything, including .git, editor swap/backup files and other default excludes
+    #[arg(long = "no-default-excludes")]
+    no_default_excludes: bool,
+
+    /// Verify every transferred file with a checksum
+    #[arg(long = "verify")]
+    verify: bool,
+
+    /// Sync even if source and destination are the same location or one contains the other
+    #[arg(long = "allow-overlap")]
+    allow_overlap: bool,
+
+    /// Number of files to copy at once (1 copies one file at a time)
+    #[arg(short = 'j', long = "jobs", default_value_t = sync::DEFAULT_MAX_CONCURRENT_TRANSFERS)]
+    jobs: usize,
+
+    /// Hide the progress bar
+    #[arg(long = "no-progress")]
+    no_progress: bool,
+
+    /// List skipped files with the reason, e.g. which exclude pattern matched
+    #[arg(short = 'v', long = "verbose")]
+    verbose: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SyncModeArg {
+    /// Source -> destination
+    OneWay,
+    /// Changes flow both ways
+    Bidirectional,
+    /// Destination matches source exactly (deletes extras)
+    Mirror,
+}
+
+impl From<SyncModeArg> for sync::SyncMode {
+    fn from(mode: SyncModeArg) -> Self {
+        match mode {
+            SyncModeArg::OneWay => sync::SyncMode::OneWay,
+            SyncModeArg::Bidirectional => sync::SyncMode::Bidirectional,
+            SyncModeArg::Mirror => sync::SyncMode::Mirror,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ConflictStrategyArg {
+    /// Keep the most recently modified file
+    LastWriteWins,
+    /// Keep the strictly newer file, destination on ties
+    NewerWins,
+    /// Always keep the source file
+    SourceWins,
+    /// Always keep the destination file
+    DestWins,
+    /// Keep both, copying each version to the other side as file.conflict-<side>.ext
+    KeepBoth,
+    /// Leave conflicting files untouched
+    Skip,
+    /// Leave conflicts unresolved and report them (exit code 2)
+    Manual,
+}
+
+impl From<ConflictStrategyArg> for sync::ConflictStrategy {
+    fn from(strategy: ConflictStrategyArg) -> Self {
+        match strategy {
+            ConflictStrategyArg::LastWriteWins => sync::ConflictStrategy::LastWriteWins,
+            ConflictStrategyArg::NewerWins => sync::ConflictStrategy::NewerWins,
+            ConflictStrategyArg::SourceWins => sync::ConflictStrategy::SourceWins,
+            ConflictStrategyArg::DestWins => sync::ConflictStrategy::DestWins,
+            ConflictStrategyArg::KeepBoth => sync::ConflictStrategy::KeepBoth,
+            ConflictStrategyArg::Skip => sync::ConflictStrategy::Skip,
+            ConflictStrategyArg::Manual => sync::ConflictStrategy::Manual,
+        }
+    }
+}
+
+#[derive(Args)]
+struct HashArgs {
+    #[command(subcommand)]
+    command: HashCommand,
+}
+
+#[derive(Subcommand)]
+enum HashCommand {
+    /// Scan a directory and write a hash database
+    Scan {
+        /// Directory to scan (defaults to current directory)
+        #[arg(index = 1)]
+        path: Option<PathBuf>,
+
+        /// Hash algorithm (see `abyss hash benchmark` for the full list)
+        #[arg(short = 'a', long = "algorithm", default_value = "blake3")]
+        algorithm: String,
+
+        /// Database file to write (defaults to PATH/hashes.txt)
+        #[arg(short = 'o', long = "output")]
+        output: Option<PathBuf>,
+
+        /// Database format
+        #[arg(short = 'f', long = "format", value_enum, default_value = "standard")]
+        format: DatabaseFormatArg,
+
+        #[command(flatten)]
+        common: HashCommonArgs,
+
+        #[command(flatten)]
+        key: HashKeyArgs,
+
+        /// Only hash the first, middle and last 100MB of large files
+        #[arg(long = "fast")]
+        fast: bool,
+
+        /// Ignore .hashignore files
+        #[arg(long = "no-ignore")]
+        no_ignore: bool,
+
+        /// Hide the progress bar
+        #[arg(long = "no-progress")]
+        no_progress: bool,
+
+        /// Sort entries by path (buffers every entry in memory until the scan ends)
+        #[arg(long = "sort")]
+        sort: bool,
+
+        /// Also record directories, so verify reports empty directories that went missing
+        #[arg(long = "include-dirs")]
+        include_dirs: bool,
+
+        /// Only hash files with these extensions (comma-separated, e.g. iso,img)
+        #[arg(long = "include-ext", value_delimiter = ',')]
+        include_ext: Vec<String>,
+
+        /// Skip files with these extensions (comma-separated, e.g. log,tmp)
+        #[arg(long = "exclude-ext", value_delimiter = ',')]
+        exclude_ext: Vec<String>,
+
+        /// Most files hashed (and held open) at once by a parallel scan
+        #[arg(long = "max-open-files", default_value_t = hash::DEFAULT_MAX_OPEN_FILES)]
+        max_open_files: usize,
+    },
+
+    /// Verify a directory against a hash database
+    Verify {
+        /// Directory to verify (defaults to current directory)
+        #[arg(index = 1)]
+        path: Option<PathBuf>,
+
+        /// Database file to check against (defaults to PATH/hashes.txt)
+        #[arg(short = 'd', long = "database")]
+        database: Option<PathBuf>,
+
+        /// File or directory to leave out of the report (repeatable; the database and report are always left out)
+        #[arg(long = "ignore-path", value_name = "PATH")]
+        ignore_path: Vec<PathBuf>,
+
+        /// Algorithm for database entries that don't record one or only imply it by hash length
+        /// (read from the database otherwise)
+        #[arg(short = 'a', long = "algorithm")]
+        algorithm: Option<String>,
+
+        #[command(flatten)]
+        common: HashCommonArgs,
+
+        #[command(flatten)]
+        key: HashKeyArgs,
+
+        #[command(flatten)]
+        report: ReportArgs,
+    },
+
+    /// Print one hash for a whole directory (changes if any file is changed, added, removed or renamed)
+    Tree {
+        /// Directory to hash (defaults to current directory)
+        #[arg(index = 1)]
+        path: Option<PathBuf>,
+
+        /// Hash algorithm (see `abyss hash benchmark` for the full list)
+        #[arg(short = 'a', long = "algorithm", default_value = "blake3")]
+        algorithm: String,
+
+        #[command(flatten)]
+        common: HashCommonArgs,
+
+        #[command(flatten)]
+        key: HashKeyArgs,
+
+        /// Ignore .hashignore files
+        #[arg(long = "no-ignore")]
+        no_ignore: bool,
+
+        #[command(flatten)]
+        report: ReportArgs,
+    },
+
+    /// Find duplicate files by content (BLAKE3)
+    Dedup {
+        /// Directory to scan (defaults to current directory)
         #[arg(index = 1)]
         path: Option<PathBuf>,
 
-        /// Dry run - show what would be deleted without actually deleting
-        #[arg(short = 'd', long = "dry-run", default_value = "false")]
-        dry_run: bool,
+        #[command(flatten)]
+        common: HashCommonArgs,
 
-        /// Verbose output - show all matched paths
-        #[arg(short = 'v', long = "verbose", default_value = "false")]
-        verbose: bool,
+        /// Only hash the first, middle and last 100MB of large files
+        #[arg(long = "fast")]
+        fast: bool,
 
-        /// Number of threads for scanning and deletion (default: CPU cores)
-        #[arg(short = 'j', long = "threads")]
-        threads: Option<usize>,
+        #[command(flatten)]
+        report: ReportArgs,
+    },
+
+    /// Compare two hash databases
+    Compare {
+        /// Older database
+        #[arg(index = 1)]
+        database1: PathBuf,
 
-        /// Only delete items older than N days
-        #[arg(long = "days")]
-        days: Option<u64>,
+        /// Newer database
+        #[arg(index = 2)]
+        database2: PathBuf,
 
-        /// Interactive TUI mode (ncdu-like)
-        #[arg(short = 'i', long = "interactive")]
-        interactive: bool,
+        /// Entry path to leave out of the report (repeatable; both databases are always left out)
+        #[arg(long = "ignore-path", value_name = "PATH")]
+        ignore_path: Vec<PathBuf>,
 
-        /// Path to TOML config file
-        #[arg(short = 'c', long = "config")]
-        config: Option<PathBuf>,
+        #[command(flatten)]
+        report: ReportArgs,
     },
+
+    /// Benchmark all hash algorithms
+    Benchmark {
+        /// Size of the generated test data in MB
+        #[arg(short = 's', long = "size", default_value = "100")]
+        size: usize,
+
+        #[command(flatten)]
+        report: ReportArgs,
+    },
+}
+
+/// Options shared by the hash subcommands that read files
+#[derive(Args)]
+struct HashCommonArgs {
+    /// Number of hashing threads (default: ABYSS_HASH_THREADS, else CPU cores; 1 disables parallelism)
+    #[arg(short = 'j', long = "threads")]
+    threads: Option<usize>,
+
+    /// Hash one file at a time (same as --threads 1)
+    #[arg(long = "no-parallel", overrides_with = "parallel")]
+    no_parallel: bool,
+
+    /// Hash files in parallel, the default (overrides an earlier --no-parallel)
+    #[arg(long = "parallel", overrides_with = "no_parallel")]
+    parallel: bool,
+
+    /// Read files instead of memory-mapping them (use on NFS/SMB mounts, where a file
+    /// truncated mid-hash can crash a mapped read with SIGBUS)
+    #[arg(long = "no-mmap")]
+    no_mmap: bool,
+}
+
+impl HashCommonArgs {
+    /// Hashing threads from --threads or ABYSS_HASH_THREADS; 0 means one per CPU core
+    fn threads(&self) -> usize {
+        self.threads.or_else(hash::threads_from_env).unwrap_or(0)
+    }
+
+    /// Hash in parallel unless --no-parallel or a single thread was asked for
+    fn parallel(&self) -> bool {
+        (self.parallel || !self.no_parallel) && self.threads() != 1
+    }
+}
+
+/// Key material for the keyed BLAKE3 modes, shared by scan, verify and tree
+#[derive(Args)]
+struct HashKeyArgs {
+    /// Key for blake3-keyed: a file holding 64 hex characters or 32 raw bytes
+    /// (default: ABYSS_HASH_KEY)
+    #[arg(long = "key-file", value_name = "FILE")]
+    key_file: Option<PathBuf>,
+
+    /// Context string for blake3-derive (default: ABYSS_HASH_CONTEXT)
+    #[arg(long = "key-context", value_name = "CONTEXT")]
+    key_context: Option<String>,
+}
+
+impl HashKeyArgs {
+    /// Key material from the environment, overridden by the command line
+    fn load(&self) -> std::result::Result<hash::HashKey, hash::HashUtilityError> {
+        let mut key = hash::HashKey::from_env()?;
+        if let Some(path) = &self.key_file {
+            key = key.with_key_file(path)?;
+        }
+        if let Some(context) = &self.key_context {
+            key = key.with_context(context.clone());
+        }
+        Ok(key)
+    }
+}
+
+/// Report output options shared by verify, tree, dedup, compare and benchmark
+#[derive(Args)]
+struct ReportArgs {
+    /// Report format
+    #[arg(short = 'f', long = "format", value_enum, default_value = "text")]
+    format: ReportFormat,
+
+    /// Write the report to a file instead of stdout
+    #[arg(short = 'o', long = "output")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DatabaseFormatArg {
+    /// hash  algorithm  mode  path
+    Standard,
+    /// hashdeep-compatible CSV
+    Hashdeep,
+    /// One JSON object per line (NDJSON)
+    Json,
+}
+
+impl From<DatabaseFormatArg> for hash::DatabaseFormat {
+    fn from(format: DatabaseFormatArg) -> Self {
+        match format {
+            DatabaseFormatArg::Standard => hash::DatabaseFormat::Standard,
+            DatabaseFormatArg::Hashdeep => hash::DatabaseFormat::Hashdeep,
+            DatabaseFormatArg::Json => hash::DatabaseFormat::Json,
+        }
+    }
 }
 
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum ReportFormat {
+    Text,
+    Json,
+}
+
+#[derive(Args)]
+struct CleanArgs {
+    /// Target folder to scan (defaults to home directory)
+    #[arg(index = 1)]
+    path: Option<PathBuf>,
+
+    /// Dry run - show what would be deleted without actually deleting
+    #[arg(short = 'd', long = "dry-run", default_value = "false")]
+    dry_run: bool,
+
+    /// Verbose output - show all matched paths
+    #[arg(short = 'v', long = "verbose", default_value = "false")]
+    verbose: bool,
+
+    /// Quiet output - no per-item warnings, only the final summary (errors are still counted)
+    #[arg(short = 'q', long = "quiet", visible_alias = "summary-only", conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Number of threads for scanning and deletion (default: CPU cores)
+    #[arg(short = 'j', long = "threads")]
+    threads: Option<usize>,
+
+    /// Only delete items older than N days
+    #[arg(long = "days")]
+    days: Option<u64>,
+
+    /// Interactive TUI mode (ncdu-like)
+    #[arg(short = 'i', long = "interactive")]
+    interactive: bool,
+
+    /// Ask before deleting each match, like rm -i: y(es), n(o), a(ll remaining), q(uit).
+    /// Needs a terminal on stdin
+    #[arg(short = 'I', long = "confirm-each", conflicts_with_all = ["interactive", "summary"])]
+    confirm_each: bool,
+
+    /// Path to TOML config file
+    #[arg(short = 'c', long = "config")]
+    config: Option<PathBuf>,
+
+    /// Pattern presets to clean (rust, node, python, terraform, java, os, editor, all).
+    /// Repeatable or comma-separated; replaces the configured patterns
+    #[arg(short = 'p', long = "preset", value_delimiter = ',')]
+    preset: Vec<String>,
+
+    /// Only match these of the configured patterns (after --preset) for this run.
+    /// Repeatable; everything else in the configuration is left alone
+    #[arg(long = "only", value_name = "PATTERN")]
+    only: Vec<String>,
+
+    /// Show reclaimable space per top-level directory instead of deleting (implies --dry-run)
+    #[arg(short = 's', long = "summary", conflicts_with = "interactive")]
+    summary: bool,
+
+    /// Only clean directories next to a project marker (target next to Cargo.toml,
+    /// node_modules next to package.json, ...)
+    #[arg(short = 'm', long = "require-markers")]
+    require_markers: bool,
+
+    /// Command to run after the clean, receiving the stats as JSON on stdin
+    #[arg(long = "post-hook", value_name = "CMD")]
+    post_hook: Option<String>,
+
+    /// Don't descend into hidden directories unless a pattern matches them (.terraform)
+    #[arg(long = "skip-hidden")]
+    skip_hidden: bool,
+}
+
+/// Cleaner exit code: items were deleted (or would be, in dry-run mode)
+const EXIT_CLEANED: i32 = 0;
+/// Cleaner exit code: one or more deletions failed
+const EXIT_CLEAN_ERRORS: i32 = 2;
+/// Cleaner exit code: no items matched the patterns
+const EXIT_NOTHING_MATCHED: i32 = 3;
+
+/// Hash exit code: verify/compare found differences
+const EXIT_HASH_DIFFERENCES: i32 = 2;
+
+/// Sync exit code: conflicts were left unresolved
+const EXIT_SYNC_CONFLICTS: i32 = 2;
+/// Sync exit code: deletions were planned without --confirm-deletes; nothing was changed
+const EXIT_SYNC_UNCONFIRMED_DELETES: i32 = 3;
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    let ignore_case = cli.ignore_case();
+    // The TUI draws over stderr, so its events need a file
+    if cli.command.is_none() && EventLog::target_from_arg_or_env(cli.events.as_deref()).as_deref() == Some("-") {
+        anyhow::bail!("The TUI can't stream events to stderr (--events -); give a file path instead");
+    }
+    let event_log = EventLog::from_arg_or_env(cli.events.as_deref())
+        .map_err(|e| anyhow::anyhow!("Can't open event stream: {}", e))?;
 
     match cli.command {
-        Some(Commands::Clean {
-            path,
-            dry_run,
-            verbose,
-            threads,
-            days,
-            interactive,
-            config,
-        }) => {
-            run_cleaner(path, dry_run, verbose, threads, days, interactive, config)?;
+        Some(Commands::Clean(args)) => {
+            run_cleaner(args, ignore_case, event_log)?;
+        }
+        Some(Commands::Hash(args)) => {
+            run_hash(args)?;
+        }
+        Some(Commands::Sync(args)) => {
+            run_sync(args, ignore_case, event_log).await?;
         }
         None => {
             // No subcommand - run normal TUI
-            run_tui().await?;
+            let mut k8s_options = abyss::k8s::K8sOptions::from_env();
+            k8s_options.context = cli.context.or(k8s_options.context);
+            k8s_options.pv_namespace = cli.pv_namespace.or(k8s_options.pv_namespace);
+            run_tui(k8s_options, ignore_case, event_log).await?;
         }
     }
     Ok(())
 }
 
 /// Run the cleaner (CLI or interactive TUI mode)
-fn run_cleaner(
-    path: Option<PathBuf>,
-    dry_run: bool,
-    verbose: bool,
-    threads: Option<usize>,
-    days: Option<u64>,
-    interactive: bool,
-    config_path: Option<PathBuf>,
-) -> Result<()> {
+fn run_cleaner(args: CleanArgs, ignore_case: Option<bool>, event_log: Option<Arc<EventLog>>) -> Result<()> {
     // Resolve folder: positional > home directory
-    let folder = path.unwrap_or_else(|| {
+    let folder = args.path.unwrap_or_else(|| {
         dirs::home_dir().unwrap_or_else(|| PathBuf::from("."))
     });
 
@@ -140,48 +726,563 @@ fn run_cleaner(
         std::process::exit(1);
     }
 
-    if !folder.is_dir() {
-        eprintln!(
-            "{} Path is not a directory: {}",
-            "Error:".red().bold(),
-            folder.display()
-        );
-        std::process::exit(1);
+    if !folder.is_dir() {
+        eprintln!(
+            "{} Path is not a directory: {}",
+            "Error:".red().bold(),
+            folder.display()
+        );
+        std::process::exit(1);
+    }
+
+    // Get absolute path
+    let folder = folder.canonicalize().unwrap_or(folder);
+
+    // Load configuration
+    let mut config = cleaner::Config::load(args.config.as_deref());
+
+    // CLI args override config
+    if let Some(d) = args.days {
+        config.days = Some(d);
+    }
+    if args.require_markers {
+        config.require_markers = true;
+    }
+    if args.skip_hidden {
+        config.skip_hidden = true;
+    }
+    if args.post_hook.is_some() {
+        config.post_hook = args.post_hook;
+    }
+    if let Some(ignore_case) = ignore_case {
+        config.ignore_case = ignore_case;
+    }
+    if let Err(e) = config
+        .apply_presets(&args.preset)
+        .and_then(|_| config.apply_only(&args.only))
+    {
+        eprintln!("{} {}", "Error:".red().bold(), e);
+        std::process::exit(1);
+    }
+
+    let config = Arc::new(config);
+
+    // Interactive TUI mode
+    if args.interactive {
+        run_cleaner_tui(folder, config, args.dry_run)?;
+        return Ok(());
+    }
+
+    if args.summary {
+        let exit_code = run_cleaner_summary(folder, config, args.threads);
+        if exit_code != EXIT_CLEANED {
+            std::process::exit(exit_code);
+        }
+        return Ok(());
+    }
+
+    // Piped or redirected input can't answer prompts, and guessing would delete blindly
+    if args.confirm_each && !std::io::IsTerminal::is_terminal(&io::stdin()) {
+        eprintln!(
+            "{} --confirm-each needs a terminal on stdin to ask about each item",
+            "Error:".red().bold()
+        );
+        std::process::exit(1);
+    }
+
+    // CLI mode - run scan and delete
+    let log_level = cleaner::LogLevel::from_flags(args.quiet, args.verbose);
+    let exit_code = run_cleaner_cli(folder, config, args.dry_run, args.confirm_each, log_level, args.threads, event_log)?;
+    if exit_code != EXIT_CLEANED {
+        std::process::exit(exit_code);
+    }
+    Ok(())
+}
+
+/// Run a hash subcommand, exiting with `EXIT_HASH_DIFFERENCES` when verify/compare find changes
+fn run_hash(args: HashArgs) -> Result<()> {
+    let cwd = || PathBuf::from(".");
+
+    let differences = match args.command {
+        HashCommand::Scan {
+            path, algorithm, output, format, common, key, fast, no_ignore, no_progress, sort, include_dirs, include_ext, exclude_ext,
+            max_open_files,
+        } => {
+            let dir = require_dir(path.unwrap_or_else(cwd));
+            let key = key.load()?;
+            if let Err(e) = hash::HashRegistry::get_keyed_hasher(&algorithm, &key) {
+                eprintln!("{} {}", "Error:".red().bold(), e);
+                std::process::exit(1);
+            }
+            let output = output.unwrap_or_else(|| dir.join("hashes.txt"));
+
+            let engine = hash::ScanEngine::new()
+                .with_parallel(common.parallel())
+                .with_threads(common.threads())
+                .with_fast_mode(fast)
+                .with_ignore(!no_ignore)
+                .with_format(format.into())
+                .with_progress(!no_progress)
+                .with_sorted_output(sort)
+                .with_include_dirs(include_dirs)
+                .with_include_extensions(&include_ext)
+                .with_exclude_extensions(&exclude_ext)
+                .with_max_open_files(max_open_files)
+                .with_mmap(!common.no_mmap)
+                .with_key(key);
+            let stats = engine.scan_directory(&dir, &algorithm, &output)?;
+
+            let dirs = if include_dirs { format!(" and {} directories", stats.directories) } else { String::new() };
+            println!(
+                "{} {} files ({}){} → {}",
+                "Scanned".green().bold(),
+                stats.files_processed,
+                humansize::format_size(stats.total_bytes, humansize::BINARY),
+                dirs,
+                output.display()
+            );
+            if stats.files_failed > 0 {
+                println!("  {} {} files could not be read", "Failed:".red(), stats.files_failed);
+            }
+            if stats.too_many_open_files > 0 {
+                println!(
+                    "  {} {} files hit the open file limit; raise it (ulimit -n) or lower --max-open-files (now {})",
+                    "Hint:".yellow(),
+                    stats.too_many_open_files,
+                    max_open_files
+                );
+            }
+            if !fast && stats.large_files > 0 {
+                println!(
+                    "  {} {} files are over {}; --fast samples them instead of reading every byte",
+                    "Hint:".yellow(),
+                    stats.large_files,
+                    humansize::format_size(hash::FAST_MODE_THRESHOLD, humansize::BINARY)
+                );
+            }
+            false
+        }
+        HashCommand::Verify { path, database, ignore_path, algorithm, common, key, report } => {
+            let dir = require_dir(path.unwrap_or_else(cwd));
+            let database = database.unwrap_or_else(|| dir.join("hashes.txt"));
+
+            let mut engine = hash::VerifyEngine::new()
+                .with_parallel(common.parallel())
+                .with_threads(common.threads())
+                .with_mmap(!common.no_mmap)
+                .with_key(key.load()?)
+                .with_ignored_paths(ignore_path.iter().chain(&report.output));
+            if let Some(algorithm) = algorithm {
+                engine = engine.with_algorithm(algorithm);
+            }
+            let result = engine.verify(&database, &dir)?;
+
+            match report.format {
+                ReportFormat::Text => write_report(&report, &result.to_plain_text())?,
+                ReportFormat::Json => write_report(&report, &serde_json::to_string_pretty(&result)?)?,
+            }
+            !result.mismatches.is_empty() || !result.missing_files.is_empty() || !result.new_files.is_empty()
+        }
+        HashCommand::Tree { path, algorithm, common, key, no_ignore, report } => {
+            let dir = require_dir(path.unwrap_or_else(cwd));
+
+            let engine = hash::TreeHashEngine::new()
+                .with_parallel(common.parallel())
+                .with_threads(common.threads())
+                .with_ignore(!no_ignore)
+                .with_mmap(!common.no_mmap)
+                .with_key(key.load()?);
+            let result = engine.hash_tree(&dir, &algorithm)?;
+
+            match report.format {
+                ReportFormat::Text => write_report(&report, &result.to_plain_text())?,
+                ReportFormat::Json => write_report(&report, &serde_json::to_string_pretty(&result)?)?,
+            }
+            false
+        }
+        HashCommand::Dedup { path, common, fast, report } => {
+            let dir = require_dir(path.unwrap_or_else(cwd));
+
+            let engine = hash::DedupEngine::new()
+                .with_fast_mode(fast)
+                .with_parallel(common.parallel())
+                .with_threads(common.threads())
+                .with_mmap(!common.no_mmap);
+            let result = engine.find_duplicates(&dir)?;
+
+            match report.format {
+                ReportFormat::Text => write_report(&report, &result.to_plain_text())?,
+                ReportFormat::Json => write_report(&report, &result.to_json()?)?,
+            }
+            false
+        }
+        HashCommand::Compare { database1, database2, ignore_path, report } => {
+            let result = hash::CompareEngine::new()
+                .with_ignored_paths(ignore_path.iter().chain(&report.output))
+                .compare(&database1, &database2)?;
+
+            match report.format {
+                ReportFormat::Text => write_report(&report, &result.to_plain_text())?,
+                ReportFormat::Json => write_report(&report, &result.to_json()?)?,
+            }
+            !result.changed_files.is_empty() || !result.removed_files.is_empty() || !result.added_files.is_empty()
+        }
+        HashCommand::Benchmark { size, report } => {
+            let engine = hash::BenchmarkEngine::new();
+            let results = engine.run_benchmarks(size)?;
+
+            match report.format {
+                ReportFormat::Text => write_report(&report, &engine.format_results(&results))?,
+                ReportFormat::Json => write_report(&report, &serde_json::to_string_pretty(&results)?)?,
+            }
+            false
+        }
+    };
+
+    if differences {
+        std::process::exit(EXIT_HASH_DIFFERENCES);
+    }
+    Ok(())
+}
+
+/// Run a headless sync between two locations
+async fn run_sync(args: SyncArgs, ignore_case: Option<bool>, event_log: Option<Arc<EventLog>>) -> Result<()> {
+    let (source, source_path) = backend_for_location(&args.source).await?;
+    let (dest, dest_path) = backend_for_location(&args.dest).await?;
+
+    let mut exclude = if args.no_default_excludes {
+        sync::ExcludePatterns::new()
+    } else {
+        sync::ExcludePatterns::with_defaults()
+    };
+    if let Some(ignore_case) = ignore_case {
+        exclude.set_ignore_case(ignore_case)?;
+    }
+    for pattern in &args.exclude {
+        exclude.add_pattern(pattern)?;
+    }
+
+    let config = sync::SyncConfig {
+        mode: args.mode.into(),
+        conflict_strategy: args.conflict.into(),
+        exclude,
+        bandwidth_limit: args.bandwidth.unwrap_or_default(),
+        dry_run: args.dry_run,
+        delete_extra: args.delete_extra,
+        verify: args.verify,
+        allow_overlap: args.allow_overlap,
+        max_concurrent_transfers: args.jobs,
+        confirm_deletes: args.confirm_deletes,
+        ..Default::default()
+    };
+
+    println!(
+        "{} {} {} {}{}{}",
+        if args.dry_run { "Dry run:".yellow().bold() } else { "Syncing:".bright_cyan().bold() },
+        source.display_path(&source_path),
+        if matches!(args.mode, SyncModeArg::Bidirectional) { "↔" } else { "→" },
+        dest.display_path(&dest_path),
+        if matches!(args.mode, SyncModeArg::Bidirectional) {
+            format!(" (conflicts: {})", config.conflict_strategy.label())
+        } else {
+            String::new()
+        },
+        if config.bandwidth_limit.is_limited() {
+            format!(" (limit {})", config.bandwidth_limit.display())
+        } else {
+            String::new()
+        }
+    );
+
+    let pb = if args.no_progress || !std::io::IsTerminal::is_terminal(&io::stderr()) {
+        indicatif::ProgressBar::hidden()
+    } else {
+        indicatif::ProgressBar::new_spinner()
+    };
+    pb.set_style(
+        indicatif::ProgressStyle::default_bar()
+            .template("{spinner:.green} {msg} [{bar:30.cyan/blue}] {pos}/{len}")
+            .unwrap()
+            .progress_chars("=> "),
+    );
+    pb.enable_steady_tick(std::time::Duration::from_millis(100));
+    pb.set_message("Scanning...");
+
+    let (progress_tx, mut progress_rx) = tokio::sync::mpsc::channel::<sync::SyncProgress>(100);
+    let pb_updater = pb.clone();
+    let progress_task = tokio::spawn(async move {
+        while let Some(p) = progress_rx.recv().await {
+            pb_updater.set_length(p.total_files as u64);
+            pb_updater.set_position(p.files_done as u64);
+            pb_updater.set_message(format!("{:?} {}", p.phase, p.current_file));
+        }
+    });
+
+    let events = OperationEvents::start(
+        event_log,
+        Operation::Sync,
+        Some(source.display_path(&source_path)),
+        dest.display_path(&dest_path),
+        args.dry_run,
+    );
+    let mut engine = sync::SyncEngine::with_progress(source, dest, config, progress_tx);
+    let result = engine.sync(&source_path, &dest_path).await;
+    drop(engine);
+    let _ = progress_task.await;
+    pb.finish_and_clear();
+    if let Err(ref e) = result {
+        events.failed(e);
+        if let Some(unconfirmed) = e.downcast_ref::<sync::UnconfirmedDeletes>() {
+            eprintln!(
+                "{} Sync would delete {} files; nothing was changed",
+                "Error:".red().bold(),
+                unconfirmed.paths.len()
+            );
+            for path in unconfirmed.sample() {
+                eprintln!("    {}", path);
+            }
+            if unconfirmed.paths.len() > unconfirmed.sample().len() {
+                eprintln!("    … and {} more", unconfirmed.paths.len() - unconfirmed.sample().len());
+            }
+            eprintln!(
+                "{} Review the full list with --dry-run, then rerun with --confirm-deletes",
+                "Hint:".yellow()
+            );
+            std::process::exit(EXIT_SYNC_UNCONFIRMED_DELETES);
+        }
+        if let Some(failed) = e.downcast_ref::<sync::SyncFailed>() {
+            eprintln!(
+                "{} {} files copied ({}) and {} deleted before the sync failed",
+                "Partial:".yellow(),
+                failed.stats.files_copied,
+                humansize::format_size(failed.stats.bytes_transferred, humansize::BINARY),
+                failed.stats.files_deleted
+            );
+        }
+    }
+    let result = result?;
+
+    // Stats only count executed actions, so a dry run counts the plan instead
+    let stats = &result.stats;
+    let (copied, deleted, dirs) = if result.dry_run {
+        let count = |f: fn(&sync::SyncAction) -> bool| result.actions.iter().filter(|a| f(a)).count();
+        (
+            count(|a| a.is_copy()),
+            count(|a| matches!(a, sync::SyncAction::DeleteFromDestination { .. } | sync::SyncAction::DeleteFromSource { .. })),
+            count(|a| matches!(a, sync::SyncAction::CreateDirInDestination { .. } | sync::SyncAction::CreateDirInSource { .. })),
+        )
+    } else {
+        (stats.files_copied, stats.files_deleted, stats.dirs_created)
+    };
+    events.completed(OperationStats {
+        files: copied + deleted,
+        directories: dirs,
+        bytes: stats.bytes_transferred,
+        skipped: stats.files_skipped,
+        conflicts: result.conflicts.len(),
+        ..Default::default()
+    });
+
+    if result.dry_run {
+        for action in result.actions.iter().filter(|a| !a.is_skip()) {
+            println!("  {:?}", action);
+        }
+    }
+    if args.verbose {
+        for action in &result.actions {
+            if let sync::SyncAction::Skip { path, reason } = action {
+                println!("  {} {} ({})", "Skip".dimmed(), path, reason);
+            }
+        }
+    }
+
+    let (copy_label, delete_label, dir_label) = if result.dry_run {
+        ("Would copy:", "Would delete:", "Would create dirs:")
+    } else {
+        ("Copied:", "Deleted:", "Dirs created:")
+    };
+    println!();
+    println!("  {} {} files", copy_label.green(), copied);
+    println!("  {} {} files", delete_label.green(), deleted);
+    if result.dry_run && deleted > 0 && !args.confirm_deletes {
+        println!("    {}", "(a real run needs --confirm-deletes to delete them)".dimmed());
+    }
+    println!("  {} {}", dir_label.green(), dirs);
+    println!("  {} {}", "Skipped:".dimmed(), stats.files_skipped);
+    if !result.dry_run {
+        println!(
+            "  {} {} in {:.2?}",
+            "Transferred:".dimmed(),
+            humansize::format_size(stats.bytes_transferred, humansize::BINARY),
+            std::time::Duration::from_millis(stats.duration_ms)
+        );
+    }
+
+    if !result.conflicts.is_empty() {
+        println!("  {} {} unresolved", "Conflicts:".red(), result.conflicts.len());
+        for conflict in &result.conflicts {
+            println!("    {}", conflict.path);
+        }
+        std::process::exit(EXIT_SYNC_CONFLICTS);
+    }
+    Ok(())
+}
+
+/// Resolve a sync location; local locations must be existing directories
+async fn backend_for_location(location: &str) -> Result<(Arc<dyn StorageBackend>, String)> {
+    let (backend, path) = abyss::fs::parse_uri(location).await?;
+    if backend.is_local() && !std::path::Path::new(&path).is_dir() {
+        anyhow::bail!("Not a directory: {}", location);
+    }
+    Ok((backend, path))
+}
+
+/// Exit with an error unless `path` is an existing directory
+fn require_dir(path: PathBuf) -> PathBuf {
+    if !path.is_dir() {
+        eprintln!(
+            "{} Not a directory: {}",
+            "Error:".red().bold(),
+            path.display()
+        );
+        std::process::exit(1);
+    }
+    path
+}
+
+/// Print a report to stdout or write it to `--output`
+fn write_report(args: &ReportArgs, report: &str) -> Result<()> {
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, report)?;
+            println!("{} {}", "Report written to".green(), path.display());
+        }
+        None => print!("{}", report),
+    }
+    Ok(())
+}
+
+/// Run cleaner in CLI mode (non-interactive)
+///
+/// Returns the process exit code (see `EXIT_*` constants).
+fn run_cleaner_cli(
+    folder: PathBuf,
+    config: Arc<cleaner::Config>,
+    dry_run: bool,
+    confirm_each: bool,
+    log_level: cleaner::LogLevel,
+    threads: Option<usize>,
+    event_log: Option<Arc<EventLog>>,
+) -> Result<i32> {
+    let num_threads = threads.unwrap_or_else(num_cpus::get);
+    let quiet = log_level == cleaner::LogLevel::Quiet;
+
+    if !quiet {
+        print_cleaner_header(&folder, &config, dry_run, num_threads);
+    }
+    let events = OperationEvents::start(event_log, Operation::Clean, None, folder.display().to_string(), dry_run);
+
+    // Create shared stats
+    let stats = Arc::new(cleaner::Stats::new());
+
+    // Create channel for scan results
+    let (tx, rx) = crossbeam_channel::unbounded();
+
+    // Start timer
+    let start = Instant::now();
+
+    // Create progress bar
+    let pb = if quiet {
+        indicatif::ProgressBar::hidden()
+    } else {
+        indicatif::ProgressBar::new_spinner()
+    };
+    pb.set_style(
+        indicatif::ProgressStyle::default_spinner()
+            .template("{spinner:.green} {msg}")
+            .unwrap(),
+    );
+    pb.set_message("Scanning directories...");
+    pb.enable_steady_tick(std::time::Duration::from_millis(100));
+
+    // Start scanner in separate thread
+    let cancelled = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let scanner = cleaner::Scanner::new(folder.clone(), num_threads, Arc::clone(&config))
+        .with_stats(Arc::clone(&stats))
+        .with_cancel(Arc::clone(&cancelled));
+    let scan_handle = thread::spawn(move || scanner.scan(tx));
+
+    // Create deleter
+    let mut deleter = cleaner::Deleter::new(Arc::clone(&stats), dry_run, log_level);
+    if log_level == cleaner::LogLevel::Verbose {
+        deleter = deleter.with_matcher(Arc::new(cleaner::PatternMatcher::new(Arc::clone(&config))));
+    }
+
+    // Process deletions (this blocks until scanner finishes and channel closes)
+    let mut kept = 0;
+    if confirm_each {
+        // Each confirmed item is removed right away; the spinner is hidden while asking
+        let deleter = deleter.with_known_sizes();
+        let mut confirmer = cleaner::Confirmer::new(io::stdin().lock(), io::stderr());
+        for item in rx {
+            match pb.suspend(|| confirmer.confirm(item)) {
+                Ok(Some(item)) => deleter.process_iter([item]),
+                Ok(None) if confirmer.quit() => break,
+                Ok(None) => {}
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red().bold(), e);
+                    break;
+                }
+            }
+        }
+        // Quitting leaves the rest of the tree unscanned
+        cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+        kept = confirmer.kept();
+    } else {
+        deleter.process(rx);
     }
 
-    // Get absolute path
-    let folder = folder.canonicalize().unwrap_or(folder);
+    // Wait for scanner to complete
+    let scanned_count = scan_handle.join().unwrap();
 
-    // Load configuration
-    let mut config = cleaner::Config::load(config_path.as_deref());
+    // Stop progress bar
+    pb.finish_and_clear();
 
-    // CLI args override config
-    if let Some(d) = days {
-        config.days = Some(d);
-    }
+    let elapsed = start.elapsed();
 
-    let config = Arc::new(config);
+    print_cleaner_results(&stats, dry_run, scanned_count, elapsed);
+    if kept > 0 {
+        println!("    {} {} matched items", "Kept:".bright_white(), kept);
+    }
+    events.completed(OperationStats {
+        files: stats.files(),
+        directories: stats.directories(),
+        bytes: stats.bytes(),
+        errors: stats.error_count(),
+        skipped: kept,
+        ..Default::default()
+    });
 
-    // Interactive TUI mode
-    if interactive {
-        run_cleaner_tui(folder, config)?;
-        return Ok(());
+    // The clean is done either way; a failing hook only gets a warning
+    if let Some(ref command) = config.post_hook {
+        let report = cleaner::CleanReport::new(&folder, &stats, dry_run, elapsed);
+        if let Err(e) = cleaner::run_post_hook(command, &report) {
+            eprintln!("{} {}", "Warning:".yellow().bold(), e);
+        }
     }
 
-    // CLI mode - run scan and delete
-    run_cleaner_cli(folder, config, dry_run, verbose, threads)
+    let exit_code = if stats.error_count() > 0 {
+        EXIT_CLEAN_ERRORS
+    } else if stats.directories() + stats.files() + kept == 0 {
+        EXIT_NOTHING_MATCHED
+    } else {
+        EXIT_CLEANED
+    };
+    Ok(exit_code)
 }
 
-/// Run cleaner in CLI mode (non-interactive)
-fn run_cleaner_cli(
-    folder: PathBuf,
-    config: Arc<cleaner::Config>,
-    dry_run: bool,
-    verbose: bool,
-    threads: Option<usize>,
-) -> Result<()> {
-    let num_threads = threads.unwrap_or_else(num_cpus::get);
-
+/// Banner, mode, target and patterns printed before a cleaner run
+fn print_cleaner_header(folder: &Path, config: &cleaner::Config, dry_run: bool, num_threads: usize) {
     // Print header
     println!();
     println!(
@@ -245,52 +1346,19 @@ fn run_cleaner_cli(
     println!(
         "    {} {}",
         "Directories:".dimmed(),
-        config.directories.join(", ").dimmed()
+        config.directories().join(", ").dimmed()
     );
     println!(
         "    {} {}",
         "Files:".dimmed(),
-        config.files.join(", ").dimmed()
+        config.files().join(", ").dimmed()
     );
     println!();
 
-    // Create shared stats
-    let stats = Arc::new(cleaner::Stats::new());
-
-    // Create channel for scan results
-    let (tx, rx) = crossbeam_channel::unbounded();
-
-    // Start timer
-    let start = Instant::now();
-
-    // Create progress bar
-    let pb = indicatif::ProgressBar::new_spinner();
-    pb.set_style(
-        indicatif::ProgressStyle::default_spinner()
-            .template("{spinner:.green} {msg}")
-            .unwrap(),
-    );
-    pb.set_message("Scanning directories...");
-    pb.enable_steady_tick(std::time::Duration::from_millis(100));
-
-    // Start scanner in separate thread
-    let scanner = cleaner::Scanner::new(folder.clone(), num_threads, Arc::clone(&config));
-    let scan_handle = thread::spawn(move || scanner.scan(tx));
-
-    // Create deleter
-    let deleter = cleaner::Deleter::new(Arc::clone(&stats), dry_run, verbose);
-
-    // Process deletions (this blocks until scanner finishes and channel closes)
-    deleter.process(rx);
-
-    // Wait for scanner to complete
-    let scanned_count = scan_handle.join().unwrap();
-
-    // Stop progress bar
-    pb.finish_and_clear();
-
-    let elapsed = start.elapsed();
+}
 
+/// Results block printed after a cleaner run (the only output with `--quiet`)
+fn print_cleaner_results(stats: &cleaner::Stats, dry_run: bool, scanned_count: usize, elapsed: std::time::Duration) {
     // Print results
     println!();
     println!(
@@ -315,6 +1383,11 @@ fn run_cleaner_cli(
         println!(
             "    {} {}",
             "Would free:".yellow(),
+            humansize::format_size(stats.disk_bytes(), humansize::BINARY)
+        );
+        println!(
+            "    {} {}",
+            "Logical size:".dimmed(),
             humansize::format_size(stats.bytes(), humansize::BINARY)
         );
     } else {
@@ -327,6 +1400,11 @@ fn run_cleaner_cli(
         println!(
             "    {} {}",
             "Freed:".green(),
+            humansize::format_size(stats.disk_bytes(), humansize::BINARY)
+        );
+        println!(
+            "    {} {}",
+            "Logical size:".dimmed(),
             humansize::format_size(stats.bytes(), humansize::BINARY)
         );
     }
@@ -339,6 +1417,14 @@ fn run_cleaner_cli(
         );
     }
 
+    if stats.special_count() > 0 {
+        println!(
+            "    {} {} special files (sockets, FIFOs, devices)",
+            "Skipped:".dimmed(),
+            stats.special_count()
+        );
+    }
+
     println!();
     println!(
         "    {} {} entries in {:.2?}",
@@ -352,12 +1438,73 @@ fn run_cleaner_cli(
             .bright_cyan()
     );
     println!();
+}
 
-    Ok(())
+/// Dry-run scan that prints reclaimable space per top-level directory, biggest first
+///
+/// Returns the process exit code (`EXIT_NOTHING_MATCHED` when nothing would be freed).
+fn run_cleaner_summary(folder: PathBuf, config: Arc<cleaner::Config>, threads: Option<usize>) -> i32 {
+    let num_threads = threads.unwrap_or_else(num_cpus::get);
+
+    let pb = indicatif::ProgressBar::new_spinner();
+    pb.set_style(
+        indicatif::ProgressStyle::default_spinner()
+            .template("{spinner:.green} {msg}")
+            .unwrap(),
+    );
+    pb.set_message("Scanning directories...");
+    pb.enable_steady_tick(std::time::Duration::from_millis(100));
+
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let scanner = cleaner::Scanner::new(folder.clone(), num_threads, Arc::clone(&config));
+    let scan_handle = thread::spawn(move || scanner.scan(tx));
+    let summary = cleaner::ReclaimSummary::collect(&folder, rx);
+    let _ = scan_handle.join();
+    pb.finish_and_clear();
+
+    println!();
+    println!(
+        "  {} {}",
+        "Reclaimable space in".bright_white().bold(),
+        folder.display()
+    );
+    println!();
+
+    if summary.is_empty() {
+        println!("    {}", "Nothing to clean".dimmed());
+        println!();
+        return EXIT_NOTHING_MATCHED;
+    }
+
+    let name_width = summary.entries.iter().map(|e| e.name.chars().count()).max().unwrap_or(0);
+    for entry in &summary.entries {
+        println!(
+            "    {}  {:<width$}  {}",
+            format!("{:>10}", humansize::format_size(entry.bytes, humansize::BINARY)).yellow(),
+            entry.name,
+            format!(
+                "({} dirs, {} files, {} on disk)",
+                entry.directories,
+                entry.files,
+                humansize::format_size(entry.disk_bytes, humansize::BINARY)
+            )
+            .dimmed(),
+            width = name_width
+        );
+    }
+    println!();
+    println!(
+        "    {}  {}",
+        format!("{:>10}", humansize::format_size(summary.total_bytes(), humansize::BINARY)).bright_green().bold(),
+        format!("total ({} on disk)", humansize::format_size(summary.total_disk_bytes(), humansize::BINARY)).bright_green()
+    );
+    println!();
+
+    EXIT_CLEANED
 }
 
 /// Run cleaner in interactive TUI mode
-fn run_cleaner_tui(root: PathBuf, config: Arc<cleaner::Config>) -> Result<()> {
+fn run_cleaner_tui(root: PathBuf, config: Arc<cleaner::Config>, dry_run: bool) -> Result<()> {
     use crossterm::event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind};
     use ratatui::prelude::*;
     use ratatui::widgets::{Block, Borders, Paragraph};
@@ -460,6 +1607,7 @@ fn run_cleaner_tui(root: PathBuf, config: Arc<cleaner::Config>) -> Result<()> {
 
     // Create cleaner TUI app state
     let mut cleaner_app = CleanerTuiApp::new(root, matcher, dir_tree, config);
+    cleaner_app.dry_run = dry_run;
 
     // Main loop
     let result = run_cleaner_tui_app(&mut terminal, &mut cleaner_app);
@@ -479,6 +1627,7 @@ struct CleanerTuiApp {
     selected: usize,
     scroll_offset: usize,
     sort_mode: CleanerSortMode,
+    size_metric: cleaner::SizeMetric,
     confirm_delete: bool,
     confirm_clean: bool,
     status_message: Option<String>,
@@ -489,6 +1638,15 @@ struct CleanerTuiApp {
     config: Arc<cleaner::Config>,
     /// Last entered folder name (for cursor restoration on go_back)
     last_entered_folder: Option<String>,
+    /// Running clean: live counts, cancel flag and completion (`true` = ran to the end)
+    clean_stats: Option<Arc<cleaner::Stats>>,
+    clean_cancelled: Option<Arc<std::sync::atomic::AtomicBool>>,
+    clean_rx: Option<crossbeam_channel::Receiver<bool>>,
+    clean_total: u64,
+    /// Deletes and cleans only log what they would remove (`D`, or started with --dry-run)
+    dry_run: bool,
+    dry_run_log: app::DryRunLog,
+    dry_run_rx: Option<crossbeam_channel::Receiver<cleaner::DeleteEvent>>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -512,6 +1670,7 @@ impl CleanerTuiApp {
             selected: 0,
             scroll_offset: 0,
             sort_mode: CleanerSortMode::Size,
+            size_metric: cleaner::SizeMetric::from_env(),
             confirm_delete: false,
             confirm_clean: false,
             status_message: None,
@@ -521,6 +1680,13 @@ impl CleanerTuiApp {
             tree: Some(tree),
             config,
             last_entered_folder: None,
+            clean_stats: None,
+            clean_cancelled: None,
+            clean_rx: None,
+            clean_total: 0,
+            dry_run: false,
+            dry_run_log: app::DryRunLog::default(),
+            dry_run_rx: None,
         };
         app.load_current_dir();
         app
@@ -534,7 +1700,7 @@ impl CleanerTuiApp {
         if let Some(ref tree) = self.tree {
             self.entries = tree.get_children(&self.current_path);
             self.apply_sort();
-            self.total_size = self.entries.iter().map(|e| e.size).sum();
+            self.total_size = self.entries.iter().map(|e| e.size_of(self.size_metric)).sum();
         }
 
         // Try to find and select the previously entered folder
@@ -555,7 +1721,7 @@ impl CleanerTuiApp {
 
     fn apply_sort(&mut self) {
         match self.sort_mode {
-            CleanerSortMode::Size => cleaner::tree::sort_by_size(&mut self.entries),
+            CleanerSortMode::Size => cleaner::tree::sort_by_size(&mut self.entries, self.size_metric),
             CleanerSortMode::Name => cleaner::tree::sort_by_name(&mut self.entries),
         }
     }
@@ -624,6 +1790,14 @@ impl CleanerTuiApp {
         self.apply_sort();
     }
 
+    /// Switch between apparent size and allocated disk usage
+    fn toggle_size_metric(&mut self) {
+        self.size_metric = self.size_metric.toggled();
+        self.apply_sort();
+        self.total_size = self.entries.iter().map(|e| e.size_of(self.size_metric)).sum();
+        self.set_status(format!("Showing {}", self.size_metric.label()));
+    }
+
     fn toggle_delete_confirm(&mut self) {
         if !self.entries.is_empty() {
             let entry = &self.entries[self.selected];
@@ -639,18 +1813,82 @@ impl CleanerTuiApp {
         self.confirm_delete = false;
     }
 
+    fn toggle_dry_run(&mut self) {
+        self.dry_run = !self.dry_run;
+        self.set_status(if self.dry_run {
+            "Dry run ON: deletes and cleans are only logged".to_string()
+        } else {
+            "Dry run OFF".to_string()
+        });
+    }
+
     fn set_status(&mut self, msg: String) {
         self.status_message = Some(msg);
         self.status_time = Some(Instant::now());
     }
 
-    fn tick(&mut self) {
+    /// Expire the status message and pick up a finished clean; returns whether the
+    /// screen changed
+    fn tick(&mut self) -> bool {
+        let mut changed = false;
+
         // Clear expired status message
         if let Some(time) = self.status_time {
             if time.elapsed().as_secs() >= 10 {
                 self.status_message = None;
                 self.status_time = None;
+                changed = true;
+            }
+        }
+
+        // Log what a simulated clean would remove
+        if let Some(ref rx) = self.dry_run_rx {
+            for event in rx.try_iter() {
+                self.dry_run_log.record(&event);
+                changed = true;
+            }
+        }
+
+        // Finish a background clean
+        if let Some(completed) = self.clean_rx.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            // A simulated clean has sent all its matches by now
+            let simulated = self.dry_run_rx.take().map(|rx| {
+                for event in rx.try_iter() {
+                    self.dry_run_log.record(&event);
+                }
+            }).is_some();
+            if let Some(stats) = self.clean_stats.take() {
+                self.set_status(format!(
+                    "{}: {} dirs, {} files ({})",
+                    match (simulated, completed) {
+                        (true, true) => "Dry run, would clean",
+                        (true, false) => "Dry run cancelled after",
+                        (false, true) => "Cleaned",
+                        (false, false) => "Clean cancelled after",
+                    },
+                    stats.directories(),
+                    stats.files(),
+                    humansize::format_size(stats.bytes(), humansize::BINARY)
+                ));
             }
+            self.clean_rx = None;
+            self.clean_cancelled = None;
+            self.clean_total = 0;
+            self.rebuild_tree();
+            changed = true;
+        }
+
+        changed
+    }
+
+    fn is_cleaning(&self) -> bool {
+        self.clean_rx.is_some()
+    }
+
+    fn cancel_clean(&mut self) {
+        if let Some(ref cancelled) = self.clean_cancelled {
+            cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+            self.set_status("Cancelling clean...".to_string());
         }
     }
 
@@ -661,18 +1899,27 @@ impl CleanerTuiApp {
                 return;
             }
 
-            let result = if entry.is_dir {
-                std::fs::remove_dir_all(&entry.path)
-            } else {
-                std::fs::remove_file(&entry.path)
-            };
+            if self.dry_run {
+                let bytes = entry.size_of(self.size_metric);
+                self.dry_run_log.would_delete(&entry.path.display().to_string(), entry.is_dir, Some(bytes));
+                self.set_status(format!(
+                    "Dry run: would delete {} ({})",
+                    entry.name,
+                    humansize::format_size(bytes, humansize::BINARY)
+                ));
+                self.confirm_delete = false;
+                return;
+            }
+
+            // Symlinks are removed as links, never followed
+            let result = abyss::fs::remove_path(&entry.path);
 
             match result {
                 Ok(_) => {
                     self.set_status(format!(
                         "Deleted: {} ({})",
                         entry.name,
-                        humansize::format_size(entry.size, humansize::BINARY)
+                        humansize::format_size(entry.size_of(self.size_metric), humansize::BINARY)
                     ));
 
                     // Update tree in-memory
@@ -694,27 +1941,34 @@ impl CleanerTuiApp {
     fn clean_current(&mut self) {
         let root = self.current_path.clone();
         let config = Arc::clone(&self.config);
+        // The tree supplies match sizes for progress; it is rebuilt once the clean stops
+        let tree = self.tree.take()
+            .unwrap_or_else(|| cleaner::DirTree { children: Default::default() });
+        self.clean_total = tree.reclaimable_size(&root);
 
         let stats = Arc::new(cleaner::Stats::new());
-        let (tx, rx) = crossbeam_channel::unbounded();
-        let scanner = cleaner::Scanner::new(root, num_cpus::get(), config);
-
-        // Run scanner
-        let _scanned = scanner.scan(tx);
-
-        // Process deletions
-        let deleter = cleaner::Deleter::new(Arc::clone(&stats), false, false);
-        deleter.process(rx);
-
-        self.set_status(format!(
-            "Cleaned: {} dirs, {} files ({})",
-            stats.directories(),
-            stats.files(),
-            humansize::format_size(stats.bytes(), humansize::BINARY)
-        ));
+        let cancelled = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let (tx, rx) = crossbeam_channel::bounded(1);
+        self.clean_stats = Some(Arc::clone(&stats));
+        self.clean_cancelled = Some(Arc::clone(&cancelled));
+        self.clean_rx = Some(rx);
+        self.status_message = None;
+
+        if self.dry_run {
+            // Matches go to the dry-run log instead of being deleted
+            let (tx_events, rx_events) = crossbeam_channel::unbounded();
+            self.dry_run_rx = Some(rx_events);
+            std::thread::spawn(move || {
+                let completed = tree.simulate_clean(&root, config, stats, cancelled, tx_events);
+                let _ = tx.send(completed);
+            });
+        } else {
+            std::thread::spawn(move || {
+                let completed = tree.clean_with_progress(&root, config, stats, cancelled);
+                let _ = tx.send(completed);
+            });
+        }
 
-        // Rebuild tree
-        self.rebuild_tree();
         self.confirm_clean = false;
     }
 
@@ -746,143 +2000,261 @@ fn run_cleaner_tui_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut CleanerTuiApp,
 ) -> Result<()> {
+    use abyss::keymap::{AnalyzerAction, Keymap};
     use crossterm::event::{self, Event, KeyCode, KeyEventKind};
     use ratatui::prelude::*;
-    use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+    use ratatui::widgets::{Block, Borders, Gauge, List, ListItem, ListState, Paragraph};
     use std::time::Duration;
 
+    let keymap = match Keymap::load() {
+        Ok(keymap) => keymap,
+        Err(e) => {
+            app.set_status(format!("Keymap not loaded: {:#}", e));
+            Keymap::default()
+        }
+    };
+    let label = |action| keymap.analyzer_label(action);
+    let keys_help = format!(
+        "{}:clean  {}:delete  {}:sort  {}:size  {}:dry run  {}:refresh  {}:quit",
+        label(AnalyzerAction::Clean),
+        label(AnalyzerAction::Delete),
+        label(AnalyzerAction::Sort),
+        label(AnalyzerAction::SizeMetric),
+        label(AnalyzerAction::DryRun),
+        label(AnalyzerAction::Refresh),
+        label(AnalyzerAction::Quit),
+    );
+    let nav_help = format!(
+        "{}{}:nav  {}:open  {}:back",
+        label(AnalyzerAction::Up),
+        label(AnalyzerAction::Down),
+        label(AnalyzerAction::Open),
+        label(AnalyzerAction::Parent),
+    );
+
     const TEMP_COLOR: Color = Color::Red;
     const DIR_COLOR: Color = Color::Blue;
     const FILE_COLOR: Color = Color::White;
 
+    // Redraw after input, on a visible change and while a clean shows live progress
+    let mut redraw = true;
     loop {
-        app.tick();
-
-        terminal.draw(|f| {
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Length(3), // Header
-                    Constraint::Min(5),    // List
-                    Constraint::Length(3), // Footer
-                ])
-                .split(f.area());
-
-            // Header
-            let path_str = app.current_path.to_string_lossy();
-            let total_size = humansize::format_size(app.total_size, humansize::BINARY);
-            let sort_str = match app.sort_mode {
-                CleanerSortMode::Size => "size",
-                CleanerSortMode::Name => "name",
-            };
-
-            let header = Paragraph::new(format!(
-                " {} │ Total: {} │ Sort: {} │ {} items",
-                path_str,
-                total_size,
-                sort_str,
-                app.entries.len()
-            ))
-            .block(Block::default().borders(Borders::ALL).title(" Abyss Cleaner "));
-
-            f.render_widget(header, chunks[0]);
-
-            // List
-            let items: Vec<ListItem> = app
-                .entries
-                .iter()
-                .enumerate()
-                .map(|(i, entry)| {
-                    let size_str = humansize::format_size(entry.size, humansize::BINARY);
-                    let prefix = if entry.is_dir { "▸ " } else { "  " };
-                    let temp_marker = if entry.is_temp { " [TEMP]" } else { "" };
+        redraw |= app.tick() || app.is_cleaning();
 
+        if redraw {
+            terminal.draw(|f| {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([
+                        Constraint::Length(3), // Header
+                        Constraint::Min(5),    // List
+                        Constraint::Length(3), // Footer
+                    ])
+                    .split(f.area());
+
+                // Header
+                let path_str = app.current_path.to_string_lossy();
+                let total_size = humansize::format_size(app.total_size, humansize::BINARY);
+                let sort_str = match app.sort_mode {
+                    CleanerSortMode::Size => "size",
+                    CleanerSortMode::Name => "name",
+                };
+
+                let header = Paragraph::new(format!(
+                    " {} │ Total: {} ({}) │ Sort: {} │ {} items",
+                    path_str,
+                    total_size,
+                    app.size_metric.label(),
+                    sort_str,
+                    app.entries.len()
+                ))
+                .block(if app.dry_run {
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::Yellow))
+                        .title(" Abyss Cleaner │ DRY RUN: nothing is deleted ")
+                } else {
+                    Block::default().borders(Borders::ALL).title(" Abyss Cleaner ")
+                });
+
+                f.render_widget(header, chunks[0]);
+
+                // While dry run is on, its log sits under the list
+                let body_area = if app.dry_run {
+                    let split = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Min(0), Constraint::Length(ui::components::DRY_RUN_LOG_HEIGHT)])
+                        .split(chunks[1]);
+                    ui::components::render_dry_run_log(f, &app.dry_run_log, split[1]);
+                    split[0]
+                } else {
+                    chunks[1]
+                };
+
+                // Clean progress replaces the list until the clean stops
+                if let Some(ref stats) = app.clean_stats {
+                    let body = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Min(0), Constraint::Length(3)])
+                        .split(body_area);
+                    let freed = humansize::format_size(stats.bytes(), humansize::BINARY);
+
+                    // A dry run counts the same matches without deleting them
+                    let (verb, done, title) = if app.dry_run_rx.is_some() {
+                        ("Dry run of", "would be deleted", " Dry run... ")
+                    } else {
+                        ("Cleaning", "deleted", " Cleaning... ")
+                    };
                     let text = format!(
-                        "{}{:<40} {:>10}{}",
-                        prefix, entry.name, size_str, temp_marker
+                        "\n\n  {} {}...\n\n  🗑️ {} folders {}\n  📄 {} files {}\n  💾 {} freed",
+                        verb,
+                        app.current_path.display(),
+                        stats.directories(),
+                        done,
+                        stats.files(),
+                        done,
+                        freed
                     );
+                    let paragraph = Paragraph::new(text)
+                        .block(Block::default().borders(Borders::ALL).title(title))
+                        .alignment(Alignment::Center);
+                    f.render_widget(paragraph, body[0]);
 
-                    let style = if i == app.selected {
-                        Style::default().bg(Color::DarkGray).bold()
-                    } else if entry.is_temp {
-                        Style::default().fg(TEMP_COLOR)
-                    } else if entry.is_dir {
-                        Style::default().fg(DIR_COLOR)
+                    let ratio = if app.clean_total > 0 {
+                        (stats.bytes() as f64 / app.clean_total as f64).min(1.0)
                     } else {
-                        Style::default().fg(FILE_COLOR)
+                        0.0
                     };
+                    let gauge = Gauge::default()
+                        .block(Block::default().borders(Borders::ALL).title(" Freed "))
+                        .gauge_style(Style::default().fg(Color::Cyan).bg(Color::Black))
+                        .ratio(ratio)
+                        .label(format!("{} / {}", freed, humansize::format_size(app.clean_total, humansize::BINARY)));
+                    f.render_widget(gauge, body[1]);
+
+                    let text = match app.status_message {
+                        Some(ref msg) => format!(" {}", msg),
+                        None => " Cleaning in progress... Esc:cancel".to_string(),
+                    };
+                    let footer = Paragraph::new(text).block(Block::default().borders(Borders::ALL));
+                    f.render_widget(footer, chunks[2]);
+                    return;
+                }
 
-                    ListItem::new(text).style(style)
-                })
-                .collect();
-
-            let list = List::new(items)
-                .block(Block::default().borders(Borders::ALL))
-                .highlight_style(Style::default().bg(Color::DarkGray));
-
-            let mut state = ListState::default();
-            state.select(Some(app.selected));
-
-            f.render_stateful_widget(list, chunks[1], &mut state);
-
-            // Footer
-            let text = if app.confirm_clean {
-                format!(
-                    " Clean all temp files in '{}'? (y/n)",
-                    app.current_path.file_name()
-                        .map(|n| n.to_string_lossy().to_string())
-                        .unwrap_or_else(|| app.current_path.to_string_lossy().to_string())
-                )
-            } else if app.confirm_delete {
-                if let Some(entry) = app.selected_entry() {
+                // List
+                let items: Vec<ListItem> = app
+                    .entries
+                    .iter()
+                    .enumerate()
+                    .map(|(i, entry)| {
+                        let size_str = humansize::format_size(entry.size_of(app.size_metric), humansize::BINARY);
+                        let prefix = if entry.is_dir { "▸ " } else { "  " };
+                        let temp_marker = if entry.is_temp { " [TEMP]" } else { "" };
+
+                        let text = format!(
+                            "{}{:<40} {:>10}{}",
+                            prefix, entry.name, size_str, temp_marker
+                        );
+
+                        let style = if i == app.selected {
+                            Style::default().bg(Color::DarkGray).bold()
+                        } else if entry.is_temp {
+                            Style::default().fg(TEMP_COLOR)
+                        } else if entry.is_dir {
+                            Style::default().fg(DIR_COLOR)
+                        } else {
+                            Style::default().fg(FILE_COLOR)
+                        };
+
+                        ListItem::new(text).style(style)
+                    })
+                    .collect();
+
+                let list = List::new(items)
+                    .block(Block::default().borders(Borders::ALL))
+                    .highlight_style(Style::default().bg(Color::DarkGray));
+
+                let mut state = ListState::default();
+                state.select(Some(app.selected));
+
+                f.render_stateful_widget(list, body_area, &mut state);
+
+                // Footer
+                let dry_run = if app.dry_run { " [dry run: only logged]" } else { "" };
+                let text = if app.confirm_clean {
                     format!(
-                        " Delete '{}'? (y/n) - {} will be freed",
-                        entry.name,
-                        humansize::format_size(entry.size, humansize::BINARY)
+                        " Clean all temp files in '{}'? (y/n){}",
+                        app.current_path.file_name()
+                            .map(|n| n.to_string_lossy().to_string())
+                            .unwrap_or_else(|| app.current_path.to_string_lossy().to_string()),
+                        dry_run
                     )
+                } else if app.confirm_delete {
+                    if let Some(entry) = app.selected_entry() {
+                        format!(
+                            " Delete '{}'? (y/n) - {} will be freed{}",
+                            entry.name,
+                            humansize::format_size(entry.size_of(app.size_metric), humansize::BINARY),
+                            dry_run
+                        )
+                    } else {
+                        format!(" Delete? (y/n){}", dry_run)
+                    }
+                } else if let Some(ref msg) = app.status_message {
+                    format!(" {} │ {}", msg, keys_help)
                 } else {
-                    " Delete? (y/n)".to_string()
-                }
-            } else if let Some(ref msg) = app.status_message {
-                format!(" {} │ c:clean  d:delete  s:sort  r:refresh  q:quit", msg)
-            } else {
-                " ↑↓:nav  Enter:open  ←:back  c:clean  d:delete  s:sort  r:refresh  q:quit".to_string()
-            };
+                    format!(" {}  {}", nav_help, keys_help)
+                };
 
-            let style = if app.confirm_delete || app.confirm_clean {
-                Style::default().fg(Color::Yellow).bold()
-            } else {
-                Style::default()
-            };
+                let style = if app.confirm_delete || app.confirm_clean {
+                    Style::default().fg(Color::Yellow).bold()
+                } else {
+                    Style::default()
+                };
 
-            let footer = Paragraph::new(text)
-                .style(style)
-                .block(Block::default().borders(Borders::ALL));
+                let footer = Paragraph::new(text)
+                    .style(style)
+                    .block(Block::default().borders(Borders::ALL));
 
-            f.render_widget(footer, chunks[2]);
-        })?;
+                f.render_widget(footer, chunks[2]);
+            })?;
+            redraw = false;
+        }
 
         // Non-blocking poll
         if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
+            let event = event::read()?;
+            redraw = true;
+            if let Event::Key(key) = event {
+                let action = keymap.analyzer_action(&key);
+                if key.kind == KeyEventKind::Press && app.is_cleaning() {
+                    if key.code == KeyCode::Esc || action == Some(AnalyzerAction::Quit) {
+                        app.cancel_clean();
+                    }
+                } else if key.kind == KeyEventKind::Press {
+                    // Esc and the confirmation answers are fixed; everything else is remappable
                     match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
-                        KeyCode::Up | KeyCode::Char('k') => app.move_up(),
-                        KeyCode::Down | KeyCode::Char('j') => app.move_down(),
-                        KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => app.enter(),
-                        KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => app.go_back(),
-                        KeyCode::Char('c') => app.toggle_clean_confirm(),
-                        KeyCode::Char('d') => app.toggle_delete_confirm(),
+                        KeyCode::Esc => return Ok(()),
                         KeyCode::Char('y') if app.confirm_delete => app.delete_selected(),
                         KeyCode::Char('y') if app.confirm_clean => app.clean_current(),
                         KeyCode::Char('n') if app.confirm_delete => app.confirm_delete = false,
                         KeyCode::Char('n') if app.confirm_clean => app.confirm_clean = false,
-                        KeyCode::Char('s') => app.toggle_sort(),
-                        KeyCode::Char('r') => app.refresh(),
-                        KeyCode::Home | KeyCode::Char('g') => app.go_top(),
-                        KeyCode::End | KeyCode::Char('G') => app.go_bottom(),
-                        _ => {}
+                        _ => match action {
+                            Some(AnalyzerAction::Quit) => return Ok(()),
+                            Some(AnalyzerAction::Up) => app.move_up(),
+                            Some(AnalyzerAction::Down) => app.move_down(),
+                            Some(AnalyzerAction::Open) => app.enter(),
+                            Some(AnalyzerAction::Parent) => app.go_back(),
+                            Some(AnalyzerAction::Clean) => app.toggle_clean_confirm(),
+                            Some(AnalyzerAction::Delete) => app.toggle_delete_confirm(),
+                            Some(AnalyzerAction::Sort) => app.toggle_sort(),
+                            Some(AnalyzerAction::SizeMetric) => app.toggle_size_metric(),
+                            Some(AnalyzerAction::DryRun) => app.toggle_dry_run(),
+                            Some(AnalyzerAction::Refresh) => app.refresh(),
+                            Some(AnalyzerAction::Top) => app.go_top(),
+                            Some(AnalyzerAction::Bottom) => app.go_bottom(),
+                            None => {}
+                        },
                     }
                 }
             }
@@ -891,7 +2263,11 @@ fn run_cleaner_tui_app<B: ratatui::backend::Backend>(
 }
 
 /// Run normal dual-pane TUI
-async fn run_tui() -> Result<()> {
+async fn run_tui(
+    k8s_options: abyss::k8s::K8sOptions,
+    ignore_case: Option<bool>,
+    event_log: Option<Arc<EventLog>>,
+) -> Result<()> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -900,7 +2276,11 @@ async fn run_tui() -> Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     // Create app
-    let mut app = App::new().await?;
+    let mut app = App::with_k8s_options(k8s_options).await?;
+    if ignore_case.is_some() {
+        app.ignore_case = ignore_case;
+    }
+    app.event_log = event_log;
 
     // Main loop
     let res = run_app(&mut terminal, &mut app).await;
@@ -922,88 +2302,211 @@ async fn run_app<B: ratatui::backend::Backend>(
     app: &mut App,
 ) -> Result<()> {
     loop {
-        terminal.draw(|f| {
-            // Determine if we need progress bar
-            let show_progress = app.progress.is_some();
+        // Idle ticks only poll for events; drawing waits until something changes
+        if app.take_redraw() {
+            terminal.draw(|f| {
+                // Determine if we need progress bar
+                let show_progress = app.progress.is_some();
+
+                let chunks = if show_progress {
+                    Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([
+                            Constraint::Min(0),    // Main area
+                            Constraint::Length(3), // Progress bar
+                            Constraint::Length(1), // Status bar
+                            Constraint::Length(1), // Help bar
+                        ])
+                        .split(f.area())
+                } else {
+                    Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([
+                            Constraint::Min(0),    // Main area
+                            Constraint::Length(1), // Status bar
+                            Constraint::Length(1), // Help bar
+                        ])
+                        .split(f.area())
+                };
+
+                // While dry run is on, its log sits under the panes (the viewer and editor cover it)
+                let covers_log = matches!(
+                    app.mode,
+                    app::AppMode::ViewFile
+                        | app::AppMode::ViewGoToLine
+                        | app::AppMode::ViewSaveAs
+                        | app::AppMode::EditFile
+                        | app::AppMode::EditorSearch
+                        | app::AppMode::EditorGoToLine
+                        | app::AppMode::EditorSaveAs
+                        | app::AppMode::ConfirmEditorOverwrite
+                );
+                let main_area = if app.dry_run && !covers_log {
+                    let split = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Min(0), Constraint::Length(ui::components::DRY_RUN_LOG_HEIGHT)])
+                        .split(chunks[0]);
+                    ui::components::render_dry_run_log(f, &app.dry_run_log, split[1]);
+                    split[0]
+                } else {
+                    chunks[0]
+                };
+
+                // Check if in DiskAnalyzer mode for single-pane layout
+                if matches!(app.mode, app::AppMode::DiskAnalyzer) {
+                    // Single pane for disk analyzer - render via components
+                    ui::components::render_disk_analyzer(f, app, main_area);
+                } else if matches!(app.mode, app::AppMode::RecentFiles) {
+                    ui::components::render_recent_files(f, app, main_area);
+                } else if matches!(app.mode, app::AppMode::VerifyResults) {
+                    ui::components::render_verify_results(f, app, main_area);
+                } else if matches!(app.mode, app::AppMode::DiffView) {
+                    ui::components::render_diff_view(f, app, main_area);
+                } else if !matches!(
+                    app.mode,
+                    app::AppMode::EditFile
+                        | app::AppMode::EditorSearch
+                        | app::AppMode::EditorGoToLine
+                        | app::AppMode::EditorSaveAs
+                        | app::AppMode::ConfirmEditorOverwrite
+                ) {
+                    // Normal 2-pane layout, with the preview pane as a third column when on
+                    let constraints = if app.preview.enabled {
+                        vec![Constraint::Percentage(35), Constraint::Percentage(35), Constraint::Percentage(30)]
+                    } else {
+                        vec![Constraint::Percentage(50), Constraint::Percentage(50)]
+                    };
+                    let panes = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints(constraints)
+                        .split(main_area);
+
+                    app.left_pane.render(f, panes[0], &app.dir_sizes, app.time_format);
+                    app.right_pane.render(f, panes[1], &app.dir_sizes, app.time_format);
+                    if app.preview.enabled {
+                        ui::components::render_preview(f, app, panes[2]);
+                    }
+                }
 
-            let chunks = if show_progress {
-                Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Min(0),    // Main area
-                        Constraint::Length(3), // Progress bar
-                        Constraint::Length(1), // Status bar
-                        Constraint::Length(1), // Help bar
-                    ])
-                    .split(f.area())
-            } else {
-                Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Min(0),    // Main area
-                        Constraint::Length(1), // Status bar
-                        Constraint::Length(1), // Help bar
-                    ])
-                    .split(f.area())
-            };
-
-            // Check if in DiskAnalyzer mode for single-pane layout
-            if matches!(app.mode, app::AppMode::DiskAnalyzer) {
-                // Single pane for disk analyzer - render via components
-                ui::components::render_disk_analyzer(f, app, chunks[0]);
-            } else if !matches!(app.mode, app::AppMode::EditFile | app::AppMode::EditorSearch) {
-                // Normal 2-pane layout
-                let panes = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-                    .split(chunks[0]);
-
-                app.left_pane.render(f, panes[0]);
-                app.right_pane.render(f, panes[1]);
-            }
+                // Render delete confirmation popup if in ConfirmDelete mode
+                if matches!(app.mode, app::AppMode::ConfirmDelete) && !app.delete_targets.is_empty() {
+                    render_delete_confirm(f, &app.delete_targets, app.delete_impact.as_ref());
+                }
 
-            // Render delete confirmation popup if in ConfirmDelete mode
-            if matches!(app.mode, app::AppMode::ConfirmDelete) {
-                if let Some(ref target) = app.delete_target {
-                    render_delete_confirm(f, target);
+                // Render rename popup
+                if matches!(app.mode, app::AppMode::Rename) {
+                    ui::components::render_rename_popup(f, &app.text_input);
                 }
-            }
 
-            // Render rename popup
-            if matches!(app.mode, app::AppMode::Rename) {
-                ui::components::render_rename_popup(f, &app.text_input);
-            }
+                // Render new file prompt
+                if matches!(app.mode, app::AppMode::CreateFile) {
+                    ui::components::render_search_popup(f, &app.text_input, " New File ");
+                }
 
-            // Render search popup
-            if matches!(app.mode, app::AppMode::Search) {
-                ui::components::render_search_popup(f, &app.text_input, " Search ");
-            }
+                // Render search popup
+                if matches!(app.mode, app::AppMode::Search) {
+                    ui::components::render_search_popup(f, &app.text_input, " Search ");
+                }
 
-            // Render file editor
-            if matches!(app.mode, app::AppMode::EditFile | app::AppMode::EditorSearch) {
-                ui::components::render_file_editor(f, &mut app.editor, chunks[0]);
-            }
+                // Render mark/unmark pattern prompt
+                if matches!(app.mode, app::AppMode::MarkPattern) {
+                    ui::components::render_search_popup(f, &app.text_input, " Mark Matching ");
+                }
+                if matches!(app.mode, app::AppMode::UnmarkPattern) {
+                    ui::components::render_search_popup(f, &app.text_input, " Unmark Matching ");
+                }
 
-            if matches!(app.mode, app::AppMode::EditorSearch) {
-                ui::components::render_search_popup(f, &app.text_input, " Where Is ");
-            }
+                // Render streaming file viewer
+                if matches!(app.mode, app::AppMode::ViewFile | app::AppMode::ViewGoToLine | app::AppMode::ViewSaveAs) {
+                    ui::components::render_file_viewer(f, app, chunks[0]);
+                }
 
-            // Render large file confirmation
-            if matches!(app.mode, app::AppMode::ConfirmLargeLoad) {
-                ui::components::render_confirm_large_load_popup(f, app);
-            }
+                // Render file editor
+                if matches!(
+                    app.mode,
+                    app::AppMode::EditFile
+                        | app::AppMode::EditorSearch
+                        | app::AppMode::EditorGoToLine
+                        | app::AppMode::EditorSaveAs
+                        | app::AppMode::ConfirmEditorOverwrite
+                ) {
+                    ui::components::render_file_editor(
+                        f,
+                        &mut app.editor,
+                        app.view_wrap,
+                        app.editor_line_numbers,
+                        chunks[0],
+                    );
+                }
 
-            if show_progress {
-                if let Some(ref progress) = app.progress {
-                    render_progress_bar(f, chunks[1], progress);
+                if matches!(app.mode, app::AppMode::EditorSearch) {
+                    ui::components::render_search_popup(f, &app.text_input, " Where Is ");
                 }
-                render_status_bar(f, chunks[2], app);
-                render_help_bar(f, chunks[3], app);
-            } else {
-                render_status_bar(f, chunks[1], app);
-                render_help_bar(f, chunks[2], app);
-            }
-        })?;
+
+                if matches!(app.mode, app::AppMode::EditorGoToLine | app::AppMode::ViewGoToLine) {
+                    ui::components::render_search_popup(f, &app.text_input, " Go to Line ");
+                }
+
+                if matches!(app.mode, app::AppMode::EditorSaveAs | app::AppMode::ViewSaveAs) {
+                    ui::components::render_save_as_popup(f, app);
+                }
+
+                // Render large file confirmation
+                if matches!(app.mode, app::AppMode::ConfirmLargeLoad) {
+                    ui::components::render_confirm_large_load_popup(f, app);
+                }
+
+                if matches!(app.mode, app::AppMode::ConfirmRetry) {
+                    ui::components::render_confirm_retry_popup(f, app);
+                }
+
+                if matches!(app.mode, app::AppMode::ConfirmMerge) {
+                    ui::components::render_merge_popup(f, app);
+                }
+
+                if matches!(app.mode, app::AppMode::ConfirmEditorOverwrite) {
+                    ui::components::render_editor_overwrite_popup(f, app);
+                }
+
+                if matches!(app.mode, app::AppMode::ConfirmCopyPlan) {
+                    if let Some(ref plan) = app.copy_plan {
+                        ui::components::render_copy_plan_popup(f, plan, &app.copy_plan_ops);
+                    }
+                }
+
+                if matches!(app.mode, app::AppMode::HashCheckInput) {
+                    ui::components::render_hash_check_input_popup(f, app);
+                }
+
+                if matches!(app.mode, app::AppMode::HashCheckResult) {
+                    if let Some(ref check) = app.hash_check {
+                        ui::components::render_hash_check_result_popup(f, check);
+                    }
+                }
+
+                if matches!(app.mode, app::AppMode::ObjectDetails) {
+                    if let Some((ref path, ref meta)) = app.object_details {
+                        ui::components::render_object_details_popup(f, path, meta);
+                    }
+                }
+
+                // Render quit confirmation on top of everything
+                if app.quit_pending {
+                    ui::components::render_quit_confirm(f, &app.pending_work());
+                }
+
+                if show_progress {
+                    if let Some(ref progress) = app.progress {
+                        render_progress_bar(f, chunks[1], progress);
+                    }
+                    render_status_bar(f, chunks[2], app);
+                    render_help_bar(f, chunks[3], app);
+                } else {
+                    render_status_bar(f, chunks[1], app);
+                    render_help_bar(f, chunks[2], app);
+                }
+            })?;
+        }
 
         // Clear expired messages (after 7 seconds)
         app.clear_expired_message();
@@ -1013,6 +2516,12 @@ async fn run_app<B: ratatui::backend::Backend>(
 
         handle_events(app).await?;
 
+        // External editor/pager needs the terminal, which only this loop owns
+        if let Some(request) = app.external_open.take() {
+            events::run_external_open(terminal, app, request).await?;
+            app.needs_redraw = true;
+        }
+
         if app.should_quit {
             break;
         }
@@ -36,6 +36,32 @@ impl ConflictStrategy {
             Self::Manual => "Ask user for each conflict",
         }
     }
+
+    /// Short name shown in the status bar and accepted by `--conflict`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::LastWriteWins => "last-write-wins",
+            Self::NewerWins => "newer-wins",
+            Self::SourceWins => "source-wins",
+            Self::DestWins => "dest-wins",
+            Self::KeepBoth => "keep-both",
+            Self::Skip => "skip",
+            Self::Manual => "manual",
+        }
+    }
+
+    /// Next strategy in selection order (wraps around).
+    pub fn next(self) -> Self {
+        match self {
+            Self::LastWriteWins => Self::NewerWins,
+            Self::NewerWins => Self::SourceWins,
+            Self::SourceWins => Self::DestWins,
+            Self::DestWins => Self::KeepBoth,
+            Self::KeepBoth => Self::Skip,
+            Self::Skip => Self::Manual,
+            Self::Manual => Self::LastWriteWins,
+        }
+    }
 }
 
 /// Information about a file for conflict comparison.
@@ -160,6 +186,16 @@ impl Conflict {
 
     /// Generate a conflict-renamed path (e.g., file.txt -> file.conflict-1.txt)
     pub fn conflict_path(&self, suffix: u32) -> String {
+        self.suffixed_path(&suffix.to_string())
+    }
+
+    /// Path for a "keep both" copy of one side's version
+    /// (e.g., file.txt -> file.conflict-source.txt)
+    pub fn side_path(&self, side: ConflictSide) -> String {
+        self.suffixed_path(side.label())
+    }
+
+    fn suffixed_path(&self, suffix: &str) -> String {
         let path = PathBuf::from(&self.path);
         let stem = path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
         let ext = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
@@ -173,6 +209,22 @@ impl Conflict {
     }
 }
 
+/// Which side of a sync a conflicting version came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictSide {
+    Source,
+    Dest,
+}
+
+impl ConflictSide {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Source => "source",
+            Self::Dest => "dest",
+        }
+    }
+}
+
 /// Conflict resolver that tracks and resolves multiple conflicts.
 #[derive(Debug, Default)]
 pub struct ConflictResolver {
@@ -201,16 +253,27 @@ impl ConflictResolver {
     }
 
     /// Resolve all pending conflicts using their configured strategies.
+    /// Manual conflicts stay pending until `resolve_manual` is called.
     pub fn resolve_all(&mut self) {
         let pending = std::mem::take(&mut self.pending);
         for mut conflict in pending {
-            if conflict.strategy != ConflictStrategy::Manual {
+            if conflict.strategy == ConflictStrategy::Manual {
+                self.pending.push(conflict);
+            } else {
                 conflict.resolve();
+                self.resolved.push(conflict);
             }
-            self.resolved.push(conflict);
         }
     }
 
+    /// Resolution chosen for `path`, if it has been resolved.
+    pub fn resolution_for(&self, path: &str) -> Option<ConflictResolution> {
+        self.resolved
+            .iter()
+            .find(|c| c.path == path)
+            .and_then(|c| c.resolution)
+    }
+
     /// Get pending conflicts that require manual resolution.
     pub fn manual_conflicts(&self) -> Vec<&Conflict> {
         self.pending
@@ -299,5 +362,35 @@ mod tests {
         
         assert_eq!(conflict.conflict_path(1), "dir/file.conflict-1.txt");
         assert_eq!(conflict.conflict_path(2), "dir/file.conflict-2.txt");
+        assert_eq!(conflict.side_path(ConflictSide::Source), "dir/file.conflict-source.txt");
+        assert_eq!(conflict.side_path(ConflictSide::Dest), "dir/file.conflict-dest.txt");
+    }
+
+    #[test]
+    fn test_resolve_all_keeps_manual_conflicts_pending() {
+        let mut resolver = ConflictResolver::new(ConflictStrategy::Manual);
+        resolver.add_conflict("a.txt", FileInfo::new("a.txt", 1, None), FileInfo::new("a.txt", 2, None));
+        resolver.resolve_all();
+
+        assert_eq!(resolver.manual_conflicts().len(), 1);
+        assert_eq!(resolver.resolution_for("a.txt"), None);
+
+        resolver.resolve_manual("a.txt", ConflictResolution::KeepBoth);
+        assert_eq!(resolver.resolution_for("a.txt"), Some(ConflictResolution::KeepBoth));
+        assert_eq!(resolver.stats().pending, 0);
+    }
+
+    #[test]
+    fn test_strategy_cycle_visits_every_strategy() {
+        let mut strategy = ConflictStrategy::default();
+        let mut seen = Vec::new();
+        loop {
+            seen.push(strategy.label());
+            strategy = strategy.next();
+            if strategy == ConflictStrategy::default() {
+                break;
+            }
+        }
+        assert_eq!(seen.len(), 7);
     }
 }
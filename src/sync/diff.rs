@@ -0,0 +1,96 @@
+//! Line-based text diff.
+//!
+//! Used by the diff viewer to compare a file from each pane.
+
+use similar::{ChangeTag, TextDiff};
+
+/// Number of leading bytes inspected when sniffing for binary content.
+const BINARY_SNIFF_LEN: usize = 8 * 1024;
+
+/// Kind of a diff line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLineKind {
+    /// Line present in both files.
+    Context,
+    /// Line only in the new (right) file.
+    Added,
+    /// Line only in the old (left) file.
+    Removed,
+}
+
+/// A single line of a unified diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    /// 1-based line number in the old file.
+    pub old_line: Option<usize>,
+    /// 1-based line number in the new file.
+    pub new_line: Option<usize>,
+    pub text: String,
+}
+
+/// Check whether data looks binary (contains a NUL byte near the start).
+pub fn is_binary(data: &[u8]) -> bool {
+    data.iter().take(BINARY_SNIFF_LEN).any(|&b| b == 0)
+}
+
+/// Compute a line diff between two texts.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let diff = TextDiff::from_lines(old, new);
+
+    diff.iter_all_changes()
+        .map(|change| {
+            let kind = match change.tag() {
+                ChangeTag::Equal => DiffLineKind::Context,
+                ChangeTag::Insert => DiffLineKind::Added,
+                ChangeTag::Delete => DiffLineKind::Removed,
+            };
+            DiffLine {
+                kind,
+                old_line: change.old_index().map(|i| i + 1),
+                new_line: change.new_index().map(|i| i + 1),
+                text: change.value().trim_end_matches(['\n', '\r']).to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Count added and removed lines.
+pub fn diff_stats(lines: &[DiffLine]) -> (usize, usize) {
+    lines.iter().fold((0, 0), |(added, removed), line| match line.kind {
+        DiffLineKind::Added => (added + 1, removed),
+        DiffLineKind::Removed => (added, removed + 1),
+        DiffLineKind::Context => (added, removed),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        let lines = diff_lines("a\nb\nc\n", "a\nB\nc\nd\n");
+
+        assert_eq!(lines[0].kind, DiffLineKind::Context);
+        assert_eq!(lines[0].text, "a");
+        assert_eq!(diff_stats(&lines), (2, 1));
+
+        let removed = lines.iter().find(|l| l.kind == DiffLineKind::Removed).unwrap();
+        assert_eq!(removed.text, "b");
+        assert_eq!(removed.old_line, Some(2));
+        assert_eq!(removed.new_line, None);
+    }
+
+    #[test]
+    fn test_identical_files() {
+        let lines = diff_lines("same\n", "same\n");
+        assert_eq!(diff_stats(&lines), (0, 0));
+    }
+
+    #[test]
+    fn test_is_binary() {
+        assert!(is_binary(b"PK\x03\x04\x00\x00"));
+        assert!(!is_binary(b"plain text\n"));
+    }
+}
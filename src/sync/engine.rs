@@ -8,13 +8,21 @@ use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use futures::stream::{self, StreamExt};
+
 use crate::fs::{FileEntry, StorageBackend};
-use crate::sync::conflict::{Conflict, ConflictResolver, ConflictResolution, ConflictStrategy, FileInfo};
+use crate::sync::conflict::{Conflict, ConflictResolver, ConflictResolution, ConflictSide, ConflictStrategy, FileInfo};
 use crate::sync::compression::{CompressionType, CompressionLevel};
 use crate::sync::exclude::ExcludePatterns;
 use crate::sync::hash::hash_bytes;
 use crate::sync::throttle::{BandwidthLimiter, TransferStats};
 
+/// Default number of file copies a sync runs at once.
+pub const DEFAULT_MAX_CONCURRENT_TRANSFERS: usize = 4;
+
+/// Bytes of each side read at a time when `heal` compares file contents.
+const HEAL_CHUNK_SIZE: u64 = 4 * 1024 * 1024;
+
 /// Sync mode.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum SyncMode {
@@ -48,6 +56,13 @@ pub struct SyncConfig {
     pub delete_extra: bool,
     /// Verify file integrity with checksums.
     pub verify: bool,
+    /// Run even when source and destination are the same location or one contains the other.
+    pub allow_overlap: bool,
+    /// File copies run at once (values below 1 copy one file at a time).
+    pub max_concurrent_transfers: usize,
+    /// Carry out planned deletions; without it a sync that would delete anything
+    /// stops with [`UnconfirmedDeletes`] before changing either side.
+    pub confirm_deletes: bool,
 }
 
 impl Default for SyncConfig {
@@ -62,10 +77,67 @@ impl Default for SyncConfig {
             dry_run: false,
             delete_extra: false,
             verify: false,
+            allow_overlap: false,
+            max_concurrent_transfers: DEFAULT_MAX_CONCURRENT_TRANSFERS,
+            confirm_deletes: false,
         }
     }
 }
 
+/// Error a sync stops with when it would delete files without `confirm_deletes`;
+/// find it with `downcast_ref`. Nothing has been copied or deleted at that point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnconfirmedDeletes {
+    /// Paths the sync would delete, relative to the side they are deleted from
+    pub paths: Vec<String>,
+}
+
+impl UnconfirmedDeletes {
+    /// Paths shown when the deletions are reported
+    pub const SAMPLE_SIZE: usize = 5;
+
+    /// The first few paths, for messages that can't list them all
+    pub fn sample(&self) -> &[String] {
+        &self.paths[..self.paths.len().min(Self::SAMPLE_SIZE)]
+    }
+}
+
+impl std::fmt::Display for UnconfirmedDeletes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Sync would delete {} files ({}", self.paths.len(), self.sample().join(", "))?;
+        if self.paths.len() > Self::SAMPLE_SIZE {
+            write!(f, ", …")?;
+        }
+        write!(f, "); deletions were not confirmed")
+    }
+}
+
+impl std::error::Error for UnconfirmedDeletes {}
+
+/// Error a sync stops with when a copy or delete fails part way through; find it with
+/// `downcast_ref`. `stats` counts the work that finished before the failure.
+#[derive(Debug)]
+pub struct SyncFailed {
+    /// What the sync did before it stopped
+    pub stats: SyncStats,
+    /// The failure that stopped it
+    pub error: anyhow::Error,
+}
+
+impl std::fmt::Display for SyncFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (after copying {} files, {})",
+            self.error,
+            self.stats.files_copied,
+            humansize::format_size(self.stats.bytes_transferred, humansize::BINARY)
+        )
+    }
+}
+
+impl std::error::Error for SyncFailed {}
+
 /// Action to take for a file.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SyncAction {
@@ -73,6 +145,10 @@ pub enum SyncAction {
     CopyToDestination { path: String },
     /// Copy file from destination to source (bidirectional).
     CopyToSource { path: String },
+    /// Copy the source file to `target` in the destination (keep both).
+    CopyToDestinationAs { path: String, target: String },
+    /// Copy the destination file to `target` in the source (keep both).
+    CopyToSourceAs { path: String, target: String },
     /// Delete file from destination.
     DeleteFromDestination { path: String },
     /// Delete file from source (bidirectional).
@@ -93,6 +169,8 @@ impl SyncAction {
         match self {
             Self::CopyToDestination { path } => path,
             Self::CopyToSource { path } => path,
+            Self::CopyToDestinationAs { path, .. } => path,
+            Self::CopyToSourceAs { path, .. } => path,
             Self::DeleteFromDestination { path } => path,
             Self::DeleteFromSource { path } => path,
             Self::CreateDirInDestination { path } => path,
@@ -102,6 +180,17 @@ impl SyncAction {
         }
     }
 
+    /// Check if this action copies a file in either direction.
+    pub fn is_copy(&self) -> bool {
+        matches!(
+            self,
+            Self::CopyToDestination { .. }
+                | Self::CopyToSource { .. }
+                | Self::CopyToDestinationAs { .. }
+                | Self::CopyToSourceAs { .. }
+        )
+    }
+
     /// Check if this is a skip action.
     pub fn is_skip(&self) -> bool {
         matches!(self, Self::Skip { .. })
@@ -143,6 +232,8 @@ pub struct SyncStats {
     pub conflicts: usize,
     /// Bytes transferred.
     pub bytes_transferred: u64,
+    /// Part of `bytes_transferred` copied from destination back to source (bidirectional).
+    pub bytes_to_source: u64,
     /// Total duration.
     pub duration_ms: u64,
 }
@@ -160,6 +251,46 @@ pub struct SyncResult {
     pub dry_run: bool,
 }
 
+/// A single file copy, from one backend to the other.
+#[derive(Debug, Clone)]
+struct CopyJob {
+    /// Path shown in progress updates.
+    display: String,
+    from: String,
+    to: String,
+    source_to_dest: bool,
+}
+
+impl CopyJob {
+    /// The copy an action performs, if it copies a file.
+    fn for_action(action: &SyncAction, source_path: &str, dest_path: &str) -> Option<Self> {
+        let (display, from, to, source_to_dest) = match action {
+            SyncAction::CopyToDestination { path } => {
+                (path, format!("{}/{}", source_path, path), format!("{}/{}", dest_path, path), true)
+            }
+            SyncAction::CopyToSource { path } => {
+                (path, format!("{}/{}", dest_path, path), format!("{}/{}", source_path, path), false)
+            }
+            SyncAction::CopyToDestinationAs { path, target } => {
+                (target, format!("{}/{}", source_path, path), format!("{}/{}", dest_path, target), true)
+            }
+            SyncAction::CopyToSourceAs { path, target } => {
+                (target, format!("{}/{}", dest_path, path), format!("{}/{}", source_path, target), false)
+            }
+            _ => return None,
+        };
+        Some(Self { display: display.clone(), from, to, source_to_dest })
+    }
+}
+
+/// Totals for a batch of copies.
+#[derive(Debug, Default)]
+struct CopyTotals {
+    files: usize,
+    bytes: u64,
+    bytes_to_source: u64,
+}
+
 /// File information for comparison.
 #[derive(Debug, Clone)]
 struct FileState {
@@ -279,7 +410,14 @@ impl SyncEngine {
 
     /// Perform a sync operation.
     pub async fn sync(&mut self, source_path: &str, dest_path: &str) -> Result<SyncResult> {
+        if !self.config.allow_overlap {
+            if let Some(message) = crate::sync::overlap::check_overlap(&*self.source, source_path, &*self.dest, dest_path) {
+                anyhow::bail!(message);
+            }
+        }
+
         self.stats.start();
+        self.conflict_resolver = ConflictResolver::new(self.config.conflict_strategy);
         
         // Scan source and destination
         let source_files = self.scan_directory(&*self.source, source_path).await?;
@@ -304,10 +442,10 @@ impl SyncEngine {
             stats.files_scanned += 1;
             
             // Check excludes
-            if self.config.exclude.is_excluded(&file.path) {
+            if let Some(pattern) = self.config.exclude.excluded_by(&file.path) {
                 actions.push(SyncAction::Skip {
                     path: file.path.clone(),
-                    reason: "Excluded by pattern".to_string(),
+                    reason: excluded_reason(pattern),
                 });
                 stats.files_skipped += 1;
                 continue;
@@ -376,76 +514,69 @@ impl SyncEngine {
             }
         }
         
-        // Resolve conflicts
+        // Resolve conflicts and replace them with the actions they resolved to
         self.conflict_resolver.resolve_all();
+        let actions: Vec<SyncAction> = actions
+            .into_iter()
+            .flat_map(|action| match action {
+                SyncAction::Conflict { path } => {
+                    match self.conflict_resolver.resolved.iter().find(|c| c.path == path) {
+                        Some(conflict) => conflict_actions(conflict),
+                        None => vec![SyncAction::Conflict { path }],
+                    }
+                }
+                other => vec![other],
+            })
+            .collect();
         
+        // Deleting needs a second, explicit yes; stop before anything is changed
+        if !self.config.dry_run && !self.config.confirm_deletes {
+            let paths: Vec<String> = actions.iter()
+                .filter(|a| matches!(a, SyncAction::DeleteFromDestination { .. } | SyncAction::DeleteFromSource { .. }))
+                .map(|a| a.path().to_string())
+                .collect();
+            if !paths.is_empty() {
+                self.stats.stop();
+                return Err(UnconfirmedDeletes { paths }.into());
+            }
+        }
+
         // Count total actions for progress
         let total_actions = actions.iter().filter(|a| !a.is_skip()).count();
         let mut actions_done = 0;
         
         // Execute actions if not dry run
         if !self.config.dry_run {
+            let dirs_created = self.create_dirs(&actions, source_path, dest_path).await?;
+            stats.dirs_created += dirs_created;
+            actions_done += dirs_created;
+
+            let jobs = actions.iter()
+                .filter_map(|action| CopyJob::for_action(action, source_path, dest_path))
+                .collect();
+            let (copied, outcome) = self.run_copies(jobs, actions_done, total_actions).await;
+            stats.files_copied += copied.files;
+            stats.bytes_to_source += copied.bytes_to_source;
+            actions_done += copied.files;
+            if let Err(error) = outcome {
+                return Err(self.failed(stats, error));
+            }
+
             for action in &actions {
-                match action {
-                    SyncAction::CopyToDestination { path } => {
-                        // Send progress update
-                        self.send_progress(SyncProgress {
-                            phase: SyncPhase::Transferring,
-                            current_file: path.clone(),
-                            files_done: actions_done,
-                            total_files: total_actions,
-                            bytes_done: self.stats.bytes_transferred,
-                            total_bytes: 0, // Unknown until we read files
-                        }).await;
-                        
-                        let src_full = format!("{}/{}", source_path, path);
-                        let dst_full = format!("{}/{}", dest_path, path);
-                        self.copy_file(&src_full, &dst_full, true).await?;
-                        stats.files_copied += 1;
-                        actions_done += 1;
-                    }
-                    SyncAction::CopyToSource { path } => {
-                        self.send_progress(SyncProgress {
-                            phase: SyncPhase::Transferring,
-                            current_file: path.clone(),
-                            files_done: actions_done,
-                            total_files: total_actions,
-                            bytes_done: self.stats.bytes_transferred,
-                            total_bytes: 0,
-                        }).await;
-                        
-                        let src_full = format!("{}/{}", source_path, path);
-                        let dst_full = format!("{}/{}", dest_path, path);
-                        self.copy_file(&dst_full, &src_full, false).await?;
-                        stats.files_copied += 1;
-                        actions_done += 1;
-                    }
-                    SyncAction::CreateDirInDestination { path } => {
-                        let dst_full = format!("{}/{}", dest_path, path);
-                        self.dest.create_dir(&dst_full).await?;
-                        stats.dirs_created += 1;
-                        actions_done += 1;
-                    }
-                    SyncAction::CreateDirInSource { path } => {
-                        let src_full = format!("{}/{}", source_path, path);
-                        self.source.create_dir(&src_full).await?;
-                        stats.dirs_created += 1;
-                        actions_done += 1;
-                    }
+                let deleted = match action {
                     SyncAction::DeleteFromDestination { path } => {
-                        let dst_full = format!("{}/{}", dest_path, path);
-                        self.dest.delete(&dst_full).await?;
-                        stats.files_deleted += 1;
-                        actions_done += 1;
+                        self.dest.delete(&format!("{}/{}", dest_path, path)).await
                     }
                     SyncAction::DeleteFromSource { path } => {
-                        let src_full = format!("{}/{}", source_path, path);
-                        self.source.delete(&src_full).await?;
-                        stats.files_deleted += 1;
-                        actions_done += 1;
+                        self.source.delete(&format!("{}/{}", source_path, path)).await
                     }
-                    _ => {}
+                    _ => continue,
+                };
+                if let Err(error) = deleted {
+                    return Err(self.failed(stats, error.into()));
                 }
+                stats.files_deleted += 1;
+                actions_done += 1;
             }
             
             // Send completion progress
@@ -477,6 +608,31 @@ impl SyncEngine {
         })
     }
 
+    /// Whether two files of `size` bytes hold the same content, reading both a chunk at
+    /// a time and stopping at the first difference.
+    async fn same_content(&self, src: &str, dst: &str, size: u64) -> Result<bool> {
+        let mut offset = 0;
+        while offset < size {
+            let length = HEAL_CHUNK_SIZE.min(size - offset);
+            let (src_chunk, dst_chunk) = tokio::try_join!(
+                async {
+                    self.source.read_range_sized(src, offset, length, size).await
+                        .context(format!("Failed to read {}", src))
+                },
+                async {
+                    self.dest.read_range_sized(dst, offset, length, size).await
+                        .context(format!("Failed to read {}", dst))
+                },
+            )?;
+            // A short read means the file changed size since the scan
+            if src_chunk != dst_chunk || src_chunk.len() as u64 != length {
+                return Ok(false);
+            }
+            offset += length;
+        }
+        Ok(true)
+    }
+
     /// Perform a dry run (preview changes without applying).
     pub async fn dry_run(&mut self, source_path: &str, dest_path: &str) -> Result<SyncResult> {
         let original_dry_run = self.config.dry_run;
@@ -486,6 +642,125 @@ impl SyncEngine {
         result
     }
 
+    /// Re-copy only files whose content differs from the source.
+    ///
+    /// Unlike `sync`, this ignores mtime and compares the content of every file of
+    /// the same size, so it catches silent corruption after a copy or sync.
+    /// Files missing from the destination, or of another size, are copied as well.
+    pub async fn heal(&mut self, source_path: &str, dest_path: &str) -> Result<SyncResult> {
+        self.stats.start();
+
+        let source_files = self.scan_directory(&*self.source, source_path).await?;
+        let dest_files = self.scan_directory(&*self.dest, dest_path).await?;
+
+        let dest_map: HashMap<&str, &FileState> = dest_files
+            .iter()
+            .map(|f| (f.path.as_str(), f))
+            .collect();
+
+        let mut actions = Vec::new();
+        let mut stats = SyncStats::default();
+        let total_files = source_files.iter().filter(|f| !f.is_dir).count();
+        let mut files_checked = 0;
+
+        for file in &source_files {
+            if let Some(pattern) = self.config.exclude.excluded_by(&file.path) {
+                actions.push(SyncAction::Skip {
+                    path: file.path.clone(),
+                    reason: excluded_reason(pattern),
+                });
+                stats.files_skipped += 1;
+                continue;
+            }
+
+            match dest_map.get(file.path.as_str()) {
+                None if file.is_dir => {
+                    actions.push(SyncAction::CreateDirInDestination { path: file.path.clone() });
+                }
+                Some(dest_file) if file.is_dir || dest_file.is_dir => {
+                    if file.is_dir != dest_file.is_dir {
+                        actions.push(SyncAction::Skip {
+                            path: file.path.clone(),
+                            reason: "File/directory type mismatch".to_string(),
+                        });
+                        stats.files_skipped += 1;
+                    }
+                }
+                Some(dest_file) if dest_file.size != file.size => {
+                    stats.files_scanned += 1;
+                    actions.push(SyncAction::CopyToDestination { path: file.path.clone() });
+                }
+                None => {
+                    stats.files_scanned += 1;
+                    actions.push(SyncAction::CopyToDestination { path: file.path.clone() });
+                }
+                Some(_) => {
+                    stats.files_scanned += 1;
+                    self.send_progress(SyncProgress {
+                        phase: SyncPhase::Verifying,
+                        current_file: file.path.clone(),
+                        files_done: files_checked,
+                        total_files,
+                        bytes_done: 0,
+                        total_bytes: 0,
+                    }).await;
+
+                    let src_full = format!("{}/{}", source_path, file.path);
+                    let dst_full = format!("{}/{}", dest_path, file.path);
+                    if self.same_content(&src_full, &dst_full, file.size).await? {
+                        actions.push(SyncAction::Skip {
+                            path: file.path.clone(),
+                            reason: "Content matches".to_string(),
+                        });
+                        stats.files_skipped += 1;
+                    } else {
+                        actions.push(SyncAction::CopyToDestination { path: file.path.clone() });
+                    }
+                }
+            }
+
+            if !file.is_dir {
+                files_checked += 1;
+            }
+        }
+
+        if !self.config.dry_run {
+            let total_actions = actions.iter().filter(|a| !a.is_skip()).count();
+            let dirs_created = self.create_dirs(&actions, source_path, dest_path).await?;
+            stats.dirs_created += dirs_created;
+
+            let jobs = actions.iter()
+                .filter_map(|action| CopyJob::for_action(action, source_path, dest_path))
+                .collect();
+            let (copied, outcome) = self.run_copies(jobs, dirs_created, total_actions).await;
+            stats.files_copied += copied.files;
+            if let Err(error) = outcome {
+                return Err(self.failed(stats, error));
+            }
+            let actions_done = dirs_created + copied.files;
+
+            self.send_progress(SyncProgress {
+                phase: SyncPhase::Complete,
+                current_file: String::new(),
+                files_done: actions_done,
+                total_files: total_actions,
+                bytes_done: self.stats.bytes_transferred,
+                total_bytes: self.stats.bytes_transferred,
+            }).await;
+        }
+
+        self.stats.stop();
+        stats.bytes_transferred = self.stats.bytes_transferred;
+        stats.duration_ms = self.stats.elapsed().as_millis() as u64;
+
+        Ok(SyncResult {
+            actions,
+            conflicts: Vec::new(),
+            stats,
+            dry_run: self.config.dry_run,
+        })
+    }
+
     /// Scan a directory recursively.
     async fn scan_directory(&self, backend: &dyn StorageBackend, path: &str) -> Result<Vec<FileState>> {
         let mut all_files = Vec::new();
@@ -558,8 +833,81 @@ impl SyncEngine {
         }
     }
 
-    /// Copy a file between backends.
-    async fn copy_file(&mut self, from: &str, to: &str, source_to_dest: bool) -> Result<()> {
+    /// Create the directories `actions` ask for, parents before children.
+    ///
+    /// Runs before any copy so files copied in parallel always find their directory.
+    async fn create_dirs(&self, actions: &[SyncAction], source_path: &str, dest_path: &str) -> Result<usize> {
+        let mut dirs: Vec<&SyncAction> = actions.iter()
+            .filter(|a| matches!(a, SyncAction::CreateDirInDestination { .. } | SyncAction::CreateDirInSource { .. }))
+            .collect();
+        dirs.sort_by_key(|a| a.path().matches('/').count());
+
+        for action in &dirs {
+            let (backend, root) = match action {
+                SyncAction::CreateDirInDestination { .. } => (&self.dest, dest_path),
+                _ => (&self.source, source_path),
+            };
+            backend.create_dir(&format!("{}/{}", root, action.path())).await?;
+        }
+        Ok(dirs.len())
+    }
+
+    /// Run `jobs` with at most `max_concurrent_transfers` copies in flight.
+    ///
+    /// Progress is sent as each copy finishes, counting on from `done_before`.
+    /// The first failed copy cancels the ones still running; the totals of the copies
+    /// that finished are returned next to it.
+    async fn run_copies(&mut self, jobs: Vec<CopyJob>, done_before: usize, total: usize) -> (CopyTotals, Result<()>) {
+        let mut totals = CopyTotals::default();
+        let bytes_before = self.stats.bytes_transferred;
+        let limit = self.config.max_concurrent_transfers.max(1);
+        let engine = &*self;
+
+        let mut copies = stream::iter(jobs.into_iter().map(|job| async move {
+            let transferred = engine.copy_file(&job.from, &job.to, job.source_to_dest).await?;
+            Ok::<_, anyhow::Error>((job, transferred))
+        }))
+        .buffer_unordered(limit);
+
+        let mut outcome = Ok(());
+        while let Some(copy) = copies.next().await {
+            let (job, transferred) = match copy {
+                Ok(copy) => copy,
+                Err(error) => {
+                    outcome = Err(error);
+                    break;
+                }
+            };
+            totals.files += 1;
+            totals.bytes += transferred;
+            if !job.source_to_dest {
+                totals.bytes_to_source += transferred;
+            }
+            engine.send_progress(SyncProgress {
+                phase: SyncPhase::Transferring,
+                current_file: job.display,
+                files_done: done_before + totals.files,
+                total_files: total,
+                bytes_done: bytes_before + totals.bytes,
+                total_bytes: 0, // Unknown until we read files
+            }).await;
+        }
+        drop(copies);
+
+        self.stats.record(totals.bytes);
+        (totals, outcome)
+    }
+
+    /// Wrap `error` in a `SyncFailed` carrying `stats` for the work done so far
+    fn failed(&mut self, mut stats: SyncStats, error: anyhow::Error) -> anyhow::Error {
+        self.stats.stop();
+        stats.bytes_transferred = self.stats.bytes_transferred;
+        stats.duration_ms = self.stats.elapsed().as_millis() as u64;
+        SyncFailed { stats, error }.into()
+    }
+
+    /// Copy a file between backends, returning the bytes transferred.
+    async fn copy_file(&self, from: &str, to: &str, source_to_dest: bool) -> Result<u64> {
         let (src_backend, dst_backend) = if source_to_dest {
             (&self.source, &self.dest)
         } else {
@@ -582,8 +930,7 @@ impl SyncEngine {
             data.clone()
         };
         
-        // Record transfer
-        self.stats.record(transfer_data.len() as u64);
+        let transferred = transfer_data.len() as u64;
         
         // Write to destination (decompress if needed)
         let write_data = if self.config.compression != CompressionType::None
@@ -608,7 +955,39 @@ impl SyncEngine {
             }
         }
         
-        Ok(())
+        Ok(transferred)
+    }
+}
+
+/// Skip reason for a path left out by an exclude pattern.
+fn excluded_reason(pattern: &str) -> String {
+    format!("Excluded by pattern '{}'", pattern)
+}
+
+/// Actions that carry out a resolved conflict.
+///
+/// "Keep both" leaves each side's file in place and copies the other side's
+/// version next to it under a suffixed name, so both sides end up with both.
+fn conflict_actions(conflict: &Conflict) -> Vec<SyncAction> {
+    let path = conflict.path.clone();
+    match conflict.resolution {
+        Some(ConflictResolution::UseSource) => vec![SyncAction::CopyToDestination { path }],
+        Some(ConflictResolution::UseDest) => vec![SyncAction::CopyToSource { path }],
+        Some(ConflictResolution::KeepBoth) => vec![
+            SyncAction::CopyToDestinationAs {
+                target: conflict.side_path(ConflictSide::Source),
+                path: path.clone(),
+            },
+            SyncAction::CopyToSourceAs {
+                target: conflict.side_path(ConflictSide::Dest),
+                path,
+            },
+        ],
+        Some(ConflictResolution::Skip) => vec![SyncAction::Skip {
+            path,
+            reason: "Conflict skipped".to_string(),
+        }],
+        None => vec![SyncAction::Conflict { path }],
     }
 }
 
@@ -624,6 +1003,8 @@ mod tests {
         assert_eq!(config.conflict_strategy, ConflictStrategy::LastWriteWins);
         assert!(!config.dry_run);
         assert!(!config.delete_extra);
+        assert!(!config.confirm_deletes);
+        assert_eq!(config.max_concurrent_transfers, DEFAULT_MAX_CONCURRENT_TRANSFERS);
     }
 
     #[test]
@@ -634,4 +1015,407 @@ mod tests {
         let skip = SyncAction::Skip { path: "skip.txt".to_string(), reason: "test".to_string() };
         assert!(skip.is_skip());
     }
+
+    #[tokio::test]
+    async fn test_heal_recopies_only_corrupted_files() {
+        use crate::fs::local::LocalBackend;
+
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        for dir in [src.path(), dst.path()] {
+            std::fs::write(dir.join("good.txt"), b"intact").unwrap();
+            std::fs::write(dir.join("bad.txt"), b"original").unwrap();
+        }
+        // Same size, newer mtime: a normal sync would consider it up to date
+        std::fs::write(dst.path().join("bad.txt"), b"corrupt!").unwrap();
+        std::fs::write(src.path().join("missing.txt"), b"new").unwrap();
+
+        let mut engine = SyncEngine::new(
+            Arc::new(LocalBackend::new(src.path().to_path_buf())),
+            Arc::new(LocalBackend::new(dst.path().to_path_buf())),
+            SyncConfig::default(),
+        );
+        let result = engine
+            .heal(src.path().to_str().unwrap(), dst.path().to_str().unwrap())
+            .await
+            .unwrap();
+
+        let mut copied: Vec<&str> = result.actions.iter()
+            .filter(|a| matches!(a, SyncAction::CopyToDestination { .. }))
+            .map(|a| a.path())
+            .collect();
+        copied.sort_unstable();
+        assert_eq!(copied, vec!["bad.txt", "missing.txt"]);
+        assert_eq!(result.stats.files_copied, 2);
+        assert_eq!(result.stats.files_skipped, 1);
+
+        assert_eq!(std::fs::read(dst.path().join("bad.txt")).unwrap(), b"original");
+        assert_eq!(std::fs::read(dst.path().join("missing.txt")).unwrap(), b"new");
+    }
+
+    #[tokio::test]
+    async fn test_heal_compares_past_the_first_chunk() {
+        use crate::fs::local::LocalBackend;
+
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        let mut data = vec![7u8; HEAL_CHUNK_SIZE as usize + 10];
+        std::fs::write(src.path().join("big.bin"), &data).unwrap();
+        std::fs::write(src.path().join("same.bin"), &data).unwrap();
+        std::fs::write(dst.path().join("same.bin"), &data).unwrap();
+        // Flipped in the second chunk only
+        *data.last_mut().unwrap() = 8;
+        std::fs::write(dst.path().join("big.bin"), &data).unwrap();
+
+        let mut engine = SyncEngine::new(
+            Arc::new(LocalBackend::new(src.path().to_path_buf())),
+            Arc::new(LocalBackend::new(dst.path().to_path_buf())),
+            SyncConfig { dry_run: true, ..Default::default() },
+        );
+        let result = engine
+            .heal(src.path().to_str().unwrap(), dst.path().to_str().unwrap())
+            .await
+            .unwrap();
+
+        let copied: Vec<&str> = result.actions.iter()
+            .filter(|a| matches!(a, SyncAction::CopyToDestination { .. }))
+            .map(|a| a.path())
+            .collect();
+        assert_eq!(copied, vec!["big.bin"]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_copy_reports_the_copies_before_it() {
+        use crate::fs::local::LocalBackend;
+
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        std::fs::write(src.path().join("a.txt"), b"copied").unwrap();
+        std::fs::write(src.path().join("b.txt"), b"blocked").unwrap();
+        // A directory in the way of the second file makes its write fail
+        std::fs::create_dir(dst.path().join("b.txt")).unwrap();
+        std::fs::write(dst.path().join("b.txt").join("keep"), b"").unwrap();
+
+        let mut engine = SyncEngine::new(
+            Arc::new(LocalBackend::new(src.path().to_path_buf())),
+            Arc::new(LocalBackend::new(dst.path().to_path_buf())),
+            SyncConfig { max_concurrent_transfers: 1, ..Default::default() },
+        );
+        let err = engine
+            .sync(src.path().to_str().unwrap(), dst.path().to_str().unwrap())
+            .await
+            .unwrap_err();
+
+        let failed = err.downcast_ref::<SyncFailed>().expect("partial stats");
+        assert_eq!(failed.stats.files_copied, 1);
+        assert_eq!(failed.stats.bytes_transferred, 6);
+        assert!(err.to_string().contains("after copying 1 files, 6 B"), "{}", err);
+        assert_eq!(std::fs::read(dst.path().join("a.txt")).unwrap(), b"copied");
+    }
+
+    fn resolved_actions(strategy: ConflictStrategy) -> Vec<SyncAction> {
+        use chrono::TimeZone;
+
+        let older = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let newer = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 30).unwrap();
+        let mut resolver = ConflictResolver::new(strategy);
+        resolver.add_conflict(
+            "docs/notes.md",
+            FileInfo::new("docs/notes.md", 10, Some(older)),
+            FileInfo::new("docs/notes.md", 20, Some(newer)),
+        );
+        resolver.resolve_all();
+
+        match resolver.resolved.first() {
+            Some(conflict) => conflict_actions(conflict),
+            None => vec![SyncAction::Conflict { path: "docs/notes.md".to_string() }],
+        }
+    }
+
+    #[test]
+    fn test_conflict_strategies_produce_expected_actions() {
+        let path = || "docs/notes.md".to_string();
+
+        // Destination is newer in the synthetic conflict
+        assert_eq!(resolved_actions(ConflictStrategy::LastWriteWins), vec![SyncAction::CopyToSource { path: path() }]);
+        assert_eq!(resolved_actions(ConflictStrategy::NewerWins), vec![SyncAction::CopyToSource { path: path() }]);
+        assert_eq!(resolved_actions(ConflictStrategy::SourceWins), vec![SyncAction::CopyToDestination { path: path() }]);
+        assert_eq!(resolved_actions(ConflictStrategy::DestWins), vec![SyncAction::CopyToSource { path: path() }]);
+        assert_eq!(
+            resolved_actions(ConflictStrategy::KeepBoth),
+            vec![
+                SyncAction::CopyToDestinationAs { path: path(), target: "docs/notes.conflict-source.md".to_string() },
+                SyncAction::CopyToSourceAs { path: path(), target: "docs/notes.conflict-dest.md".to_string() },
+            ]
+        );
+        assert_eq!(
+            resolved_actions(ConflictStrategy::Skip),
+            vec![SyncAction::Skip { path: path(), reason: "Conflict skipped".to_string() }]
+        );
+        assert_eq!(resolved_actions(ConflictStrategy::Manual), vec![SyncAction::Conflict { path: path() }]);
+    }
+
+    #[tokio::test]
+    async fn test_keep_both_copies_each_version_to_the_other_side() {
+        use crate::fs::local::LocalBackend;
+
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        // Different sizes written within a minute of each other: a bidirectional conflict
+        std::fs::write(src.path().join("notes.md"), b"source edit").unwrap();
+        std::fs::write(dst.path().join("notes.md"), b"dest edit, longer").unwrap();
+
+        let config = SyncConfig {
+            mode: SyncMode::Bidirectional,
+            conflict_strategy: ConflictStrategy::KeepBoth,
+            ..Default::default()
+        };
+        let mut engine = SyncEngine::new(
+            Arc::new(LocalBackend::new(src.path().to_path_buf())),
+            Arc::new(LocalBackend::new(dst.path().to_path_buf())),
+            config,
+        );
+        let result = engine
+            .sync(src.path().to_str().unwrap(), dst.path().to_str().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(result.stats.conflicts, 1);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(std::fs::read(dst.path().join("notes.conflict-source.md")).unwrap(), b"source edit");
+        assert_eq!(std::fs::read(src.path().join("notes.conflict-dest.md")).unwrap(), b"dest edit, longer");
+        assert_eq!(std::fs::read(src.path().join("notes.md")).unwrap(), b"source edit");
+        assert_eq!(std::fs::read(dst.path().join("notes.md")).unwrap(), b"dest edit, longer");
+        // Each version crossed once; the destination's went back to the source
+        assert_eq!(result.stats.bytes_transferred, 11 + 17);
+        assert_eq!(result.stats.bytes_to_source, 17);
+    }
+
+    #[tokio::test]
+    async fn test_mirror_deletes_only_once_confirmed() {
+        use crate::fs::local::LocalBackend;
+
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        std::fs::write(src.path().join("keep.txt"), b"keep").unwrap();
+        for name in ["a.txt", "b.txt", "c.txt", "d.txt", "e.txt", "f.txt"] {
+            std::fs::write(dst.path().join(name), b"extra").unwrap();
+        }
+        let (src_path, dst_path) = (src.path().to_str().unwrap(), dst.path().to_str().unwrap());
+        let engine = |config| SyncEngine::new(
+            Arc::new(LocalBackend::new(src.path().to_path_buf())),
+            Arc::new(LocalBackend::new(dst.path().to_path_buf())),
+            config,
+        );
+
+        let err = engine(SyncConfig { mode: SyncMode::Mirror, ..Default::default() })
+            .sync(src_path, dst_path)
+            .await
+            .unwrap_err();
+        let unconfirmed = err.downcast_ref::<UnconfirmedDeletes>().unwrap();
+        assert_eq!(unconfirmed.paths.len(), 6);
+        assert_eq!(unconfirmed.sample().len(), UnconfirmedDeletes::SAMPLE_SIZE);
+        assert!(err.to_string().starts_with("Sync would delete 6 files ("));
+        // Nothing was copied or deleted
+        assert!(!dst.path().join("keep.txt").exists());
+        assert!(dst.path().join("a.txt").exists());
+
+        // A dry run still plans the deletions
+        let plan = engine(SyncConfig { mode: SyncMode::Mirror, dry_run: true, ..Default::default() })
+            .sync(src_path, dst_path)
+            .await
+            .unwrap();
+        assert_eq!(plan.actions.iter().filter(|a| matches!(a, SyncAction::DeleteFromDestination { .. })).count(), 6);
+
+        let result = engine(SyncConfig { mode: SyncMode::Mirror, confirm_deletes: true, ..Default::default() })
+            .sync(src_path, dst_path)
+            .await
+            .unwrap();
+        assert_eq!(result.stats.files_deleted, 6);
+        assert_eq!(result.stats.files_copied, 1);
+        assert!(!dst.path().join("a.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_default_excludes_skip_vcs_dirs_unless_disabled() {
+        use crate::fs::local::LocalBackend;
+
+        // The local backend never lists dotfiles, so use CVS and backup files here
+        let src = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(src.path().join("CVS")).unwrap();
+        std::fs::write(src.path().join("CVS/Root"), b":pserver:").unwrap();
+        std::fs::write(src.path().join("notes.txt~"), b"backup").unwrap();
+        std::fs::write(src.path().join("notes.txt"), b"notes").unwrap();
+
+        for (exclude, expect_junk) in [(ExcludePatterns::with_defaults(), false), (ExcludePatterns::new(), true)] {
+            let dst = tempfile::tempdir().unwrap();
+            let config = SyncConfig { exclude, ..Default::default() };
+            let mut engine = SyncEngine::new(
+                Arc::new(LocalBackend::new(src.path().to_path_buf())),
+                Arc::new(LocalBackend::new(dst.path().to_path_buf())),
+                config,
+            );
+            let result = engine.sync(src.path().to_str().unwrap(), dst.path().to_str().unwrap()).await.unwrap();
+
+            assert!(dst.path().join("notes.txt").exists());
+            assert_eq!(dst.path().join("CVS/Root").exists(), expect_junk);
+            assert_eq!(dst.path().join("notes.txt~").exists(), expect_junk);
+            // Skips name the pattern that excluded them
+            let backup_skip = SyncAction::Skip {
+                path: "notes.txt~".to_string(),
+                reason: "Excluded by pattern '*~'".to_string(),
+            };
+            assert_eq!(result.actions.contains(&backup_skip), !expect_junk);
+        }
+    }
+
+    /// Local destination that refuses writes into missing directories (like a strict
+    /// filesystem without implicit parent creation) and tracks overlapping writes.
+    struct StrictDirBackend {
+        inner: crate::fs::local::LocalBackend,
+        in_flight: std::sync::atomic::AtomicUsize,
+        max_in_flight: std::sync::atomic::AtomicUsize,
+    }
+
+    impl StrictDirBackend {
+        fn new(root: &std::path::Path) -> Self {
+            Self {
+                inner: crate::fs::local::LocalBackend::new(root.to_path_buf()),
+                in_flight: Default::default(),
+                max_in_flight: Default::default(),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl StorageBackend for StrictDirBackend {
+        async fn list_dir(&self, path: &str) -> crate::fs::FsResult<Vec<FileEntry>> {
+            self.inner.list_dir(path).await
+        }
+        async fn delete(&self, path: &str) -> crate::fs::FsResult<()> {
+            self.inner.delete(path).await
+        }
+        async fn create_dir(&self, path: &str) -> crate::fs::FsResult<()> {
+            let parent = std::path::Path::new(path).parent().unwrap();
+            if !parent.is_dir() {
+                return Err(crate::fs::FsError::NotFound(format!("{} has no parent", path)));
+            }
+            self.inner.create_dir(path).await
+        }
+        async fn is_dir(&self, path: &str) -> crate::fs::FsResult<bool> {
+            self.inner.is_dir(path).await
+        }
+        async fn upload(&self, local_path: &std::path::Path, remote_path: &str) -> crate::fs::FsResult<()> {
+            self.inner.upload(local_path, remote_path).await
+        }
+        async fn download(&self, remote_path: &str, local_path: &std::path::Path) -> crate::fs::FsResult<()> {
+            self.inner.download(remote_path, local_path).await
+        }
+        async fn read_bytes(&self, path: &str) -> crate::fs::FsResult<Vec<u8>> {
+            self.inner.read_bytes(path).await
+        }
+        async fn write_bytes(&self, path: &str, data: Vec<u8>) -> crate::fs::FsResult<()> {
+            use std::sync::atomic::Ordering;
+
+            let parent = std::path::Path::new(path).parent().unwrap();
+            if !parent.is_dir() {
+                return Err(crate::fs::FsError::NotFound(format!("{} has no parent", path)));
+            }
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            let result = self.inner.write_bytes(path, data).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            result
+        }
+        fn backend_type(&self) -> crate::fs::backend::BackendType {
+            crate::fs::backend::BackendType::Local
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parallel_sync_copies_every_file() {
+        use crate::fs::local::LocalBackend;
+
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        let mut expected_bytes = 0;
+        for i in 0..12 {
+            let dir = src.path().join(format!("d{}", i % 3));
+            std::fs::create_dir_all(&dir).unwrap();
+            let content = format!("file number {}", i);
+            expected_bytes += content.len() as u64;
+            std::fs::write(dir.join(format!("f{}.txt", i)), content).unwrap();
+        }
+
+        let dest = Arc::new(StrictDirBackend::new(dst.path()));
+        let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+        let config = SyncConfig { max_concurrent_transfers: 4, ..Default::default() };
+        let mut engine = SyncEngine::with_progress(
+            Arc::new(LocalBackend::new(src.path().to_path_buf())),
+            dest.clone(),
+            config,
+            tx,
+        );
+        let result = engine
+            .sync(src.path().to_str().unwrap(), dst.path().to_str().unwrap())
+            .await
+            .unwrap();
+        drop(engine);
+
+        for i in 0..12 {
+            let written = std::fs::read_to_string(dst.path().join(format!("d{}/f{}.txt", i % 3, i))).unwrap();
+            assert_eq!(written, format!("file number {}", i));
+        }
+        assert_eq!(result.stats.files_copied, 12);
+        assert_eq!(result.stats.dirs_created, 3);
+        assert_eq!(result.stats.bytes_transferred, expected_bytes);
+        assert!(dest.max_in_flight.load(std::sync::atomic::Ordering::SeqCst) > 1);
+        assert!(dest.max_in_flight.load(std::sync::atomic::Ordering::SeqCst) <= 4);
+
+        // Every finished copy is counted once, ending at the total
+        let mut transferring = Vec::new();
+        let mut last = None;
+        while let Some(progress) = rx.recv().await {
+            if progress.phase == SyncPhase::Transferring {
+                transferring.push(progress.files_done);
+            }
+            last = Some(progress);
+        }
+        assert_eq!(transferring, (4..=15).collect::<Vec<_>>());
+        let last = last.unwrap();
+        assert_eq!(last.phase, SyncPhase::Complete);
+        assert_eq!((last.files_done, last.total_files), (15, 15));
+        assert_eq!(last.bytes_done, expected_bytes);
+    }
+
+    #[tokio::test]
+    async fn test_parallel_sync_creates_dirs_before_their_files() {
+        use crate::fs::local::LocalBackend;
+
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(src.path().join("a/b/c")).unwrap();
+        std::fs::create_dir_all(src.path().join("z")).unwrap();
+        std::fs::write(src.path().join("top.txt"), b"top").unwrap();
+        std::fs::write(src.path().join("a/one.txt"), b"one").unwrap();
+        std::fs::write(src.path().join("a/b/c/deep.txt"), b"deep").unwrap();
+        std::fs::write(src.path().join("z/last.txt"), b"last").unwrap();
+
+        let config = SyncConfig { max_concurrent_transfers: 8, ..Default::default() };
+        let mut engine = SyncEngine::new(
+            Arc::new(LocalBackend::new(src.path().to_path_buf())),
+            Arc::new(StrictDirBackend::new(dst.path())),
+            config,
+        );
+        let result = engine
+            .sync(src.path().to_str().unwrap(), dst.path().to_str().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(result.stats.dirs_created, 4);
+        assert_eq!(result.stats.files_copied, 4);
+        assert_eq!(std::fs::read(dst.path().join("a/b/c/deep.txt")).unwrap(), b"deep");
+        assert_eq!(std::fs::read(dst.path().join("z/last.txt")).unwrap(), b"last");
+    }
 }
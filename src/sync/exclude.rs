@@ -3,9 +3,11 @@
 //! Supports .gitignore-style patterns for excluding files from sync.
 
 use anyhow::Result;
-use globset::{Glob, GlobSet, GlobSetBuilder};
+use crate::hash::wildcard::ignore_case_default;
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
 
 /// Default patterns to exclude from sync operations.
+/// Keep the README "Exclude Patterns" list in sync with this one.
 pub const DEFAULT_EXCLUDES: &[&str] = &[
     // Version control
     ".git",
@@ -14,14 +16,24 @@ pub const DEFAULT_EXCLUDES: &[&str] = &[
     ".svn/**",
     ".hg",
     ".hg/**",
+    ".bzr",
+    ".bzr/**",
+    "CVS",
+    "CVS/**",
     
     // OS-specific
     ".DS_Store",
+    "._*",
     "Thumbs.db",
     "desktop.ini",
+    
+    // Editor swap, lock and backup files
     "*.swp",
     "*.swo",
+    "*.swx",
     "*~",
+    ".#*",
+    "#*#",
     
     // IDE/Editor
     ".idea",
@@ -56,6 +68,8 @@ pub struct ExcludePatterns {
     patterns: Vec<String>,
     /// Whether to use default excludes.
     use_defaults: bool,
+    /// Whether patterns match regardless of case.
+    ignore_case: bool,
 }
 
 impl Default for ExcludePatterns {
@@ -64,6 +78,11 @@ impl Default for ExcludePatterns {
     }
 }
 
+/// Compile one exclude pattern.
+fn compile(pattern: &str, ignore_case: bool) -> Result<Glob, globset::Error> {
+    GlobBuilder::new(pattern).case_insensitive(ignore_case).build()
+}
+
 impl ExcludePatterns {
     /// Create a new empty exclude pattern set.
     pub fn new() -> Self {
@@ -71,16 +90,18 @@ impl ExcludePatterns {
             glob_set: GlobSet::empty(),
             patterns: Vec::new(),
             use_defaults: false,
+            ignore_case: ignore_case_default(),
         }
     }
 
     /// Create with default exclude patterns.
     pub fn with_defaults() -> Self {
+        let ignore_case = ignore_case_default();
         let mut builder = GlobSetBuilder::new();
         let mut patterns = Vec::new();
         
         for pattern in DEFAULT_EXCLUDES {
-            if let Ok(glob) = Glob::new(pattern) {
+            if let Ok(glob) = compile(pattern, ignore_case) {
                 builder.add(glob);
                 patterns.push(pattern.to_string());
             }
@@ -90,16 +111,18 @@ impl ExcludePatterns {
             glob_set: builder.build().unwrap_or_else(|_| GlobSet::empty()),
             patterns,
             use_defaults: true,
+            ignore_case,
         }
     }
 
     /// Create from a list of patterns.
     pub fn from_patterns(patterns: &[&str]) -> Result<Self> {
+        let ignore_case = ignore_case_default();
         let mut builder = GlobSetBuilder::new();
         let mut pattern_list = Vec::new();
         
         for pattern in patterns {
-            let glob = Glob::new(pattern)?;
+            let glob = compile(pattern, ignore_case)?;
             builder.add(glob);
             pattern_list.push(pattern.to_string());
         }
@@ -108,42 +131,45 @@ impl ExcludePatterns {
             glob_set: builder.build()?,
             patterns: pattern_list,
             use_defaults: false,
+            ignore_case,
         })
     }
 
-    /// Add a pattern to the exclude set.
-    pub fn add_pattern(&mut self, pattern: &str) -> Result<()> {
-        // Rebuild the glob set with the new pattern
+    /// Rebuild the glob set from the pattern strings.
+    fn rebuild(&mut self) -> Result<()> {
         let mut builder = GlobSetBuilder::new();
-        
         for existing in &self.patterns {
-            if let Ok(glob) = Glob::new(existing) {
+            if let Ok(glob) = compile(existing, self.ignore_case) {
                 builder.add(glob);
             }
         }
         
-        let glob = Glob::new(pattern)?;
-        builder.add(glob);
-        self.patterns.push(pattern.to_string());
-        
         self.glob_set = builder.build()?;
         Ok(())
     }
 
+    /// Add a pattern to the exclude set.
+    pub fn add_pattern(&mut self, pattern: &str) -> Result<()> {
+        compile(pattern, self.ignore_case)?;
+        self.patterns.push(pattern.to_string());
+        self.rebuild()
+    }
+
     /// Remove a pattern from the exclude set.
     pub fn remove_pattern(&mut self, pattern: &str) -> Result<()> {
         self.patterns.retain(|p| p != pattern);
-        
-        // Rebuild glob set
-        let mut builder = GlobSetBuilder::new();
-        for existing in &self.patterns {
-            if let Ok(glob) = Glob::new(existing) {
-                builder.add(glob);
-            }
-        }
-        
-        self.glob_set = builder.build()?;
-        Ok(())
+        self.rebuild()
+    }
+
+    /// Match patterns regardless of case (the default on macOS and Windows).
+    pub fn set_ignore_case(&mut self, ignore_case: bool) -> Result<()> {
+        self.ignore_case = ignore_case;
+        self.rebuild()
+    }
+
+    /// Whether patterns match regardless of case.
+    pub fn ignores_case(&self) -> bool {
+        self.ignore_case
     }
 
     /// Check if a path should be excluded.
@@ -172,6 +198,30 @@ impl ExcludePatterns {
         false
     }
 
+    /// The pattern that excludes `path`, or `None` if nothing does.
+    ///
+    /// Checks the same names as [`is_excluded`](Self::is_excluded) in the same order (the
+    /// whole path, the file name, then each component); among patterns matching the same
+    /// name, the one added first is reported.
+    pub fn excluded_by(&self, path: &str) -> Option<&str> {
+        let as_path = std::path::Path::new(path);
+        let components = as_path.components().filter_map(|component| match component {
+            std::path::Component::Normal(name) => Some(name),
+            _ => None,
+        });
+        std::iter::once(std::borrow::Cow::Borrowed(path))
+            .chain(as_path.file_name().into_iter().chain(components).map(|name| name.to_string_lossy()))
+            .find_map(|name| {
+                let index = self.glob_set.matches(name.as_ref()).into_iter().min()?;
+                self.patterns.get(index).map(String::as_str)
+            })
+    }
+
+    /// Whether the default patterns are part of this set.
+    pub fn uses_defaults(&self) -> bool {
+        self.use_defaults
+    }
+
     /// Get the list of patterns.
     pub fn patterns(&self) -> &[String] {
         &self.patterns
@@ -230,6 +280,40 @@ mod tests {
         assert!(!excludes.is_excluded("README.md"));
     }
 
+    #[test]
+    fn test_default_excludes_cover_vcs_and_editor_junk() {
+        let excludes = ExcludePatterns::with_defaults();
+
+        for path in [
+            ".git/objects/ab/cdef",
+            "sub/.svn/entries",
+            "docs/.hg/store",
+            ".bzr/branch",
+            "old/CVS/Root",
+            "notes/.main.rs.swp",
+            "src/lib.rs~",
+            "src/.#lib.rs",
+            "src/#lib.rs#",
+            "photos/.DS_Store",
+            "photos/._IMG_0001.jpg",
+        ] {
+            assert!(excludes.is_excluded(path), "{} should be excluded", path);
+        }
+
+        assert!(!excludes.is_excluded("src/gitignore.rs"));
+        assert!(!excludes.is_excluded("photos/IMG_0001.jpg"));
+    }
+
+    #[test]
+    fn test_without_defaults_nothing_is_excluded() {
+        let excludes = ExcludePatterns::new();
+
+        assert!(!excludes.uses_defaults());
+        assert!(!excludes.is_excluded(".git/config"));
+        assert!(!excludes.is_excluded("file.swp"));
+        assert!(!excludes.is_excluded(".DS_Store"));
+    }
+
     #[test]
     fn test_custom_patterns() {
         let excludes = ExcludePatterns::from_patterns(&["*.log", "temp/**"]).unwrap();
@@ -240,6 +324,37 @@ mod tests {
         assert!(!excludes.is_excluded("main.rs"));
     }
 
+    #[test]
+    fn test_excluded_by_reports_the_matching_pattern() {
+        let excludes = ExcludePatterns::from_patterns(&["*.log", "build", "build/**", "secret.txt"]).unwrap();
+
+        assert_eq!(excludes.excluded_by("debug.log"), Some("*.log"));
+        assert_eq!(excludes.excluded_by("build/out/app.o"), Some("build/**"));
+        assert_eq!(excludes.excluded_by("src/build"), Some("build"));
+        assert_eq!(excludes.excluded_by("docs/secret.txt"), Some("secret.txt"));
+        assert_eq!(excludes.excluded_by("src/main.rs"), None);
+
+        let defaults = ExcludePatterns::with_defaults();
+        assert_eq!(defaults.excluded_by("old/CVS/Root"), Some("CVS"));
+        assert_eq!(defaults.excluded_by("photos/.DS_Store"), Some(".DS_Store"));
+        assert_eq!(defaults.excluded_by("src/lib.rs~"), Some("*~"));
+        assert_eq!(defaults.excluded_by("README.md"), None);
+        for path in ["node_modules/left-pad/index.js", "a/b/c.pyc", "notes/.main.rs.swp"] {
+            assert_eq!(defaults.excluded_by(path).is_some(), defaults.is_excluded(path), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_brace_and_class_patterns() {
+        let excludes = ExcludePatterns::from_patterns(&["*.{tmp,cache}", "test[0-9].log"]).unwrap();
+
+        assert!(excludes.is_excluded("a.tmp"));
+        assert!(excludes.is_excluded("sub/b.cache"));
+        assert!(excludes.is_excluded("logs/test7.log"));
+        assert!(!excludes.is_excluded("test10.log"));
+        assert!(!excludes.is_excluded("a.tmpl"));
+    }
+
     #[test]
     fn test_gitignore_parsing() {
         let content = r#"
@@ -257,6 +372,30 @@ temp/
         assert!(excludes.is_excluded("temp/"));
     }
 
+    #[test]
+    fn test_ignore_case_on_mixed_case_tree() {
+        let paths = ["Node_Modules/pkg/index.js", "Build/App.TMP", "docs/.ds_store", "src/Main.rs"];
+
+        let mut excludes = ExcludePatterns::with_defaults();
+        excludes.add_pattern("build/**").unwrap();
+        excludes.set_ignore_case(false).unwrap();
+        assert!(!excludes.ignores_case());
+        for path in paths {
+            assert!(!excludes.is_excluded(path), "{} should be kept", path);
+        }
+
+        excludes.set_ignore_case(true).unwrap();
+        assert!(excludes.ignores_case());
+        for path in &paths[..3] {
+            assert!(excludes.is_excluded(path), "{} should be excluded", path);
+        }
+        assert!(!excludes.is_excluded("src/Main.rs"));
+
+        // Patterns added later follow the same setting
+        excludes.add_pattern("*.LOG").unwrap();
+        assert!(excludes.is_excluded("debug.log"));
+    }
+
     #[test]
     fn test_add_remove_pattern() {
         let mut excludes = ExcludePatterns::new();
@@ -5,15 +5,18 @@
 
 pub mod conflict;
 pub mod compression;
+pub mod diff;
 pub mod engine;
 pub mod exclude;
 pub mod hash;
+pub mod overlap;
 pub mod throttle;
 pub mod watcher;
 
-pub use conflict::{Conflict, ConflictStrategy};
+pub use conflict::{Conflict, ConflictResolution, ConflictSide, ConflictStrategy};
 pub use compression::{CompressionType, CompressedReader, CompressedWriter};
-pub use engine::{SyncEngine, SyncConfig, SyncResult, SyncStatus, SyncAction, SyncMode, SyncProgress, SyncPhase};
+pub use diff::{DiffLine, DiffLineKind};
+pub use engine::{DEFAULT_MAX_CONCURRENT_TRANSFERS, SyncEngine, SyncConfig, SyncResult, SyncStatus, SyncAction, SyncMode, SyncProgress, SyncPhase, SyncFailed, UnconfirmedDeletes};
 pub use exclude::ExcludePatterns;
 pub use hash::{HashType, FileHash, hash_file, hash_bytes};
 pub use throttle::BandwidthLimiter;
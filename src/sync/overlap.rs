@@ -0,0 +1,143 @@
+//! Self-sync guard.
+//!
+//! Syncing a location onto itself, or into/out of one of its own subdirectories, copies
+//! files onto themselves or keeps feeding its own output back in (and mirror mode would
+//! delete from the tree it reads). These checks detect that before anything runs.
+
+use std::path::{Component, Path};
+
+use crate::fs::{BackendType, StorageBackend};
+
+/// Whether two backends address the same storage: the same instance, the same bucket
+/// or PVC, or both the local filesystem.
+pub fn same_storage(source: &dyn StorageBackend, dest: &dyn StorageBackend) -> bool {
+    if std::ptr::addr_eq(source as *const dyn StorageBackend, dest as *const dyn StorageBackend) {
+        return true;
+    }
+    let source_type = source.backend_type();
+    !matches!(source_type, BackendType::Selecting) && source_type == dest.backend_type()
+}
+
+/// Path components after resolving `.`, `..` and repeated or trailing slashes.
+/// Leading slashes are ignored, so `/data/x` and `data/x/` are the same object-store prefix.
+pub fn normalize_sync_path(path: &str) -> Vec<String> {
+    let mut parts: Vec<String> = Vec::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            Component::ParentDir => {
+                parts.pop();
+            }
+            Component::RootDir | Component::CurDir | Component::Prefix(_) => {}
+        }
+    }
+    parts
+}
+
+/// Whether one path is the other or contains it
+pub fn paths_overlap(a: &str, b: &str) -> bool {
+    let (a, b) = (normalize_sync_path(a), normalize_sync_path(b));
+    a.starts_with(&b) || b.starts_with(&a)
+}
+
+/// Absolute form of a sync root; local paths are canonicalized so symlinks resolve
+fn resolve_root(backend: &dyn StorageBackend, path: &str) -> String {
+    if !matches!(backend.backend_type(), BackendType::Local) {
+        return path.to_string();
+    }
+    let full = backend.display_path(path);
+    std::fs::canonicalize(&full)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or(full)
+}
+
+/// Error message if syncing `source_path` to `dest_path` would overlap, `None` if it is safe
+pub fn check_overlap(
+    source: &dyn StorageBackend,
+    source_path: &str,
+    dest: &dyn StorageBackend,
+    dest_path: &str,
+) -> Option<String> {
+    if !same_storage(source, dest) {
+        return None;
+    }
+    let (source_root, dest_root) = (resolve_root(source, source_path), resolve_root(dest, dest_path));
+    if !paths_overlap(&source_root, &dest_root) {
+        return None;
+    }
+
+    let relation = if normalize_sync_path(&source_root) == normalize_sync_path(&dest_root) {
+        "are the same location"
+    } else {
+        "overlap (one is inside the other)"
+    };
+    Some(format!(
+        "Refusing to sync: source {} and destination {} {}",
+        source.display_path(source_path),
+        dest.display_path(dest_path),
+        relation
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::LocalBackend;
+
+    #[test]
+    fn test_paths_overlap() {
+        assert!(paths_overlap("/data/a", "/data/a/"));
+        assert!(paths_overlap("/data/a", "/data/a/b"));
+        assert!(paths_overlap("/data/a/b/..", "/data//a"));
+        assert!(paths_overlap("/", "/anything"));
+        assert!(!paths_overlap("/data/a", "/data/ab"));
+        assert!(!paths_overlap("/data/a", "/data/b"));
+
+        // Object-store prefixes, with or without leading slash
+        assert!(paths_overlap("photos/2024/", "/photos"));
+        assert!(paths_overlap("", "photos"));
+        assert!(!paths_overlap("photos/2024", "photos/2025"));
+    }
+
+    #[test]
+    fn test_check_overlap_local() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+        std::fs::create_dir_all(dir.path().join("dst")).unwrap();
+        let root = dir.path().to_string_lossy().to_string();
+
+        // Separate instances still share the local filesystem
+        let source = LocalBackend::new(dir.path().to_path_buf());
+        let dest = LocalBackend::new(dir.path().to_path_buf());
+        assert!(check_overlap(&source, "src", &dest, "dst").is_none());
+        assert!(check_overlap(&source, "src", &dest, &format!("{}/src/nested", root)).is_some());
+        let same = check_overlap(&source, "src", &dest, &format!("{}/./src/", root)).unwrap();
+        assert!(same.contains("same location"), "{}", same);
+
+        // Symlinks resolve to their target
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.path().join("src"), dir.path().join("link")).unwrap();
+            assert!(check_overlap(&source, "link", &dest, "src").is_some());
+        }
+    }
+
+    #[test]
+    fn test_check_overlap_cloud() {
+        use crate::fs::backend::S3Provider;
+        use crate::fs::s3::S3Fs;
+
+        let bucket = |name: &str| {
+            let provider = S3Provider::Custom { name: "mock".to_string(), endpoint: "http://127.0.0.1:9".to_string() };
+            S3Fs::new(name, "us-east-1", "key", "secret", provider).unwrap()
+        };
+        let (a, also_a, b) = (bucket("a"), bucket("a"), bucket("b"));
+
+        assert!(check_overlap(&a, "/backup/", &also_a, "backup/daily").is_some());
+        assert!(check_overlap(&a, "backup", &also_a, "restore").is_none());
+        assert!(check_overlap(&a, "backup", &b, "backup").is_none());
+        // A local directory never overlaps a bucket prefix
+        let local = LocalBackend::new(std::env::temp_dir());
+        assert!(check_overlap(&local, "/backup", &a, "/backup").is_none());
+    }
+}
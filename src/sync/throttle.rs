@@ -71,6 +71,32 @@ impl Default for BandwidthLimit {
     }
 }
 
+impl std::str::FromStr for BandwidthLimit {
+    type Err = String;
+
+    /// Parse a rate like `500K`, `10M`, `1.5G` or plain bytes per second (`0` = unlimited).
+    /// Decimal units, matching `display()`; an optional `B`, `B/s` or `/s` suffix is ignored.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let lower = trimmed.to_ascii_lowercase();
+        let lower = lower.trim_end_matches("/s").trim_end_matches('b');
+        let (number, multiplier) = match lower.chars().last() {
+            Some('k') => (&lower[..lower.len() - 1], 1_000.0),
+            Some('m') => (&lower[..lower.len() - 1], 1_000_000.0),
+            Some('g') => (&lower[..lower.len() - 1], 1_000_000_000.0),
+            _ => (lower, 1.0),
+        };
+        let value: f64 = number
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid bandwidth '{}' (expected e.g. 500K, 10M, 1G)", trimmed))?;
+        if value < 0.0 || !value.is_finite() {
+            return Err(format!("Invalid bandwidth '{}'", trimmed));
+        }
+        Ok(Self::new((value * multiplier) as u64))
+    }
+}
+
 /// Bandwidth limiter using token bucket algorithm.
 #[derive(Clone)]
 pub struct BandwidthLimiter {
@@ -241,6 +267,17 @@ mod tests {
         assert_eq!(BandwidthLimit::new(2_500_000_000).display(), "2.5 GB/s");
     }
 
+    #[test]
+    fn test_bandwidth_limit_parse() {
+        assert_eq!("1000".parse::<BandwidthLimit>().unwrap().bytes_per_second, 1000);
+        assert_eq!("500K".parse::<BandwidthLimit>().unwrap().bytes_per_second, 500_000);
+        assert_eq!("10MB/s".parse::<BandwidthLimit>().unwrap().bytes_per_second, 10_000_000);
+        assert_eq!("1.5g".parse::<BandwidthLimit>().unwrap().bytes_per_second, 1_500_000_000);
+        assert!(!"0".parse::<BandwidthLimit>().unwrap().is_limited());
+        assert!("fast".parse::<BandwidthLimit>().is_err());
+        assert!("-1M".parse::<BandwidthLimit>().is_err());
+    }
+
     #[test]
     fn test_unlimited_limiter() {
         let limiter = BandwidthLimiter::unlimited();
@@ -9,10 +9,11 @@ use ratatui::{
 };
 
 use crate::app::{App, AppMode, Progress, ProgressStage, SyncStatus};
+use crate::keymap::{Action, AnalyzerAction};
 
 /// Render the help bar with context-sensitive key bindings.
 pub fn render_help_bar(f: &mut Frame, area: Rect, app: &App) {
-    let help_text = build_help_text(app);
+    let help_text = build_help_text(app, area.width as usize);
     
     let help = Paragraph::new(Line::from(help_text))
         .style(Style::default().bg(Color::DarkGray).fg(Color::White));
@@ -20,8 +21,29 @@ pub fn render_help_bar(f: &mut Frame, area: Rect, app: &App) {
     f.render_widget(help, area);
 }
 
-/// Build help text based on current app state.
-fn build_help_text(app: &App) -> Vec<Span<'static>> {
+/// Split entries of the given widths into pages of at most `available` columns.
+///
+/// Every page gets at least one entry, so a very narrow bar still pages through all of them.
+fn help_pages(widths: &[usize], available: usize) -> Vec<std::ops::Range<usize>> {
+    let mut pages = Vec::new();
+    let mut start = 0;
+    let mut used = 0;
+    for (i, &width) in widths.iter().enumerate() {
+        if i > start && used + width > available {
+            pages.push(start..i);
+            start = i;
+            used = 0;
+        }
+        used += width;
+    }
+    if start < widths.len() {
+        pages.push(start..widths.len());
+    }
+    pages
+}
+
+/// Build help text based on current app state, fitted to `width` columns.
+fn build_help_text(app: &App, width: usize) -> Vec<Span<'static>> {
     let mut spans = Vec::new();
     
     // Style helpers
@@ -30,13 +52,39 @@ fn build_help_text(app: &App) -> Vec<Span<'static>> {
     let text_style = Style::default().fg(Color::White);
     
     match app.mode {
-        AppMode::ConfirmDelete | AppMode::ConfirmLargeLoad => {
+        AppMode::ConfirmDelete | AppMode::ConfirmLargeLoad | AppMode::ConfirmRetry | AppMode::ConfirmCopyPlan => {
             spans.push(Span::styled("Y", key_style));
             spans.push(Span::styled(":Confirm ", text_style));
             spans.push(Span::styled("│", sep_style));
             spans.push(Span::styled(" N/Esc", key_style));
             spans.push(Span::styled(":Cancel", text_style));
         }
+        AppMode::ConfirmEditorOverwrite => {
+            spans.push(Span::styled("Y", key_style));
+            spans.push(Span::styled(":Overwrite ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" V", key_style));
+            spans.push(Span::styled(":View on disk ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" N/Esc", key_style));
+            spans.push(Span::styled(":Cancel", text_style));
+        }
+        AppMode::ConfirmMerge => {
+            spans.push(Span::styled("M", key_style));
+            spans.push(Span::styled(":Merge ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" K", key_style));
+            spans.push(Span::styled(":Keep existing ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" N", key_style));
+            spans.push(Span::styled(":Newer ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" R", key_style));
+            spans.push(Span::styled(":Replace ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" Esc", key_style));
+            spans.push(Span::styled(":Cancel", text_style));
+        }
         AppMode::EditorSearch => {
              spans.push(Span::styled("Enter", key_style));
              spans.push(Span::styled(":Find ", text_style));
@@ -44,14 +92,36 @@ fn build_help_text(app: &App) -> Vec<Span<'static>> {
              spans.push(Span::styled(" Esc", key_style));
              spans.push(Span::styled(":Cancel", text_style));
         }
+        AppMode::EditorSaveAs | AppMode::ViewSaveAs => {
+            spans.push(Span::styled("Enter", key_style));
+            spans.push(Span::styled(":Download ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" Esc", key_style));
+            spans.push(Span::styled(":Cancel", text_style));
+        }
+        AppMode::EditorGoToLine | AppMode::ViewGoToLine => {
+            spans.push(Span::styled("0-9", key_style));
+            spans.push(Span::styled(":Line ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" Enter", key_style));
+            spans.push(Span::styled(":Go ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" Esc", key_style));
+            spans.push(Span::styled(":Cancel", text_style));
+        }
         AppMode::SelectStorage | AppMode::SelectNamespace | AppMode::SelectPvc | 
-        AppMode::SelectPv | AppMode::SelectCloudProvider | AppMode::ConfigureCloud => {
+        AppMode::SelectPv | AppMode::SelectContext | AppMode::SelectCloudProvider | AppMode::ConfigureCloud => {
             spans.push(Span::styled("↑↓", key_style));
             spans.push(Span::styled(":Navigate ", text_style));
             spans.push(Span::styled("│", sep_style));
             spans.push(Span::styled(" Enter", key_style));
             spans.push(Span::styled(":Select ", text_style));
             spans.push(Span::styled("│", sep_style));
+            if matches!(app.mode, AppMode::SelectCloudProvider) {
+                spans.push(Span::styled(" p", key_style));
+                spans.push(Span::styled(":Profile ", text_style));
+                spans.push(Span::styled("│", sep_style));
+            }
             spans.push(Span::styled(" Esc", key_style));
             spans.push(Span::styled(":Cancel", text_style));
         }
@@ -65,57 +135,75 @@ fn build_help_text(app: &App) -> Vec<Span<'static>> {
             spans.push(Span::styled(" Bksp", key_style));
             spans.push(Span::styled(":Up ", text_style));
             spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" 1-9", key_style));
+            spans.push(Span::styled(":Jump up ", text_style));
+            spans.push(Span::styled("│", sep_style));
             spans.push(Span::styled(" Esc", key_style));
             spans.push(Span::styled(":Exit", text_style));
         }
         AppMode::Normal => {
-            // Function keys sorted
-            spans.push(Span::styled("F3", key_style));
-            spans.push(Span::styled(":View ", text_style));
-            spans.push(Span::styled("│", sep_style));
-
-            spans.push(Span::styled(" F4", key_style));
-            spans.push(Span::styled(":Edit ", text_style));
-            spans.push(Span::styled("│", sep_style));
-
-            spans.push(Span::styled(" F5", key_style));
-            spans.push(Span::styled(":Cp ", text_style));
-            spans.push(Span::styled("│", sep_style));
-            
-            spans.push(Span::styled(" F6", key_style));
-            spans.push(Span::styled(":Mv ", text_style));
-            spans.push(Span::styled("│", sep_style));
-            
-            spans.push(Span::styled(" F7", key_style));
-            spans.push(Span::styled(":Mk ", text_style));
-            spans.push(Span::styled("│", sep_style));
-            
-            spans.push(Span::styled(" F8", key_style));
-            spans.push(Span::styled(":Del ", text_style));
-            spans.push(Span::styled("│", sep_style));
-
-            spans.push(Span::styled(" F9", key_style));
-            spans.push(Span::styled(":Analyz ", text_style));
-            spans.push(Span::styled("│", sep_style));
-            
-            // Storage
-            spans.push(Span::styled(" ^N", key_style));
-            spans.push(Span::styled(":Src ", text_style));
-            spans.push(Span::styled("│", sep_style));
-            
+            // Labels follow the keymap, so rebound keys show up here
+            let keys = &app.keymap;
+            let mut entries = vec![
+                (keys.label(Action::View), ":View "),
+                (keys.label(Action::Edit), ":Edit "),
+                (keys.label(Action::Copy), ":Cp "),
+                (keys.label(Action::Move), ":Mv "),
+                (keys.label(Action::Mkdir), ":Mk "),
+                (keys.label(Action::Delete), ":Del "),
+                (keys.label(Action::DiskAnalyzer), ":Analyz "),
+                (
+                    format!("{}/{}", keys.label(Action::HistoryBack), keys.label(Action::HistoryForward)),
+                    ":Back/Fwd ",
+                ),
+                (
+                    format!(
+                        "{}/{}/{}",
+                        keys.label(Action::ToggleMark),
+                        keys.label(Action::MarkPattern),
+                        keys.label(Action::UnmarkPattern)
+                    ),
+                    ":Mark ",
+                ),
+                (keys.label(Action::SelectStorage), ":Src "),
+                (keys.label(Action::TogglePreview), ":Preview "),
+            ];
             // Sync
             if app.sync_enabled {
-                spans.push(Span::styled(" ^Y", key_style));
-                spans.push(Span::styled(":Sync ", text_style));
-                spans.push(Span::styled("│", sep_style));
+                entries.push((keys.label(Action::SyncNow), ":Sync "));
+            } else {
+                entries.push((keys.label(Action::SyncToggle), ":Sync "));
+            }
+
+            // Quit stays on every page; if the rest doesn't fit, it is paged with a "more" hint
+            let quit = format!(" {}:Quit", keys.label(Action::Quit));
+            let widths: Vec<usize> = entries.iter()
+                .map(|(key, text)| Span::raw(format!(" {}{}│", key, text)).width())
+                .collect();
+            let all_width: usize = widths.iter().sum::<usize>() + Span::raw(quit.as_str()).width();
+            let available = if all_width <= width {
+                width
             } else {
-                spans.push(Span::styled(" ^S", key_style));
-                spans.push(Span::styled(":Sync ", text_style));
+                let more_width = Span::raw(format!(" {}:More {}/{} │", keys.label(Action::HelpPage), entries.len(), entries.len())).width();
+                width.saturating_sub(Span::raw(quit.as_str()).width() + more_width)
+            };
+            let pages = help_pages(&widths, available);
+            let page = app.help_page % pages.len();
+
+            for (i, (key, text)) in entries[pages[page].clone()].iter().enumerate() {
+                let key = if i == 0 { key.clone() } else { format!(" {}", key) };
+                spans.push(Span::styled(key, key_style));
+                spans.push(Span::styled(*text, text_style));
                 spans.push(Span::styled("│", sep_style));
             }
-            
+            if pages.len() > 1 {
+                spans.push(Span::styled(format!(" {}", keys.label(Action::HelpPage)), key_style));
+                spans.push(Span::styled(format!(":More {}/{} ", page + 1, pages.len()), text_style));
+                spans.push(Span::styled("│", sep_style));
+            }
+
             // Quit
-            spans.push(Span::styled(" q", key_style));
+            spans.push(Span::styled(format!(" {}", keys.label(Action::Quit)), key_style));
             spans.push(Span::styled(":Quit", text_style));
         }
         AppMode::Rename => {
@@ -128,6 +216,16 @@ fn build_help_text(app: &App) -> Vec<Span<'static>> {
             spans.push(Span::styled(" Esc", key_style));
             spans.push(Span::styled(":Cancel", text_style));
         }
+        AppMode::CreateFile => {
+            spans.push(Span::styled("Type", key_style));
+            spans.push(Span::styled(":File name ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" Enter", key_style));
+            spans.push(Span::styled(":Create ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" Esc", key_style));
+            spans.push(Span::styled(":Cancel", text_style));
+        }
         AppMode::ViewFile => {
             spans.push(Span::styled("j/k", key_style));
             spans.push(Span::styled(":Scroll ", text_style));
@@ -138,6 +236,48 @@ fn build_help_text(app: &App) -> Vec<Span<'static>> {
             spans.push(Span::styled(" Home/End", key_style));
             spans.push(Span::styled(":Top/Bot ", text_style));
             spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" g", key_style));
+            spans.push(Span::styled(":Line ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" w", key_style));
+            spans.push(Span::styled(if app.view_wrap { ":NoWrap " } else { ":Wrap " }, text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" x", key_style));
+            spans.push(Span::styled(if app.view_hex { ":Text " } else { ":Hex " }, text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" v", key_style));
+            spans.push(Span::styled(if app.view_selection.is_some() { ":Unselect " } else { ":Select " }, text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" y/Y", key_style));
+            spans.push(Span::styled(":Copy line/page ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" s", key_style));
+            spans.push(Span::styled(":Save local ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            if !app.view_wrap {
+                spans.push(Span::styled(" ←/→", key_style));
+                spans.push(Span::styled(":HScroll ", text_style));
+                spans.push(Span::styled("│", sep_style));
+            }
+            spans.push(Span::styled(" q/Esc", key_style));
+            spans.push(Span::styled(":Close", text_style));
+        }
+        AppMode::EditFile if app.editor.readonly && !app.recent_viewing => {
+            spans.push(Span::styled("s", key_style));
+            spans.push(Span::styled(":Save local ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" ^W", key_style));
+            spans.push(Span::styled(":Search ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" g", key_style));
+            spans.push(Span::styled(":Line ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" w", key_style));
+            spans.push(Span::styled(if app.view_wrap { ":NoWrap " } else { ":Wrap " }, text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" x", key_style));
+            spans.push(Span::styled(":Hex ", text_style));
+            spans.push(Span::styled("│", sep_style));
             spans.push(Span::styled(" q/Esc", key_style));
             spans.push(Span::styled(":Close", text_style));
         }
@@ -151,6 +291,12 @@ fn build_help_text(app: &App) -> Vec<Span<'static>> {
             spans.push(Span::styled(" ^U", key_style));
             spans.push(Span::styled(":Uncut ", text_style));
             spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" ^G", key_style));
+            spans.push(Span::styled(":Line ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" ^N", key_style));
+            spans.push(Span::styled(":Numbers ", text_style));
+            spans.push(Span::styled("│", sep_style));
             spans.push(Span::styled(" ^X", key_style));
             spans.push(Span::styled(":Exit", text_style));
         }
@@ -164,6 +310,72 @@ fn build_help_text(app: &App) -> Vec<Span<'static>> {
             spans.push(Span::styled(" Esc", key_style));
             spans.push(Span::styled(":Cancel", text_style));
         }
+        AppMode::DiffView => {
+            spans.push(Span::styled("j/k", key_style));
+            spans.push(Span::styled(":Scroll ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" PgUp/PgDn", key_style));
+            spans.push(Span::styled(":Page ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" n", key_style));
+            spans.push(Span::styled(":Next change ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" Esc", key_style));
+            spans.push(Span::styled(":Close", text_style));
+        }
+        AppMode::RecentFiles => {
+            spans.push(Span::styled("↑↓", key_style));
+            spans.push(Span::styled(":Navigate ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" Enter", key_style));
+            spans.push(Span::styled(":View ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" s", key_style));
+            spans.push(Span::styled(":Sort ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" r", key_style));
+            spans.push(Span::styled(":Rescan ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" Esc", key_style));
+            spans.push(Span::styled(":Exit", text_style));
+        }
+        AppMode::VerifyResults => {
+            spans.push(Span::styled("↑↓", key_style));
+            spans.push(Span::styled(":Navigate ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" Enter", key_style));
+            spans.push(Span::styled(":Go to file ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" r", key_style));
+            spans.push(Span::styled(":Verify again ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" Esc", key_style));
+            spans.push(Span::styled(":Exit", text_style));
+        }
+        AppMode::MarkPattern | AppMode::UnmarkPattern => {
+            spans.push(Span::styled("Type", key_style));
+            spans.push(Span::styled(":Glob (*, ?, [abc]) ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" Enter", key_style));
+            spans.push(Span::styled(":Apply ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" Esc", key_style));
+            spans.push(Span::styled(":Cancel", text_style));
+        }
+        AppMode::HashCheckInput => {
+            spans.push(Span::styled("Type/paste", key_style));
+            spans.push(Span::styled(":Expected hash ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" Enter", key_style));
+            spans.push(Span::styled(":Check ", text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" Esc", key_style));
+            spans.push(Span::styled(":Cancel", text_style));
+        }
+        AppMode::HashCheckResult | AppMode::ObjectDetails => {
+            spans.push(Span::styled("Any key", key_style));
+            spans.push(Span::styled(":Close", text_style));
+        }
         AppMode::HashMenu => {
             spans.push(Span::styled("↑↓", key_style));
             spans.push(Span::styled(":Navigate ", text_style));
@@ -171,6 +383,12 @@ fn build_help_text(app: &App) -> Vec<Span<'static>> {
             spans.push(Span::styled(" Enter", key_style));
             spans.push(Span::styled(":Select ", text_style));
             spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" f", key_style));
+            spans.push(Span::styled(if app.hash_fast_mode { ":Fast mode off " } else { ":Fast mode on " }, text_style));
+            spans.push(Span::styled("│", sep_style));
+            spans.push(Span::styled(" p", key_style));
+            spans.push(Span::styled(if app.hash_parallel { ":Parallel off " } else { ":Parallel on " }, text_style));
+            spans.push(Span::styled("│", sep_style));
             spans.push(Span::styled(" Esc", key_style));
             spans.push(Span::styled(":Cancel", text_style));
         }
@@ -180,7 +398,7 @@ fn build_help_text(app: &App) -> Vec<Span<'static>> {
 }
 
 /// Render file editor.
-pub fn render_file_editor(f: &mut Frame, editor: &mut crate::app::TextEditor, area: Rect) {
+pub fn render_file_editor(f: &mut Frame, editor: &mut crate::app::TextEditor, wrap: bool, line_numbers: bool, area: Rect) {
     // Clear the entire area first
     f.render_widget(Clear, area);
     
@@ -226,6 +444,52 @@ pub fn render_file_editor(f: &mut Frame, editor: &mut crate::app::TextEditor, ar
     let start_line = editor.scroll_offset;
     let end_line = (start_line + visible_height).min(editor.content.len());
 
+    // Line-number gutter sized for the last line, plus one space
+    let gutter_width = if line_numbers {
+        (editor.content.len().max(1).ilog10() as usize + 1).max(3) + 1
+    } else {
+        0
+    };
+    let with_gutter = |mut line: Line<'static>, line_idx: usize| {
+        if line_numbers {
+            line.spans.insert(0, Span::styled(
+                format!("{:>width$} ", line_idx + 1, width = gutter_width - 1),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        line
+    };
+
+    // Readonly view with wrapping: one paragraph, no cursor
+    if wrap && editor.readonly {
+        let lines: Vec<Line> = editor.content[start_line..end_line]
+            .iter()
+            .enumerate()
+            .map(|(i, line)| with_gutter(crate::ui::syntax::highlight_line(&line.replace('\t', "    "), extension), start_line + i))
+            .collect();
+        f.render_widget(
+            Paragraph::new(lines)
+                .style(Style::default().bg(bg_color))
+                .wrap(Wrap { trim: false }),
+            inner_area,
+        );
+        return;
+    }
+
+    // Keep the cursor column in view (tabs count as 4 columns)
+    let visual_col = editor.content.get(editor.cursor_row)
+        .map(|line| {
+            let before: String = line.chars().take(editor.cursor_col).collect();
+            before.replace('\t', "    ").chars().count()
+        })
+        .unwrap_or(0);
+    let width = (inner_area.width as usize).saturating_sub(gutter_width).max(1);
+    if visual_col < editor.col_offset {
+        editor.col_offset = visual_col;
+    } else if visual_col >= editor.col_offset + width {
+        editor.col_offset = visual_col + 1 - width;
+    }
+
     // Render each line individually to its own row
     for (i, line_idx) in (start_line..end_line).enumerate() {
         if i >= visible_height {
@@ -237,6 +501,7 @@ pub fn render_file_editor(f: &mut Frame, editor: &mut crate::app::TextEditor, ar
         let clean_content = line_content.replace('\t', "    ");
         
         let highlighted = crate::ui::syntax::highlight_line(&clean_content, extension);
+        let highlighted = with_gutter(skip_columns(highlighted, editor.col_offset), line_idx);
         
         let line_area = Rect::new(
             inner_area.x,
@@ -256,13 +521,8 @@ pub fn render_file_editor(f: &mut Frame, editor: &mut crate::app::TextEditor, ar
     // Set cursor
     let cursor_y = editor.cursor_row as i32 - editor.scroll_offset as i32;
     if cursor_y >= 0 && cursor_y < inner_area.height as i32 {
-        // Also account for tab expansion in cursor position
-        let current_line = &editor.content[editor.cursor_row];
-        let chars_before_cursor: String = current_line.chars().take(editor.cursor_col).collect();
-        let visual_col = chars_before_cursor.replace('\t', "    ").len();
-        
         f.set_cursor_position(
-            (inner_area.x + visual_col as u16,
+            (inner_area.x + (gutter_width + visual_col - editor.col_offset) as u16,
             inner_area.y + cursor_y as u16)
         );
     }
@@ -274,29 +534,88 @@ pub fn render_file_editor(f: &mut Frame, editor: &mut crate::app::TextEditor, ar
 
 /// Render the status bar with message and sync status.
 pub fn render_status_bar(f: &mut Frame, area: Rect, app: &App) {
+    let direction = app.sync_direction.label();
     let sync_indicator = match &app.sync_status {
         SyncStatus::Disabled => String::new(),
-        SyncStatus::Idle => " │ 🔄 Sync: Idle".to_string(),
-        SyncStatus::Scanning => " │ 🔄 Scanning...".to_string(),
+        SyncStatus::Idle => format!(" │ 🔄 Sync {}: Idle", direction),
+        SyncStatus::Scanning => format!(" │ 🔄 {} Scanning...", direction),
         SyncStatus::Syncing { current_file, progress } => {
-            format!(" │ 🔄 {:.0}% {}", progress * 100.0, truncate_path(current_file, 20))
+            format!(" │ 🔄 {} {:.0}% {}", direction, progress * 100.0, truncate_path(current_file, 20))
         }
         SyncStatus::Complete { files_synced } => format!(" │ ✅ Synced {} files", files_synced),
         SyncStatus::Error { message } => format!(" │ ❌ {}", truncate_path(message, 30)),
     };
     
+    let transfer_indicator = match app.transfer_totals.indicator() {
+        text if text.is_empty() => text,
+        text => format!(" │ {}", text),
+    };
+
+    let k8s_indicator = match app.kube_context() {
+        Some(context) => format!(" │ ☸ {}", truncate_path(context, 30)),
+        None => String::new(),
+    };
+    
     // Use get_status_message which includes full filename for truncated entries
     let status_msg = app.get_status_message();
-    let text = format!("{}{}", status_msg, sync_indicator);
+    let text = format!("{}{}{}{}", status_msg, sync_indicator, transfer_indicator, k8s_indicator);
     
-    let status = Paragraph::new(text)
+    // Stays visible for as long as deletes skip the confirmation
+    let mut spans = Vec::new();
+    if app.auto_confirm_delete {
+        spans.push(Span::styled(
+            " ⚠ NO DELETE CONFIRM ",
+            Style::default().bg(Color::Red).fg(Color::White).add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::raw(" "));
+    }
+    if app.dry_run {
+        spans.push(Span::styled(
+            " DRY RUN: NOTHING IS DELETED ",
+            Style::default().bg(Color::Yellow).fg(Color::Black).add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::raw(" "));
+    }
+    spans.push(Span::raw(text));
+    
+    let status = Paragraph::new(Line::from(spans))
         .style(Style::default().bg(Color::Blue).fg(Color::White));
     
     f.render_widget(status, area);
 }
 
+/// Rows the dry-run log panel takes under the panes (including its border)
+pub const DRY_RUN_LOG_HEIGHT: u16 = 8;
+
+/// Render the log of what deletes and cleans would have removed, newest at the bottom.
+pub fn render_dry_run_log(f: &mut Frame, log: &crate::app::DryRunLog, area: Rect) {
+    let visible = area.height.saturating_sub(2) as usize;
+    let lines: Vec<Line> = log
+        .lines()
+        .skip(log.len().saturating_sub(visible))
+        .map(|line| Line::from(line.to_string()))
+        .collect();
+    let title = if log.is_empty() {
+        " Dry run log: deletes are only listed here ".to_string()
+    } else {
+        format!(" Dry run log ({} entries): nothing was deleted ", log.len())
+    };
+
+    let panel = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow))
+            .title(title),
+    );
+    f.render_widget(panel, area);
+}
+
 /// Render progress bar for file operations.
 pub fn render_progress_bar(f: &mut Frame, area: Rect, progress: &Progress) {
+    let batch = match progress.batch {
+        Some((position, total)) => format!("[{}/{}] ", position, total),
+        None => String::new(),
+    };
     let label = match progress.stage {
         ProgressStage::Counting => format!("Scanning: {}", progress.current_file),
         ProgressStage::Archiving => {
@@ -320,8 +639,21 @@ pub fn render_progress_bar(f: &mut Frame, area: Rect, progress: &Progress) {
             }
         }
         ProgressStage::Extracting => format!("Extracting: {}", progress.current_file),
+        ProgressStage::Saving => format!(
+            "Saving: {} ({} / {})",
+            truncate_path(&progress.current_file, 30),
+            humansize::format_size(progress.current, humansize::BINARY),
+            humansize::format_size(progress.total, humansize::BINARY)
+        ),
+        ProgressStage::Deleting => format!(
+            "Deleting: {} ({}/{} objects)",
+            truncate_path(&progress.current_file, 30),
+            progress.current,
+            progress.total
+        ),
         ProgressStage::Complete => "Complete!".to_string(),
     };
+    let label = format!("{}{}", batch, label);
     
     let ratio = if progress.total > 0 {
         (progress.current as f64 / progress.total as f64).min(1.0)
@@ -371,11 +703,104 @@ pub fn render_popup(f: &mut Frame, title: &str, lines: Vec<Line>, style: Style)
     f.render_widget(popup, popup_area);
 }
 
-/// Render delete confirmation popup.
-pub fn render_delete_confirm(f: &mut Frame, target: &crate::app::DeleteTarget) {
-    let type_str = if target.is_dir { "directory" } else { "file" };
+/// Ask how copies onto existing directories should merge.
+pub fn render_merge_popup(f: &mut Frame, app: &App) {
+    /// Conflicting directories listed by name before summarizing the rest
+    const MAX_LISTED: usize = 5;
+
+    let conflicts: Vec<&crate::app::QueuedOperation> =
+        app.merge_ops.iter().filter(|op| app.merge_conflict(op)).collect();
+    let key_style = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "Already in the destination:",
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        )),
+    ];
+    for op in conflicts.iter().take(MAX_LISTED) {
+        lines.push(Line::from(Span::styled(format!("📁 {}", op.name), Style::default().fg(Color::Yellow))));
+    }
+    if conflicts.len() > MAX_LISTED {
+        lines.push(Line::from(Span::styled(
+            format!("...and {} more", conflicts.len() - MAX_LISTED),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    lines.push(Line::from(""));
+    for (key, label) in [
+        ("M/Enter", "Merge, overwrite files that exist in both"),
+        ("K", "Merge, keep existing files"),
+        ("N", "Merge, overwrite only older files"),
+        ("R", "Replace: delete the target directory first"),
+        ("Esc", "Cancel"),
+    ] {
+        lines.push(Line::from(vec![Span::styled(format!("{:>8} ", key), key_style), Span::raw(label)]));
+    }
+
+    render_popup(f, " Merge Directories ", lines, Style::default().fg(Color::Yellow));
+}
+
+/// Render the prompt for an editor save onto a file that changed on disk since it was opened
+pub fn render_editor_overwrite_popup(f: &mut Frame, app: &App) {
+    let key_style = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    let now = chrono::Utc::now();
+    let describe = |version: Option<crate::app::FileVersion>| match version {
+        Some(v) => format!(
+            "{}, modified {}",
+            humansize::format_size(v.size, humansize::BINARY),
+            crate::fs::format_timestamp(v.modified, app.time_format, now)
+        ),
+        None => "unknown".to_string(),
+    };
+
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("'{}' changed on disk since it was opened", app.editor.filename),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![Span::styled("  Opened: ", key_style), Span::raw(describe(app.editor.on_disk))]),
+        Line::from(vec![Span::styled("     Now: ", key_style), Span::raw(describe(app.editor_disk_version))]),
+        Line::from(""),
+    ];
+    for (key, label) in [
+        ("Y", "Overwrite it with your version"),
+        ("V", "View the version on disk"),
+        ("N/Esc", "Keep editing without saving"),
+    ] {
+        lines.push(Line::from(vec![Span::styled(format!("{:>6} ", key), key_style), Span::raw(label)]));
+    }
+
+    render_popup(f, " ⚠ File Changed on Disk ", lines, Style::default().fg(Color::Red));
+}
+
+/// Render delete confirmation popup for one or more (marked) entries.
+///
+/// When directories are involved, `impact` is their recursive file count and size once the
+/// background count has finished (or the error that stopped it).
+pub fn render_delete_confirm(
+    f: &mut Frame,
+    targets: &[crate::app::DeleteTarget],
+    impact: Option<&Result<crate::app::DeleteImpact, String>>,
+) {
+    /// Marked entries listed by name before summarizing the rest
+    const MAX_LISTED: usize = 5;
+
+    let Some(first) = targets.first() else {
+        return;
+    };
+    let type_str = if targets.len() > 1 {
+        format!("{} entries", targets.len())
+    } else if first.is_dir {
+        "directory".to_string()
+    } else {
+        "file".to_string()
+    };
     let location = if matches!(
-        target.backend.backend_type(),
+        first.backend.backend_type(),
         crate::fs::BackendType::Local
     ) {
         "LOCAL"
@@ -383,7 +808,7 @@ pub fn render_delete_confirm(f: &mut Frame, target: &crate::app::DeleteTarget) {
         "REMOTE"
     };
     
-    let lines = vec![
+    let mut lines = vec![
         Line::from(""),
         Line::from(vec![
             Span::styled("Delete ", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
@@ -393,12 +818,33 @@ pub fn render_delete_confirm(f: &mut Frame, target: &crate::app::DeleteTarget) {
             Span::raw("):"),
         ]),
         Line::from(""),
-        Line::from(vec![
+    ];
+    for target in targets.iter().take(MAX_LISTED) {
+        lines.push(Line::from(vec![
             Span::styled(
                 target.display_path.clone(),
                 Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
             ),
-        ]),
+        ]));
+    }
+    if targets.len() > MAX_LISTED {
+        lines.push(Line::from(Span::styled(
+            format!("...and {} more", targets.len() - MAX_LISTED),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    if targets.iter().any(|target| target.is_dir) {
+        let contents = match impact {
+            None => Span::styled("Counting files...", Style::default().fg(Color::DarkGray)),
+            Some(Ok(impact)) => Span::styled(
+                impact.summary(),
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            ),
+            Some(Err(e)) => Span::styled(format!("Size unknown: {}", e), Style::default().fg(Color::DarkGray)),
+        };
+        lines.extend([Line::from(""), Line::from(vec![Span::raw("Removes "), contents])]);
+    }
+    lines.extend([
         Line::from(""),
         Line::from(vec![
             Span::styled("[Y]", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
@@ -406,11 +852,86 @@ pub fn render_delete_confirm(f: &mut Frame, target: &crate::app::DeleteTarget) {
             Span::styled("[N/Esc]", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
             Span::raw(" No"),
         ]),
-    ];
+    ]);
     
     render_popup(f, " ⚠ Confirm Delete ", lines, Style::default().fg(Color::Red));
 }
 
+/// Render the files a copy would transfer, their total size and the destination.
+pub fn render_copy_plan_popup(f: &mut Frame, plan: &crate::fs::copy::CopyPlan, ops: &[crate::app::QueuedOperation]) {
+    /// Files listed by path before summarizing the rest
+    const MAX_LISTED: usize = 10;
+
+    let destination = ops
+        .first()
+        .map(|op| op.dest_storage.display_path(op.dest_path.rsplit_once('/').map_or("", |(parent, _)| parent)))
+        .unwrap_or_default();
+
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Copy ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(plan.summary()),
+        ]),
+        Line::from(vec![Span::raw("to "), Span::styled(destination, Style::default().fg(Color::Yellow))]),
+        Line::from(""),
+    ];
+    for file in plan.files.iter().take(MAX_LISTED) {
+        lines.push(Line::from(vec![
+            Span::raw(format!("{:>10}  ", crate::fs::types::format_bytes(file.size))),
+            Span::styled(file.path.clone(), Style::default().fg(Color::Cyan)),
+        ]));
+    }
+    if plan.files.len() > MAX_LISTED {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "+{} more ({})",
+                plan.files.len() - MAX_LISTED,
+                crate::fs::types::format_bytes(plan.files[MAX_LISTED..].iter().map(|file| file.size).sum())
+            ),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    lines.extend([
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("[Y/Enter]", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::raw(" Copy  "),
+            Span::styled("[N/Esc]", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            Span::raw(" Cancel"),
+        ]),
+    ]);
+
+    render_popup(f, " Copy Plan ", lines, Style::default().fg(Color::Cyan));
+}
+
+/// Render quit confirmation popup listing the work that would be lost.
+pub fn render_quit_confirm(f: &mut Frame, pending: &[&str]) {
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "Quit and abandon pending work?",
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    for item in pending {
+        lines.push(Line::from(vec![
+            Span::raw("  • "),
+            Span::styled(item.to_string(), Style::default().fg(Color::Cyan)),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("[Y]", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+        Span::raw(" Quit  "),
+        Span::styled("[Any other key]", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+        Span::raw(" Stay"),
+    ]));
+
+    render_popup(f, " ⚠ Confirm Quit ", lines, Style::default().fg(Color::Yellow));
+}
+
 /// Truncate a path for display.
 fn truncate_path(path: &str, max_len: usize) -> String {
     if path.len() <= max_len {
@@ -466,8 +987,41 @@ pub fn render_rename_popup(f: &mut Frame, text_input: &crate::app::TextInput) {
     f.render_widget(popup, popup_area);
 }
 
-/// Render file viewer overlay.
-pub fn render_file_viewer(f: &mut Frame, content: &[String], scroll: usize, filename: &str, area: Rect) {
+/// Drop the first `skip` characters of a line, preserving span styles (horizontal scroll).
+fn skip_columns(line: Line<'static>, skip: usize) -> Line<'static> {
+    if skip == 0 {
+        return line;
+    }
+
+    let mut remaining = skip;
+    let spans: Vec<Span<'static>> = line.spans
+        .into_iter()
+        .filter_map(|span| {
+            if remaining == 0 {
+                return Some(span);
+            }
+            let len = span.content.chars().count();
+            if len <= remaining {
+                remaining -= len;
+                return None;
+            }
+            let text: String = span.content.chars().skip(remaining).collect();
+            remaining = 0;
+            Some(Span::styled(text, span.style))
+        })
+        .collect();
+    Line::from(spans)
+}
+
+/// Render file viewer overlay, recording the rows and columns it shows for page copies.
+pub fn render_file_viewer(f: &mut Frame, app: &mut App, area: Rect) {
+    let content = &app.view_content;
+    let scroll = app.view_scroll;
+    let filename = app.view_file_path.as_str();
+    let wrap = app.view_wrap;
+    let hscroll = app.view_hscroll;
+    let line_base = app.view_line_base;
+
     // Use most of the screen (passed area)
     let margin = 2;
     let popup_area = Rect::new(
@@ -481,6 +1035,9 @@ pub fn render_file_viewer(f: &mut Frame, content: &[String], scroll: usize, file
     
     // Get visible lines
     let visible_height = popup_area.height.saturating_sub(2) as usize;
+    app.view_visible_height = visible_height;
+    app.view_text_width = popup_area.width.saturating_sub(2) as usize;
+    let selection = app.view_selection;
     // Get file extension
     let extension = std::path::Path::new(filename)
         .extension()
@@ -493,7 +1050,20 @@ pub fn render_file_viewer(f: &mut Frame, content: &[String], scroll: usize, file
         .take(visible_height)
         .enumerate()
         .map(|(i, line)| {
-            let line_num = scroll + i + 1;
+            let selected = selection.is_some_and(|s| s.contains(scroll + i));
+            let selected_style = Style::default().bg(Color::Blue);
+            if app.view_hex {
+                // Rows carry their own offset column instead of a line number
+                let (offset, rest) = line.split_at(line.len().min(8));
+                let spans = vec![
+                    Span::styled(offset.to_string(), Style::default().fg(Color::DarkGray)),
+                    Span::raw(rest.to_string()),
+                ];
+                let spans = if wrap { Line::from(spans) } else { skip_columns(Line::from(spans), hscroll) };
+                return if selected { spans.style(selected_style) } else { spans };
+            }
+
+            let line_num = line_base.unwrap_or(0) + scroll + i + 1;
             let mut spans = vec![
                 Span::styled(
                     format!("{:4} ", line_num),
@@ -503,27 +1073,91 @@ pub fn render_file_viewer(f: &mut Frame, content: &[String], scroll: usize, file
             
             // Highlight the code content
             let highlighted = crate::ui::syntax::highlight_line(line, extension);
+            let highlighted = if wrap { highlighted } else { skip_columns(highlighted, hscroll) };
             spans.extend(highlighted.spans);
             
-            Line::from(spans)
+            if selected {
+                Line::from(spans).style(selected_style)
+            } else {
+                Line::from(spans)
+            }
         })
         .collect();
     
-    let title = format!(" {} (line {}/{}) ", filename, scroll + 1, content.len());
+    // Position within the file when known, otherwise within the loaded chunk
+    let position = match line_base {
+        _ if app.view_hex => format!(
+            "hex, offset 0x{:08x}/0x{:08x}",
+            app.view_file_offset + (scroll * crate::ui::hexdump::HEX_ROW_BYTES) as u64,
+            app.view_file_size
+        ),
+        Some(base) => format!("line {}", base + scroll + 1),
+        None => format!("line {}/{}", scroll + 1, content.len()),
+    };
+    let title = if wrap {
+        format!(" {} ({}) ", filename, position)
+    } else {
+        format!(" {} ({}, col {}, nowrap) ", filename, position, hscroll + 1)
+    };
     
-    let popup = Paragraph::new(visible_lines)
+    let mut popup = Paragraph::new(visible_lines)
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(Color::Green))
                 .title(title)
                 .title_style(Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
-        )
-        .wrap(Wrap { trim: false });
+        );
+    if wrap {
+        popup = popup.wrap(Wrap { trim: false });
+    }
     
     f.render_widget(popup, popup_area);
 }
 
+/// Render the quick-look pane: the start of the selected file, highlighted or as hex rows.
+pub fn render_preview(f: &mut Frame, app: &App, area: Rect) {
+    let preview = &app.preview;
+    let name = preview.path.as_deref().and_then(|p| p.rsplit('/').next()).unwrap_or_default();
+    let extension = std::path::Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("txt");
+    let note_style = Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC);
+
+    let lines: Vec<Line> = if let Some(ref note) = preview.note {
+        vec![Line::from(Span::styled(note.clone(), note_style))]
+    } else if preview.rx.is_some() {
+        vec![Line::from(Span::styled("Loading...", note_style))]
+    } else {
+        preview
+            .lines
+            .iter()
+            .take(area.height.saturating_sub(2) as usize)
+            .map(|line| {
+                if preview.hex {
+                    let (offset, rest) = line.split_at(line.len().min(8));
+                    Line::from(vec![
+                        Span::styled(offset.to_string(), Style::default().fg(Color::DarkGray)),
+                        Span::raw(rest.to_string()),
+                    ])
+                } else {
+                    crate::ui::syntax::highlight_line(line, extension)
+                }
+            })
+            .collect()
+    };
+
+    let title = if name.is_empty() { " Preview ".to_string() } else { format!(" Preview: {} ", name) };
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Gray))
+            .title(title),
+    );
+    f.render_widget(paragraph, area);
+}
+
 /// Render search input popup.
 pub fn render_search_popup(f: &mut Frame, text_input: &crate::app::TextInput, title: &str) {
     let area = f.area();
@@ -576,7 +1210,11 @@ pub fn render_confirm_large_load_popup(f: &mut Frame, app: &crate::app::App) {
     let area = f.area();
     
     // Check if View or Edit
-    let action_str = if matches!(app.pending_large_action, Some(crate::app::LargeFileAction::Edit)) { "Edit" } else { "View" };
+    let action_str = match app.pending_large_action {
+        Some(crate::app::LargeFileAction::Edit) => "Edit",
+        Some(crate::app::LargeFileAction::Diff) => "Diff",
+        _ => "View",
+    };
     let size_mb = app.view_file_size / 1024 / 1024;
     
     let blocks = vec![
@@ -639,21 +1277,43 @@ pub fn render_disk_analyzer(f: &mut Frame, app: &App, area: Rect) {
         ])
         .split(area);
 
-    // Header
-    let path_str = app.cleaner_path.to_string_lossy();
+    // Header: breadcrumb with numbered ancestors (1-9 jump there), then totals
+    let key_style = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    let mut header_spans = vec![Span::raw(" ")];
+    for (i, ancestor) in app.cleaner_path_stack.iter().enumerate() {
+        if i < 9 {
+            header_spans.push(Span::styled(format!("{}:", i + 1), key_style));
+        }
+        // The scan root in full, then one name per level
+        let label = if i == 0 {
+            ancestor.to_string_lossy().to_string()
+        } else {
+            ancestor.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+        };
+        header_spans.push(Span::raw(label));
+        header_spans.push(Span::styled(" › ", Style::default().fg(Color::DarkGray)));
+    }
+    let current = if app.cleaner_path_stack.is_empty() {
+        app.cleaner_path.to_string_lossy().to_string()
+    } else {
+        app.cleaner_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+    };
+    header_spans.push(Span::styled(current, Style::default().add_modifier(Modifier::BOLD)));
     let total_size = humansize::format_size(app.cleaner_total_size, humansize::BINARY);
     let sort_str = match app.cleaner_sort_mode {
         crate::app::CleanerSortMode::Size => "size",
         crate::app::CleanerSortMode::Name => "name",
     };
 
-    let header = Paragraph::new(format!(
-        " {} │ Total: {} │ Sort: {} │ {} items",
-        path_str,
+    header_spans.push(Span::raw(format!(
+        " │ Total: {} ({}) │ Sort: {} │ {} items",
         total_size,
+        app.cleaner_size_metric.label(),
         sort_str,
         app.cleaner_entries.len()
-    ))
+    )));
+
+    let header = Paragraph::new(Line::from(header_spans))
     .block(Block::default().borders(Borders::ALL).title(" Disk Analyzer "));
 
     f.render_widget(header, chunks[0]);
@@ -665,8 +1325,12 @@ pub fn render_disk_analyzer(f: &mut Frame, app: &App, area: Rect) {
         let bytes = progress.get_bytes();
         let size_str = humansize::format_size(bytes, humansize::BINARY);
         
+        // Remote listings can take a while per directory, so say what is happening
+        let verb = if app.cleaner_backend.is_some() { "Listing" } else { "Scanning" };
         let text = format!(
-            "\n\n  Scanning {}...\n\n  📁 {} folders\n  📄 {} files\n  💾 {}\n\n  Press 'q' to cancel",
+            "\n\n  {} {} {}...\n\n  📁 {} folders\n  📄 {} files\n  💾 {}\n\n  Press 'q' to cancel",
+            crate::ui::pane::spinner_frame(),
+            verb,
             app.cleaner_path.display(),
             dirs,
             files,
@@ -693,25 +1357,53 @@ pub fn render_disk_analyzer(f: &mut Frame, app: &App, area: Rect) {
         let dirs = stats.directories();
         let bytes = stats.bytes();
         let size_str = humansize::format_size(bytes, humansize::BINARY);
-        
+        let total = app.cleaner_clean_total;
+
+        let body = ratatui::layout::Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .constraints([ratatui::layout::Constraint::Min(0), ratatui::layout::Constraint::Length(3)])
+            .split(chunks[1]);
+
+        // A dry run counts the same matches without deleting them
+        let (verb, done, title) = if app.cleaner_dry_run_rx.is_some() {
+            ("Dry run of", "would be deleted", " Dry run... ")
+        } else {
+            ("Cleaning", "deleted", " Cleaning... ")
+        };
         let text = format!(
-            "\n\n  Cleaning {}...\n\n  🗑️ {} folders deleted\n  📄 {} files deleted\n  💾 {} freed",
+            "\n\n  {} {} {}...\n\n  🗑️ {} folders {}\n  📄 {} files {}\n  💾 {} freed",
+            crate::ui::pane::spinner_frame(),
+            verb,
             app.cleaner_path.display(),
             dirs,
+            done,
             files,
+            done,
             size_str
         );
         
         let block = Block::default()
             .borders(Borders::ALL)
-            .title(" Cleaning... (Async) ");
+            .title(title);
         let paragraph = Paragraph::new(text)
             .block(block)
             .alignment(ratatui::layout::Alignment::Center);
-        f.render_widget(paragraph, chunks[1]);
+        f.render_widget(paragraph, body[0]);
+
+        let ratio = if total > 0 {
+            (bytes as f64 / total as f64).min(1.0)
+        } else {
+            0.0
+        };
+        let gauge = Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title(" Freed "))
+            .gauge_style(Style::default().fg(Color::Cyan).bg(Color::Black))
+            .ratio(ratio)
+            .label(format!("{} / {}", size_str, humansize::format_size(total, humansize::BINARY)));
+        f.render_widget(gauge, body[1]);
         
         // Render simple footer
-        let footer = Paragraph::new("Cleaning in progress...").block(Block::default().borders(Borders::ALL));
+        let footer = Paragraph::new("Cleaning in progress... Press Esc to cancel").block(Block::default().borders(Borders::ALL));
         f.render_widget(footer, chunks[2]);
         return;
     }
@@ -722,7 +1414,7 @@ pub fn render_disk_analyzer(f: &mut Frame, app: &App, area: Rect) {
         .iter()
         .enumerate()
         .map(|(i, entry)| {
-            let size_str = humansize::format_size(entry.size, humansize::BINARY);
+            let size_str = humansize::format_size(entry.size_of(app.cleaner_size_metric), humansize::BINARY);
             let prefix = if entry.is_dir { "▸ " } else { "  " };
             let temp_marker = if entry.is_temp { " [TEMP]" } else { "" };
 
@@ -755,27 +1447,48 @@ pub fn render_disk_analyzer(f: &mut Frame, app: &App, area: Rect) {
     f.render_stateful_widget(list, chunks[1], &mut state);
 
     // Footer
+    let dry_run = if app.dry_run { " [dry run: only logged]" } else { "" };
     let text = if app.cleaner_confirm_clean {
         format!(
-            " Clean all temp files in '{}'? (y/n)",
+            " Clean all temp files in '{}'? (y/n){}",
             app.cleaner_path.file_name()
                 .map(|n| n.to_string_lossy().to_string())
-                .unwrap_or_else(|| app.cleaner_path.to_string_lossy().to_string())
+                .unwrap_or_else(|| app.cleaner_path.to_string_lossy().to_string()),
+            dry_run
         )
     } else if app.cleaner_confirm_delete {
         if let Some(entry) = app.cleaner_entries.get(app.cleaner_selected) {
             format!(
-                " Delete '{}'? (y/n) - {} will be freed",
+                " Delete '{}'? (y/n) - {} will be freed{}",
                 entry.name,
-                humansize::format_size(entry.size, humansize::BINARY)
+                humansize::format_size(entry.size_of(app.cleaner_size_metric), humansize::BINARY),
+                dry_run
             )
         } else {
-            " Delete? (y/n)".to_string()
+            format!(" Delete? (y/n){}", dry_run)
         }
-    } else if let Some(ref msg) = app.cleaner_status {
-        format!(" {} │ c:clean  d:delete  s:sort  r:refresh  Esc:exit", msg)
     } else {
-        " ↑↓:nav  Enter:open  ←:back  c:clean  d:delete  s:sort  r:refresh  Esc:exit".to_string()
+        let label = |action| app.keymap.analyzer_label(action);
+        let keys = format!(
+            "{}:clean  {}:delete  {}:sort  {}:size  {}:dry run  {}:refresh  Esc:exit",
+            label(AnalyzerAction::Clean),
+            label(AnalyzerAction::Delete),
+            label(AnalyzerAction::Sort),
+            label(AnalyzerAction::SizeMetric),
+            label(AnalyzerAction::DryRun),
+            label(AnalyzerAction::Refresh),
+        );
+        match app.cleaner_status {
+            Some(ref msg) => format!(" {} │ {}", msg, keys),
+            None => format!(
+                " {}{}:nav  {}:open  {}:back  1-9:jump up  {}",
+                label(AnalyzerAction::Up),
+                label(AnalyzerAction::Down),
+                label(AnalyzerAction::Open),
+                label(AnalyzerAction::Parent),
+                keys
+            ),
+        }
     };
 
     let style = if app.cleaner_confirm_delete || app.cleaner_confirm_clean {
@@ -790,3 +1503,398 @@ pub fn render_disk_analyzer(f: &mut Frame, app: &App, area: Rect) {
 
     f.render_widget(footer, chunks[2]);
 }
+
+/// Render the unified diff of the two selected files.
+pub fn render_diff_view(f: &mut Frame, app: &App, area: Rect) {
+    use crate::sync::DiffLineKind;
+
+    f.render_widget(Clear, area);
+
+    let visible_height = area.height.saturating_sub(2) as usize;
+    let num = |n: Option<usize>| n.map(|n| format!("{:>5}", n)).unwrap_or_else(|| "     ".to_string());
+
+    let lines: Vec<Line> = app
+        .diff_lines
+        .iter()
+        .skip(app.diff_scroll)
+        .take(visible_height)
+        .map(|line| {
+            let (marker, style) = match line.kind {
+                DiffLineKind::Added => ("+", Style::default().fg(Color::Green)),
+                DiffLineKind::Removed => ("-", Style::default().fg(Color::Red)),
+                DiffLineKind::Context => (" ", Style::default().fg(Color::White)),
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{} {} ", num(line.old_line), num(line.new_line)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(format!("{} {}", marker, line.text.replace('\t', "    ")), style),
+            ])
+        })
+        .collect();
+
+    let title = format!(
+        "{}(line {}/{}) ",
+        app.diff_title,
+        (app.diff_scroll + 1).min(app.diff_lines.len()),
+        app.diff_lines.len()
+    );
+
+    let diff = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Magenta))
+            .title(title)
+            .title_style(Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
+    );
+
+    f.render_widget(diff, area);
+}
+
+/// Render the recently modified files list in single-pane mode.
+pub fn render_recent_files(f: &mut Frame, app: &App, area: Rect) {
+    use ratatui::widgets::{List, ListItem, ListState};
+
+    let chunks = ratatui::layout::Layout::default()
+        .direction(ratatui::layout::Direction::Vertical)
+        .constraints([
+            ratatui::layout::Constraint::Length(3), // Header
+            ratatui::layout::Constraint::Min(5),    // List
+        ])
+        .split(area);
+
+    let header = Paragraph::new(format!(
+        " {} │ Sort: {} │ {} files",
+        app.recent_root.display(),
+        app.recent_sort_mode.label(),
+        app.recent_files.len()
+    ))
+    .block(Block::default().borders(Borders::ALL).title(" Recently Modified "));
+
+    f.render_widget(header, chunks[0]);
+
+    // Still walking the tree
+    if let Some(ref progress) = app.recent_progress {
+        let text = format!(
+            "\n\n  Scanning {}...\n\n  📁 {} folders\n  📄 {} files\n\n  Press Esc to cancel",
+            app.recent_root.display(),
+            progress.get_dirs(),
+            progress.get_files()
+        );
+
+        let paragraph = Paragraph::new(text)
+            .block(Block::default().borders(Borders::ALL).title(" Scanning... "))
+            .alignment(Alignment::Center);
+        f.render_widget(paragraph, chunks[1]);
+        return;
+    }
+
+    let now = chrono::Utc::now();
+    let age_width = match app.time_format {
+        crate::fs::TimeFormat::Relative => 12,
+        crate::fs::TimeFormat::Absolute => 16,
+    };
+    let name_width = (chunks[1].width as usize).saturating_sub(20 + age_width).max(10);
+
+    let items: Vec<ListItem> = app
+        .recent_files
+        .iter()
+        .map(|file| {
+            let modified = chrono::DateTime::<chrono::Utc>::from(file.modified);
+            let age = crate::fs::format_timestamp(Some(modified), app.time_format, now);
+            let size_str = humansize::format_size(file.size, humansize::BINARY);
+            let name = truncate_path(&file.relative_to(&app.recent_root), name_width);
+
+            ListItem::new(format!(
+                "{:<width$} {:>10} {:>age_width$}",
+                name,
+                size_str,
+                age,
+                width = name_width,
+                age_width = age_width
+            ))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD));
+
+    let mut state = ListState::default();
+    if !app.recent_files.is_empty() {
+        state.select(Some(app.recent_selected));
+    }
+
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+/// Render the verify results: progress while it runs, then changed/missing/new files.
+pub fn render_verify_results(f: &mut Frame, app: &App, area: Rect) {
+    use crate::app::VerifyEntryKind;
+    use ratatui::widgets::{List, ListItem, ListState};
+
+    let chunks = ratatui::layout::Layout::default()
+        .direction(ratatui::layout::Direction::Vertical)
+        .constraints([
+            ratatui::layout::Constraint::Length(3), // Header
+            ratatui::layout::Constraint::Min(5),    // List
+        ])
+        .split(area);
+
+    let count = |kind: VerifyEntryKind| app.verify_entries.iter().filter(|e| e.kind == kind).count();
+    let header = Paragraph::new(format!(
+        " {} │ {} OK, {} changed, {} missing, {} new",
+        app.verify_root.display(),
+        app.verify_matches,
+        count(VerifyEntryKind::Changed),
+        count(VerifyEntryKind::Missing),
+        count(VerifyEntryKind::New)
+    ))
+    .block(Block::default().borders(Borders::ALL).title(" Verify "));
+    f.render_widget(header, chunks[0]);
+
+    // Still hashing
+    if let Some(ref tally) = app.verify_progress {
+        let checked = tally.checked.load(std::sync::atomic::Ordering::Relaxed);
+        let total = tally.total.load(std::sync::atomic::Ordering::Relaxed);
+        let ratio = if total > 0 { (checked as f64 / total as f64).min(1.0) } else { 0.0 };
+        let gauge = Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title(format!(
+                " Verifying against {} - Esc to leave ",
+                app.verify_db.display()
+            )))
+            .gauge_style(Style::default().fg(Color::Cyan))
+            .ratio(ratio)
+            .label(format!("{}/{} files", checked, total));
+        let gauge_area = Rect { height: chunks[1].height.min(3), ..chunks[1] };
+        f.render_widget(gauge, gauge_area);
+        return;
+    }
+
+    if app.verify_entries.is_empty() {
+        let paragraph = Paragraph::new("\n\n✓ Every file matches the database")
+            .style(Style::default().fg(Color::Green))
+            .block(Block::default().borders(Borders::ALL))
+            .alignment(Alignment::Center);
+        f.render_widget(paragraph, chunks[1]);
+        return;
+    }
+
+    let name_width = (chunks[1].width as usize).saturating_sub(14).max(10);
+    let items: Vec<ListItem> = app
+        .verify_entries
+        .iter()
+        .map(|entry| {
+            let (marker, color) = match entry.kind {
+                VerifyEntryKind::Changed => ("~", Color::Yellow),
+                VerifyEntryKind::Missing => ("-", Color::Red),
+                VerifyEntryKind::New => ("+", Color::Green),
+            };
+            let relative = entry.path.strip_prefix(&app.verify_root).unwrap_or(&entry.path);
+            let name = truncate_path(&relative.to_string_lossy(), name_width);
+            ListItem::new(Line::from(vec![
+                Span::styled(format!(" {} {:<8} ", marker, entry.kind.label()), Style::default().fg(color)),
+                Span::raw(name),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD));
+
+    let mut state = ListState::default();
+    state.select(Some(app.verify_selected));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+/// Render the expected-hash prompt of a quick hash check.
+pub fn render_hash_check_input_popup(f: &mut Frame, app: &crate::app::App) {
+    let name = app.hash_check_path.as_ref()
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let input = &app.text_input;
+    let input_display = format!("{}|{}", &input.value[..input.cursor], &input.value[input.cursor..]);
+
+    let lines = vec![
+        Line::from(""),
+        Line::from(format!("Expected hash of {}", name)),
+        Line::from(Span::styled(input_display, Style::default().fg(Color::Yellow))),
+        Line::from("[algorithm:]digest - algorithm is detected from the length if omitted"),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Enter", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::raw(": Check  "),
+            Span::styled("Esc", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            Span::raw(": Cancel"),
+        ]),
+    ];
+
+    render_popup(f, " Check Hash ", lines, Style::default().fg(Color::Cyan));
+}
+
+/// Render the local path prompt for downloading the viewed file.
+pub fn render_save_as_popup(f: &mut Frame, app: &crate::app::App) {
+    let name = app.view_file_path.rsplit('/').next().unwrap_or_default();
+    let input = &app.text_input;
+    let input_display = format!("{}|{}", &input.value[..input.cursor], &input.value[input.cursor..]);
+
+    let lines = vec![
+        Line::from(""),
+        Line::from(format!(
+            "Download {} ({}) to",
+            name,
+            humansize::format_size(app.view_file_size, humansize::BINARY)
+        )),
+        Line::from(Span::styled(input_display, Style::default().fg(Color::Yellow))),
+        Line::from(format!(
+            "Written to {}{} first; a partial one left by an earlier try is continued",
+            name,
+            crate::fs::copy::PARTIAL_DOWNLOAD_SUFFIX
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Enter", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::raw(": Download  "),
+            Span::styled("Esc", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            Span::raw(": Cancel"),
+        ]),
+    ];
+
+    render_popup(f, " Save Local Copy ", lines, Style::default().fg(Color::Cyan));
+}
+
+/// Render the match/mismatch popup of a quick hash check.
+pub fn render_hash_check_result_popup(f: &mut Frame, check: &crate::hash::HashCheck) {
+    let (verdict, color) = if check.matches() {
+        ("✓ MATCH", Color::Green)
+    } else {
+        ("✗ MISMATCH", Color::Red)
+    };
+    let name = check.path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| check.path.display().to_string());
+
+    let lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(verdict, Style::default().fg(color).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from(format!("{} ({})", name, check.algorithm.to_uppercase())),
+        Line::from(format!("expected {}", check.expected)),
+        Line::from(Span::styled(format!("actual   {}", check.actual), Style::default().fg(color))),
+        Line::from(""),
+        Line::from("Press any key to close"),
+    ];
+
+    render_popup(f, " Hash Check ", lines, Style::default().fg(color));
+}
+
+/// Popup listing the metadata of one entry (ETag, storage class, ... for cloud objects)
+pub fn render_object_details_popup(f: &mut Frame, path: &str, meta: &crate::fs::ObjectMeta) {
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(path.to_string(), Style::default().add_modifier(Modifier::BOLD))),
+        Line::from(""),
+    ];
+    for (label, value) in meta.rows() {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<14}", label), Style::default().fg(Color::Cyan)),
+            Span::raw(value),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("Press any key to close"));
+
+    render_popup(f, " Details ", lines, Style::default().fg(Color::Cyan));
+}
+
+/// Popup offering to retry copies/moves that failed, resuming partial transfers
+pub fn render_confirm_retry_popup(f: &mut Frame, app: &crate::app::App) {
+    let area = f.area();
+    let failed = &app.operation_queue.failed_transfers;
+    let Some(first) = failed.first() else {
+        return;
+    };
+
+    let detail = if first.resumable() {
+        format!(
+            "{} of {} transferred, retry resumes from there",
+            crate::fs::types::format_bytes(first.bytes_transferred),
+            crate::fs::types::format_bytes(first.op.size)
+        )
+    } else if first.bytes_transferred > 0 {
+        format!(
+            "{} transferred, destination can't resume: retry starts over",
+            crate::fs::types::format_bytes(first.bytes_transferred)
+        )
+    } else {
+        "Nothing transferred, retry starts over".to_string()
+    };
+    let more = if failed.len() > 1 {
+        format!(" (+{} more)", failed.len() - 1)
+    } else {
+        String::new()
+    };
+
+    let blocks = vec![
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                format!("{} {} failed{}", first.op.kind.verb(), first.op.name, more),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(format!("{} → {}", first.op.src_path, first.op.dest_path)),
+        Line::from(first.error.clone()),
+        Line::from(detail),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Enter/Y", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::raw(": Retry  "),
+            Span::styled("Esc/N", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            Span::raw(": Dismiss"),
+        ]),
+    ];
+
+    let popup_width = 70u16.min(area.width.saturating_sub(4));
+    let popup_height = 9;
+    let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+    let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+
+    if popup_x >= area.width || popup_y >= area.height {
+        return;
+    }
+
+    let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+    f.render_widget(Clear, popup_area);
+
+    let popup = Paragraph::new(blocks)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+                .title(" ⚠ Transfer Failed ")
+                .title_style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+        )
+        .alignment(Alignment::Center);
+
+    f.render_widget(popup, popup_area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_help_pages_split_at_the_width() {
+        assert_eq!(help_pages(&[4, 4, 4], 12), vec![0..3]);
+        assert_eq!(help_pages(&[4, 4, 4, 4, 4], 10), vec![0..2, 2..4, 4..5]);
+        assert_eq!(help_pages(&[3, 8, 2, 2], 10), vec![0..1, 1..3, 3..4]);
+        // Entries wider than the bar still get a page each
+        assert_eq!(help_pages(&[20, 5, 30], 10), vec![0..1, 1..2, 2..3]);
+        assert!(help_pages(&[], 10).is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+//! Hex-dump rendering for the streaming viewer.
+
+/// Bytes shown per hex-dump row.
+pub const HEX_ROW_BYTES: usize = 16;
+
+/// Bytes inspected when deciding whether a file is binary.
+pub const BINARY_SNIFF_LEN: usize = 8 * 1024;
+
+/// Null-byte heuristic: text files (including UTF-8) practically never contain NUL.
+pub fn looks_binary(data: &[u8]) -> bool {
+    data[..data.len().min(BINARY_SNIFF_LEN)].contains(&0)
+}
+
+/// Format `data` as `hexdump -C` style rows: offset, 16 hex bytes, ASCII gutter.
+/// `base` is the file offset of the first byte (for chunked viewing).
+pub fn hex_dump_lines(data: &[u8], base: u64) -> Vec<String> {
+    data.chunks(HEX_ROW_BYTES)
+        .enumerate()
+        .map(|(i, row)| {
+            let mut line = format!("{:08x} ", base + (i * HEX_ROW_BYTES) as u64);
+            for col in 0..HEX_ROW_BYTES {
+                // Extra gap between the two 8-byte halves
+                if col % 8 == 0 {
+                    line.push(' ');
+                }
+                match row.get(col) {
+                    Some(byte) => line.push_str(&format!("{:02x} ", byte)),
+                    None => line.push_str("   "),
+                }
+            }
+            line.push_str(" |");
+            line.extend(row.iter().map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' }));
+            line.push('|');
+            line
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows_show_offset_hex_and_ascii() {
+        let data: Vec<u8> = (b'A'..=b'Q').chain([0, b'\n']).collect();
+        let lines = hex_dump_lines(&data, 0x100);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "00000100  41 42 43 44 45 46 47 48  49 4a 4b 4c 4d 4e 4f 50  |ABCDEFGHIJKLMNOP|"
+        );
+        // A short last row is padded so its gutter lines up
+        assert_eq!(lines[1].len(), lines[0].len() - 13);
+        assert!(lines[1].starts_with("00000110  51 00 0a "));
+        assert!(lines[1].ends_with("|Q..|"));
+    }
+
+    #[test]
+    fn test_binary_detection_looks_at_the_start_only() {
+        assert!(looks_binary(b"PK\x03\x04\x00\x00"));
+        assert!(!looks_binary("plain text, €uro\n".as_bytes()));
+        assert!(!looks_binary(b""));
+
+        let mut late_nul = vec![b'a'; BINARY_SNIFF_LEN];
+        late_nul.push(0);
+        assert!(!looks_binary(&late_nul));
+    }
+}
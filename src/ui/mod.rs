@@ -1,4 +1,5 @@
 pub mod components;
+pub mod hexdump;
 pub mod pane;
 pub mod syntax;
 
@@ -6,4 +7,4 @@ pub use components::{
     render_delete_confirm, render_help_bar, render_popup, 
     render_progress_bar, render_status_bar,
 };
-pub use pane::Pane;
+pub use pane::{DirSizes, Pane};
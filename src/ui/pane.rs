@@ -6,16 +6,62 @@ use ratatui::{
     Frame,
 };
 
-use crate::fs::{BackendType, FileEntry, LocalBackend, SelectingBackend, StorageBackend};
-use std::path::PathBuf;
+use crate::fs::{
+    format_bytes, format_timestamp, sort_entries, BackendType, FileEntry, LocalBackend, PendingEntries,
+    SelectingBackend, SpaceInfo, StorageBackend, TimeFormat,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// Panes narrower than this (inside the borders) leave out the modification time column
+const TIME_COLUMN_MIN_WIDTH: usize = 60;
+
+/// Spinner frames shown while a directory size is being computed
+const SPINNER_FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Current spinner frame, advancing every 100ms
+pub(crate) fn spinner_frame() -> &'static str {
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    SPINNER_FRAMES[(millis / 100) as usize % SPINNER_FRAMES.len()]
+}
+
+/// On-demand recursive directory sizes for local panes, shared by both panes
+#[derive(Debug, Default)]
+pub struct DirSizes {
+    /// Computed sizes by absolute directory path
+    pub sizes: HashMap<PathBuf, u64>,
+    /// Directory currently being computed
+    pub computing: Option<PathBuf>,
+}
+
+impl DirSizes {
+    /// Size column text for a directory: cached size, spinner, or None if unknown
+    fn display(&self, path: &Path) -> Option<String> {
+        if self.computing.as_deref() == Some(path) {
+            return Some(spinner_frame().to_string());
+        }
+        self.sizes.get(path).map(|&size| format_bytes(size))
+    }
+}
+
 pub struct Pane {
     pub path: String,
     pub entries: Vec<FileEntry>,
     pub state: ListState,
     pub is_active: bool,
     pub storage: Arc<dyn StorageBackend>,
+    /// Full paths of entries marked for batch operations
+    pub marked: HashSet<String>,
+    /// Locations visited in this pane, for back/forward
+    pub history: crate::app::NavHistory,
+    /// Free space where the current path lives, refreshed with the listing
+    pub space: Option<SpaceInfo>,
+    /// Rest of a huge directory, still being read after the first page was shown
+    pub pending: Option<PendingEntries>,
 }
 
 impl Pane {
@@ -31,6 +77,10 @@ impl Pane {
             state,
             is_active: false,
             storage,
+            marked: HashSet::new(),
+            history: Default::default(),
+            space: None,
+            pending: None,
         }
     }
 
@@ -44,6 +94,10 @@ impl Pane {
             state,
             is_active: false,
             storage: Arc::new(SelectingBackend),
+            marked: HashSet::new(),
+            history: Default::default(),
+            space: None,
+            pending: None,
         }
     }
 
@@ -87,19 +141,158 @@ impl Pane {
         self.state.selected().and_then(|i| self.entries.get(i))
     }
 
-    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+    /// Full path of an entry in this pane's directory
+    pub fn entry_path(&self, name: &str) -> String {
+        if self.path.ends_with('/') || self.path.is_empty() {
+            format!("{}{}", self.path, name)
+        } else {
+            format!("{}/{}", self.path, name)
+        }
+    }
+
+    pub fn is_marked(&self, entry: &FileEntry) -> bool {
+        self.marked.contains(&self.entry_path(&entry.name))
+    }
+
+    /// Toggle the mark on the selected entry and move to the next one
+    pub fn toggle_mark(&mut self) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+        if entry.name != ".." {
+            let path = self.entry_path(&entry.name);
+            if !self.marked.remove(&path) {
+                self.marked.insert(path);
+            }
+        }
+        self.select_next();
+    }
+
+    /// Mark (or unmark) every entry whose name matches `pattern`; returns how many changed
+    pub fn mark_matching(&mut self, pattern: &globset::GlobMatcher, mark: bool) -> usize {
+        let paths: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|e| e.name != ".." && pattern.is_match(&e.name))
+            .map(|e| self.entry_path(&e.name))
+            .collect();
+        paths
+            .into_iter()
+            .filter(|path| {
+                if mark {
+                    self.marked.insert(path.clone())
+                } else {
+                    self.marked.remove(path)
+                }
+            })
+            .count()
+    }
+
+    /// Mark every unmarked entry and unmark every marked one
+    pub fn invert_marks(&mut self) {
+        let paths: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|e| e.name != "..")
+            .map(|e| self.entry_path(&e.name))
+            .collect();
+        for path in paths {
+            if !self.marked.remove(&path) {
+                self.marked.insert(path);
+            }
+        }
+    }
+
+    /// Marked entries in listing order
+    pub fn marked_entries(&self) -> Vec<&FileEntry> {
+        self.entries.iter().filter(|e| self.is_marked(e)).collect()
+    }
+
+    /// Put the cursor on the entry called `name`, or at `fallback` (kept in range) when
+    /// that entry is gone
+    pub fn select_name(&mut self, name: Option<&str>, fallback: Option<usize>) {
+        let found = name.and_then(|name| self.entries.iter().position(|e| e.name == name));
+        let index = found.or(fallback).map(|i| i.min(self.entries.len().saturating_sub(1)));
+        self.state.select(index);
+    }
+
+    /// Replace the listing, dropping the rest of a paged one still being read
+    pub fn set_entries(&mut self, entries: Vec<FileEntry>) {
+        self.entries = entries;
+        self.pending = None;
+    }
+
+    /// Merge the rest of a paged listing, re-sorting the full set and keeping the cursor on
+    /// the same entry
+    pub fn finish_listing(&mut self, rest: Vec<FileEntry>) {
+        self.pending = None;
+        let selected = self.selected_entry().map(|e| e.name.clone());
+        let parent = self.entries.first().filter(|e| e.name == "..").is_some();
+        self.entries.extend(rest);
+        sort_entries(&mut self.entries[usize::from(parent)..]);
+        if let Some(name) = selected {
+            let index = self.entries.iter().position(|e| e.name == name).unwrap_or(0);
+            self.state.select(Some(index));
+        }
+        self.prune_marks();
+    }
+
+    /// Drop marks for entries no longer in the listing (after a refresh or directory change)
+    pub fn prune_marks(&mut self) {
+        let listed: HashSet<String> = self.entries.iter().map(|e| self.entry_path(&e.name)).collect();
+        self.marked.retain(|path| listed.contains(path));
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect, dir_sizes: &DirSizes, time_format: TimeFormat) {
         // Calculate available width for content (minus borders and padding)
         let inner_width = area.width.saturating_sub(2) as usize; // -2 for borders
         let icon_width = 3; // emoji + space
         let size_width = 8; // e.g., " 123.4 MB" 
-        let name_width = inner_width.saturating_sub(icon_width + size_width + 1);
+        // Menus of the selecting backend have no times to show
+        let time_width = if inner_width >= TIME_COLUMN_MIN_WIDTH
+            && !matches!(self.storage.backend_type(), BackendType::Selecting)
+        {
+            match time_format {
+                TimeFormat::Relative => 9,  // " 365d ago"
+                TimeFormat::Absolute => 17, // " 2024-05-01 14:03"
+            }
+        } else {
+            0
+        };
+        let name_width = inner_width.saturating_sub(icon_width + size_width + time_width + 1);
+        let is_local = self.storage.is_local();
+        let now = chrono::Utc::now();
         
+        // Only the visible rows are built, so huge directories cost nothing extra per frame
+        let height = area.height.saturating_sub(2) as usize;
+        let selected = self.state.selected().unwrap_or(0);
+        let mut offset = self.state.offset().min(self.entries.len().saturating_sub(1));
+        if selected < offset {
+            offset = selected;
+        } else if height > 0 && selected >= offset + height {
+            offset = selected + 1 - height;
+        }
+        *self.state.offset_mut() = offset;
+
         let items: Vec<ListItem> = self
             .entries
             .iter()
+            .skip(offset)
+            .take(height)
             .map(|entry| {
                 let icon = if entry.is_dir { "📁" } else { "📄" };
-                let size = entry.format_size();
+                let name_style = if self.is_marked(entry) {
+                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                let size = if entry.is_dir && is_local {
+                    dir_sizes
+                        .display(&Path::new(&self.path).join(&entry.name))
+                        .unwrap_or_else(|| entry.format_size())
+                } else {
+                    entry.format_size()
+                };
                 
                 // Truncate filename if too long
                 let name = if entry.name.len() > name_width && name_width > 3 {
@@ -109,14 +302,25 @@ impl Pane {
                 };
 
                 // Build spans with proper styling
-                let spans = vec![
+                let mut spans = vec![
                     Span::raw(format!("{} ", icon)),
-                    Span::raw(format!("{:<width$}", name, width = name_width)),
+                    Span::styled(format!("{:<width$}", name, width = name_width), name_style),
                     Span::styled(
                         format!("{:>8}", size),
                         Style::default().fg(Color::DarkGray),
                     ),
                 ];
+                if time_width > 0 {
+                    let modified = if entry.name == ".." {
+                        String::new()
+                    } else {
+                        format_timestamp(entry.modified, time_format, now)
+                    };
+                    spans.push(Span::styled(
+                        format!("{:>width$}", modified, width = time_width),
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                }
 
                 ListItem::new(Line::from(spans))
             })
@@ -131,9 +335,11 @@ impl Pane {
         // Build title - truncate if too long
         let backend_type = self.storage.backend_type();
         let display_path = self.storage.display_path(&self.path);
-        let max_title_len = inner_width.saturating_sub(4); // leave room for brackets
+        let space_label = self.space.map(|space| format!(" {} ", space.label()));
+        let space_len = space_label.as_ref().map_or(0, |label| label.chars().count());
+        let max_title_len = inner_width.saturating_sub(4 + space_len); // leave room for brackets and free space
         
-        let title = match backend_type {
+        let mut title = match backend_type {
             BackendType::Local => {
                 if self.path.is_empty() {
                     "[Local] Select directory".to_string()
@@ -164,15 +370,32 @@ impl Pane {
             }
         };
 
+        let marked = self.marked_entries().len();
+        if marked > 0 {
+            title = format!("{} [{} marked]", title, marked);
+        }
+        if self.pending.is_some() {
+            title = format!("{} [{} {} more...]", title, self.entries.len(), spinner_frame());
+        }
+
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(border_style);
+        if let (Some(label), Some(space)) = (space_label, self.space) {
+            // Free space goes on the right of the header, highlighted when running low
+            let style = if space.is_low() {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            block = block.title_top(Line::from(label).style(style).right_aligned());
+        }
+
         // Only show selection highlight on active pane
         let list = if self.is_active {
             List::new(items)
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .title(title)
-                        .border_style(border_style),
-                )
+                .block(block)
                 .highlight_style(
                     Style::default()
                         .bg(Color::DarkGray)
@@ -180,15 +403,77 @@ impl Pane {
                 )
         } else {
             // Inactive pane - no highlight
-            List::new(items)
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .title(title)
-                        .border_style(border_style),
-                )
+            List::new(items).block(block)
         };
 
-        f.render_stateful_widget(list, area, &mut self.state);
+        // The list sees only the visible window, so its state is relative to the offset
+        let mut window = ListState::default();
+        window.select(self.state.selected().map(|i| i.saturating_sub(offset)));
+        f.render_stateful_widget(list, area, &mut window);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pane_with(names: &[&str]) -> Pane {
+        let mut pane = Pane::new("/data".to_string());
+        pane.set_entries(
+            names
+                .iter()
+                .map(|name| FileEntry {
+                    name: name.to_string(),
+                    size: 0,
+                    is_dir: *name == "..",
+                    modified: None,
+                    permissions: None,
+                })
+                .collect(),
+        );
+        pane
+    }
+
+    fn marked(pane: &Pane) -> Vec<&str> {
+        pane.marked_entries().iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_mark_matching_counts_changes() {
+        let mut pane = pane_with(&["..", "a.log", "b.log", "c.txt"]);
+        let logs = globset::Glob::new("*.log").unwrap().compile_matcher();
+
+        assert_eq!(pane.mark_matching(&logs, true), 2);
+        assert_eq!(marked(&pane), vec!["a.log", "b.log"]);
+        // Already marked entries don't count again
+        assert_eq!(pane.mark_matching(&logs, true), 0);
+
+        let everything = globset::Glob::new("*").unwrap().compile_matcher();
+        assert_eq!(pane.mark_matching(&everything, false), 2);
+        assert!(pane.marked.is_empty());
+        // The parent entry is never marked
+        assert_eq!(pane.mark_matching(&globset::Glob::new("..").unwrap().compile_matcher(), true), 0);
+    }
+
+    #[test]
+    fn test_invert_marks_skips_the_parent_entry() {
+        let mut pane = pane_with(&["..", "a.log", "b.log", "c.txt"]);
+        pane.marked.insert(pane.entry_path("b.log"));
+
+        pane.invert_marks();
+        assert_eq!(marked(&pane), vec!["a.log", "c.txt"]);
+        pane.invert_marks();
+        assert_eq!(marked(&pane), vec!["b.log"]);
+    }
+
+    #[test]
+    fn test_prune_marks_drops_entries_gone_from_the_listing() {
+        let mut pane = pane_with(&["..", "a.log", "b.log"]);
+        pane.invert_marks();
+        pane.marked.insert("/elsewhere/c.txt".to_string());
+
+        pane.set_entries(pane_with(&["..", "b.log"]).entries);
+        pane.prune_marks();
+        assert_eq!(pane.marked, HashSet::from(["/data/b.log".to_string()]));
     }
 }
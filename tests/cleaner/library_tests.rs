@@ -0,0 +1,176 @@
+// Tests for running a clean through the library API
+
+use abyss::cleaner::{Config, DeleteEvent, Deleter, LogLevel, ScanProgress, Scanner, Stats};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+
+fn build_tree(root: &Path, files: &[(&str, &str)]) {
+    for (path, content) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+}
+
+fn config() -> Config {
+    Config {
+        directories: vec!["target".to_string(), "node_modules".to_string()],
+        files: vec![".DS_Store".to_string()],
+        days: None,
+        require_markers: false,
+        markers: HashMap::new(),
+        post_hook: None,
+        only: Vec::new(),
+        ignore_case: false,
+        skip_hidden: false,
+    }
+}
+
+/// Scan and delete `root` on background threads, returning every event in arrival order
+fn clean(root: &Path, dry_run: bool) -> (Arc<Stats>, Arc<ScanProgress>, Vec<DeleteEvent>) {
+    let stats = Arc::new(Stats::new());
+    let progress = Arc::new(ScanProgress::new());
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let (events_tx, events_rx) = crossbeam_channel::unbounded();
+
+    let scanner = Scanner::new(root.to_path_buf(), 2, Arc::new(config()))
+        .with_stats(Arc::clone(&stats))
+        .with_progress(Arc::clone(&progress));
+    let deleter = Deleter::new(Arc::clone(&stats), dry_run, LogLevel::Quiet)
+        .with_measured_sizes()
+        .with_events(events_tx);
+
+    let scan_handle = thread::spawn(move || scanner.scan(tx));
+    let delete_handle = thread::spawn(move || deleter.process(rx));
+    scan_handle.join().unwrap();
+    delete_handle.join().unwrap();
+
+    // The deleter (and its sender) is gone, so this ends
+    (stats, progress, events_rx.iter().collect())
+}
+
+const FILES: &[(&str, &str)] = &[
+    ("app/Cargo.toml", "[package]"),
+    ("app/src/main.rs", "fn main() {}"),
+    ("app/target/debug/app", "0123456789"),
+    ("web/node_modules/left-pad/index.js", "pad"),
+    ("web/.DS_Store", "ds"),
+];
+
+#[test]
+fn test_library_clean_reports_events() {
+    let dir = tempfile::tempdir().unwrap();
+    build_tree(dir.path(), FILES);
+
+    let (stats, progress, events) = clean(dir.path(), false);
+
+    assert!(progress.is_done());
+    assert!(progress.get_files() >= 3);
+    assert!(progress.get_dirs() >= 3);
+
+    let mut removed: Vec<_> = events
+        .iter()
+        .map(|event| match event {
+            DeleteEvent::Removed { path, is_dir, bytes } => {
+                (path.strip_prefix(dir.path()).unwrap().to_path_buf(), *is_dir, *bytes)
+            }
+            other => panic!("unexpected event {:?}", other),
+        })
+        .collect();
+    removed.sort();
+    assert_eq!(
+        removed,
+        vec![
+            (Path::new("app/target").to_path_buf(), true, 10),
+            (Path::new("web/.DS_Store").to_path_buf(), false, 2),
+            (Path::new("web/node_modules").to_path_buf(), true, 3),
+        ]
+    );
+
+    assert_eq!(stats.directories(), 2);
+    assert_eq!(stats.files(), 1);
+    assert_eq!(stats.bytes(), 15);
+    assert_eq!(stats.error_count(), 0);
+
+    assert!(!dir.path().join("app/target").exists());
+    assert!(!dir.path().join("web/node_modules").exists());
+    assert!(!dir.path().join("web/.DS_Store").exists());
+    assert!(dir.path().join("app/src/main.rs").exists());
+    assert!(dir.path().join("app/Cargo.toml").exists());
+}
+
+#[test]
+fn test_library_dry_run_reports_without_deleting() {
+    let dir = tempfile::tempdir().unwrap();
+    build_tree(dir.path(), FILES);
+
+    let (stats, _, events) = clean(dir.path(), true);
+
+    assert_eq!(events.len(), 3);
+    assert!(events.iter().all(|event| matches!(event, DeleteEvent::Removed { .. })));
+    assert_eq!(stats.bytes(), 15);
+    assert!(dir.path().join("app/target/debug/app").exists());
+    assert!(dir.path().join("web/.DS_Store").exists());
+}
+
+#[test]
+fn test_library_skip_hidden_leaves_unmatched_dot_dirs() {
+    let dir = tempfile::tempdir().unwrap();
+    build_tree(
+        dir.path(),
+        &[
+            (".cache/pkg/node_modules/dep/index.js", "dep"),
+            (".terraform/providers/aws", "aws"),
+            ("web/node_modules/left-pad/index.js", "pad"),
+            ("web/.DS_Store", "ds"),
+        ],
+    );
+    let config = Config {
+        directories: vec![".terraform".to_string(), "node_modules".to_string()],
+        skip_hidden: true,
+        ..config()
+    };
+
+    let (tx, rx) = crossbeam_channel::unbounded();
+    Scanner::new(dir.path().to_path_buf(), 2, Arc::new(config)).scan(tx);
+
+    let mut found: Vec<_> = rx
+        .iter()
+        .map(|item| item.path.strip_prefix(dir.path()).unwrap().to_path_buf())
+        .collect();
+    found.sort();
+    assert_eq!(
+        found,
+        vec![
+            Path::new(".terraform").to_path_buf(),
+            Path::new("web/.DS_Store").to_path_buf(),
+            Path::new("web/node_modules").to_path_buf(),
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_library_counts_disk_usage_of_sparse_files() {
+    let dir = tempfile::tempdir().unwrap();
+    build_tree(dir.path(), &[("app/target/debug/app", "0123456789")]);
+    // 8 MiB long, but only the first few bytes are ever written
+    let sparse = fs::File::create(dir.path().join("app/target/debug/sparse.img")).unwrap();
+    std::io::Write::write_all(&mut &sparse, b"header").unwrap();
+    sparse.set_len(8 * 1024 * 1024).unwrap();
+    drop(sparse);
+
+    let (stats, _, _) = clean(dir.path(), true);
+
+    assert_eq!(stats.bytes(), 8 * 1024 * 1024 + 10);
+    assert!(stats.disk_bytes() > 0);
+    assert!(
+        stats.disk_bytes() < stats.bytes() / 2,
+        "disk {} vs logical {}",
+        stats.disk_bytes(),
+        stats.bytes()
+    );
+}
@@ -0,0 +1,3 @@
+// Test module entry point for cleaner tests
+
+mod library_tests;
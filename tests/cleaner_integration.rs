@@ -0,0 +1,5 @@
+// Cleaner integration tests
+// Drives a clean through the library API, the way an embedding program would
+// Tests are organized in the cleaner/ subdirectory
+
+mod cleaner;
@@ -0,0 +1,54 @@
+// Tests for quick hash check module
+
+use abyss::hash::{check_file, parse_expected_hash, ExpectedHash};
+use std::fs;
+
+const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+#[test]
+fn test_detect_algorithm_from_length() {
+    let detect = |len: usize| parse_expected_hash(&"a".repeat(len)).ok().map(|expected| expected.algorithm);
+    // xxh3 digests are too short to tell apart from other hashes
+    assert_eq!(detect(16), None);
+    assert_eq!(detect(32).as_deref(), Some("md5"));
+    assert_eq!(detect(40).as_deref(), Some("sha1"));
+    assert_eq!(detect(64).as_deref(), Some("sha256"));
+    assert_eq!(detect(128).as_deref(), Some("sha512"));
+    assert_eq!(detect(63), None);
+}
+
+#[test]
+fn test_parse_published_formats() {
+    let bare = parse_expected_hash(&format!("  {}\n", HELLO_SHA256.to_uppercase())).unwrap();
+    assert_eq!(bare, ExpectedHash { algorithm: "sha256".to_string(), hash: HELLO_SHA256.to_string() });
+
+    let sum = parse_expected_hash(&format!("{}  hello.txt", HELLO_SHA256)).unwrap();
+    assert_eq!(sum.algorithm, "sha256");
+
+    let prefixed = parse_expected_hash(&format!("blake3:{}", HELLO_SHA256)).unwrap();
+    assert_eq!(prefixed.algorithm, "blake3");
+
+    let bsd = parse_expected_hash(&format!("SHA3-256 (hello.txt) = {}", HELLO_SHA256)).unwrap();
+    assert_eq!(bsd.algorithm, "sha3-256");
+    assert_eq!(bsd.hash, HELLO_SHA256);
+
+    assert!(parse_expected_hash("not-a-hash").is_err());
+    assert!(parse_expected_hash("abc123").is_err()); // length matches no algorithm
+    assert!(parse_expected_hash(&format!("nope:{}", HELLO_SHA256)).is_err());
+}
+
+#[test]
+fn test_check_file_match_and_mismatch() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("hello.txt");
+    fs::write(&path, b"hello").unwrap();
+
+    let good = check_file(&path, &parse_expected_hash(HELLO_SHA256).unwrap()).unwrap();
+    assert!(good.matches());
+    assert_eq!(good.algorithm, "sha256");
+
+    let bad_hash = format!("{}0", &HELLO_SHA256[..63]);
+    let bad = check_file(&path, &parse_expected_hash(&bad_hash).unwrap()).unwrap();
+    assert!(!bad.matches());
+    assert_eq!(bad.actual, HELLO_SHA256);
+}
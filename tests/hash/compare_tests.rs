@@ -186,6 +186,7 @@ fn test_find_duplicates_no_duplicates() {
             hash: "hash1".to_string(),
             algorithm: "sha256".to_string(),
             fast_mode: false,
+            inferred: false,
         },
     );
     db.insert(
@@ -194,6 +195,7 @@ fn test_find_duplicates_no_duplicates() {
             hash: "hash2".to_string(),
             algorithm: "sha256".to_string(),
             fast_mode: false,
+            inferred: false,
         },
     );
     
@@ -213,6 +215,7 @@ fn test_find_duplicates_with_duplicates() {
             hash: "hash_dup".to_string(),
             algorithm: "sha256".to_string(),
             fast_mode: false,
+            inferred: false,
         },
     );
     db.insert(
@@ -221,6 +224,7 @@ fn test_find_duplicates_with_duplicates() {
             hash: "hash_dup".to_string(),
             algorithm: "sha256".to_string(),
             fast_mode: false,
+            inferred: false,
         },
     );
     db.insert(
@@ -229,6 +233,7 @@ fn test_find_duplicates_with_duplicates() {
             hash: "hash_unique".to_string(),
             algorithm: "sha256".to_string(),
             fast_mode: false,
+            inferred: false,
         },
     );
     
@@ -366,3 +371,31 @@ fn test_compare_compressed_databases() {
     fs::remove_file(db1_compressed).unwrap();
     fs::remove_file(db2_compressed).unwrap();
 }
+
+#[test]
+fn test_compare_ignores_databases_in_the_tree() {
+    let dir = tempfile::tempdir().unwrap();
+    let db1 = dir.path().join("hashes.txt");
+    let db2 = dir.path().join("hashes-new.txt");
+
+    // The second scan picked up the first database and a scratch file
+    fs::write(&db1, "hash1  sha256  normal  file1.txt\n").unwrap();
+    fs::write(
+        &db2,
+        "hash1  sha256  normal  file1.txt\n\
+         hash9  sha256  normal  hashes.txt\n\
+         hash8  sha256  normal  tmp/scratch.bin\n",
+    )
+    .unwrap();
+
+    let report = CompareEngine::new().compare(&db1, &db2).unwrap();
+    assert_eq!(report.added_files, vec![PathBuf::from("tmp/scratch.bin")]);
+    assert_eq!(report.db2_total_files, 2);
+
+    let report = CompareEngine::new()
+        .with_ignored_paths([dir.path().join("tmp")])
+        .compare(&db1, &db2)
+        .unwrap();
+    assert!(report.added_files.is_empty(), "{:?}", report.added_files);
+    assert_eq!(report.unchanged_files, 1);
+}
@@ -345,3 +345,63 @@ fn test_compress_database() {
     fs::remove_file(plain_db).unwrap();
     fs::remove_file(compressed_path).unwrap();
 }
+
+#[test]
+fn test_write_json_entry() {
+    let mut buffer = Vec::new();
+
+    DatabaseHandler::write_json_entry(&mut buffer, "abc123", "sha256", false, 42, Path::new("dir/file.txt")).unwrap();
+
+    let output = String::from_utf8(buffer).unwrap();
+    assert_eq!(output, "{\"path\":\"dir/file.txt\",\"algorithm\":\"sha256\",\"hash\":\"abc123\",\"size\":42}\n");
+}
+
+#[test]
+fn test_parse_json_line() {
+    let (path, entry) = DatabaseHandler::parse_json_line(
+        r#"{"path":"a b/c.txt","algorithm":"blake3","hash":"ff00","size":3,"fast_mode":true}"#,
+    ).unwrap();
+
+    assert_eq!(path, PathBuf::from("a b/c.txt"));
+    assert_eq!(entry.hash, "ff00");
+    assert_eq!(entry.algorithm, "blake3");
+    assert!(entry.fast_mode);
+
+    assert!(DatabaseHandler::parse_json_line(r#"{"algorithm":"md5","hash":"ff"}"#).is_none());
+    assert!(DatabaseHandler::parse_json_line("not json").is_none());
+}
+
+#[test]
+fn test_read_json_database_detects_format() {
+    let temp_file = "test_json_database.jsonl";
+    let mut buffer = Vec::new();
+    DatabaseHandler::write_json_entry(&mut buffer, "hash1", "sha256", false, 1, Path::new("file1.txt")).unwrap();
+    DatabaseHandler::write_json_entry(&mut buffer, "hash2", "sha256", true, 2, Path::new("sub/file2.txt")).unwrap();
+    fs::write(temp_file, &buffer).unwrap();
+
+    assert_eq!(DatabaseHandler::detect_format(Path::new(temp_file)).unwrap(), abyss::hash::DatabaseFormat::Json);
+    let database = DatabaseHandler::read_database(Path::new(temp_file)).unwrap();
+    assert_eq!(database.len(), 2);
+    assert_eq!(database[&PathBuf::from("file1.txt")].hash, "hash1");
+    assert!(database[&PathBuf::from("sub/file2.txt")].fast_mode);
+
+    fs::remove_file(temp_file).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_json_entry_round_trips_non_utf8_path() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = Path::new(OsStr::from_bytes(b"caf\xe9/menu.txt"));
+    let mut buffer = Vec::new();
+    DatabaseHandler::write_json_entry(&mut buffer, "abc", "md5", false, 0, path).unwrap();
+
+    let line = String::from_utf8(buffer).unwrap();
+    assert!(line.contains("\"path_base64\""));
+    assert!(!line.contains("\"path\""));
+
+    let (parsed, _) = DatabaseHandler::parse_json_line(&line).unwrap();
+    assert_eq!(parsed, path);
+}
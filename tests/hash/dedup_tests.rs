@@ -0,0 +1,140 @@
+// Tests for dedup module
+
+use abyss::hash::dedup::PARTIAL_HASH_BLOCK;
+use abyss::hash::DedupEngine;
+use std::fs;
+use std::path::Path;
+
+/// File of `len` bytes whose content is derived from `seed`
+fn seeded_content(seed: u8, len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i as u8).wrapping_mul(31).wrapping_add(seed)).collect()
+}
+
+fn create_test_file(path: &Path, content: &[u8]) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+    fs::write(path, content).unwrap();
+}
+
+#[test]
+fn test_dedup_partial_hash_skips_non_duplicates() {
+    let test_dir = "test_dedup_partial_prepass";
+    let _ = fs::remove_dir_all(test_dir);
+    let dir = Path::new(test_dir);
+    let len = 4 * PARTIAL_HASH_BLOCK as usize;
+
+    // Many files of the same size that differ in their first block
+    for seed in 0..40u8 {
+        create_test_file(&dir.join(format!("unique_{:02}.bin", seed)), &seeded_content(seed, len));
+    }
+
+    // Two real duplicates, and a file that only differs from them in the middle
+    let original = seeded_content(200, len);
+    create_test_file(&dir.join("dup_a.bin"), &original);
+    create_test_file(&dir.join("nested/dup_b.bin"), &original);
+    let mut middle = original.clone();
+    middle[len / 2] ^= 0xff;
+    create_test_file(&dir.join("middle.bin"), &middle);
+
+    // A file with a unique size is never hashed at all
+    create_test_file(&dir.join("odd.bin"), &seeded_content(1, len + 1));
+
+    for parallel in [true, false] {
+        let engine = DedupEngine::new().with_parallel(parallel).with_threads(2);
+        let report = engine.find_duplicates(dir).unwrap();
+
+        assert_eq!(report.stats.files_scanned, 44);
+        assert_eq!(report.stats.files_failed, 0);
+        assert_eq!(report.stats.total_bytes, 44 * len as u64 + 1);
+        assert_eq!(report.stats.partial_hashed, 43);
+        // Only the two duplicates and the middle-differing file survive the pre-pass
+        assert_eq!(report.stats.full_hashed, 3);
+
+        assert_eq!(report.stats.duplicate_groups, 1);
+        assert_eq!(report.stats.duplicate_files, 2);
+        assert_eq!(report.stats.wasted_space, len as u64);
+        let names: Vec<_> = report.duplicate_groups[0].paths.iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["dup_a.bin", "dup_b.bin"]);
+    }
+
+    fs::remove_dir_all(test_dir).unwrap();
+}
+
+#[test]
+fn test_dedup_small_files_hashed_directly() {
+    let test_dir = "test_dedup_small_files";
+    let _ = fs::remove_dir_all(test_dir);
+    let dir = Path::new(test_dir);
+
+    create_test_file(&dir.join("a.txt"), b"same content");
+    create_test_file(&dir.join("b.txt"), b"same content");
+    create_test_file(&dir.join("c.txt"), b"diff content");
+    create_test_file(&dir.join("empty1"), b"");
+    create_test_file(&dir.join("empty2"), b"");
+
+    let report = DedupEngine::new().find_duplicates(dir).unwrap();
+
+    // Files no bigger than two blocks skip the partial pass
+    assert_eq!(report.stats.partial_hashed, 0);
+    assert_eq!(report.stats.full_hashed, 5);
+    assert_eq!(report.stats.duplicate_groups, 2);
+    assert_eq!(report.stats.duplicate_files, 4);
+    assert_eq!(report.stats.wasted_space, 12);
+
+    fs::remove_dir_all(test_dir).unwrap();
+}
+
+#[test]
+fn test_dedup_reports_progress_per_phase_and_can_be_cancelled() {
+    use abyss::hash::{DedupPhase, DedupProgress, HashUtilityError};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    let dir = tempfile::tempdir().unwrap();
+    let len = 4 * PARTIAL_HASH_BLOCK as usize;
+    create_test_file(&dir.path().join("a.bin"), &seeded_content(1, len));
+    create_test_file(&dir.path().join("copy/a.bin"), &seeded_content(1, len));
+    create_test_file(&dir.path().join("b.bin"), &seeded_content(2, len));
+    create_test_file(&dir.path().join("lonely.txt"), b"unique size");
+
+    for parallel in [false, true] {
+        let events: Arc<Mutex<Vec<DedupProgress>>> = Arc::default();
+        let sink = Arc::clone(&events);
+        let report = DedupEngine::new()
+            .with_parallel(parallel)
+            .with_summary(false)
+            .with_progress(false)
+            .with_progress_callback(move |p| sink.lock().unwrap().push(p))
+            .find_duplicates(dir.path())
+            .unwrap();
+        assert_eq!(report.stats.duplicate_groups, 1);
+
+        let events = events.lock().unwrap();
+        let phase = |phase| events.iter().filter(|p| p.phase == phase).collect::<Vec<_>>();
+        // Every file is sized, the three same-size files sampled, the two matching ones hashed
+        assert_eq!(phase(DedupPhase::Sizes).len(), 4);
+        assert_eq!(phase(DedupPhase::Partial).len(), 3);
+        let full = phase(DedupPhase::Full);
+        assert_eq!(full.len(), 2);
+        assert!(full.iter().all(|p| p.total_files == 2));
+        assert_eq!(full.iter().map(|p| p.files_scanned).max(), Some(2));
+        assert_eq!(full.iter().map(|p| p.duplicate_groups_found).max(), Some(1));
+        // Both sampling passes and the full hashes count towards the bytes read
+        let bytes = events.iter().map(|p| p.bytes_hashed).max().unwrap();
+        assert_eq!(bytes, 3 * 2 * PARTIAL_HASH_BLOCK + 2 * len as u64);
+    }
+
+    // Cancelling from the callback stops the search with a distinct error
+    let cancel = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&cancel);
+    let result = DedupEngine::new()
+        .with_summary(false)
+        .with_progress(false)
+        .with_cancel(Arc::clone(&cancel))
+        .with_progress_callback(move |_| flag.store(true, Ordering::Relaxed))
+        .find_duplicates(dir.path());
+    assert!(matches!(result, Err(HashUtilityError::Cancelled { .. })), "{:?}", result.map(|r| r.stats));
+}
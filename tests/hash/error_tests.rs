@@ -65,6 +65,25 @@ fn test_io_error_with_path() {
     assert!(message.contains("output.txt"));
 }
 
+#[test]
+fn test_too_many_open_files_suggests_higher_limit() {
+    // EMFILE (ERROR_TOO_MANY_OPEN_FILES on Windows)
+    let code = if cfg!(windows) { 4 } else { 24 };
+    let error = HashUtilityError::from_io_error(
+        io::Error::from_raw_os_error(code),
+        "reading",
+        Some(PathBuf::from("big.iso")),
+    );
+    assert!(error.is_too_many_open_files());
+    let message = format!("{}", error);
+    assert!(message.contains("ulimit -n"));
+    assert!(message.contains("--max-open-files"));
+
+    let other = HashUtilityError::from_io_error(io::Error::other("disk full"), "writing", None);
+    assert!(!other.is_too_many_open_files());
+    assert!(format!("{}", other).contains("disk space"));
+}
+
 #[test]
 fn test_io_error_without_path() {
     let io_err = io::Error::new(io::ErrorKind::Other, "unknown error");
@@ -147,6 +166,18 @@ fn test_database_parse_error_display() {
     assert!(message.contains("invalid format"));
 }
 
+#[test]
+fn test_database_algorithm_error_display() {
+    let error = HashUtilityError::DatabaseAlgorithm {
+        path: PathBuf::from("db.txt"),
+        reason: "entries mix md5, sha256".to_string(),
+    };
+    let message = format!("{}", error);
+    assert!(message.contains("db.txt"));
+    assert!(message.contains("entries mix md5, sha256"));
+    assert!(message.contains("--algorithm"));
+}
+
 #[test]
 fn test_hash_computation_failed_display() {
     let error = HashUtilityError::HashComputationFailed {
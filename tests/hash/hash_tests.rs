@@ -356,3 +356,286 @@ fn test_compute_multiple_hashes_stdin_structure() {
     assert_eq!(computer.buffer_size, 1024 * 1024);
 }
 
+
+#[test]
+fn test_buffered_only_hashing_matches_mmap() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data.bin");
+    // Several buffers' worth, with a partial last buffer
+    let data: Vec<u8> = (0..(3 * 4096 + 123)).map(|i| (i % 251) as u8).collect();
+    fs::write(&path, &data).unwrap();
+    let empty = dir.path().join("empty.bin");
+    fs::write(&empty, b"").unwrap();
+
+    let mapped = HashComputer::with_buffer_size(4096);
+    let buffered = HashComputer::with_buffer_size(4096).with_mmap(false);
+    assert!(mapped.use_mmap);
+    assert!(!buffered.use_mmap);
+
+    for algorithm in ["sha256", "blake3", "xxh3"] {
+        for file in [&path, &empty] {
+            assert_eq!(
+                buffered.compute_hash(file, algorithm).unwrap().hash,
+                mapped.compute_hash(file, algorithm).unwrap().hash,
+            );
+        }
+    }
+
+    let algorithms = vec!["md5".to_string(), "sha1".to_string()];
+    let from_buffered: Vec<_> = buffered.compute_multiple_hashes(&path, &algorithms).unwrap().into_iter().map(|r| r.hash).collect();
+    let from_mapped: Vec<_> = mapped.compute_multiple_hashes(&path, &algorithms).unwrap().into_iter().map(|r| r.hash).collect();
+    assert_eq!(from_buffered, from_mapped);
+}
+
+#[test]
+fn test_scan_and_verify_without_mmap() {
+    use abyss::hash::{ScanEngine, VerifyEngine};
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a.txt"), b"alpha").unwrap();
+    fs::write(dir.path().join("b.txt"), b"beta").unwrap();
+    let output = dir.path().join("hashes.txt");
+
+    for parallel in [false, true] {
+        ScanEngine::new().with_parallel(parallel)
+            .with_mmap(false)
+            .with_progress(false)
+            .with_summary(false)
+            .scan_directory(dir.path(), "sha256", &output)
+            .unwrap();
+        let report = VerifyEngine::new().with_parallel(parallel)
+            .with_mmap(false)
+            .with_progress(false)
+            .verify(&output, dir.path())
+            .unwrap();
+        assert_eq!(report.matches, 2);
+        assert!(report.mismatches.is_empty());
+    }
+}
+
+#[test]
+fn test_thread_settings_give_identical_results() {
+    use abyss::hash::{DedupEngine, ScanEngine, TreeHashEngine, VerifyEngine};
+
+    // 40 files over 4 directories, every content shared by 4 of them
+    let dir = tempfile::tempdir().unwrap();
+    for i in 0..40 {
+        let sub = dir.path().join(format!("d{}", i % 4));
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(sub.join(format!("f{:02}.txt", i)), format!("content {}", i % 10)).unwrap();
+    }
+    // A stale database: one wrong hash and one file that is gone
+    let out = tempfile::tempdir().unwrap();
+    let stale = out.path().join("stale.txt");
+    fs::write(
+        &stale,
+        "0000000000000000000000000000000000000000000000000000000000000000  sha256  normal  d0/f00.txt\n\
+         1111111111111111111111111111111111111111111111111111111111111111  sha256  normal  gone.txt\n",
+    )
+    .unwrap();
+
+    let run = |parallel: bool, threads: usize| {
+        let database = out.path().join(format!("hashes-{}-{}.txt", parallel, threads));
+        ScanEngine::new()
+            .with_parallel(parallel)
+            .with_threads(threads)
+            .with_sorted_output(true)
+            .with_progress(false)
+            .with_summary(false)
+            .scan_directory(dir.path(), "sha256", &database)
+            .unwrap();
+        let verify = VerifyEngine::new()
+            .with_parallel(parallel)
+            .with_threads(threads)
+            .with_progress(false)
+            .verify(&stale, dir.path())
+            .unwrap();
+        let tree = TreeHashEngine::new()
+            .with_parallel(parallel)
+            .with_threads(threads)
+            .hash_tree(dir.path(), "sha256")
+            .unwrap();
+        let dedup = DedupEngine::new()
+            .with_parallel(parallel)
+            .with_threads(threads)
+            .with_progress(false)
+            .with_summary(false)
+            .find_duplicates(dir.path())
+            .unwrap();
+
+        let mut mismatches: Vec<_> = verify.mismatches.iter().map(|m| (m.path.clone(), m.actual.clone())).collect();
+        let mut missing = verify.missing_files.clone();
+        let mut new_files = verify.new_files.clone();
+        let mut groups: Vec<_> = dedup.duplicate_groups.iter().map(|g| (g.hash.clone(), g.paths.clone())).collect();
+        mismatches.sort();
+        missing.sort();
+        new_files.sort();
+        groups.sort();
+        (fs::read_to_string(&database).unwrap(), verify.matches, mismatches, missing, new_files, tree.hash, groups)
+    };
+
+    let sequential = run(false, 0);
+    assert_eq!(sequential.0.lines().count(), 40);
+    assert_eq!(sequential.2.len(), 1);
+    assert_eq!(sequential.3.len(), 1);
+    assert_eq!(sequential.4.len(), 39);
+    assert_eq!(sequential.6.len(), 10);
+    for (parallel, threads) in [(true, 0), (true, 1), (true, 4)] {
+        assert_eq!(run(parallel, threads), sequential, "parallel: {}, threads: {}", parallel, threads);
+    }
+}
+
+#[test]
+fn test_blake3_hashes_on_the_engine_pool() {
+    use abyss::hash::{ScanEngine, VerifyEngine};
+    use std::collections::BTreeSet;
+    use std::sync::{Arc, Mutex};
+
+    // blake3 splits large files over the rayon pool it runs on, so that has to be the
+    // engine's pool: a single thread without parallelism, `threads` threads with a count
+    let dir = tempfile::tempdir().unwrap();
+    for i in 0..3u8 {
+        fs::write(dir.path().join(format!("big{}.bin", i)), vec![i; 2 * 1024 * 1024]).unwrap();
+    }
+    let out = tempfile::tempdir().unwrap();
+    let database = out.path().join("hashes.txt");
+
+    let seen = Arc::new(Mutex::new(BTreeSet::new()));
+    let record = Arc::clone(&seen);
+    ScanEngine::new()
+        .with_parallel(false)
+        .with_progress(false)
+        .with_summary(false)
+        .with_progress_callback(move |_| {
+            record.lock().unwrap().insert(rayon::current_num_threads());
+        })
+        .scan_directory(dir.path(), "blake3", &database)
+        .unwrap();
+    assert_eq!(*seen.lock().unwrap(), BTreeSet::from([1]));
+
+    for (parallel, threads, expected) in [(false, 0, 1), (false, 4, 1), (true, 1, 1), (true, 3, 3)] {
+        let seen = Arc::new(Mutex::new(BTreeSet::new()));
+        let record = Arc::clone(&seen);
+        let report = VerifyEngine::new()
+            .with_parallel(parallel)
+            .with_threads(threads)
+            .with_progress(false)
+            .with_progress_callback(move |_| {
+                record.lock().unwrap().insert(rayon::current_num_threads());
+            })
+            .verify(&database, dir.path())
+            .unwrap();
+        assert_eq!(report.matches, 3);
+        assert_eq!(*seen.lock().unwrap(), BTreeSet::from([expected]), "parallel {} threads {}", parallel, threads);
+    }
+}
+
+/// Official BLAKE3 test vectors: input bytes are `i % 251`, truncated to the default 32-byte output
+const BLAKE3_TEST_KEY: &[u8; 32] = b"whats the Elvish word for friend";
+const BLAKE3_TEST_CONTEXT: &str = "BLAKE3 2019-12-27 16:29:52 test vectors context";
+const BLAKE3_VECTORS: &[(usize, &str, &str)] = &[
+    (
+        0,
+        "92b2b75604ed3c761f9d6f62392c8a9227ad0ea3f09573e783f1498a4ed60d26",
+        "2cc39783c223154fea8dfb7c1b1660f2ac2dcbd1c1de8277b0b0dd39b7e50d7d",
+    ),
+    (
+        1,
+        "6d7878dfff2f485635d39013278ae14f1454b8c0a3a2d34bc1ab38228a80c95b",
+        "b3e2e340a117a499c6cf2398a19ee0d29cca2bb7404c73063382693bf66cb06c",
+    ),
+    (
+        1023,
+        "c951ecdf03288d0fcc96ee3413563d8a6d3589547f2c2fb36d9786470f1b9d6e",
+        "74a16c1c3d44368a86e1ca6df64be6a2f64cce8f09220787450722d85725dea5",
+    ),
+    (
+        1024,
+        "75c46f6f3d9eb4f55ecaaee480db732e6c2105546f1e675003687c31719c7ba4",
+        "7356cd7720d5b66b6d0697eb3177d9f8d73a4a5c5e968896eb6a689684302706",
+    ),
+    (
+        1025,
+        "357dc55de0c7e382c900fd6e320acc04146be01db6a8ce7210b7189bd664ea69",
+        "effaa245f065fbf82ac186839a249707c3bddf6d3fdda22d1b95a3c970379bcb",
+    ),
+];
+
+#[test]
+fn test_blake3_keyed_and_derive_test_vectors() {
+    use abyss::hash::HashKey;
+
+    let dir = tempfile::tempdir().unwrap();
+    let key = HashKey::new().with_key(*BLAKE3_TEST_KEY).with_context(BLAKE3_TEST_CONTEXT);
+
+    for use_mmap in [false, true] {
+        let computer = HashComputer::new().with_mmap(use_mmap).with_key(key.clone());
+        for &(len, keyed, derived) in BLAKE3_VECTORS {
+            let path = dir.path().join(format!("input-{}", len));
+            fs::write(&path, (0..len).map(|i| (i % 251) as u8).collect::<Vec<u8>>()).unwrap();
+
+            assert_eq!(computer.compute_hash(&path, "blake3-keyed").unwrap().hash, keyed, "keyed, {} bytes", len);
+            assert_eq!(computer.compute_hash(&path, "blake3-derive").unwrap().hash, derived, "derive, {} bytes", len);
+        }
+    }
+}
+
+#[test]
+fn test_blake3_keyed_needs_key_material() {
+    use abyss::hash::HashKey;
+
+    assert!(matches!(
+        HashRegistry::get_hasher("blake3-keyed"),
+        Err(HashUtilityError::MissingRequiredArgument { .. })
+    ));
+    assert!(matches!(
+        HashRegistry::get_keyed_hasher("blake3-derive", &HashKey::new().with_key([7; 32])),
+        Err(HashUtilityError::MissingRequiredArgument { .. })
+    ));
+
+    let hex = "00112233445566778899aabbccddeeff00112233445566778899aabbccddeeff";
+    assert!(HashKey::new().with_key_hex(hex).unwrap().has_key());
+    for bad in ["abc", &hex[1..], &hex.replace('0', "g")] {
+        let message = HashKey::new().with_key_hex(bad).unwrap_err().to_string();
+        assert!(!message.contains(bad), "key echoed in {:?}", message);
+    }
+}
+
+#[test]
+fn test_blake3_keyed_key_never_appears_in_output() {
+    use abyss::hash::{HashKey, ScanEngine, VerifyEngine};
+
+    let hex = "8f3a5c7e9b1d2f4a6c8e0b2d4f6a8c0e1f3b5d7a9c2e4f6b8d0a1c3e5f7b9d2a";
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a.txt"), b"alpha").unwrap();
+    let key_file = dir.path().join("key");
+    fs::write(&key_file, format!("{}\n", hex)).unwrap();
+    let output = dir.path().join("hashes.txt");
+
+    let key = HashKey::new().with_key_file(&key_file).unwrap();
+    assert!(!format!("{:?}", key).contains(hex));
+
+    ScanEngine::new()
+        .with_progress(false)
+        .with_summary(false)
+        .with_key(key.clone())
+        .scan_directory(dir.path(), "blake3-keyed", &output)
+        .unwrap();
+    let database = fs::read_to_string(&output).unwrap();
+    assert!(database.contains("blake3-keyed"));
+    assert!(!database.contains(hex));
+
+    // Same key verifies; another key sees every file as changed
+    let verify = |key: HashKey| {
+        VerifyEngine::new()
+            .with_progress(false)
+            .with_ignored_paths([&key_file])
+            .with_key(key)
+            .verify(&output, dir.path())
+            .unwrap()
+    };
+    assert_eq!(verify(key).matches, 1);
+    let forged = verify(HashKey::new().with_key([1; 32]));
+    assert_eq!(forged.matches, 0);
+    assert_eq!(forged.mismatches.len(), 1);
+}
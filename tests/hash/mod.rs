@@ -8,6 +8,9 @@ mod scan_tests;
 mod verify_tests;
 mod compare_tests;
 mod database_tests;
+mod dedup_tests;
+mod check_tests;
+mod tree_tests;
 mod error_tests;
 mod international_tests;
 
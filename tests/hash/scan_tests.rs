@@ -127,6 +127,30 @@ fn test_scan_empty_directory() {
     fs::remove_dir_all(test_dir).unwrap();
 }
 
+#[test]
+fn test_scan_leaves_out_database_inside_scanned_directory() {
+    // The database is created before the walk starts, so a walker reaching it first
+    // still recognizes it instead of hashing the half-written output
+    for parallel in [true, false] {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..50 {
+            fs::write(dir.path().join(format!("f{:02}.txt", i)), format!("content {}", i)).unwrap();
+        }
+        let output = dir.path().join("aaa-hashes.txt");
+        let stats = ScanEngine::new()
+            .with_parallel(parallel)
+            .with_progress(false)
+            .with_summary(false)
+            .scan_directory(dir.path(), "sha256", &output)
+            .unwrap();
+
+        assert_eq!(stats.files_processed, 50, "parallel {}", parallel);
+        let database = fs::read_to_string(&output).unwrap();
+        assert_eq!(database.lines().count(), 50);
+        assert!(!database.contains("aaa-hashes.txt"), "parallel {}", parallel);
+    }
+}
+
 #[test]
 fn test_scan_nonexistent_directory() {
     let engine = ScanEngine::new();
@@ -151,7 +175,7 @@ fn test_scan_parallel_mode() {
     fs::write(format!("{}/file4.txt", test_dir), b"content4").unwrap();
     
     // Scan the directory with parallel mode enabled
-    let engine = ScanEngine::with_parallel(true);
+    let engine = ScanEngine::new().with_parallel(true);
     let output = format!("{}/hashes_parallel.txt", test_dir);
     let stats = engine.scan_directory(
         Path::new(test_dir),
@@ -214,7 +238,7 @@ fn test_scan_parallel_vs_sequential() {
     fs::write(format!("{}/file3.txt", test_dir_par), b"test data 3").unwrap();
     
     // Scan sequentially
-    let engine_seq = ScanEngine::with_parallel(false);
+    let engine_seq = ScanEngine::new().with_parallel(false);
     let output_seq = format!("{}/output_seq.txt", test_dir_seq);
     let stats_seq = engine_seq.scan_directory(
         Path::new(test_dir_seq),
@@ -223,7 +247,7 @@ fn test_scan_parallel_vs_sequential() {
     ).unwrap();
     
     // Scan in parallel
-    let engine_par = ScanEngine::with_parallel(true);
+    let engine_par = ScanEngine::new().with_parallel(true);
     let output_par = format!("{}/output_par.txt", test_dir_par);
     let stats_par = engine_par.scan_directory(
         Path::new(test_dir_par),
@@ -240,3 +264,342 @@ fn test_scan_parallel_vs_sequential() {
     fs::remove_dir_all(test_dir_par).unwrap();
 }
 
+
+#[test]
+fn test_scan_without_progress_bar() {
+    // Progress settings must not affect the scan result or summary stats
+    let test_dir = "test_scan_no_progress";
+    fs::create_dir_all(test_dir).unwrap();
+    
+    fs::write(format!("{}/a.txt", test_dir), b"alpha").unwrap();
+    fs::write(format!("{}/b.txt", test_dir), b"beta").unwrap();
+    
+    let output = format!("{}/hashes.txt", test_dir);
+    for engine in [
+        ScanEngine::new().with_progress(false),
+        ScanEngine::new().with_parallel(true)
+            .with_progress(true)
+            .with_progress_interval(std::time::Duration::from_secs(1)),
+    ] {
+        let stats = engine.scan_directory(
+            Path::new(test_dir),
+            "sha256",
+            Path::new(&output),
+        ).unwrap();
+        
+        assert_eq!(stats.files_processed, 2);
+        assert_eq!(stats.files_failed, 0);
+    }
+    
+    // Cleanup
+    fs::remove_dir_all(test_dir).unwrap();
+}
+
+#[test]
+fn test_fast_mode_scan_verify_round_trip() {
+    use abyss::hash::{VerifyEngine, FAST_MODE_THRESHOLD};
+    
+    let test_dir = "test_scan_fast_round_trip";
+    fs::create_dir_all(test_dir).unwrap();
+    
+    // Sparse file over the threshold, sampled as first/middle/last 100MB
+    let big = format!("{}/big.bin", test_dir);
+    let file = fs::File::create(&big).unwrap();
+    file.set_len(FAST_MODE_THRESHOLD + 100 * 1024 * 1024).unwrap();
+    drop(file);
+    fs::write(format!("{}/small.txt", test_dir), b"small").unwrap();
+    
+    let output = format!("{}/hashes.txt", test_dir);
+    let stats = ScanEngine::new()
+        .with_fast_mode(true)
+        .with_progress(false)
+        .scan_directory(Path::new(test_dir), "xxh3", Path::new(&output))
+        .unwrap();
+    assert_eq!(stats.files_processed, 2);
+    assert_eq!(stats.large_files, 1);
+    
+    // Every entry is marked as a fast-mode hash
+    let content = fs::read_to_string(&output).unwrap();
+    assert_eq!(content.lines().filter(|l| l.contains("  xxh3  fast  ")).count(), 2);
+    
+    // A change between the sampled regions goes unnoticed, so verify must sample too
+    {
+        use std::io::{Seek, SeekFrom, Write};
+        let mut file = fs::OpenOptions::new().write(true).open(&big).unwrap();
+        file.seek(SeekFrom::Start(120 * 1024 * 1024)).unwrap();
+        file.write_all(b"unsampled").unwrap();
+    }
+    let report = VerifyEngine::new().verify(Path::new(&output), Path::new(test_dir)).unwrap();
+    assert_eq!(report.matches, 2);
+    assert!(report.mismatches.is_empty());
+    
+    // A change inside the first sample is detected
+    {
+        use std::io::Write;
+        let mut file = fs::OpenOptions::new().write(true).open(&big).unwrap();
+        file.write_all(b"sampled").unwrap();
+    }
+    let report = VerifyEngine::new().verify(Path::new(&output), Path::new(test_dir)).unwrap();
+    assert_eq!(report.mismatches.len(), 1);
+    
+    // Cleanup
+    fs::remove_dir_all(test_dir).unwrap();
+}
+
+#[test]
+fn test_scan_json_format_round_trips_through_verify() {
+    use abyss::hash::{DatabaseFormat, VerifyEngine};
+
+    let test_dir = "test_scan_json_round_trip";
+    fs::create_dir_all(format!("{}/nested", test_dir)).unwrap();
+    fs::write(format!("{}/a.txt", test_dir), b"alpha").unwrap();
+    fs::write(format!("{}/nested/b.txt", test_dir), b"beta").unwrap();
+
+    let output = format!("{}/hashes.jsonl", test_dir);
+    let stats = ScanEngine::new()
+        .with_format(DatabaseFormat::Json)
+        .scan_directory(Path::new(test_dir), "sha256", Path::new(&output))
+        .unwrap();
+    assert_eq!(stats.files_processed, 2);
+
+    let content = fs::read_to_string(&output).unwrap();
+    for line in content.lines() {
+        let value: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(value["algorithm"], "sha256");
+        assert!(value["hash"].is_string());
+        assert!(value["size"].is_u64());
+    }
+
+    let report = VerifyEngine::new().verify(Path::new(&output), Path::new(test_dir)).unwrap();
+    assert_eq!(report.matches, 2);
+    assert!(report.mismatches.is_empty());
+    assert!(report.missing_files.is_empty());
+
+    // A changed file is reported as a mismatch
+    fs::write(format!("{}/a.txt", test_dir), b"changed").unwrap();
+    let report = VerifyEngine::new().verify(Path::new(&output), Path::new(test_dir)).unwrap();
+    assert_eq!(report.matches, 1);
+    assert_eq!(report.mismatches.len(), 1);
+
+    fs::remove_dir_all(test_dir).unwrap();
+}
+
+#[test]
+fn test_parallel_scan_streams_many_files_with_bounded_buffering() {
+    use abyss::hash::WRITE_QUEUE_CAPACITY;
+    use std::collections::BTreeSet;
+
+    let dir = tempfile::tempdir().unwrap();
+    let file_count = WRITE_QUEUE_CAPACITY * 5;
+    for i in 0..file_count {
+        let sub = dir.path().join(format!("d{}", i % 16));
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(sub.join(format!("f{:05}.txt", i)), i.to_string()).unwrap();
+    }
+    let out = tempfile::tempdir().unwrap();
+    let lines = |path: &Path| -> Vec<String> {
+        fs::read_to_string(path).unwrap().lines().map(str::to_string).collect()
+    };
+
+    // Streaming (default): every file written once, only the queue held in memory
+    let streamed = out.path().join("streamed.txt");
+    let stats = ScanEngine::new().with_parallel(true)
+        .with_progress(false)
+        .scan_directory(dir.path(), "xxh3", &streamed)
+        .unwrap();
+    assert_eq!(stats.files_processed, file_count);
+    assert!(stats.peak_buffered_entries <= WRITE_QUEUE_CAPACITY, "{}", stats.peak_buffered_entries);
+
+    // Same entries as a sequential scan
+    let sequential = out.path().join("sequential.txt");
+    ScanEngine::new()
+        .with_progress(false)
+        .scan_directory(dir.path(), "xxh3", &sequential)
+        .unwrap();
+    let streamed_lines = lines(&streamed);
+    assert_eq!(streamed_lines.len(), file_count);
+    assert_eq!(
+        streamed_lines.iter().collect::<BTreeSet<_>>(),
+        lines(&sequential).iter().collect::<BTreeSet<_>>()
+    );
+
+    // Sorted output buffers everything, then writes in path order
+    let sorted = out.path().join("sorted.txt");
+    let stats = ScanEngine::new().with_parallel(true)
+        .with_progress(false)
+        .with_sorted_output(true)
+        .scan_directory(dir.path(), "xxh3", &sorted)
+        .unwrap();
+    assert_eq!(stats.peak_buffered_entries, file_count);
+    let paths: Vec<String> = lines(&sorted)
+        .iter()
+        .map(|line| line.rsplit("  ").next().unwrap().to_string())
+        .collect();
+    let mut expected = paths.clone();
+    expected.sort();
+    assert_eq!(paths, expected);
+    assert_eq!(paths.len(), file_count);
+}
+
+#[test]
+fn test_include_dirs_records_empty_directory_and_flags_its_removal() {
+    use abyss::hash::{DatabaseFormat, DatabaseHandler, VerifyEngine};
+
+    for (parallel, format) in [(false, DatabaseFormat::Standard), (true, DatabaseFormat::Json), (true, DatabaseFormat::Hashdeep)] {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("empty")).unwrap();
+        fs::create_dir_all(dir.path().join("docs/nested")).unwrap();
+        fs::write(dir.path().join("docs/readme.md"), b"read me").unwrap();
+        let out = tempfile::tempdir().unwrap();
+        let output = out.path().join("hashes");
+
+        let stats = ScanEngine::new().with_parallel(parallel)
+            .with_progress(false)
+            .with_summary(false)
+            .with_format(format)
+            .with_include_dirs(true)
+            .scan_directory(dir.path(), "sha256", &output)
+            .unwrap();
+        assert_eq!(stats.files_processed, 1);
+        assert_eq!(stats.directories, 3);
+
+        let database = DatabaseHandler::read_database(&output).unwrap();
+        assert_eq!(database.len(), 4);
+        assert!(database[Path::new("empty")].is_directory());
+        assert!(database[Path::new("docs/nested")].is_directory());
+        assert!(!database[Path::new("docs/readme.md")].is_directory());
+
+        // Everything still there: directories count as matches
+        let report = VerifyEngine::new().with_parallel(parallel).with_progress(false).verify(&output, dir.path()).unwrap();
+        assert_eq!(report.matches, 4);
+        assert!(report.missing_files.is_empty() && report.new_files.is_empty(), "{:?}", report);
+
+        // The empty directory goes missing, a new one appears
+        fs::remove_dir(dir.path().join("empty")).unwrap();
+        fs::create_dir(dir.path().join("added")).unwrap();
+        let report = VerifyEngine::new().with_parallel(parallel).with_progress(false).verify(&output, dir.path()).unwrap();
+        assert_eq!(report.matches, 3);
+        assert_eq!(report.missing_files, vec![dir.path().canonicalize().unwrap().join("empty")]);
+        assert_eq!(report.new_files, vec![dir.path().join("added").canonicalize().unwrap()]);
+    }
+}
+
+#[test]
+fn test_scan_without_include_dirs_ignores_directories() {
+    use abyss::hash::VerifyEngine;
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("empty")).unwrap();
+    fs::write(dir.path().join("a.txt"), b"alpha").unwrap();
+    let output = dir.path().join("hashes.txt");
+    let stats = ScanEngine::new().with_progress(false).with_summary(false).scan_directory(dir.path(), "sha256", &output).unwrap();
+    assert_eq!(stats.directories, 0);
+    assert_eq!(fs::read_to_string(&output).unwrap().lines().count(), 1);
+
+    // No directory entries in the database, so directories are never reported
+    fs::remove_dir(dir.path().join("empty")).unwrap();
+    fs::create_dir(dir.path().join("added")).unwrap();
+    let report = VerifyEngine::new().with_progress(false).verify(&output, dir.path()).unwrap();
+    assert_eq!(report.matches, 1);
+    assert!(report.missing_files.is_empty() && report.new_files.is_empty());
+}
+
+#[test]
+fn test_extension_filters_limit_the_database() {
+    use abyss::hash::DatabaseHandler;
+    use std::path::PathBuf;
+
+    for parallel in [false, true] {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("images/old")).unwrap();
+        for name in ["a.iso", "images/b.IMG", "images/old/c.iso", "notes.txt", "images/readme.md", "noext", ".iso", "backup.tar.gz"] {
+            fs::write(dir.path().join(name), name.as_bytes()).unwrap();
+        }
+        let out = tempfile::tempdir().unwrap();
+        let output = out.path().join("hashes.txt");
+
+        let scan = |engine: ScanEngine| {
+            engine.with_progress(false).with_summary(false).scan_directory(dir.path(), "sha256", &output).unwrap();
+            let mut paths: Vec<_> = DatabaseHandler::read_database(&output).unwrap().into_keys().collect();
+            paths.sort();
+            paths
+        };
+
+        // Only the wanted types are hashed, whatever their case or a leading dot
+        let paths = scan(ScanEngine::new().with_parallel(parallel).with_include_extensions(["iso", ".img"]));
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("a.iso"), PathBuf::from("images/b.IMG"), PathBuf::from("images/old/c.iso")],
+            "parallel: {}",
+            parallel
+        );
+
+        // Exclusions win, and multi-part extensions match
+        let paths = scan(
+            ScanEngine::new().with_parallel(parallel)
+                .with_include_extensions(["iso", "tar.gz"])
+                .with_exclude_extensions(["ISO"]),
+        );
+        assert_eq!(paths, vec![PathBuf::from("backup.tar.gz")]);
+
+        let paths = scan(ScanEngine::new().with_parallel(parallel).with_exclude_extensions(["txt", "md", "iso", "img", "gz"]));
+        assert_eq!(paths, vec![PathBuf::from(".iso"), PathBuf::from("noext")]);
+    }
+}
+
+#[test]
+fn test_open_file_limit_bounds_concurrent_holders() {
+    use abyss::hash::OpenFileLimit;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    let limit = OpenFileLimit::new(3);
+    let holding = AtomicUsize::new(0);
+    let peak = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for _ in 0..16 {
+            scope.spawn(|| {
+                for _ in 0..5 {
+                    let _permit = limit.acquire();
+                    let now = holding.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(1));
+                    holding.fetch_sub(1, Ordering::SeqCst);
+                }
+            });
+        }
+    });
+
+    assert!(peak.into_inner() <= 3);
+    assert_eq!(limit.in_use(), 0);
+    // A zero limit would deadlock, so it allows one
+    assert_eq!(OpenFileLimit::new(0).max(), 1);
+}
+
+#[test]
+fn test_parallel_scan_respects_max_open_files() {
+    // blake3 hashes multi-MB files on rayon threads too, which must not deadlock with
+    // workers waiting for an open-file slot
+    let dir = tempfile::tempdir().unwrap();
+    let file_count = 24;
+    for i in 0..file_count {
+        fs::write(dir.path().join(format!("f{:03}.bin", i)), vec![i as u8; 3 * 1024 * 1024 + i]).unwrap();
+    }
+    let out = tempfile::tempdir().unwrap();
+    let output = out.path().join("hashes.txt");
+
+    let stats = ScanEngine::new().with_parallel(true)
+        .with_threads(4)
+        .with_progress(false)
+        .with_summary(false)
+        .with_max_open_files(2)
+        .scan_directory(dir.path(), "blake3", &output)
+        .unwrap();
+
+    assert_eq!(stats.files_processed, file_count);
+    assert_eq!(stats.files_failed, 0);
+    assert_eq!(stats.too_many_open_files, 0);
+    assert!((1..=2).contains(&stats.peak_open_files), "{}", stats.peak_open_files);
+    assert_eq!(fs::read_to_string(&output).unwrap().lines().count(), file_count);
+}
@@ -0,0 +1,77 @@
+// Tests for directory tree hashes
+
+use abyss::hash::TreeHashEngine;
+use std::fs;
+use std::path::Path;
+
+fn build_tree(root: &Path, files: &[(&str, &str)]) {
+    for (path, content) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+}
+
+const FILES: &[(&str, &str)] = &[
+    ("a.txt", "alpha"),
+    ("docs/readme.md", "read me"),
+    ("docs/nested/deep.bin", "deep"),
+    ("z.txt", "omega"),
+];
+
+#[test]
+fn test_tree_hash_is_order_independent() {
+    let first = tempfile::tempdir().unwrap();
+    let second = tempfile::tempdir().unwrap();
+    build_tree(first.path(), FILES);
+    // Same content, created in reverse order
+    let reversed: Vec<_> = FILES.iter().rev().cloned().collect();
+    build_tree(second.path(), &reversed);
+
+    let sequential = TreeHashEngine::new().with_parallel(false).hash_tree(first.path(), "blake3").unwrap();
+    let parallel = TreeHashEngine::new().with_parallel(true).hash_tree(second.path(), "blake3").unwrap();
+
+    assert_eq!(sequential.hash, parallel.hash);
+    assert_eq!(sequential.files, 4);
+    assert_eq!(sequential.total_bytes, 5 + 7 + 4 + 5);
+    assert!(sequential.to_plain_text().starts_with(&sequential.hash));
+}
+
+#[test]
+fn test_tree_hash_changes_with_any_file() {
+    let dir = tempfile::tempdir().unwrap();
+    build_tree(dir.path(), FILES);
+    let engine = TreeHashEngine::new();
+    let original = engine.hash_tree(dir.path(), "sha256").unwrap().hash;
+
+    // Content change
+    fs::write(dir.path().join("docs/nested/deep.bin"), "Deep").unwrap();
+    let changed = engine.hash_tree(dir.path(), "sha256").unwrap().hash;
+    assert_ne!(changed, original);
+    fs::write(dir.path().join("docs/nested/deep.bin"), "deep").unwrap();
+    assert_eq!(engine.hash_tree(dir.path(), "sha256").unwrap().hash, original);
+
+    // Rename with the same content
+    fs::rename(dir.path().join("z.txt"), dir.path().join("y.txt")).unwrap();
+    assert_ne!(engine.hash_tree(dir.path(), "sha256").unwrap().hash, original);
+    fs::rename(dir.path().join("y.txt"), dir.path().join("z.txt")).unwrap();
+
+    // Added file
+    fs::write(dir.path().join("new.txt"), "").unwrap();
+    assert_ne!(engine.hash_tree(dir.path(), "sha256").unwrap().hash, original);
+    fs::remove_file(dir.path().join("new.txt")).unwrap();
+    assert_eq!(engine.hash_tree(dir.path(), "sha256").unwrap().hash, original);
+}
+
+#[test]
+fn test_tree_hash_rejects_bad_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let engine = TreeHashEngine::new();
+    assert!(engine.hash_tree(dir.path(), "nope").is_err());
+    assert!(engine.hash_tree(&dir.path().join("missing"), "blake3").is_err());
+
+    // An empty directory still has a (stable) tree hash
+    let empty = engine.hash_tree(dir.path(), "blake3").unwrap();
+    assert_eq!(empty.files, 0);
+    assert_eq!(empty.hash, engine.hash_tree(dir.path(), "blake3").unwrap().hash);
+}
@@ -62,6 +62,75 @@ fn test_expand_pattern_with_matches() {
     }
 }
 
+#[test]
+fn test_name_pattern() {
+    use abyss::hash::wildcard::name_pattern;
+
+    let pattern = name_pattern("*.log").unwrap();
+    assert!(pattern.is_match("build.log"));
+    assert!(!pattern.is_match("build.log.gz"));
+
+    let exact = name_pattern(" Cargo.toml ").unwrap();
+    assert!(exact.is_match("Cargo.toml"));
+    assert!(!exact.is_match("Cargo.lock"));
+
+    assert!(name_pattern("[unclosed").is_err());
+}
+
+#[test]
+fn test_name_pattern_braces_and_classes() {
+    use abyss::hash::wildcard::{name_pattern, name_pattern_ignore_case};
+
+    let braces = name_pattern("*.{tmp,cache}").unwrap();
+    assert!(braces.is_match("a.tmp"));
+    assert!(braces.is_match("b.cache"));
+    assert!(!braces.is_match("c.log"));
+
+    let class = name_pattern("test[0-9].log").unwrap();
+    assert!(class.is_match("test4.log"));
+    assert!(!class.is_match("testx.log"));
+    assert!(!class.is_match("test10.log"));
+
+    let negated = name_pattern("[!.]*").unwrap();
+    assert!(negated.is_match("visible"));
+    assert!(!negated.is_match(".hidden"));
+
+    assert!(!name_pattern("*.LOG").unwrap().is_match("build.log"));
+    assert!(name_pattern_ignore_case("*.LOG").unwrap().is_match("build.log"));
+}
+
+#[test]
+fn test_expand_pattern_braces() {
+    use abyss::hash::wildcard::expand_pattern;
+
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["a.md", "b.txt", "c.log", "notes.md"] {
+        fs::write(dir.path().join(name), b"test").unwrap();
+    }
+
+    let pattern = dir.path().join("*.{md,txt}").to_string_lossy().to_string();
+    let result = expand_pattern(&pattern).unwrap();
+    let names: Vec<_> = result.iter().map(|p| p.file_name().unwrap().to_string_lossy().to_string()).collect();
+    assert_eq!(names, vec!["a.md", "b.txt", "notes.md"]);
+
+    // Overlapping alternatives list each file once
+    let pattern = dir.path().join("{a,*}.md").to_string_lossy().to_string();
+    assert_eq!(expand_pattern(&pattern).unwrap().len(), 2);
+}
+
+#[test]
+fn test_expand_braces() {
+    use abyss::hash::wildcard::expand_braces;
+
+    assert_eq!(expand_braces("*.{md,txt}"), vec!["*.md", "*.txt"]);
+    assert_eq!(expand_braces("{a,b}{1,2}"), vec!["a1", "a2", "b1", "b2"]);
+    assert_eq!(expand_braces("x{a,{b,c}}"), vec!["xa", "xb", "xc"]);
+    // Nothing to expand
+    assert_eq!(expand_braces("plain.txt"), vec!["plain.txt"]);
+    assert_eq!(expand_braces("{single}.txt"), vec!["{single}.txt"]);
+    assert_eq!(expand_braces("{open,.txt"), vec!["{open,.txt"]);
+}
+
 // ============ Path Utils Tests ============
 
 #[test]
@@ -334,6 +403,7 @@ fn test_contains_wildcard() {
     assert!(contains_wildcard("file?.bin"));
     assert!(contains_wildcard("[abc]*.jpg"));
     assert!(contains_wildcard("data/*/hashes"));
+    assert!(contains_wildcard("*.{md,txt}"));
     assert!(!contains_wildcard("file.txt"));
     assert!(!contains_wildcard("path/to/file.bin"));
 }
@@ -1,7 +1,7 @@
 // Tests for verify module
 // Extracted from src/hash/verify.rs
 
-use abyss::hash::{VerifyEngine, HashUtilityError};
+use abyss::hash::{VerifyEngine, VerifyProgress, HashUtilityError};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -225,3 +225,160 @@ fn test_collect_files_recursive() {
     fs::remove_dir_all(test_dir).unwrap();
 }
 
+
+#[test]
+fn test_verify_ignores_database_and_ignored_paths() {
+    let dir = tempfile::tempdir().unwrap();
+    create_test_file(&dir.path().join("file1.txt"), b"hello");
+    create_test_file(&dir.path().join("old/hashes.txt"), b"an older database");
+    create_test_file(&dir.path().join("previous.txt"), b"yet another one");
+
+    // The database lives inside the tree and even lists itself (as another tool might)
+    let db_path = dir.path().join("hashes.txt");
+    fs::write(
+        &db_path,
+        "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  sha256  normal  file1.txt\n\
+         0000000000000000000000000000000000000000000000000000000000000000  sha256  normal  hashes.txt\n",
+    )
+    .unwrap();
+
+    let report = VerifyEngine::new().verify(&db_path, dir.path()).unwrap();
+    assert_eq!(report.matches, 1);
+    assert!(report.mismatches.is_empty(), "{:?}", report.mismatches);
+    assert!(!report.new_files.contains(&db_path.canonicalize().unwrap()));
+    assert_eq!(report.new_files.len(), 2);
+
+    // A directory, a file and a report that doesn't exist yet
+    let report = VerifyEngine::new()
+        .with_ignored_paths([dir.path().join("old"), dir.path().join("previous.txt"), dir.path().join("report.json")])
+        .verify(&db_path, dir.path())
+        .unwrap();
+    assert_eq!(report.matches, 1);
+    assert!(report.new_files.is_empty(), "{:?}", report.new_files);
+    assert!(report.missing_files.is_empty());
+}
+
+#[test]
+fn test_verify_progress_callback_reports_total() {
+    let dir = tempfile::tempdir().unwrap();
+    create_test_file(&dir.path().join("a.txt"), b"hello");
+    create_test_file(&dir.path().join("b.txt"), b"changed");
+    let db_path = dir.path().join("hashes.txt");
+    fs::write(
+        &db_path,
+        "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  sha256  normal  a.txt\n\
+         0000000000000000000000000000000000000000000000000000000000000000  sha256  normal  b.txt\n\
+         1111111111111111111111111111111111111111111111111111111111111111  sha256  normal  gone.txt\n",
+    )
+    .unwrap();
+
+    for parallel in [false, true] {
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = std::sync::Arc::clone(&seen);
+        let report = VerifyEngine::new().with_parallel(parallel)
+            .with_progress(false)
+            .with_progress_callback(move |p: VerifyProgress| sink.lock().unwrap().push((p.files_checked, p.total_files)))
+            .verify(&db_path, dir.path())
+            .unwrap();
+
+        assert_eq!(report.matches, 1);
+        let mut seen = seen.lock().unwrap().clone();
+        seen.sort();
+        assert_eq!(seen, vec![(1, 3), (2, 3), (3, 3)]);
+    }
+}
+
+#[test]
+fn test_verify_reads_algorithm_from_database() {
+    use abyss::hash::{DatabaseFormat, ScanEngine};
+
+    for algorithm in ["sha256", "blake3"] {
+        for format in [DatabaseFormat::Standard, DatabaseFormat::Hashdeep, DatabaseFormat::Json] {
+            let dir = tempfile::tempdir().unwrap();
+            create_test_file(&dir.path().join("a.txt"), b"alpha");
+            create_test_file(&dir.path().join("sub/b.txt"), b"beta");
+            let out = tempfile::tempdir().unwrap();
+            let output = out.path().join("hashes");
+            ScanEngine::new()
+                .with_progress(false)
+                .with_summary(false)
+                .with_format(format)
+                .scan_directory(dir.path(), algorithm, &output)
+                .unwrap();
+
+            // No algorithm given: it comes from the database
+            let report = VerifyEngine::new().with_progress(false).verify(&output, dir.path()).unwrap();
+            assert_eq!(report.matches, 2, "{} {:?}", algorithm, format);
+            assert!(report.mismatches.is_empty(), "{} {:?}: {:?}", algorithm, format, report);
+
+            create_test_file(&dir.path().join("a.txt"), b"changed");
+            let report = VerifyEngine::new().with_progress(false).verify(&output, dir.path()).unwrap();
+            assert_eq!(report.mismatches.len(), 1, "{} {:?}", algorithm, format);
+
+            // Naming the algorithm the database already records is fine, another one is not
+            let named = VerifyEngine::new().with_progress(false).with_algorithm(algorithm.to_uppercase());
+            assert_eq!(named.verify(&output, dir.path()).unwrap().matches, 1);
+            let err = VerifyEngine::new()
+                .with_progress(false)
+                .with_algorithm("md5")
+                .verify(&output, dir.path())
+                .unwrap_err();
+            assert!(matches!(err, HashUtilityError::DatabaseAlgorithm { .. }), "{}", err);
+        }
+    }
+}
+
+#[test]
+fn test_verify_rejects_mixed_or_missing_algorithms() {
+    let dir = tempfile::tempdir().unwrap();
+    create_test_file(&dir.path().join("a.txt"), b"hello");
+    create_test_file(&dir.path().join("b.txt"), b"hello");
+
+    let mixed = dir.path().join("mixed.txt");
+    fs::write(
+        &mixed,
+        "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  sha256  normal  a.txt\n\
+         5d41402abc4b2a76b9719d911017c592  md5  normal  b.txt\n",
+    )
+    .unwrap();
+    let err = VerifyEngine::new().with_progress(false).verify(&mixed, dir.path()).unwrap_err();
+    assert!(matches!(err, HashUtilityError::DatabaseAlgorithm { .. }), "{}", err);
+    assert!(err.to_string().contains("md5, sha256"), "{}", err);
+
+    // A header-less hashdeep file only records hash lengths, and 16 hex digits match nothing known
+    let xxh3 = abyss::hash::HashComputer::new().compute_hash_text("hello", "xxh3").unwrap().hash;
+    let headerless = dir.path().join("sums.hashdeep");
+    fs::write(&headerless, format!("5,{},a.txt\n5,{},b.txt\n", xxh3, xxh3)).unwrap();
+    let err = VerifyEngine::new().with_progress(false).verify(&headerless, dir.path()).unwrap_err();
+    assert!(matches!(err, HashUtilityError::DatabaseAlgorithm { .. }), "{}", err);
+
+    let report = VerifyEngine::new()
+        .with_progress(false)
+        .with_algorithm("xxh3")
+        .verify(&headerless, dir.path())
+        .unwrap();
+    assert_eq!(report.matches, 2);
+    assert!(report.mismatches.is_empty());
+}
+
+#[test]
+fn test_verify_algorithm_overrides_a_guess_from_hash_length() {
+    let dir = tempfile::tempdir().unwrap();
+    create_test_file(&dir.path().join("a.txt"), b"hello");
+
+    // 64 hex digits are taken for sha256, but blake3 hashes are just as long
+    let blake3 = abyss::hash::HashComputer::new().compute_hash_text("hello", "blake3").unwrap().hash;
+    let headerless = dir.path().join("sums.hashdeep");
+    fs::write(&headerless, format!("5,{},a.txt\n", blake3)).unwrap();
+
+    let report = VerifyEngine::new().with_progress(false).verify(&headerless, dir.path()).unwrap();
+    assert_eq!(report.mismatches.len(), 1);
+
+    let report = VerifyEngine::new()
+        .with_progress(false)
+        .with_algorithm("blake3")
+        .verify(&headerless, dir.path())
+        .unwrap();
+    assert_eq!(report.matches, 1);
+    assert!(report.mismatches.is_empty());
+}